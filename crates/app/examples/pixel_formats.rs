@@ -0,0 +1,121 @@
+//! Lists the pixel formats that OpenGL can draw to, scored against some requirements.
+//!
+//! ```text
+//! cargo run -p triangle-from-scratch-app --example pixel_formats -- [--depth <bits>]
+//!     [--stencil <bits>] [--samples <count>] [--srgb] [--single-buffer]
+//! ```
+//!
+//! On Windows, that's every WGL pixel format. On Linux, it's the GLX FBConfigs and EGL configs on
+//! `$DISPLAY`, or just the EGL configs of the surfaceless platform if there's no X server.
+
+extern crate triangle_from_scratch_app as app;
+
+use std::{env, error::Error};
+
+use app::pixel_format::{choose, table, PixelFormatInfo, PixelFormatRequirements};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let requirements = requirements_from_args()?;
+    println!("Requirements: {requirements:?}");
+    for (api, formats) in pixel_formats()? {
+        println!();
+        println!("{api}: {} formats", formats.len());
+        print!("{}", table(&formats, Some(&requirements)));
+        match choose(&formats, &requirements) {
+            Some(best) => println!("Best fit: {}", best.id),
+            None => println!("Nothing fits"),
+        }
+    }
+    Ok(())
+}
+
+/// The name of each API, and its formats.
+type Apis = Vec<(&'static str, Vec<PixelFormatInfo>)>;
+
+fn requirements_from_args() -> Result<PixelFormatRequirements, String> {
+    let mut requirements = PixelFormatRequirements::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut number = || {
+            args.next()
+                .and_then(|n| n.parse().ok())
+                .ok_or(format!("{arg} needs a number"))
+        };
+        match arg.as_str() {
+            "--depth" => requirements.depth_bits = number()?,
+            "--stencil" => requirements.stencil_bits = number()?,
+            "--samples" => requirements.samples = number()?,
+            "--srgb" => requirements.srgb = true,
+            "--single-buffer" => requirements.double_buffer = false,
+            _ => return Err(format!("Unknown argument {arg:?}")),
+        }
+    }
+    Ok(requirements)
+}
+
+#[cfg(windows)]
+fn pixel_formats() -> Result<Apis, Box<dyn Error>> {
+    use core::ptr;
+    use win32::{
+        get_process_handle, get_wgl_basics,
+        handles::{DeviceContext, Window, WindowClass},
+        prelude::*,
+        utf16_null,
+    };
+
+    let (extensions, _, _, _, get_attribs) = get_wgl_basics()?;
+
+    // formats belong to a device, so a window that's never shown is needed to ask about them
+    const CLASS_NAME: [u16; 14] = utf16_null!("Pixel Formats");
+    let wc = WNDCLASSW {
+        style: CS_OWNDC,
+        lpfnWndProc: Some(DefWindowProcW),
+        hInstance: get_process_handle(),
+        lpszClassName: CLASS_NAME.as_ptr(),
+        ..Default::default()
+    };
+    let class = unsafe { WindowClass::register(&wc) }?;
+    let window =
+        unsafe { Window::create_app(&class, "Pixel Formats", None, [1, 1], ptr::null_mut()) }?;
+    let dc = DeviceContext::get(&window)?;
+
+    let formats =
+        unsafe { app::pixel_format::wgl::pixel_formats(dc.hdc(), &extensions, get_attribs) }?;
+    Ok(vec![("WGL", formats)])
+}
+
+#[cfg(target_os = "linux")]
+fn pixel_formats() -> Result<Apis, Box<dyn Error>> {
+    use core::ptr;
+    use egl::prelude::*;
+    use glx::XlibDisplay;
+
+    let mut apis = Vec::new();
+    let x_display = XlibDisplay::open();
+    if let Some(x_display) = &x_display {
+        let formats = unsafe {
+            app::pixel_format::glx::pixel_formats(x_display.as_ptr(), x_display.default_screen())
+        };
+        apis.push(("GLX", formats));
+    }
+
+    // EGL hands out the same display to everyone who asks for it, so it's left initialized
+    let (api, display) = match &x_display {
+        Some(x_display) => ("EGL (X11)", unsafe {
+            egl::get_platform_display(EGL_PLATFORM_X11_KHR, x_display.as_ptr().cast())
+        }?),
+        None => ("EGL (surfaceless)", unsafe {
+            egl::get_platform_display(EGL_PLATFORM_SURFACELESS_MESA, ptr::null_mut())
+        }?),
+    };
+    unsafe { egl::initialize(display) }?;
+    apis.push((api, unsafe {
+        app::pixel_format::egl::pixel_formats(display)
+    }?));
+    Ok(apis)
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+fn pixel_formats() -> Result<Apis, Box<dyn Error>> {
+    Err("there's no way to list pixel formats on this platform".into())
+}
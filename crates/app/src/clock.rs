@@ -0,0 +1,185 @@
+//! Monotonic time, from a clock that can be swapped for a fake one in tests.
+//!
+//! [`MonotonicClock`] reads the platform's high-resolution clock: `QueryPerformanceCounter` on
+//! Windows, and `clock_gettime(CLOCK_MONOTONIC)` on Linux. [`ManualClock`] only moves when it's
+//! told to, so that code timed by a [`Clock`] can be tested without sleeping.
+
+use core::{cell::Cell, hint};
+use std::{thread, time::Duration};
+
+/// Something that tells the time.
+pub trait Clock {
+    /// The time since some fixed point in the past. This never goes backwards.
+    fn now(&self) -> Duration;
+
+    /// Waits until it's `deadline` by [`now`](Self::now). Sleeping is cheap but can oversleep, so
+    /// the thread sleeps until `spin_threshold` before the deadline, and spins on the clock for
+    /// the rest.
+    fn wait_until(&self, deadline: Duration, spin_threshold: Duration) {
+        let sleep = sleep_time(self.now(), deadline, spin_threshold);
+        if !sleep.is_zero() {
+            thread::sleep(sleep);
+        }
+        while self.now() < deadline {
+            hint::spin_loop();
+        }
+    }
+}
+
+/// The platform's monotonic clock, which isn't affected by changes to the system time.
+///
+/// Elsewhere than Windows and Linux, this falls back to [`Instant`](std::time::Instant).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MonotonicClock;
+
+#[cfg(windows)]
+impl Clock for MonotonicClock {
+    fn now(&self) -> Duration {
+        use std::sync::OnceLock;
+
+        static FREQUENCY: OnceLock<i64> = OnceLock::new();
+        let frequency = *FREQUENCY.get_or_init(win32::query_performance_frequency);
+        ticks_to_duration(win32::query_performance_counter() as u64, frequency as u64)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Clock for MonotonicClock {
+    fn now(&self) -> Duration {
+        linux::monotonic_time()
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+impl Clock for MonotonicClock {
+    fn now(&self) -> Duration {
+        use std::{sync::OnceLock, time::Instant};
+
+        static EPOCH: OnceLock<Instant> = OnceLock::new();
+        EPOCH.get_or_init(Instant::now).elapsed()
+    }
+}
+
+/// A clock that stands still until it's moved.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Cell<Duration>,
+}
+
+impl ManualClock {
+    pub fn new(now: Duration) -> Self {
+        Self {
+            now: Cell::new(now),
+        }
+    }
+
+    /// Moves the clock forwards.
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+
+    /// Moves the clock to `deadline`, as if the wait ended right on time, unless it's already past
+    /// it.
+    fn wait_until(&self, deadline: Duration, _spin_threshold: Duration) {
+        self.now.set(self.now.get().max(deadline));
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+
+    fn wait_until(&self, deadline: Duration, spin_threshold: Duration) {
+        (**self).wait_until(deadline, spin_threshold);
+    }
+}
+
+/// How long to sleep at `now`, so that there's `spin_threshold` left to spin for before
+/// `deadline`.
+fn sleep_time(now: Duration, deadline: Duration, spin_threshold: Duration) -> Duration {
+    deadline.saturating_sub(now).saturating_sub(spin_threshold)
+}
+
+/// Converts a count of ticks of a clock that ticks `per_second` times a second, without
+/// overflowing for any count that fits in a [`Duration`].
+#[cfg_attr(not(windows), allow(dead_code))]
+fn ticks_to_duration(ticks: u64, per_second: u64) -> Duration {
+    let seconds = ticks / per_second;
+    let nanos = u128::from(ticks % per_second) * 1_000_000_000 / u128::from(per_second);
+    Duration::new(seconds, nanos as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn monotonic() {
+        let clock = MonotonicClock;
+        let before = clock.now();
+        std::thread::sleep(Duration::from_millis(5));
+        let after = clock.now();
+        assert!(
+            after - before >= Duration::from_millis(5),
+            "{before:?} {after:?}"
+        );
+    }
+
+    #[test]
+    fn manual() {
+        let clock = ManualClock::new(Duration::from_secs(1));
+        assert_eq!(clock.now(), Duration::from_secs(1));
+        clock.advance(Duration::from_millis(16));
+        // and can be lent to whatever's being tested
+        fn now(clock: impl Clock) -> Duration {
+            clock.now()
+        }
+        assert_eq!(now(&clock), Duration::from_millis(1016));
+
+        // waiting takes no time at all
+        clock.wait_until(Duration::from_secs(2), Duration::from_millis(2));
+        assert_eq!(clock.now(), Duration::from_secs(2));
+        clock.wait_until(Duration::from_secs(1), Duration::ZERO);
+        assert_eq!(clock.now(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn waiting() {
+        let clock = MonotonicClock;
+        let deadline = clock.now() + Duration::from_millis(5);
+        clock.wait_until(deadline, Duration::from_millis(2));
+        assert!(clock.now() >= deadline);
+    }
+
+    #[test]
+    fn sleeping_then_spinning() {
+        const MS: Duration = Duration::from_millis(1);
+        let now = Duration::from_secs(1);
+        assert_eq!(sleep_time(now, now + 10 * MS, 2 * MS), 8 * MS);
+        // close enough to only spin
+        assert_eq!(sleep_time(now, now + MS, 2 * MS), Duration::ZERO);
+        // already late
+        assert_eq!(sleep_time(now + 5 * MS, now, 2 * MS), Duration::ZERO);
+    }
+
+    #[test]
+    fn ticks() {
+        // the usual QueryPerformanceFrequency since Windows 10
+        assert_eq!(
+            ticks_to_duration(25_000_005, 10_000_000),
+            Duration::new(2, 500_000_500)
+        );
+        assert_eq!(
+            ticks_to_duration(u64::MAX, 1),
+            Duration::from_secs(u64::MAX)
+        );
+        assert_eq!(ticks_to_duration(1, 3), Duration::from_nanos(333_333_333));
+    }
+}
@@ -0,0 +1,13 @@
+//! The parts of an application's main loop that don't depend on the platform it runs on.
+//!
+//! - [`clock`] tells the time, in a way that can be faked in tests.
+//! - [`pacing`] waits between frames to hold a target frame rate.
+//! - [`pixel_format`] lists the pixel formats that can be drawn to, and picks the best fit.
+//! - [`present`] picks whether frames wait for vertical blanks, with whatever API is drawing them.
+//! - [`runner`] updates a simulation with a fixed timestep, and renders it between updates.
+
+pub mod clock;
+pub mod pacing;
+pub mod pixel_format;
+pub mod present;
+pub mod runner;
@@ -0,0 +1,198 @@
+//! Holding a target frame rate by waiting out what's left of each frame.
+//!
+//! Sleeping is cheap but imprecise, since the OS can wake the thread a millisecond or more late.
+//! Spinning on the clock is precise, but keeps a core busy. [`FramePacer`] does both, with
+//! [`Clock::wait_until`]: it sleeps until shortly before the next frame is due, and spins for the
+//! rest.
+//!
+//! Frames are due at a fixed cadence, so being woken a little late doesn't push every frame after
+//! it back. If a frame takes so long that the next one is already a whole frame late, the cadence
+//! starts over from there instead of rendering a burst of frames to catch up.
+
+use std::time::Duration;
+
+use crate::clock::{Clock, MonotonicClock};
+
+/// How long before a frame is due [`FramePacer`] stops sleeping and starts spinning, unless it's
+/// told otherwise.
+pub const DEFAULT_SPIN_THRESHOLD: Duration = Duration::from_millis(2);
+
+/// Waits between frames to hold a target frame rate, timed by a [`Clock`].
+///
+/// Call [`wait`](Self::wait) once a frame has been rendered (and presented), and it returns when
+/// the next one should start.
+#[derive(Debug, Clone)]
+pub struct FramePacer<C = MonotonicClock> {
+    clock: C,
+    frame_time: Option<Duration>,
+    spin_threshold: Duration,
+    /// When the current frame was due, or `None` if there's no cadence yet.
+    frame_start: Option<Duration>,
+}
+
+impl FramePacer {
+    /// Holds `frames_per_second`, as timed by the platform's clock.
+    ///
+    /// **See:** [`with_clock`](Self::with_clock)
+    pub fn new(frames_per_second: Option<f64>) -> Self {
+        Self::with_clock(MonotonicClock, frames_per_second)
+    }
+}
+
+impl<C: Clock> FramePacer<C> {
+    /// Holds `frames_per_second`, as timed by `clock`, or doesn't wait at all if it's `None`,
+    /// isn't a positive number, or is so low that a frame wouldn't fit in a [`Duration`].
+    pub fn with_clock(clock: C, frames_per_second: Option<f64>) -> Self {
+        Self {
+            clock,
+            frame_time: frames_per_second.and_then(frame_time),
+            spin_threshold: DEFAULT_SPIN_THRESHOLD,
+            frame_start: None,
+        }
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Changes the target frame rate, from the next frame on. `None` (or anything
+    /// [`with_clock`](Self::with_clock) wouldn't wait for) stops waiting.
+    pub fn set_target_frame_rate(&mut self, frames_per_second: Option<f64>) {
+        self.frame_time = frames_per_second.and_then(frame_time);
+        if self.frame_time.is_none() {
+            self.frame_start = None;
+        }
+    }
+
+    /// The frame rate being held, if there is one.
+    pub fn target_frame_rate(&self) -> Option<f64> {
+        self.frame_time.map(|frame| 1.0 / frame.as_secs_f64())
+    }
+
+    /// How long each frame gets, if there's a target frame rate.
+    pub fn frame_time(&self) -> Option<Duration> {
+        self.frame_time
+    }
+
+    /// Changes how long before a frame is due to start spinning. Longer wastes more CPU time, but
+    /// copes with sleeps that oversleep by more.
+    pub fn set_spin_threshold(&mut self, spin_threshold: Duration) {
+        self.spin_threshold = spin_threshold;
+    }
+
+    pub fn spin_threshold(&self) -> Duration {
+        self.spin_threshold
+    }
+
+    /// Starts the cadence over from the next call to [`wait`](Self::wait), e.g. after the loop has
+    /// been paused.
+    pub fn reset(&mut self) {
+        self.frame_start = None;
+    }
+
+    /// Waits until the next frame is due. The first call after creating (or resetting) the pacer
+    /// returns straight away, and starts the cadence.
+    pub fn wait(&mut self) {
+        let Some(frame_time) = self.frame_time else {
+            return;
+        };
+        let Some(frame_start) = self.frame_start else {
+            self.frame_start = Some(self.clock.now());
+            return;
+        };
+
+        let deadline = frame_start + frame_time;
+        self.clock.wait_until(deadline, self.spin_threshold);
+        self.frame_start = Some(next_frame_start(deadline, self.clock.now(), frame_time));
+    }
+}
+
+fn frame_time(frames_per_second: f64) -> Option<Duration> {
+    if !(frames_per_second.is_finite() && frames_per_second > 0.0) {
+        return None;
+    }
+    Duration::try_from_secs_f64(1.0 / frames_per_second).ok()
+}
+
+/// When the frame that was due at `deadline` counts as having started, if the wait for it ended at
+/// `now`: on time, unless it's a whole frame late.
+fn next_frame_start(deadline: Duration, now: Duration, frame_time: Duration) -> Duration {
+    if now.saturating_sub(deadline) >= frame_time {
+        now
+    } else {
+        deadline
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    fn pacer(frames_per_second: f64) -> FramePacer<ManualClock> {
+        FramePacer::with_clock(ManualClock::default(), Some(frames_per_second))
+    }
+
+    /// Takes `render` to draw a frame, and then waits for the next one.
+    ///
+    /// **Returns:** When the wait ended.
+    fn frame(pacer: &mut FramePacer<ManualClock>, render: Duration) -> Duration {
+        pacer.clock().advance(render);
+        pacer.wait();
+        pacer.clock().now()
+    }
+
+    #[test]
+    fn target_frame_rates() {
+        let pacer = FramePacer::new(Some(50.0));
+        assert_eq!(pacer.frame_time(), Some(20 * MS));
+        assert_eq!(pacer.target_frame_rate(), Some(50.0));
+
+        for nonsense in [0.0, -60.0, f64::NAN, f64::INFINITY, 1e-30] {
+            assert_eq!(FramePacer::new(Some(nonsense)).frame_time(), None);
+        }
+        assert_eq!(FramePacer::new(None).target_frame_rate(), None);
+    }
+
+    #[test]
+    fn keeping_the_cadence() {
+        let deadline = Duration::from_secs(1);
+        let frame = 10 * MS;
+        assert_eq!(next_frame_start(deadline, deadline, frame), deadline);
+        // woken a bit late, which the next frame makes up for
+        assert_eq!(
+            next_frame_start(deadline, deadline + 3 * MS, frame),
+            deadline
+        );
+        // a whole frame late, so start over rather than catching up
+        let late = deadline + 25 * MS;
+        assert_eq!(next_frame_start(deadline, late, frame), late);
+    }
+
+    #[test]
+    fn waiting() {
+        let mut pacer = pacer(100.0);
+        // the first wait only starts the cadence
+        assert_eq!(frame(&mut pacer, 3 * MS), 3 * MS);
+        assert_eq!(frame(&mut pacer, 4 * MS), 13 * MS);
+        assert_eq!(frame(&mut pacer, 9 * MS), 23 * MS);
+
+        // a frame that's late doesn't wait, and the next makes up for it
+        assert_eq!(frame(&mut pacer, 15 * MS), 38 * MS);
+        assert_eq!(frame(&mut pacer, MS), 43 * MS);
+
+        // but one that's a whole frame late starts the cadence over
+        assert_eq!(frame(&mut pacer, 25 * MS), 68 * MS);
+        assert_eq!(frame(&mut pacer, 2 * MS), 78 * MS);
+
+        pacer.reset();
+        assert_eq!(frame(&mut pacer, 2 * MS), 80 * MS);
+        assert_eq!(frame(&mut pacer, 2 * MS), 90 * MS);
+
+        pacer.set_target_frame_rate(None);
+        assert_eq!(pacer.frame_time(), None);
+        assert_eq!(frame(&mut pacer, 2 * MS), 92 * MS);
+    }
+}
@@ -0,0 +1,49 @@
+//! Listing EGL configs.
+
+use egl::{prelude::*, EglError};
+
+use super::PixelFormatInfo;
+
+/// Decodes every OpenGL-capable RGB config on `display`, in the order EGL gives them.
+///
+/// EGL picks a surface's colour space when it's created rather than with its config, so every
+/// config can do sRGB if the display supports `EGL_KHR_gl_colorspace`.
+///
+/// ## Safety
+///
+/// `display` must be a valid, initialized display.
+pub unsafe fn pixel_formats(display: EGLDisplay) -> Result<Vec<PixelFormatInfo>, EglError> {
+    let srgb = egl::query_string(display, EGL_EXTENSIONS)?
+        .split(' ')
+        .any(|s| s == "EGL_KHR_gl_colorspace");
+
+    let mut formats = Vec::new();
+    for config in egl::get_configs(display)? {
+        let get = |attribute| egl::get_config_attrib(display, config, attribute);
+        let bits = |attribute| get(attribute).map(|n| n.clamp(0, u8::MAX.into()) as u8);
+
+        if get(EGL_RENDERABLE_TYPE)? & EGL_OPENGL_BIT == 0
+            || get(EGL_COLOR_BUFFER_TYPE)? != EGL_RGB_BUFFER
+        {
+            continue;
+        }
+        formats.push(PixelFormatInfo {
+            id: get(EGL_CONFIG_ID)?,
+            color_bits: [
+                bits(EGL_RED_SIZE)?,
+                bits(EGL_GREEN_SIZE)?,
+                bits(EGL_BLUE_SIZE)?,
+                bits(EGL_ALPHA_SIZE)?,
+            ],
+            depth_bits: bits(EGL_DEPTH_SIZE)?,
+            stencil_bits: bits(EGL_STENCIL_SIZE)?,
+            samples: bits(EGL_SAMPLES)?,
+            srgb,
+            // window surfaces are always back buffered
+            double_buffer: true,
+            accelerated: get(EGL_CONFIG_CAVEAT)? != EGL_SLOW_CONFIG,
+            window: get(EGL_SURFACE_TYPE)? & EGL_WINDOW_BIT != 0,
+        });
+    }
+    Ok(formats)
+}
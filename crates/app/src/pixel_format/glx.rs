@@ -0,0 +1,46 @@
+//! Listing GLX FBConfigs.
+
+use c_types::CInt;
+use glx::prelude::*;
+
+use super::PixelFormatInfo;
+
+/// Decodes every RGBA FBConfig on `screen` that can be used with an X visual, in the order GLX
+/// gives them.
+///
+/// ## Safety
+///
+/// `display` must be an open display.
+pub unsafe fn pixel_formats(display: *mut Display, screen: CInt) -> Vec<PixelFormatInfo> {
+    let mut formats = Vec::new();
+    for config in glx::get_fb_configs(display, screen) {
+        // attributes from extensions the server doesn't have just count as missing
+        let get = |attribute| glx::get_fb_config_attrib(display, config, attribute).unwrap_or(0);
+        let bits = |attribute| get(attribute).clamp(0, u8::MAX.into()) as u8;
+
+        if get(GLX_RENDER_TYPE) & GLX_RGBA_BIT == 0 || get(GLX_X_RENDERABLE) == 0 {
+            continue;
+        }
+        formats.push(PixelFormatInfo {
+            id: get(GLX_FBCONFIG_ID),
+            color_bits: [
+                bits(GLX_RED_SIZE),
+                bits(GLX_GREEN_SIZE),
+                bits(GLX_BLUE_SIZE),
+                bits(GLX_ALPHA_SIZE),
+            ],
+            depth_bits: bits(GLX_DEPTH_SIZE),
+            stencil_bits: bits(GLX_STENCIL_SIZE),
+            samples: if get(GLX_SAMPLE_BUFFERS_ARB) != 0 {
+                bits(GLX_SAMPLES_ARB)
+            } else {
+                0
+            },
+            srgb: get(GLX_FRAMEBUFFER_SRGB_CAPABLE_ARB) != 0,
+            double_buffer: get(GLX_DOUBLEBUFFER) != 0,
+            accelerated: get(GLX_CONFIG_CAVEAT) != GLX_SLOW_CONFIG,
+            window: get(GLX_DRAWABLE_TYPE) & GLX_WINDOW_BIT != 0,
+        });
+    }
+    formats
+}
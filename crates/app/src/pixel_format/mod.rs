@@ -0,0 +1,373 @@
+//! Listing the pixel formats an API offers, and picking the one that best fits what's needed.
+//!
+//! Asking the API to choose (e.g. with `wglChoosePixelFormatARB`) only says whether what was asked
+//! for exists, so anything optional has to be guessed up front, and it's up to the driver what
+//! "best" means. Instead, every format is decoded into a [`PixelFormatInfo`], and [`score`]d
+//! against some [`PixelFormatRequirements`]. The highest scoring format is the one to use.
+//!
+//! The formats for each API are listed by a module of its own:
+//!
+//! - [`wgl`], with `wglGetPixelFormatAttribivARB` or `DescribePixelFormat` on Windows.
+//! - [`glx`], with GLX FBConfigs on Linux.
+//! - [`egl`], with EGL configs on Linux.
+
+use core::fmt::Write;
+
+#[cfg(target_os = "linux")]
+pub mod egl;
+#[cfg(target_os = "linux")]
+pub mod glx;
+#[cfg(windows)]
+pub mod wgl;
+
+/// What a pixel format (or GLX FBConfig, or EGL config) has, in terms every API can describe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PixelFormatInfo {
+    /// What the API calls it: a WGL pixel format index, or a GLX or EGL config ID.
+    pub id: i32,
+    /// Red, green, blue, and alpha bits.
+    pub color_bits: [u8; 4],
+    pub depth_bits: u8,
+    pub stencil_bits: u8,
+    /// Samples per pixel, or zero if it isn't multisampled.
+    pub samples: u8,
+    /// Whether it can be rendered to in sRGB.
+    pub srgb: bool,
+    pub double_buffer: bool,
+    /// Whether it's drawn by the GPU, rather than by a slow (or software) fallback.
+    pub accelerated: bool,
+    /// Whether it can be used for a window.
+    pub window: bool,
+}
+
+/// What a pixel format needs to have to be used.
+///
+/// Formats with fewer bits than these, a different buffering, or without sRGB when it's asked for,
+/// aren't considered at all. Formats with fewer samples are, as a fallback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelFormatRequirements {
+    /// The least red, green, blue, and alpha bits.
+    pub color_bits: [u8; 4],
+    pub depth_bits: u8,
+    pub stencil_bits: u8,
+    /// Samples per pixel, or zero for no multisampling.
+    pub samples: u8,
+    pub srgb: bool,
+    pub double_buffer: bool,
+}
+
+impl Default for PixelFormatRequirements {
+    /// 8 bits for each colour, a 24-bit depth buffer, an 8-bit stencil buffer, double buffered, and
+    /// nothing else.
+    fn default() -> Self {
+        Self {
+            color_bits: [8; 4],
+            depth_bits: 24,
+            stencil_bits: 8,
+            samples: 0,
+            srgb: false,
+            double_buffer: true,
+        }
+    }
+}
+
+/// The most a format can score, if it's exactly what's required.
+pub const PERFECT_SCORE: u32 = 1_000_000;
+
+/// How much is taken off for a format that isn't accelerated, which is always worse than any other
+/// mismatch.
+const UNACCELERATED: u32 = PERFECT_SCORE / 2;
+/// How much is taken off for each sample fewer than asked for.
+const MISSING_SAMPLE: u32 = 1000;
+/// How much is taken off for each sample more than asked for, which is wasted memory and time.
+const EXTRA_SAMPLE: u32 = 100;
+/// How much is taken off for each colour, depth, or stencil bit more than asked for.
+const EXTRA_BIT: u32 = 1;
+
+/// How well `format` fits `requirements`, out of [`PERFECT_SCORE`], or `None` if it can't be used
+/// at all.
+///
+/// In order of importance, a format loses points for not being accelerated, for having fewer
+/// samples than asked for, for having more, and for having more bits than asked for.
+pub fn score(format: &PixelFormatInfo, requirements: &PixelFormatRequirements) -> Option<u32> {
+    let bits = |format: &PixelFormatInfo| {
+        let [r, g, b, a] = format.color_bits;
+        [r, g, b, a, format.depth_bits, format.stencil_bits]
+    };
+    let required = bits(&PixelFormatInfo {
+        color_bits: requirements.color_bits,
+        depth_bits: requirements.depth_bits,
+        stencil_bits: requirements.stencil_bits,
+        ..Default::default()
+    });
+    let has = bits(format);
+
+    let usable = format.window
+        && format.double_buffer == requirements.double_buffer
+        && (format.srgb || !requirements.srgb)
+        && has
+            .iter()
+            .zip(&required)
+            .all(|(has, required)| has >= required);
+    if !usable {
+        return None;
+    }
+
+    let extra_bits: u32 = has
+        .iter()
+        .zip(&required)
+        .map(|(has, required)| u32::from(has - required))
+        .sum();
+    let samples = u32::from(format.samples);
+    let wanted_samples = u32::from(requirements.samples);
+
+    let mut penalty = extra_bits * EXTRA_BIT;
+    penalty += wanted_samples.saturating_sub(samples) * MISSING_SAMPLE;
+    penalty += samples.saturating_sub(wanted_samples) * EXTRA_SAMPLE;
+    if !format.accelerated {
+        penalty += UNACCELERATED;
+    }
+    Some(PERFECT_SCORE.saturating_sub(penalty))
+}
+
+/// The format that best fits `requirements`, or `None` if none of them can be used. Ties go to the
+/// one that comes first, which is usually the API's own preference.
+pub fn choose<'a>(
+    formats: &'a [PixelFormatInfo],
+    requirements: &PixelFormatRequirements,
+) -> Option<&'a PixelFormatInfo> {
+    let mut best: Option<(u32, &PixelFormatInfo)> = None;
+    for format in formats {
+        let Some(score) = score(format, requirements) else {
+            continue;
+        };
+        if best.is_none_or(|(best, _)| score > best) {
+            best = Some((score, format));
+        }
+    }
+    best.map(|(_, format)| format)
+}
+
+/// Lays `formats` out as a table, one per line, with each one's score against `requirements` if
+/// there are any.
+pub fn table(
+    formats: &[PixelFormatInfo],
+    requirements: Option<&PixelFormatRequirements>,
+) -> String {
+    let mut table =
+        String::from("   id  rgba         depth  stencil  samples  srgb  double  accel  window");
+    if requirements.is_some() {
+        table.push_str("    score");
+    }
+    table.push('\n');
+
+    let yes_no = |b: bool| if b { "yes" } else { "no" };
+    for format in formats {
+        let [r, g, b, a] = format.color_bits;
+        let _ = write!(
+            table,
+            "{:>5}  {:<11}  {:>5}  {:>7}  {:>7}  {:>4}  {:>6}  {:>5}  {:>6}",
+            format.id,
+            format!("{r}/{g}/{b}/{a}"),
+            format.depth_bits,
+            format.stencil_bits,
+            format.samples,
+            yes_no(format.srgb),
+            yes_no(format.double_buffer),
+            yes_no(format.accelerated),
+            yes_no(format.window),
+        );
+        if let Some(requirements) = requirements {
+            match score(format, requirements) {
+                Some(score) => _ = write!(table, "  {score:>7}"),
+                None => table.push_str("        -"),
+            }
+        }
+        table.push('\n');
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An accelerated, double-buffered window format with 8 bits for each colour, and nothing
+    /// else.
+    fn format(id: i32) -> PixelFormatInfo {
+        PixelFormatInfo {
+            id,
+            color_bits: [8; 4],
+            double_buffer: true,
+            accelerated: true,
+            window: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn exact_match() {
+        let requirements = PixelFormatRequirements::default();
+        let exact = PixelFormatInfo {
+            depth_bits: 24,
+            stencil_bits: 8,
+            ..format(1)
+        };
+        assert_eq!(score(&exact, &requirements), Some(PERFECT_SCORE));
+    }
+
+    #[test]
+    fn unusable_formats() {
+        let requirements = PixelFormatRequirements {
+            depth_bits: 24,
+            srgb: true,
+            ..Default::default()
+        };
+        let good = PixelFormatInfo {
+            depth_bits: 24,
+            stencil_bits: 8,
+            srgb: true,
+            ..format(1)
+        };
+        assert!(score(&good, &requirements).is_some());
+
+        for bad in [
+            PixelFormatInfo {
+                color_bits: [5, 6, 5, 0],
+                ..good
+            },
+            PixelFormatInfo {
+                depth_bits: 16,
+                ..good
+            },
+            PixelFormatInfo {
+                stencil_bits: 0,
+                ..good
+            },
+            PixelFormatInfo {
+                srgb: false,
+                ..good
+            },
+            PixelFormatInfo {
+                double_buffer: false,
+                ..good
+            },
+            PixelFormatInfo {
+                window: false,
+                ..good
+            },
+        ] {
+            assert_eq!(score(&bad, &requirements), None, "{bad:?}");
+        }
+
+        // sRGB that isn't asked for doesn't hurt
+        let requirements = PixelFormatRequirements::default();
+        assert_eq!(score(&good, &requirements), Some(PERFECT_SCORE));
+    }
+
+    #[test]
+    fn preferences() {
+        let requirements = PixelFormatRequirements {
+            depth_bits: 0,
+            stencil_bits: 0,
+            samples: 4,
+            ..Default::default()
+        };
+        let score = |format| score(&format, &requirements).unwrap();
+        let msaa = |samples| PixelFormatInfo {
+            samples,
+            ..format(1)
+        };
+
+        assert_eq!(score(msaa(4)), PERFECT_SCORE);
+        // fewer samples are worse than more, which are worse than extra bits
+        assert!(score(msaa(2)) < score(msaa(8)));
+        assert!(
+            score(msaa(8))
+                < score(PixelFormatInfo {
+                    depth_bits: 32,
+                    stencil_bits: 8,
+                    ..msaa(4)
+                })
+        );
+        // and anything accelerated beats something that isn't
+        let software = PixelFormatInfo {
+            accelerated: false,
+            ..msaa(4)
+        };
+        assert!(score(software) < score(msaa(0)));
+    }
+
+    #[test]
+    fn choosing() {
+        let requirements = PixelFormatRequirements {
+            samples: 4,
+            ..Default::default()
+        };
+        let formats = [
+            // what asking for sample buffers without a sample count got
+            PixelFormatInfo {
+                depth_bits: 24,
+                stencil_bits: 8,
+                samples: 16,
+                ..format(1)
+            },
+            PixelFormatInfo {
+                depth_bits: 16,
+                samples: 4,
+                ..format(2)
+            },
+            PixelFormatInfo {
+                depth_bits: 24,
+                stencil_bits: 8,
+                samples: 4,
+                ..format(3)
+            },
+            PixelFormatInfo {
+                depth_bits: 24,
+                stencil_bits: 8,
+                samples: 4,
+                ..format(4)
+            },
+        ];
+        assert_eq!(choose(&formats, &requirements).map(|f| f.id), Some(3));
+
+        let requirements = PixelFormatRequirements {
+            depth_bits: 32,
+            ..requirements
+        };
+        assert_eq!(choose(&formats, &requirements), None);
+        assert_eq!(choose(&[], &requirements), None);
+    }
+
+    #[test]
+    fn tables() {
+        let formats = [
+            PixelFormatInfo {
+                depth_bits: 24,
+                stencil_bits: 8,
+                samples: 4,
+                srgb: true,
+                ..format(7)
+            },
+            PixelFormatInfo {
+                color_bits: [10, 10, 10, 2],
+                accelerated: false,
+                ..format(12)
+            },
+        ];
+        assert_eq!(
+            table(&formats, None),
+            "   id  rgba         depth  stencil  samples  srgb  double  accel  window\n\
+             \x20   7  8/8/8/8         24        8        4   yes     yes    yes     yes\n\
+             \x20  12  10/10/10/2       0        0        0    no     yes     no     yes\n"
+        );
+
+        let requirements = PixelFormatRequirements::default();
+        let table = table(&formats, Some(&requirements));
+        let lines: Vec<_> = table.lines().collect();
+        assert!(lines[0].ends_with("window    score"), "{table}");
+        assert!(lines[1].ends_with("yes   999600"), "{table}");
+        assert!(lines[2].ends_with("yes        -"), "{table}");
+    }
+}
@@ -0,0 +1,121 @@
+//! Listing WGL pixel formats.
+
+use c_types::CInt;
+use win32::{
+    describe_pixel_format, do_wgl_get_pixel_format_attribiv_arb, get_max_pixel_format_index,
+    prelude::*,
+};
+
+use super::PixelFormatInfo;
+
+/// Decodes every RGBA OpenGL pixel format that `hdc` has, in index order.
+///
+/// With `WGL_ARB_pixel_format`, this asks `get_attribs` about each format, which can tell it about
+/// multisampling and sRGB (if `extensions`, the WGL extensions, say it can). Without it, this falls
+/// back to [`describe_pixel_format`], which can't.
+///
+/// ## Safety
+///
+/// `hdc` must be a valid handle to a DC, and `get_attribs` must be a valid nullable pointer to the
+/// `wglGetPixelFormatAttribivARB` function.
+pub unsafe fn pixel_formats(
+    hdc: HDC,
+    extensions: &[String],
+    get_attribs: wglGetPixelFormatAttribivARB_t,
+) -> Result<Vec<PixelFormatInfo>, Win32Error> {
+    let supports = |name: &str| extensions.iter().any(|s| s == name);
+    if get_attribs.is_none() || !supports("WGL_ARB_pixel_format") {
+        return described_pixel_formats(hdc);
+    }
+
+    let mut attributes = vec![
+        WGL_SUPPORT_OPENGL_ARB,
+        WGL_PIXEL_TYPE_ARB,
+        WGL_DRAW_TO_WINDOW_ARB,
+        WGL_ACCELERATION_ARB,
+        WGL_DOUBLE_BUFFER_ARB,
+        WGL_RED_BITS_ARB,
+        WGL_GREEN_BITS_ARB,
+        WGL_BLUE_BITS_ARB,
+        WGL_ALPHA_BITS_ARB,
+        WGL_DEPTH_BITS_ARB,
+        WGL_STENCIL_BITS_ARB,
+    ];
+    // asking about an attribute the driver doesn't know fails the whole call
+    if supports("WGL_ARB_multisample") {
+        attributes.extend([WGL_SAMPLE_BUFFERS_ARB, WGL_SAMPLES_ARB]);
+    }
+    if supports("WGL_EXT_framebuffer_sRGB") || supports("WGL_ARB_framebuffer_sRGB") {
+        attributes.push(WGL_FRAMEBUFFER_SRGB_CAPABLE_EXT);
+    }
+
+    let count =
+        do_wgl_get_pixel_format_attribiv_arb(get_attribs, hdc, 0, &[WGL_NUMBER_PIXEL_FORMATS_ARB])?
+            [0];
+    let mut formats = Vec::new();
+    for index in 1..=count {
+        let values = do_wgl_get_pixel_format_attribiv_arb(get_attribs, hdc, index, &attributes)?;
+        // missing attributes are zero, which is false or none
+        let get = |attribute: CInt| {
+            attributes
+                .iter()
+                .position(|&a| a == attribute)
+                .map_or(0, |i| values[i])
+        };
+        let bits = |attribute| get(attribute).clamp(0, u8::MAX.into()) as u8;
+
+        if get(WGL_SUPPORT_OPENGL_ARB) == 0 || get(WGL_PIXEL_TYPE_ARB) != WGL_TYPE_RGBA_ARB {
+            continue;
+        }
+        formats.push(PixelFormatInfo {
+            id: index,
+            color_bits: [
+                bits(WGL_RED_BITS_ARB),
+                bits(WGL_GREEN_BITS_ARB),
+                bits(WGL_BLUE_BITS_ARB),
+                bits(WGL_ALPHA_BITS_ARB),
+            ],
+            depth_bits: bits(WGL_DEPTH_BITS_ARB),
+            stencil_bits: bits(WGL_STENCIL_BITS_ARB),
+            samples: if get(WGL_SAMPLE_BUFFERS_ARB) != 0 {
+                bits(WGL_SAMPLES_ARB)
+            } else {
+                0
+            },
+            srgb: get(WGL_FRAMEBUFFER_SRGB_CAPABLE_EXT) != 0,
+            double_buffer: get(WGL_DOUBLE_BUFFER_ARB) != 0,
+            accelerated: get(WGL_ACCELERATION_ARB) == WGL_FULL_ACCELERATION_ARB,
+            window: get(WGL_DRAW_TO_WINDOW_ARB) != 0,
+        });
+    }
+    Ok(formats)
+}
+
+/// Decodes every RGBA OpenGL pixel format that `hdc` has, with [`describe_pixel_format`].
+///
+/// ## Safety
+///
+/// `hdc` must be a valid handle to a DC.
+pub unsafe fn described_pixel_formats(hdc: HDC) -> Result<Vec<PixelFormatInfo>, Win32Error> {
+    let mut formats = Vec::new();
+    for index in 1..=get_max_pixel_format_index(hdc)? {
+        let pfd = describe_pixel_format(hdc, index)?;
+        if pfd.dwFlags & PFD_SUPPORT_OPENGL == 0 || pfd.iPixelType != PFD_TYPE_RGBA {
+            continue;
+        }
+        formats.push(PixelFormatInfo {
+            id: index,
+            color_bits: [pfd.cRedBits, pfd.cGreenBits, pfd.cBlueBits, pfd.cAlphaBits],
+            depth_bits: pfd.cDepthBits,
+            stencil_bits: pfd.cStencilBits,
+            samples: 0,
+            srgb: false,
+            double_buffer: pfd.dwFlags & PFD_DOUBLEBUFFER != 0,
+            // generic formats are Microsoft's software renderer, unless a driver accelerates them
+            accelerated: pfd.dwFlags & PFD_GENERIC_FORMAT == 0
+                || pfd.dwFlags & PFD_GENERIC_ACCELERATED != 0,
+            window: pfd.dwFlags & PFD_DRAW_TO_WINDOW != 0,
+        });
+    }
+    Ok(formats)
+}
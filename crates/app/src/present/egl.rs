@@ -0,0 +1,51 @@
+//! Swap control for EGL, with `eglSwapInterval`.
+
+use egl::{prelude::*, EglError};
+
+use super::{PresentModeError, SwapControl, SwapIntervals};
+
+/// Sets the swap interval of the current context's draw surface.
+///
+/// EGL has no way to let late swaps tear, so adaptive vsync is never achieved, and no way to read
+/// the interval back, so what's reported is what was asked for.
+#[derive(Debug, Clone, Copy)]
+pub struct EglSwapControl {
+    display: EGLDisplay,
+    intervals: SwapIntervals,
+}
+
+impl EglSwapControl {
+    /// Swap control for contexts on `display` that were created with `config`, whose
+    /// [`EGL_MIN_SWAP_INTERVAL`] and [`EGL_MAX_SWAP_INTERVAL`] limit what can be set.
+    ///
+    /// ## Safety
+    ///
+    /// `display` must be a valid, initialized display and `config` must have come from it.
+    pub unsafe fn new(display: EGLDisplay, config: EGLConfig) -> Result<Self, EglError> {
+        Ok(Self {
+            display,
+            intervals: SwapIntervals {
+                min: egl::get_config_attrib(display, config, EGL_MIN_SWAP_INTERVAL)?,
+                max: egl::get_config_attrib(display, config, EGL_MAX_SWAP_INTERVAL)?,
+                late_swaps_tear: false,
+            },
+        })
+    }
+}
+
+impl SwapControl for EglSwapControl {
+    fn intervals(&self) -> SwapIntervals {
+        self.intervals
+    }
+
+    unsafe fn set_swap_interval(&self, interval: i32) -> Result<(), PresentModeError> {
+        egl::swap_interval(self.display, interval).map_err(|e| PresentModeError::Rejected {
+            interval,
+            reason: e.to_string(),
+        })
+    }
+
+    unsafe fn swap_interval(&self) -> Option<i32> {
+        None
+    }
+}
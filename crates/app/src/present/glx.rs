@@ -0,0 +1,129 @@
+//! Swap control for GLX, with `GLX_EXT_swap_control` or, failing that, `GLX_MESA_swap_control`.
+
+use core::mem;
+
+use c_types::*;
+use glx::prelude::*;
+
+use super::{PresentModeError, SwapControl, SwapIntervals};
+
+/// Sets the swap interval of the drawable that was current when this was loaded.
+#[derive(Debug, Clone, Copy)]
+pub struct GlxSwapControl {
+    display: *mut Display,
+    drawable: GLXDrawable,
+    extension: Extension,
+}
+
+/// The extension the swap interval is set with.
+#[derive(Debug, Clone, Copy)]
+enum Extension {
+    /// `GLX_EXT_swap_control`, which sets a drawable's interval.
+    Ext {
+        set: unsafe extern "C" fn(*mut Display, GLXDrawable, CInt),
+        max: i32,
+        /// `GLX_EXT_swap_control_tear`.
+        late_swaps_tear: bool,
+    },
+    /// `GLX_MESA_swap_control`, which sets the current drawable's.
+    Mesa {
+        set: unsafe extern "C" fn(CUInt) -> CInt,
+        get: glXGetSwapIntervalMESA_t,
+    },
+}
+
+impl GlxSwapControl {
+    /// Loads swap control for the context that's current on this thread, or returns `None` if
+    /// there's no context or neither extension is supported.
+    ///
+    /// ## Safety
+    ///
+    /// The context must stay current, with the same drawable, for as long as this is used.
+    pub unsafe fn load() -> Option<Self> {
+        let current = glx::current_context()?;
+        let screen = glx::query_context_screen(current.display, current.context)?;
+        let extensions = glx::query_extensions(current.display, screen);
+        let supports = |name: &str| extensions.iter().any(|s| s == name);
+
+        let extension = if supports("GLX_EXT_swap_control") {
+            let set: glXSwapIntervalEXT_t =
+                mem::transmute(glx::get_proc_address(b"glXSwapIntervalEXT\0")?);
+            let max =
+                glx::query_drawable(current.display, current.drawable, GLX_MAX_SWAP_INTERVAL_EXT);
+            Extension::Ext {
+                set: set?,
+                max: max.try_into().unwrap_or(i32::MAX),
+                late_swaps_tear: supports("GLX_EXT_swap_control_tear"),
+            }
+        } else if supports("GLX_MESA_swap_control") {
+            let set: glXSwapIntervalMESA_t =
+                mem::transmute(glx::get_proc_address(b"glXSwapIntervalMESA\0")?);
+            let get: glXGetSwapIntervalMESA_t =
+                mem::transmute(glx::get_proc_address(b"glXGetSwapIntervalMESA\0")?);
+            Extension::Mesa { set: set?, get }
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            display: current.display,
+            drawable: current.drawable,
+            extension,
+        })
+    }
+}
+
+impl SwapControl for GlxSwapControl {
+    fn intervals(&self) -> SwapIntervals {
+        match self.extension {
+            Extension::Ext {
+                max,
+                late_swaps_tear,
+                ..
+            } => SwapIntervals {
+                min: 0,
+                max,
+                late_swaps_tear,
+            },
+            Extension::Mesa { .. } => SwapIntervals {
+                min: 0,
+                max: i32::MAX,
+                late_swaps_tear: false,
+            },
+        }
+    }
+
+    unsafe fn set_swap_interval(&self, interval: i32) -> Result<(), PresentModeError> {
+        match self.extension {
+            // errors from this come back asynchronously, as X errors
+            Extension::Ext { set, .. } => set(self.display, self.drawable, interval),
+            Extension::Mesa { set, .. } => match set(interval.max(0) as CUInt) {
+                0 => (),
+                code => {
+                    return Err(PresentModeError::Rejected {
+                        interval,
+                        reason: format!("glXSwapIntervalMESA returned {code}"),
+                    })
+                }
+            },
+        }
+        Ok(())
+    }
+
+    unsafe fn swap_interval(&self) -> Option<i32> {
+        match self.extension {
+            Extension::Ext {
+                late_swaps_tear, ..
+            } => {
+                // the interval is always given as positive, with whether it's negative separate
+                let interval =
+                    glx::query_drawable(self.display, self.drawable, GLX_SWAP_INTERVAL_EXT) as i32;
+                let tears = late_swaps_tear
+                    && glx::query_drawable(self.display, self.drawable, GLX_LATE_SWAPS_TEAR_EXT)
+                        != 0;
+                Some(if tears { -interval } else { interval })
+            }
+            Extension::Mesa { get, .. } => get.map(|get| get()),
+        }
+    }
+}
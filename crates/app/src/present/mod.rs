@@ -0,0 +1,324 @@
+//! Choosing how finished frames are shown: as soon as they're ready, at the next vertical blank,
+//! or at the next vertical blank unless they've missed one.
+//!
+//! Every windowing API does this with a swap interval, which is how many vertical blanks a buffer
+//! swap waits for. Zero doesn't wait, one is vsync, and with a "swap control tear" extension a
+//! negative interval waits unless the frame is late. Not every API (or driver) can do every
+//! interval, so [`set_present_mode`] falls back to the closest [`PresentMode`] that can be done,
+//! and reports which one it ended up with.
+//!
+//! The [`SwapControl`] for each API is in a module of its own:
+//!
+//! - [`wgl`], with `WGL_EXT_swap_control` and `WGL_EXT_swap_control_tear` on Windows.
+//! - [`glx`], with `GLX_EXT_swap_control` (and `_tear`) or `GLX_MESA_swap_control` on Linux.
+//! - [`egl`], with `eglSwapInterval` on Linux.
+
+use core::{fmt, str::FromStr};
+
+#[cfg(target_os = "linux")]
+pub mod egl;
+#[cfg(target_os = "linux")]
+pub mod glx;
+#[cfg(windows)]
+pub mod wgl;
+
+/// How finished frames are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PresentMode {
+    /// Straight away, tearing if the display is part way through a refresh.
+    Immediate,
+    /// At the next vertical blank, so never torn, but a late frame waits a whole refresh.
+    Vsync,
+    /// At the next vertical blank, unless the frame missed the last one, in which case it's shown
+    /// straight away (and torn) rather than waiting another refresh.
+    AdaptiveVsync,
+}
+
+impl PresentMode {
+    /// Every mode, in the order they're cycled through.
+    pub const ALL: [Self; 3] = [Self::Vsync, Self::AdaptiveVsync, Self::Immediate];
+
+    /// The swap interval that asks for this mode.
+    pub fn swap_interval(self) -> i32 {
+        match self {
+            Self::Immediate => 0,
+            Self::Vsync => 1,
+            Self::AdaptiveVsync => -1,
+        }
+    }
+
+    /// The mode that a swap interval gives. Intervals longer than one (e.g. vsync at half the
+    /// refresh rate) still wait for a vertical blank, so they count as [`Vsync`](Self::Vsync).
+    pub fn from_swap_interval(interval: i32) -> Self {
+        match interval {
+            0 => Self::Immediate,
+            1.. => Self::Vsync,
+            _ => Self::AdaptiveVsync,
+        }
+    }
+
+    /// The mode after this one in [`ALL`](Self::ALL), wrapping around.
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&mode| mode == self).unwrap();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    /// The closest mode to this one that `intervals` allows, or `None` if it allows none of them.
+    ///
+    /// Adaptive vsync falls back to vsync, as it only differs for late frames. Immediate falls back
+    /// to adaptive vsync before vsync, as that at least doesn't hold late frames back.
+    pub fn resolve(self, intervals: SwapIntervals) -> Option<Self> {
+        let fallbacks = match self {
+            Self::Immediate => [Self::Immediate, Self::AdaptiveVsync, Self::Vsync],
+            Self::Vsync => [Self::Vsync, Self::AdaptiveVsync, Self::Immediate],
+            Self::AdaptiveVsync => [Self::AdaptiveVsync, Self::Vsync, Self::Immediate],
+        };
+        fallbacks.into_iter().find(|&mode| intervals.allows(mode))
+    }
+}
+
+impl fmt::Display for PresentMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Immediate => "immediate",
+            Self::Vsync => "vsync",
+            Self::AdaptiveVsync => "adaptive",
+        })
+    }
+}
+
+impl FromStr for PresentMode {
+    type Err = ParsePresentModeError;
+
+    /// Parses what [`Display`](fmt::Display) writes.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.to_string() == s)
+            .ok_or_else(|| ParsePresentModeError(s.to_string()))
+    }
+}
+
+/// A string that isn't the name of a [`PresentMode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsePresentModeError(pub String);
+
+impl fmt::Display for ParsePresentModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} isn't a present mode (expected immediate, vsync, or adaptive)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParsePresentModeError {}
+
+/// The swap intervals that an API will take.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapIntervals {
+    /// The shortest non-negative interval.
+    pub min: i32,
+    /// The longest interval.
+    pub max: i32,
+    /// Whether negative intervals are allowed, to let late swaps tear.
+    pub late_swaps_tear: bool,
+}
+
+impl SwapIntervals {
+    /// Whether `mode`'s swap interval can be set.
+    pub fn allows(&self, mode: PresentMode) -> bool {
+        match mode {
+            PresentMode::AdaptiveVsync => self.late_swaps_tear && self.max >= 1,
+            mode => (self.min..=self.max).contains(&mode.swap_interval()),
+        }
+    }
+}
+
+/// Why a present mode couldn't be set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PresentModeError {
+    /// None of the modes can be set, so not even a fallback could be used.
+    Unsupported(SwapIntervals),
+    /// The API refused the swap interval.
+    Rejected {
+        interval: i32,
+        /// What the API said was wrong.
+        reason: String,
+    },
+}
+
+impl fmt::Display for PresentModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported(intervals) => {
+                write!(f, "no present mode can be set with {intervals:?}")
+            }
+            Self::Rejected { interval, reason } => {
+                write!(f, "the swap interval {interval} was rejected: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PresentModeError {}
+
+/// Control over an API's swap interval, for the context that's current on this thread.
+pub trait SwapControl {
+    /// The swap intervals that can be set.
+    fn intervals(&self) -> SwapIntervals;
+
+    /// Sets the swap interval. It's only ever called with one that [`intervals`](Self::intervals)
+    /// allows.
+    ///
+    /// ## Safety
+    ///
+    /// The context this was made for must be current on this thread.
+    unsafe fn set_swap_interval(&self, interval: i32) -> Result<(), PresentModeError>;
+
+    /// The swap interval that's in effect, if the API can say.
+    ///
+    /// ## Safety
+    ///
+    /// The context this was made for must be current on this thread.
+    unsafe fn swap_interval(&self) -> Option<i32>;
+}
+
+/// Switches to `mode`, or the closest mode to it that `control` can do, and returns the mode that
+/// was actually achieved.
+///
+/// This can be called whenever the context is current, as often as needed. The achieved mode is
+/// read back from the API where it can say, since a driver (or a setting in its control panel) can
+/// override what's asked for.
+///
+/// ## Safety
+///
+/// The context `control` was made for must be current on this thread.
+pub unsafe fn set_present_mode(
+    control: &impl SwapControl,
+    mode: PresentMode,
+) -> Result<PresentMode, PresentModeError> {
+    let intervals = control.intervals();
+    let mode = mode
+        .resolve(intervals)
+        .ok_or(PresentModeError::Unsupported(intervals))?;
+    control.set_swap_interval(mode.swap_interval())?;
+    Ok(control
+        .swap_interval()
+        .map_or(mode, PresentMode::from_swap_interval))
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use super::*;
+
+    use PresentMode::*;
+
+    /// Takes any interval it allows, and can be made to say it's got a different one.
+    struct Fake {
+        intervals: SwapIntervals,
+        interval: Cell<Option<i32>>,
+        overridden: Option<i32>,
+    }
+
+    impl Fake {
+        fn new(min: i32, max: i32, late_swaps_tear: bool) -> Self {
+            Self {
+                intervals: SwapIntervals {
+                    min,
+                    max,
+                    late_swaps_tear,
+                },
+                interval: Cell::new(None),
+                overridden: None,
+            }
+        }
+    }
+
+    impl SwapControl for Fake {
+        fn intervals(&self) -> SwapIntervals {
+            self.intervals
+        }
+
+        unsafe fn set_swap_interval(&self, interval: i32) -> Result<(), PresentModeError> {
+            let allowed = if interval < 0 {
+                self.intervals.late_swaps_tear
+            } else {
+                (self.intervals.min..=self.intervals.max).contains(&interval)
+            };
+            assert!(allowed, "{interval} isn't allowed");
+            self.interval.set(Some(interval));
+            Ok(())
+        }
+
+        unsafe fn swap_interval(&self) -> Option<i32> {
+            self.overridden.or(self.interval.get())
+        }
+    }
+
+    fn set(control: &Fake, mode: PresentMode) -> Result<PresentMode, PresentModeError> {
+        unsafe { set_present_mode(control, mode) }
+    }
+
+    #[test]
+    fn names() {
+        for mode in PresentMode::ALL {
+            assert_eq!(mode.to_string().parse(), Ok(mode));
+            assert_eq!(PresentMode::from_swap_interval(mode.swap_interval()), mode);
+        }
+        assert_eq!(PresentMode::from_swap_interval(2), Vsync);
+        assert_eq!(
+            "tearing".parse::<PresentMode>(),
+            Err(ParsePresentModeError("tearing".to_string()))
+        );
+        assert_eq!(Vsync.next(), AdaptiveVsync);
+        assert_eq!(Immediate.next(), Vsync);
+    }
+
+    #[test]
+    fn everything_supported() {
+        let control = Fake::new(0, 8, true);
+        for mode in PresentMode::ALL {
+            assert_eq!(set(&control, mode), Ok(mode));
+            assert_eq!(control.interval.get(), Some(mode.swap_interval()));
+        }
+    }
+
+    #[test]
+    fn fallbacks() {
+        // no swap control tear
+        let control = Fake::new(0, 1, false);
+        assert_eq!(set(&control, AdaptiveVsync), Ok(Vsync));
+        assert_eq!(set(&control, Immediate), Ok(Immediate));
+
+        // an EGL config that can't turn vsync off
+        let control = Fake::new(1, 1, true);
+        assert_eq!(set(&control, Immediate), Ok(AdaptiveVsync));
+        let control = Fake::new(1, 1, false);
+        assert_eq!(set(&control, Immediate), Ok(Vsync));
+
+        // one that can only turn it off
+        let control = Fake::new(0, 0, false);
+        assert_eq!(set(&control, Vsync), Ok(Immediate));
+        assert_eq!(set(&control, AdaptiveVsync), Ok(Immediate));
+
+        let control = Fake::new(2, 4, false);
+        assert_eq!(
+            set(&control, Vsync),
+            Err(PresentModeError::Unsupported(control.intervals))
+        );
+        assert_eq!(control.interval.get(), None);
+    }
+
+    #[test]
+    fn achieved_mode_is_read_back() {
+        // e.g. vsync forced on in the driver's control panel
+        let mut control = Fake::new(0, 1, true);
+        control.overridden = Some(1);
+        assert_eq!(set(&control, Immediate), Ok(Vsync));
+        assert_eq!(control.interval.get(), Some(0));
+    }
+}
@@ -0,0 +1,76 @@
+//! Swap control for WGL, with `WGL_EXT_swap_control`.
+
+use core::{cell::Cell, mem};
+
+use c_types::CInt;
+use win32::{c_str, get_last_error, prelude::*, wgl_get_proc_address};
+
+use super::{PresentModeError, SwapControl, SwapIntervals};
+
+/// Sets the swap interval of the current context.
+#[derive(Debug, Clone)]
+pub struct WglSwapControl {
+    set: unsafe extern "system" fn(CInt) -> BOOL,
+    get: wglGetSwapIntervalEXT_t,
+    /// `WGL_EXT_swap_control_tear`.
+    late_swaps_tear: bool,
+    /// The last interval that was set.
+    interval: Cell<Option<i32>>,
+}
+
+impl WglSwapControl {
+    /// Loads swap control for the context that's current on this thread, or returns `None` if
+    /// `extensions`, the WGL extensions it supports, don't include `WGL_EXT_swap_control`.
+    ///
+    /// ## Safety
+    ///
+    /// A context must be current, and this must only be used while contexts from the same driver
+    /// are.
+    pub unsafe fn load(extensions: &[String]) -> Option<Self> {
+        let supports = |name: &str| extensions.iter().any(|s| s == name);
+        if !supports("WGL_EXT_swap_control") {
+            return None;
+        }
+
+        let set: wglSwapIntervalEXT_t =
+            mem::transmute(wgl_get_proc_address(c_str!("wglSwapIntervalEXT")).ok()?);
+        let get: wglGetSwapIntervalEXT_t =
+            mem::transmute(wgl_get_proc_address(c_str!("wglGetSwapIntervalEXT")).ok()?);
+        Some(Self {
+            set: set?,
+            get,
+            late_swaps_tear: supports("WGL_EXT_swap_control_tear"),
+            interval: Cell::new(None),
+        })
+    }
+}
+
+impl SwapControl for WglSwapControl {
+    fn intervals(&self) -> SwapIntervals {
+        SwapIntervals {
+            min: 0,
+            max: i32::MAX,
+            late_swaps_tear: self.late_swaps_tear,
+        }
+    }
+
+    unsafe fn set_swap_interval(&self, interval: i32) -> Result<(), PresentModeError> {
+        if (self.set)(interval) == 0 {
+            return Err(PresentModeError::Rejected {
+                interval,
+                reason: get_last_error().to_string(),
+            });
+        }
+        self.interval.set(Some(interval));
+        Ok(())
+    }
+
+    unsafe fn swap_interval(&self) -> Option<i32> {
+        let interval = (self.get?)();
+        // some drivers give a negative interval back as positive
+        Some(match self.interval.get() {
+            Some(set) if set == -interval => set,
+            _ => interval,
+        })
+    }
+}
@@ -0,0 +1,288 @@
+//! Running a simulation at a fixed rate, however fast frames are being drawn.
+//!
+//! [`Runner::frame`] is meant to be called once for each trip around the event loop (e.g. from
+//! `run_app_loop` on Windows). It measures how much time has passed since the last frame, runs
+//! [`Application::update`] with a fixed timestep as many times as fit in that (and whatever was
+//! left over from before), and then [`Application::render`]s once.
+//!
+//! Since a frame rarely lines up with the updates, what's drawn should be interpolated between the
+//! state before the last update and the state after it. The leftover time becomes `alpha`, how far
+//! to go from one to the other, at the cost of drawing everything one update late.
+//!
+//! If a frame takes longer than the updates it causes can catch up on, every frame after it needs
+//! more updates than the last, and the loop never recovers. So the time a frame can cover is
+//! capped, and past that the simulation just runs slow.
+
+use core::mem;
+use std::time::Duration;
+
+use crate::clock::{Clock, MonotonicClock};
+
+/// How long a frame can cover, unless the runner is told otherwise.
+pub const DEFAULT_MAX_FRAME_TIME: Duration = Duration::from_millis(250);
+
+/// A simulation that's updated at a fixed rate and drawn at a variable one.
+pub trait Application {
+    /// Moves the simulation on by `dt`, which is always the runner's timestep.
+    fn update(&mut self, dt: Duration);
+
+    /// Draws the simulation `alpha` of the way (from 0 up to, but not including, 1) from the state
+    /// before the last update to the state after it.
+    fn render(&mut self, alpha: f64);
+}
+
+/// What happened in a call to [`Runner::frame`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    /// How many times [`Application::update`] was called.
+    pub updates: u32,
+    /// What [`Application::render`] was called with.
+    pub alpha: f64,
+}
+
+/// Runs an [`Application`]'s updates with a fixed timestep, timed by a [`Clock`].
+#[derive(Debug, Clone)]
+pub struct Runner<C = MonotonicClock> {
+    clock: C,
+    timestep: Duration,
+    max_frame_time: Duration,
+    time_scale: f64,
+    paused: bool,
+    /// Updates to run while paused, from [`step`](Self::step).
+    steps: u32,
+    /// Simulated time that hasn't been updated yet.
+    accumulator: Duration,
+    /// When the last frame was, or `None` before the first one.
+    last_frame: Option<Duration>,
+}
+
+impl Runner {
+    /// Updates every `timestep`, as timed by the platform's clock.
+    ///
+    /// ## Panics
+    ///
+    /// If `timestep` is zero.
+    pub fn new(timestep: Duration) -> Self {
+        Self::with_clock(MonotonicClock, timestep)
+    }
+}
+
+impl<C: Clock> Runner<C> {
+    /// Updates every `timestep`, as timed by `clock`.
+    ///
+    /// ## Panics
+    ///
+    /// If `timestep` is zero.
+    pub fn with_clock(clock: C, timestep: Duration) -> Self {
+        assert!(!timestep.is_zero(), "a timestep of zero");
+        Self {
+            clock,
+            timestep,
+            max_frame_time: DEFAULT_MAX_FRAME_TIME,
+            time_scale: 1.0,
+            paused: false,
+            steps: 0,
+            accumulator: Duration::ZERO,
+            last_frame: None,
+        }
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    pub fn timestep(&self) -> Duration {
+        self.timestep
+    }
+
+    /// Changes how much simulated time a single frame can cover, which caps how many updates it
+    /// can run to `max_frame_time / timestep`.
+    pub fn set_max_frame_time(&mut self, max_frame_time: Duration) {
+        self.max_frame_time = max_frame_time;
+    }
+
+    pub fn max_frame_time(&self) -> Duration {
+        self.max_frame_time
+    }
+
+    /// Makes the simulation run `time_scale` times as fast as real time, from the next frame on.
+    /// The timestep stays the same, so this changes how many updates there are.
+    ///
+    /// ## Panics
+    ///
+    /// If `time_scale` is negative or isn't finite.
+    pub fn set_time_scale(&mut self, time_scale: f64) {
+        assert!(
+            time_scale.is_finite() && time_scale >= 0.0,
+            "a time scale of {time_scale}"
+        );
+        self.time_scale = time_scale;
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    /// Stops updating until [`resume`](Self::resume) is called. Frames are still rendered, with
+    /// the same `alpha` as before.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Carries on updating from where the simulation was paused, without making up for the time
+    /// spent paused.
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.steps = 0;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Runs one update in the next frame, if the runner is paused.
+    pub fn step(&mut self) {
+        if self.paused {
+            self.steps += 1;
+        }
+    }
+
+    /// Runs however many updates are due, and then renders.
+    pub fn frame(&mut self, app: &mut impl Application) -> Frame {
+        let now = self.clock.now();
+        let elapsed = self
+            .last_frame
+            .map_or(Duration::ZERO, |last| now.saturating_sub(last));
+        self.last_frame = Some(now);
+
+        let updates = if self.paused {
+            mem::take(&mut self.steps)
+        } else {
+            self.accumulator += elapsed.mul_f64(self.time_scale).min(self.max_frame_time);
+            let updates = self.accumulator.as_nanos() / self.timestep.as_nanos();
+            self.accumulator -= self.timestep * updates as u32;
+            updates as u32
+        };
+        for _ in 0..updates {
+            app.update(self.timestep);
+        }
+
+        let alpha = self.accumulator.as_secs_f64() / self.timestep.as_secs_f64();
+        app.render(alpha);
+        Frame { updates, alpha }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    /// Counts its updates, and remembers what it was last rendered with.
+    #[derive(Default)]
+    struct Counter {
+        updates: u32,
+        alpha: Option<f64>,
+    }
+
+    impl Application for Counter {
+        fn update(&mut self, dt: Duration) {
+            assert_eq!(dt, 10 * MS);
+            self.updates += 1;
+        }
+
+        fn render(&mut self, alpha: f64) {
+            assert!((0.0..1.0).contains(&alpha), "{alpha}");
+            self.alpha = Some(alpha);
+        }
+    }
+
+    fn runner() -> Runner<ManualClock> {
+        Runner::with_clock(ManualClock::default(), 10 * MS)
+    }
+
+    fn frame(runner: &mut Runner<ManualClock>, app: &mut Counter, elapsed: Duration) -> u32 {
+        runner.clock().advance(elapsed);
+        runner.frame(app).updates
+    }
+
+    #[test]
+    fn fixed_updates() {
+        let mut runner = runner();
+        let mut app = Counter::default();
+        // the first frame has nothing to measure from
+        assert_eq!(frame(&mut runner, &mut app, 100 * MS), 0);
+        assert_eq!(app.alpha, Some(0.0));
+
+        // half an update left over
+        assert_eq!(frame(&mut runner, &mut app, 25 * MS), 2);
+        assert_eq!(app.alpha, Some(0.5));
+        // which adds up with the next frame's
+        assert_eq!(frame(&mut runner, &mut app, 7 * MS), 1);
+        assert!((app.alpha.unwrap() - 0.2).abs() < 1e-9);
+        assert_eq!(frame(&mut runner, &mut app, 3 * MS), 0);
+        assert!((app.alpha.unwrap() - 0.5).abs() < 1e-9);
+        assert_eq!(app.updates, 3);
+    }
+
+    #[test]
+    fn long_frames_are_clamped() {
+        let mut runner = runner();
+        let mut app = Counter::default();
+        frame(&mut runner, &mut app, Duration::ZERO);
+
+        // a breakpoint, say: only catch up on the most a frame can cover
+        assert_eq!(frame(&mut runner, &mut app, Duration::from_secs(30)), 25);
+        runner.set_max_frame_time(50 * MS);
+        assert_eq!(frame(&mut runner, &mut app, Duration::from_secs(30)), 5);
+    }
+
+    #[test]
+    fn time_scale() {
+        let mut runner = runner();
+        let mut app = Counter::default();
+        frame(&mut runner, &mut app, Duration::ZERO);
+
+        runner.set_time_scale(0.5);
+        assert_eq!(frame(&mut runner, &mut app, 40 * MS), 2);
+        runner.set_time_scale(3.0);
+        assert_eq!(frame(&mut runner, &mut app, 10 * MS), 3);
+        runner.set_time_scale(0.0);
+        assert_eq!(frame(&mut runner, &mut app, 100 * MS), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn negative_time_scale() {
+        runner().set_time_scale(-1.0);
+    }
+
+    #[test]
+    fn pausing_and_stepping() {
+        let mut runner = runner();
+        let mut app = Counter::default();
+        frame(&mut runner, &mut app, Duration::ZERO);
+        frame(&mut runner, &mut app, 15 * MS);
+
+        runner.pause();
+        assert_eq!(frame(&mut runner, &mut app, 100 * MS), 0);
+        assert_eq!(app.alpha, Some(0.5));
+
+        runner.step();
+        runner.step();
+        assert_eq!(frame(&mut runner, &mut app, 100 * MS), 2);
+        assert_eq!(frame(&mut runner, &mut app, 100 * MS), 0);
+        assert_eq!(app.alpha, Some(0.5));
+
+        // the time spent paused isn't made up for
+        runner.resume();
+        assert_eq!(frame(&mut runner, &mut app, 5 * MS), 1);
+        assert_eq!(app.updates, 4);
+
+        // stepping does nothing unless paused
+        runner.step();
+        assert_eq!(frame(&mut runner, &mut app, Duration::ZERO), 0);
+    }
+}
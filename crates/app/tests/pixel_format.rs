@@ -0,0 +1,35 @@
+#![cfg(target_os = "linux")]
+
+extern crate triangle_from_scratch_app as app;
+
+use app::pixel_format::{choose, egl::pixel_formats, PixelFormatRequirements};
+use egl::HeadlessContext;
+
+#[test]
+fn egl_configs() {
+    let ctx = match HeadlessContext::new(3, 3) {
+        Ok(ctx) => ctx,
+        Err(e) => {
+            eprintln!("skipping: couldn't create a headless OpenGL context: {e}");
+            return;
+        }
+    };
+    let formats = unsafe { pixel_formats(ctx.display()) }.unwrap();
+    assert!(!formats.is_empty());
+
+    let mut ids: Vec<_> = formats.iter().map(|format| format.id).collect();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), formats.len(), "config IDs aren't unique");
+
+    // the context was created with one of these
+    let config_id =
+        unsafe { egl::get_config_attrib(ctx.display(), ctx.config(), egl::prelude::EGL_CONFIG_ID) }
+            .unwrap();
+    assert!(ids.contains(&config_id));
+
+    // the surfaceless platform can't draw to windows, so nothing fits
+    let windowed = formats.iter().any(|format| format.window);
+    let best = choose(&formats, &PixelFormatRequirements::default());
+    assert_eq!(best.is_some(), windowed, "{best:?}");
+}
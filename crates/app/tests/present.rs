@@ -0,0 +1,59 @@
+#![cfg(target_os = "linux")]
+
+extern crate triangle_from_scratch_app as app;
+
+use app::present::{
+    egl::EglSwapControl, glx::GlxSwapControl, set_present_mode, PresentMode, PresentModeError,
+    SwapControl,
+};
+use egl::{prelude::*, HeadlessContext};
+
+#[test]
+fn egl_swap_control() {
+    let ctx = match HeadlessContext::new(3, 3) {
+        Ok(ctx) => ctx,
+        Err(e) => {
+            eprintln!("skipping: couldn't create a headless OpenGL context: {e}");
+            return;
+        }
+    };
+    let control = unsafe { EglSwapControl::new(ctx.display(), ctx.config()) }.unwrap();
+    let intervals = control.intervals();
+    assert!(intervals.min <= intervals.max, "{intervals:?}");
+    assert!(!intervals.late_swaps_tear);
+
+    // the context has no surface, so there's no swap interval to set
+    let result = unsafe { set_present_mode(&control, PresentMode::Vsync) };
+    assert!(
+        matches!(result, Err(PresentModeError::Rejected { .. })),
+        "{result:?}"
+    );
+
+    // with one, the modes the config allows are set as they are, and the rest fall back
+    unsafe {
+        #[rustfmt::skip]
+        let attribs = [
+            EGL_WIDTH, 16,
+            EGL_HEIGHT, 16,
+            EGL_NONE,
+        ];
+        let surface = egl::create_pbuffer_surface(ctx.display(), ctx.config(), &attribs).unwrap();
+        egl::make_current(ctx.display(), surface, surface, ctx.context()).unwrap();
+
+        for mode in PresentMode::ALL {
+            let achieved = set_present_mode(&control, mode).ok();
+            assert_eq!(achieved, mode.resolve(intervals), "{mode}");
+            if intervals.allows(mode) {
+                assert_eq!(achieved, Some(mode));
+            }
+        }
+
+        egl::make_current(ctx.display(), EGL_NO_SURFACE, EGL_NO_SURFACE, ctx.context()).unwrap();
+        egl::destroy_surface(ctx.display(), surface).unwrap();
+    }
+}
+
+#[test]
+fn no_glx_context() {
+    assert!(unsafe { GlxSwapControl::load() }.is_none());
+}
@@ -1,31 +1,389 @@
 //! Types typically used in C programs.
+//!
+//! The sizes of some C types depend on the target. Windows is LLP64, so `long` is 32 bits
+//! everywhere, whereas 64-bit Unix-likes are LP64 and 32-bit ones are ILP32. Whether a plain `char`
+//! is signed depends on the architecture too. Each definition here picks the right type for the
+//! target, and is checked against the definitions in [`core::ffi`] at compile time.
 
-/// The C representation of a `char` on x86.
+use core::{ffi::CStr, fmt, str::Utf8Error};
+
+/// The C representation of a `char`, which is signed on x86 (and on Windows and Apple platforms),
+/// but unsigned on most other Linux architectures.
+#[cfg(not(all(
+    not(windows),
+    not(target_vendor = "apple"),
+    any(
+        target_arch = "aarch64",
+        target_arch = "arm",
+        target_arch = "powerpc",
+        target_arch = "powerpc64",
+        target_arch = "riscv32",
+        target_arch = "riscv64",
+        target_arch = "s390x",
+    )
+)))]
 pub type CChar = i8;
 
-/// The C representation of a `double` on x86.
+/// The C representation of a `char`, which is signed on x86 (and on Windows and Apple platforms),
+/// but unsigned on most other Linux architectures.
+#[cfg(all(
+    not(windows),
+    not(target_vendor = "apple"),
+    any(
+        target_arch = "aarch64",
+        target_arch = "arm",
+        target_arch = "powerpc",
+        target_arch = "powerpc64",
+        target_arch = "riscv32",
+        target_arch = "riscv64",
+        target_arch = "s390x",
+    )
+))]
+pub type CChar = u8;
+
+/// The C representation of a `double`.
 pub type CDouble = f64;
 
-/// The C representation of an `int` on x86.
+/// The C representation of an `int`.
 pub type CInt = i32;
 
-/// The C representation of a `long` on x86.
+/// The C representation of a `long`. This is 32 bits on Windows (LLP64) and on 32-bit targets
+/// (ILP32).
+#[cfg(any(windows, target_pointer_width = "32"))]
 pub type CLong = i32;
 
-/// The C representation of a single-precision floating point number on x86
+/// The C representation of a `long`. This is 64 bits on 64-bit Unix-likes (LP64).
+#[cfg(not(any(windows, target_pointer_width = "32")))]
+pub type CLong = i64;
+
+/// The C representation of a `long long`.
+pub type CLongLong = i64;
+
+/// The C representation of a single-precision floating point number.
 pub type CFloat = f32;
 
-/// The C representation of a `short` on x86.
+/// The C representation of a `signed char`, which is always signed, unlike a plain `char`.
+pub type CSChar = i8;
+
+/// The C representation of a `short`.
 pub type CShort = i16;
 
-/// The C representation of an `unsigned char` on x86;
+/// The C representation of a `size_t`.
+pub type CSizeT = usize;
+
+/// The C representation of a POSIX `ssize_t` (or a Win32 `SSIZE_T`).
+pub type CSSizeT = isize;
+
+/// The C representation of an `unsigned char`.
 pub type CUChar = u8;
 
-/// The C representation of an `unsigned int` on x86.
+/// The C representation of an `unsigned int`.
 pub type CUInt = u32;
 
-/// The C representation of an `unsigned long` on x86.
+/// The C representation of an `unsigned long`. This is 32 bits on Windows (LLP64) and on 32-bit
+/// targets (ILP32).
+#[cfg(any(windows, target_pointer_width = "32"))]
 pub type CULong = u32;
 
-/// The C representation of an `unsigned short` on x86.
+/// The C representation of an `unsigned long`. This is 64 bits on 64-bit Unix-likes (LP64).
+#[cfg(not(any(windows, target_pointer_width = "32")))]
+pub type CULong = u64;
+
+/// The C representation of an `unsigned long long`.
+pub type CULongLong = u64;
+
+/// The C representation of an `unsigned short`.
 pub type CUShort = u16;
+
+/// The C representation of `void`, for use behind pointers.
+///
+/// This is the same type as [`core::ffi::c_void`], so pointers to it can be passed to anything
+/// else that expects one.
+pub type CVoid = core::ffi::c_void;
+
+/// Checks that two types are the same, at compile time.
+macro_rules! assert_same_type {
+    ($($ours:ty => $abi:ty),* $(,)?) => {
+        $(const _: fn($ours) -> $abi = |x| x;)*
+    };
+}
+
+// Every type must match what the Rust compiler thinks the platform ABI is.
+assert_same_type! {
+    CChar => core::ffi::c_char,
+    CDouble => core::ffi::c_double,
+    CFloat => core::ffi::c_float,
+    CInt => core::ffi::c_int,
+    CLong => core::ffi::c_long,
+    CLongLong => core::ffi::c_longlong,
+    CSChar => core::ffi::c_schar,
+    CShort => core::ffi::c_short,
+    CUChar => core::ffi::c_uchar,
+    CUInt => core::ffi::c_uint,
+    CULong => core::ffi::c_ulong,
+    CULongLong => core::ffi::c_ulonglong,
+    CUShort => core::ffi::c_ushort,
+}
+
+// `size_t` and `ssize_t` are pointer-sized on every target we support.
+const _: () = assert!(core::mem::size_of::<CSizeT>() == core::mem::size_of::<*const CVoid>());
+const _: () = assert!(core::mem::size_of::<CSSizeT>() == core::mem::size_of::<CSizeT>());
+
+/// Why some bytes couldn't be used as a [`CStrRef`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CStrError {
+    /// There's a null before the last byte.
+    InteriorNull { position: usize },
+    /// There's no null at all.
+    NotNullTerminated,
+}
+
+impl fmt::Display for CStrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InteriorNull { position } => write!(f, "interior null at byte {position}"),
+            Self::NotNullTerminated => f.write_str("not null-terminated"),
+        }
+    }
+}
+
+impl std::error::Error for CStrError {}
+
+/// A borrowed, null-terminated C string.
+///
+/// It's a borrowed byte slice that's guaranteed to be null-terminated: like [`CStr`], it always
+/// ends in exactly one null with no nulls before it, so [`as_ptr`](Self::as_ptr) can be handed
+/// straight to C. It can be built and taken apart in `const` contexts, which is what lets
+/// [`c_str!`] reject interior nulls at compile time.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CStrRef<'a> {
+    /// The bytes, including the null terminator.
+    bytes: &'a [u8],
+}
+
+impl<'a> CStrRef<'a> {
+    /// Wraps bytes that end in a null, with no other nulls.
+    pub const fn from_bytes_with_null(bytes: &'a [u8]) -> Result<Self, CStrError> {
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == 0 {
+                return if i + 1 == bytes.len() {
+                    Ok(Self { bytes })
+                } else {
+                    Err(CStrError::InteriorNull { position: i })
+                };
+            }
+            i += 1;
+        }
+        Err(CStrError::NotNullTerminated)
+    }
+
+    /// Wraps bytes up to (and including) the first null, ignoring anything after it. This suits
+    /// fixed-size buffers that C has written a string into.
+    pub const fn from_bytes_until_null(bytes: &'a [u8]) -> Result<Self, CStrError> {
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == 0 {
+                let (with_null, _) = bytes.split_at(i + 1);
+                return Ok(Self { bytes: with_null });
+            }
+            i += 1;
+        }
+        Err(CStrError::NotNullTerminated)
+    }
+
+    /// Wraps bytes without checking them.
+    ///
+    /// ## Safety
+    ///
+    /// `bytes` must end in a null, and contain no other nulls.
+    pub const unsafe fn from_bytes_with_null_unchecked(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Wraps a null-terminated string that C has handed back.
+    ///
+    /// ## Safety
+    ///
+    /// - `ptr` must point to a null-terminated string.
+    /// - The string must stay alive and unchanged for `'a`.
+    pub unsafe fn from_ptr(ptr: *const CChar) -> Self {
+        Self::from(CStr::from_ptr(ptr))
+    }
+
+    /// A pointer to the first byte, which can be passed to C as a `const char *`.
+    pub const fn as_ptr(self) -> *const CChar {
+        self.bytes.as_ptr().cast()
+    }
+
+    /// The bytes, without the null terminator.
+    pub const fn to_bytes(self) -> &'a [u8] {
+        match self.bytes {
+            [rest @ .., _null] => rest,
+            // a `CStrRef` always has at least its null
+            [] => unreachable!(),
+        }
+    }
+
+    /// The bytes, including the null terminator.
+    pub const fn to_bytes_with_null(self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// The length in bytes, not counting the null terminator.
+    pub const fn len(self) -> usize {
+        self.bytes.len() - 1
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the string as a `&str`, if it's valid UTF-8.
+    pub fn to_str(self) -> Result<&'a str, Utf8Error> {
+        core::str::from_utf8(self.to_bytes())
+    }
+
+    pub fn as_c_str(self) -> &'a CStr {
+        // Safety: same guarantees
+        unsafe { CStr::from_bytes_with_nul_unchecked(self.bytes) }
+    }
+}
+
+impl Default for CStrRef<'_> {
+    fn default() -> Self {
+        Self { bytes: &[0] }
+    }
+}
+
+impl fmt::Debug for CStrRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"", self.to_bytes().escape_ascii())
+    }
+}
+
+impl<'a> From<&'a CStr> for CStrRef<'a> {
+    fn from(s: &'a CStr) -> Self {
+        Self {
+            bytes: s.to_bytes_with_nul(),
+        }
+    }
+}
+
+impl<'a> From<CStrRef<'a>> for &'a CStr {
+    fn from(s: CStrRef<'a>) -> Self {
+        s.as_c_str()
+    }
+}
+
+/// Converts a UTF-8 Rust string literal into a [`CStrRef`], at compile time.
+///
+/// Literals containing a null are rejected with a compile error, since C would only see the part
+/// before the null.
+///
+/// ```
+/// use triangle_from_scratch_c_types::{c_str, CStrRef};
+///
+/// const NAME: CStrRef<'static> = c_str!("glClear");
+/// assert_eq!(NAME.to_bytes_with_null(), b"glClear\0");
+/// ```
+///
+/// ```compile_fail
+/// let _ = triangle_from_scratch_c_types::c_str!("gl\0Clear");
+/// ```
+///
+/// **Note**: This macro can only be passed string _literals_ (not variables or constants!) due to
+/// its internal use of [`concat`] to add a null byte to the end of the string.
+#[macro_export]
+macro_rules! c_str {
+    ($text:expr) => {{
+        const __C_STR: $crate::CStrRef<'static> =
+            match $crate::CStrRef::from_bytes_with_null(concat!($text, '\0').as_bytes()) {
+                Ok(s) => s,
+                Err(_) => panic!("c_str! can't be given a string containing a null"),
+            };
+        __C_STR
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod data_model {
+        use core::mem::size_of;
+
+        use super::*;
+
+        #[test]
+        fn long_matches_the_target() {
+            let expected = if cfg!(windows) { 4 } else { size_of::<usize>() };
+            assert_eq!(size_of::<CLong>(), expected);
+            assert_eq!(size_of::<CULong>(), expected);
+        }
+
+        #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+        #[test]
+        fn linux_x86_64_is_lp64() {
+            assert_eq!(size_of::<CInt>(), 4);
+            assert_eq!(size_of::<CLong>(), 8);
+            assert_eq!(size_of::<CSizeT>(), 8);
+            assert_eq!(CChar::MIN, -128);
+        }
+    }
+
+    mod c_str_ref {
+        use super::*;
+
+        #[test]
+        fn checks_null_termination() {
+            let s = CStrRef::from_bytes_with_null(b"hi\0").unwrap();
+            assert_eq!(s.to_bytes(), b"hi");
+            assert_eq!(s.to_bytes_with_null(), b"hi\0");
+            assert_eq!(s.len(), 2);
+            assert_eq!(s.to_str(), Ok("hi"));
+
+            assert_eq!(
+                CStrRef::from_bytes_with_null(b"hi"),
+                Err(CStrError::NotNullTerminated)
+            );
+            assert_eq!(
+                CStrRef::from_bytes_with_null(b""),
+                Err(CStrError::NotNullTerminated)
+            );
+            assert_eq!(
+                CStrRef::from_bytes_with_null(b"h\0i\0"),
+                Err(CStrError::InteriorNull { position: 1 })
+            );
+        }
+
+        #[test]
+        fn stops_at_the_first_null() {
+            let s = CStrRef::from_bytes_until_null(b"hi\0there\0\0").unwrap();
+            assert_eq!(s.to_bytes_with_null(), b"hi\0");
+            assert_eq!(
+                CStrRef::from_bytes_until_null(b"hi"),
+                Err(CStrError::NotNullTerminated)
+            );
+        }
+
+        #[test]
+        fn converts_to_and_from_c_str() {
+            let s = c_str!("hello");
+            let c: &CStr = s.into();
+            assert_eq!(c.to_bytes(), b"hello");
+            assert_eq!(CStrRef::from(c), s);
+            assert_eq!(unsafe { CStrRef::from_ptr(s.as_ptr()) }, s);
+        }
+
+        #[test]
+        fn formatting() {
+            assert_eq!(format!("{:?}", c_str!("a\"b\n")), r#""a\"b\n""#);
+            assert!(CStrRef::default().is_empty());
+            assert_eq!(
+                CStrError::InteriorNull { position: 3 }.to_string(),
+                "interior null at byte 3"
+            );
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! EGL constants.
+//!
+//! Unless otherwise specified, all constants are from
+//! [`egl.h`](https://registry.khronos.org/EGL/api/EGL/egl.h) in the EGL registry.
+
+use core::ptr;
+
+use super::typedefs::*;
+
+pub const EGL_FALSE: EGLBoolean = 0;
+pub const EGL_TRUE: EGLBoolean = 1;
+
+pub const EGL_NO_CONTEXT: EGLContext = ptr::null_mut();
+pub const EGL_NO_DISPLAY: EGLDisplay = ptr::null_mut();
+pub const EGL_NO_SURFACE: EGLSurface = ptr::null_mut();
+pub const EGL_DEFAULT_DISPLAY: EGLNativeDisplayType = ptr::null_mut();
+
+pub const EGL_SUCCESS: EGLint = 0x3000;
+pub const EGL_NOT_INITIALIZED: EGLint = 0x3001;
+pub const EGL_BAD_ACCESS: EGLint = 0x3002;
+pub const EGL_BAD_ALLOC: EGLint = 0x3003;
+pub const EGL_BAD_ATTRIBUTE: EGLint = 0x3004;
+pub const EGL_BAD_CONFIG: EGLint = 0x3005;
+pub const EGL_BAD_CONTEXT: EGLint = 0x3006;
+pub const EGL_BAD_CURRENT_SURFACE: EGLint = 0x3007;
+pub const EGL_BAD_DISPLAY: EGLint = 0x3008;
+pub const EGL_BAD_MATCH: EGLint = 0x3009;
+pub const EGL_BAD_NATIVE_PIXMAP: EGLint = 0x300A;
+pub const EGL_BAD_NATIVE_WINDOW: EGLint = 0x300B;
+pub const EGL_BAD_PARAMETER: EGLint = 0x300C;
+pub const EGL_BAD_SURFACE: EGLint = 0x300D;
+pub const EGL_CONTEXT_LOST: EGLint = 0x300E;
+
+pub const EGL_BUFFER_SIZE: EGLint = 0x3020;
+pub const EGL_ALPHA_SIZE: EGLint = 0x3021;
+pub const EGL_BLUE_SIZE: EGLint = 0x3022;
+pub const EGL_GREEN_SIZE: EGLint = 0x3023;
+pub const EGL_RED_SIZE: EGLint = 0x3024;
+pub const EGL_DEPTH_SIZE: EGLint = 0x3025;
+pub const EGL_STENCIL_SIZE: EGLint = 0x3026;
+pub const EGL_CONFIG_CAVEAT: EGLint = 0x3027;
+pub const EGL_CONFIG_ID: EGLint = 0x3028;
+pub const EGL_SAMPLES: EGLint = 0x3031;
+pub const EGL_SAMPLE_BUFFERS: EGLint = 0x3032;
+pub const EGL_SURFACE_TYPE: EGLint = 0x3033;
+pub const EGL_MIN_SWAP_INTERVAL: EGLint = 0x303B;
+pub const EGL_MAX_SWAP_INTERVAL: EGLint = 0x303C;
+pub const EGL_COLOR_BUFFER_TYPE: EGLint = 0x303F;
+pub const EGL_NONE: EGLint = 0x3038;
+pub const EGL_RENDERABLE_TYPE: EGLint = 0x3040;
+
+pub const EGL_SLOW_CONFIG: EGLint = 0x3050;
+pub const EGL_NON_CONFORMANT_CONFIG: EGLint = 0x3051;
+
+pub const EGL_RGB_BUFFER: EGLint = 0x308E;
+
+pub const EGL_PBUFFER_BIT: EGLint = 0x0001;
+pub const EGL_WINDOW_BIT: EGLint = 0x0004;
+
+pub const EGL_OPENGL_BIT: EGLint = 0x0008;
+
+pub const EGL_HEIGHT: EGLint = 0x3056;
+pub const EGL_WIDTH: EGLint = 0x3057;
+
+pub const EGL_VENDOR: EGLint = 0x3053;
+pub const EGL_VERSION: EGLint = 0x3054;
+pub const EGL_EXTENSIONS: EGLint = 0x3055;
+pub const EGL_CLIENT_APIS: EGLint = 0x308D;
+
+pub const EGL_OPENGL_API: EGLenum = 0x30A2;
+
+pub const EGL_CONTEXT_MAJOR_VERSION: EGLint = 0x3098;
+pub const EGL_CONTEXT_MINOR_VERSION: EGLint = 0x30FB;
+pub const EGL_CONTEXT_OPENGL_PROFILE_MASK: EGLint = 0x30FD;
+pub const EGL_CONTEXT_OPENGL_DEBUG: EGLint = 0x31B0;
+
+pub const EGL_CONTEXT_OPENGL_CORE_PROFILE_BIT: EGLint = 0x0000_0001;
+pub const EGL_CONTEXT_OPENGL_COMPATIBILITY_PROFILE_BIT: EGLint = 0x0000_0002;
+
+/// A platform for [`eglGetPlatformDisplay`] whose native display is an Xlib `Display*`.
+///
+/// Defined in [`EGL_KHR_platform_x11`](https://registry.khronos.org/EGL/extensions/KHR/EGL_KHR_platform_x11.txt).
+///
+/// [`eglGetPlatformDisplay`]: super::extern_bindings::eglGetPlatformDisplay
+pub const EGL_PLATFORM_X11_KHR: EGLenum = 0x31D5;
+
+/// A platform for [`eglGetPlatformDisplay`] that has no windowing system at all. Contexts created on
+/// it can only render into framebuffer objects, which is perfect for headless testing.
+///
+/// Defined in [`EGL_MESA_platform_surfaceless`](https://registry.khronos.org/EGL/extensions/MESA/EGL_MESA_platform_surfaceless.txt).
+///
+/// [`eglGetPlatformDisplay`]: super::extern_bindings::eglGetPlatformDisplay
+pub const EGL_PLATFORM_SURFACELESS_MESA: EGLenum = 0x31DD;
@@ -0,0 +1,89 @@
+//! Bindings to functions exported by `libEGL`.
+
+use super::typedefs::*;
+use c_types::*;
+
+#[link(name = "EGL")]
+extern "C" {
+    /// See [`eglBindAPI` on the EGL registry](https://registry.khronos.org/EGL/sdk/docs/man/html/eglBindAPI.xhtml).
+    pub fn eglBindAPI(api: EGLenum) -> EGLBoolean;
+
+    /// See [`eglChooseConfig` on the EGL registry](https://registry.khronos.org/EGL/sdk/docs/man/html/eglChooseConfig.xhtml).
+    pub fn eglChooseConfig(
+        dpy: EGLDisplay,
+        attrib_list: *const EGLint,
+        configs: *mut EGLConfig,
+        config_size: EGLint,
+        num_config: *mut EGLint,
+    ) -> EGLBoolean;
+
+    /// See [`eglCreateContext` on the EGL registry](https://registry.khronos.org/EGL/sdk/docs/man/html/eglCreateContext.xhtml).
+    pub fn eglCreateContext(
+        dpy: EGLDisplay,
+        config: EGLConfig,
+        share_context: EGLContext,
+        attrib_list: *const EGLint,
+    ) -> EGLContext;
+
+    /// See [`eglCreatePbufferSurface` on the EGL registry](https://registry.khronos.org/EGL/sdk/docs/man/html/eglCreatePbufferSurface.xhtml).
+    pub fn eglCreatePbufferSurface(
+        dpy: EGLDisplay,
+        config: EGLConfig,
+        attrib_list: *const EGLint,
+    ) -> EGLSurface;
+
+    /// See [`eglDestroyContext` on the EGL registry](https://registry.khronos.org/EGL/sdk/docs/man/html/eglDestroyContext.xhtml).
+    pub fn eglDestroyContext(dpy: EGLDisplay, ctx: EGLContext) -> EGLBoolean;
+
+    /// See [`eglDestroySurface` on the EGL registry](https://registry.khronos.org/EGL/sdk/docs/man/html/eglDestroySurface.xhtml).
+    pub fn eglDestroySurface(dpy: EGLDisplay, surface: EGLSurface) -> EGLBoolean;
+
+    /// See [`eglGetConfigAttrib` on the EGL registry](https://registry.khronos.org/EGL/sdk/docs/man/html/eglGetConfigAttrib.xhtml).
+    pub fn eglGetConfigAttrib(
+        dpy: EGLDisplay,
+        config: EGLConfig,
+        attribute: EGLint,
+        value: *mut EGLint,
+    ) -> EGLBoolean;
+
+    /// See [`eglGetConfigs` on the EGL registry](https://registry.khronos.org/EGL/sdk/docs/man/html/eglGetConfigs.xhtml).
+    pub fn eglGetConfigs(
+        dpy: EGLDisplay,
+        configs: *mut EGLConfig,
+        config_size: EGLint,
+        num_config: *mut EGLint,
+    ) -> EGLBoolean;
+
+    /// See [`eglGetError` on the EGL registry](https://registry.khronos.org/EGL/sdk/docs/man/html/eglGetError.xhtml).
+    pub fn eglGetError() -> EGLint;
+
+    /// See [`eglGetPlatformDisplay` on the EGL registry](https://registry.khronos.org/EGL/sdk/docs/man/html/eglGetPlatformDisplay.xhtml).
+    pub fn eglGetPlatformDisplay(
+        platform: EGLenum,
+        native_display: *mut core::ffi::c_void,
+        attrib_list: *const EGLAttrib,
+    ) -> EGLDisplay;
+
+    /// See [`eglGetProcAddress` on the EGL registry](https://registry.khronos.org/EGL/sdk/docs/man/html/eglGetProcAddress.xhtml).
+    pub fn eglGetProcAddress(procname: *const CChar) -> __eglMustCastToProperFunctionPointerType;
+
+    /// See [`eglInitialize` on the EGL registry](https://registry.khronos.org/EGL/sdk/docs/man/html/eglInitialize.xhtml).
+    pub fn eglInitialize(dpy: EGLDisplay, major: *mut EGLint, minor: *mut EGLint) -> EGLBoolean;
+
+    /// See [`eglMakeCurrent` on the EGL registry](https://registry.khronos.org/EGL/sdk/docs/man/html/eglMakeCurrent.xhtml).
+    pub fn eglMakeCurrent(
+        dpy: EGLDisplay,
+        draw: EGLSurface,
+        read: EGLSurface,
+        ctx: EGLContext,
+    ) -> EGLBoolean;
+
+    /// See [`eglQueryString` on the EGL registry](https://registry.khronos.org/EGL/sdk/docs/man/html/eglQueryString.xhtml).
+    pub fn eglQueryString(dpy: EGLDisplay, name: EGLint) -> *const CChar;
+
+    /// See [`eglSwapInterval` on the EGL registry](https://registry.khronos.org/EGL/sdk/docs/man/html/eglSwapInterval.xhtml).
+    pub fn eglSwapInterval(dpy: EGLDisplay, interval: EGLint) -> EGLBoolean;
+
+    /// See [`eglTerminate` on the EGL registry](https://registry.khronos.org/EGL/sdk/docs/man/html/eglTerminate.xhtml).
+    pub fn eglTerminate(dpy: EGLDisplay) -> EGLBoolean;
+}
@@ -0,0 +1,448 @@
+#![cfg(target_os = "linux")]
+
+//! Bindings to EGL types, constants, and functions.
+//!
+//! EGL is mostly used here to get an OpenGL context without a window, so that code in the other
+//! crates can be exercised on headless machines (like CI runners using Mesa's `llvmpipe`).
+
+// EGL names are very incompatible with Rust's default lints, so we have to disable some of them.
+#![allow(non_snake_case, non_camel_case_types, non_upper_case_globals)]
+
+use core::{fmt, ptr};
+
+pub mod constants;
+pub mod extern_bindings;
+pub mod prelude;
+pub mod typedefs;
+
+use prelude::*;
+
+/// Represents an error code returned by [`eglGetError`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct EglError(pub EGLint);
+
+impl EglError {
+    /// The name of this error code, as given in the EGL specification.
+    pub fn name(self) -> &'static str {
+        match self.0 {
+            EGL_SUCCESS => "EGL_SUCCESS",
+            EGL_NOT_INITIALIZED => "EGL_NOT_INITIALIZED",
+            EGL_BAD_ACCESS => "EGL_BAD_ACCESS",
+            EGL_BAD_ALLOC => "EGL_BAD_ALLOC",
+            EGL_BAD_ATTRIBUTE => "EGL_BAD_ATTRIBUTE",
+            EGL_BAD_CONFIG => "EGL_BAD_CONFIG",
+            EGL_BAD_CONTEXT => "EGL_BAD_CONTEXT",
+            EGL_BAD_CURRENT_SURFACE => "EGL_BAD_CURRENT_SURFACE",
+            EGL_BAD_DISPLAY => "EGL_BAD_DISPLAY",
+            EGL_BAD_MATCH => "EGL_BAD_MATCH",
+            EGL_BAD_NATIVE_PIXMAP => "EGL_BAD_NATIVE_PIXMAP",
+            EGL_BAD_NATIVE_WINDOW => "EGL_BAD_NATIVE_WINDOW",
+            EGL_BAD_PARAMETER => "EGL_BAD_PARAMETER",
+            EGL_BAD_SURFACE => "EGL_BAD_SURFACE",
+            EGL_CONTEXT_LOST => "EGL_CONTEXT_LOST",
+            _ => "unknown EGL error",
+        }
+    }
+}
+
+impl fmt::Debug for EglError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EglError")
+            .field(&format!("{:#06X} => {}", &self.0, self.name()))
+            .finish()
+    }
+}
+
+impl fmt::Display for EglError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:#06X})", self.name(), self.0)
+    }
+}
+
+impl std::error::Error for EglError {}
+
+/// Returns the error code of the last EGL call made on this thread.
+///
+/// See [`eglGetError`](https://registry.khronos.org/EGL/sdk/docs/man/html/eglGetError.xhtml)
+pub fn get_error() -> EglError {
+    // Safety: eglGetError has no preconditions.
+    EglError(unsafe { eglGetError() })
+}
+
+/// Turns an [`EGLBoolean`] result into a [`Result`], fetching the error code on failure.
+fn check(success: EGLBoolean) -> Result<(), EglError> {
+    if success == EGL_TRUE {
+        Ok(())
+    } else {
+        Err(get_error())
+    }
+}
+
+/// Gets a display connection for the given platform, with no platform attributes.
+///
+/// See [`eglGetPlatformDisplay`](https://registry.khronos.org/EGL/sdk/docs/man/html/eglGetPlatformDisplay.xhtml)
+///
+/// ## Safety
+///
+/// `native_display` must be a valid native display for `platform`, or null if the platform
+/// allows it (e.g. [`EGL_PLATFORM_SURFACELESS_MESA`]).
+pub unsafe fn get_platform_display(
+    platform: EGLenum,
+    native_display: *mut core::ffi::c_void,
+) -> Result<EGLDisplay, EglError> {
+    let attribs = [EGL_NONE as EGLAttrib];
+    let display = eglGetPlatformDisplay(platform, native_display, attribs.as_ptr());
+    if display == EGL_NO_DISPLAY {
+        Err(get_error())
+    } else {
+        Ok(display)
+    }
+}
+
+/// Initializes an EGL display connection, returning the `(major, minor)` EGL version.
+///
+/// See [`eglInitialize`](https://registry.khronos.org/EGL/sdk/docs/man/html/eglInitialize.xhtml)
+///
+/// ## Safety
+///
+/// `display` must have been returned from [`get_platform_display`] or similar.
+pub unsafe fn initialize(display: EGLDisplay) -> Result<(EGLint, EGLint), EglError> {
+    let mut major = 0;
+    let mut minor = 0;
+    check(eglInitialize(display, &mut major, &mut minor))?;
+    Ok((major, minor))
+}
+
+/// Releases the resources associated with an EGL display connection.
+///
+/// See [`eglTerminate`](https://registry.khronos.org/EGL/sdk/docs/man/html/eglTerminate.xhtml)
+///
+/// ## Safety
+///
+/// `display` must be a valid display. Any contexts or surfaces created on it become invalid.
+pub unsafe fn terminate(display: EGLDisplay) -> Result<(), EglError> {
+    check(eglTerminate(display))
+}
+
+/// Sets the rendering API used by later EGL calls on this thread.
+///
+/// See [`eglBindAPI`](https://registry.khronos.org/EGL/sdk/docs/man/html/eglBindAPI.xhtml)
+pub fn bind_api(api: EGLenum) -> Result<(), EglError> {
+    // Safety: invalid values are reported through eglGetError rather than causing UB.
+    check(unsafe { eglBindAPI(api) })
+}
+
+/// Returns up to `max` frame buffer configurations that match the given attributes.
+///
+/// - `attribs` is a list of `key, value` pairs, which must end with [`EGL_NONE`].
+///
+/// See [`eglChooseConfig`](https://registry.khronos.org/EGL/sdk/docs/man/html/eglChooseConfig.xhtml)
+///
+/// ## Safety
+///
+/// `display` must be a valid, initialized display.
+pub unsafe fn choose_config(
+    display: EGLDisplay,
+    attribs: &[EGLint],
+    max: usize,
+) -> Result<Vec<EGLConfig>, EglError> {
+    assert_eq!(
+        attribs.last(),
+        Some(&EGL_NONE),
+        "attribs must end with EGL_NONE"
+    );
+
+    let mut configs = vec![ptr::null_mut(); max];
+    let mut count = 0;
+    check(eglChooseConfig(
+        display,
+        attribs.as_ptr(),
+        configs.as_mut_ptr(),
+        max.try_into().unwrap_or(EGLint::MAX),
+        &mut count,
+    ))?;
+    configs.truncate(count.max(0) as usize);
+    Ok(configs)
+}
+
+/// Returns every frame buffer configuration the display has.
+///
+/// See [`eglGetConfigs`](https://registry.khronos.org/EGL/sdk/docs/man/html/eglGetConfigs.xhtml)
+///
+/// ## Safety
+///
+/// `display` must be a valid, initialized display.
+pub unsafe fn get_configs(display: EGLDisplay) -> Result<Vec<EGLConfig>, EglError> {
+    let mut count = 0;
+    check(eglGetConfigs(display, ptr::null_mut(), 0, &mut count))?;
+
+    let mut configs = vec![ptr::null_mut(); count.max(0) as usize];
+    check(eglGetConfigs(
+        display,
+        configs.as_mut_ptr(),
+        count,
+        &mut count,
+    ))?;
+    configs.truncate(count.max(0) as usize);
+    Ok(configs)
+}
+
+/// Gets the value of one of a frame buffer configuration's attributes, like [`EGL_DEPTH_SIZE`].
+///
+/// See [`eglGetConfigAttrib`](https://registry.khronos.org/EGL/sdk/docs/man/html/eglGetConfigAttrib.xhtml)
+///
+/// ## Safety
+///
+/// `display` must be a valid, initialized display and `config` must have come from it.
+pub unsafe fn get_config_attrib(
+    display: EGLDisplay,
+    config: EGLConfig,
+    attribute: EGLint,
+) -> Result<EGLint, EglError> {
+    let mut value = 0;
+    check(eglGetConfigAttrib(display, config, attribute, &mut value))?;
+    Ok(value)
+}
+
+/// Creates a rendering context for the currently bound API.
+///
+/// - `attribs` is a list of `key, value` pairs, which must end with [`EGL_NONE`].
+///
+/// See [`eglCreateContext`](https://registry.khronos.org/EGL/sdk/docs/man/html/eglCreateContext.xhtml)
+///
+/// ## Safety
+///
+/// `display` must be a valid, initialized display and `config` must have come from it.
+pub unsafe fn create_context(
+    display: EGLDisplay,
+    config: EGLConfig,
+    share_context: EGLContext,
+    attribs: &[EGLint],
+) -> Result<EGLContext, EglError> {
+    assert_eq!(
+        attribs.last(),
+        Some(&EGL_NONE),
+        "attribs must end with EGL_NONE"
+    );
+
+    let context = eglCreateContext(display, config, share_context, attribs.as_ptr());
+    if context == EGL_NO_CONTEXT {
+        Err(get_error())
+    } else {
+        Ok(context)
+    }
+}
+
+/// Destroys a rendering context.
+///
+/// See [`eglDestroyContext`](https://registry.khronos.org/EGL/sdk/docs/man/html/eglDestroyContext.xhtml)
+///
+/// ## Safety
+///
+/// `context` must have been created on `display`.
+pub unsafe fn destroy_context(display: EGLDisplay, context: EGLContext) -> Result<(), EglError> {
+    check(eglDestroyContext(display, context))
+}
+
+/// Creates an off-screen surface to draw to.
+///
+/// - `attribs` is a list of `key, value` pairs, like [`EGL_WIDTH`] and [`EGL_HEIGHT`], which must
+///   end with [`EGL_NONE`].
+///
+/// See [`eglCreatePbufferSurface`](https://registry.khronos.org/EGL/sdk/docs/man/html/eglCreatePbufferSurface.xhtml)
+///
+/// ## Safety
+///
+/// `display` must be a valid, initialized display and `config` must have come from it.
+pub unsafe fn create_pbuffer_surface(
+    display: EGLDisplay,
+    config: EGLConfig,
+    attribs: &[EGLint],
+) -> Result<EGLSurface, EglError> {
+    assert_eq!(
+        attribs.last(),
+        Some(&EGL_NONE),
+        "attribs must end with EGL_NONE"
+    );
+
+    let surface = eglCreatePbufferSurface(display, config, attribs.as_ptr());
+    if surface == EGL_NO_SURFACE {
+        Err(get_error())
+    } else {
+        Ok(surface)
+    }
+}
+
+/// Destroys a surface. If it's current, that happens once it stops being current.
+///
+/// See [`eglDestroySurface`](https://registry.khronos.org/EGL/sdk/docs/man/html/eglDestroySurface.xhtml)
+///
+/// ## Safety
+///
+/// `surface` must have been created on `display`.
+pub unsafe fn destroy_surface(display: EGLDisplay, surface: EGLSurface) -> Result<(), EglError> {
+    check(eglDestroySurface(display, surface))
+}
+
+/// Makes a context current on this thread, optionally attached to draw and read surfaces.
+///
+/// - You can pass [`EGL_NO_SURFACE`] for both surfaces if the display supports surfaceless
+///   contexts, and [`EGL_NO_CONTEXT`] to release the current context.
+///
+/// See [`eglMakeCurrent`](https://registry.khronos.org/EGL/sdk/docs/man/html/eglMakeCurrent.xhtml)
+///
+/// ## Safety
+///
+/// All handles must be valid for `display` (or the relevant `EGL_NO_*` value).
+pub unsafe fn make_current(
+    display: EGLDisplay,
+    draw: EGLSurface,
+    read: EGLSurface,
+    context: EGLContext,
+) -> Result<(), EglError> {
+    check(eglMakeCurrent(display, draw, read, context))
+}
+
+/// Sets how many vertical blanks a buffer swap on the current context's draw surface waits for.
+///
+/// EGL silently clamps `interval` to the config's [`EGL_MIN_SWAP_INTERVAL`] and
+/// [`EGL_MAX_SWAP_INTERVAL`], so there are no negative ("late swaps tear") intervals.
+///
+/// See [`eglSwapInterval`](https://registry.khronos.org/EGL/sdk/docs/man/html/eglSwapInterval.xhtml)
+///
+/// ## Safety
+///
+/// `display` must be the display of the current context.
+pub unsafe fn swap_interval(display: EGLDisplay, interval: EGLint) -> Result<(), EglError> {
+    check(eglSwapInterval(display, interval))
+}
+
+/// Queries a string describing some part of the EGL implementation, like [`EGL_VENDOR`] or
+/// [`EGL_EXTENSIONS`].
+///
+/// See [`eglQueryString`](https://registry.khronos.org/EGL/sdk/docs/man/html/eglQueryString.xhtml)
+///
+/// ## Safety
+///
+/// `display` must be a valid, initialized display, or [`EGL_NO_DISPLAY`] for client extensions.
+pub unsafe fn query_string(display: EGLDisplay, name: EGLint) -> Result<String, EglError> {
+    let p = eglQueryString(display, name);
+    if p.is_null() {
+        return Err(get_error());
+    }
+    Ok(core::ffi::CStr::from_ptr(p).to_string_lossy().into_owned())
+}
+
+/// Gets the address of a client API or EGL function.
+///
+/// - `func_name` must be a null-terminated ASCII string.
+///
+/// See [`eglGetProcAddress`](https://registry.khronos.org/EGL/sdk/docs/man/html/eglGetProcAddress.xhtml)
+///
+/// ## Safety
+///
+/// Calling this function is fine, but the returned pointer must only be called with the signature
+/// of the named function.
+pub fn get_proc_address(func_name: &[u8]) -> Result<*mut core::ffi::c_void, EglError> {
+    // check that we end the slice with a \0 as expected
+    match func_name.last() {
+        Some(b'\0') => (),
+        _ => return Err(EglError(EGL_BAD_PARAMETER)),
+    }
+
+    // Safety: we've already checked that the end of the slice is null-terminated
+    let proc = unsafe { eglGetProcAddress(func_name.as_ptr().cast()) };
+
+    if proc.is_null() {
+        Err(get_error())
+    } else {
+        Ok(proc)
+    }
+}
+
+/// An OpenGL core profile context with no window or surface attached, made current on the thread
+/// that created it.
+///
+/// This relies on the
+/// [`EGL_MESA_platform_surfaceless`](https://registry.khronos.org/EGL/extensions/MESA/EGL_MESA_platform_surfaceless.txt)
+/// extension, so all rendering has to go into framebuffer objects. The context is destroyed on
+/// drop.
+///
+/// EGL hands out the same display handle to everyone who asks for the surfaceless platform, so the
+/// display is never terminated. Doing so would pull the rug out from under contexts on other
+/// threads.
+pub struct HeadlessContext {
+    display: EGLDisplay,
+    config: EGLConfig,
+    context: EGLContext,
+}
+
+impl HeadlessContext {
+    /// Creates an OpenGL `major.minor` core profile context and makes it current.
+    pub fn new(major: EGLint, minor: EGLint) -> Result<Self, EglError> {
+        // Safety: the surfaceless platform takes no native display, and every handle used below
+        // comes straight from the previous call.
+        unsafe {
+            let display = get_platform_display(EGL_PLATFORM_SURFACELESS_MESA, ptr::null_mut())?;
+            initialize(display)?;
+
+            bind_api(EGL_OPENGL_API)?;
+
+            #[rustfmt::skip]
+            let config_attribs = [
+                EGL_SURFACE_TYPE, EGL_PBUFFER_BIT,
+                EGL_RENDERABLE_TYPE, EGL_OPENGL_BIT,
+                EGL_NONE,
+            ];
+            let config = *choose_config(display, &config_attribs, 1)?
+                .first()
+                .ok_or(EglError(EGL_BAD_CONFIG))?;
+
+            #[rustfmt::skip]
+            let context_attribs = [
+                EGL_CONTEXT_MAJOR_VERSION, major,
+                EGL_CONTEXT_MINOR_VERSION, minor,
+                EGL_CONTEXT_OPENGL_PROFILE_MASK, EGL_CONTEXT_OPENGL_CORE_PROFILE_BIT,
+                EGL_NONE,
+            ];
+            let context = create_context(display, config, EGL_NO_CONTEXT, &context_attribs)?;
+
+            if let Err(e) = make_current(display, EGL_NO_SURFACE, EGL_NO_SURFACE, context) {
+                destroy_context(display, context).ok();
+                return Err(e);
+            }
+
+            Ok(Self {
+                display,
+                config,
+                context,
+            })
+        }
+    }
+
+    /// The display connection this context was created on.
+    pub fn display(&self) -> EGLDisplay {
+        self.display
+    }
+
+    /// The frame buffer configuration the context was created with.
+    pub fn config(&self) -> EGLConfig {
+        self.config
+    }
+
+    /// The raw context handle.
+    pub fn context(&self) -> EGLContext {
+        self.context
+    }
+}
+
+impl Drop for HeadlessContext {
+    fn drop(&mut self) {
+        // Safety: both handles were created together in `new` and haven't been released since.
+        unsafe {
+            make_current(self.display, EGL_NO_SURFACE, EGL_NO_SURFACE, EGL_NO_CONTEXT).ok();
+            destroy_context(self.display, self.context).ok();
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Basic EGL type definitions.
+//!
+//! Unless otherwise specified, all type definitions are from
+//! [`egl.h`](https://registry.khronos.org/EGL/api/EGL/egl.h) in the EGL registry.
+
+use core::ffi::c_void;
+
+use c_types::*;
+
+pub type EGLBoolean = CUInt;
+
+pub type EGLenum = CUInt;
+
+pub type EGLint = i32;
+
+/// An attribute value wide enough to hold a pointer. Introduced in EGL 1.5.
+pub type EGLAttrib = isize;
+
+pub type EGLDisplay = *mut c_void;
+
+pub type EGLConfig = *mut c_void;
+
+pub type EGLSurface = *mut c_void;
+
+pub type EGLContext = *mut c_void;
+
+pub type EGLClientBuffer = *mut c_void;
+
+pub type EGLNativeDisplayType = *mut c_void;
+
+/// A pointer to a procedure of unknown type, as returned by [`eglGetProcAddress`].
+///
+/// [`eglGetProcAddress`]: super::extern_bindings::eglGetProcAddress
+pub type __eglMustCastToProperFunctionPointerType = *mut c_void;
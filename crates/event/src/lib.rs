@@ -0,0 +1,12 @@
+//! Window events shared by the platform crates, so that applications handle them the same way
+//! whichever platform they came from.
+
+use std::path::PathBuf;
+
+/// Something that happened to a window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A file (or folder) was dropped on the window. When several are dropped together, each comes
+    /// as an event of its own, in the order the platform listed them.
+    FileDropped(PathBuf),
+}
@@ -4,7 +4,10 @@
 //! [`gl.xml`](https://github.com/KhronosGroup/OpenGL-Registry/blob/main/xml/gl.xml)
 //! in the OpenGL registry.
 
-use super::{prelude::GLboolean, typedefs::GLenum};
+use super::{
+    prelude::GLboolean,
+    typedefs::{GLenum, GLuint},
+};
 
 pub const GL_FALSE: GLboolean = 0;
 pub const GL_TRUE: GLboolean = 1;
@@ -71,19 +74,99 @@ pub const GL_ARRAY_BUFFER: GLenum = 0x8892;
 pub const GL_ARRAY_BUFFER_ARB: GLenum = 0x8892;
 pub const GL_ELEMENT_ARRAY_BUFFER: GLenum = 0x8893;
 pub const GL_ELEMENT_ARRAY_BUFFER_ARB: GLenum = 0x8893;
+pub const GL_UNIFORM_BUFFER: GLenum = 0x8A11;
+pub const GL_SHADER_STORAGE_BUFFER: GLenum = 0x90D2;
+pub const GL_DISPATCH_INDIRECT_BUFFER: GLenum = 0x90EE;
 
 pub const GL_STATIC_DRAW: GLenum = 0x88E4;
 pub const GL_STATIC_DRAW_ARB: GLenum = 0x88E4;
+pub const GL_STREAM_DRAW: GLenum = 0x88E0;
+pub const GL_STREAM_DRAW_ARB: GLenum = 0x88E0;
+pub const GL_DYNAMIC_DRAW: GLenum = 0x88E8;
+pub const GL_DYNAMIC_DRAW_ARB: GLenum = 0x88E8;
 
 pub const GL_FRAGMENT_SHADER: GLenum = 0x8B30;
 pub const GL_FRAGMENT_SHADER_ARB: GLenum = 0x8B30;
 pub const GL_VERTEX_SHADER: GLenum = 0x8B31;
 pub const GL_VERTEX_SHADER_ARB: GLenum = 0x8B31;
+pub const GL_COMPUTE_SHADER: GLenum = 0x91B9;
 
 pub const GL_COMPILE_STATUS: GLenum = 0x8B81;
 pub const GL_OBJECT_COMPILE_STATUS_ARB: GLenum = 0x8B81;
 pub const GL_LINK_STATUS: GLenum = 0x8B82;
 pub const GL_OBJECT_LINK_STATUS_ARB: GLenum = 0x8B82;
+pub const GL_INFO_LOG_LENGTH: GLenum = 0x8B84;
+pub const GL_OBJECT_INFO_LOG_LENGTH_ARB: GLenum = 0x8B84;
+
+pub const GL_PROGRAM_BINARY_RETRIEVABLE_HINT: GLenum = 0x8257;
+pub const GL_PROGRAM_BINARY_LENGTH: GLenum = 0x8741;
+pub const GL_NUM_PROGRAM_BINARY_FORMATS: GLenum = 0x87FE;
+pub const GL_PROGRAM_BINARY_FORMATS: GLenum = 0x87FF;
+
+pub const GL_VENDOR: GLenum = 0x1F00;
+pub const GL_RENDERER: GLenum = 0x1F01;
+pub const GL_VERSION: GLenum = 0x1F02;
+pub const GL_SHADING_LANGUAGE_VERSION: GLenum = 0x8B8C;
+
+pub const GL_UNIFORM_BLOCK_INDEX: GLenum = 0x8A3A;
+pub const GL_UNIFORM_OFFSET: GLenum = 0x8A3B;
+pub const GL_UNIFORM_ARRAY_STRIDE: GLenum = 0x8A3C;
+pub const GL_UNIFORM_MATRIX_STRIDE: GLenum = 0x8A3D;
+pub const GL_UNIFORM_BLOCK_DATA_SIZE: GLenum = 0x8A40;
+pub const GL_INVALID_INDEX: GLuint = 0xFFFFFFFF;
+
+pub const GL_UNIFORM_BLOCK: GLenum = 0x92E2;
+pub const GL_BUFFER_VARIABLE: GLenum = 0x92E5;
+pub const GL_SHADER_STORAGE_BLOCK: GLenum = 0x92E6;
+pub const GL_OFFSET: GLenum = 0x92FC;
+pub const GL_ARRAY_STRIDE: GLenum = 0x92FE;
+pub const GL_MATRIX_STRIDE: GLenum = 0x92FF;
+pub const GL_BUFFER_DATA_SIZE: GLenum = 0x9303;
+pub const GL_TOP_LEVEL_ARRAY_SIZE: GLenum = 0x930C;
+pub const GL_TOP_LEVEL_ARRAY_STRIDE: GLenum = 0x930D;
+
+pub const GL_MAX_COMPUTE_WORK_GROUP_INVOCATIONS: GLenum = 0x90EB;
+pub const GL_MAX_COMPUTE_WORK_GROUP_COUNT: GLenum = 0x91BE;
+pub const GL_MAX_COMPUTE_WORK_GROUP_SIZE: GLenum = 0x91BF;
+pub const GL_MAX_COMPUTE_SHARED_MEMORY_SIZE: GLenum = 0x8262;
+pub const GL_COMPUTE_WORK_GROUP_SIZE: GLenum = 0x8267;
+
+pub const GL_VERTEX_ATTRIB_ARRAY_BARRIER_BIT: GLenum = 0x00000001;
+pub const GL_ELEMENT_ARRAY_BARRIER_BIT: GLenum = 0x00000002;
+pub const GL_UNIFORM_BARRIER_BIT: GLenum = 0x00000004;
+pub const GL_TEXTURE_FETCH_BARRIER_BIT: GLenum = 0x00000008;
+pub const GL_SHADER_IMAGE_ACCESS_BARRIER_BIT: GLenum = 0x00000020;
+pub const GL_COMMAND_BARRIER_BIT: GLenum = 0x00000040;
+pub const GL_PIXEL_BUFFER_BARRIER_BIT: GLenum = 0x00000080;
+pub const GL_TEXTURE_UPDATE_BARRIER_BIT: GLenum = 0x00000100;
+pub const GL_BUFFER_UPDATE_BARRIER_BIT: GLenum = 0x00000200;
+pub const GL_FRAMEBUFFER_BARRIER_BIT: GLenum = 0x00000400;
+pub const GL_TRANSFORM_FEEDBACK_BARRIER_BIT: GLenum = 0x00000800;
+pub const GL_ATOMIC_COUNTER_BARRIER_BIT: GLenum = 0x00001000;
+pub const GL_SHADER_STORAGE_BARRIER_BIT: GLenum = 0x00002000;
+pub const GL_CLIENT_MAPPED_BUFFER_BARRIER_BIT: GLenum = 0x00004000;
+pub const GL_QUERY_BUFFER_BARRIER_BIT: GLenum = 0x00008000;
+pub const GL_ALL_BARRIER_BITS: GLenum = 0xFFFFFFFF;
+
+pub const GL_TEXTURE_2D: GLenum = 0x0DE1;
+
+// glGet parameters
+pub const GL_VIEWPORT: GLenum = 0x0BA2;
+pub const GL_SCISSOR_BOX: GLenum = 0x0C10;
+
+pub const GL_READ_ONLY: GLenum = 0x88B8;
+pub const GL_WRITE_ONLY: GLenum = 0x88B9;
+pub const GL_READ_WRITE: GLenum = 0x88BA;
+
+pub const GL_RED: GLenum = 0x1903;
+pub const GL_RGBA: GLenum = 0x1908;
+pub const GL_RGBA8: GLenum = 0x8058;
+pub const GL_R32F: GLenum = 0x822E;
+pub const GL_R32UI: GLenum = 0x8236;
+pub const GL_RGBA32F: GLenum = 0x8814;
+
+pub const GL_NO_ERROR: GLenum = 0;
+pub const GL_INVALID_ENUM: GLenum = 0x0500;
 
 pub const GL_BYTE: GLenum = 0x1400;
 pub const GL_UNSIGNED_BYTE: GLenum = 0x1401;
@@ -16,6 +16,45 @@ pub type glAttachShader_t = Option<unsafe extern "system" fn(program: GLuint, sh
 /// **See**: [`glBindBuffer` on docs.gl](https://docs.gl/gl4/glBindBuffer)
 pub type glBindBuffer_t = Option<unsafe extern "system" fn(target: GLenum, buffer: GLuint)>;
 
+/// Bind a buffer object to an indexed buffer target.
+///
+/// **See**: [`glBindBufferBase` on docs.gl](https://docs.gl/gl4/glBindBufferBase)
+pub type glBindBufferBase_t =
+    Option<unsafe extern "system" fn(target: GLenum, index: GLuint, buffer: GLuint)>;
+
+/// Bind a range within a buffer object to an indexed buffer target.
+///
+/// **See**: [`glBindBufferRange` on docs.gl](https://docs.gl/gl4/glBindBufferRange)
+pub type glBindBufferRange_t = Option<
+    unsafe extern "system" fn(
+        target: GLenum,
+        index: GLuint,
+        buffer: GLuint,
+        offset: GLintptr,
+        size: GLsizeiptr,
+    ),
+>;
+
+/// Bind a level of a texture to an image unit.
+///
+/// **See**: [`glBindImageTexture` on docs.gl](https://docs.gl/gl4/glBindImageTexture)
+pub type glBindImageTexture_t = Option<
+    unsafe extern "system" fn(
+        unit: GLuint,
+        texture: GLuint,
+        level: GLint,
+        layered: GLboolean,
+        layer: GLint,
+        access: GLenum,
+        format: GLenum,
+    ),
+>;
+
+/// Bind a named texture to a texturing target.
+///
+/// **See**: [`glBindTexture` on docs.gl](https://docs.gl/gl4/glBindTexture)
+pub type glBindTexture_t = Option<unsafe extern "system" fn(target: GLenum, texture: GLuint)>;
+
 /// Bind a vertex array object.
 ///
 /// **See**: [`glBindVertexArray` on docs.gl](https://docs.gl/gl4/glBindVertexArray)
@@ -54,11 +93,39 @@ pub type glCreateProgram_t = Option<unsafe extern "system" fn() -> GLuint>;
 /// **See**: [`glCreateShader` on docs.gl](https://docs.gl/gl4/glCreateShader)
 pub type glCreateShader_t = Option<unsafe extern "system" fn(shaderType: GLenum) -> GLuint>;
 
+/// Delete named buffer objects.
+///
+/// **See**: [`glDeleteBuffers` on docs.gl](https://docs.gl/gl4/glDeleteBuffers)
+pub type glDeleteBuffers_t = Option<unsafe extern "system" fn(n: GLsizei, buffers: *const GLuint)>;
+
+/// Deletes a program object.
+///
+/// **See**: [`glDeleteProgram` on docs.gl](https://docs.gl/gl4/glDeleteProgram)
+pub type glDeleteProgram_t = Option<unsafe extern "system" fn(program: GLuint)>;
+
 /// Deletes a shader object
 ///
 /// **See**: [`glDeleteShader` on docs.gl](https://docs.gl/gl4/glDeleteShader)
 pub type glDeleteShader_t = Option<unsafe extern "system" fn(shader: GLuint)>;
 
+/// Delete named textures.
+///
+/// **See**: [`glDeleteTextures` on docs.gl](https://docs.gl/gl4/glDeleteTextures)
+pub type glDeleteTextures_t =
+    Option<unsafe extern "system" fn(n: GLsizei, textures: *const GLuint)>;
+
+/// Launch one or more compute work groups.
+///
+/// **See**: [`glDispatchCompute` on docs.gl](https://docs.gl/gl4/glDispatchCompute)
+pub type glDispatchCompute_t = Option<
+    unsafe extern "system" fn(num_groups_x: GLuint, num_groups_y: GLuint, num_groups_z: GLuint),
+>;
+
+/// Launch one or more compute work groups using parameters stored in a buffer.
+///
+/// **See**: [`glDispatchComputeIndirect` on docs.gl](https://docs.gl/gl4/glDispatchComputeIndirect)
+pub type glDispatchComputeIndirect_t = Option<unsafe extern "system" fn(indirect: GLintptr)>;
+
 /// Render primitives from array data
 ///
 /// **See**: [`glDrawArrays` on docs.gl](https://docs.gl/gl4/glDrawArrays)
@@ -82,11 +149,82 @@ pub type glEnableVertexAttribArray_t = Option<unsafe extern "system" fn(index: G
 /// **See**: [`glGenBuffers` on docs.gl](https://docs.gl/gl4/glGenBuffers)
 pub type glGenBuffers_t = Option<unsafe extern "system" fn(n: GLsizei, buffers: *mut GLuint)>;
 
+/// Generate texture names.
+///
+/// **See**: [`glGenTextures` on docs.gl](https://docs.gl/gl4/glGenTextures)
+pub type glGenTextures_t = Option<unsafe extern "system" fn(n: GLsizei, textures: *mut GLuint)>;
+
 /// Generate vertex array object names
 ///
 /// **See**: [`glGenVertexArrays` on docs.gl](https://docs.gl/gl4/glGenVertexArrays)
 pub type glGenVertexArrays_t = Option<unsafe extern "system" fn(n: GLsizei, arrays: *mut GLuint)>;
 
+/// Query information about an active uniform block.
+///
+/// **See**: [`glGetActiveUniformBlock` on docs.gl](https://docs.gl/gl4/glGetActiveUniformBlock)
+pub type glGetActiveUniformBlockiv_t = Option<
+    unsafe extern "system" fn(
+        program: GLuint,
+        uniformBlockIndex: GLuint,
+        pname: GLenum,
+        params: *mut GLint,
+    ),
+>;
+
+/// Returns information about several active uniform variables for the specified program object.
+///
+/// **See**: [`glGetActiveUniformsiv` on docs.gl](https://docs.gl/gl4/glGetActiveUniformsiv)
+pub type glGetActiveUniformsiv_t = Option<
+    unsafe extern "system" fn(
+        program: GLuint,
+        uniformCount: GLsizei,
+        uniformIndices: *const GLuint,
+        pname: GLenum,
+        params: *mut GLint,
+    ),
+>;
+
+/// Returns a subset of a buffer object's data store.
+///
+/// **See**: [`glGetBufferSubData` on docs.gl](https://docs.gl/gl4/glGetBufferSubData)
+pub type glGetBufferSubData_t = Option<
+    unsafe extern "system" fn(
+        target: GLenum,
+        offset: GLintptr,
+        size: GLsizeiptr,
+        data: *mut GLvoid,
+    ),
+>;
+
+/// Return error information.
+///
+/// **See**: [`glGetError` on docs.gl](https://docs.gl/gl4/glGetError)
+pub type glGetError_t = Option<unsafe extern "system" fn() -> GLenum>;
+
+/// Return the value of an indexed parameter.
+///
+/// **See**: [`glGet` on docs.gl](https://docs.gl/gl4/glGet)
+pub type glGetIntegeri_v_t =
+    Option<unsafe extern "system" fn(target: GLenum, index: GLuint, data: *mut GLint)>;
+
+/// Return the value of a selected parameter.
+///
+/// **See**: [`glGet` on docs.gl](https://docs.gl/gl4/glGet)
+pub type glGetIntegerv_t = Option<unsafe extern "system" fn(pname: GLenum, data: *mut GLint)>;
+
+/// Return a binary representation of a program object's compiled and linked executable source.
+///
+/// **See**: [`glGetProgramBinary` on docs.gl](https://docs.gl/gl4/glGetProgramBinary)
+pub type glGetProgramBinary_t = Option<
+    unsafe extern "system" fn(
+        program: GLuint,
+        bufSize: GLsizei,
+        length: *mut GLsizei,
+        binaryFormat: *mut GLenum,
+        binary: *mut GLvoid,
+    ),
+>;
+
 /// Returns the information log for a program object.
 ///
 /// **See**: [`glGetProgramInfoLog` on docs.gl](https://docs.gl/gl4/glGetProgramInfoLog)
@@ -105,6 +243,33 @@ pub type glGetProgramInfoLog_t = Option<
 pub type glGetProgramiv_t =
     Option<unsafe extern "system" fn(program: GLuint, pname: GLenum, params: *mut GLint)>;
 
+/// Query the index of a named resource within a program.
+///
+/// **See**: [`glGetProgramResourceIndex` on docs.gl](https://docs.gl/gl4/glGetProgramResourceIndex)
+pub type glGetProgramResourceIndex_t = Option<
+    unsafe extern "system" fn(
+        program: GLuint,
+        programInterface: GLenum,
+        name: *const GLchar,
+    ) -> GLuint,
+>;
+
+/// Retrieve values for multiple properties of a single active resource within a program object.
+///
+/// **See**: [`glGetProgramResource` on docs.gl](https://docs.gl/gl4/glGetProgramResource)
+pub type glGetProgramResourceiv_t = Option<
+    unsafe extern "system" fn(
+        program: GLuint,
+        programInterface: GLenum,
+        index: GLuint,
+        propCount: GLsizei,
+        props: *const GLenum,
+        count: GLsizei,
+        length: *mut GLsizei,
+        params: *mut GLint,
+    ),
+>;
+
 /// Returns the information log for a shader object.
 ///
 /// **See**: [`glGetShaderInfoLog` on docs.gl](https://docs.gl/gl4/glGetShaderInfoLog);
@@ -123,11 +288,81 @@ pub type glGetShaderInfoLog_t = Option<
 pub type glGetShaderiv_t =
     Option<unsafe extern "system" fn(shader: GLuint, pname: GLenum, params: *mut GLint)>;
 
+/// Return a string describing the current GL connection.
+///
+/// **See**: [`glGetString` on docs.gl](https://docs.gl/gl4/glGetString)
+pub type glGetString_t = Option<unsafe extern "system" fn(name: GLenum) -> *const GLubyte>;
+
+/// Return a texture image.
+///
+/// **See**: [`glGetTexImage` on docs.gl](https://docs.gl/gl4/glGetTexImage)
+pub type glGetTexImage_t = Option<
+    unsafe extern "system" fn(
+        target: GLenum,
+        level: GLint,
+        format: GLenum,
+        gltype: GLenum,
+        pixels: *mut GLvoid,
+    ),
+>;
+
+/// Retrieve the index of a named uniform block.
+///
+/// **See**: [`glGetUniformBlockIndex` on docs.gl](https://docs.gl/gl4/glGetUniformBlockIndex)
+pub type glGetUniformBlockIndex_t =
+    Option<unsafe extern "system" fn(program: GLuint, uniformBlockName: *const GLchar) -> GLuint>;
+
+/// Retrieve the index of one or more uniforms within a program.
+///
+/// **See**: [`glGetUniformIndices` on docs.gl](https://docs.gl/gl4/glGetUniformIndices)
+pub type glGetUniformIndices_t = Option<
+    unsafe extern "system" fn(
+        program: GLuint,
+        uniformCount: GLsizei,
+        uniformNames: *const *const GLchar,
+        uniformIndices: *mut GLuint,
+    ),
+>;
+
+/// Determine if a name corresponds to a program object.
+///
+/// **See**: [`glIsProgram` on docs.gl](https://docs.gl/gl4/glIsProgram)
+pub type glIsProgram_t = Option<unsafe extern "system" fn(program: GLuint) -> GLboolean>;
+
 /// Links a program object.
 ///
 /// **See**: [`glLinkProgram` on docs.gl](https://docs.gl/gl4/glLinkProgram)
 pub type glLinkProgram_t = Option<unsafe extern "system" fn(program: GLuint)>;
 
+/// Defines a barrier ordering memory transactions.
+///
+/// **See**: [`glMemoryBarrier` on docs.gl](https://docs.gl/gl4/glMemoryBarrier)
+pub type glMemoryBarrier_t = Option<unsafe extern "system" fn(barriers: GLbitfield)>;
+
+/// Load a program object with a program binary.
+///
+/// **See**: [`glProgramBinary` on docs.gl](https://docs.gl/gl4/glProgramBinary)
+pub type glProgramBinary_t = Option<
+    unsafe extern "system" fn(
+        program: GLuint,
+        binaryFormat: GLenum,
+        binary: *const GLvoid,
+        length: GLsizei,
+    ),
+>;
+
+/// Specify a parameter for a program object.
+///
+/// **See**: [`glProgramParameter` on docs.gl](https://docs.gl/gl4/glProgramParameter)
+pub type glProgramParameteri_t =
+    Option<unsafe extern "system" fn(program: GLuint, pname: GLenum, value: GLint)>;
+
+/// Define the scissor box.
+///
+/// **See**: [`glScissor` on docs.gl](https://docs.gl/gl4/glScissor)
+pub type glScissor_t =
+    Option<unsafe extern "system" fn(x: GLint, y: GLint, width: GLsizei, height: GLsizei)>;
+
 /// Replaces the source code in a shader object.
 ///
 /// **See**: [`glShaderSource` on docs.gl](https://docs.gl/gl4/glShaderSource)
@@ -140,11 +375,59 @@ pub type glShaderSource_t = Option<
     ),
 >;
 
+/// Change an active shader storage block binding.
+///
+/// **See**: [`glShaderStorageBlockBinding` on docs.gl](https://docs.gl/gl4/glShaderStorageBlockBinding)
+pub type glShaderStorageBlockBinding_t = Option<
+    unsafe extern "system" fn(
+        program: GLuint,
+        storageBlockIndex: GLuint,
+        storageBlockBinding: GLuint,
+    ),
+>;
+
+/// Simultaneously specify storage for all levels of a two-dimensional or one-dimensional array texture.
+///
+/// **See**: [`glTexStorage2D` on docs.gl](https://docs.gl/gl4/glTexStorage2D)
+pub type glTexStorage2D_t = Option<
+    unsafe extern "system" fn(
+        target: GLenum,
+        levels: GLsizei,
+        internalformat: GLenum,
+        width: GLsizei,
+        height: GLsizei,
+    ),
+>;
+
+/// Assign a binding point to an active uniform block.
+///
+/// **See**: [`glUniformBlockBinding` on docs.gl](https://docs.gl/gl4/glUniformBlockBinding)
+pub type glUniformBlockBinding_t = Option<
+    unsafe extern "system" fn(
+        program: GLuint,
+        uniformBlockIndex: GLuint,
+        uniformBlockBinding: GLuint,
+    ),
+>;
+
 /// Installs a program object as part of current rendering state
 ///
 /// **See**: [`glUseProgram` on docs.gl](https://docs.gl/gl4/glUseProgram)
 pub type glUseProgram_t = Option<unsafe extern "system" fn(program: GLuint)>;
 
+/// Define an array of generic vertex attribute data that's read as integers
+///
+/// **See**: [`glVertexAttribPointer` on docs.gl](https://docs.gl/gl4/glVertexAttribPointer)
+pub type glVertexAttribIPointer_t = Option<
+    unsafe extern "system" fn(
+        index: GLuint,
+        size: GLint,
+        gltype: GLenum,
+        stride: GLsizei,
+        pointer: *const GLvoid,
+    ),
+>;
+
 /// Define an array of generic vertex attribute data
 ///
 /// **See**: [`glVertexAttribPointer` on docs.gl](https://docs.gl/gl4/glVertexAttribPointer)
@@ -158,3 +441,9 @@ pub type glVertexAttribPointer_t = Option<
         pointer: *const GLvoid,
     ),
 >;
+
+/// Set the viewport.
+///
+/// **See**: [`glViewport` on docs.gl](https://docs.gl/gl4/glViewport)
+pub type glViewport_t =
+    Option<unsafe extern "system" fn(x: GLint, y: GLint, width: GLsizei, height: GLsizei)>;
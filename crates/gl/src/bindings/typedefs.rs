@@ -104,7 +104,7 @@ use khrplatform_h::*;
 pub mod khrplatform_h {
     use c_types::*;
 
-    pub type khronos_int8_t = CChar;
+    pub type khronos_int8_t = CSChar;
     pub type khronos_int16_t = CShort;
     pub type khronos_int32_t = i32;
     pub type khronos_int64_t = i64;
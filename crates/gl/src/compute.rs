@@ -0,0 +1,758 @@
+//! Compute shaders, memory barriers, and image load/store.
+//!
+//! A [`ComputePipeline`] owns a linked compute program along with the local work group size it was
+//! built with. The local size is given in Rust rather than in the shader source, so it can be
+//! checked against the driver's `GL_MAX_COMPUTE_WORK_GROUP_*` limits _before_ the shader is
+//! compiled; some drivers give very unhelpful errors (or just crash) when those limits are
+//! exceeded.
+//!
+//! ```glsl
+//! #version 430 core
+//! // No `layout(local_size_x = ...) in;` here. ComputePipeline adds it for you.
+//! layout(std430, binding = 0) buffer Output { uint values[]; };
+//!
+//! void main() {
+//!     values[gl_GlobalInvocationID.x] = gl_GlobalInvocationID.x * 2u;
+//! }
+//! ```
+
+use std::{fmt, ops};
+
+use crate::{
+    bindings::prelude::*,
+    shader::{self, ShaderError},
+    GlContext,
+};
+
+/// The names of the three work group axes, for error messages.
+const AXES: [char; 3] = ['x', 'y', 'z'];
+
+/// A set of barrier bits for [`memory_barrier`].
+///
+/// Each bit orders shader writes made before the barrier against a particular _kind_ of read made
+/// after it. For example, use [`BarrierBits::SHADER_STORAGE`] between two dispatches that
+/// communicate through a shader storage buffer, or [`BarrierBits::BUFFER_UPDATE`] before reading
+/// that buffer back with `glGetBufferSubData`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(transparent)]
+pub struct BarrierBits(GLbitfield);
+
+impl BarrierBits {
+    pub const NONE: Self = Self(0);
+    pub const VERTEX_ATTRIB_ARRAY: Self = Self(GL_VERTEX_ATTRIB_ARRAY_BARRIER_BIT);
+    pub const ELEMENT_ARRAY: Self = Self(GL_ELEMENT_ARRAY_BARRIER_BIT);
+    pub const UNIFORM: Self = Self(GL_UNIFORM_BARRIER_BIT);
+    pub const TEXTURE_FETCH: Self = Self(GL_TEXTURE_FETCH_BARRIER_BIT);
+    pub const SHADER_IMAGE_ACCESS: Self = Self(GL_SHADER_IMAGE_ACCESS_BARRIER_BIT);
+    pub const COMMAND: Self = Self(GL_COMMAND_BARRIER_BIT);
+    pub const PIXEL_BUFFER: Self = Self(GL_PIXEL_BUFFER_BARRIER_BIT);
+    pub const TEXTURE_UPDATE: Self = Self(GL_TEXTURE_UPDATE_BARRIER_BIT);
+    pub const BUFFER_UPDATE: Self = Self(GL_BUFFER_UPDATE_BARRIER_BIT);
+    pub const FRAMEBUFFER: Self = Self(GL_FRAMEBUFFER_BARRIER_BIT);
+    pub const TRANSFORM_FEEDBACK: Self = Self(GL_TRANSFORM_FEEDBACK_BARRIER_BIT);
+    pub const ATOMIC_COUNTER: Self = Self(GL_ATOMIC_COUNTER_BARRIER_BIT);
+    pub const SHADER_STORAGE: Self = Self(GL_SHADER_STORAGE_BARRIER_BIT);
+    pub const CLIENT_MAPPED_BUFFER: Self = Self(GL_CLIENT_MAPPED_BUFFER_BARRIER_BIT);
+    pub const QUERY_BUFFER: Self = Self(GL_QUERY_BUFFER_BARRIER_BIT);
+    pub const ALL: Self = Self(GL_ALL_BARRIER_BITS);
+
+    /// The raw bitfield, as passed to `glMemoryBarrier`.
+    pub const fn bits(self) -> GLbitfield {
+        self.0
+    }
+
+    /// Returns `true` if every bit set in `other` is also set in `self`.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if no bits are set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl ops::BitOr for BarrierBits {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl ops::BitOrAssign for BarrierBits {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Inserts a memory barrier ordering shader writes before this call against the kinds of access
+/// described by `barriers` after it.
+///
+/// **See**: [`glMemoryBarrier` on docs.gl](https://docs.gl/gl4/glMemoryBarrier)
+///
+/// ## Safety
+///
+/// - `ctx` must be able to load correct OpenGL procedure addresses, and a context must be current.
+pub unsafe fn memory_barrier(ctx: &GlContext, barriers: BarrierBits) {
+    if !barriers.is_empty() {
+        ctx.gl_memory_barrier(barriers.bits());
+    }
+}
+
+/// How a shader is allowed to access an image bound with [`bind_image_texture`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageAccess {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+}
+
+impl ImageAccess {
+    pub const fn to_gl(self) -> GLenum {
+        match self {
+            Self::ReadOnly => GL_READ_ONLY,
+            Self::WriteOnly => GL_WRITE_ONLY,
+            Self::ReadWrite => GL_READ_WRITE,
+        }
+    }
+}
+
+/// Binds a level of a texture to an image unit, for use with `imageLoad`/`imageStore`.
+///
+/// - `layer` selects a single layer of an array, cube map, or 3D texture. Pass `None` to bind every
+///   layer at once.
+/// - `format` is the internal format the shader will see the image as, e.g. [`GL_RGBA32F`]. It
+///   must match the format qualifier used in the shader.
+///
+/// **See**: [`glBindImageTexture` on docs.gl](https://docs.gl/gl4/glBindImageTexture)
+///
+/// ## Safety
+///
+/// - `ctx` must be able to load correct OpenGL procedure addresses, and a context must be current.
+/// - `texture` must be zero or the name of an existing texture with immutable storage.
+pub unsafe fn bind_image_texture(
+    ctx: &GlContext,
+    unit: GLuint,
+    texture: GLuint,
+    level: GLint,
+    layer: Option<GLint>,
+    access: ImageAccess,
+    format: GLenum,
+) {
+    let (layered, layer) = match layer {
+        Some(layer) => (GL_FALSE, layer),
+        None => (GL_TRUE, 0),
+    };
+    ctx.gl_bind_image_texture(unit, texture, level, layered, layer, access.to_gl(), format);
+}
+
+/// The implementation-defined limits on compute work groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComputeLimits {
+    /// `GL_MAX_COMPUTE_WORK_GROUP_SIZE`: the largest local size along each axis.
+    pub max_local_size: [u32; 3],
+    /// `GL_MAX_COMPUTE_WORK_GROUP_INVOCATIONS`: the largest product of the local size's axes.
+    pub max_invocations: u32,
+    /// `GL_MAX_COMPUTE_WORK_GROUP_COUNT`: the largest number of work groups along each axis in a
+    /// single dispatch.
+    pub max_group_count: [u32; 3],
+}
+
+impl ComputeLimits {
+    /// The minimum limits that every OpenGL 4.3 implementation must support.
+    pub const MINIMUM: Self = Self {
+        max_local_size: [1024, 1024, 64],
+        max_invocations: 1024,
+        max_group_count: [65535, 65535, 65535],
+    };
+
+    /// Queries the limits of the current context.
+    ///
+    /// ## Safety
+    ///
+    /// - `ctx` must be able to load correct OpenGL procedure addresses, and a context supporting
+    ///   OpenGL 4.3 or `ARB_compute_shader` must be current.
+    pub unsafe fn query(ctx: &GlContext) -> Self {
+        let mut limits = Self::MINIMUM;
+
+        for axis in 0..3 {
+            let mut v: GLint = 0;
+            ctx.gl_get_integer_i_v(GL_MAX_COMPUTE_WORK_GROUP_SIZE, axis as GLuint, &mut v);
+            limits.max_local_size[axis] = v.max(0) as u32;
+
+            ctx.gl_get_integer_i_v(GL_MAX_COMPUTE_WORK_GROUP_COUNT, axis as GLuint, &mut v);
+            limits.max_group_count[axis] = v.max(0) as u32;
+        }
+
+        let mut v: GLint = 0;
+        ctx.gl_get_integer_v(GL_MAX_COMPUTE_WORK_GROUP_INVOCATIONS, &mut v);
+        limits.max_invocations = v.max(0) as u32;
+
+        limits
+    }
+
+    /// Checks that a local work group size is non-zero and fits within these limits.
+    pub fn validate_local_size(&self, local_size: [u32; 3]) -> Result<(), WorkGroupError> {
+        for (axis, (&size, &max)) in local_size.iter().zip(&self.max_local_size).enumerate() {
+            if size == 0 {
+                return Err(WorkGroupError::ZeroLocalSize { axis });
+            }
+            if size > max {
+                return Err(WorkGroupError::LocalSizeTooLarge { axis, size, max });
+            }
+        }
+
+        // Saturate rather than overflow; anything near u64::MAX is far over any real limit anyway.
+        let invocations = local_size
+            .iter()
+            .fold(1u64, |acc, &s| acc.saturating_mul(s as u64));
+        if invocations > self.max_invocations as u64 {
+            return Err(WorkGroupError::TooManyInvocations {
+                invocations,
+                max: self.max_invocations,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that a dispatch's work group count fits within these limits.
+    ///
+    /// Zero is allowed along any axis; such a dispatch simply does nothing.
+    pub fn validate_group_count(&self, group_count: [u32; 3]) -> Result<(), WorkGroupError> {
+        for (axis, (&count, &max)) in group_count.iter().zip(&self.max_group_count).enumerate() {
+            if count > max {
+                return Err(WorkGroupError::GroupCountTooLarge { axis, count, max });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The number of work groups of size `local_size` needed to cover `invocations` along each axis.
+pub fn group_count_for(invocations: [u32; 3], local_size: [u32; 3]) -> [u32; 3] {
+    [0, 1, 2].map(|axis| invocations[axis].div_ceil(local_size[axis].max(1)))
+}
+
+/// A work group size or count that the implementation can't handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkGroupError {
+    ZeroLocalSize { axis: usize },
+    LocalSizeTooLarge { axis: usize, size: u32, max: u32 },
+    TooManyInvocations { invocations: u64, max: u32 },
+    GroupCountTooLarge { axis: usize, count: u32, max: u32 },
+}
+
+impl fmt::Display for WorkGroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::ZeroLocalSize { axis } => {
+                write!(f, "local_size_{} must be at least 1", AXES[axis])
+            }
+            Self::LocalSizeTooLarge { axis, size, max } => write!(
+                f,
+                "local_size_{} is {size}, but GL_MAX_COMPUTE_WORK_GROUP_SIZE[{axis}] is {max}",
+                AXES[axis]
+            ),
+            Self::TooManyInvocations { invocations, max } => write!(
+                f,
+                "local work group has {invocations} invocations, \
+                 but GL_MAX_COMPUTE_WORK_GROUP_INVOCATIONS is {max}"
+            ),
+            Self::GroupCountTooLarge { axis, count, max } => write!(
+                f,
+                "dispatching {count} work groups along {}, \
+                 but GL_MAX_COMPUTE_WORK_GROUP_COUNT[{axis}] is {max}",
+                AXES[axis]
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WorkGroupError {}
+
+/// Anything that can go wrong while building a [`ComputePipeline`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComputeError {
+    /// The requested local size doesn't fit the implementation's limits.
+    WorkGroup(WorkGroupError),
+    /// The source already declares a local size with `layout(local_size_*) in;`.
+    LocalSizeInSource,
+    /// The source has no `#version` directive to insert the local size after.
+    MissingVersion,
+    Shader(ShaderError),
+}
+
+impl fmt::Display for ComputeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WorkGroup(e) => write!(f, "invalid work group size: {e}"),
+            Self::LocalSizeInSource => write!(
+                f,
+                "compute shader source declares its own local size; \
+                 pass it to ComputePipeline instead"
+            ),
+            Self::MissingVersion => write!(f, "compute shader source has no #version directive"),
+            Self::Shader(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ComputeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::WorkGroup(e) => Some(e),
+            Self::Shader(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<WorkGroupError> for ComputeError {
+    fn from(e: WorkGroupError) -> Self {
+        Self::WorkGroup(e)
+    }
+}
+
+impl From<ShaderError> for ComputeError {
+    fn from(e: ShaderError) -> Self {
+        Self::Shader(e)
+    }
+}
+
+/// Inserts a `layout(local_size_x = .., local_size_y = .., local_size_z = ..) in;` declaration
+/// into compute shader source, on the line after its `#version` directive.
+///
+/// A `#line` directive is added after the declaration so that line numbers in the driver's error
+/// messages still match the original source.
+pub fn insert_local_size(source: &str, local_size: [u32; 3]) -> Result<String, ComputeError> {
+    if declares_local_size(source) {
+        return Err(ComputeError::LocalSizeInSource);
+    }
+
+    let mut offset = 0;
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        offset += line.len();
+        if !line.trim_start().starts_with("#version") {
+            continue;
+        }
+
+        let [x, y, z] = local_size;
+        let mut out = String::with_capacity(source.len() + 96);
+        out.push_str(&source[..offset]);
+        if !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str(&format!(
+            "layout(local_size_x = {x}, local_size_y = {y}, local_size_z = {z}) in;\n"
+        ));
+        // `#line` gives the number of the _next_ line, and GLSL numbers lines from 1.
+        out.push_str(&format!("#line {}\n", index + 2));
+        out.push_str(&source[offset..]);
+        return Ok(out);
+    }
+
+    Err(ComputeError::MissingVersion)
+}
+
+/// Whether `source` has a `layout(local_size_x = ..) in;` declaration of its own. Comments and
+/// preprocessor directives are skipped, so mentioning `local_size_x` in either is fine.
+fn declares_local_size(source: &str) -> bool {
+    const QUALIFIERS: [&str; 6] = [
+        "local_size_x",
+        "local_size_y",
+        "local_size_z",
+        "local_size_x_id",
+        "local_size_y_id",
+        "local_size_z_id",
+    ];
+
+    let code = strip_comments(source);
+    let code: Vec<&str> = code
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .collect();
+    code.join("\n").split([';', '{', '}']).any(|statement| {
+        let Some(rest) = statement.trim_start().strip_prefix("layout") else {
+            return false;
+        };
+        let Some((qualifiers, rest)) = rest
+            .trim_start()
+            .strip_prefix('(')
+            .and_then(|rest| rest.split_once(')'))
+        else {
+            return false;
+        };
+        rest.trim() == "in"
+            && qualifiers.split(',').any(|qualifier| {
+                let name = qualifier.split('=').next().unwrap_or_default().trim();
+                QUALIFIERS.contains(&name)
+            })
+    })
+}
+
+/// Replaces `//` and `/* */` comments with spaces, keeping the newlines so that lines still line
+/// up.
+fn strip_comments(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                while chars.next_if(|&c| c != '\n').is_some() {}
+                out.push(' ');
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push('\n');
+                    }
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+                out.push(' ');
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// A linked compute program, along with the local work group size it was built with.
+///
+/// Pipelines don't delete their program on drop, since they don't hold on to a [`GlContext`]. Call
+/// [`ComputePipeline::delete`] when finished with one.
+#[derive(Debug)]
+pub struct ComputePipeline {
+    program: GLuint,
+    local_size: [u32; 3],
+    limits: ComputeLimits,
+}
+
+impl ComputePipeline {
+    /// Validates `local_size` against the current context's limits, then compiles and links
+    /// `source` with that local size.
+    ///
+    /// `source` must start with a `#version` directive and must _not_ declare its own local size;
+    /// see [`insert_local_size`].
+    ///
+    /// ## Safety
+    ///
+    /// - `ctx` must be able to load correct OpenGL procedure addresses, and a context supporting
+    ///   OpenGL 4.3 or `ARB_compute_shader` must be current.
+    pub unsafe fn new(
+        ctx: &GlContext,
+        source: &str,
+        local_size: [u32; 3],
+    ) -> Result<Self, ComputeError> {
+        Self::with_limits(ctx, source, local_size, ComputeLimits::query(ctx))
+    }
+
+    /// Like [`ComputePipeline::new`], but validates against already-known limits instead of
+    /// querying them again.
+    ///
+    /// ## Safety
+    ///
+    /// - Same as [`ComputePipeline::new`].
+    pub unsafe fn with_limits(
+        ctx: &GlContext,
+        source: &str,
+        local_size: [u32; 3],
+        limits: ComputeLimits,
+    ) -> Result<Self, ComputeError> {
+        limits.validate_local_size(local_size)?;
+        let source = insert_local_size(source, local_size)?;
+
+        let shader = shader::compile_shader(ctx, GL_COMPUTE_SHADER, &source)?;
+        let program = shader::link_program(ctx, &[shader]);
+        ctx.gl_delete_shader(shader);
+        let program = program?;
+
+        let mut linked_size: [GLint; 3] = [0; 3];
+        ctx.gl_get_program_iv(
+            program,
+            GL_COMPUTE_WORK_GROUP_SIZE,
+            linked_size.as_mut_ptr(),
+        );
+        debug_assert_eq!(
+            linked_size.map(|s| s as u32),
+            local_size,
+            "driver disagrees about the local size of a compute program"
+        );
+
+        Ok(Self {
+            program,
+            local_size,
+            limits,
+        })
+    }
+
+    /// The name of the underlying program object.
+    pub fn program(&self) -> GLuint {
+        self.program
+    }
+
+    /// The local work group size the program was built with.
+    pub fn local_size(&self) -> [u32; 3] {
+        self.local_size
+    }
+
+    /// The limits the local size was validated against.
+    pub fn limits(&self) -> &ComputeLimits {
+        &self.limits
+    }
+
+    /// Makes this pipeline's program current.
+    ///
+    /// ## Safety
+    ///
+    /// - `ctx` must be the context this pipeline was created with.
+    pub unsafe fn bind(&self, ctx: &GlContext) {
+        ctx.gl_use_program(self.program);
+    }
+
+    /// Binds this pipeline and dispatches `group_count` work groups.
+    ///
+    /// Returns an error without dispatching anything if `group_count` exceeds
+    /// `GL_MAX_COMPUTE_WORK_GROUP_COUNT`.
+    ///
+    /// ## Safety
+    ///
+    /// - `ctx` must be the context this pipeline was created with.
+    /// - Any buffers and images the shader uses must be bound and large enough for every
+    ///   invocation.
+    pub unsafe fn dispatch(
+        &self,
+        ctx: &GlContext,
+        group_count: [u32; 3],
+    ) -> Result<(), WorkGroupError> {
+        self.limits.validate_group_count(group_count)?;
+        self.bind(ctx);
+        ctx.gl_dispatch_compute(group_count[0], group_count[1], group_count[2]);
+        Ok(())
+    }
+
+    /// Dispatches enough work groups to cover at least `invocations` invocations along each axis.
+    ///
+    /// Shaders should bounds-check `gl_GlobalInvocationID` when `invocations` isn't a multiple of
+    /// the local size.
+    ///
+    /// ## Safety
+    ///
+    /// - Same as [`ComputePipeline::dispatch`].
+    pub unsafe fn dispatch_invocations(
+        &self,
+        ctx: &GlContext,
+        invocations: [u32; 3],
+    ) -> Result<(), WorkGroupError> {
+        self.dispatch(ctx, group_count_for(invocations, self.local_size))
+    }
+
+    /// Binds this pipeline and dispatches work groups using the three `GLuint` counts stored at
+    /// byte `offset` in the buffer bound to [`GL_DISPATCH_INDIRECT_BUFFER`].
+    ///
+    /// The counts aren't visible from the CPU, so they can't be validated here.
+    ///
+    /// ## Safety
+    ///
+    /// - Same as [`ComputePipeline::dispatch`].
+    /// - A buffer must be bound to [`GL_DISPATCH_INDIRECT_BUFFER`], `offset` must be a multiple of
+    ///   four, and the buffer must hold at least `offset + 12` bytes.
+    pub unsafe fn dispatch_indirect(&self, ctx: &GlContext, offset: usize) {
+        self.bind(ctx);
+        ctx.gl_dispatch_compute_indirect(offset as GLintptr);
+    }
+
+    /// Deletes the underlying program.
+    ///
+    /// ## Safety
+    ///
+    /// - `ctx` must be the context this pipeline was created with.
+    pub unsafe fn delete(self, ctx: &GlContext) {
+        ctx.gl_delete_program(self.program);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod barrier_bits {
+        use super::*;
+
+        #[test]
+        fn combines_bits() {
+            let bits = BarrierBits::SHADER_STORAGE | BarrierBits::BUFFER_UPDATE;
+            assert_eq!(
+                bits.bits(),
+                GL_SHADER_STORAGE_BARRIER_BIT | GL_BUFFER_UPDATE_BARRIER_BIT
+            );
+            assert!(bits.contains(BarrierBits::SHADER_STORAGE));
+            assert!(!bits.contains(BarrierBits::SHADER_IMAGE_ACCESS));
+            assert!(BarrierBits::ALL.contains(bits));
+            assert!(BarrierBits::NONE.is_empty());
+        }
+    }
+
+    mod limits {
+        use super::*;
+
+        const LIMITS: ComputeLimits = ComputeLimits::MINIMUM;
+
+        #[test]
+        fn accepts_sizes_within_limits() {
+            assert_eq!(LIMITS.validate_local_size([1, 1, 1]), Ok(()));
+            assert_eq!(LIMITS.validate_local_size([1024, 1, 1]), Ok(()));
+            assert_eq!(LIMITS.validate_local_size([16, 16, 4]), Ok(()));
+            assert_eq!(LIMITS.validate_local_size([1, 16, 64]), Ok(()));
+        }
+
+        #[test]
+        fn rejects_zero_sizes() {
+            assert_eq!(
+                LIMITS.validate_local_size([8, 0, 1]),
+                Err(WorkGroupError::ZeroLocalSize { axis: 1 })
+            );
+        }
+
+        #[test]
+        fn rejects_sizes_over_axis_limit() {
+            assert_eq!(
+                LIMITS.validate_local_size([1, 1, 65]),
+                Err(WorkGroupError::LocalSizeTooLarge {
+                    axis: 2,
+                    size: 65,
+                    max: 64
+                })
+            );
+        }
+
+        #[test]
+        fn rejects_too_many_invocations() {
+            // Every axis is fine on its own, but 32 * 32 * 2 = 2048.
+            assert_eq!(
+                LIMITS.validate_local_size([32, 32, 2]),
+                Err(WorkGroupError::TooManyInvocations {
+                    invocations: 2048,
+                    max: 1024
+                })
+            );
+        }
+
+        #[test]
+        fn invocation_count_does_not_overflow() {
+            let limits = ComputeLimits {
+                max_local_size: [u32::MAX; 3],
+                ..LIMITS
+            };
+            assert!(matches!(
+                limits.validate_local_size([u32::MAX; 3]),
+                Err(WorkGroupError::TooManyInvocations { .. })
+            ));
+        }
+
+        #[test]
+        fn validates_group_counts() {
+            assert_eq!(LIMITS.validate_group_count([0, 0, 0]), Ok(()));
+            assert_eq!(LIMITS.validate_group_count([65535, 1, 1]), Ok(()));
+            assert_eq!(
+                LIMITS.validate_group_count([1, 65536, 1]),
+                Err(WorkGroupError::GroupCountTooLarge {
+                    axis: 1,
+                    count: 65536,
+                    max: 65535
+                })
+            );
+        }
+
+        #[test]
+        fn computes_group_counts() {
+            assert_eq!(group_count_for([64, 1, 1], [64, 1, 1]), [1, 1, 1]);
+            assert_eq!(group_count_for([65, 1, 1], [64, 1, 1]), [2, 1, 1]);
+            assert_eq!(group_count_for([100, 30, 0], [8, 8, 1]), [13, 4, 0]);
+        }
+    }
+
+    mod insert_local_size {
+        use super::*;
+
+        #[test]
+        fn inserts_after_version() {
+            let source = "#version 430 core\nvoid main() {}\n";
+            assert_eq!(
+                insert_local_size(source, [8, 4, 1]).unwrap(),
+                "#version 430 core\n\
+                 layout(local_size_x = 8, local_size_y = 4, local_size_z = 1) in;\n\
+                 #line 2\n\
+                 void main() {}\n"
+            );
+        }
+
+        #[test]
+        fn keeps_line_numbers_after_leading_comments() {
+            let source = "// a comment\n\n#version 430\nvoid main() {}";
+            let out = insert_local_size(source, [1, 1, 1]).unwrap();
+            assert!(out.contains("#version 430\nlayout("));
+            assert!(out.contains("#line 4\nvoid main() {}"));
+        }
+
+        #[test]
+        fn handles_version_on_last_line() {
+            let out = insert_local_size("#version 430", [2, 2, 2]).unwrap();
+            assert!(out.starts_with("#version 430\nlayout(local_size_x = 2"));
+        }
+
+        #[test]
+        fn rejects_existing_local_size() {
+            let source = "#version 430\nlayout(local_size_x = 64) in;\nvoid main() {}";
+            assert_eq!(
+                insert_local_size(source, [1, 1, 1]),
+                Err(ComputeError::LocalSizeInSource)
+            );
+        }
+
+        #[test]
+        fn rejects_local_size_after_other_code() {
+            let source = "#version 430\nlayout(std430) buffer B { uint v[]; };\n\
+                          layout( local_size_y=2,local_size_x = 1 )\n  in ;";
+            assert_eq!(
+                insert_local_size(source, [1, 1, 1]),
+                Err(ComputeError::LocalSizeInSource)
+            );
+        }
+
+        #[test]
+        fn ignores_local_size_in_comments_and_names() {
+            // the example from the module's documentation
+            let source = "#version 430 core\n\
+                          // No `layout(local_size_x = ...) in;` here. ComputePipeline adds it for you.\n\
+                          layout(std430, binding = 0) buffer Output { uint values[]; };\n\
+                          \n\
+                          void main() {\n\
+                          \x20   values[gl_GlobalInvocationID.x] = gl_GlobalInvocationID.x * 2u;\n\
+                          }\n";
+            assert!(insert_local_size(source, [64, 1, 1]).is_ok());
+
+            let source = "#version 430\n\
+                          /* layout(local_size_x = 1)\n in; */\n\
+                          #define local_size_x 4\n\
+                          uniform uint local_size_x_times_two;\n\
+                          layout(location = 0) in vec4 v;\n\
+                          void main() {}";
+            assert!(insert_local_size(source, [1, 1, 1]).is_ok());
+        }
+
+        #[test]
+        fn rejects_missing_version() {
+            assert_eq!(
+                insert_local_size("void main() {}", [1, 1, 1]),
+                Err(ComputeError::MissingVersion)
+            );
+        }
+    }
+}
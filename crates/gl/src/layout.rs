@@ -0,0 +1,1022 @@
+//! `std140` and `std430` memory layouts for sharing data between Rust and GLSL interface blocks.
+//!
+//! GLSL uniform blocks and shader storage blocks lay out their members according to a set of
+//! alignment rules that look nothing like Rust's (or C's). For example, a `vec3` is aligned like a
+//! `vec4`, and under `std140` every array element is padded out to 16 bytes. Rather than
+//! hand-padding Rust structs to match, types implement [`GlslType`], which computes the offset of
+//! every member under a given set of [`LayoutRules`] and serializes values into correctly padded
+//! bytes.
+//!
+//! Use the [`interface_block!`](crate::interface_block) macro to declare a struct that mirrors a
+//! GLSL block:
+//!
+//! ```
+//! use triangle_from_scratch_gl::{interface_block, layout::*};
+//!
+//! interface_block! {
+//!     /// Mirrors `layout(std140) uniform Camera { mat4 view; vec3 position; float exposure; };`
+//!     pub struct Camera {
+//!         pub view: Mat4,
+//!         pub position: Vec3,
+//!         pub exposure: f32,
+//!     }
+//! }
+//!
+//! let offsets = member_offsets::<Camera>(LayoutRules::Std140);
+//! assert_eq!(offsets[1].offset, 64);
+//! assert_eq!(offsets[2].offset, 76);
+//! ```
+//!
+//! After linking a program, [`verify_uniform_block()`] and [`verify_storage_block()`] compare the
+//! computed offsets against the ones reported by the driver, so any disagreement between the Rust
+//! and GLSL declarations is caught immediately instead of showing up as garbage on screen.
+//!
+//! **See**: section 7.6.2.2, "Standard Uniform Block Layout", of the
+//! [OpenGL 4.6 core specification](https://registry.khronos.org/OpenGL/specs/gl/glspec46.core.pdf).
+
+use core::fmt;
+use std::ffi::CString;
+
+use crate::{bindings::prelude::*, GlContext};
+
+/// The memory layout rules used by a GLSL interface block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutRules {
+    /// `layout(std140)`. Usable with both uniform blocks and shader storage blocks.
+    Std140,
+    /// `layout(std430)`. Only usable with shader storage blocks.
+    Std430,
+}
+
+impl LayoutRules {
+    /// Rounds the base alignment of an array element or struct up as required by these rules.
+    ///
+    /// `std140` rounds up to the alignment of a `vec4`; `std430` leaves the alignment alone.
+    pub const fn round_aggregate_alignment(self, align: usize) -> usize {
+        match self {
+            Self::Std140 => round_up(align, 16),
+            Self::Std430 => align,
+        }
+    }
+}
+
+impl fmt::Display for LayoutRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Std140 => write!(f, "std140"),
+            Self::Std430 => write!(f, "std430"),
+        }
+    }
+}
+
+/// Rounds `offset` up to the next multiple of `align`.
+///
+/// `align` must be non-zero.
+pub const fn round_up(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align) * align
+}
+
+/// A named leaf member of an interface block, along with its byte offset from the start of the
+/// block.
+///
+/// Names use the same syntax as the names reported by `glGetActiveUniformName` and
+/// `glGetProgramResourceName`, e.g. `"lights[2].color"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberOffset {
+    pub name: String,
+    pub offset: usize,
+}
+
+/// A Rust type with a GLSL equivalent that can be stored in an interface block.
+pub trait GlslType {
+    /// `true` for structs, whose members are reported individually by the driver.
+    const IS_AGGREGATE: bool = false;
+
+    /// The base alignment of this type, in bytes.
+    fn align(rules: LayoutRules) -> usize;
+
+    /// The number of bytes this type occupies, including any trailing padding the rules require.
+    fn size(rules: LayoutRules) -> usize;
+
+    /// Serializes `self` into the start of `out`, which must be at least
+    /// [`size(rules)`](GlslType::size) bytes long.
+    fn write(&self, rules: LayoutRules, out: &mut [u8]);
+
+    /// Appends the name and offset of every leaf member of this type to `out`.
+    ///
+    /// `name` is the full name of this value within its block, and `offset` its offset in bytes.
+    fn members(rules: LayoutRules, name: &str, offset: usize, out: &mut Vec<MemberOffset>) {
+        let _ = rules;
+        out.push(MemberOffset {
+            name: name.to_string(),
+            offset,
+        });
+    }
+}
+
+/// Implements [`GlslType`] for 4-byte scalar types.
+macro_rules! impl_glsl_scalar {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl GlslType for $t {
+                fn align(_rules: LayoutRules) -> usize {
+                    4
+                }
+
+                fn size(_rules: LayoutRules) -> usize {
+                    4
+                }
+
+                fn write(&self, _rules: LayoutRules, out: &mut [u8]) {
+                    out[..4].copy_from_slice(&self.to_ne_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_glsl_scalar!(f32, i32, u32);
+
+/// GLSL `bool`s are four bytes wide.
+impl GlslType for bool {
+    fn align(_rules: LayoutRules) -> usize {
+        4
+    }
+
+    fn size(_rules: LayoutRules) -> usize {
+        4
+    }
+
+    fn write(&self, rules: LayoutRules, out: &mut [u8]) {
+        (*self as u32).write(rules, out);
+    }
+}
+
+/// Declares `#[repr(transparent)]` wrappers around arrays for GLSL vector types.
+macro_rules! glsl_vectors {
+    ($( $(#[$meta:meta])* $name:ident => [$t:ty; $n:literal] ),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            #[derive(Debug, Default, Clone, Copy, PartialEq)]
+            #[repr(transparent)]
+            pub struct $name(pub [$t; $n]);
+
+            impl GlslType for $name {
+                fn align(_rules: LayoutRules) -> usize {
+                    // A three-component vector is aligned like a four-component one.
+                    if $n == 2 { 8 } else { 16 }
+                }
+
+                fn size(_rules: LayoutRules) -> usize {
+                    4 * $n
+                }
+
+                fn write(&self, rules: LayoutRules, out: &mut [u8]) {
+                    for (i, component) in self.0.iter().enumerate() {
+                        component.write(rules, &mut out[4 * i..]);
+                    }
+                }
+            }
+
+            impl From<[$t; $n]> for $name {
+                fn from(v: [$t; $n]) -> Self {
+                    Self(v)
+                }
+            }
+        )*
+    };
+}
+
+glsl_vectors! {
+    /// A GLSL `vec2`.
+    Vec2 => [f32; 2],
+    /// A GLSL `vec3`.
+    Vec3 => [f32; 3],
+    /// A GLSL `vec4`.
+    Vec4 => [f32; 4],
+    /// A GLSL `ivec2`.
+    IVec2 => [i32; 2],
+    /// A GLSL `ivec3`.
+    IVec3 => [i32; 3],
+    /// A GLSL `ivec4`.
+    IVec4 => [i32; 4],
+    /// A GLSL `uvec2`.
+    UVec2 => [u32; 2],
+    /// A GLSL `uvec3`.
+    UVec3 => [u32; 3],
+    /// A GLSL `uvec4`.
+    UVec4 => [u32; 4],
+}
+
+/// Declares column-major GLSL matrix types.
+///
+/// A matrix is laid out exactly like an array of its column vectors, so that's how it's
+/// implemented. It still reports itself as a single member, since that's how the driver sees it.
+macro_rules! glsl_matrices {
+    ($( $(#[$meta:meta])* $name:ident => [$col:ident; $n:literal] ),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            #[derive(Debug, Default, Clone, Copy, PartialEq)]
+            #[repr(transparent)]
+            pub struct $name(pub [$col; $n]);
+
+            impl GlslType for $name {
+                fn align(rules: LayoutRules) -> usize {
+                    <[$col; $n]>::align(rules)
+                }
+
+                fn size(rules: LayoutRules) -> usize {
+                    <[$col; $n]>::size(rules)
+                }
+
+                fn write(&self, rules: LayoutRules, out: &mut [u8]) {
+                    self.0.write(rules, out);
+                }
+            }
+        )*
+    };
+}
+
+glsl_matrices! {
+    /// A column-major GLSL `mat2`.
+    Mat2 => [Vec2; 2],
+    /// A column-major GLSL `mat3`.
+    Mat3 => [Vec3; 3],
+    /// A column-major GLSL `mat4`.
+    Mat4 => [Vec4; 4],
+}
+
+/// Fixed-size GLSL arrays.
+impl<T: GlslType, const N: usize> GlslType for [T; N] {
+    const IS_AGGREGATE: bool = T::IS_AGGREGATE;
+
+    fn align(rules: LayoutRules) -> usize {
+        rules.round_aggregate_alignment(T::align(rules))
+    }
+
+    fn size(rules: LayoutRules) -> usize {
+        array_stride::<T>(rules) * N
+    }
+
+    fn write(&self, rules: LayoutRules, out: &mut [u8]) {
+        let stride = array_stride::<T>(rules);
+        for (i, element) in self.iter().enumerate() {
+            element.write(rules, &mut out[i * stride..]);
+        }
+    }
+
+    fn members(rules: LayoutRules, name: &str, offset: usize, out: &mut Vec<MemberOffset>) {
+        if T::IS_AGGREGATE {
+            // Arrays of structs are reported one member at a time, e.g. `lights[1].color`.
+            let stride = array_stride::<T>(rules);
+            for i in 0..N {
+                T::members(rules, &format!("{name}[{i}]"), offset + i * stride, out);
+            }
+        } else {
+            // Arrays of basic types are reported as a single member, at the offset of the first
+            // element.
+            out.push(MemberOffset {
+                name: name.to_string(),
+                offset,
+            });
+        }
+    }
+}
+
+/// The distance in bytes between consecutive elements of an array of `T`.
+pub fn array_stride<T: GlslType>(rules: LayoutRules) -> usize {
+    round_up(
+        T::size(rules),
+        rules.round_aggregate_alignment(T::align(rules)),
+    )
+}
+
+/// Declares a struct that can be shared with a GLSL interface block, and implements [`GlslType`]
+/// for it.
+///
+/// Fields are laid out in declaration order, just like members of a GLSL block or struct. Every
+/// field's type must implement [`GlslType`]. Structs declared with this macro can be nested inside
+/// each other, and inside arrays.
+///
+/// See the [module-level documentation](crate::layout) for an example.
+#[macro_export]
+macro_rules! interface_block {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $( $(#[$field_meta:meta])* $field_vis:vis $field:ident : $field_ty:ty ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $( $(#[$field_meta])* $field_vis $field : $field_ty ),*
+        }
+
+        impl $crate::layout::GlslType for $name {
+            const IS_AGGREGATE: bool = true;
+
+            fn align(rules: $crate::layout::LayoutRules) -> usize {
+                let mut align = 1;
+                $( align = align.max(<$field_ty as $crate::layout::GlslType>::align(rules)); )*
+                rules.round_aggregate_alignment(align)
+            }
+
+            fn size(rules: $crate::layout::LayoutRules) -> usize {
+                let mut offset = 0;
+                $(
+                    offset = $crate::layout::round_up(
+                        offset,
+                        <$field_ty as $crate::layout::GlslType>::align(rules),
+                    );
+                    offset += <$field_ty as $crate::layout::GlslType>::size(rules);
+                )*
+                $crate::layout::round_up(offset, <Self as $crate::layout::GlslType>::align(rules))
+            }
+
+            fn write(&self, rules: $crate::layout::LayoutRules, out: &mut [u8]) {
+                let mut offset = 0;
+                $(
+                    offset = $crate::layout::round_up(
+                        offset,
+                        <$field_ty as $crate::layout::GlslType>::align(rules),
+                    );
+                    $crate::layout::GlslType::write(&self.$field, rules, &mut out[offset..]);
+                    offset += <$field_ty as $crate::layout::GlslType>::size(rules);
+                )*
+                let _ = offset;
+            }
+
+            fn members(
+                rules: $crate::layout::LayoutRules,
+                name: &str,
+                base_offset: usize,
+                out: &mut Vec<$crate::layout::MemberOffset>,
+            ) {
+                let mut offset = 0;
+                $(
+                    offset = $crate::layout::round_up(
+                        offset,
+                        <$field_ty as $crate::layout::GlslType>::align(rules),
+                    );
+                    let field_name = if name.is_empty() {
+                        String::from(stringify!($field))
+                    } else {
+                        format!(concat!("{}.", stringify!($field)), name)
+                    };
+                    <$field_ty as $crate::layout::GlslType>::members(
+                        rules,
+                        &field_name,
+                        base_offset + offset,
+                        out,
+                    );
+                    offset += <$field_ty as $crate::layout::GlslType>::size(rules);
+                )*
+                let _ = offset;
+            }
+        }
+    };
+}
+
+/// Lists the name and offset of every leaf member of the block `T`.
+pub fn member_offsets<T: GlslType>(rules: LayoutRules) -> Vec<MemberOffset> {
+    let mut out = vec![];
+    T::members(rules, "", 0, &mut out);
+    out
+}
+
+/// Serializes `value` into a correctly padded buffer, ready to be uploaded with `glBufferData`.
+pub fn to_bytes<T: GlslType>(value: &T, rules: LayoutRules) -> Vec<u8> {
+    let mut out = vec![0; T::size(rules)];
+    value.write(rules, &mut out);
+    out
+}
+
+/// A single disagreement between the layout computed in Rust and the one reported by the driver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutProblem {
+    /// The driver doesn't know about a member that the Rust type declares.
+    MissingMember { name: String },
+    /// A member lives at a different offset than the Rust type expects.
+    OffsetMismatch {
+        name: String,
+        expected: usize,
+        actual: usize,
+    },
+    /// The block as a whole has a different size than the Rust type.
+    SizeMismatch { expected: usize, actual: usize },
+}
+
+impl fmt::Display for LayoutProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingMember { name } => write!(f, "member `{name}` is not active in the block"),
+            Self::OffsetMismatch {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "member `{name}` is at offset {actual}, but the Rust type expects {expected}"
+            ),
+            Self::SizeMismatch { expected, actual } => write!(
+                f,
+                "block is {actual} bytes, but the Rust type is {expected} bytes"
+            ),
+        }
+    }
+}
+
+/// Returned when an interface block's layout doesn't match the Rust type it's supposed to mirror.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    /// The program has no active block with the given name.
+    BlockNotFound { block: String },
+    /// The block exists, but its layout disagrees with the Rust type.
+    Mismatch {
+        block: String,
+        rules: LayoutRules,
+        problems: Vec<LayoutProblem>,
+    },
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BlockNotFound { block } => {
+                write!(
+                    f,
+                    "the program has no active interface block named `{block}`"
+                )
+            }
+
+            Self::Mismatch {
+                block,
+                rules,
+                problems,
+            } => {
+                write!(
+                    f,
+                    "interface block `{block}` doesn't match its {rules} Rust declaration:"
+                )?;
+                for problem in problems {
+                    write!(f, "\n  - {problem}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+/// Compares the offsets computed in Rust against the ones reported by the driver.
+///
+/// `actual` holds the driver's offset for each member of `expected`, or `None` if the driver
+/// didn't know about it.
+fn compare_layouts(
+    expected: &[MemberOffset],
+    actual: &[Option<usize>],
+    expected_size: usize,
+    actual_size: Option<usize>,
+) -> Vec<LayoutProblem> {
+    let mut problems = vec![];
+
+    for (member, actual) in expected.iter().zip(actual) {
+        match *actual {
+            None => problems.push(LayoutProblem::MissingMember {
+                name: member.name.clone(),
+            }),
+            Some(actual) if actual != member.offset => {
+                problems.push(LayoutProblem::OffsetMismatch {
+                    name: member.name.clone(),
+                    expected: member.offset,
+                    actual,
+                })
+            }
+            Some(_) => {}
+        }
+    }
+
+    match actual_size {
+        // Drivers are allowed to report a larger size for a block than strictly necessary, as
+        // long as all the members fit.
+        Some(actual) if actual < expected_size => problems.push(LayoutProblem::SizeMismatch {
+            expected: expected_size,
+            actual,
+        }),
+        _ => {}
+    }
+
+    problems
+}
+
+/// For a member of an element other than the first of a top-level array of structs, like
+/// `lights[2].color`, the name of the same member of the first element (`lights[0].color`), and
+/// the element's index.
+fn first_element_name(name: &str) -> Option<(String, usize)> {
+    let (array, rest) = name.split_once('[')?;
+    let (element, member) = rest.split_once(']')?;
+    if array.contains('.') || !member.starts_with('.') {
+        return None;
+    }
+    let element: usize = element.parse().ok()?;
+    (element > 0).then(|| (format!("{array}[0]{member}"), element))
+}
+
+/// Converts `name` into a C string. Names containing interior null bytes can't possibly be found in
+/// a program, so they're mapped to an empty string, which won't be found either.
+fn name_to_c_string(name: &str) -> CString {
+    CString::new(name).unwrap_or_default()
+}
+
+/// Checks that the uniform block `block_name` in `program` is laid out exactly like `T` under the
+/// `std140` rules.
+///
+/// Member offsets are queried with `glGetActiveUniformsiv(..., GL_UNIFORM_OFFSET, ...)`. Members
+/// are looked up by their bare names first, and then by `"{block_name}.{member}"`, so blocks with
+/// and without instance names are both supported.
+///
+/// ## Safety
+///
+/// - `program` must be a successfully linked program object.
+/// - If `ctx`'s GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+///   will occur.
+pub unsafe fn verify_uniform_block<T: GlslType>(
+    ctx: &GlContext,
+    program: GLuint,
+    block_name: &str,
+) -> Result<(), LayoutError> {
+    let block_name_c = name_to_c_string(block_name);
+    let block_index = ctx.gl_get_uniform_block_index(program, block_name_c.as_ptr());
+    if block_index == GL_INVALID_INDEX {
+        return Err(LayoutError::BlockNotFound {
+            block: block_name.to_string(),
+        });
+    }
+
+    let expected = member_offsets::<T>(LayoutRules::Std140);
+
+    let lookup = |names: Vec<CString>| -> Vec<GLuint> {
+        let ptrs: Vec<*const GLchar> = names.iter().map(|n| n.as_ptr()).collect();
+        let mut indices = vec![GL_INVALID_INDEX; ptrs.len()];
+        ctx.gl_get_uniform_indices(
+            program,
+            ptrs.len() as _,
+            ptrs.as_ptr(),
+            indices.as_mut_ptr(),
+        );
+        indices
+    };
+
+    let mut indices = lookup(expected.iter().map(|m| name_to_c_string(&m.name)).collect());
+    let prefixed = lookup(
+        expected
+            .iter()
+            .map(|m| name_to_c_string(&format!("{block_name}.{}", m.name)))
+            .collect(),
+    );
+    for (index, prefixed) in indices.iter_mut().zip(prefixed) {
+        if *index == GL_INVALID_INDEX {
+            *index = prefixed;
+        }
+    }
+
+    let mut actual = vec![None; expected.len()];
+    for (actual, &index) in actual.iter_mut().zip(&indices) {
+        if index == GL_INVALID_INDEX {
+            continue;
+        }
+
+        // Make sure the uniform we found actually belongs to this block, and not to some other
+        // block with an identically named member.
+        let mut owner: GLint = -1;
+        ctx.gl_get_active_uniforms_iv(program, 1, &index, GL_UNIFORM_BLOCK_INDEX, &mut owner);
+        if owner != block_index as GLint {
+            continue;
+        }
+
+        let mut offset: GLint = -1;
+        ctx.gl_get_active_uniforms_iv(program, 1, &index, GL_UNIFORM_OFFSET, &mut offset);
+        *actual = usize::try_from(offset).ok();
+    }
+
+    let mut size: GLint = -1;
+    ctx.gl_get_active_uniform_block_iv(program, block_index, GL_UNIFORM_BLOCK_DATA_SIZE, &mut size);
+
+    let problems = compare_layouts(
+        &expected,
+        &actual,
+        T::size(LayoutRules::Std140),
+        usize::try_from(size).ok(),
+    );
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(LayoutError::Mismatch {
+            block: block_name.to_string(),
+            rules: LayoutRules::Std140,
+            problems,
+        })
+    }
+}
+
+/// Checks that the shader storage block `block_name` in `program` is laid out exactly like `T`
+/// under the given rules.
+///
+/// Shader storage block members aren't uniforms, so their offsets are queried through the program
+/// interface API (`glGetProgramResourceiv(..., GL_BUFFER_VARIABLE, ..., GL_OFFSET, ...)`) instead.
+///
+/// ## Safety
+///
+/// - `program` must be a successfully linked program object.
+/// - If `ctx`'s GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+///   will occur.
+pub unsafe fn verify_storage_block<T: GlslType>(
+    ctx: &GlContext,
+    program: GLuint,
+    block_name: &str,
+    rules: LayoutRules,
+) -> Result<(), LayoutError> {
+    let block_name_c = name_to_c_string(block_name);
+    let block_index =
+        ctx.gl_get_program_resource_index(program, GL_SHADER_STORAGE_BLOCK, block_name_c.as_ptr());
+    if block_index == GL_INVALID_INDEX {
+        return Err(LayoutError::BlockNotFound {
+            block: block_name.to_string(),
+        });
+    }
+
+    let get_resource_property = |interface: GLenum, index: GLuint, property: GLenum| {
+        let mut value: GLint = -1;
+        ctx.gl_get_program_resource_iv(
+            program,
+            interface,
+            index,
+            1,
+            &property,
+            1,
+            core::ptr::null_mut(),
+            &mut value,
+        );
+        usize::try_from(value).ok()
+    };
+
+    let find = |name: &str| {
+        let bare = name_to_c_string(name);
+        let index = ctx.gl_get_program_resource_index(program, GL_BUFFER_VARIABLE, bare.as_ptr());
+        if index != GL_INVALID_INDEX {
+            return index;
+        }
+        let prefixed = name_to_c_string(&format!("{block_name}.{name}"));
+        ctx.gl_get_program_resource_index(program, GL_BUFFER_VARIABLE, prefixed.as_ptr())
+    };
+
+    let expected = member_offsets::<T>(rules);
+    let actual: Vec<Option<usize>> = expected
+        .iter()
+        .map(|member| {
+            let index = find(&member.name);
+            if index != GL_INVALID_INDEX {
+                return get_resource_property(GL_BUFFER_VARIABLE, index, GL_OFFSET);
+            }
+
+            // Only the first element of a top-level array of structs is enumerated (e.g.
+            // `lights[0].color`, but not `lights[1].color`), so the others are found from the
+            // first one's offset and the array's stride.
+            let (first, element) = first_element_name(&member.name)?;
+            let index = find(&first);
+            if index == GL_INVALID_INDEX {
+                return None;
+            }
+            let offset = get_resource_property(GL_BUFFER_VARIABLE, index, GL_OFFSET)?;
+            let stride =
+                get_resource_property(GL_BUFFER_VARIABLE, index, GL_TOP_LEVEL_ARRAY_STRIDE)?;
+            Some(offset + element * stride)
+        })
+        .collect();
+
+    let size = get_resource_property(GL_SHADER_STORAGE_BLOCK, block_index, GL_BUFFER_DATA_SIZE);
+
+    let problems = compare_layouts(&expected, &actual, T::size(rules), size);
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(LayoutError::Mismatch {
+            block: block_name.to_string(),
+            rules,
+            problems,
+        })
+    }
+}
+
+/// Assigns the uniform block `block_name` in `program` to the indexed `GL_UNIFORM_BUFFER` binding
+/// point `binding`. Bind a buffer to the same binding point with `glBindBufferBase` or
+/// `glBindBufferRange` to supply the block's data.
+///
+/// ## Safety
+///
+/// - `program` must be a successfully linked program object.
+/// - If `ctx`'s GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+///   will occur.
+pub unsafe fn bind_uniform_block(
+    ctx: &GlContext,
+    program: GLuint,
+    block_name: &str,
+    binding: GLuint,
+) -> Result<(), LayoutError> {
+    let block_name_c = name_to_c_string(block_name);
+    let block_index = ctx.gl_get_uniform_block_index(program, block_name_c.as_ptr());
+    if block_index == GL_INVALID_INDEX {
+        return Err(LayoutError::BlockNotFound {
+            block: block_name.to_string(),
+        });
+    }
+
+    ctx.gl_uniform_block_binding(program, block_index, binding);
+    Ok(())
+}
+
+/// Assigns the shader storage block `block_name` in `program` to the indexed
+/// `GL_SHADER_STORAGE_BUFFER` binding point `binding`.
+///
+/// ## Safety
+///
+/// - `program` must be a successfully linked program object.
+/// - If `ctx`'s GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+///   will occur.
+pub unsafe fn bind_storage_block(
+    ctx: &GlContext,
+    program: GLuint,
+    block_name: &str,
+    binding: GLuint,
+) -> Result<(), LayoutError> {
+    let block_name_c = name_to_c_string(block_name);
+    let block_index =
+        ctx.gl_get_program_resource_index(program, GL_SHADER_STORAGE_BLOCK, block_name_c.as_ptr());
+    if block_index == GL_INVALID_INDEX {
+        return Err(LayoutError::BlockNotFound {
+            block: block_name.to_string(),
+        });
+    }
+
+    ctx.gl_shader_storage_block_binding(program, block_index, binding);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offsets<T: GlslType>(rules: LayoutRules) -> Vec<(String, usize)> {
+        member_offsets::<T>(rules)
+            .into_iter()
+            .map(|m| (m.name, m.offset))
+            .collect()
+    }
+
+    fn owned(expected: &[(&str, usize)]) -> Vec<(String, usize)> {
+        expected.iter().map(|(n, o)| (n.to_string(), *o)).collect()
+    }
+
+    interface_block! {
+        struct ScalarThenVec3 {
+            a: f32,
+            b: Vec3,
+        }
+    }
+
+    interface_block! {
+        struct Vec3ThenScalar {
+            a: Vec3,
+            b: f32,
+        }
+    }
+
+    interface_block! {
+        struct ScalarArray {
+            a: f32,
+            array: [f32; 3],
+            c: f32,
+        }
+    }
+
+    interface_block! {
+        struct MatrixThenVec2 {
+            m: Mat3,
+            v: Vec2,
+        }
+    }
+
+    interface_block! {
+        struct Inner {
+            x: f32,
+        }
+    }
+
+    interface_block! {
+        struct Outer {
+            a: f32,
+            inner: Inner,
+            b: f32,
+            inners: [Inner; 2],
+        }
+    }
+
+    mod std140 {
+        use super::*;
+
+        #[test]
+        fn vec3_is_aligned_like_vec4() {
+            assert_eq!(
+                offsets::<ScalarThenVec3>(LayoutRules::Std140),
+                owned(&[("a", 0), ("b", 16)])
+            );
+            assert_eq!(ScalarThenVec3::size(LayoutRules::Std140), 32);
+        }
+
+        #[test]
+        fn scalar_can_follow_vec3_directly() {
+            assert_eq!(
+                offsets::<Vec3ThenScalar>(LayoutRules::Std140),
+                owned(&[("a", 0), ("b", 12)])
+            );
+            assert_eq!(Vec3ThenScalar::size(LayoutRules::Std140), 16);
+        }
+
+        #[test]
+        fn array_elements_are_padded_to_vec4() {
+            assert_eq!(
+                offsets::<ScalarArray>(LayoutRules::Std140),
+                owned(&[("a", 0), ("array", 16), ("c", 64)])
+            );
+            assert_eq!(array_stride::<Vec2>(LayoutRules::Std140), 16);
+        }
+
+        #[test]
+        fn matrices_are_arrays_of_columns() {
+            assert_eq!(
+                offsets::<MatrixThenVec2>(LayoutRules::Std140),
+                owned(&[("m", 0), ("v", 48)])
+            );
+            assert_eq!(Mat4::size(LayoutRules::Std140), 64);
+            assert_eq!(Mat2::size(LayoutRules::Std140), 32);
+        }
+
+        #[test]
+        fn nested_structs_are_aligned_to_vec4() {
+            assert_eq!(
+                offsets::<Outer>(LayoutRules::Std140),
+                owned(&[
+                    ("a", 0),
+                    ("inner.x", 16),
+                    ("b", 32),
+                    ("inners[0].x", 48),
+                    ("inners[1].x", 64),
+                ])
+            );
+            assert_eq!(Outer::size(LayoutRules::Std140), 80);
+        }
+    }
+
+    mod std430 {
+        use super::*;
+
+        #[test]
+        fn vec3_is_still_aligned_like_vec4() {
+            assert_eq!(
+                offsets::<ScalarThenVec3>(LayoutRules::Std430),
+                owned(&[("a", 0), ("b", 16)])
+            );
+        }
+
+        #[test]
+        fn scalar_arrays_are_tightly_packed() {
+            assert_eq!(
+                offsets::<ScalarArray>(LayoutRules::Std430),
+                owned(&[("a", 0), ("array", 4), ("c", 16)])
+            );
+            assert_eq!(array_stride::<Vec2>(LayoutRules::Std430), 8);
+            assert_eq!(Mat2::size(LayoutRules::Std430), 16);
+        }
+
+        #[test]
+        fn nested_structs_use_their_natural_alignment() {
+            assert_eq!(
+                offsets::<Outer>(LayoutRules::Std430),
+                owned(&[
+                    ("a", 0),
+                    ("inner.x", 4),
+                    ("b", 8),
+                    ("inners[0].x", 12),
+                    ("inners[1].x", 16),
+                ])
+            );
+            assert_eq!(Outer::size(LayoutRules::Std430), 20);
+        }
+    }
+
+    mod to_bytes {
+        use super::*;
+
+        fn f32_at(bytes: &[u8], offset: usize) -> f32 {
+            f32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+        }
+
+        #[test]
+        fn writes_members_at_their_offsets() {
+            let value = ScalarArray {
+                a: 1.0,
+                array: [2.0, 3.0, 4.0],
+                c: 5.0,
+            };
+
+            let bytes = to_bytes(&value, LayoutRules::Std140);
+            assert_eq!(bytes.len(), 80);
+            for (offset, expected) in [(0, 1.0), (16, 2.0), (32, 3.0), (48, 4.0), (64, 5.0)] {
+                assert_eq!(f32_at(&bytes, offset), expected);
+            }
+
+            let bytes = to_bytes(&value, LayoutRules::Std430);
+            assert_eq!(bytes.len(), 20);
+            for (i, expected) in [1.0, 2.0, 3.0, 4.0, 5.0].into_iter().enumerate() {
+                assert_eq!(f32_at(&bytes, 4 * i), expected);
+            }
+        }
+
+        #[test]
+        fn padding_is_zeroed() {
+            let value = ScalarThenVec3 {
+                a: 1.0,
+                b: Vec3([2.0, 3.0, 4.0]),
+            };
+            let bytes = to_bytes(&value, LayoutRules::Std140);
+            assert!(bytes[4..16].iter().all(|&b| b == 0));
+            assert!(bytes[28..32].iter().all(|&b| b == 0));
+        }
+    }
+
+    mod compare_layouts {
+        use super::*;
+
+        fn expected() -> Vec<MemberOffset> {
+            member_offsets::<ScalarThenVec3>(LayoutRules::Std140)
+        }
+
+        #[test]
+        fn matching_layouts_have_no_problems() {
+            assert!(compare_layouts(&expected(), &[Some(0), Some(16)], 32, Some(32)).is_empty());
+        }
+
+        #[test]
+        fn reports_every_problem() {
+            let problems = compare_layouts(&expected(), &[None, Some(4)], 32, Some(16));
+            assert_eq!(
+                problems,
+                vec![
+                    LayoutProblem::MissingMember { name: "a".into() },
+                    LayoutProblem::OffsetMismatch {
+                        name: "b".into(),
+                        expected: 16,
+                        actual: 4,
+                    },
+                    LayoutProblem::SizeMismatch {
+                        expected: 32,
+                        actual: 16,
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn error_message_lists_problems() {
+            let error = LayoutError::Mismatch {
+                block: "Camera".into(),
+                rules: LayoutRules::Std140,
+                problems: compare_layouts(&expected(), &[Some(0), Some(12)], 32, None),
+            };
+            assert_eq!(
+                error.to_string(),
+                "interface block `Camera` doesn't match its std140 Rust declaration:\n  \
+                 - member `b` is at offset 12, but the Rust type expects 16"
+            );
+        }
+    }
+
+    #[test]
+    fn first_element_names() {
+        assert_eq!(
+            first_element_name("lights[2].color"),
+            Some(("lights[0].color".to_string(), 2))
+        );
+        assert_eq!(
+            first_element_name("lights[1].shadow.bias"),
+            Some(("lights[0].shadow.bias".to_string(), 1))
+        );
+        assert_eq!(first_element_name("lights[0].color"), None);
+        assert_eq!(first_element_name("offsets[2]"), None);
+        assert_eq!(first_element_name("light.colors[1].r"), None);
+        assert_eq!(first_element_name("exposure"), None);
+    }
+}
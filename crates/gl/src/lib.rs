@@ -1,19 +1,31 @@
 //! Bindings and utility functions for working with OpenGL.
 
 pub mod bindings;
+pub mod compute;
+pub mod layout;
+pub mod program_cache;
+pub mod program_manager;
+pub mod shader;
+pub mod surface;
+pub mod vertex;
 
 use bindings::prelude::*;
 
+#[doc(hidden)]
+pub use c_types as __c_types;
+
 use core::cell::RefCell;
 
 /// Convert a UTF-8 rust string literal into a null-terminated `&[u8]`.
 ///
+/// Literals containing a null are rejected at compile time, like [`c_types::c_str!`].
+///
 /// **Note**: This macro can only be passed string _literals_ (not variables or constants!) due to
 /// its internal use of [`concat`] to add a null byte to the end of the string.
 #[macro_export]
 macro_rules! c_str {
     ($text:expr) => {{
-        concat!($text, '\0').as_bytes()
+        $crate::__c_types::c_str!($text).to_bytes_with_null()
     }};
 }
 
@@ -50,6 +62,10 @@ pub struct GlContext {
 struct GlProcs {
     gl_attach_shader: RefCell<glAttachShader_t>,
     gl_bind_buffer: RefCell<glBindBuffer_t>,
+    gl_bind_buffer_base: RefCell<glBindBufferBase_t>,
+    gl_bind_buffer_range: RefCell<glBindBufferRange_t>,
+    gl_bind_image_texture: RefCell<glBindImageTexture_t>,
+    gl_bind_texture: RefCell<glBindTexture_t>,
     gl_bind_vertex_array: RefCell<glBindVertexArray_t>,
     gl_buffer_data: RefCell<glBufferData_t>,
     gl_clear: RefCell<glClear_t>,
@@ -57,20 +73,49 @@ struct GlProcs {
     gl_compile_shader: RefCell<glCompileShader_t>,
     gl_create_program: RefCell<glCreateProgram_t>,
     gl_create_shader: RefCell<glCreateShader_t>,
+    gl_delete_buffers: RefCell<glDeleteBuffers_t>,
+    gl_delete_program: RefCell<glDeleteProgram_t>,
     gl_delete_shader: RefCell<glDeleteShader_t>,
+    gl_delete_textures: RefCell<glDeleteTextures_t>,
+    gl_dispatch_compute: RefCell<glDispatchCompute_t>,
+    gl_dispatch_compute_indirect: RefCell<glDispatchComputeIndirect_t>,
     gl_draw_arrays: RefCell<glDrawArrays_t>,
     gl_draw_elements: RefCell<glDrawElements_t>,
     gl_enable_vertex_attrib_array: RefCell<glEnableVertexAttribArray_t>,
     gl_gen_buffers: RefCell<glGenBuffers_t>,
+    gl_gen_textures: RefCell<glGenTextures_t>,
     gl_gen_vertex_arrays: RefCell<glGenVertexArrays_t>,
+    gl_get_active_uniform_block_iv: RefCell<glGetActiveUniformBlockiv_t>,
+    gl_get_active_uniforms_iv: RefCell<glGetActiveUniformsiv_t>,
+    gl_get_buffer_sub_data: RefCell<glGetBufferSubData_t>,
+    gl_get_error: RefCell<glGetError_t>,
+    gl_get_integer_i_v: RefCell<glGetIntegeri_v_t>,
+    gl_get_integer_v: RefCell<glGetIntegerv_t>,
+    gl_get_program_binary: RefCell<glGetProgramBinary_t>,
     gl_get_program_info_log: RefCell<glGetProgramInfoLog_t>,
     gl_get_program_iv: RefCell<glGetProgramiv_t>,
+    gl_get_program_resource_index: RefCell<glGetProgramResourceIndex_t>,
+    gl_get_program_resource_iv: RefCell<glGetProgramResourceiv_t>,
     gl_get_shader_info_log: RefCell<glGetShaderInfoLog_t>,
     gl_get_shader_iv: RefCell<glGetShaderiv_t>,
+    gl_get_string: RefCell<glGetString_t>,
+    gl_get_tex_image: RefCell<glGetTexImage_t>,
+    gl_get_uniform_block_index: RefCell<glGetUniformBlockIndex_t>,
+    gl_get_uniform_indices: RefCell<glGetUniformIndices_t>,
+    gl_is_program: RefCell<glIsProgram_t>,
     gl_link_program: RefCell<glLinkProgram_t>,
+    gl_memory_barrier: RefCell<glMemoryBarrier_t>,
+    gl_program_binary: RefCell<glProgramBinary_t>,
+    gl_program_parameter_i: RefCell<glProgramParameteri_t>,
+    gl_scissor: RefCell<glScissor_t>,
     gl_shader_source: RefCell<glShaderSource_t>,
+    gl_shader_storage_block_binding: RefCell<glShaderStorageBlockBinding_t>,
+    gl_tex_storage_2d: RefCell<glTexStorage2D_t>,
+    gl_uniform_block_binding: RefCell<glUniformBlockBinding_t>,
     gl_use_program: RefCell<glUseProgram_t>,
+    gl_vertex_attrib_i_pointer: RefCell<glVertexAttribIPointer_t>,
     gl_vertex_attrib_pointer: RefCell<glVertexAttribPointer_t>,
+    gl_viewport: RefCell<glViewport_t>,
 }
 
 /// This macro is used in the implementation of [`GlContext`] to cut down on
@@ -86,6 +131,7 @@ macro_rules! impl_glcontext_proc_call {
         $($tail:tt)*
     ) => {
         $( #[$meta] )*
+        #[allow(clippy::too_many_arguments)]
         pub unsafe fn $name ( &self, $( $arg_name : $arg_ty ),* ) $( -> $ret_ty )? {
             let mut cell = self.gl_procs.$name.borrow_mut();
 
@@ -96,7 +142,10 @@ macro_rules! impl_glcontext_proc_call {
                 println!(concat!("OpenGL function `", stringify!($glDllName), "` has not yet been loaded! Loading."));
 
                 // Load the procedure's address into the refcell
-                *cell = core::mem::transmute(
+                *cell = core::mem::transmute::<
+                    *mut core::ffi::c_void,
+                    Option<unsafe extern "system" fn($( $arg_ty ),*) $( -> $ret_ty )?>,
+                >(
                     self.loader
                         .as_ref()
                         .expect(
@@ -157,6 +206,64 @@ impl GlContext {
         ///   will occur.
         glBindBuffer => unsafe fn gl_bind_buffer(target: GLenum, buffer: GLuint);
 
+        /// Bind a buffer object to an indexed buffer target.
+        ///
+        /// **See**: [`glBindBufferBase` on docs.gl](https://docs.gl/gl4/glBindBufferBase)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glBindBufferBase => unsafe fn gl_bind_buffer_base(
+            target: GLenum,
+            index: GLuint,
+            buffer: GLuint,
+        );
+
+        /// Bind a range within a buffer object to an indexed buffer target.
+        ///
+        /// **See**: [`glBindBufferRange` on docs.gl](https://docs.gl/gl4/glBindBufferRange)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glBindBufferRange => unsafe fn gl_bind_buffer_range(
+            target: GLenum,
+            index: GLuint,
+            buffer: GLuint,
+            offset: GLintptr,
+            size: GLsizeiptr,
+        );
+
+        /// Bind a level of a texture to an image unit.
+        ///
+        /// **See**: [`glBindImageTexture` on docs.gl](https://docs.gl/gl4/glBindImageTexture)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glBindImageTexture => unsafe fn gl_bind_image_texture(
+            unit: GLuint,
+            texture: GLuint,
+            level: GLint,
+            layered: GLboolean,
+            layer: GLint,
+            access: GLenum,
+            format: GLenum,
+        );
+
+        /// Bind a named texture to a texturing target.
+        ///
+        /// **See**: [`glBindTexture` on docs.gl](https://docs.gl/gl4/glBindTexture)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glBindTexture => unsafe fn gl_bind_texture(target: GLenum, texture: GLuint);
+
         /// Bind a vertex array object.
         ///
         /// **See**: [`glBindVertexArray` on docs.gl](https://docs.gl/gl4/glBindVertexArray)
@@ -232,6 +339,26 @@ impl GlContext {
         ///   will occur.
         glCreateShader => unsafe fn gl_create_shader(shader_type: GLenum) -> GLuint;
 
+        /// Delete named buffer objects.
+        ///
+        /// **See**: [`glDeleteBuffers` on docs.gl](https://docs.gl/gl4/glDeleteBuffers)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glDeleteBuffers => unsafe fn gl_delete_buffers(n: GLsizei, buffers: *const GLuint);
+
+        /// Deletes a program object.
+        ///
+        /// **See**: [`glDeleteProgram` on docs.gl](https://docs.gl/gl4/glDeleteProgram)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glDeleteProgram => unsafe fn gl_delete_program(program: GLuint);
+
         /// Deletes a shader object
         ///
         /// **See**: [`glDeleteShader` on docs.gl](https://docs.gl/gl4/glDeleteShader)
@@ -242,6 +369,40 @@ impl GlContext {
         ///   will occur.
         glDeleteShader => unsafe fn gl_delete_shader(shader: GLuint);
 
+        /// Delete named textures.
+        ///
+        /// **See**: [`glDeleteTextures` on docs.gl](https://docs.gl/gl4/glDeleteTextures)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glDeleteTextures => unsafe fn gl_delete_textures(n: GLsizei, textures: *const GLuint);
+
+        /// Launch one or more compute work groups.
+        ///
+        /// **See**: [`glDispatchCompute` on docs.gl](https://docs.gl/gl4/glDispatchCompute)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glDispatchCompute => unsafe fn gl_dispatch_compute(
+            num_groups_x: GLuint,
+            num_groups_y: GLuint,
+            num_groups_z: GLuint,
+        );
+
+        /// Launch one or more compute work groups using parameters stored in a buffer.
+        ///
+        /// **See**: [`glDispatchComputeIndirect` on docs.gl](https://docs.gl/gl4/glDispatchComputeIndirect)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glDispatchComputeIndirect => unsafe fn gl_dispatch_compute_indirect(indirect: GLintptr);
+
         /// Render primitives from array data
         ///
         /// **See**: [`glDrawArrays` on docs.gl](https://docs.gl/gl4/glDrawArrays)
@@ -287,6 +448,16 @@ impl GlContext {
         ///   will occur.
         glGenBuffers => unsafe fn gl_gen_buffers(n: GLsizei, buffers: *mut GLuint);
 
+        /// Generate texture names.
+        ///
+        /// **See**: [`glGenTextures` on docs.gl](https://docs.gl/gl4/glGenTextures)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glGenTextures => unsafe fn gl_gen_textures(n: GLsizei, textures: *mut GLuint);
+
         /// Generate vertex array object names
         ///
         /// **See**: [`glGenVertexArrays` on docs.gl](https://docs.gl/gl4/glGenVertexArrays)
@@ -297,6 +468,102 @@ impl GlContext {
         ///   will occur.
         glGenVertexArrays => unsafe fn gl_gen_vertex_arrays(n: GLsizei, arrays: *mut GLuint);
 
+        /// Query information about an active uniform block.
+        ///
+        /// **See**: [`glGetActiveUniformBlock` on docs.gl](https://docs.gl/gl4/glGetActiveUniformBlock)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glGetActiveUniformBlockiv => unsafe fn gl_get_active_uniform_block_iv(
+            program: GLuint,
+            uniform_block_index: GLuint,
+            pname: GLenum,
+            params: *mut GLint,
+        );
+
+        /// Returns information about several active uniform variables for the specified program object.
+        ///
+        /// **See**: [`glGetActiveUniformsiv` on docs.gl](https://docs.gl/gl4/glGetActiveUniformsiv)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glGetActiveUniformsiv => unsafe fn gl_get_active_uniforms_iv(
+            program: GLuint,
+            uniform_count: GLsizei,
+            uniform_indices: *const GLuint,
+            pname: GLenum,
+            params: *mut GLint,
+        );
+
+        /// Returns a subset of a buffer object's data store.
+        ///
+        /// **See**: [`glGetBufferSubData` on docs.gl](https://docs.gl/gl4/glGetBufferSubData)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glGetBufferSubData => unsafe fn gl_get_buffer_sub_data(
+            target: GLenum,
+            offset: GLintptr,
+            size: GLsizeiptr,
+            data: *mut GLvoid,
+        );
+
+        /// Return error information.
+        ///
+        /// **See**: [`glGetError` on docs.gl](https://docs.gl/gl4/glGetError)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glGetError => unsafe fn gl_get_error() -> GLenum;
+
+        /// Return the value of an indexed parameter.
+        ///
+        /// **See**: [`glGet` on docs.gl](https://docs.gl/gl4/glGet)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glGetIntegeri_v => unsafe fn gl_get_integer_i_v(
+            target: GLenum,
+            index: GLuint,
+            data: *mut GLint,
+        );
+
+        /// Return the value of a selected parameter.
+        ///
+        /// **See**: [`glGet` on docs.gl](https://docs.gl/gl4/glGet)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glGetIntegerv => unsafe fn gl_get_integer_v(pname: GLenum, data: *mut GLint);
+
+        /// Return a binary representation of a program object's compiled and linked executable source.
+        ///
+        /// **See**: [`glGetProgramBinary` on docs.gl](https://docs.gl/gl4/glGetProgramBinary)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glGetProgramBinary => unsafe fn gl_get_program_binary(
+            program: GLuint,
+            buf_size: GLsizei,
+            length: *mut GLsizei,
+            binary_format: *mut GLenum,
+            binary: *mut GLvoid,
+        );
+
         /// Returns the information log for a program object.
         ///
         /// **See**: [`glGetProgramInfoLog` on docs.gl](https://docs.gl/gl4/glGetProgramInfoLog)
@@ -326,6 +593,39 @@ impl GlContext {
             params: *mut GLint
         );
 
+        /// Query the index of a named resource within a program.
+        ///
+        /// **See**: [`glGetProgramResourceIndex` on docs.gl](https://docs.gl/gl4/glGetProgramResourceIndex)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glGetProgramResourceIndex => unsafe fn gl_get_program_resource_index(
+            program: GLuint,
+            program_interface: GLenum,
+            name: *const GLchar,
+        ) -> GLuint;
+
+        /// Retrieve values for multiple properties of a single active resource within a program object.
+        ///
+        /// **See**: [`glGetProgramResource` on docs.gl](https://docs.gl/gl4/glGetProgramResource)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glGetProgramResourceiv => unsafe fn gl_get_program_resource_iv(
+            program: GLuint,
+            program_interface: GLenum,
+            index: GLuint,
+            prop_count: GLsizei,
+            props: *const GLenum,
+            count: GLsizei,
+            length: *mut GLsizei,
+            params: *mut GLint,
+        );
+
         /// Returns the information log for a shader object.
         ///
         /// **See**: [`glGetShaderInfoLog` on docs.gl](https://docs.gl/gl4/glGetShaderInfoLog);
@@ -355,6 +655,70 @@ impl GlContext {
             params: *mut GLint
         );
 
+        /// Return a string describing the current GL connection.
+        ///
+        /// **See**: [`glGetString` on docs.gl](https://docs.gl/gl4/glGetString)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glGetString => unsafe fn gl_get_string(name: GLenum) -> *const GLubyte;
+
+        /// Return a texture image.
+        ///
+        /// **See**: [`glGetTexImage` on docs.gl](https://docs.gl/gl4/glGetTexImage)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glGetTexImage => unsafe fn gl_get_tex_image(
+            target: GLenum,
+            level: GLint,
+            format: GLenum,
+            gltype: GLenum,
+            pixels: *mut GLvoid,
+        );
+
+        /// Retrieve the index of a named uniform block.
+        ///
+        /// **See**: [`glGetUniformBlockIndex` on docs.gl](https://docs.gl/gl4/glGetUniformBlockIndex)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glGetUniformBlockIndex => unsafe fn gl_get_uniform_block_index(
+            program: GLuint,
+            uniform_block_name: *const GLchar,
+        ) -> GLuint;
+
+        /// Retrieve the index of one or more uniforms within a program.
+        ///
+        /// **See**: [`glGetUniformIndices` on docs.gl](https://docs.gl/gl4/glGetUniformIndices)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glGetUniformIndices => unsafe fn gl_get_uniform_indices(
+            program: GLuint,
+            uniform_count: GLsizei,
+            uniform_names: *const *const GLchar,
+            uniform_indices: *mut GLuint,
+        );
+
+        /// Determine if a name corresponds to a program object.
+        ///
+        /// **See**: [`glIsProgram` on docs.gl](https://docs.gl/gl4/glIsProgram)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glIsProgram => unsafe fn gl_is_program(program: GLuint) -> GLboolean;
+
         /// Links a program object.
         ///
         /// **See**: [`glLinkProgram` on docs.gl](https://docs.gl/gl4/glLinkProgram)
@@ -365,6 +729,55 @@ impl GlContext {
         ///   will occur.
         glLinkProgram => unsafe fn gl_link_program(program: GLuint);
 
+        /// Defines a barrier ordering memory transactions.
+        ///
+        /// **See**: [`glMemoryBarrier` on docs.gl](https://docs.gl/gl4/glMemoryBarrier)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glMemoryBarrier => unsafe fn gl_memory_barrier(barriers: GLbitfield);
+
+        /// Load a program object with a program binary.
+        ///
+        /// **See**: [`glProgramBinary` on docs.gl](https://docs.gl/gl4/glProgramBinary)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glProgramBinary => unsafe fn gl_program_binary(
+            program: GLuint,
+            binary_format: GLenum,
+            binary: *const GLvoid,
+            length: GLsizei,
+        );
+
+        /// Specify a parameter for a program object.
+        ///
+        /// **See**: [`glProgramParameter` on docs.gl](https://docs.gl/gl4/glProgramParameter)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glProgramParameteri => unsafe fn gl_program_parameter_i(
+            program: GLuint,
+            pname: GLenum,
+            value: GLint,
+        );
+
+        /// Define the scissor box.
+        ///
+        /// **See**: [`glScissor` on docs.gl](https://docs.gl/gl4/glScissor)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glScissor => unsafe fn gl_scissor(x: GLint, y: GLint, width: GLsizei, height: GLsizei);
+
         /// Replaces the source code in a shader object.
         ///
         /// **See**: [`glShaderSource` on docs.gl](https://docs.gl/gl4/glShaderSource)
@@ -380,6 +793,50 @@ impl GlContext {
             length: *const GLint,
         );
 
+        /// Change an active shader storage block binding.
+        ///
+        /// **See**: [`glShaderStorageBlockBinding` on docs.gl](https://docs.gl/gl4/glShaderStorageBlockBinding)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glShaderStorageBlockBinding => unsafe fn gl_shader_storage_block_binding(
+            program: GLuint,
+            storage_block_index: GLuint,
+            storage_block_binding: GLuint,
+        );
+
+        /// Simultaneously specify storage for all levels of a two-dimensional or one-dimensional array texture.
+        ///
+        /// **See**: [`glTexStorage2D` on docs.gl](https://docs.gl/gl4/glTexStorage2D)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glTexStorage2D => unsafe fn gl_tex_storage_2d(
+            target: GLenum,
+            levels: GLsizei,
+            internalformat: GLenum,
+            width: GLsizei,
+            height: GLsizei,
+        );
+
+        /// Assign a binding point to an active uniform block.
+        ///
+        /// **See**: [`glUniformBlockBinding` on docs.gl](https://docs.gl/gl4/glUniformBlockBinding)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glUniformBlockBinding => unsafe fn gl_uniform_block_binding(
+            program: GLuint,
+            uniform_block_index: GLuint,
+            uniform_block_binding: GLuint,
+        );
+
         /// Installs a program object as part of current rendering state
         ///
         /// **See**: [`glUseProgram` on docs.gl](https://docs.gl/gl4/glUseProgram)
@@ -390,6 +847,22 @@ impl GlContext {
         ///   will occur.
         glUseProgram => unsafe fn gl_use_program(program: GLuint);
 
+        /// Define an array of generic vertex attribute data that's read as integers
+        ///
+        /// **See**: [`glVertexAttribPointer` on docs.gl](https://docs.gl/gl4/glVertexAttribPointer)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glVertexAttribIPointer => unsafe fn gl_vertex_attrib_i_pointer(
+            index: GLuint,
+            size: GLint,
+            gltype: GLenum,
+            stride: GLsizei,
+            pointer: *const GLvoid,
+        );
+
         /// Define an array of generic vertex attribute data
         ///
         /// **See**: [`glVertexAttribPointer` on docs.gl](https://docs.gl/gl4/glVertexAttribPointer)
@@ -406,5 +879,15 @@ impl GlContext {
             stride: GLsizei,
             pointer: *const GLvoid
         );
+
+        /// Set the viewport.
+        ///
+        /// **See**: [`glViewport` on docs.gl](https://docs.gl/gl4/glViewport)
+        ///
+        /// ## Safety
+        ///
+        /// - If this struct's GL proc loader gives incorrect addresses to OpenGL procedures, undefined behaviour
+        ///   will occur.
+        glViewport => unsafe fn gl_viewport(x: GLint, y: GLint, width: GLsizei, height: GLsizei);
     }
 }
@@ -0,0 +1,451 @@
+//! An on-disk cache of linked program binaries.
+//!
+//! Compiling and linking shaders from source can take a noticeable amount of time on every launch.
+//! Most drivers can instead hand back an opaque binary blob for a linked program (via
+//! `glGetProgramBinary`) and later load that blob directly (via `glProgramBinary`).
+//!
+//! Those blobs are only valid for the exact driver that produced them, so cache entries are keyed
+//! by a hash of the shader sources _and_ the `GL_RENDERER` and `GL_VERSION` strings. Even then, a
+//! driver is allowed to reject any binary it doesn't like, so [`ProgramCache::load_or_build`]
+//! always falls back to compiling from source when that happens.
+
+use std::{
+    ffi::CStr,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    bindings::prelude::*,
+    shader::{self, ShaderError},
+    GlContext,
+};
+
+/// Magic bytes at the start of every cache entry.
+const ENTRY_MAGIC: &[u8; 8] = b"TFSPROG\0";
+
+/// Bumped whenever the layout of a cache entry changes.
+const ENTRY_VERSION: u32 = 1;
+
+/// Length of the header that precedes the driver's binary in a cache entry.
+const ENTRY_HEADER_LEN: usize = ENTRY_MAGIC.len() + 4 + 8 + 4 + 4;
+
+/// How many times to call `glGetError` after `glProgramBinary`. There are only a handful of error
+/// codes, and each has at most one flag, so this is only reached if the driver is misbehaving.
+const MAX_PENDING_ERRORS: usize = 8;
+
+/// One stage of a program, as handed to [`ProgramCache::load_or_build`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShaderSource<'a> {
+    /// The shader type, e.g. [`GL_VERTEX_SHADER`].
+    pub shader_type: GLenum,
+    pub source: &'a str,
+}
+
+impl<'a> ShaderSource<'a> {
+    pub const fn new(shader_type: GLenum, source: &'a str) -> Self {
+        Self {
+            shader_type,
+            source,
+        }
+    }
+}
+
+/// 64-bit FNV-1a, which is plenty for telling cache entries apart.
+#[derive(Clone, Copy)]
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    /// Writes a length-prefixed byte string, so that e.g. `["ab", "c"]` and `["a", "bc"]` hash
+    /// differently.
+    fn write_field(&mut self, bytes: &[u8]) {
+        self.write(&(bytes.len() as u64).to_le_bytes());
+        self.write(bytes);
+    }
+}
+
+/// Computes the cache key for a program built from `sources` by the driver identified by
+/// `renderer` and `version`.
+pub fn cache_key(renderer: &str, version: &str, sources: &[ShaderSource<'_>]) -> u64 {
+    let mut hash = Fnv1a::new();
+    hash.write(&ENTRY_VERSION.to_le_bytes());
+    hash.write_field(renderer.as_bytes());
+    hash.write_field(version.as_bytes());
+    for s in sources {
+        hash.write(&s.shader_type.to_le_bytes());
+        hash.write_field(s.source.as_bytes());
+    }
+    hash.0
+}
+
+/// Serializes a cache entry.
+pub fn encode_entry(key: u64, binary_format: GLenum, binary: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(ENTRY_HEADER_LEN + binary.len());
+    out.extend_from_slice(ENTRY_MAGIC);
+    out.extend_from_slice(&ENTRY_VERSION.to_le_bytes());
+    out.extend_from_slice(&key.to_le_bytes());
+    out.extend_from_slice(&binary_format.to_le_bytes());
+    out.extend_from_slice(&(binary.len() as u32).to_le_bytes());
+    out.extend_from_slice(binary);
+    out
+}
+
+/// Parses a cache entry, returning its binary format and the driver's binary.
+///
+/// Returns `None` if the entry is truncated, was written by a different version of this module, or
+/// belongs to a different key.
+pub fn decode_entry(key: u64, entry: &[u8]) -> Option<(GLenum, &[u8])> {
+    let (header, binary) = entry.split_at_checked(ENTRY_HEADER_LEN)?;
+    let (magic, header) = header.split_at(ENTRY_MAGIC.len());
+    let u32_at = |i: usize| u32::from_le_bytes(header[i..i + 4].try_into().unwrap());
+
+    if magic != ENTRY_MAGIC || u32_at(0) != ENTRY_VERSION {
+        return None;
+    }
+    if u64::from_le_bytes(header[4..12].try_into().unwrap()) != key {
+        return None;
+    }
+
+    let binary_format = u32_at(12);
+    let len = u32_at(16) as usize;
+    (binary.len() == len).then_some((binary_format, binary))
+}
+
+/// How [`ProgramCache::load_or_build`] came up with its program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheOutcome {
+    /// The program was loaded from a cached binary.
+    Hit,
+    /// There was no usable cache entry, so the program was built from source.
+    Miss,
+    /// A cache entry existed, but the driver rejected it. The program was built from source and
+    /// the entry replaced.
+    Stale,
+    /// The driver doesn't support program binaries, so the program was built from source and
+    /// nothing was cached.
+    Unsupported,
+}
+
+impl fmt::Display for CacheOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Hit => "loaded from cache",
+            Self::Miss => "built from source",
+            Self::Stale => "cached binary rejected, rebuilt from source",
+            Self::Unsupported => "built from source (program binaries unsupported)",
+        })
+    }
+}
+
+/// A directory of cached program binaries.
+#[derive(Debug, Clone)]
+pub struct ProgramCache {
+    dir: PathBuf,
+}
+
+impl ProgramCache {
+    /// Uses `dir` as the cache directory. It will be created the first time an entry is stored.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Uses the platform's usual per-user cache location, in a subdirectory named after `app`:
+    ///
+    /// - Windows: `%LOCALAPPDATA%\<app>\shader-cache`
+    /// - Elsewhere: `$XDG_CACHE_HOME/<app>/shader-cache`, or `~/.cache/<app>/shader-cache`
+    ///
+    /// Falls back to the system's temporary directory if none of those variables are set.
+    pub fn in_user_cache_dir(app: &str) -> Self {
+        let base = if cfg!(windows) {
+            std::env::var_os("LOCALAPPDATA").map(PathBuf::from)
+        } else {
+            std::env::var_os("XDG_CACHE_HOME")
+                .map(PathBuf::from)
+                .or_else(|| std::env::var_os("HOME").map(|h| Path::new(&h).join(".cache")))
+        };
+
+        Self::new(
+            base.unwrap_or_else(std::env::temp_dir)
+                .join(app)
+                .join("shader-cache"),
+        )
+    }
+
+    /// The directory that entries are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The file that the entry for `key` is stored in.
+    pub fn entry_path(&self, key: u64) -> PathBuf {
+        self.dir.join(format!("{key:016x}.bin"))
+    }
+
+    /// Loads a program from the cache, or builds it from `sources` and stores it in the cache.
+    ///
+    /// Failing to read or write the cache is never fatal; the worst case is a rebuild from source.
+    /// Only compile and link errors are returned.
+    ///
+    /// ## Safety
+    ///
+    /// - `ctx` must be able to load correct OpenGL procedure addresses, and a context supporting
+    ///   OpenGL 4.1 or `ARB_get_program_binary` must be current.
+    pub unsafe fn load_or_build(
+        &self,
+        ctx: &GlContext,
+        sources: &[ShaderSource<'_>],
+    ) -> Result<(GLuint, CacheOutcome), ShaderError> {
+        let mut num_formats: GLint = 0;
+        ctx.gl_get_integer_v(GL_NUM_PROGRAM_BINARY_FORMATS, &mut num_formats);
+        if num_formats <= 0 {
+            return build_program(ctx, sources, false).map(|p| (p, CacheOutcome::Unsupported));
+        }
+
+        let key = cache_key(
+            &get_string(ctx, GL_RENDERER),
+            &get_string(ctx, GL_VERSION),
+            sources,
+        );
+        let path = self.entry_path(key);
+
+        let mut outcome = CacheOutcome::Miss;
+        if let Ok(entry) = fs::read(&path) {
+            match decode_entry(key, &entry) {
+                Some((binary_format, binary)) => {
+                    if let Some(program) = load_binary(ctx, binary_format, binary) {
+                        return Ok((program, CacheOutcome::Hit));
+                    }
+                    outcome = CacheOutcome::Stale;
+                }
+                None => outcome = CacheOutcome::Stale,
+            }
+        }
+
+        let program = build_program(ctx, sources, true)?;
+        if let Err(e) = self.store(ctx, key, program) {
+            eprintln!(
+                "Unable to write program cache entry {}: {e}",
+                path.display()
+            );
+        }
+
+        Ok((program, outcome))
+    }
+
+    /// Writes the binary for a linked `program` to the entry for `key`.
+    unsafe fn store(&self, ctx: &GlContext, key: u64, program: GLuint) -> io::Result<()> {
+        let mut len: GLint = 0;
+        ctx.gl_get_program_iv(program, GL_PROGRAM_BINARY_LENGTH, &mut len);
+        if len <= 0 {
+            return Err(io::Error::other("driver returned an empty program binary"));
+        }
+
+        let mut binary = vec![0u8; len as usize];
+        let mut written: GLsizei = 0;
+        let mut binary_format: GLenum = 0;
+        ctx.gl_get_program_binary(
+            program,
+            len,
+            &mut written,
+            &mut binary_format,
+            binary.as_mut_ptr().cast(),
+        );
+        binary.truncate(written.max(0) as usize);
+
+        // Write to a temporary file first, so that a crash mid-write can't leave a truncated entry
+        // behind under the real name.
+        fs::create_dir_all(&self.dir)?;
+        let path = self.entry_path(key);
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&tmp, encode_entry(key, binary_format, &binary))?;
+        fs::rename(&tmp, &path).inspect_err(|_| {
+            fs::remove_file(&tmp).ok();
+        })
+    }
+}
+
+/// Fetches a string from `glGetString`, or an empty string if the driver returns null.
+unsafe fn get_string(ctx: &GlContext, name: GLenum) -> String {
+    let p = ctx.gl_get_string(name);
+    if p.is_null() {
+        return String::new();
+    }
+    CStr::from_ptr(p.cast()).to_string_lossy().into_owned()
+}
+
+/// Tries to create a program from a driver binary, returning `None` if the driver rejects it.
+unsafe fn load_binary(ctx: &GlContext, binary_format: GLenum, binary: &[u8]) -> Option<GLuint> {
+    let program = ctx.gl_create_program();
+    ctx.gl_program_binary(
+        program,
+        binary_format,
+        binary.as_ptr().cast(),
+        binary.len() as GLsizei,
+    );
+
+    // glProgramBinary reports failure through the link status. It may also raise
+    // GL_INVALID_ENUM for formats it doesn't know about, which we don't want to leak to callers.
+    // Nothing else is expected, but an implementation may keep a flag per error code, so read a
+    // few in case something else was already pending.
+    for _ in 0..MAX_PENDING_ERRORS {
+        match ctx.gl_get_error() {
+            GL_NO_ERROR => break,
+            GL_INVALID_ENUM => {}
+            error => eprintln!("Unexpected GL error {error:#06X} around glProgramBinary"),
+        }
+    }
+
+    let mut success: GLint = 0;
+    ctx.gl_get_program_iv(program, GL_LINK_STATUS, &mut success);
+
+    if success == GL_TRUE as GLint {
+        Some(program)
+    } else {
+        ctx.gl_delete_program(program);
+        None
+    }
+}
+
+/// Compiles and links `sources`, optionally asking the driver to keep the binary around.
+unsafe fn build_program(
+    ctx: &GlContext,
+    sources: &[ShaderSource<'_>],
+    retrievable: bool,
+) -> Result<GLuint, ShaderError> {
+    let mut shaders = Vec::with_capacity(sources.len());
+    for s in sources {
+        match shader::compile_shader(ctx, s.shader_type, s.source) {
+            Ok(shader) => shaders.push(shader),
+            Err(e) => {
+                shaders.iter().for_each(|&s| ctx.gl_delete_shader(s));
+                return Err(e);
+            }
+        }
+    }
+
+    let program = shader::link_program_with(ctx, &shaders, |program| {
+        if retrievable {
+            ctx.gl_program_parameter_i(
+                program,
+                GL_PROGRAM_BINARY_RETRIEVABLE_HINT,
+                GL_TRUE as GLint,
+            );
+        }
+    });
+    shaders.iter().for_each(|&s| ctx.gl_delete_shader(s));
+    program
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VS: ShaderSource = ShaderSource::new(GL_VERTEX_SHADER, "void main() {}");
+    const FS: ShaderSource = ShaderSource::new(GL_FRAGMENT_SHADER, "void main() {}");
+
+    mod cache_key {
+        use super::*;
+
+        #[test]
+        fn is_stable() {
+            assert_eq!(
+                cache_key("llvmpipe", "4.5", &[VS, FS]),
+                cache_key("llvmpipe", "4.5", &[VS, FS])
+            );
+        }
+
+        #[test]
+        fn depends_on_driver() {
+            let key = cache_key("llvmpipe", "4.5 Mesa 23.0", &[VS, FS]);
+            assert_ne!(key, cache_key("llvmpipe", "4.5 Mesa 23.1", &[VS, FS]));
+            assert_ne!(key, cache_key("radeonsi", "4.5 Mesa 23.0", &[VS, FS]));
+        }
+
+        #[test]
+        fn depends_on_sources_and_stages() {
+            let key = cache_key("r", "v", &[VS, FS]);
+            assert_ne!(key, cache_key("r", "v", &[FS, VS]));
+            assert_ne!(key, cache_key("r", "v", &[VS]));
+            assert_ne!(
+                key,
+                cache_key(
+                    "r",
+                    "v",
+                    &[VS, ShaderSource::new(GL_FRAGMENT_SHADER, "void main(){}")]
+                )
+            );
+        }
+
+        #[test]
+        fn fields_are_delimited() {
+            assert_ne!(cache_key("ab", "c", &[]), cache_key("a", "bc", &[]));
+        }
+    }
+
+    mod entries {
+        use super::*;
+
+        #[test]
+        fn round_trip() {
+            let entry = encode_entry(42, 0x8E21, &[1, 2, 3, 4, 5]);
+            assert_eq!(
+                decode_entry(42, &entry),
+                Some((0x8E21, &[1, 2, 3, 4, 5][..]))
+            );
+        }
+
+        #[test]
+        fn empty_binary_round_trips() {
+            let entry = encode_entry(7, 1, &[]);
+            assert_eq!(decode_entry(7, &entry), Some((1, &[][..])));
+        }
+
+        #[test]
+        fn rejects_other_keys() {
+            let entry = encode_entry(42, 1, &[1, 2, 3]);
+            assert_eq!(decode_entry(43, &entry), None);
+        }
+
+        #[test]
+        fn rejects_truncated_entries() {
+            let entry = encode_entry(42, 1, &[1, 2, 3]);
+            for len in 0..entry.len() {
+                assert_eq!(decode_entry(42, &entry[..len]), None, "length {len}");
+            }
+        }
+
+        #[test]
+        fn rejects_trailing_garbage() {
+            let mut entry = encode_entry(42, 1, &[1, 2, 3]);
+            entry.push(0);
+            assert_eq!(decode_entry(42, &entry), None);
+        }
+
+        #[test]
+        fn rejects_bad_magic_and_version() {
+            let entry = encode_entry(42, 1, &[1, 2, 3]);
+
+            let mut bad_magic = entry.clone();
+            bad_magic[0] ^= 0xFF;
+            assert_eq!(decode_entry(42, &bad_magic), None);
+
+            let mut bad_version = entry;
+            bad_version[ENTRY_MAGIC.len()] ^= 0xFF;
+            assert_eq!(decode_entry(42, &bad_version), None);
+        }
+    }
+}
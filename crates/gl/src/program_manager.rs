@@ -0,0 +1,282 @@
+//! Rebuilds a program from shader files on disk whenever they change.
+//!
+//! This is meant for iterating on shaders while the program is running. [`ProgramManager`] doesn't
+//! watch for changes itself; it reports which files each build read (including `#include`d ones),
+//! and something like a file watcher tells it when any of them change. If a rebuild fails, the
+//! last program that built successfully stays in use and the error is handed back to be shown.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use glsl::preprocess::{FileSystem, GlslVersion, PreprocessError, Preprocessor};
+
+use crate::{
+    bindings::prelude::*,
+    shader::{self, ShaderError},
+    GlContext,
+};
+
+/// Why a program couldn't be rebuilt.
+#[derive(Debug)]
+pub enum ReloadError {
+    /// A shader file (or something it includes) couldn't be preprocessed.
+    Preprocess(PreprocessError),
+    /// A stage failed to compile, or the program failed to link. Compile logs have their
+    /// locations rewritten to point at the original files.
+    Shader(ShaderError),
+}
+
+impl fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Preprocess(e) => write!(f, "{e}"),
+            Self::Shader(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for ReloadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Preprocess(e) => Some(e),
+            Self::Shader(e) => Some(e),
+        }
+    }
+}
+
+impl From<PreprocessError> for ReloadError {
+    fn from(e: PreprocessError) -> Self {
+        Self::Preprocess(e)
+    }
+}
+
+impl From<ShaderError> for ReloadError {
+    fn from(e: ShaderError) -> Self {
+        Self::Shader(e)
+    }
+}
+
+/// Owns a program built from shader files below a root directory, and rebuilds it on request.
+///
+/// ```no_run
+/// use glsl::preprocess::GlslVersion;
+/// use triangle_from_scratch_gl::{bindings::prelude::*, program_manager::ProgramManager};
+/// # unsafe fn f(ctx: &triangle_from_scratch_gl::GlContext, changed: &[std::path::PathBuf]) {
+///
+/// let mut shaders = ProgramManager::new("src", GlslVersion::GL_460_CORE)
+///     .stage(GL_VERTEX_SHADER, "vertex.vs")
+///     .stage(GL_FRAGMENT_SHADER, "fragment.fs");
+/// shaders.reload(ctx).unwrap();
+///
+/// // later, once a file watcher says `changed` have been written to:
+/// if let Some(Err(e)) = shaders.reload_if_changed(ctx, changed) {
+///     eprintln!("{e}");
+/// }
+/// ctx.gl_use_program(shaders.program().unwrap());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ProgramManager {
+    root: PathBuf,
+    preprocessor: Preprocessor<FileSystem>,
+    /// Each stage's shader type and file name, relative to `root`.
+    stages: Vec<(GLenum, String)>,
+    /// The last program that built successfully.
+    program: Option<GLuint>,
+    /// Every file the last build attempt read, or would have read.
+    files: Vec<PathBuf>,
+}
+
+impl ProgramManager {
+    /// Creates a manager for shaders below `root`, which will be preprocessed with a `version`
+    /// header. Nothing is built until [`reload`](Self::reload) is called.
+    pub fn new(root: impl Into<PathBuf>, version: GlslVersion) -> Self {
+        let root = root.into();
+        Self {
+            preprocessor: Preprocessor::new(version, FileSystem::new(&root)),
+            root,
+            stages: Vec::new(),
+            program: None,
+            files: Vec::new(),
+        }
+    }
+
+    /// Adds `#define name value` to every stage. See [`Preprocessor::define`].
+    pub fn define(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.preprocessor = self.preprocessor.define(name, value);
+        self
+    }
+
+    /// Adds a stage of type `shader_type`, loaded from the file `name` below the root.
+    pub fn stage(mut self, shader_type: GLenum, name: impl Into<String>) -> Self {
+        let name = name.into();
+        self.files.push(self.root.join(&name));
+        self.stages.push((shader_type, name));
+        self
+    }
+
+    /// The last program that built successfully, if any.
+    pub fn program(&self) -> Option<GLuint> {
+        self.program
+    }
+
+    /// Every file that went into the last build, including `#include`d ones. These are the files
+    /// to watch for changes.
+    ///
+    /// If a file couldn't be loaded, this still covers everything up to and including it, so that
+    /// fixing it triggers a rebuild.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Rebuilds the program from the files on disk.
+    ///
+    /// On success, the old program is deleted and the new one is returned. On failure, the old
+    /// program is left alone and [`program`](Self::program) keeps returning it.
+    ///
+    /// ## Safety
+    ///
+    /// - `ctx` must be able to load correct OpenGL procedure addresses, and the context the
+    ///   program was built in must be current.
+    pub unsafe fn reload(&mut self, ctx: &GlContext) -> Result<GLuint, ReloadError> {
+        let program = self.build(ctx)?;
+        if let Some(old) = self.program.replace(program) {
+            ctx.gl_delete_program(old);
+        }
+        Ok(program)
+    }
+
+    /// Rebuilds the program if any of the `changed` paths went into the last build. Returns
+    /// `None` if none of them did.
+    ///
+    /// ## Safety
+    ///
+    /// - Same as [`reload`](Self::reload).
+    pub unsafe fn reload_if_changed(
+        &mut self,
+        ctx: &GlContext,
+        changed: &[impl AsRef<Path>],
+    ) -> Option<Result<GLuint, ReloadError>> {
+        if self.is_affected_by(changed) {
+            Some(self.reload(ctx))
+        } else {
+            None
+        }
+    }
+
+    /// Deletes the program, if one was built.
+    ///
+    /// ## Safety
+    ///
+    /// - Same as [`reload`](Self::reload).
+    pub unsafe fn delete(mut self, ctx: &GlContext) {
+        if let Some(program) = self.program.take() {
+            ctx.gl_delete_program(program);
+        }
+    }
+
+    fn is_affected_by(&self, changed: &[impl AsRef<Path>]) -> bool {
+        changed
+            .iter()
+            .any(|path| self.files.iter().any(|f| f == path.as_ref()))
+    }
+
+    /// Preprocesses, compiles, and links every stage, updating `files` along the way.
+    unsafe fn build(&mut self, ctx: &GlContext) -> Result<GLuint, ReloadError> {
+        let mut files: Vec<PathBuf> = Vec::new();
+        let mut add_file = |path: PathBuf| {
+            if !files.contains(&path) {
+                files.push(path);
+            }
+        };
+
+        let mut outputs = Vec::with_capacity(self.stages.len());
+        let mut error = None;
+        for (shader_type, name) in &self.stages {
+            add_file(self.root.join(name));
+            match self.preprocessor.run(name) {
+                Ok(output) => {
+                    for file in output.map.files() {
+                        add_file(self.root.join(file));
+                    }
+                    outputs.push((*shader_type, output));
+                }
+                Err(e) => {
+                    if let PreprocessError::Io { path, includer, .. } = &e {
+                        // the missing file has to be watched too, so that creating it retries
+                        let includer = includer.as_ref().map_or("", |at| at.file.as_str());
+                        add_file(
+                            self.root
+                                .join(glsl::preprocess::join_include_path(includer, path)),
+                        );
+                    }
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        self.files = files;
+        if let Some(e) = error {
+            return Err(e.into());
+        }
+
+        let mut shaders = Vec::with_capacity(outputs.len());
+        let result = outputs
+            .iter()
+            .try_for_each(|(shader_type, output)| {
+                match shader::compile_shader(ctx, *shader_type, &output.source) {
+                    Ok(shader) => {
+                        shaders.push(shader);
+                        Ok(())
+                    }
+                    Err(ShaderError::Compile { shader_type, log }) => Err(ShaderError::Compile {
+                        shader_type,
+                        log: output.map.rewrite_info_log(&log),
+                    }),
+                    Err(e) => Err(e),
+                }
+            })
+            .and_then(|()| shader::link_program(ctx, &shaders));
+
+        for shader in shaders {
+            ctx.gl_delete_shader(shader);
+        }
+        Ok(result?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod program_manager {
+        use super::*;
+
+        #[test]
+        fn stages_are_watched_before_the_first_build() {
+            let manager = ProgramManager::new("shaders", GlslVersion::GL_330_CORE)
+                .stage(GL_VERTEX_SHADER, "a.vs")
+                .stage(GL_FRAGMENT_SHADER, "b.fs");
+            assert_eq!(
+                manager.files(),
+                [Path::new("shaders/a.vs"), Path::new("shaders/b.fs")]
+            );
+            assert_eq!(manager.program(), None);
+        }
+
+        #[test]
+        fn only_watched_files_count_as_changes() {
+            let manager = ProgramManager::new("shaders", GlslVersion::GL_330_CORE)
+                .stage(GL_VERTEX_SHADER, "a.vs");
+            assert!(manager.is_affected_by(&[Path::new("shaders/a.vs")]));
+            assert!(manager.is_affected_by(&[
+                PathBuf::from("shaders/other.vs"),
+                PathBuf::from("shaders/a.vs"),
+            ]));
+            assert!(!manager.is_affected_by(&[Path::new("shaders/other.vs")]));
+            assert!(!manager.is_affected_by(&[Path::new("a.vs")]));
+            assert!(!manager.is_affected_by(&[] as &[&Path]));
+        }
+    }
+}
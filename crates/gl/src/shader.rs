@@ -0,0 +1,175 @@
+//! Helpers for compiling shaders and linking programs.
+//!
+//! Unlike calling [`GlContext::gl_compile_shader`] and friends directly, these helpers check the
+//! compile/link status and hand back the driver's info log when something goes wrong.
+
+use std::fmt;
+
+use crate::{bindings::prelude::*, GlContext};
+
+/// Describes a shader that failed to compile, or a program that failed to link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShaderError {
+    /// The source contained an interior null byte, so it couldn't be handed to the driver.
+    InteriorNul,
+    /// `glCompileShader` failed for a shader of type `shader_type`.
+    Compile { shader_type: GLenum, log: String },
+    /// `glLinkProgram` failed. Contains the program's info log.
+    Link(String),
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InteriorNul => write!(f, "shader source contains an interior null byte"),
+            Self::Compile { shader_type, log } => {
+                write!(
+                    f,
+                    "failed to compile {} shader:\n{log}",
+                    stage_name(*shader_type)
+                )
+            }
+            Self::Link(log) => write!(f, "failed to link program:\n{log}"),
+        }
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+/// A human-readable name for a shader type, e.g. `"fragment"` for [`GL_FRAGMENT_SHADER`].
+pub fn stage_name(shader_type: GLenum) -> &'static str {
+    match shader_type {
+        GL_VERTEX_SHADER => "vertex",
+        GL_FRAGMENT_SHADER => "fragment",
+        GL_COMPUTE_SHADER => "compute",
+        _ => "unknown",
+    }
+}
+
+/// Creates and compiles a shader of the given type (e.g. [`GL_COMPUTE_SHADER`]).
+///
+/// On failure, the shader object is deleted and its info log is returned.
+///
+/// ## Safety
+///
+/// - `ctx` must be able to load correct OpenGL procedure addresses, and a context must be current.
+pub unsafe fn compile_shader(
+    ctx: &GlContext,
+    shader_type: GLenum,
+    source: &str,
+) -> Result<GLuint, ShaderError> {
+    if source.contains('\0') {
+        return Err(ShaderError::InteriorNul);
+    }
+
+    let shader = ctx.gl_create_shader(shader_type);
+    ctx.gl_shader_source(
+        shader,
+        1,
+        [source.as_ptr() as *const GLchar].as_ptr(),
+        [source.len() as GLint].as_ptr(),
+    );
+    ctx.gl_compile_shader(shader);
+
+    let mut success: GLint = 0;
+    ctx.gl_get_shader_iv(shader, GL_COMPILE_STATUS, &mut success);
+    if success != GL_TRUE as GLint {
+        let log = shader_info_log(ctx, shader);
+        ctx.gl_delete_shader(shader);
+        return Err(ShaderError::Compile { shader_type, log });
+    }
+
+    Ok(shader)
+}
+
+/// Links the given shaders into a new program.
+///
+/// The shaders stay attached to the program, so callers can delete them as soon as this returns.
+/// On failure, the program object is deleted and its info log is returned.
+///
+/// ## Safety
+///
+/// - `ctx` must be able to load correct OpenGL procedure addresses, and a context must be current.
+/// - Every entry in `shaders` must be a valid, compiled shader object.
+pub unsafe fn link_program(ctx: &GlContext, shaders: &[GLuint]) -> Result<GLuint, ShaderError> {
+    link_program_with(ctx, shaders, |_| {})
+}
+
+/// Like [`link_program`], but calls `before_link` with the new program right before linking it.
+///
+/// This is the place to set parameters that only take effect at link time, such as
+/// `GL_PROGRAM_BINARY_RETRIEVABLE_HINT`.
+///
+/// ## Safety
+///
+/// - Same as [`link_program`].
+pub unsafe fn link_program_with(
+    ctx: &GlContext,
+    shaders: &[GLuint],
+    before_link: impl FnOnce(GLuint),
+) -> Result<GLuint, ShaderError> {
+    let program = ctx.gl_create_program();
+    for &shader in shaders {
+        ctx.gl_attach_shader(program, shader);
+    }
+    before_link(program);
+    ctx.gl_link_program(program);
+
+    let mut success: GLint = 0;
+    ctx.gl_get_program_iv(program, GL_LINK_STATUS, &mut success);
+    if success != GL_TRUE as GLint {
+        let log = program_info_log(ctx, program);
+        ctx.gl_delete_program(program);
+        return Err(ShaderError::Link(log));
+    }
+
+    Ok(program)
+}
+
+/// Returns a shader's info log, with any trailing null bytes and whitespace removed.
+///
+/// ## Safety
+///
+/// - `ctx` must be able to load correct OpenGL procedure addresses, and a context must be current.
+/// - `shader` must be a valid shader object.
+pub unsafe fn shader_info_log(ctx: &GlContext, shader: GLuint) -> String {
+    let mut len: GLint = 0;
+    ctx.gl_get_shader_iv(shader, GL_INFO_LOG_LENGTH, &mut len);
+
+    let mut buf = vec![0u8; len.max(1) as usize];
+    let mut written: GLsizei = 0;
+    ctx.gl_get_shader_info_log(
+        shader,
+        buf.len() as GLsizei,
+        &mut written,
+        buf.as_mut_ptr() as *mut GLchar,
+    );
+    info_log_to_string(&buf[..written.max(0) as usize])
+}
+
+/// Returns a program's info log, with any trailing null bytes and whitespace removed.
+///
+/// ## Safety
+///
+/// - `ctx` must be able to load correct OpenGL procedure addresses, and a context must be current.
+/// - `program` must be a valid program object.
+pub unsafe fn program_info_log(ctx: &GlContext, program: GLuint) -> String {
+    let mut len: GLint = 0;
+    ctx.gl_get_program_iv(program, GL_INFO_LOG_LENGTH, &mut len);
+
+    let mut buf = vec![0u8; len.max(1) as usize];
+    let mut written: GLsizei = 0;
+    ctx.gl_get_program_info_log(
+        program,
+        buf.len() as GLsizei,
+        &mut written,
+        buf.as_mut_ptr() as *mut GLchar,
+    );
+    info_log_to_string(&buf[..written.max(0) as usize])
+}
+
+fn info_log_to_string(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .trim_end_matches(['\0', ' ', '\n', '\r', '\t'])
+        .to_string()
+}
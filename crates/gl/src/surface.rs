@@ -0,0 +1,164 @@
+//! Keeping the viewport, and anything else that's drawn at the window's size, in step with the
+//! size of what's being drawn to.
+//!
+//! A [`Surface`] is told the drawable's size in physical pixels, and its scale factor, whenever
+//! the window reports a change (e.g. with `WM_SIZE` and `WM_DPICHANGED`). Then, once a frame,
+//! [`Surface::prepare`] sets the viewport and scissor box to cover the whole surface, and tells any
+//! [`SurfaceTarget`]s, like offscreen framebuffers, that the surface has changed since they last
+//! heard, so they can reallocate.
+//!
+//! While the surface has no area, which is what a minimized window has, there's nothing to draw,
+//! so [`Surface::prepare`] says not to.
+
+use core::{cell::RefCell, fmt};
+use std::rc::{Rc, Weak};
+
+use crate::{bindings::prelude::*, GlContext};
+
+/// Something that depends on the surface's size, like a framebuffer that's drawn at the surface's
+/// size and then copied to it.
+pub trait SurfaceTarget {
+    /// The surface is now `size` physical pixels (neither of which is zero), with `scale_factor`
+    /// physical pixels for each logical pixel.
+    ///
+    /// This is called from [`Surface::prepare`], with the same context current.
+    fn surface_changed(&mut self, ctx: &GlContext, size: [u32; 2], scale_factor: f64);
+}
+
+/// The size of what's being drawn to, and what depends on it.
+pub struct Surface {
+    size: [u32; 2],
+    scale_factor: f64,
+    targets: Vec<Target>,
+}
+
+/// A [`SurfaceTarget`], and what it was last told.
+struct Target {
+    target: Weak<RefCell<dyn SurfaceTarget>>,
+    told: Option<([u32; 2], f64)>,
+}
+
+impl Surface {
+    /// A surface that's `size` physical pixels, at `scale_factor`.
+    pub fn new(size: [u32; 2], scale_factor: f64) -> Self {
+        Self {
+            size,
+            scale_factor,
+            targets: Vec::new(),
+        }
+    }
+
+    /// The surface's size in physical pixels.
+    pub fn size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    /// How many physical pixels there are for each logical pixel, e.g. `1.5` on a monitor with 150%
+    /// scaling.
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// The surface's size in logical pixels.
+    pub fn logical_size(&self) -> [f64; 2] {
+        self.size.map(|n| f64::from(n) / self.scale_factor)
+    }
+
+    /// Whether there's nothing to draw to, because the surface has no area.
+    pub fn is_minimized(&self) -> bool {
+        self.size.contains(&0)
+    }
+
+    /// The drawable is now `size` physical pixels. This takes effect at the next
+    /// [`prepare`](Self::prepare).
+    pub fn resize(&mut self, size: [u32; 2]) {
+        self.size = size;
+    }
+
+    /// The drawable now has `scale_factor` physical pixels for each logical one, usually because
+    /// its window moved to another monitor. This takes effect at the next
+    /// [`prepare`](Self::prepare).
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+    }
+
+    /// Has `target` told about changes to the surface, starting with the next
+    /// [`prepare`](Self::prepare). The surface only keeps a weak reference, so the target stops
+    /// hearing about changes once it's dropped.
+    pub fn add_target<T: SurfaceTarget + 'static>(&mut self, target: &Rc<RefCell<T>>) {
+        let target: Rc<RefCell<dyn SurfaceTarget>> = target.clone();
+        self.targets.push(Target {
+            target: Rc::downgrade(&target),
+            told: None,
+        });
+    }
+
+    /// Gets ready to draw a frame: sets the viewport and scissor box to the whole surface, and
+    /// tells the targets about anything that's changed since they last heard.
+    ///
+    /// Drawing to offscreen targets usually changes the viewport, so this sets it every time
+    /// rather than only when the size changes.
+    ///
+    /// **Returns:** `false`, having done nothing, if the surface is minimized and so there's
+    /// nothing to draw.
+    ///
+    /// ## Safety
+    ///
+    /// - A context must be current, and `ctx` must load procedures for it.
+    pub unsafe fn prepare(&mut self, ctx: &GlContext) -> bool {
+        if self.is_minimized() {
+            return false;
+        }
+
+        let [width, height] = self.size.map(|n| n.min(GLsizei::MAX as u32) as GLsizei);
+        ctx.gl_viewport(0, 0, width, height);
+        ctx.gl_scissor(0, 0, width, height);
+
+        let now = (self.size, self.scale_factor);
+        self.targets.retain_mut(|target| {
+            let Some(strong) = target.target.upgrade() else {
+                return false;
+            };
+            if target.told != Some(now) {
+                strong
+                    .borrow_mut()
+                    .surface_changed(ctx, self.size, self.scale_factor);
+                target.told = Some(now);
+            }
+            true
+        });
+        true
+    }
+}
+
+impl fmt::Debug for Surface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Surface")
+            .field("size", &self.size)
+            .field("scale_factor", &self.scale_factor)
+            .field("targets", &self.targets.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes() {
+        let mut surface = Surface::new([1200, 900], 1.5);
+        assert_eq!(surface.logical_size(), [800.0, 600.0]);
+        assert!(!surface.is_minimized());
+
+        surface.resize([0, 0]);
+        assert!(surface.is_minimized());
+        surface.resize([1, 0]);
+        assert!(surface.is_minimized());
+
+        surface.resize([300, 200]);
+        surface.set_scale_factor(2.0);
+        assert_eq!(surface.size(), [300, 200]);
+        assert_eq!(surface.logical_size(), [150.0, 100.0]);
+    }
+}
@@ -0,0 +1,161 @@
+//! Vertex layouts that can be both applied to a vertex array object and checked against a vertex
+//! shader.
+//!
+//! Describing a layout as data rather than as a series of `glVertexAttribPointer` calls means the
+//! same description can be handed to [`glsl::validate::Validator::vertex_inputs`], so a shader that
+//! reads an attribute the layout doesn't provide (or reads it as the wrong type) is caught by
+//! `cargo test` instead of showing up as a black screen.
+//!
+//! ```
+//! use triangle_from_scratch_gl::vertex::{VertexAttribute, VertexLayout};
+//!
+//! /// Interleaved `[x, y, z, r, g, b]` vertices.
+//! const LAYOUT: VertexLayout = VertexLayout::new(
+//!     6 * 4,
+//!     &[
+//!         VertexAttribute::floats(0, 3, 0),
+//!         VertexAttribute::floats(1, 3, 3 * 4),
+//!     ],
+//! );
+//!
+//! assert_eq!(LAYOUT.shader_inputs()[1].location, 1);
+//! ```
+
+use glsl::validate::{AttributeKind, VertexInput};
+
+use crate::{bindings::prelude::*, GlContext};
+
+/// One attribute of a [`VertexLayout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VertexAttribute {
+    /// The attribute index, matching `layout(location = N)` in the vertex shader.
+    pub location: GLuint,
+    /// The number of components, from 1 to 4.
+    pub components: GLint,
+    /// The type of each component in the buffer, e.g. `GL_FLOAT` or `GL_UNSIGNED_BYTE`.
+    pub gl_type: GLenum,
+    /// For float attributes stored as integers, whether to map them to `[0, 1]` or `[-1, 1]`.
+    pub normalized: bool,
+    /// Whether the shader reads the attribute as integers, using `glVertexAttribIPointer`.
+    pub integer: bool,
+    /// The byte offset of the attribute within each vertex.
+    pub offset: usize,
+}
+
+impl VertexAttribute {
+    /// An attribute stored as `f32`s and read as `float` or `vecN`.
+    pub const fn floats(location: GLuint, components: GLint, offset: usize) -> Self {
+        Self {
+            location,
+            components,
+            gl_type: GL_FLOAT,
+            normalized: false,
+            integer: false,
+            offset,
+        }
+    }
+
+    /// An attribute stored as integers of type `gl_type`, and read as normalized `float`s or
+    /// `vecN`s.
+    pub const fn normalized(
+        location: GLuint,
+        components: GLint,
+        gl_type: GLenum,
+        offset: usize,
+    ) -> Self {
+        Self {
+            location,
+            components,
+            gl_type,
+            normalized: true,
+            integer: false,
+            offset,
+        }
+    }
+
+    /// An attribute stored as integers of type `gl_type`, and read as `int`, `uint`, `ivecN` or
+    /// `uvecN`.
+    pub const fn integers(
+        location: GLuint,
+        components: GLint,
+        gl_type: GLenum,
+        offset: usize,
+    ) -> Self {
+        Self {
+            location,
+            components,
+            gl_type,
+            normalized: false,
+            integer: true,
+            offset,
+        }
+    }
+}
+
+/// The layout of the vertices in an array buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VertexLayout<'a> {
+    /// The number of bytes between the starts of consecutive vertices.
+    pub stride: usize,
+    pub attributes: &'a [VertexAttribute],
+}
+
+impl<'a> VertexLayout<'a> {
+    pub const fn new(stride: usize, attributes: &'a [VertexAttribute]) -> Self {
+        Self { stride, attributes }
+    }
+
+    /// Points every attribute at the buffer currently bound to `GL_ARRAY_BUFFER`, and enables it.
+    ///
+    /// The attribute state is stored in the currently bound vertex array object.
+    ///
+    /// ## Safety
+    ///
+    /// - An array buffer must be bound, and a vertex array object must be bound in a core profile
+    ///   context.
+    /// - If `ctx`'s GL proc loader gives incorrect addresses to OpenGL procedures, undefined
+    ///   behaviour will occur.
+    pub unsafe fn apply(&self, ctx: &GlContext) {
+        for attribute in self.attributes {
+            if attribute.integer {
+                ctx.gl_vertex_attrib_i_pointer(
+                    attribute.location,
+                    attribute.components,
+                    attribute.gl_type,
+                    self.stride as _,
+                    attribute.offset as _,
+                );
+            } else {
+                ctx.gl_vertex_attrib_pointer(
+                    attribute.location,
+                    attribute.components,
+                    attribute.gl_type,
+                    if attribute.normalized {
+                        GL_TRUE
+                    } else {
+                        GL_FALSE
+                    },
+                    self.stride as _,
+                    attribute.offset as _,
+                );
+            }
+            ctx.gl_enable_vertex_attrib_array(attribute.location);
+        }
+    }
+
+    /// Describes the layout for [`glsl::validate::Validator::vertex_inputs`].
+    pub fn shader_inputs(&self) -> Vec<VertexInput> {
+        self.attributes
+            .iter()
+            .map(|a| VertexInput {
+                location: a.location,
+                components: a.components as u8,
+                kind: if a.integer {
+                    AttributeKind::Integer
+                } else {
+                    AttributeKind::Float
+                },
+            })
+            .collect()
+    }
+}
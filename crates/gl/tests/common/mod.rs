@@ -0,0 +1,62 @@
+//! Shared setup for tests that need a real OpenGL context.
+//!
+//! Contexts come from EGL's surfaceless platform, so these tests can run on machines without a
+//! display server (e.g. with Mesa's `llvmpipe`). When no such context can be created, [`context`]
+//! returns `None` and tests should return early instead of failing.
+
+#![allow(dead_code)]
+
+use std::{fs, path::PathBuf};
+
+use egl::HeadlessContext;
+use gl::{GlContext, GlProcLoader};
+
+struct EglProcLoader;
+
+impl GlProcLoader for EglProcLoader {
+    unsafe fn load_proc(&self, name: &[u8]) -> *mut core::ffi::c_void {
+        egl::get_proc_address(name).unwrap_or(core::ptr::null_mut())
+    }
+}
+
+/// Creates an OpenGL `major.minor` core context, makes it current on this thread, and returns it
+/// along with a [`GlContext`] that loads procedures through EGL.
+pub fn context(major: i32, minor: i32) -> Option<(HeadlessContext, GlContext)> {
+    match HeadlessContext::new(major, minor) {
+        Ok(egl_ctx) => Some((egl_ctx, GlContext::new_with_loader(Box::new(EglProcLoader)))),
+        Err(e) => {
+            eprintln!("skipping: couldn't create a headless OpenGL {major}.{minor} context: {e}");
+            None
+        }
+    }
+}
+
+/// A fresh, empty directory that's removed when dropped.
+pub struct TempDir(pub PathBuf);
+
+impl TempDir {
+    /// Creates the directory, named after `name` and this process so that test binaries running
+    /// at the same time don't share it.
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "triangle-from-scratch-{name}-{}",
+            std::process::id()
+        ));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+
+    /// Writes `contents` to `name` inside the directory, returning its path.
+    pub fn write(&self, name: &str, contents: &str) -> PathBuf {
+        let path = self.0.join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.0).ok();
+    }
+}
@@ -0,0 +1,234 @@
+#![cfg(target_os = "linux")]
+
+extern crate triangle_from_scratch_gl as gl;
+
+mod common;
+
+use gl::{
+    bindings::prelude::*,
+    compute::{
+        bind_image_texture, memory_barrier, BarrierBits, ComputeError, ComputeLimits,
+        ComputePipeline, ImageAccess, WorkGroupError,
+    },
+    GlContext,
+};
+
+/// Creates a buffer bound to `target` and fills it with `data`.
+unsafe fn create_buffer(ctx: &GlContext, target: GLenum, data: &[u32]) -> GLuint {
+    let mut buffer = 0;
+    ctx.gl_gen_buffers(1, &mut buffer);
+    ctx.gl_bind_buffer(target, buffer);
+    ctx.gl_buffer_data(
+        target,
+        core::mem::size_of_val(data) as GLsizeiptr,
+        data.as_ptr().cast(),
+        GL_DYNAMIC_DRAW,
+    );
+    buffer
+}
+
+/// Reads `len` `u32`s back from the buffer bound to `target`.
+unsafe fn read_buffer(ctx: &GlContext, target: GLenum, len: usize) -> Vec<u32> {
+    let mut out = vec![0u32; len];
+    ctx.gl_get_buffer_sub_data(target, 0, (len * 4) as GLsizeiptr, out.as_mut_ptr().cast());
+    out
+}
+
+#[test]
+fn dispatch_writes_storage_buffer() {
+    let Some((_egl, ctx)) = common::context(4, 3) else {
+        return;
+    };
+
+    const SOURCE: &str = "#version 430 core
+        layout(std430, binding = 0) buffer Output { uint values[]; };
+        uniform uint count = 200u;
+
+        void main() {
+            uint i = gl_GlobalInvocationID.x;
+            if (i < count) {
+                values[i] = i * 2u;
+            }
+        }
+    ";
+
+    unsafe {
+        let pipeline = ComputePipeline::new(&ctx, SOURCE, [64, 1, 1]).unwrap();
+        assert_eq!(pipeline.local_size(), [64, 1, 1]);
+
+        let buffer = create_buffer(&ctx, GL_SHADER_STORAGE_BUFFER, &[u32::MAX; 256]);
+        ctx.gl_bind_buffer_base(GL_SHADER_STORAGE_BUFFER, 0, buffer);
+
+        // 200 invocations need 4 groups of 64; the last 56 invocations are skipped by the shader.
+        pipeline.dispatch_invocations(&ctx, [200, 1, 1]).unwrap();
+        memory_barrier(&ctx, BarrierBits::BUFFER_UPDATE);
+
+        let values = read_buffer(&ctx, GL_SHADER_STORAGE_BUFFER, 256);
+        for (i, &v) in values.iter().enumerate() {
+            let expected = if i < 200 { i as u32 * 2 } else { u32::MAX };
+            assert_eq!(v, expected, "values[{i}]");
+        }
+
+        assert_eq!(ctx.gl_get_error(), GL_NO_ERROR);
+        ctx.gl_delete_buffers(1, &buffer);
+        pipeline.delete(&ctx);
+    }
+}
+
+#[test]
+fn dispatch_stores_into_image() {
+    let Some((_egl, ctx)) = common::context(4, 3) else {
+        return;
+    };
+
+    const WIDTH: usize = 16;
+    const HEIGHT: usize = 8;
+    const SOURCE: &str = "#version 430 core
+        layout(r32f, binding = 3) uniform writeonly image2D img;
+
+        void main() {
+            ivec2 p = ivec2(gl_GlobalInvocationID.xy);
+            imageStore(img, p, vec4(float(p.x + p.y * 100)));
+        }
+    ";
+
+    unsafe {
+        let pipeline = ComputePipeline::new(&ctx, SOURCE, [8, 8, 1]).unwrap();
+
+        let mut texture = 0;
+        ctx.gl_gen_textures(1, &mut texture);
+        ctx.gl_bind_texture(GL_TEXTURE_2D, texture);
+        ctx.gl_tex_storage_2d(GL_TEXTURE_2D, 1, GL_R32F, WIDTH as _, HEIGHT as _);
+        bind_image_texture(&ctx, 3, texture, 0, None, ImageAccess::WriteOnly, GL_R32F);
+
+        pipeline
+            .dispatch_invocations(&ctx, [WIDTH as u32, HEIGHT as u32, 1])
+            .unwrap();
+        memory_barrier(&ctx, BarrierBits::TEXTURE_UPDATE);
+
+        let mut pixels = vec![0f32; WIDTH * HEIGHT];
+        ctx.gl_get_tex_image(
+            GL_TEXTURE_2D,
+            0,
+            GL_RED,
+            GL_FLOAT,
+            pixels.as_mut_ptr().cast(),
+        );
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                assert_eq!(
+                    pixels[y * WIDTH + x],
+                    (x + y * 100) as f32,
+                    "pixel ({x}, {y})"
+                );
+            }
+        }
+
+        assert_eq!(ctx.gl_get_error(), GL_NO_ERROR);
+        ctx.gl_delete_textures(1, &texture);
+        pipeline.delete(&ctx);
+    }
+}
+
+#[test]
+fn dispatch_indirect_reads_group_count_from_buffer() {
+    let Some((_egl, ctx)) = common::context(4, 3) else {
+        return;
+    };
+
+    const SOURCE: &str = "#version 430 core
+        layout(std430, binding = 0) buffer Output { uint values[]; };
+
+        void main() {
+            values[gl_GlobalInvocationID.x] = gl_WorkGroupID.x + 1u;
+        }
+    ";
+
+    unsafe {
+        let pipeline = ComputePipeline::new(&ctx, SOURCE, [4, 1, 1]).unwrap();
+
+        let output = create_buffer(&ctx, GL_SHADER_STORAGE_BUFFER, &[0; 16]);
+        ctx.gl_bind_buffer_base(GL_SHADER_STORAGE_BUFFER, 0, output);
+
+        // The second set of counts is the one we dispatch with.
+        let indirect = create_buffer(&ctx, GL_DISPATCH_INDIRECT_BUFFER, &[9, 9, 9, 3, 1, 1]);
+        pipeline.dispatch_indirect(&ctx, 12);
+        memory_barrier(&ctx, BarrierBits::BUFFER_UPDATE);
+
+        ctx.gl_bind_buffer(GL_SHADER_STORAGE_BUFFER, output);
+        let values = read_buffer(&ctx, GL_SHADER_STORAGE_BUFFER, 16);
+        assert_eq!(values, [1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 0, 0, 0, 0]);
+
+        assert_eq!(ctx.gl_get_error(), GL_NO_ERROR);
+        ctx.gl_delete_buffers(1, &indirect);
+        ctx.gl_delete_buffers(1, &output);
+        pipeline.delete(&ctx);
+    }
+}
+
+#[test]
+fn limits_are_enforced_before_compiling() {
+    let Some((_egl, ctx)) = common::context(4, 3) else {
+        return;
+    };
+
+    const SOURCE: &str = "#version 430 core\nvoid main() {}\n";
+
+    unsafe {
+        let limits = ComputeLimits::query(&ctx);
+        let min = ComputeLimits::MINIMUM;
+        for axis in 0..3 {
+            assert!(limits.max_local_size[axis] >= min.max_local_size[axis]);
+            assert!(limits.max_group_count[axis] >= min.max_group_count[axis]);
+        }
+        assert!(limits.max_invocations >= min.max_invocations);
+
+        let too_wide = [limits.max_local_size[0] + 1, 1, 1];
+        assert_eq!(
+            ComputePipeline::new(&ctx, SOURCE, too_wide).unwrap_err(),
+            ComputeError::WorkGroup(WorkGroupError::LocalSizeTooLarge {
+                axis: 0,
+                size: too_wide[0],
+                max: limits.max_local_size[0],
+            })
+        );
+
+        let too_many = [limits.max_local_size[0], 2, 1];
+        assert!(matches!(
+            ComputePipeline::new(&ctx, SOURCE, too_many),
+            Err(ComputeError::WorkGroup(
+                WorkGroupError::TooManyInvocations { .. }
+            ))
+        ));
+
+        let pipeline = ComputePipeline::new(&ctx, SOURCE, [1, 1, 1]).unwrap();
+        let too_many_groups = [1, 1, limits.max_group_count[2] + 1];
+        assert!(matches!(
+            pipeline.dispatch(&ctx, too_many_groups),
+            Err(WorkGroupError::GroupCountTooLarge { axis: 2, .. })
+        ));
+
+        assert_eq!(ctx.gl_get_error(), GL_NO_ERROR);
+        pipeline.delete(&ctx);
+    }
+}
+
+#[test]
+fn compile_errors_include_the_info_log() {
+    let Some((_egl, ctx)) = common::context(4, 3) else {
+        return;
+    };
+
+    const SOURCE: &str = "#version 430 core\nvoid main() { this_is_not_defined = 1; }\n";
+
+    unsafe {
+        match ComputePipeline::new(&ctx, SOURCE, [1, 1, 1]) {
+            Err(ComputeError::Shader(gl::shader::ShaderError::Compile { log, .. })) => {
+                assert!(log.contains("this_is_not_defined"), "{log}");
+                // The inserted layout line shouldn't shift line numbers.
+                assert!(log.contains("0:2"), "{log}");
+            }
+            other => panic!("expected a compile error, got {other:?}"),
+        }
+    }
+}
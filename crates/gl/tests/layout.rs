@@ -0,0 +1,112 @@
+#![cfg(target_os = "linux")]
+
+extern crate triangle_from_scratch_gl as gl;
+
+mod common;
+
+use gl::{
+    compute::ComputePipeline,
+    interface_block,
+    layout::{
+        verify_storage_block, verify_uniform_block, LayoutError, LayoutProblem, LayoutRules, Mat4,
+        Vec2, Vec3, Vec4,
+    },
+};
+
+const SOURCE: &str = "#version 430 core
+    layout(std140, binding = 0) uniform Camera {
+        mat4 view;
+        vec3 position;
+        float exposure;
+        vec2 jitter[3];
+    };
+
+    struct Light {
+        vec3 color;
+        float intensity;
+    };
+
+    layout(std430, binding = 0) buffer Lights {
+        vec2 offsets[3];
+        Light lights[2];
+        uint count;
+    };
+
+    void main() {
+        lights[0].color = position + view[0].xyz;
+        lights[1].intensity = exposure + jitter[2].x + offsets[2].y;
+        count = uint(lights[1].color.x + lights[0].intensity);
+    }
+";
+
+interface_block! {
+    struct Camera {
+        view: Mat4,
+        position: Vec3,
+        exposure: f32,
+        jitter: [Vec2; 3],
+    }
+}
+
+interface_block! {
+    struct Light {
+        color: Vec3,
+        intensity: f32,
+    }
+}
+
+interface_block! {
+    struct Lights {
+        offsets: [Vec2; 3],
+        lights: [Light; 2],
+        count: u32,
+    }
+}
+
+interface_block! {
+    /// `position` is a `vec3` in the shader, so `exposure` is packed in after it rather than
+    /// starting a new `vec4`.
+    struct PaddedCamera {
+        view: Mat4,
+        position: Vec4,
+        exposure: f32,
+        jitter: [Vec2; 3],
+    }
+}
+
+#[test]
+fn blocks_match_the_driver() {
+    let Some((_egl, ctx)) = common::context(4, 3) else {
+        return;
+    };
+
+    unsafe {
+        let pipeline = ComputePipeline::new(&ctx, SOURCE, [1, 1, 1]).unwrap();
+        let program = pipeline.program();
+
+        verify_uniform_block::<Camera>(&ctx, program, "Camera").unwrap();
+        verify_storage_block::<Lights>(&ctx, program, "Lights", LayoutRules::Std430).unwrap();
+
+        // std140 pads every array element out to 16 bytes, which std430 doesn't
+        assert!(matches!(
+            verify_storage_block::<Lights>(&ctx, program, "Lights", LayoutRules::Std140),
+            Err(LayoutError::Mismatch { .. })
+        ));
+
+        match verify_uniform_block::<PaddedCamera>(&ctx, program, "Camera") {
+            Err(LayoutError::Mismatch { problems, .. }) => {
+                assert!(problems.contains(&LayoutProblem::OffsetMismatch {
+                    name: "exposure".to_string(),
+                    expected: 80,
+                    actual: 76,
+                }));
+            }
+            other => panic!("{other:?}"),
+        }
+
+        assert!(matches!(
+            verify_uniform_block::<Camera>(&ctx, program, "Missing"),
+            Err(LayoutError::BlockNotFound { .. })
+        ));
+    }
+}
@@ -0,0 +1,159 @@
+#![cfg(target_os = "linux")]
+
+extern crate triangle_from_scratch_gl as gl;
+
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use common::TempDir;
+use gl::{
+    bindings::prelude::*,
+    program_cache::{CacheOutcome, ProgramCache, ShaderSource},
+    shader::ShaderError,
+    GlContext,
+};
+
+const SOURCES: [ShaderSource; 1] = [ShaderSource::new(
+    GL_COMPUTE_SHADER,
+    "#version 430 core
+    layout(local_size_x = 4) in;
+    layout(std430, binding = 0) buffer Output { uint values[]; };
+
+    void main() {
+        values[gl_GlobalInvocationID.x] = gl_GlobalInvocationID.x + 10u;
+    }
+    ",
+)];
+
+/// Runs `program` over a four-element buffer and checks the result, to make sure a program loaded
+/// from the cache actually works.
+unsafe fn assert_program_works(ctx: &GlContext, program: GLuint) {
+    let mut buffer = 0;
+    ctx.gl_gen_buffers(1, &mut buffer);
+    ctx.gl_bind_buffer(GL_SHADER_STORAGE_BUFFER, buffer);
+    ctx.gl_buffer_data(
+        GL_SHADER_STORAGE_BUFFER,
+        16,
+        [0u32; 4].as_ptr().cast(),
+        GL_DYNAMIC_DRAW,
+    );
+    ctx.gl_bind_buffer_base(GL_SHADER_STORAGE_BUFFER, 0, buffer);
+
+    ctx.gl_use_program(program);
+    ctx.gl_dispatch_compute(1, 1, 1);
+    ctx.gl_memory_barrier(GL_BUFFER_UPDATE_BARRIER_BIT);
+
+    let mut values = [0u32; 4];
+    ctx.gl_get_buffer_sub_data(GL_SHADER_STORAGE_BUFFER, 0, 16, values.as_mut_ptr().cast());
+    assert_eq!(values, [10, 11, 12, 13]);
+
+    ctx.gl_delete_buffers(1, &buffer);
+    assert_eq!(ctx.gl_get_error(), GL_NO_ERROR);
+}
+
+/// Returns the only entry in the cache directory.
+fn only_entry(cache: &ProgramCache) -> PathBuf {
+    let entries: Vec<_> = fs::read_dir(cache.dir())
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    assert_eq!(entries.len(), 1, "{entries:?}");
+    entries.into_iter().next().unwrap()
+}
+
+#[test]
+fn second_load_hits_the_cache() {
+    let Some((_egl, ctx)) = common::context(4, 3) else {
+        return;
+    };
+    let dir = TempDir::new("program-cache-hit");
+    let cache = ProgramCache::new(&dir.0);
+
+    unsafe {
+        let (program, outcome) = cache.load_or_build(&ctx, &SOURCES).unwrap();
+        if outcome == CacheOutcome::Unsupported {
+            eprintln!("skipping: driver doesn't support program binaries");
+            return;
+        }
+        assert_eq!(outcome, CacheOutcome::Miss);
+        assert_program_works(&ctx, program);
+        ctx.gl_delete_program(program);
+
+        let entry = only_entry(&cache);
+
+        let (program, outcome) = cache.load_or_build(&ctx, &SOURCES).unwrap();
+        assert_eq!(outcome, CacheOutcome::Hit);
+        assert_program_works(&ctx, program);
+        ctx.gl_delete_program(program);
+
+        assert_eq!(only_entry(&cache), entry);
+    }
+}
+
+#[test]
+fn rejected_binaries_fall_back_to_source() {
+    let Some((_egl, ctx)) = common::context(4, 3) else {
+        return;
+    };
+    let dir = TempDir::new("program-cache-stale");
+    let cache = ProgramCache::new(&dir.0);
+
+    unsafe {
+        let (program, outcome) = cache.load_or_build(&ctx, &SOURCES).unwrap();
+        if outcome == CacheOutcome::Unsupported {
+            eprintln!("skipping: driver doesn't support program binaries");
+            return;
+        }
+        ctx.gl_delete_program(program);
+
+        // Scribble over the driver's part of the entry, leaving our own header intact. The driver
+        // should refuse to load it.
+        let entry = only_entry(&cache);
+        let mut corrupted = fs::read(&entry).unwrap();
+        let header_len = 28; // magic, version, key, binary format, and length
+        for b in &mut corrupted[header_len..] {
+            *b = !*b;
+        }
+        fs::write(&entry, &corrupted).unwrap();
+
+        let (program, outcome) = cache.load_or_build(&ctx, &SOURCES).unwrap();
+        assert_eq!(outcome, CacheOutcome::Stale);
+        assert_program_works(&ctx, program);
+        ctx.gl_delete_program(program);
+
+        // The entry should have been replaced with a working one.
+        assert_ne!(fs::read(&entry).unwrap(), corrupted);
+        let (program, outcome) = cache.load_or_build(&ctx, &SOURCES).unwrap();
+        assert_eq!(outcome, CacheOutcome::Hit);
+        ctx.gl_delete_program(program);
+
+        // Entries that aren't even ours are treated the same way.
+        fs::write(&entry, b"not a cache entry").unwrap();
+        let (program, outcome) = cache.load_or_build(&ctx, &SOURCES).unwrap();
+        assert_eq!(outcome, CacheOutcome::Stale);
+        ctx.gl_delete_program(program);
+    }
+}
+
+#[test]
+fn compile_errors_are_not_cached() {
+    let Some((_egl, ctx)) = common::context(4, 3) else {
+        return;
+    };
+    let dir = TempDir::new("program-cache-error");
+    let cache = ProgramCache::new(&dir.0);
+
+    let broken = [ShaderSource::new(
+        GL_COMPUTE_SHADER,
+        "#version 430 core\nlayout(local_size_x = 1) in;\nvoid main() { nope(); }\n",
+    )];
+
+    unsafe {
+        assert!(matches!(
+            cache.load_or_build(&ctx, &broken),
+            Err(ShaderError::Compile { .. })
+        ));
+    }
+    assert!(!cache.dir().exists() || fs::read_dir(cache.dir()).unwrap().next().is_none());
+}
@@ -0,0 +1,156 @@
+#![cfg(target_os = "linux")]
+
+extern crate triangle_from_scratch_gl as gl;
+
+mod common;
+
+use std::{fs, time::Duration};
+
+use common::TempDir;
+use gl::{
+    bindings::prelude::*,
+    program_manager::{ProgramManager, ReloadError},
+    shader::ShaderError,
+    GlContext,
+};
+use glsl::preprocess::{GlslVersion, PreprocessError};
+use watch::FileWatcher;
+
+const MAIN: &str = "#include \"value.glsl\"
+layout(local_size_x = 4) in;
+layout(std430, binding = 0) buffer Output { uint values[]; };
+
+void main() {
+    values[gl_GlobalInvocationID.x] = VALUE;
+}
+";
+
+/// Runs `program` over a four-element buffer and returns what it wrote.
+unsafe fn run(ctx: &GlContext, program: GLuint) -> [u32; 4] {
+    let mut buffer = 0;
+    ctx.gl_gen_buffers(1, &mut buffer);
+    ctx.gl_bind_buffer(GL_SHADER_STORAGE_BUFFER, buffer);
+    ctx.gl_buffer_data(
+        GL_SHADER_STORAGE_BUFFER,
+        16,
+        [0u32; 4].as_ptr().cast(),
+        GL_DYNAMIC_DRAW,
+    );
+    ctx.gl_bind_buffer_base(GL_SHADER_STORAGE_BUFFER, 0, buffer);
+
+    ctx.gl_use_program(program);
+    ctx.gl_dispatch_compute(1, 1, 1);
+    ctx.gl_memory_barrier(GL_BUFFER_UPDATE_BARRIER_BIT);
+
+    let mut values = [0u32; 4];
+    ctx.gl_get_buffer_sub_data(GL_SHADER_STORAGE_BUFFER, 0, 16, values.as_mut_ptr().cast());
+    ctx.gl_delete_buffers(1, &buffer);
+    assert_eq!(ctx.gl_get_error(), GL_NO_ERROR);
+    values
+}
+
+fn manager(dir: &TempDir) -> ProgramManager {
+    ProgramManager::new(&dir.0, GlslVersion::GL_430_CORE).stage(GL_COMPUTE_SHADER, "main.comp")
+}
+
+#[test]
+fn failed_reloads_keep_the_last_good_program() {
+    let Some((_egl, ctx)) = common::context(4, 3) else {
+        return;
+    };
+    let dir = TempDir::new("program-manager-keep");
+    let main = dir.write("main.comp", MAIN);
+    let value = dir.write("value.glsl", "#define VALUE 1u\n");
+
+    let mut shaders = manager(&dir);
+    unsafe {
+        let first = shaders.reload(&ctx).unwrap();
+        assert_eq!(shaders.program(), Some(first));
+        assert_eq!(shaders.files(), [main.clone(), value.clone()]);
+        assert_eq!(run(&ctx, first), [1; 4]);
+
+        // Break the included file. The error should point at it, not at the generated source.
+        fs::write(&value, "#define VALUE 1u\nuint broken = ;\n").unwrap();
+        match shaders.reload_if_changed(&ctx, std::slice::from_ref(&value)) {
+            Some(Err(ReloadError::Shader(ShaderError::Compile { log, .. }))) => {
+                assert!(log.contains("value.glsl:2"), "{log}");
+            }
+            other => panic!("expected a compile error, got {other:?}"),
+        }
+        assert_eq!(shaders.program(), Some(first));
+        assert_eq!(run(&ctx, first), [1; 4]);
+
+        // Fixing it swaps in the new program and deletes the old one.
+        fs::write(&value, "#define VALUE 2u\n").unwrap();
+        let second = shaders
+            .reload_if_changed(&ctx, std::slice::from_ref(&value))
+            .unwrap()
+            .unwrap();
+        assert_eq!(shaders.program(), Some(second));
+        assert_eq!(run(&ctx, second), [2; 4]);
+        assert_eq!(ctx.gl_is_program(first), GL_FALSE);
+
+        // Files that didn't go into the program don't trigger a rebuild.
+        let unrelated = dir.write("unrelated.glsl", "");
+        assert!(shaders.reload_if_changed(&ctx, &[unrelated]).is_none());
+        assert_eq!(shaders.program(), Some(second));
+
+        // programs that are in use are only flagged for deletion
+        ctx.gl_use_program(0);
+        shaders.delete(&ctx);
+        assert_eq!(ctx.gl_is_program(second), GL_FALSE);
+    }
+}
+
+#[test]
+fn missing_includes_are_watched_until_they_exist() {
+    let Some((_egl, ctx)) = common::context(4, 3) else {
+        return;
+    };
+    let dir = TempDir::new("program-manager-missing");
+    let main = dir.write("main.comp", MAIN);
+    let value = dir.0.join("value.glsl");
+
+    let mut shaders = manager(&dir);
+    unsafe {
+        assert!(matches!(
+            shaders.reload(&ctx),
+            Err(ReloadError::Preprocess(PreprocessError::Io { .. }))
+        ));
+        assert_eq!(shaders.program(), None);
+        assert_eq!(shaders.files(), [main, value.clone()]);
+
+        fs::write(&value, "#define VALUE 3u\n").unwrap();
+        let program = shaders.reload_if_changed(&ctx, &[&value]).unwrap().unwrap();
+        assert_eq!(run(&ctx, program), [3; 4]);
+        shaders.delete(&ctx);
+    }
+}
+
+#[test]
+fn file_watcher_drives_reloads() {
+    let Some((_egl, ctx)) = common::context(4, 3) else {
+        return;
+    };
+    let dir = TempDir::new("program-manager-watch");
+    dir.write("main.comp", MAIN);
+    let value = dir.write("value.glsl", "#define VALUE 4u\n");
+
+    let mut shaders = manager(&dir);
+    let mut watcher = FileWatcher::new().unwrap();
+    unsafe {
+        shaders.reload(&ctx).unwrap();
+        for file in shaders.files() {
+            watcher.watch(file).unwrap();
+        }
+        assert!(watcher.poll().unwrap().is_empty());
+
+        fs::write(&value, "#define VALUE 5u\n").unwrap();
+        let changed = watcher.wait(Some(Duration::from_secs(5))).unwrap();
+        assert_eq!(changed, [value]);
+
+        let program = shaders.reload_if_changed(&ctx, &changed).unwrap().unwrap();
+        assert_eq!(run(&ctx, program), [5; 4]);
+        shaders.delete(&ctx);
+    }
+}
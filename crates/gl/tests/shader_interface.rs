@@ -0,0 +1,51 @@
+//! Checks the triangle's shaders against each other and against its vertex layout, without a GPU.
+
+extern crate triangle_from_scratch_gl as gl;
+
+#[allow(dead_code)]
+#[path = "../../../src/triangle.rs"]
+mod triangle;
+
+use glsl::{
+    preprocess::{GlslVersion, Preprocessor},
+    validate::{Stage, Validator},
+};
+use triangle::{SHADER_FILES, TRIANGLE_LAYOUT};
+
+#[test]
+fn triangle_shaders_match_each_other_and_the_vertex_layout() {
+    // the same preprocessing main.rs does
+    let preprocessor = Preprocessor::new(GlslVersion::GL_460_CORE, SHADER_FILES);
+    let vertex = preprocessor.run("vertex.vs").unwrap();
+    let fragment = preprocessor.run("fragment.fs").unwrap();
+
+    let diagnostics = Validator::new()
+        .preprocessed_stage(Stage::Vertex, &vertex)
+        .preprocessed_stage(Stage::Fragment, &fragment)
+        .vertex_inputs(&TRIANGLE_LAYOUT.shader_inputs())
+        .run();
+
+    let report: Vec<_> = diagnostics.iter().map(|d| d.to_string()).collect();
+    assert!(report.is_empty(), "{}", report.join("\n"));
+}
+
+#[test]
+fn layout_changes_are_caught() {
+    let preprocessor = Preprocessor::new(GlslVersion::GL_460_CORE, SHADER_FILES);
+    let vertex = preprocessor.run("vertex.vs").unwrap();
+
+    // drop the color attribute
+    let mut inputs = TRIANGLE_LAYOUT.shader_inputs();
+    inputs.pop();
+    let diagnostics = Validator::new()
+        .preprocessed_stage(Stage::Vertex, &vertex)
+        .vertex_inputs(&inputs)
+        .run();
+
+    assert!(
+        diagnostics
+            .iter()
+            .any(|d| d.is_error() && d.file == "vertex.vs" && d.message.contains("location 1")),
+        "{diagnostics:#?}"
+    );
+}
@@ -0,0 +1,74 @@
+#![cfg(target_os = "linux")]
+
+extern crate triangle_from_scratch_gl as gl;
+
+mod common;
+
+use std::{cell::RefCell, rc::Rc};
+
+use gl::{
+    bindings::prelude::*,
+    surface::{Surface, SurfaceTarget},
+    GlContext,
+};
+
+/// Remembers what it's told.
+#[derive(Default)]
+struct Recorder {
+    changes: Vec<([u32; 2], f64)>,
+}
+
+impl SurfaceTarget for Recorder {
+    fn surface_changed(&mut self, _ctx: &GlContext, size: [u32; 2], scale_factor: f64) {
+        self.changes.push((size, scale_factor));
+    }
+}
+
+unsafe fn get_rect(ctx: &GlContext, pname: GLenum) -> [GLint; 4] {
+    let mut rect = [0; 4];
+    ctx.gl_get_integer_v(pname, rect.as_mut_ptr());
+    rect
+}
+
+#[test]
+fn viewport_follows_the_surface() {
+    let Some((_egl, ctx)) = common::context(3, 3) else {
+        return;
+    };
+    let mut surface = Surface::new([640, 480], 1.0);
+    let target = Rc::new(RefCell::new(Recorder::default()));
+    surface.add_target(&target);
+
+    unsafe {
+        assert!(surface.prepare(&ctx));
+        assert_eq!(get_rect(&ctx, GL_VIEWPORT), [0, 0, 640, 480]);
+        assert_eq!(get_rect(&ctx, GL_SCISSOR_BOX), [0, 0, 640, 480]);
+
+        // the viewport is put back even if nothing changed, but targets only hear about changes
+        ctx.gl_viewport(0, 0, 16, 16);
+        assert!(surface.prepare(&ctx));
+        assert_eq!(get_rect(&ctx, GL_VIEWPORT), [0, 0, 640, 480]);
+        assert_eq!(target.borrow().changes, [([640, 480], 1.0)]);
+
+        // minimized, so there's nothing to draw or reallocate
+        surface.resize([0, 0]);
+        assert!(!surface.prepare(&ctx));
+        assert_eq!(get_rect(&ctx, GL_VIEWPORT), [0, 0, 640, 480]);
+
+        // restored onto a monitor with a different DPI
+        surface.resize([960, 720]);
+        surface.set_scale_factor(1.5);
+        assert!(surface.prepare(&ctx));
+        assert_eq!(get_rect(&ctx, GL_VIEWPORT), [0, 0, 960, 720]);
+        assert_eq!(get_rect(&ctx, GL_SCISSOR_BOX), [0, 0, 960, 720]);
+        assert_eq!(
+            target.borrow().changes,
+            [([640, 480], 1.0), ([960, 720], 1.5)]
+        );
+
+        // targets that have been dropped are forgotten
+        drop(target);
+        surface.resize([100, 100]);
+        assert!(surface.prepare(&ctx));
+    }
+}
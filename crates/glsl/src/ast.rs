@@ -0,0 +1,311 @@
+//! The declarations that make up a GLSL shader, as produced by [`parse`](crate::parser::parse).
+//!
+//! Only the outside of a shader is described in any detail: global variables, interface blocks,
+//! structs, and function signatures. Function bodies are kept as plain tokens, since nothing here
+//! needs to understand statements or expressions.
+
+use crate::{lexer::Token, preprocess::GlslVersion};
+
+/// A whole shader.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranslationUnit<'a> {
+    /// The `#version` directive, if there was one.
+    pub version: Option<VersionDirective>,
+    /// Every `#extension` directive that wasn't skipped by conditional compilation.
+    pub extensions: Vec<ExtensionDirective<'a>>,
+    pub declarations: Vec<Declaration<'a>>,
+    /// Every floating-point or integer literal with a suffix, for checking which versions allow
+    /// them.
+    pub suffixed_literals: Vec<Token<'a>>,
+}
+
+impl<'a> TranslationUnit<'a> {
+    /// Every global variable declaration, not including interface block members.
+    pub fn variables(&self) -> impl Iterator<Item = &Variable<'a>> {
+        self.declarations.iter().filter_map(|d| match d {
+            Declaration::Variable(v) => Some(v),
+            _ => None,
+        })
+    }
+
+    /// Every interface block.
+    pub fn blocks(&self) -> impl Iterator<Item = &Block<'a>> {
+        self.declarations.iter().filter_map(|d| match d {
+            Declaration::Block(b) => Some(b),
+            _ => None,
+        })
+    }
+
+    /// Every function definition or prototype.
+    pub fn functions(&self) -> impl Iterator<Item = &Function<'a>> {
+        self.declarations.iter().filter_map(|d| match d {
+            Declaration::Function(f) => Some(f),
+            _ => None,
+        })
+    }
+
+    /// Returns whether the extension `name` was enabled (or required, or warned about).
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.extensions
+            .iter()
+            .any(|e| (e.name == name || e.name == "all") && e.behavior != "disable")
+    }
+}
+
+/// A `#version` directive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionDirective {
+    /// The version, with the profile filled in with its default if it wasn't written.
+    pub version: GlslVersion,
+    /// Whether the profile was written out.
+    pub explicit_profile: bool,
+    pub line: u32,
+}
+
+/// An `#extension name : behavior` directive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtensionDirective<'a> {
+    pub name: &'a str,
+    /// One of `require`, `enable`, `warn` or `disable`.
+    pub behavior: &'a str,
+    pub line: u32,
+}
+
+/// Anything that can appear at the top level of a shader.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Declaration<'a> {
+    Variable(Variable<'a>),
+    Block(Block<'a>),
+    Struct(Struct<'a>),
+    Function(Function<'a>),
+    /// A default precision, like `precision highp float;`.
+    Precision {
+        precision: &'a str,
+        ty: TypeSpecifier<'a>,
+        line: u32,
+    },
+    /// Qualifiers with nothing to apply to, like `layout(local_size_x = 8) in;`.
+    Qualifiers {
+        qualifiers: Qualifiers<'a>,
+        line: u32,
+    },
+    /// Redeclares existing variables as invariant, like `invariant gl_Position;`.
+    Invariant {
+        names: Vec<&'a str>,
+        line: u32,
+    },
+}
+
+/// The storage qualifier of a variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Storage {
+    Const,
+    In,
+    Out,
+    /// Only valid on function parameters.
+    InOut,
+    Uniform,
+    Buffer,
+    Shared,
+    /// The pre-1.30 spelling of a vertex shader `in`.
+    Attribute,
+    /// The pre-1.30 spelling of `out` in a vertex shader, and `in` in a fragment shader.
+    Varying,
+}
+
+impl Storage {
+    pub fn from_keyword(word: &str) -> Option<Self> {
+        Some(match word {
+            "const" => Self::Const,
+            "in" => Self::In,
+            "out" => Self::Out,
+            "inout" => Self::InOut,
+            "uniform" => Self::Uniform,
+            "buffer" => Self::Buffer,
+            "shared" => Self::Shared,
+            "attribute" => Self::Attribute,
+            "varying" => Self::Varying,
+            _ => return None,
+        })
+    }
+
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Self::Const => "const",
+            Self::In => "in",
+            Self::Out => "out",
+            Self::InOut => "inout",
+            Self::Uniform => "uniform",
+            Self::Buffer => "buffer",
+            Self::Shared => "shared",
+            Self::Attribute => "attribute",
+            Self::Varying => "varying",
+        }
+    }
+}
+
+/// An interpolation qualifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Interpolation {
+    Smooth,
+    Flat,
+    NoPerspective,
+}
+
+impl Interpolation {
+    pub fn from_keyword(word: &str) -> Option<Self> {
+        Some(match word {
+            "smooth" => Self::Smooth,
+            "flat" => Self::Flat,
+            "noperspective" => Self::NoPerspective,
+            _ => return None,
+        })
+    }
+
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Self::Smooth => "smooth",
+            Self::Flat => "flat",
+            Self::NoPerspective => "noperspective",
+        }
+    }
+}
+
+/// The value of a layout qualifier or the size of an array, when it's written as an expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstValue {
+    /// An integer constant expression that could be evaluated without knowing about any
+    /// variables.
+    Int(i64),
+    /// Anything else, kept as text.
+    Expr(String),
+}
+
+/// One `name` or `name = value` inside `layout(...)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutQualifier<'a> {
+    pub name: &'a str,
+    pub value: Option<ConstValue>,
+}
+
+/// All the qualifiers in front of a declaration.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Qualifiers<'a> {
+    pub layout: Vec<LayoutQualifier<'a>>,
+    pub storage: Option<Storage>,
+    pub interpolation: Option<Interpolation>,
+    pub centroid: bool,
+    pub sample: bool,
+    pub patch: bool,
+    pub invariant: bool,
+    pub precise: bool,
+    /// `highp`, `mediump` or `lowp`.
+    pub precision: Option<&'a str>,
+    /// `coherent`, `volatile`, `restrict`, `readonly` and `writeonly`, in the order written.
+    pub memory: Vec<&'a str>,
+}
+
+impl<'a> Qualifiers<'a> {
+    /// Returns the last layout qualifier called `name`, since later ones override earlier ones.
+    pub fn layout(&self, name: &str) -> Option<&LayoutQualifier<'a>> {
+        self.layout.iter().rev().find(|l| l.name == name)
+    }
+
+    /// The value of `layout(location = N)`, if it was given and could be evaluated.
+    pub fn location(&self) -> Option<i64> {
+        match self.layout("location")?.value {
+            Some(ConstValue::Int(location)) => Some(location),
+            _ => None,
+        }
+    }
+
+    /// Returns whether nothing at all was written.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The size of one dimension of an array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArraySize {
+    /// `[]`
+    Unsized,
+    Sized(ConstValue),
+}
+
+/// A type, like `vec3`, `float[4]` or the name of a struct.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeSpecifier<'a> {
+    /// The type name. Anonymous structs are given an empty name.
+    pub name: &'a str,
+    /// Array dimensions, outermost first.
+    pub array: Vec<ArraySize>,
+}
+
+impl TypeSpecifier<'_> {
+    /// The type with its outermost array dimension removed, as seen by a single vertex of a
+    /// geometry or tessellation shader's per-vertex inputs.
+    pub fn element(&self) -> Self {
+        Self {
+            name: self.name,
+            array: self.array.iter().skip(1).cloned().collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for TypeSpecifier<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name)?;
+        for dim in &self.array {
+            match dim {
+                ArraySize::Unsized => f.write_str("[]")?,
+                ArraySize::Sized(ConstValue::Int(n)) => write!(f, "[{n}]")?,
+                ArraySize::Sized(ConstValue::Expr(e)) => write!(f, "[{e}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A variable, struct member, block member, or function parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variable<'a> {
+    pub qualifiers: Qualifiers<'a>,
+    /// The type, including any array dimensions written after the name.
+    pub ty: TypeSpecifier<'a>,
+    /// The name. Unnamed function parameters have an empty name.
+    pub name: &'a str,
+    pub line: u32,
+}
+
+/// An interface block, like `uniform Camera { mat4 view; } camera;`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block<'a> {
+    pub qualifiers: Qualifiers<'a>,
+    /// The block name, which is what stages and the API use to match blocks up.
+    pub name: &'a str,
+    pub members: Vec<Variable<'a>>,
+    /// The instance name, if there is one, along with its array dimensions.
+    pub instance: Option<(&'a str, Vec<ArraySize>)>,
+    pub line: u32,
+}
+
+/// A struct definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Struct<'a> {
+    /// The struct name, or empty for an anonymous struct.
+    pub name: &'a str,
+    pub members: Vec<Variable<'a>>,
+    pub line: u32,
+}
+
+/// A function definition, or a prototype if it has no body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function<'a> {
+    pub return_type: TypeSpecifier<'a>,
+    pub name: &'a str,
+    pub params: Vec<Variable<'a>>,
+    /// The tokens between the braces, or `None` for a prototype.
+    pub body: Option<Vec<Token<'a>>>,
+    pub line: u32,
+}
@@ -0,0 +1,213 @@
+//! Validates the stages of a GLSL program without a GPU.
+//!
+//! ```text
+//! glsl-validate [OPTIONS] FILE...
+//! ```
+//!
+//! Each file's stage is worked out from its extension (`.vs`/`.vert`, `.fs`/`.frag`, and so on),
+//! and all of the files are checked together as one program. Diagnostics are printed like a
+//! compiler's, and the exit code is 1 if there were any errors.
+
+use std::{env, fs, path::Path, process::ExitCode};
+
+use triangle_from_scratch_glsl::{
+    preprocess::{FileSystem, GlslVersion, Preprocessed, Preprocessor, Profile},
+    validate::{AttributeKind, Stage, Validator, VertexInput},
+};
+
+const USAGE: &str = "\
+usage: glsl-validate [OPTIONS] FILE...
+
+Checks the shader stages in FILE... against each other, like a linker would.
+
+options:
+  --version N [core|compatibility|es]
+                         the #version to preprocess with (default: 460 core)
+  -D NAME[=VALUE]        #define NAME before preprocessing
+  --raw                  check the files as written, without expanding #includes or
+                         replacing their #version
+  --attribute LOC=TYPE   declare a vertex attribute at location LOC that's read as
+                         TYPE (e.g. 0=vec3 or 2=ivec4), and check the vertex shader's
+                         inputs against the attributes given
+  -h, --help             show this message";
+
+struct Options {
+    version: GlslVersion,
+    defines: Vec<(String, String)>,
+    raw: bool,
+    attributes: Option<Vec<VertexInput>>,
+    files: Vec<String>,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("glsl-validate: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    // Load (and maybe preprocess) everything up front, since the validator borrows the sources.
+    let mut sources = Vec::new();
+    for file in &options.files {
+        let path = Path::new(file);
+        let Some(stage) = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(Stage::from_extension)
+        else {
+            eprintln!("glsl-validate: can't tell which stage `{file}` is from its extension");
+            return ExitCode::from(2);
+        };
+
+        let source = if options.raw {
+            fs::read_to_string(path)
+                .map(Source::Raw)
+                .map_err(|e| e.to_string())
+        } else {
+            let dir = path.parent().unwrap_or(Path::new("."));
+            let name = path.file_name().unwrap().to_string_lossy();
+            let mut preprocessor = Preprocessor::new(options.version, FileSystem::new(dir));
+            for (name, value) in &options.defines {
+                preprocessor = preprocessor.define(name, value);
+            }
+            preprocessor
+                .run(&name)
+                .map(Source::Preprocessed)
+                .map_err(|e| e.to_string())
+        };
+        match source {
+            Ok(source) => sources.push((stage, file, source)),
+            Err(e) => {
+                eprintln!("{file}: error: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let mut validator = Validator::new();
+    for (stage, file, source) in &sources {
+        validator = match source {
+            Source::Raw(text) => validator.stage(*stage, file, text),
+            Source::Preprocessed(preprocessed) => {
+                validator.preprocessed_stage(*stage, preprocessed)
+            }
+        };
+    }
+    if let Some(attributes) = &options.attributes {
+        validator = validator.vertex_inputs(attributes);
+    }
+
+    let diagnostics = validator.run();
+    for diagnostic in &diagnostics {
+        eprintln!("{diagnostic}");
+    }
+    if diagnostics.iter().any(|d| d.is_error()) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+enum Source {
+    Raw(String),
+    Preprocessed(Preprocessed),
+}
+
+/// Parses the command line, returning `None` if help was asked for.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        version: GlslVersion::GL_460_CORE,
+        defines: Vec::new(),
+        raw: false,
+        attributes: None,
+        files: Vec::new(),
+    };
+
+    let mut pending_profile = false;
+    while let Some(arg) = args.next() {
+        // `--version 330 es`: the profile is optional, so it's only known once the next argument is
+        // seen.
+        if pending_profile {
+            pending_profile = false;
+            let profile = match arg.as_str() {
+                "core" => Some(Profile::Core),
+                "compatibility" => Some(Profile::Compatibility),
+                "es" => Some(Profile::Es),
+                _ => None,
+            };
+            if let Some(profile) = profile {
+                options.version.profile = profile;
+                continue;
+            }
+        }
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--raw" => options.raw = true,
+            "--version" => {
+                let number = args.next().ok_or("--version needs a version number")?;
+                let number = number
+                    .parse()
+                    .map_err(|_| format!("`{number}` isn't a version number"))?;
+                let profile = match number {
+                    100 | 300 | 310 | 320 => Profile::Es,
+                    150.. => Profile::Core,
+                    _ => Profile::Compatibility,
+                };
+                options.version = GlslVersion::new(number, profile);
+                pending_profile = true;
+            }
+            "--attribute" => {
+                let spec = args.next().ok_or("--attribute needs LOC=TYPE")?;
+                options
+                    .attributes
+                    .get_or_insert_with(Vec::new)
+                    .push(parse_attribute(&spec)?);
+            }
+            _ if arg.starts_with("-D") => {
+                let define = match &arg[2..] {
+                    "" => args.next().ok_or("-D needs a macro name")?,
+                    define => define.to_string(),
+                };
+                let (name, value) = define.split_once('=').unwrap_or((&define, ""));
+                options.defines.push((name.to_string(), value.to_string()));
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+            _ => options.files.push(arg),
+        }
+    }
+
+    if options.files.is_empty() {
+        return Err("no files given".to_string());
+    }
+    Ok(Some(options))
+}
+
+/// Parses an attribute like `0=vec3`.
+fn parse_attribute(spec: &str) -> Result<VertexInput, String> {
+    let invalid = || format!("`{spec}` isn't an attribute like `0=vec3`");
+    let (location, ty) = spec.split_once('=').ok_or_else(invalid)?;
+    let location = location.parse().map_err(|_| invalid())?;
+    let (kind, vector) = match ty {
+        "float" => (AttributeKind::Float, "vec1"),
+        "int" | "uint" => (AttributeKind::Integer, "vec1"),
+        "double" => (AttributeKind::Double, "vec1"),
+        _ => match ty.as_bytes().first() {
+            Some(b'i' | b'u') => (AttributeKind::Integer, &ty[1..]),
+            Some(b'd') => (AttributeKind::Double, &ty[1..]),
+            _ => (AttributeKind::Float, ty),
+        },
+    };
+    let components = vector
+        .strip_prefix("vec")
+        .and_then(|n| n.parse().ok())
+        .filter(|n| (1..=4).contains(n))
+        .ok_or_else(invalid)?;
+    Ok(VertexInput::new(location, components, kind))
+}
@@ -0,0 +1,422 @@
+//! Splits GLSL source into tokens.
+//!
+//! Comments are skipped and preprocessor directives come out as a single [`TokenKind::Directive`]
+//! token each, so the parser can decide what to do with them. Line continuations (a `\` at the end
+//! of a line) aren't supported, since GLSL only allows them from version 4.20 onwards and nothing
+//! here uses them.
+
+use std::fmt;
+
+/// What sort of token a [`Token`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// An identifier or keyword, like `vec3`, `main` or `layout`.
+    Identifier,
+    /// An integer literal, like `1`, `0x1F` or `3u`.
+    IntConstant,
+    /// A floating-point literal, like `1.0`, `.5e3` or `2.0lf`.
+    FloatConstant,
+    /// An operator or piece of punctuation, like `+=`, `(` or `;`.
+    Punct,
+    /// A whole preprocessor directive line, from the `#` to the end of the line.
+    Directive,
+}
+
+/// A token, borrowing its text from the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    /// The 1-based line the token starts on.
+    pub line: u32,
+    /// The 1-based byte column the token starts at.
+    pub column: u32,
+}
+
+impl Token<'_> {
+    /// Returns whether this is the punctuation `p`.
+    pub fn is_punct(&self, p: &str) -> bool {
+        self.kind == TokenKind::Punct && self.text == p
+    }
+
+    /// Returns whether this is the identifier or keyword `name`.
+    pub fn is_ident(&self, name: &str) -> bool {
+        self.kind == TokenKind::Identifier && self.text == name
+    }
+}
+
+/// A character that can't start any token, or a malformed number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexError {
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for LexError {}
+
+/// Operators and punctuation, longest first so that the first match is the right one.
+const PUNCTUATION: &[&str] = &[
+    "<<=", ">>=", "++", "--", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "^^", "+=", "-=",
+    "*=", "/=", "%=", "&=", "|=", "^=", "(", ")", "[", "]", "{", "}", ".", ",", ";", ":", "?", "+",
+    "-", "*", "/", "%", "<", ">", "!", "~", "&", "|", "^", "=",
+];
+
+/// Splits `source` into tokens.
+pub fn tokenize(source: &str) -> Result<Vec<Token<'_>>, LexError> {
+    let mut lexer = Lexer {
+        source,
+        pos: 0,
+        line: 1,
+        line_start: 0,
+        at_line_start: true,
+    };
+    let mut tokens = Vec::new();
+    while let Some(token) = lexer.next_token()? {
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+struct Lexer<'a> {
+    source: &'a str,
+    pos: usize,
+    line: u32,
+    /// Byte offset of the start of the current line, for working out columns.
+    line_start: usize,
+    /// Whether only whitespace has been seen on the current line so far.
+    at_line_start: bool,
+}
+
+impl<'a> Lexer<'a> {
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    fn peek_byte(&self, offset: usize) -> Option<u8> {
+        self.source.as_bytes().get(self.pos + offset).copied()
+    }
+
+    fn error(&self, message: impl Into<String>) -> LexError {
+        LexError {
+            line: self.line,
+            column: (self.pos - self.line_start) as u32 + 1,
+            message: message.into(),
+        }
+    }
+
+    /// Moves past `len` bytes, keeping track of line numbers.
+    fn advance(&mut self, len: usize) {
+        for (i, b) in self.source.as_bytes()[self.pos..self.pos + len]
+            .iter()
+            .enumerate()
+        {
+            if *b == b'\n' {
+                self.line += 1;
+                self.line_start = self.pos + i + 1;
+                self.at_line_start = true;
+            }
+        }
+        self.pos += len;
+    }
+
+    /// Skips whitespace and comments.
+    fn skip_trivia(&mut self) -> Result<(), LexError> {
+        loop {
+            let rest = self.rest();
+            if let Some(c) = rest.chars().next().filter(|c| c.is_whitespace()) {
+                self.advance(c.len_utf8());
+            } else if rest.starts_with("//") {
+                self.advance(rest.find('\n').unwrap_or(rest.len()));
+            } else if let Some(comment) = rest.strip_prefix("/*") {
+                let Some(end) = comment.find("*/") else {
+                    return Err(self.error("unterminated block comment"));
+                };
+                // a block comment doesn't stop a directive from being first on its line
+                let at_line_start = self.at_line_start;
+                self.advance(end + 4);
+                self.at_line_start |= at_line_start;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token<'a>>, LexError> {
+        self.skip_trivia()?;
+        let Some(first) = self.rest().chars().next() else {
+            return Ok(None);
+        };
+        let start = self.pos;
+        let line = self.line;
+        let column = (start - self.line_start) as u32 + 1;
+
+        let (kind, len) = if first == '#' && self.at_line_start {
+            (TokenKind::Directive, self.directive_len())
+        } else if first.is_ascii_alphabetic() || first == '_' {
+            (TokenKind::Identifier, self.ident_len(0))
+        } else if first.is_ascii_digit()
+            || (first == '.' && self.peek_byte(1).is_some_and(|b| b.is_ascii_digit()))
+        {
+            self.number()?
+        } else if let Some(p) = PUNCTUATION.iter().find(|p| self.rest().starts_with(**p)) {
+            (TokenKind::Punct, p.len())
+        } else {
+            return Err(self.error(format!("unexpected character `{first}`")));
+        };
+
+        self.at_line_start = false;
+        self.advance(len);
+        Ok(Some(Token {
+            kind,
+            text: &self.source[start..start + len],
+            line,
+            column,
+        }))
+    }
+
+    /// The length of a directive, up to (but not including) the end of the line or a comment.
+    fn directive_len(&self) -> usize {
+        let rest = self.rest();
+        let mut end = rest.find('\n').unwrap_or(rest.len());
+        for comment in ["//", "/*"] {
+            if let Some(i) = rest[..end].find(comment) {
+                end = end.min(i);
+            }
+        }
+        rest[..end].trim_end().len()
+    }
+
+    /// The length of an identifier starting `offset` bytes ahead.
+    fn ident_len(&self, offset: usize) -> usize {
+        self.rest()[offset..]
+            .bytes()
+            .take_while(|b| b.is_ascii_alphanumeric() || *b == b'_')
+            .count()
+    }
+
+    fn digits_len(&self, offset: usize, radix: u32) -> usize {
+        self.rest()[offset..]
+            .bytes()
+            .take_while(|b| (*b as char).is_digit(radix))
+            .count()
+    }
+
+    /// Works out the kind and length of the number at the current position.
+    fn number(&self) -> Result<(TokenKind, usize), LexError> {
+        let rest = self.rest().as_bytes();
+        let mut len;
+        let kind;
+
+        if rest.starts_with(b"0x") || rest.starts_with(b"0X") {
+            len = 2 + self.digits_len(2, 16);
+            if len == 2 {
+                return Err(self.error("hexadecimal literal has no digits"));
+            }
+            kind = TokenKind::IntConstant;
+        } else {
+            len = self.digits_len(0, 10);
+            let mut is_float = false;
+            if rest.get(len) == Some(&b'.') {
+                is_float = true;
+                len += 1 + self.digits_len(len + 1, 10);
+            }
+            if matches!(rest.get(len), Some(b'e' | b'E')) {
+                let mut exp = len + 1;
+                if matches!(rest.get(exp), Some(b'+' | b'-')) {
+                    exp += 1;
+                }
+                let digits = self.digits_len(exp, 10);
+                if digits == 0 {
+                    return Err(self.error("floating-point exponent has no digits"));
+                }
+                is_float = true;
+                len = exp + digits;
+            }
+            kind = if is_float {
+                TokenKind::FloatConstant
+            } else {
+                TokenKind::IntConstant
+            };
+        }
+
+        // suffixes
+        let suffix_len = self.ident_len(len);
+        let suffix = &self.rest()[len..len + suffix_len];
+        let valid = matches!(
+            (kind, suffix),
+            (_, "")
+                | (TokenKind::IntConstant, "u" | "U")
+                | (TokenKind::FloatConstant, "f" | "F" | "lf" | "LF")
+        );
+        if !valid {
+            return Err(self.error(format!(
+                "invalid suffix `{suffix}` on number `{}`",
+                &self.rest()[..len]
+            )));
+        }
+        Ok((kind, len + suffix_len))
+    }
+}
+
+/// The suffix of a numeric literal, e.g. `"u"` for `3u` or `"lf"` for `1.0lf`. Empty if there is
+/// none.
+pub fn number_suffix(text: &str) -> &str {
+    let body = if text.starts_with("0x") || text.starts_with("0X") {
+        2 + text[2..].bytes().take_while(u8::is_ascii_hexdigit).count()
+    } else {
+        text.bytes()
+            .take_while(|b| !b.is_ascii_alphabetic() || matches!(b, b'e' | b'E'))
+            .count()
+    };
+    &text[body..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds_and_text(source: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.kind, t.text))
+            .collect()
+    }
+
+    mod tokenize {
+        use super::*;
+        use TokenKind::*;
+
+        #[test]
+        fn declarations() {
+            assert_eq!(
+                kinds_and_text("layout (location = 0) in vec3 aPos;"),
+                [
+                    (Identifier, "layout"),
+                    (Punct, "("),
+                    (Identifier, "location"),
+                    (Punct, "="),
+                    (IntConstant, "0"),
+                    (Punct, ")"),
+                    (Identifier, "in"),
+                    (Identifier, "vec3"),
+                    (Identifier, "aPos"),
+                    (Punct, ";"),
+                ]
+            );
+        }
+
+        #[test]
+        fn numbers() {
+            assert_eq!(
+                kinds_and_text("1 0x1F 3u 1.0 .5 2. 1e3 1.5E-2 1.0f 2.0lf"),
+                [
+                    (IntConstant, "1"),
+                    (IntConstant, "0x1F"),
+                    (IntConstant, "3u"),
+                    (FloatConstant, "1.0"),
+                    (FloatConstant, ".5"),
+                    (FloatConstant, "2."),
+                    (FloatConstant, "1e3"),
+                    (FloatConstant, "1.5E-2"),
+                    (FloatConstant, "1.0f"),
+                    (FloatConstant, "2.0lf"),
+                ]
+            );
+        }
+
+        #[test]
+        fn swizzles_are_not_numbers() {
+            assert_eq!(
+                kinds_and_text("v.xy"),
+                [(Identifier, "v"), (Punct, "."), (Identifier, "xy")]
+            );
+        }
+
+        #[test]
+        fn longest_operator_wins() {
+            assert_eq!(
+                kinds_and_text("a<<=b>>c"),
+                [
+                    (Identifier, "a"),
+                    (Punct, "<<="),
+                    (Identifier, "b"),
+                    (Punct, ">>"),
+                    (Identifier, "c"),
+                ]
+            );
+        }
+
+        #[test]
+        fn skips_comments() {
+            assert_eq!(
+                kinds_and_text("a // b\n/* c\n d */ e"),
+                [(Identifier, "a"), (Identifier, "e")]
+            );
+        }
+
+        #[test]
+        fn directives_are_single_tokens() {
+            let tokens = tokenize("#version 460 core // hi\n  # define X 1\nx # y").unwrap_err();
+            assert_eq!(tokens.line, 3);
+
+            let tokens =
+                tokenize("#version 460 core // hi\n  # define X 1\n/* */ #line 3\n").unwrap();
+            assert_eq!(
+                tokens
+                    .iter()
+                    .map(|t| (t.kind, t.text, t.line))
+                    .collect::<Vec<_>>(),
+                [
+                    (Directive, "#version 460 core", 1),
+                    (Directive, "# define X 1", 2),
+                    (Directive, "#line 3", 3),
+                ]
+            );
+        }
+
+        #[test]
+        fn tracks_lines_and_columns() {
+            let tokens = tokenize("a\n  bb /* x\n */ c").unwrap();
+            assert_eq!(
+                tokens
+                    .iter()
+                    .map(|t| (t.line, t.column))
+                    .collect::<Vec<_>>(),
+                [(1, 1), (2, 3), (3, 5)]
+            );
+        }
+
+        #[test]
+        fn rejects_bad_input() {
+            assert_eq!(tokenize("a @ b").unwrap_err().column, 3);
+            assert!(tokenize("1.0q").is_err());
+            assert!(tokenize("3x").is_err());
+            assert!(tokenize("4f").is_err());
+            assert!(tokenize("0x").is_err());
+            assert!(tokenize("1e").is_err());
+            assert!(tokenize("/* open").is_err());
+        }
+    }
+
+    mod number_suffix {
+        use super::*;
+
+        #[test]
+        fn finds_suffixes() {
+            assert_eq!(number_suffix("1"), "");
+            assert_eq!(number_suffix("3u"), "u");
+            assert_eq!(number_suffix("0xFFu"), "u");
+            assert_eq!(number_suffix("1.0f"), "f");
+            assert_eq!(number_suffix("1e5lf"), "lf");
+            assert_eq!(number_suffix("1e5"), "");
+        }
+    }
+}
@@ -0,0 +1,7 @@
+//! Tools for working with GLSL source code before it reaches the driver.
+
+pub mod ast;
+pub mod lexer;
+pub mod parser;
+pub mod preprocess;
+pub mod validate;
@@ -0,0 +1,1390 @@
+//! Parses the declarations in a GLSL shader.
+//!
+//! The parser handles the parts of the preprocessor that change which declarations exist:
+//! `#version`, `#extension`, object-like `#define`s and `#undef`, and conditional compilation with
+//! `#if`, `#ifdef`, `#ifndef`, `#elif`, `#else` and `#endif`. Function-like macros are recorded
+//! (so `defined` works on them) but never expanded. Run [`Preprocessor`](crate::preprocess) over a
+//! shader first if it uses `#include`.
+//!
+//! ```
+//! use triangle_from_scratch_glsl::{ast::Storage, parser::parse};
+//!
+//! let unit = parse(
+//!     "#version 330 core
+//!     #define COLOR_LOCATION 1
+//!     layout(location = 0) in vec3 pos;
+//!     layout(location = COLOR_LOCATION) in vec3 color;
+//!     out vec3 vert_color;
+//!     void main() { gl_Position = vec4(pos, 1.0); vert_color = color; }",
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(unit.version.unwrap().version.number, 330);
+//! let inputs: Vec<_> = unit
+//!     .variables()
+//!     .filter(|v| v.qualifiers.storage == Some(Storage::In))
+//!     .map(|v| (v.name, v.qualifiers.location()))
+//!     .collect();
+//! assert_eq!(inputs, [("pos", Some(0)), ("color", Some(1))]);
+//! ```
+
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    ast::*,
+    lexer::{number_suffix, tokenize, LexError, Token, TokenKind},
+    preprocess::{GlslVersion, Profile},
+};
+
+/// Something that isn't valid GLSL, or that this parser doesn't understand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<LexError> for ParseError {
+    fn from(e: LexError) -> Self {
+        Self {
+            line: e.line,
+            column: e.column,
+            message: e.message,
+        }
+    }
+}
+
+fn error_at(token: &Token<'_>, message: impl Into<String>) -> ParseError {
+    ParseError {
+        line: token.line,
+        column: token.column,
+        message: message.into(),
+    }
+}
+
+/// Parses a shader.
+pub fn parse(source: &str) -> Result<TranslationUnit<'_>, ParseError> {
+    let mut directives = Directives::default();
+    for token in tokenize(source)? {
+        directives.process(token)?;
+    }
+    if directives.conditions.last().is_some() {
+        let line = source.lines().count() as u32;
+        return Err(ParseError {
+            line,
+            column: 1,
+            message: "unterminated #if".into(),
+        });
+    }
+
+    let suffixed_literals = directives
+        .out
+        .iter()
+        .filter(|t| {
+            matches!(t.kind, TokenKind::IntConstant | TokenKind::FloatConstant)
+                && !number_suffix(t.text).is_empty()
+        })
+        .copied()
+        .collect();
+
+    let mut parser = Parser {
+        tokens: directives.out,
+        pos: 0,
+        end_line: source.lines().count().max(1) as u32,
+        declarations: Vec::new(),
+    };
+    while parser.peek(0).is_some() {
+        parser.external_declaration()?;
+    }
+
+    Ok(TranslationUnit {
+        version: directives.version,
+        extensions: directives.extensions,
+        declarations: parser.declarations,
+        suffixed_literals,
+    })
+}
+
+#[derive(Debug, Clone)]
+enum Macro<'a> {
+    Object(Vec<Token<'a>>),
+    /// Function-like macros are never expanded.
+    Function,
+}
+
+/// The state of one `#if`/`#ifdef`/`#ifndef` group.
+#[derive(Debug, Clone, Copy)]
+struct Condition {
+    /// Whether the current branch is being compiled.
+    active: bool,
+    /// Whether any branch so far has been compiled (or the whole group is being skipped).
+    taken: bool,
+    seen_else: bool,
+}
+
+/// Runs the directives that matter for parsing, and expands macros in everything else.
+#[derive(Debug, Default)]
+struct Directives<'a> {
+    macros: HashMap<&'a str, Macro<'a>>,
+    conditions: Vec<Condition>,
+    version: Option<VersionDirective>,
+    extensions: Vec<ExtensionDirective<'a>>,
+    /// Whether any tokens (besides directives) have been seen, since `#version` has to come first.
+    seen_tokens: bool,
+    out: Vec<Token<'a>>,
+}
+
+impl<'a> Directives<'a> {
+    fn active(&self) -> bool {
+        self.conditions.last().is_none_or(|c| c.active)
+    }
+
+    fn process(&mut self, token: Token<'a>) -> Result<(), ParseError> {
+        if token.kind != TokenKind::Directive {
+            if self.active() {
+                self.seen_tokens = true;
+                let mut expanded = Vec::new();
+                self.expand(token, token, &mut Vec::new(), &mut |t| expanded.push(t));
+                self.out.extend(expanded);
+            }
+            return Ok(());
+        }
+
+        // Split `# name rest` up.
+        let body = token.text[1..].trim_start();
+        let name_len = body
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(body.len());
+        let (name, rest) = body.split_at(name_len);
+        // Only tokenize the rest when it's needed, since `#error` and `#pragma` can contain anything.
+        let args = || directive_tokens(&token, rest);
+
+        match name {
+            "ifdef" | "ifndef" => {
+                let args = args()?;
+                let Some(macro_name) = args.first().filter(|t| t.kind == TokenKind::Identifier)
+                else {
+                    return Err(error_at(&token, format!("#{name} needs a macro name")));
+                };
+                let defined = self.macros.contains_key(macro_name.text);
+                self.push_condition(defined == (name == "ifdef"));
+                return Ok(());
+            }
+            "if" => {
+                let value = self.active() && self.evaluate(&token, &args()?)?;
+                self.push_condition(value);
+                return Ok(());
+            }
+            "elif" => {
+                let parent_active =
+                    self.conditions.len() < 2 || self.conditions[self.conditions.len() - 2].active;
+                let Some(&condition) = self.conditions.last() else {
+                    return Err(error_at(&token, "#elif without #if"));
+                };
+                if condition.seen_else {
+                    return Err(error_at(&token, "#elif after #else"));
+                }
+                let value = parent_active && !condition.taken && self.evaluate(&token, &args()?)?;
+                let top = self.conditions.last_mut().unwrap();
+                top.active = value;
+                top.taken |= value;
+                return Ok(());
+            }
+            "else" => {
+                let Some(top) = self.conditions.last_mut() else {
+                    return Err(error_at(&token, "#else without #if"));
+                };
+                if top.seen_else {
+                    return Err(error_at(&token, "#else after #else"));
+                }
+                top.active = !top.taken;
+                top.taken = true;
+                top.seen_else = true;
+                return Ok(());
+            }
+            "endif" => {
+                if self.conditions.pop().is_none() {
+                    return Err(error_at(&token, "#endif without #if"));
+                }
+                return Ok(());
+            }
+            _ if !self.active() => return Ok(()),
+            _ => (),
+        }
+
+        match name {
+            "version" => self.version(&token, &args()?)?,
+            "extension" => match args()?.as_slice() {
+                [ext, colon, behavior]
+                    if ext.kind == TokenKind::Identifier
+                        && colon.is_punct(":")
+                        && matches!(behavior.text, "require" | "enable" | "warn" | "disable") =>
+                {
+                    self.extensions.push(ExtensionDirective {
+                        name: ext.text,
+                        behavior: behavior.text,
+                        line: token.line,
+                    });
+                }
+                _ => {
+                    return Err(error_at(
+                        &token,
+                        "expected `#extension name : require|enable|warn|disable`",
+                    ))
+                }
+            },
+            "define" => {
+                let args = args()?;
+                let Some(macro_name) = args.first().filter(|t| t.kind == TokenKind::Identifier)
+                else {
+                    return Err(error_at(&token, "#define needs a macro name"));
+                };
+                // `#define F(x)` is function-like, but `#define F (x)` isn't.
+                let function_like = args.get(1).is_some_and(|t| {
+                    t.is_punct("(") && t.column == macro_name.column + macro_name.text.len() as u32
+                });
+                let value = if function_like {
+                    Macro::Function
+                } else {
+                    Macro::Object(args[1..].to_vec())
+                };
+                self.macros.insert(macro_name.text, value);
+            }
+            "undef" => {
+                if let Some(macro_name) = args()?.first() {
+                    self.macros.remove(macro_name.text);
+                }
+            }
+            "error" => return Err(error_at(&token, format!("#error{rest}"))),
+            "include" => {
+                return Err(error_at(
+                    &token,
+                    "#include isn't part of GLSL; run the source through the preprocessor first",
+                ))
+            }
+            "line" | "pragma" | "" => (),
+            _ => return Err(error_at(&token, format!("unknown directive `#{name}`"))),
+        }
+        Ok(())
+    }
+
+    fn push_condition(&mut self, value: bool) {
+        let parent_active = self.active();
+        self.conditions.push(Condition {
+            active: parent_active && value,
+            taken: !parent_active || value,
+            seen_else: false,
+        });
+    }
+
+    fn version(&mut self, token: &Token<'a>, args: &[Token<'a>]) -> Result<(), ParseError> {
+        if self.version.is_some() || self.seen_tokens {
+            return Err(error_at(
+                token,
+                "#version must come before everything else in the shader",
+            ));
+        }
+        let (number, profile) = match args {
+            [n] => (n, None),
+            [n, p] if p.kind == TokenKind::Identifier => (n, Some(p)),
+            _ => return Err(error_at(token, "expected `#version number [profile]`")),
+        };
+        let number: u16 = match number.kind {
+            TokenKind::IntConstant => number
+                .text
+                .parse()
+                .map_err(|_| error_at(number, "invalid version number"))?,
+            _ => return Err(error_at(number, "invalid version number")),
+        };
+
+        let profile = match (profile.map(|p| p.text), number) {
+            (Some("es"), 300 | 310 | 320) | (None, 100) => Profile::Es,
+            (Some("core"), 150..) | (None, 150..) => Profile::Core,
+            (Some("compatibility"), 150..) | (None, _) => Profile::Compatibility,
+            (Some(p), _) => {
+                return Err(error_at(
+                    profile.unwrap(),
+                    format!("profile `{p}` isn't allowed with #version {number}"),
+                ))
+            }
+        };
+        if matches!(number, 300 | 310 | 320) && profile != Profile::Es {
+            return Err(error_at(
+                token,
+                format!("#version {number} is only valid as `#version {number} es`"),
+            ));
+        }
+
+        self.version = Some(VersionDirective {
+            version: GlslVersion::new(number, profile),
+            explicit_profile: args.len() == 2,
+            line: token.line,
+        });
+        // The predefined macros that depend on the version
+        let one = synthetic(TokenKind::IntConstant, "1");
+        self.macros
+            .insert("__VERSION__", Macro::Object(vec![args[0]]));
+        let profile_macro = match profile {
+            Profile::Core => "GL_core_profile",
+            Profile::Compatibility => "GL_compatibility_profile",
+            Profile::Es => "GL_es_profile",
+        };
+        self.macros.insert(profile_macro, Macro::Object(vec![one]));
+        if profile == Profile::Es {
+            self.macros.insert("GL_ES", Macro::Object(vec![one]));
+        }
+        Ok(())
+    }
+
+    /// Evaluates the expression after `#if` or `#elif`.
+    fn evaluate(&self, token: &Token<'a>, args: &[Token<'a>]) -> Result<bool, ParseError> {
+        // Replace `defined X` and `defined(X)` first, so the names don't get expanded.
+        let mut replaced = Vec::with_capacity(args.len());
+        let mut i = 0;
+        while i < args.len() {
+            if args[i].is_ident("defined") {
+                let (name, len) = match &args[i + 1..] {
+                    [open, name, close, ..] if open.is_punct("(") && close.is_punct(")") => {
+                        (name, 4)
+                    }
+                    [name, ..] => (name, 2),
+                    [] => return Err(error_at(&args[i], "`defined` needs a macro name")),
+                };
+                let value = if self.macros.contains_key(name.text) {
+                    "1"
+                } else {
+                    "0"
+                };
+                replaced.push(Token {
+                    text: value,
+                    kind: TokenKind::IntConstant,
+                    ..args[i]
+                });
+                i += len;
+            } else {
+                replaced.push(args[i]);
+                i += 1;
+            }
+        }
+
+        let mut expanded = Vec::new();
+        for &t in &replaced {
+            self.expand(t, t, &mut Vec::new(), &mut |t| expanded.push(t));
+        }
+        // Names that aren't macros count as 0.
+        for t in &mut expanded {
+            if t.kind == TokenKind::Identifier {
+                *t = Token {
+                    text: "0",
+                    kind: TokenKind::IntConstant,
+                    ..*t
+                };
+            }
+        }
+
+        eval_int(&expanded)
+            .map(|v| v != 0)
+            .ok_or_else(|| error_at(token, "can't evaluate #if expression"))
+    }
+
+    /// Expands `token` if it names an object-like macro, passing the results to `emit`. Expanded
+    /// tokens take the position of `at`, where the macro was used.
+    fn expand(
+        &self,
+        token: Token<'a>,
+        at: Token<'a>,
+        expanding: &mut Vec<&'a str>,
+        emit: &mut dyn FnMut(Token<'a>),
+    ) {
+        let body = match self.macros.get(token.text) {
+            Some(Macro::Object(body))
+                if token.kind == TokenKind::Identifier && !expanding.contains(&token.text) =>
+            {
+                body
+            }
+            _ => {
+                emit(Token {
+                    line: at.line,
+                    column: at.column,
+                    ..token
+                });
+                return;
+            }
+        };
+        expanding.push(token.text);
+        for &t in body {
+            self.expand(t, at, expanding, emit);
+        }
+        expanding.pop();
+    }
+}
+
+/// A token that doesn't come from the source, like the value of `__VERSION__`.
+fn synthetic<'a>(kind: TokenKind, text: &'static str) -> Token<'a> {
+    Token {
+        kind,
+        text,
+        line: 0,
+        column: 0,
+    }
+}
+
+/// Tokenizes the part of a directive after its name, positioning the tokens within the source.
+fn directive_tokens<'a>(
+    directive: &Token<'a>,
+    rest: &'a str,
+) -> Result<Vec<Token<'a>>, ParseError> {
+    let offset = rest.as_ptr() as usize - directive.text.as_ptr() as usize;
+    let place = |line: u32, column: u32| {
+        (
+            directive.line + line - 1,
+            directive.column + offset as u32 + column - 1,
+        )
+    };
+    match tokenize(rest) {
+        Ok(mut tokens) => {
+            for t in &mut tokens {
+                (t.line, t.column) = place(t.line, t.column);
+            }
+            Ok(tokens)
+        }
+        Err(e) => {
+            let (line, column) = place(e.line, e.column);
+            Err(ParseError {
+                line,
+                column,
+                message: e.message,
+            })
+        }
+    }
+}
+
+/// Evaluates an integer constant expression made of literals and operators, like `2 * (3 + 1)`.
+///
+/// Returns `None` if the expression names anything (even a constant), is malformed, or divides
+/// by zero.
+pub fn eval_int(tokens: &[Token<'_>]) -> Option<i64> {
+    let mut eval = Eval { tokens, pos: 0 };
+    let value = eval.ternary()?;
+    (eval.pos == tokens.len()).then_some(value)
+}
+
+struct Eval<'t, 'a> {
+    tokens: &'t [Token<'a>],
+    pos: usize,
+}
+
+impl Eval<'_, '_> {
+    fn peek_punct(&self) -> Option<&str> {
+        self.tokens
+            .get(self.pos)
+            .filter(|t| t.kind == TokenKind::Punct)
+            .map(|t| t.text)
+    }
+
+    fn ternary(&mut self) -> Option<i64> {
+        let condition = self.binary(0)?;
+        if self.peek_punct() != Some("?") {
+            return Some(condition);
+        }
+        self.pos += 1;
+        let a = self.ternary()?;
+        (self.peek_punct() == Some(":")).then_some(())?;
+        self.pos += 1;
+        let b = self.ternary()?;
+        Some(if condition != 0 { a } else { b })
+    }
+
+    fn binary(&mut self, min_precedence: u8) -> Option<i64> {
+        let mut lhs = self.unary()?;
+        loop {
+            let Some(op) = self.peek_punct() else {
+                return Some(lhs);
+            };
+            let precedence = match op {
+                "||" => 1,
+                "^^" => 2,
+                "&&" => 3,
+                "|" => 4,
+                "^" => 5,
+                "&" => 6,
+                "==" | "!=" => 7,
+                "<" | ">" | "<=" | ">=" => 8,
+                "<<" | ">>" => 9,
+                "+" | "-" => 10,
+                "*" | "/" | "%" => 11,
+                _ => return Some(lhs),
+            };
+            if precedence < min_precedence {
+                return Some(lhs);
+            }
+            let op = op.to_string();
+            self.pos += 1;
+            let rhs = self.binary(precedence + 1)?;
+            lhs = match op.as_str() {
+                "||" => ((lhs != 0) || (rhs != 0)) as i64,
+                "^^" => ((lhs != 0) != (rhs != 0)) as i64,
+                "&&" => ((lhs != 0) && (rhs != 0)) as i64,
+                "|" => lhs | rhs,
+                "^" => lhs ^ rhs,
+                "&" => lhs & rhs,
+                "==" => (lhs == rhs) as i64,
+                "!=" => (lhs != rhs) as i64,
+                "<" => (lhs < rhs) as i64,
+                ">" => (lhs > rhs) as i64,
+                "<=" => (lhs <= rhs) as i64,
+                ">=" => (lhs >= rhs) as i64,
+                "<<" => lhs.checked_shl(rhs.try_into().ok()?)?,
+                ">>" => lhs.checked_shr(rhs.try_into().ok()?)?,
+                "+" => lhs.wrapping_add(rhs),
+                "-" => lhs.wrapping_sub(rhs),
+                "*" => lhs.wrapping_mul(rhs),
+                "/" => lhs.checked_div(rhs)?,
+                _ => lhs.checked_rem(rhs)?,
+            };
+        }
+    }
+
+    fn unary(&mut self) -> Option<i64> {
+        let token = self.tokens.get(self.pos)?;
+        self.pos += 1;
+        match (token.kind, token.text) {
+            (TokenKind::Punct, "-") => Some(self.unary()?.wrapping_neg()),
+            (TokenKind::Punct, "+") => self.unary(),
+            (TokenKind::Punct, "!") => Some((self.unary()? == 0) as i64),
+            (TokenKind::Punct, "~") => Some(!self.unary()?),
+            (TokenKind::Punct, "(") => {
+                let value = self.ternary()?;
+                (self.peek_punct() == Some(")")).then_some(())?;
+                self.pos += 1;
+                Some(value)
+            }
+            (TokenKind::IntConstant, text) => parse_int(text),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a decimal, octal, or hexadecimal integer literal, with an optional `u` suffix.
+fn parse_int(text: &str) -> Option<i64> {
+    let text = text.trim_end_matches(['u', 'U']);
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16).ok()
+    } else if text.len() > 1 && text.starts_with('0') {
+        i64::from_str_radix(&text[1..], 8).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+/// Words that can start (or continue) a list of qualifiers.
+fn is_qualifier(word: &str) -> bool {
+    matches!(
+        word,
+        "layout"
+            | "smooth"
+            | "flat"
+            | "noperspective"
+            | "centroid"
+            | "sample"
+            | "patch"
+            | "invariant"
+            | "precise"
+            | "highp"
+            | "mediump"
+            | "lowp"
+            | "coherent"
+            | "volatile"
+            | "restrict"
+            | "readonly"
+            | "writeonly"
+    ) || Storage::from_keyword(word).is_some()
+}
+
+/// Parses declarations out of a stream of tokens that have already been through [`Directives`].
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    /// The last line of the source, for reporting errors at the end of the input.
+    end_line: u32,
+    declarations: Vec<Declaration<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self, n: usize) -> Option<&Token<'a>> {
+        self.tokens.get(self.pos + n)
+    }
+
+    fn peek_is_punct(&self, p: &str) -> bool {
+        self.peek(0).is_some_and(|t| t.is_punct(p))
+    }
+
+    fn error_here(&self, message: impl Into<String>) -> ParseError {
+        match self.peek(0) {
+            Some(t) => error_at(t, message),
+            None => ParseError {
+                line: self.end_line,
+                column: 1,
+                message: message.into(),
+            },
+        }
+    }
+
+    fn next(&mut self) -> Result<Token<'a>, ParseError> {
+        let token = *self
+            .peek(0)
+            .ok_or_else(|| self.error_here("unexpected end of input"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect_punct(&mut self, p: &str) -> Result<Token<'a>, ParseError> {
+        match self.peek(0) {
+            Some(t) if t.is_punct(p) => self.next(),
+            Some(t) => Err(error_at(t, format!("expected `{p}`, found `{}`", t.text))),
+            None => Err(self.error_here(format!("expected `{p}`, found the end of the input"))),
+        }
+    }
+
+    fn expect_ident(&mut self, what: &str) -> Result<Token<'a>, ParseError> {
+        match self.peek(0) {
+            Some(t) if t.kind == TokenKind::Identifier => self.next(),
+            Some(t) => Err(error_at(t, format!("expected {what}, found `{}`", t.text))),
+            None => Err(self.error_here(format!("expected {what}, found the end of the input"))),
+        }
+    }
+
+    fn external_declaration(&mut self) -> Result<(), ParseError> {
+        if self.peek_is_punct(";") {
+            self.pos += 1;
+            return Ok(());
+        }
+        let first = *self.peek(0).unwrap();
+        let line = first.line;
+
+        if first.is_ident("precision") {
+            self.pos += 1;
+            let precision = self.expect_ident("a precision qualifier")?.text;
+            let ty = self.type_specifier()?;
+            self.expect_punct(";")?;
+            self.declarations.push(Declaration::Precision {
+                precision,
+                ty,
+                line,
+            });
+            return Ok(());
+        }
+
+        let qualifiers = self.qualifiers()?;
+        if self.peek_is_punct(";") {
+            self.pos += 1;
+            self.declarations
+                .push(Declaration::Qualifiers { qualifiers, line });
+            return Ok(());
+        }
+
+        // `invariant gl_Position;`
+        let only_invariant = Qualifiers {
+            invariant: true,
+            ..Default::default()
+        };
+        if qualifiers == only_invariant
+            && self
+                .peek(1)
+                .is_some_and(|t| t.is_punct(";") || t.is_punct(","))
+        {
+            let mut names = vec![self.expect_ident("a variable name")?.text];
+            while self.peek_is_punct(",") {
+                self.pos += 1;
+                names.push(self.expect_ident("a variable name")?.text);
+            }
+            self.expect_punct(";")?;
+            self.declarations
+                .push(Declaration::Invariant { names, line });
+            return Ok(());
+        }
+
+        // interface blocks
+        let is_block_storage = matches!(
+            qualifiers.storage,
+            Some(Storage::In | Storage::Out | Storage::Uniform | Storage::Buffer)
+        );
+        if is_block_storage
+            && self
+                .peek(0)
+                .is_some_and(|t| t.kind == TokenKind::Identifier)
+            && self.peek(1).is_some_and(|t| t.is_punct("{"))
+        {
+            let name = self.next()?.text;
+            let members = self.member_list()?;
+            let instance = if self.peek_is_punct(";") {
+                None
+            } else {
+                let instance = self.expect_ident("an instance name or `;`")?.text;
+                Some((instance, self.array_specifiers()?))
+            };
+            self.expect_punct(";")?;
+            self.declarations.push(Declaration::Block(Block {
+                qualifiers,
+                name,
+                members,
+                instance,
+                line,
+            }));
+            return Ok(());
+        }
+
+        let ty = self.type_specifier()?;
+        if self.peek_is_punct(";") {
+            // a struct definition on its own, or a pointless `float;`
+            self.pos += 1;
+            return Ok(());
+        }
+
+        let name = self.expect_ident("a name")?;
+        if self.peek_is_punct("(") {
+            return self.function(ty, name.text, line);
+        }
+
+        let mut name = name;
+        loop {
+            let mut var_ty = ty.clone();
+            var_ty.array.extend(self.array_specifiers()?);
+            if self.peek_is_punct("=") {
+                self.pos += 1;
+                self.skip_initializer()?;
+            }
+            self.declarations.push(Declaration::Variable(Variable {
+                qualifiers: qualifiers.clone(),
+                ty: var_ty,
+                name: name.text,
+                line: name.line,
+            }));
+
+            if self.peek_is_punct(",") {
+                self.pos += 1;
+                name = self.expect_ident("a variable name")?;
+            } else {
+                self.expect_punct(";")?;
+                return Ok(());
+            }
+        }
+    }
+
+    fn qualifiers(&mut self) -> Result<Qualifiers<'a>, ParseError> {
+        let mut q = Qualifiers::default();
+        while let Some(&token) = self
+            .peek(0)
+            .filter(|t| t.kind == TokenKind::Identifier && is_qualifier(t.text))
+        {
+            self.pos += 1;
+            let word = token.text;
+            if word == "layout" {
+                self.layout_qualifiers(&mut q.layout)?;
+            } else if let Some(storage) = Storage::from_keyword(word) {
+                // `centroid in` and friends are written in either order
+                if q.storage.is_some_and(|s| s != storage) {
+                    return Err(error_at(&token, "more than one storage qualifier"));
+                }
+                q.storage = Some(storage);
+            } else if let Some(interpolation) = Interpolation::from_keyword(word) {
+                if q.interpolation.is_some_and(|i| i != interpolation) {
+                    return Err(error_at(&token, "more than one interpolation qualifier"));
+                }
+                q.interpolation = Some(interpolation);
+            } else {
+                match word {
+                    "centroid" => q.centroid = true,
+                    "sample" => q.sample = true,
+                    "patch" => q.patch = true,
+                    "invariant" => q.invariant = true,
+                    "precise" => q.precise = true,
+                    "highp" | "mediump" | "lowp" => q.precision = Some(word),
+                    _ => q.memory.push(word),
+                }
+            }
+        }
+        Ok(q)
+    }
+
+    fn layout_qualifiers(&mut self, out: &mut Vec<LayoutQualifier<'a>>) -> Result<(), ParseError> {
+        self.expect_punct("(")?;
+        loop {
+            let name = self.expect_ident("a layout qualifier")?.text;
+            let value = if self.peek_is_punct("=") {
+                self.pos += 1;
+                let tokens = self.balanced_until(&[",", ")"])?;
+                if tokens.is_empty() {
+                    return Err(self.error_here(format!("`{name} =` needs a value")));
+                }
+                Some(const_value(&tokens))
+            } else {
+                None
+            };
+            out.push(LayoutQualifier { name, value });
+
+            if self.next()?.is_punct(")") {
+                return Ok(());
+            }
+        }
+    }
+
+    fn type_specifier(&mut self) -> Result<TypeSpecifier<'a>, ParseError> {
+        let name = self.expect_ident("a type")?;
+        let name = if name.is_ident("struct") {
+            let struct_name = if self.peek_is_punct("{") {
+                ""
+            } else {
+                self.expect_ident("a struct name")?.text
+            };
+            let members = self.member_list()?;
+            self.declarations.push(Declaration::Struct(Struct {
+                name: struct_name,
+                members,
+                line: name.line,
+            }));
+            struct_name
+        } else {
+            name.text
+        };
+        Ok(TypeSpecifier {
+            name,
+            array: self.array_specifiers()?,
+        })
+    }
+
+    /// Parses `{ members }`, for structs and interface blocks.
+    fn member_list(&mut self) -> Result<Vec<Variable<'a>>, ParseError> {
+        self.expect_punct("{")?;
+        let mut members = Vec::new();
+        while !self.peek_is_punct("}") {
+            let qualifiers = self.qualifiers()?;
+            let ty = self.type_specifier()?;
+            loop {
+                let name = self.expect_ident("a member name")?;
+                let mut member_ty = ty.clone();
+                member_ty.array.extend(self.array_specifiers()?);
+                members.push(Variable {
+                    qualifiers: qualifiers.clone(),
+                    ty: member_ty,
+                    name: name.text,
+                    line: name.line,
+                });
+                if self.next()?.is_punct(";") {
+                    break;
+                }
+                self.pos -= 1;
+                self.expect_punct(",")?;
+            }
+        }
+        self.pos += 1;
+        Ok(members)
+    }
+
+    fn array_specifiers(&mut self) -> Result<Vec<ArraySize>, ParseError> {
+        let mut dims = Vec::new();
+        while self.peek_is_punct("[") {
+            self.pos += 1;
+            let tokens = self.balanced_until(&["]"])?;
+            self.pos += 1;
+            dims.push(if tokens.is_empty() {
+                ArraySize::Unsized
+            } else {
+                ArraySize::Sized(const_value(&tokens))
+            });
+        }
+        Ok(dims)
+    }
+
+    fn function(
+        &mut self,
+        return_type: TypeSpecifier<'a>,
+        name: &'a str,
+        line: u32,
+    ) -> Result<(), ParseError> {
+        self.expect_punct("(")?;
+        let mut params = Vec::new();
+        let is_void = self.peek(0).is_some_and(|t| t.is_ident("void"))
+            && self.peek(1).is_some_and(|t| t.is_punct(")"));
+        if is_void {
+            self.pos += 1;
+        }
+        while !self.peek_is_punct(")") {
+            let qualifiers = self.qualifiers()?;
+            let mut ty = self.type_specifier()?;
+            let (param_name, param_line) = match self.peek(0) {
+                Some(t) if t.kind == TokenKind::Identifier => {
+                    let t = self.next()?;
+                    (t.text, t.line)
+                }
+                _ => ("", line),
+            };
+            ty.array.extend(self.array_specifiers()?);
+            params.push(Variable {
+                qualifiers,
+                ty,
+                name: param_name,
+                line: param_line,
+            });
+            if !self.peek_is_punct(")") {
+                self.expect_punct(",")?;
+            }
+        }
+        self.pos += 1;
+
+        let body = if self.peek_is_punct(";") {
+            self.pos += 1;
+            None
+        } else {
+            self.expect_punct("{")?;
+            let body = self.balanced_until(&["}"])?;
+            self.pos += 1;
+            Some(body)
+        };
+        self.declarations.push(Declaration::Function(Function {
+            return_type,
+            name,
+            params,
+            body,
+            line,
+        }));
+        Ok(())
+    }
+
+    /// Skips an initializer, leaving the `,` or `;` after it.
+    fn skip_initializer(&mut self) -> Result<(), ParseError> {
+        let tokens = self.balanced_until(&[",", ";"])?;
+        if tokens.is_empty() {
+            return Err(self.error_here("expected an initializer"));
+        }
+        Ok(())
+    }
+
+    /// Collects tokens up to (but not including) the first of `stops` that isn't nested inside
+    /// brackets.
+    fn balanced_until(&mut self, stops: &[&str]) -> Result<Vec<Token<'a>>, ParseError> {
+        let mut depth = Vec::new();
+        let start = self.pos;
+        loop {
+            let Some(&token) = self.peek(0) else {
+                return Err(self.error_here(format!(
+                    "expected `{}`, found the end of the input",
+                    stops[0]
+                )));
+            };
+            if token.kind == TokenKind::Punct {
+                if depth.is_empty() && stops.contains(&token.text) {
+                    return Ok(self.tokens[start..self.pos].to_vec());
+                }
+                match token.text {
+                    "(" => depth.push(")"),
+                    "[" => depth.push("]"),
+                    "{" => depth.push("}"),
+                    ")" | "]" | "}" if depth.pop() != Some(token.text) => {
+                        return Err(error_at(&token, format!("unbalanced `{}`", token.text)));
+                    }
+                    _ => (),
+                }
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+fn const_value(tokens: &[Token<'_>]) -> ConstValue {
+    match eval_int(tokens) {
+        Some(value) => ConstValue::Int(value),
+        None => ConstValue::Expr(tokens.iter().map(|t| t.text).collect::<Vec<_>>().join(" ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_ok(source: &str) -> TranslationUnit<'_> {
+        parse(source).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn variable_names<'a>(unit: &TranslationUnit<'a>) -> Vec<&'a str> {
+        unit.variables().map(|v| v.name).collect()
+    }
+
+    mod directives {
+        use super::*;
+
+        #[test]
+        fn version_and_default_profiles() {
+            let version = |s| parse_ok(s).version.unwrap().version;
+            assert_eq!(version("#version 460 core"), GlslVersion::GL_460_CORE);
+            assert_eq!(version("#version 330"), GlslVersion::GL_330_CORE);
+            assert_eq!(version("#version 300 es"), GlslVersion::ES_300);
+            assert_eq!(
+                version("#version 120"),
+                GlslVersion::new(120, Profile::Compatibility)
+            );
+            assert_eq!(parse_ok("void main() {}").version, None);
+        }
+
+        #[test]
+        fn bad_versions() {
+            assert!(parse("#version 300").is_err());
+            assert!(parse("#version 330 es").is_err());
+            assert!(parse("#version 120 core").is_err());
+            assert!(parse("#version three").is_err());
+            assert_eq!(
+                parse("float x;\n#version 330").unwrap_err().line,
+                2,
+                "#version has to come first"
+            );
+        }
+
+        #[test]
+        fn extensions() {
+            let unit = parse_ok(
+                "#version 330\n#extension GL_ARB_separate_shader_objects : enable\n\
+                 #extension GL_foo : disable\n",
+            );
+            assert!(unit.has_extension("GL_ARB_separate_shader_objects"));
+            assert!(!unit.has_extension("GL_foo"));
+            assert!(!unit.has_extension("GL_bar"));
+            assert!(parse("#extension GL_foo : please").is_err());
+        }
+
+        #[test]
+        fn conditional_compilation() {
+            let unit = parse_ok(
+                "#version 330 core
+                #define A
+                #ifdef A
+                float a;
+                #else
+                float not_a;
+                #endif
+                #ifndef B
+                float not_b;
+                #endif
+                #if defined(A) && !defined B && __VERSION__ >= 330
+                float c;
+                #elif 1
+                float not_c;
+                #endif
+                #if 0
+                  #if 1
+                  float nested;
+                  #endif
+                #elif GL_core_profile
+                float core;
+                #else
+                float compat;
+                #endif
+                #if UNDEFINED_NAME
+                float undefined;
+                #endif
+                ",
+            );
+            assert_eq!(variable_names(&unit), ["a", "not_b", "c", "core"]);
+        }
+
+        #[test]
+        fn skipped_directives_are_ignored() {
+            let unit = parse_ok("#if 0\n#error don't\n#whatever\n#endif\nfloat x;");
+            assert_eq!(variable_names(&unit), ["x"]);
+        }
+
+        #[test]
+        fn unbalanced_conditionals() {
+            assert!(parse("#endif").is_err());
+            assert!(parse("#else").is_err());
+            assert!(parse("#if 1\n#else\n#else\n#endif").is_err());
+            assert!(parse("#if 1\nfloat x;").is_err());
+        }
+
+        #[test]
+        fn object_macros_are_expanded() {
+            let unit = parse_ok(
+                "#define LOC 3
+                #define TYPE vec3
+                #define NAME TYPE_NAME
+                #define TYPE_NAME color
+                layout(location = LOC + 1) in TYPE NAME;
+                #undef LOC
+                #define SELF SELF
+                float SELF;
+                ",
+            );
+            let vars: Vec<_> = unit.variables().collect();
+            assert_eq!(vars[0].name, "color");
+            assert_eq!(vars[0].ty.name, "vec3");
+            assert_eq!(vars[0].qualifiers.location(), Some(4));
+            assert_eq!(
+                vars[0].line, 5,
+                "expanded tokens take the line they're used on"
+            );
+            assert_eq!(vars[1].name, "SELF");
+        }
+
+        #[test]
+        fn function_macros_are_not_expanded() {
+            let unit = parse_ok("#define F(x) x\n#if defined(F)\nvoid F() {}\n#endif");
+            assert_eq!(unit.functions().next().unwrap().name, "F");
+        }
+
+        #[test]
+        fn errors_and_includes_are_reported() {
+            let e = parse("\n#error something's wrong").unwrap_err();
+            assert_eq!(
+                (e.line, e.message.as_str()),
+                (2, "#error something's wrong")
+            );
+            assert!(parse("#include \"x.glsl\"").is_err());
+            assert!(parse("#frobnicate").is_err());
+        }
+
+        #[test]
+        fn records_suffixed_literals() {
+            let unit = parse_ok("float x = 1.0f;\nuint y = 2u;\nfloat z = 3.0;");
+            let suffixed: Vec<_> = unit
+                .suffixed_literals
+                .iter()
+                .map(|t| (t.text, t.line))
+                .collect();
+            assert_eq!(suffixed, [("1.0f", 1), ("2u", 2)]);
+        }
+    }
+
+    mod declarations {
+        use super::*;
+
+        #[test]
+        fn the_triangle_shaders() {
+            let unit = parse_ok(include_str!("../../../src/vertex.vs"));
+            let vars: Vec<_> = unit
+                .variables()
+                .map(|v| {
+                    (
+                        v.qualifiers.storage,
+                        v.qualifiers.location(),
+                        v.ty.name,
+                        v.name,
+                    )
+                })
+                .collect();
+            assert_eq!(
+                vars,
+                [
+                    (Some(Storage::In), Some(0), "vec3", "aPos"),
+                    (Some(Storage::In), Some(1), "vec3", "aColor"),
+                    (Some(Storage::Out), None, "vec3", "vert_color"),
+                ]
+            );
+
+            let unit = parse_ok(include_str!("../../../src/fragment.fs"));
+            assert_eq!(variable_names(&unit), ["FragColor", "vert_color"]);
+            assert_eq!(unit.functions().next().unwrap().name, "main");
+        }
+
+        #[test]
+        fn qualifiers() {
+            let unit = parse_ok(
+                "layout(location = 2, component = 1) flat centroid out highp ivec2 a;
+                 layout(std430, binding = 3) readonly restrict buffer;
+                 invariant gl_Position, other;
+                 precise invariant out float b;",
+            );
+            let Declaration::Variable(a) = &unit.declarations[0] else {
+                panic!()
+            };
+            assert_eq!(a.qualifiers.storage, Some(Storage::Out));
+            assert_eq!(a.qualifiers.interpolation, Some(Interpolation::Flat));
+            assert!(a.qualifiers.centroid);
+            assert_eq!(a.qualifiers.precision, Some("highp"));
+            assert_eq!(a.qualifiers.location(), Some(2));
+            assert_eq!(
+                a.qualifiers.layout("component").unwrap().value,
+                Some(ConstValue::Int(1))
+            );
+
+            let Declaration::Qualifiers { qualifiers, .. } = &unit.declarations[1] else {
+                panic!()
+            };
+            assert_eq!(qualifiers.memory, ["readonly", "restrict"]);
+            assert_eq!(qualifiers.layout[0].name, "std430");
+
+            assert_eq!(
+                unit.declarations[2],
+                Declaration::Invariant {
+                    names: vec!["gl_Position", "other"],
+                    line: 3
+                }
+            );
+
+            let Declaration::Variable(b) = &unit.declarations[3] else {
+                panic!()
+            };
+            assert!(b.qualifiers.precise && b.qualifiers.invariant);
+
+            assert!(parse("in out float x;").is_err());
+        }
+
+        #[test]
+        fn arrays_and_multiple_declarators() {
+            let unit = parse_ok("uniform float a[2 * 2], b, c[N][];");
+            let vars: Vec<_> = unit
+                .variables()
+                .map(|v| (v.name, v.ty.to_string()))
+                .collect();
+            assert_eq!(
+                vars,
+                [
+                    ("a", "float[4]".to_string()),
+                    ("b", "float".to_string()),
+                    ("c", "float[N][]".to_string()),
+                ]
+            );
+        }
+
+        #[test]
+        fn initializers_are_skipped() {
+            let unit = parse_ok(
+                "const vec3 a = vec3(1.0, f(2, 3), 3.0), b = a;\nconst float c[2] = float[](1.0, 2.0);",
+            );
+            assert_eq!(variable_names(&unit), ["a", "b", "c"]);
+        }
+
+        #[test]
+        fn interface_blocks() {
+            let unit = parse_ok(
+                "layout(std140, binding = 0) uniform Camera { mat4 view; layout(offset = 64) vec3 pos; } camera;
+                 out VertexData { vec2 uv; flat int id; } vs_out[3];
+                 buffer Particles { vec4 p[]; };",
+            );
+            let blocks: Vec<_> = unit.blocks().collect();
+            assert_eq!(blocks.len(), 3);
+            assert_eq!(blocks[0].name, "Camera");
+            assert_eq!(blocks[0].instance, Some(("camera", vec![])));
+            assert_eq!(
+                blocks[0].members[1]
+                    .qualifiers
+                    .layout("offset")
+                    .unwrap()
+                    .value,
+                Some(ConstValue::Int(64))
+            );
+            assert_eq!(
+                blocks[1].instance,
+                Some(("vs_out", vec![ArraySize::Sized(ConstValue::Int(3))]))
+            );
+            assert_eq!(
+                blocks[1].members[1].qualifiers.interpolation,
+                Some(Interpolation::Flat)
+            );
+            assert_eq!(blocks[2].instance, None);
+            assert_eq!(blocks[2].members[0].ty.array, [ArraySize::Unsized]);
+        }
+
+        #[test]
+        fn structs() {
+            let unit = parse_ok(
+                "struct Light { vec3 pos, color; float radius; };
+                 uniform Light lights[4];
+                 struct { int x; } anonymous;",
+            );
+            let Declaration::Struct(light) = &unit.declarations[0] else {
+                panic!()
+            };
+            assert_eq!(light.name, "Light");
+            assert_eq!(
+                light.members.iter().map(|m| m.name).collect::<Vec<_>>(),
+                ["pos", "color", "radius"]
+            );
+            assert_eq!(variable_names(&unit), ["lights", "anonymous"]);
+            assert_eq!(unit.variables().nth(1).unwrap().ty.name, "");
+        }
+
+        #[test]
+        fn functions() {
+            let unit = parse_ok(
+                "vec3 tint(in vec3 c, const float amount[2], out int);
+                 void main(void) { if (true) { gl_Position = vec4(0.0); } }
+                 precision mediump float;",
+            );
+            let functions: Vec<_> = unit.functions().collect();
+            assert_eq!(functions[0].name, "tint");
+            assert_eq!(functions[0].body, None);
+            assert_eq!(
+                functions[0]
+                    .params
+                    .iter()
+                    .map(|p| (p.qualifiers.storage, p.name, p.ty.to_string()))
+                    .collect::<Vec<_>>(),
+                [
+                    (Some(Storage::In), "c", "vec3".to_string()),
+                    (Some(Storage::Const), "amount", "float[2]".to_string()),
+                    (Some(Storage::Out), "", "int".to_string()),
+                ]
+            );
+            assert!(functions[1].params.is_empty());
+            let body = functions[1].body.as_ref().unwrap();
+            assert!(body.iter().any(|t| t.is_ident("gl_Position")));
+            assert!(matches!(
+                unit.declarations[2],
+                Declaration::Precision {
+                    precision: "mediump",
+                    ..
+                }
+            ));
+        }
+
+        #[test]
+        fn syntax_errors_have_positions() {
+            let e = parse("float x\nfloat y;").unwrap_err();
+            assert_eq!((e.line, e.column), (2, 1));
+            assert!(e.message.contains("expected `;`"), "{}", e.message);
+
+            let e = parse("void main() {").unwrap_err();
+            assert!(e.message.contains("end of the input"), "{}", e.message);
+
+            assert!(parse("void main() { ) }").is_err());
+            assert!(parse("layout(location = ) in float x;").is_err());
+        }
+    }
+
+    mod eval_int {
+        use super::*;
+
+        fn eval(source: &str) -> Option<i64> {
+            eval_int(&tokenize(source).unwrap())
+        }
+
+        #[test]
+        fn arithmetic_and_precedence() {
+            assert_eq!(eval("1 + 2 * 3"), Some(7));
+            assert_eq!(eval("(1 + 2) * 3"), Some(9));
+            assert_eq!(eval("-4 / 2 % 3"), Some(-2));
+            assert_eq!(eval("1 << 4 | 1"), Some(17));
+            assert_eq!(eval("0x10 + 010 + 3u"), Some(16 + 8 + 3));
+            assert_eq!(eval("2 > 1 && !0"), Some(1));
+            assert_eq!(eval("1 == 2 || 3 != 3"), Some(0));
+            assert_eq!(eval("0 ? 1 : 2 ? 3 : 4"), Some(3));
+        }
+
+        #[test]
+        fn rejects_what_it_cannot_evaluate() {
+            assert_eq!(eval("N"), None);
+            assert_eq!(eval("1 / 0"), None);
+            assert_eq!(eval("1 +"), None);
+            assert_eq!(eval("1 2"), None);
+            assert_eq!(eval("1.0"), None);
+            assert_eq!(eval("(1"), None);
+        }
+    }
+}
@@ -0,0 +1,874 @@
+//! A small GLSL preprocessor that runs before the driver's own.
+//!
+//! The driver already handles `#define`, `#ifdef` and friends, so this only does the things GLSL
+//! can't do by itself:
+//!
+//! - `#include "path"` is replaced by the contents of `path`, found relative to the including file
+//!   using an [`IncludeResolver`]. Files containing `#pragma once` are only included once, and
+//!   include cycles are reported as errors instead of recursing forever.
+//! - A `#version` header for the chosen [`GlslVersion`] is written at the top, followed by any
+//!   extra `#define`s. `#version` directives in the sources themselves are dropped, so the same
+//!   file can be built for several targets.
+//! - A [`SourceMap`] records where every output line came from, so that info logs from
+//!   `glGetShaderInfoLog` can be rewritten to point at the original file and line.
+//!
+//! ```
+//! use triangle_from_scratch_glsl::preprocess::{EmbeddedFiles, GlslVersion, Preprocessor};
+//!
+//! let files = EmbeddedFiles(&[
+//!     ("main.frag", "#include \"lib/color.glsl\"\nout vec4 c;\nvoid main() { c = tint(); }\n"),
+//!     ("lib/color.glsl", "#pragma once\nvec4 tint() { return vec4(TINT); }\n"),
+//! ]);
+//!
+//! let out = Preprocessor::new(GlslVersion::GL_330_CORE, files)
+//!     .define("TINT", "1.0, 0.5, 0.0, 1.0")
+//!     .run("main.frag")
+//!     .unwrap();
+//!
+//! assert!(out.source.starts_with("#version 330 core\n#define TINT 1.0, 0.5, 0.0, 1.0\n"));
+//!
+//! // Output line 4 is the body of `tint`:
+//! let loc = out.map.lookup(4).unwrap();
+//! assert_eq!((loc.file, loc.line), ("lib/color.glsl", 2));
+//!
+//! assert_eq!(
+//!     out.map.rewrite_info_log("0:4(21): error: `TINT' undeclared"),
+//!     "lib/color.glsl:2(21): error: `TINT' undeclared",
+//! );
+//! ```
+
+use std::{
+    collections::HashSet,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The name reported by [`SourceMap::lookup`] for lines this preprocessor wrote itself, like the
+/// `#version` header and injected `#define`s.
+pub const GENERATED_FILE: &str = "<generated>";
+
+/// Which flavour of GLSL a [`GlslVersion`] refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Profile {
+    Core,
+    Compatibility,
+    Es,
+}
+
+/// The GLSL version and profile to emit a `#version` header for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GlslVersion {
+    /// The version number as written in `#version`, e.g. `460`.
+    pub number: u16,
+    pub profile: Profile,
+}
+
+impl GlslVersion {
+    pub const GL_330_CORE: Self = Self::new(330, Profile::Core);
+    pub const GL_410_CORE: Self = Self::new(410, Profile::Core);
+    pub const GL_430_CORE: Self = Self::new(430, Profile::Core);
+    pub const GL_450_CORE: Self = Self::new(450, Profile::Core);
+    pub const GL_460_CORE: Self = Self::new(460, Profile::Core);
+    pub const ES_300: Self = Self::new(300, Profile::Es);
+    pub const ES_310: Self = Self::new(310, Profile::Es);
+    pub const ES_320: Self = Self::new(320, Profile::Es);
+
+    pub const fn new(number: u16, profile: Profile) -> Self {
+        Self { number, profile }
+    }
+}
+
+impl fmt::Display for GlslVersion {
+    /// Formats this version as a `#version` directive, without a trailing newline.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let profile = match self.profile {
+            Profile::Core => "core",
+            Profile::Compatibility => "compatibility",
+            Profile::Es => "es",
+        };
+        write!(f, "#version {} {profile}", self.number)
+    }
+}
+
+/// Finds the files named by `#include` directives.
+pub trait IncludeResolver {
+    /// Loads the file that `#include "path"` refers to when written inside `includer`.
+    ///
+    /// `includer` is empty when loading the top-level file. On success, returns a canonical name
+    /// for the file (used for cycle detection, `#pragma once`, and the [`SourceMap`]) along with
+    /// its contents.
+    fn resolve(&self, path: &str, includer: &str) -> io::Result<(String, String)>;
+}
+
+impl<R: IncludeResolver + ?Sized> IncludeResolver for &R {
+    fn resolve(&self, path: &str, includer: &str) -> io::Result<(String, String)> {
+        (**self).resolve(path, includer)
+    }
+}
+
+/// Works out the canonical name of the file that `#include "path"` refers to when written inside
+/// `includer`.
+///
+/// Paths are relative to the directory containing `includer`, unless they start with `/`, in which
+/// case they're relative to the root of the include tree. `.` and `..` components are resolved
+/// lexically, and `..` can't climb above the root. Both `/` and `\` are accepted as separators, but
+/// the result always uses `/`.
+pub fn join_include_path(includer: &str, path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    if !path.starts_with(['/', '\\']) {
+        parts.extend(includer.split(['/', '\\']).filter(|p| !p.is_empty()));
+        // Drop the includer's own file name, leaving its directory.
+        parts.pop();
+    }
+
+    for part in path.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+
+    parts.join("/")
+}
+
+/// Resolves includes from a table of `(name, contents)` pairs compiled into the program, e.g. with
+/// [`include_str!`].
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddedFiles<'a>(pub &'a [(&'a str, &'a str)]);
+
+impl IncludeResolver for EmbeddedFiles<'_> {
+    fn resolve(&self, path: &str, includer: &str) -> io::Result<(String, String)> {
+        let name = join_include_path(includer, path);
+        self.0
+            .iter()
+            .find(|(n, _)| join_include_path("", n) == name)
+            .map(|(_, contents)| (name.clone(), contents.to_string()))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no such embedded file"))
+    }
+}
+
+/// Resolves includes from files on disk, below a root directory.
+#[derive(Debug, Clone)]
+pub struct FileSystem {
+    root: PathBuf,
+}
+
+impl FileSystem {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl IncludeResolver for FileSystem {
+    fn resolve(&self, path: &str, includer: &str) -> io::Result<(String, String)> {
+        let name = join_include_path(includer, path);
+        let contents = fs::read_to_string(self.root.join(&name))?;
+        Ok((name, contents))
+    }
+}
+
+/// Where a line of preprocessed output came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceLocation<'a> {
+    /// The canonical file name, or [`GENERATED_FILE`].
+    pub file: &'a str,
+    /// The 1-based line number within `file`.
+    pub line: u32,
+}
+
+impl fmt::Display for SourceLocation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// Maps lines of preprocessed output back to the files they came from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    files: Vec<String>,
+    /// For each output line, the index into `files` (or `None` for generated lines) and the line
+    /// number within that file.
+    lines: Vec<(Option<usize>, u32)>,
+}
+
+impl SourceMap {
+    /// Returns where 1-based output line `line` came from.
+    pub fn lookup(&self, line: u32) -> Option<SourceLocation<'_>> {
+        let &(file, line) = self.lines.get((line as usize).checked_sub(1)?)?;
+        Some(SourceLocation {
+            file: file.map_or(GENERATED_FILE, |i| &self.files[i]),
+            line,
+        })
+    }
+
+    /// The number of output lines.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Every file that contributed to the output, in the order they were first included.
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    fn push(&mut self, file: Option<usize>, line: u32) {
+        self.lines.push((file, line));
+    }
+
+    fn file_index(&mut self, name: &str) -> usize {
+        match self.files.iter().position(|f| f == name) {
+            Some(i) => i,
+            None => {
+                self.files.push(name.to_string());
+                self.files.len() - 1
+            }
+        }
+    }
+
+    /// Rewrites the locations in a driver's info log to point at the original files.
+    ///
+    /// Drivers don't agree on how to format locations, so the common styles are all recognised:
+    ///
+    /// - Mesa: `0:12(5): error: ...`
+    /// - NVIDIA: `0(12) : error C1008: ...`
+    /// - AMD and Intel on Windows: `ERROR: 0:12: ...`
+    ///
+    /// Only the first location on each line is rewritten, and locations whose line isn't in the
+    /// map are left alone.
+    pub fn rewrite_info_log(&self, log: &str) -> String {
+        let mut out = String::with_capacity(log.len());
+        for line in log.split_inclusive('\n') {
+            match find_log_location(line) {
+                Some((start, end, out_line, paren)) => match self.lookup(out_line) {
+                    Some(loc) => {
+                        out.push_str(&line[..start]);
+                        if paren {
+                            out.push_str(&format!("{}({})", loc.file, loc.line));
+                        } else {
+                            out.push_str(&format!("{}:{}", loc.file, loc.line));
+                        }
+                        out.push_str(&line[end..]);
+                    }
+                    None => out.push_str(line),
+                },
+                None => out.push_str(line),
+            }
+        }
+        out
+    }
+}
+
+/// Finds the first `string:line` or `string(line)` location in an info log line.
+///
+/// Returns the byte range of the location, the line number, and whether it used parentheses.
+fn find_log_location(text: &str) -> Option<(usize, usize, u32, bool)> {
+    let bytes = text.as_bytes();
+    let digits_from = |i: usize| {
+        let n = bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+        (n > 0).then_some(i + n)
+    };
+
+    for start in 0..bytes.len() {
+        if start > 0 && (bytes[start - 1].is_ascii_alphanumeric() || bytes[start - 1] == b'_') {
+            continue;
+        }
+        let Some(string_end) = digits_from(start) else {
+            continue;
+        };
+
+        let (paren, line_start) = match bytes.get(string_end) {
+            Some(b':') => (false, string_end + 1),
+            Some(b'(') => (true, string_end + 1),
+            _ => continue,
+        };
+        if line_start >= bytes.len() {
+            continue;
+        }
+        let Some(line_end) = digits_from(line_start) else {
+            continue;
+        };
+        let end = if paren {
+            if bytes.get(line_end) != Some(&b')') {
+                continue;
+            }
+            line_end + 1
+        } else {
+            line_end
+        };
+
+        let line = text[line_start..line_end].parse().ok()?;
+        return Some((start, end, line, paren));
+    }
+
+    None
+}
+
+/// The result of [`Preprocessor::run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preprocessed {
+    /// The source to hand to the driver.
+    pub source: String,
+    pub map: SourceMap,
+}
+
+/// Anything that can go wrong while preprocessing.
+#[derive(Debug)]
+pub enum PreprocessError {
+    /// A file couldn't be loaded. `includer` is `None` for the top-level file.
+    Io {
+        path: String,
+        includer: Option<SourceLocationBuf>,
+        error: io::Error,
+    },
+    /// An `#include` directive wasn't followed by a quoted path.
+    MalformedInclude { at: SourceLocationBuf, text: String },
+    /// A file (indirectly) includes itself. The chain starts and ends with the same file.
+    IncludeCycle { chain: Vec<String> },
+}
+
+/// An owned [`SourceLocation`], for use in errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocationBuf {
+    pub file: String,
+    pub line: u32,
+}
+
+impl fmt::Display for SourceLocationBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+impl fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io {
+                path,
+                includer: Some(at),
+                error,
+            } => write!(f, "{at}: unable to include \"{path}\": {error}"),
+            Self::Io {
+                path,
+                includer: None,
+                error,
+            } => write!(f, "unable to load \"{path}\": {error}"),
+            Self::MalformedInclude { at, text } => {
+                write!(f, "{at}: expected `#include \"path\"`, found `{text}`")
+            }
+            Self::IncludeCycle { chain } => {
+                write!(f, "include cycle: {}", chain.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for PreprocessError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Expands `#include`s and adds a `#version` header and `#define`s to GLSL sources.
+#[derive(Debug, Clone)]
+pub struct Preprocessor<R> {
+    version: GlslVersion,
+    defines: Vec<(String, String)>,
+    resolver: R,
+}
+
+impl<R: IncludeResolver> Preprocessor<R> {
+    pub fn new(version: GlslVersion, resolver: R) -> Self {
+        Self {
+            version,
+            defines: Vec::new(),
+            resolver,
+        }
+    }
+
+    /// Adds `#define name value` after the `#version` header. `value` may be empty.
+    pub fn define(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.defines.push((name.into(), value.into()));
+        self
+    }
+
+    /// Changes the version to emit a header for.
+    pub fn version(mut self, version: GlslVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Loads `name` through the resolver and preprocesses it.
+    pub fn run(&self, name: &str) -> Result<Preprocessed, PreprocessError> {
+        let (name, source) =
+            self.resolver
+                .resolve(name, "")
+                .map_err(|error| PreprocessError::Io {
+                    path: name.to_string(),
+                    includer: None,
+                    error,
+                })?;
+        self.run_source(&name, &source)
+    }
+
+    /// Preprocesses `source` as though it had been loaded from a file called `name`. Includes are
+    /// resolved relative to `name`.
+    pub fn run_source(&self, name: &str, source: &str) -> Result<Preprocessed, PreprocessError> {
+        let mut state = State {
+            out: Preprocessed {
+                source: String::with_capacity(source.len()),
+                map: SourceMap::default(),
+            },
+            stack: Vec::new(),
+            once: HashSet::new(),
+        };
+
+        state.emit_generated(&self.version.to_string());
+        for (name, value) in &self.defines {
+            state.emit_generated(format!("#define {name} {value}").trim_end());
+        }
+
+        self.expand(&mut state, name, source)?;
+        Ok(state.out)
+    }
+
+    fn expand(&self, state: &mut State, name: &str, source: &str) -> Result<(), PreprocessError> {
+        // A file that's already marked `#pragma once` is skipped even if it's still being expanded
+        // further up the stack, so mutually including guarded files isn't a cycle.
+        if state.once.contains(name) {
+            return Ok(());
+        }
+        if state.stack.iter().any(|f| f == name) {
+            let mut chain = state.stack.clone();
+            chain.push(name.to_string());
+            return Err(PreprocessError::IncludeCycle { chain });
+        }
+
+        state.stack.push(name.to_string());
+        let file = state.out.map.file_index(name);
+        let mut in_comment = false;
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index as u32 + 1;
+            let directive = if in_comment { None } else { directive(line) };
+            in_comment = ends_in_block_comment(line, in_comment);
+
+            match directive {
+                Some(("include", rest)) => {
+                    let at = || SourceLocationBuf {
+                        file: name.to_string(),
+                        line: line_number,
+                    };
+                    let path = parse_include_path(rest).ok_or_else(|| {
+                        PreprocessError::MalformedInclude {
+                            at: at(),
+                            text: line.trim().to_string(),
+                        }
+                    })?;
+                    let (included, contents) =
+                        self.resolver
+                            .resolve(path, name)
+                            .map_err(|error| PreprocessError::Io {
+                                path: path.to_string(),
+                                includer: Some(at()),
+                                error,
+                            })?;
+                    self.expand(state, &included, &contents)?;
+                }
+                Some(("pragma", rest)) if rest.split_whitespace().next() == Some("once") => {
+                    state.once.insert(name.to_string());
+                    state.emit(file, line_number, "");
+                }
+                // The header already has the one true #version; keep the line so numbering
+                // doesn't shift.
+                Some(("version", _)) => state.emit(file, line_number, ""),
+                _ => state.emit(file, line_number, line),
+            }
+        }
+
+        state.stack.pop();
+        Ok(())
+    }
+}
+
+struct State {
+    out: Preprocessed,
+    /// The chain of files currently being expanded, for cycle detection.
+    stack: Vec<String>,
+    /// Files that contained `#pragma once`.
+    once: HashSet<String>,
+}
+
+impl State {
+    fn emit(&mut self, file: usize, line: u32, text: &str) {
+        self.out.source.push_str(text);
+        self.out.source.push('\n');
+        self.out.map.push(Some(file), line);
+    }
+
+    fn emit_generated(&mut self, text: &str) {
+        self.out.source.push_str(text);
+        self.out.source.push('\n');
+        let line = self.out.map.lines.len() as u32 + 1;
+        self.out.map.push(None, line);
+    }
+}
+
+/// Splits a preprocessor directive line into its name and the rest of the line.
+fn directive(line: &str) -> Option<(&str, &str)> {
+    let rest = line.trim_start().strip_prefix('#')?.trim_start();
+    let name_len = rest
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .unwrap_or(rest.len());
+    Some((&rest[..name_len], &rest[name_len..]))
+}
+
+/// Parses the `"path"` part of an `#include` directive, allowing a trailing comment.
+fn parse_include_path(rest: &str) -> Option<&str> {
+    let rest = rest.trim_start().strip_prefix('"')?;
+    let end = rest.find('"')?;
+    let (path, tail) = (&rest[..end], rest[end + 1..].trim());
+    let tail_ok = tail.is_empty() || tail.starts_with("//") || tail.starts_with("/*");
+    (!path.is_empty() && tail_ok).then_some(path)
+}
+
+/// Returns whether a block comment is still open at the end of `line`.
+fn ends_in_block_comment(line: &str, mut in_comment: bool) -> bool {
+    let mut rest = line;
+    loop {
+        if in_comment {
+            match rest.find("*/") {
+                Some(i) => {
+                    rest = &rest[i + 2..];
+                    in_comment = false;
+                }
+                None => return true,
+            }
+        } else {
+            let block = rest.find("/*");
+            let line_comment = rest.find("//");
+            match (block, line_comment) {
+                (Some(b), Some(l)) if l < b => return false,
+                (Some(b), _) => {
+                    rest = &rest[b + 2..];
+                    in_comment = true;
+                }
+                (None, _) => return false,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(files: &[(&str, &str)], main: &str) -> Result<Preprocessed, PreprocessError> {
+        Preprocessor::new(GlslVersion::GL_460_CORE, EmbeddedFiles(files)).run(main)
+    }
+
+    mod join_include_path {
+        use super::*;
+
+        #[test]
+        fn relative_to_includer() {
+            assert_eq!(join_include_path("a/b/main.glsl", "c.glsl"), "a/b/c.glsl");
+            assert_eq!(join_include_path("main.glsl", "lib/c.glsl"), "lib/c.glsl");
+            assert_eq!(join_include_path("", "main.glsl"), "main.glsl");
+        }
+
+        #[test]
+        fn resolves_dots() {
+            assert_eq!(join_include_path("a/b/main.glsl", "../c.glsl"), "a/c.glsl");
+            assert_eq!(
+                join_include_path("a/main.glsl", "./x/../c.glsl"),
+                "a/c.glsl"
+            );
+            assert_eq!(join_include_path("main.glsl", "../../c.glsl"), "c.glsl");
+        }
+
+        #[test]
+        fn absolute_paths_start_at_root() {
+            assert_eq!(join_include_path("a/b/main.glsl", "/c.glsl"), "c.glsl");
+        }
+
+        #[test]
+        fn accepts_backslashes() {
+            assert_eq!(
+                join_include_path("a\\main.glsl", "lib\\c.glsl"),
+                "a/lib/c.glsl"
+            );
+        }
+    }
+
+    mod expansion {
+        use super::*;
+
+        #[test]
+        fn writes_header_and_defines() {
+            let out = Preprocessor::new(GlslVersion::ES_310, EmbeddedFiles(&[]))
+                .define("A", "1")
+                .define("FLAG", "")
+                .run_source("main.glsl", "void main() {}")
+                .unwrap();
+            assert_eq!(
+                out.source,
+                "#version 310 es\n#define A 1\n#define FLAG\nvoid main() {}\n"
+            );
+            assert_eq!(out.map.lookup(2).unwrap().file, GENERATED_FILE);
+            assert_eq!(
+                out.map.lookup(4),
+                Some(SourceLocation {
+                    file: "main.glsl",
+                    line: 1
+                })
+            );
+        }
+
+        #[test]
+        fn replaces_source_version() {
+            let out = run(
+                &[("main.glsl", "#version 330 core\nvoid main() {}\n")],
+                "main.glsl",
+            )
+            .unwrap();
+            assert_eq!(out.source, "#version 460 core\n\nvoid main() {}\n");
+            assert_eq!(out.map.lookup(3).unwrap().line, 2);
+        }
+
+        #[test]
+        fn expands_nested_includes() {
+            let files = [
+                (
+                    "main.glsl",
+                    "// main\n#include \"lib/a.glsl\"\nvoid main() {}",
+                ),
+                (
+                    "lib/a.glsl",
+                    "#include \"b.glsl\" // b is next door\nfloat a;",
+                ),
+                ("lib/b.glsl", "float b;"),
+            ];
+            let out = run(&files, "main.glsl").unwrap();
+            assert_eq!(
+                out.source,
+                "#version 460 core\n// main\nfloat b;\nfloat a;\nvoid main() {}\n"
+            );
+
+            let locations: Vec<_> = (1..=5)
+                .map(|l| out.map.lookup(l).unwrap().to_string())
+                .collect();
+            assert_eq!(
+                locations,
+                [
+                    "<generated>:1",
+                    "main.glsl:1",
+                    "lib/b.glsl:1",
+                    "lib/a.glsl:2",
+                    "main.glsl:3"
+                ]
+            );
+            assert_eq!(out.map.lookup(6), None);
+            assert_eq!(out.map.lookup(0), None);
+        }
+
+        #[test]
+        fn pragma_once_includes_a_file_once() {
+            let files = [
+                (
+                    "main.glsl",
+                    "#include \"a.glsl\"\n#include \"a.glsl\"\nvoid main() {}",
+                ),
+                ("a.glsl", "#pragma once\nfloat a;"),
+            ];
+            let out = run(&files, "main.glsl").unwrap();
+            assert_eq!(out.source.matches("float a;").count(), 1);
+        }
+
+        #[test]
+        fn files_without_pragma_once_can_repeat() {
+            let files = [
+                ("main.glsl", "#include \"a.glsl\"\n#include \"a.glsl\""),
+                ("a.glsl", "float a;"),
+            ];
+            let out = run(&files, "main.glsl").unwrap();
+            assert_eq!(out.source.matches("float a;").count(), 2);
+        }
+
+        #[test]
+        fn ignores_includes_in_comments() {
+            let files = [(
+                "main.glsl",
+                "/* old:\n#include \"gone.glsl\"\n*/\n// #include \"gone.glsl\"\nvoid main() {}",
+            )];
+            let out = run(&files, "main.glsl").unwrap();
+            assert!(out.source.contains("#include \"gone.glsl\""));
+        }
+    }
+
+    mod errors {
+        use super::*;
+
+        #[test]
+        fn detects_cycles() {
+            let files = [
+                ("main.glsl", "#include \"a.glsl\""),
+                ("a.glsl", "#include \"b.glsl\""),
+                ("b.glsl", "#include \"a.glsl\""),
+            ];
+            match run(&files, "main.glsl") {
+                Err(PreprocessError::IncludeCycle { chain }) => {
+                    assert_eq!(chain, ["main.glsl", "a.glsl", "b.glsl", "a.glsl"])
+                }
+                other => panic!("expected a cycle, got {other:?}"),
+            }
+        }
+
+        #[test]
+        fn pragma_once_breaks_cycles() {
+            let files = [
+                ("main.glsl", "#include \"a.glsl\""),
+                ("a.glsl", "#pragma once\n#include \"b.glsl\"\nfloat a;"),
+                ("b.glsl", "#pragma once\n#include \"a.glsl\"\nfloat b;"),
+            ];
+            let out = run(&files, "main.glsl").unwrap();
+            assert_eq!(out.source.matches("float a;").count(), 1);
+            assert_eq!(out.source.matches("float b;").count(), 1);
+        }
+
+        #[test]
+        fn detects_self_includes() {
+            let files = [("main.glsl", "#include \"main.glsl\"")];
+            assert!(matches!(
+                run(&files, "main.glsl"),
+                Err(PreprocessError::IncludeCycle { .. })
+            ));
+        }
+
+        #[test]
+        fn reports_missing_includes() {
+            let files = [("main.glsl", "\n\n#include \"missing.glsl\"")];
+            let err = run(&files, "main.glsl").unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "main.glsl:3: unable to include \"missing.glsl\": no such embedded file"
+            );
+        }
+
+        #[test]
+        fn reports_malformed_includes() {
+            for bad in [
+                "#include <a.glsl>",
+                "#include a.glsl",
+                "#include \"\"",
+                "#include \"a.glsl",
+                "#include \"a.glsl\" junk",
+            ] {
+                let files = [("main.glsl", bad), ("a.glsl", "")];
+                assert!(
+                    matches!(
+                        run(&files, "main.glsl"),
+                        Err(PreprocessError::MalformedInclude { .. })
+                    ),
+                    "{bad}"
+                );
+            }
+        }
+
+        #[test]
+        fn reports_missing_top_level_file() {
+            assert!(matches!(
+                run(&[], "main.glsl"),
+                Err(PreprocessError::Io { includer: None, .. })
+            ));
+        }
+    }
+
+    mod file_system {
+        use super::*;
+
+        #[test]
+        fn resolves_relative_to_root() {
+            let root = std::env::temp_dir().join(format!(
+                "triangle-from-scratch-glsl-fs-{}",
+                std::process::id()
+            ));
+            fs::create_dir_all(root.join("shaders/lib")).unwrap();
+            fs::write(
+                root.join("shaders/main.frag"),
+                "#include \"lib/util.glsl\"\nvoid main() {}\n",
+            )
+            .unwrap();
+            fs::write(root.join("shaders/lib/util.glsl"), "float util;\n").unwrap();
+
+            let result = Preprocessor::new(GlslVersion::GL_330_CORE, FileSystem::new(&root))
+                .run("shaders/main.frag");
+            fs::remove_dir_all(&root).ok();
+
+            let out = result.unwrap();
+            assert_eq!(
+                out.source,
+                "#version 330 core\nfloat util;\nvoid main() {}\n"
+            );
+            assert_eq!(
+                out.map.files(),
+                ["shaders/main.frag", "shaders/lib/util.glsl"]
+            );
+        }
+    }
+
+    mod info_logs {
+        use super::*;
+
+        fn map() -> SourceMap {
+            let files = [
+                ("main.glsl", "#include \"lib.glsl\"\nvoid main() {}"),
+                ("lib.glsl", "float a;\nfloat b;"),
+            ];
+            run(&files, "main.glsl").unwrap().map
+        }
+
+        #[test]
+        fn rewrites_mesa_logs() {
+            assert_eq!(
+                map().rewrite_info_log("0:3(7): error: syntax error\n0:4(1): warning: hmm\n"),
+                "lib.glsl:2(7): error: syntax error\nmain.glsl:2(1): warning: hmm\n"
+            );
+        }
+
+        #[test]
+        fn rewrites_nvidia_logs() {
+            assert_eq!(
+                map().rewrite_info_log("0(2) : error C1008: undefined variable \"x\""),
+                "lib.glsl(1) : error C1008: undefined variable \"x\""
+            );
+        }
+
+        #[test]
+        fn rewrites_amd_logs() {
+            assert_eq!(
+                map().rewrite_info_log("ERROR: 0:4: 'x' : undeclared identifier"),
+                "ERROR: main.glsl:2: 'x' : undeclared identifier"
+            );
+        }
+
+        #[test]
+        fn leaves_unknown_locations_alone() {
+            let log = "0:99(1): error: past the end\nno location here\nvec4:3 isn't one";
+            assert_eq!(map().rewrite_info_log(log), log);
+        }
+    }
+}
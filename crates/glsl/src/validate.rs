@@ -0,0 +1,1260 @@
+//! Checks shaders for mistakes that otherwise only show up when a program fails to link, or
+//! silently renders garbage.
+//!
+//! [`Validator`] parses every stage of a program and checks:
+//!
+//! - that each stage has a `#version` the stage and its features are available in,
+//! - that the stages agree on which flavour of GLSL they're written in,
+//! - that every `in` variable has a matching `out` in the previous stage, with the same type,
+//!   location and interpolation,
+//! - and, when given the vertex layout the program is drawn with, that every vertex shader input
+//!   has an attribute of the right kind at its location.
+//!
+//! None of this needs a GPU or an OpenGL context.
+//!
+//! ```
+//! use triangle_from_scratch_glsl::validate::{AttributeKind, Stage, Validator, VertexInput};
+//!
+//! let diagnostics = Validator::new()
+//!     .stage(
+//!         Stage::Vertex,
+//!         "tri.vs",
+//!         "#version 330 core
+//!         layout(location = 0) in vec2 pos;
+//!         out vec3 color;
+//!         void main() { gl_Position = vec4(pos, 0.0, 1.0); color = vec3(1.0); }",
+//!     )
+//!     .stage(
+//!         Stage::Fragment,
+//!         "tri.fs",
+//!         "#version 330 core
+//!         in vec4 color;
+//!         out vec4 frag_color;
+//!         void main() { frag_color = color; }",
+//!     )
+//!     .vertex_inputs(&[VertexInput::new(0, 2, AttributeKind::Float)])
+//!     .run();
+//!
+//! assert_eq!(diagnostics.len(), 1);
+//! assert_eq!(
+//!     diagnostics[0].to_string(),
+//!     "tri.fs:2: error: `in vec4 color` doesn't match `out vec3 color` in the vertex shader (tri.vs:3)"
+//! );
+//! ```
+
+use std::fmt;
+
+use crate::{
+    ast::*,
+    lexer::number_suffix,
+    parser::parse,
+    preprocess::{Preprocessed, Profile, SourceMap, GENERATED_FILE},
+};
+
+/// A programmable stage of the pipeline, in pipeline order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stage {
+    Vertex,
+    TessControl,
+    TessEvaluation,
+    Geometry,
+    Fragment,
+    Compute,
+}
+
+impl Stage {
+    /// Guesses a shader's stage from its file extension, recognising both the short extensions
+    /// used in this repository (`.vs`, `.fs`, ...) and the ones `glslangValidator` uses (`.vert`,
+    /// `.frag`, ...).
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Some(match extension {
+            "vs" | "vert" => Self::Vertex,
+            "tcs" | "tesc" => Self::TessControl,
+            "tes" | "tese" => Self::TessEvaluation,
+            "gs" | "geom" => Self::Geometry,
+            "fs" | "frag" => Self::Fragment,
+            "cs" | "comp" => Self::Compute,
+            _ => return None,
+        })
+    }
+
+    /// The stage's name, as used in messages.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Vertex => "vertex shader",
+            Self::TessControl => "tessellation control shader",
+            Self::TessEvaluation => "tessellation evaluation shader",
+            Self::Geometry => "geometry shader",
+            Self::Fragment => "fragment shader",
+            Self::Compute => "compute shader",
+        }
+    }
+
+    /// Whether this stage's inputs are arrays with one element per vertex.
+    fn has_per_vertex_inputs(self) -> bool {
+        matches!(
+            self,
+            Self::TessControl | Self::TessEvaluation | Self::Geometry
+        )
+    }
+
+    /// The versions this stage first became available in, for desktop GL and for GLES.
+    fn minimum_versions(self) -> (u16, u16) {
+        match self {
+            Self::Vertex | Self::Fragment => (110, 100),
+            Self::Geometry => (150, 320),
+            Self::TessControl | Self::TessEvaluation => (400, 320),
+            Self::Compute => (430, 310),
+        }
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Probably a mistake, but the program will still link.
+    Warning,
+    /// The program won't compile or link, or won't get the data it expects.
+    Error,
+}
+
+/// A problem found by a [`Validator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The file the problem is in.
+    pub file: String,
+    /// The 1-based line within `file`, or 0 if the problem isn't on any particular line.
+    pub line: u32,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    /// Formats the diagnostic like a compiler would, e.g. `fragment.fs:2: error: ...`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        match self.line {
+            0 => write!(f, "{}: {severity}: {}", self.file, self.message),
+            line => write!(f, "{}:{line}: {severity}: {}", self.file, self.message),
+        }
+    }
+}
+
+/// Which `glVertexAttrib*Pointer` function an attribute is set up with, and so which vertex
+/// shader input types can read it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AttributeKind {
+    /// `glVertexAttribPointer`, read by `float` and `vec*` inputs.
+    Float,
+    /// `glVertexAttribIPointer`, read by `int`, `uint`, `ivec*` and `uvec*` inputs.
+    Integer,
+    /// `glVertexAttribLPointer`, read by `double` and `dvec*` inputs.
+    Double,
+}
+
+/// One attribute of the vertex layout a program is drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VertexInput {
+    pub location: u32,
+    /// The number of components, from 1 to 4.
+    pub components: u8,
+    pub kind: AttributeKind,
+}
+
+impl VertexInput {
+    pub const fn new(location: u32, components: u8, kind: AttributeKind) -> Self {
+        Self {
+            location,
+            components,
+            kind,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct StageSource<'a> {
+    stage: Stage,
+    name: &'a str,
+    source: &'a str,
+    /// Maps lines back to the original files, for preprocessed sources.
+    map: Option<&'a SourceMap>,
+}
+
+impl StageSource<'_> {
+    /// Works out which file and line `line` of the source came from.
+    fn locate(&self, line: u32) -> (String, u32) {
+        match self.map.and_then(|m| m.lookup(line)) {
+            Some(loc) => (loc.file.to_string(), loc.line),
+            None => (self.name.to_string(), line),
+        }
+    }
+}
+
+/// Checks the stages of a program against each other and against the vertex layout they're used
+/// with.
+#[derive(Debug, Clone, Default)]
+pub struct Validator<'a> {
+    stages: Vec<StageSource<'a>>,
+    vertex_inputs: Option<Vec<VertexInput>>,
+}
+
+impl<'a> Validator<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a stage's source, which should already have had any `#include`s expanded. `name` is
+    /// used as the file name in diagnostics.
+    pub fn stage(mut self, stage: Stage, name: &'a str, source: &'a str) -> Self {
+        self.stages.push(StageSource {
+            stage,
+            name,
+            source,
+            map: None,
+        });
+        self
+    }
+
+    /// Adds the output of the [`Preprocessor`](crate::preprocess::Preprocessor) as a stage.
+    /// Diagnostics point at the files the lines came from.
+    pub fn preprocessed_stage(mut self, stage: Stage, preprocessed: &'a Preprocessed) -> Self {
+        self.stages.push(StageSource {
+            stage,
+            name: preprocessed
+                .map
+                .files()
+                .first()
+                .map_or(GENERATED_FILE, String::as_str),
+            source: &preprocessed.source,
+            map: Some(&preprocessed.map),
+        });
+        self
+    }
+
+    /// Sets the vertex layout the program will be drawn with, so the vertex shader's inputs can be
+    /// checked against it.
+    pub fn vertex_inputs(mut self, inputs: &[VertexInput]) -> Self {
+        self.vertex_inputs = Some(inputs.to_vec());
+        self
+    }
+
+    /// Runs every check, returning the problems found in the order of the stages they were found
+    /// in.
+    pub fn run(&self) -> Vec<Diagnostic> {
+        let mut report = Report::default();
+
+        let mut units = Vec::new();
+        for source in &self.stages {
+            match parse(source.source) {
+                Ok(unit) => units.push((*source, unit)),
+                Err(e) => report.error(source, e.line, e.message),
+            }
+        }
+
+        for (source, unit) in &units {
+            check_stage(&mut report, source, unit);
+        }
+        check_versions_agree(&mut report, &units);
+
+        let mut pipeline: Vec<_> = units
+            .iter()
+            .filter(|(s, _)| s.stage != Stage::Compute)
+            .collect();
+        pipeline.sort_by_key(|(s, _)| s.stage);
+        for pair in pipeline.windows(2) {
+            let [(producer, out_unit), (consumer, in_unit)] = pair else {
+                unreachable!()
+            };
+            if producer.stage == consumer.stage {
+                report.error(
+                    consumer,
+                    0,
+                    format!("more than one {} in the program", consumer.stage),
+                );
+                continue;
+            }
+            check_interface(&mut report, (producer, out_unit), (consumer, in_unit));
+        }
+
+        if let Some(inputs) = &self.vertex_inputs {
+            for (source, unit) in units.iter().filter(|(s, _)| s.stage == Stage::Vertex) {
+                check_vertex_inputs(&mut report, source, unit, inputs);
+            }
+        }
+
+        report.diagnostics
+    }
+}
+
+#[derive(Debug, Default)]
+struct Report {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Report {
+    fn push(
+        &mut self,
+        severity: Severity,
+        source: &StageSource<'_>,
+        line: u32,
+        message: impl Into<String>,
+    ) {
+        let (file, line) = match line {
+            0 => (source.name.to_string(), 0),
+            line => source.locate(line),
+        };
+        self.diagnostics.push(Diagnostic {
+            severity,
+            file,
+            line,
+            message: message.into(),
+        });
+    }
+
+    fn error(&mut self, source: &StageSource<'_>, line: u32, message: impl Into<String>) {
+        self.push(Severity::Error, source, line, message);
+    }
+
+    fn warning(&mut self, source: &StageSource<'_>, line: u32, message: impl Into<String>) {
+        self.push(Severity::Warning, source, line, message);
+    }
+}
+
+/// The GLSL versions that exist, for desktop GL and for GLES.
+const DESKTOP_VERSIONS: &[u16] = &[
+    110, 120, 130, 140, 150, 330, 400, 410, 420, 430, 440, 450, 460,
+];
+const ES_VERSIONS: &[u16] = &[100, 300, 310, 320];
+
+/// The version a shader without a `#version` directive is compiled as.
+const DEFAULT_VERSION: VersionDirective = VersionDirective {
+    version: crate::preprocess::GlslVersion::new(110, Profile::Compatibility),
+    explicit_profile: false,
+    line: 0,
+};
+
+/// Returns whether `version` is at least `desktop` (for desktop GL) or `es` (for GLES).
+fn at_least(version: &VersionDirective, desktop: u16, es: u16) -> bool {
+    match version.version.profile {
+        Profile::Es => version.version.number >= es,
+        _ => version.version.number >= desktop,
+    }
+}
+
+/// Formats a version for messages, like `GLSL 3.30` or `GLSL ES 3.00`.
+fn version_name(version: &VersionDirective) -> String {
+    let number = version.version.number;
+    let es = if version.version.profile == Profile::Es {
+        " ES"
+    } else {
+        ""
+    };
+    format!("GLSL{es} {}.{:02}", number / 100, number % 100)
+}
+
+/// Checks everything that only involves a single stage.
+fn check_stage(report: &mut Report, source: &StageSource<'_>, unit: &TranslationUnit<'_>) {
+    let stage = source.stage;
+    let version = match &unit.version {
+        Some(version) => version,
+        None => {
+            report.warning(
+                source,
+                0,
+                "no #version directive, so this will be compiled as GLSL 1.10",
+            );
+            &DEFAULT_VERSION
+        }
+    };
+    let known = match version.version.profile {
+        Profile::Es => ES_VERSIONS,
+        _ => DESKTOP_VERSIONS,
+    };
+    if !known.contains(&version.version.number) {
+        report.error(
+            source,
+            version.line,
+            format!("{} doesn't exist", version_name(version)),
+        );
+        return;
+    }
+
+    let (desktop, es) = stage.minimum_versions();
+    if !at_least(version, desktop, es) {
+        report.error(
+            source,
+            version.line,
+            format!(
+                "{}s aren't available in {}",
+                stage.name(),
+                version_name(version)
+            ),
+        );
+    }
+
+    for literal in &unit.suffixed_literals {
+        let (what, desktop, es) = match number_suffix(literal.text) {
+            "u" | "U" => ("unsigned integer literals need", 130, 300),
+            "f" | "F" => ("the `f` suffix needs", 120, 300),
+            _ => ("double literals need", 400, u16::MAX),
+        };
+        if !at_least(version, desktop, es) {
+            report.error(
+                source,
+                literal.line,
+                format!(
+                    "`{}`: {what} {}",
+                    literal.text,
+                    required_version(version, desktop, es)
+                ),
+            );
+        }
+    }
+
+    let mut unlocated_outputs = Vec::new();
+    for decl in &unit.declarations {
+        let (qualifiers, line, description) = match decl {
+            Declaration::Variable(v) => (&v.qualifiers, v.line, describe(v)),
+            Declaration::Block(b) => (&b.qualifiers, b.line, format!("block `{}`", b.name)),
+            _ => continue,
+        };
+        let Some(storage) = qualifiers.storage else {
+            continue;
+        };
+
+        match storage {
+            Storage::In | Storage::Out if !at_least(version, 130, 300) => report.error(
+                source,
+                line,
+                format!(
+                    "`{}` variables need {}; use `{}` instead",
+                    storage.keyword(),
+                    required_version(version, 130, 300),
+                    match (storage, stage) {
+                        (Storage::In, Stage::Vertex) => "attribute",
+                        _ => "varying",
+                    }
+                ),
+            ),
+            Storage::Attribute | Storage::Varying if at_least(version, 130, 300) => {
+                let message = format!(
+                    "`{}` is deprecated in {}; use `{}` instead",
+                    storage.keyword(),
+                    version_name(version),
+                    match (storage, stage) {
+                        (Storage::Varying, Stage::Vertex) => "out",
+                        (Storage::Varying, Stage::Fragment) | (Storage::Attribute, _) => "in",
+                        _ => "in/out",
+                    }
+                );
+                match version.version.profile {
+                    Profile::Es => report.error(source, line, message),
+                    _ => report.warning(source, line, message),
+                }
+            }
+            _ => (),
+        }
+
+        let is_input = is_input(storage, stage);
+        let is_output = is_output(storage, stage);
+        if qualifiers.layout("location").is_some() && (is_input || is_output) {
+            // The API-facing interfaces got locations earlier than the ones between stages.
+            let api_facing =
+                (is_input && stage == Stage::Vertex) || (is_output && stage == Stage::Fragment);
+            let (desktop, es, extension) = if api_facing {
+                (330, 300, "GL_ARB_explicit_attrib_location")
+            } else {
+                (410, 310, "GL_ARB_separate_shader_objects")
+            };
+            if !at_least(version, desktop, es) && !unit.has_extension(extension) {
+                report.error(
+                    source,
+                    line,
+                    format!(
+                        "layout(location) on {description} needs {} or {extension}",
+                        required_version(version, desktop, es)
+                    ),
+                );
+            }
+        }
+
+        if let Declaration::Variable(v) = decl {
+            if stage == Stage::Fragment && is_output && qualifiers.location().is_none() {
+                unlocated_outputs.push(v);
+            }
+            // Integers can't be interpolated.
+            if stage == Stage::Fragment
+                && is_input
+                && qualifiers.interpolation != Some(Interpolation::Flat)
+                && matches!(
+                    scalar_kind(v.ty.name),
+                    Some(AttributeKind::Integer | AttributeKind::Double)
+                )
+            {
+                report.error(
+                    source,
+                    line,
+                    format!("{description} has to be `flat`, since it can't be interpolated"),
+                );
+            }
+        }
+    }
+
+    if unlocated_outputs.len() > 1 {
+        let names: Vec<_> = unlocated_outputs
+            .iter()
+            .map(|v| format!("`{}`", v.name))
+            .collect();
+        report.error(
+            source,
+            unlocated_outputs[1].line,
+            format!(
+                "fragment outputs {} need layout(location) to say which draw buffer they write to",
+                names.join(", ")
+            ),
+        );
+    }
+}
+
+fn required_version(version: &VersionDirective, desktop: u16, es: u16) -> String {
+    match version.version.profile {
+        Profile::Es if es == u16::MAX => "desktop GLSL".to_string(),
+        Profile::Es => format!("GLSL ES {}.{:02}", es / 100, es % 100),
+        _ => format!("GLSL {}.{:02}", desktop / 100, desktop % 100),
+    }
+}
+
+fn is_input(storage: Storage, stage: Stage) -> bool {
+    match storage {
+        Storage::In => true,
+        Storage::Attribute => stage == Stage::Vertex,
+        Storage::Varying => stage != Stage::Vertex,
+        _ => false,
+    }
+}
+
+fn is_output(storage: Storage, stage: Stage) -> bool {
+    match storage {
+        Storage::Out => true,
+        Storage::Varying => stage == Stage::Vertex,
+        _ => false,
+    }
+}
+
+/// Describes a variable the way it was declared, like `in vec3 vert_color`.
+fn describe(v: &Variable<'_>) -> String {
+    match v.qualifiers.storage {
+        Some(storage) => format!("`{} {} {}`", storage.keyword(), v.ty, v.name),
+        None => format!("`{} {}`", v.ty, v.name),
+    }
+}
+
+/// Checks that the stages were all written for the same flavour and version of GLSL.
+fn check_versions_agree(report: &mut Report, units: &[(StageSource<'_>, TranslationUnit<'_>)]) {
+    let versioned: Vec<_> = units
+        .iter()
+        .filter_map(|(s, u)| Some((s, u.version?)))
+        .collect();
+    let Some(&(first_source, first)) = versioned.first() else {
+        return;
+    };
+
+    for &(source, version) in &versioned[1..] {
+        let is_es = |v: &VersionDirective| v.version.profile == Profile::Es;
+        if is_es(&version) != is_es(&first) {
+            report.error(
+                source,
+                version.line,
+                format!(
+                    "{} can't be linked with {} ({})",
+                    version_name(&version),
+                    version_name(&first),
+                    first_source.name
+                ),
+            );
+        } else if version.version != first.version {
+            report.warning(
+                source,
+                version.line,
+                format!(
+                    "`{}` differs from `{}` in {}; stages should be written for the same version",
+                    version.version, first.version, first_source.name
+                ),
+            );
+        }
+    }
+}
+
+/// An `in` or `out` variable, as seen by one vertex.
+struct InterfaceVariable<'u, 'a> {
+    var: &'u Variable<'a>,
+    ty: TypeSpecifier<'a>,
+    used: bool,
+}
+
+fn interface_variables<'u, 'a>(
+    unit: &'u TranslationUnit<'a>,
+    stage: Stage,
+    inputs: bool,
+) -> Vec<InterfaceVariable<'u, 'a>> {
+    let per_vertex = if inputs {
+        stage.has_per_vertex_inputs()
+    } else {
+        stage == Stage::TessControl
+    };
+    unit.variables()
+        .filter(|v| !v.name.starts_with("gl_"))
+        .filter(|v| {
+            v.qualifiers.storage.is_some_and(|s| {
+                if inputs {
+                    is_input(s, stage)
+                } else {
+                    is_output(s, stage)
+                }
+            })
+        })
+        .map(|var| InterfaceVariable {
+            var,
+            ty: if per_vertex && !var.qualifiers.patch {
+                var.ty.element()
+            } else {
+                var.ty.clone()
+            },
+            used: false,
+        })
+        .collect()
+}
+
+/// Checks that every input of `consumer` is written by `producer`, the stage before it.
+fn check_interface(
+    report: &mut Report,
+    (producer, out_unit): (&StageSource<'_>, &TranslationUnit<'_>),
+    (consumer, in_unit): (&StageSource<'_>, &TranslationUnit<'_>),
+) {
+    let mut outputs = interface_variables(out_unit, producer.stage, false);
+    let inputs = interface_variables(in_unit, consumer.stage, true);
+
+    for input in &inputs {
+        let location = input.var.qualifiers.location();
+        let found = match location {
+            Some(location) => outputs
+                .iter_mut()
+                .find(|o| o.var.qualifiers.location() == Some(location)),
+            None => outputs.iter_mut().find(|o| o.var.name == input.var.name),
+        };
+        let Some(output) = found else {
+            let how = match location {
+                Some(location) => format!("at location {location}"),
+                None => format!("called `{}`", input.var.name),
+            };
+            report.error(
+                consumer,
+                input.var.line,
+                format!(
+                    "{} isn't written by the {}; it has no output {how}",
+                    describe(input.var),
+                    producer.stage
+                ),
+            );
+            continue;
+        };
+        output.used = true;
+
+        let (out_file, out_line) = producer.locate(output.var.line);
+        let mismatch = |what: &str| {
+            format!(
+                "{} doesn't match {} in the {} ({out_file}:{out_line}){what}",
+                describe(input.var),
+                describe(output.var),
+                producer.stage
+            )
+        };
+        if input.ty != output.ty {
+            report.error(consumer, input.var.line, mismatch(""));
+        } else if let (Some(a), Some(b)) = (location, output.var.qualifiers.location()) {
+            if a != b {
+                report.error(consumer, input.var.line, mismatch(": the locations differ"));
+            }
+        } else if location.is_some() != output.var.qualifiers.location().is_some() {
+            report.warning(
+                consumer,
+                input.var.line,
+                mismatch(": only one of them has a location, so they're matched by name"),
+            );
+        }
+
+        let interpolation =
+            |v: &Variable<'_>| v.qualifiers.interpolation.unwrap_or(Interpolation::Smooth);
+        if interpolation(input.var) != interpolation(output.var) {
+            report.error(
+                consumer,
+                input.var.line,
+                mismatch(": the interpolation qualifiers differ"),
+            );
+        }
+    }
+
+    for output in outputs.iter().filter(|o| !o.used) {
+        report.warning(
+            producer,
+            output.var.line,
+            format!(
+                "{} is never read by the {}",
+                describe(output.var),
+                consumer.stage
+            ),
+        );
+    }
+}
+
+/// Works out the kind of attribute a scalar, vector or matrix type reads.
+fn scalar_kind(type_name: &str) -> Option<AttributeKind> {
+    let kind = match type_name {
+        "float" => AttributeKind::Float,
+        "int" | "uint" => AttributeKind::Integer,
+        "double" => AttributeKind::Double,
+        _ if type_name.starts_with("vec") || type_name.starts_with("mat") => AttributeKind::Float,
+        _ if type_name.starts_with("ivec") || type_name.starts_with("uvec") => {
+            AttributeKind::Integer
+        }
+        _ if type_name.starts_with("dvec") || type_name.starts_with("dmat") => {
+            AttributeKind::Double
+        }
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Works out the kind, components per location, and number of locations a vertex shader input of
+/// type `ty` reads, or `None` if it can't be a vertex shader input (or has an array size that
+/// couldn't be evaluated).
+fn attribute_shape(ty: &TypeSpecifier<'_>) -> Option<(AttributeKind, u8, u32)> {
+    let kind = scalar_kind(ty.name)?;
+    let digits = |s: &str| -> Option<u8> {
+        let n = s.parse().ok()?;
+        (2..=4).contains(&n).then_some(n)
+    };
+    let name = ty.name.trim_start_matches(['i', 'u', 'd']);
+    let (components, mut locations) = if let Some(n) = name.strip_prefix("vec") {
+        (digits(n)?, 1)
+    } else if let Some(dims) = name.strip_prefix("mat") {
+        // `matN`, or `matCxR` with C columns of R rows
+        match dims.split_once('x') {
+            Some((columns, rows)) => (digits(rows)?, digits(columns)? as u32),
+            None => (digits(dims)?, digits(dims)? as u32),
+        }
+    } else {
+        (1, 1)
+    };
+    for dim in &ty.array {
+        match dim {
+            ArraySize::Sized(ConstValue::Int(n)) => locations *= u32::try_from(*n).ok()?,
+            _ => return None,
+        }
+    }
+    Some((kind, components, locations))
+}
+
+/// Checks the vertex shader's inputs against the vertex layout the program is drawn with.
+fn check_vertex_inputs(
+    report: &mut Report,
+    source: &StageSource<'_>,
+    unit: &TranslationUnit<'_>,
+    layout: &[VertexInput],
+) {
+    let mut used = vec![false; layout.len()];
+    for input in interface_variables(unit, Stage::Vertex, true) {
+        let var = input.var;
+        let Some(first_location) = var.qualifiers.location() else {
+            report.error(
+                source,
+                var.line,
+                format!(
+                    "{} has no layout(location), so the driver decides which attribute it reads",
+                    describe(var)
+                ),
+            );
+            continue;
+        };
+        let Some((kind, components, locations)) = attribute_shape(&var.ty) else {
+            report.warning(
+                source,
+                var.line,
+                format!("can't check {} against the vertex layout", describe(var)),
+            );
+            continue;
+        };
+
+        for location in first_location..first_location + i64::from(locations) {
+            let Some(i) = layout
+                .iter()
+                .position(|a| i64::from(a.location) == location)
+            else {
+                report.error(
+                    source,
+                    var.line,
+                    format!(
+                        "{} reads location {location}, but the vertex layout has no attribute there",
+                        describe(var)
+                    ),
+                );
+                continue;
+            };
+            used[i] = true;
+            let attribute = &layout[i];
+
+            if attribute.kind != kind {
+                report.error(
+                    source,
+                    var.line,
+                    format!(
+                        "{} reads location {location} as {}, but the vertex layout sets it up as {}",
+                        describe(var),
+                        kind_name(kind),
+                        kind_name(attribute.kind)
+                    ),
+                );
+            } else if attribute.components != components {
+                report.warning(
+                    source,
+                    var.line,
+                    format!(
+                        "{} reads {components} components from location {location}, but the \
+                         vertex layout provides {}",
+                        describe(var),
+                        attribute.components
+                    ),
+                );
+            }
+        }
+    }
+
+    for (attribute, used) in layout.iter().zip(used) {
+        if !used {
+            report.warning(
+                source,
+                0,
+                format!(
+                    "the vertex layout's attribute at location {} isn't read by the vertex shader",
+                    attribute.location
+                ),
+            );
+        }
+    }
+}
+
+fn kind_name(kind: AttributeKind) -> &'static str {
+    match kind {
+        AttributeKind::Float => "floats",
+        AttributeKind::Integer => "integers",
+        AttributeKind::Double => "doubles",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preprocess::{EmbeddedFiles, GlslVersion, Preprocessor};
+
+    fn messages(diagnostics: &[Diagnostic]) -> Vec<String> {
+        diagnostics.iter().map(|d| d.to_string()).collect()
+    }
+
+    fn check(stages: &[(Stage, &str)]) -> Vec<String> {
+        let names = ["a", "b", "c", "d", "e"];
+        let mut validator = Validator::new();
+        for (&(stage, source), name) in stages.iter().zip(names) {
+            validator = validator.stage(stage, name, source);
+        }
+        messages(&validator.run())
+    }
+
+    const VS: &str = "#version 330 core
+layout(location = 0) in vec3 pos;
+out vec3 color;
+void main() {}";
+    const FS: &str = "#version 330 core
+in vec3 color;
+out vec4 frag;
+void main() {}";
+
+    mod versions {
+        use super::*;
+
+        #[test]
+        fn matching_stages_are_fine() {
+            assert_eq!(
+                check(&[(Stage::Vertex, VS), (Stage::Fragment, FS)]),
+                [""; 0]
+            );
+        }
+
+        #[test]
+        fn missing_and_unknown_versions() {
+            assert_eq!(
+                check(&[(Stage::Vertex, "void main() {}")]),
+                ["a: warning: no #version directive, so this will be compiled as GLSL 1.10"]
+            );
+            assert_eq!(
+                check(&[(Stage::Vertex, "#version 350\nvoid main() {}")]),
+                ["a:1: error: GLSL 3.50 doesn't exist"]
+            );
+        }
+
+        #[test]
+        fn mixed_versions() {
+            let fs_460 = FS.replace("330 core", "460 core");
+            assert_eq!(
+                check(&[(Stage::Vertex, VS), (Stage::Fragment, &fs_460)]),
+                [
+                    "b:1: warning: `#version 460 core` differs from `#version 330 core` in a; \
+                  stages should be written for the same version"
+                ]
+            );
+            let fs_es = FS.replace("330 core", "300 es");
+            assert_eq!(
+                check(&[(Stage::Vertex, VS), (Stage::Fragment, &fs_es)]),
+                ["b:1: error: GLSL ES 3.00 can't be linked with GLSL 3.30 (a)"]
+            );
+        }
+
+        #[test]
+        fn stage_availability() {
+            assert_eq!(
+                check(&[(Stage::Geometry, "#version 330\nvoid main() {}")]),
+                [""; 0]
+            );
+            assert_eq!(
+                check(&[(Stage::Compute, "#version 420\nvoid main() {}")]),
+                ["a:1: error: compute shaders aren't available in GLSL 4.20"]
+            );
+            assert_eq!(
+                check(&[(Stage::Compute, "#version 310 es\nvoid main() {}")]),
+                [""; 0]
+            );
+        }
+
+        #[test]
+        fn literal_suffixes() {
+            assert_eq!(
+                check(&[(
+                    Stage::Vertex,
+                    "#version 110\nfloat a = 1.0f;\nint b = 2u;\ndouble c = 3.0lf;"
+                )]),
+                [
+                    "a:2: error: `1.0f`: the `f` suffix needs GLSL 1.20",
+                    "a:3: error: `2u`: unsigned integer literals need GLSL 1.30",
+                    "a:4: error: `3.0lf`: double literals need GLSL 4.00",
+                ]
+            );
+            assert_eq!(
+                check(&[(Stage::Vertex, "#version 300 es\ndouble c = 3.0lf;")]),
+                ["a:2: error: `3.0lf`: double literals need desktop GLSL"]
+            );
+        }
+
+        #[test]
+        fn storage_qualifiers() {
+            assert_eq!(
+                check(&[(Stage::Vertex, "#version 120\nin vec3 pos;\nvarying vec3 c;")]),
+                ["a:2: error: `in` variables need GLSL 1.30; use `attribute` instead"]
+            );
+            assert_eq!(
+                check(&[(Stage::Vertex, "#version 330\nattribute vec3 pos;")]),
+                ["a:2: warning: `attribute` is deprecated in GLSL 3.30; use `in` instead"]
+            );
+            assert_eq!(
+                check(&[(Stage::Fragment, "#version 300 es\nvarying vec3 c;")]),
+                ["a:2: error: `varying` is deprecated in GLSL ES 3.00; use `in` instead"]
+            );
+        }
+
+        #[test]
+        fn explicit_locations() {
+            assert_eq!(
+                check(&[(
+                    Stage::Vertex,
+                    "#version 150\nlayout(location = 0) in vec3 pos;"
+                )]),
+                [
+                    "a:2: error: layout(location) on `in vec3 pos` needs GLSL 3.30 or \
+                     GL_ARB_explicit_attrib_location"
+                ]
+            );
+            assert_eq!(
+                check(&[(
+                    Stage::Vertex,
+                    "#version 150\n#extension GL_ARB_explicit_attrib_location : require\n\
+                     layout(location = 0) in vec3 pos;"
+                )]),
+                [""; 0]
+            );
+            assert_eq!(
+                check(&[(
+                    Stage::Vertex,
+                    "#version 330\nlayout(location = 0) out vec3 c;"
+                )]),
+                [
+                    "a:2: error: layout(location) on `out vec3 c` needs GLSL 4.10 or \
+                     GL_ARB_separate_shader_objects"
+                ]
+            );
+        }
+
+        #[test]
+        fn fragment_outputs() {
+            assert_eq!(
+                check(&[(Stage::Fragment, "#version 330\nout vec4 a;\nout vec4 b;")]),
+                [
+                    "a:3: error: fragment outputs `a`, `b` need layout(location) to say which \
+                     draw buffer they write to"
+                ]
+            );
+            assert_eq!(
+                check(&[(
+                    Stage::Fragment,
+                    "#version 330\nlayout(location = 0) out vec4 a;\nlayout(location = 1) out vec4 b;"
+                )]),
+                [""; 0]
+            );
+        }
+
+        #[test]
+        fn parse_errors() {
+            assert_eq!(
+                check(&[(Stage::Vertex, "#version 330\nfloat x")]),
+                ["a:2: error: expected `;`, found the end of the input"]
+            );
+        }
+    }
+
+    mod interfaces {
+        use super::*;
+
+        #[test]
+        fn missing_inputs_and_unused_outputs() {
+            let fs = FS.replace("in vec3 color", "in vec3 colour");
+            assert_eq!(
+                check(&[(Stage::Vertex, VS), (Stage::Fragment, &fs)]),
+                [
+                    "b:2: error: `in vec3 colour` isn't written by the vertex shader; it has no \
+                     output called `colour`",
+                    "a:3: warning: `out vec3 color` is never read by the fragment shader",
+                ]
+            );
+        }
+
+        #[test]
+        fn type_mismatches() {
+            let fs = FS.replace("in vec3 color", "in vec4 color");
+            assert_eq!(
+                check(&[(Stage::Vertex, VS), (Stage::Fragment, &fs)]),
+                ["b:2: error: `in vec4 color` doesn't match `out vec3 color` in the vertex shader \
+                  (a:3)"]
+            );
+        }
+
+        #[test]
+        fn locations() {
+            let vs =
+                "#version 410\nlayout(location = 1) out vec3 a;\nlayout(location = 2) out vec2 b;";
+            let fs = "#version 410\nlayout(location = 2) in vec2 renamed;\nlayout(location = 3) in vec3 a;";
+            assert_eq!(
+                check(&[(Stage::Vertex, vs), (Stage::Fragment, fs)]),
+                [
+                    "b:3: error: `in vec3 a` isn't written by the vertex shader; it has no output \
+                     at location 3",
+                    "a:2: warning: `out vec3 a` is never read by the fragment shader",
+                ]
+            );
+
+            let fs = "#version 410\nin vec3 a;\nlayout(location = 2) in vec2 b;";
+            assert_eq!(
+                check(&[(Stage::Vertex, vs), (Stage::Fragment, fs)]),
+                ["b:2: warning: `in vec3 a` doesn't match `out vec3 a` in the vertex shader (a:2): \
+                  only one of them has a location, so they're matched by name"]
+            );
+        }
+
+        #[test]
+        fn interpolation() {
+            let vs = "#version 330\nflat out int id;\nout vec3 c;";
+            let fs = "#version 330\nflat in int id;\nflat in vec3 c;";
+            assert_eq!(
+                check(&[(Stage::Vertex, vs), (Stage::Fragment, fs)]),
+                ["b:3: error: `in vec3 c` doesn't match `out vec3 c` in the vertex shader (a:3): \
+                  the interpolation qualifiers differ"]
+            );
+
+            let vs = "#version 330\nout int id;";
+            let fs = "#version 330\nin int id;";
+            assert_eq!(
+                check(&[(Stage::Vertex, vs), (Stage::Fragment, fs)]),
+                ["b:2: error: `in int id` has to be `flat`, since it can't be interpolated"]
+            );
+        }
+
+        #[test]
+        fn per_vertex_arrays_and_builtins() {
+            let vs = "#version 400\nout vec3 normal;\nout float gl_ClipDistance[1];";
+            let tcs =
+                "#version 400\nin vec3 normal[];\nout vec3 tc_normal[];\npatch out vec4 data;";
+            let tes = "#version 400\nin vec3 tc_normal[];\npatch in vec4 data;";
+            assert_eq!(
+                check(&[
+                    (Stage::TessEvaluation, tes),
+                    (Stage::Vertex, vs),
+                    (Stage::TessControl, tcs)
+                ]),
+                [""; 0],
+                "stages are sorted into pipeline order"
+            );
+        }
+
+        #[test]
+        fn duplicate_stages() {
+            assert_eq!(
+                check(&[(Stage::Vertex, VS), (Stage::Vertex, VS)]),
+                ["b: error: more than one vertex shader in the program"]
+            );
+        }
+
+        #[test]
+        fn preprocessed_lines_point_at_the_original_files() {
+            let files = EmbeddedFiles(&[
+                ("vertex.vs", "#include \"io.glsl\"\nvoid main() {}"),
+                ("io.glsl", "out vec3 color;"),
+                ("fragment.fs", "\nin vec2 color;\nvoid main() {}"),
+            ]);
+            let pre = Preprocessor::new(GlslVersion::GL_460_CORE, files);
+            let vs = pre.run("vertex.vs").unwrap();
+            let fs = pre.run("fragment.fs").unwrap();
+            let diagnostics = Validator::new()
+                .preprocessed_stage(Stage::Vertex, &vs)
+                .preprocessed_stage(Stage::Fragment, &fs)
+                .run();
+            assert_eq!(
+                messages(&diagnostics),
+                [
+                    "fragment.fs:2: error: `in vec2 color` doesn't match `out vec3 color` in the \
+                  vertex shader (io.glsl:1)"
+                ]
+            );
+        }
+    }
+
+    mod vertex_inputs {
+        use super::*;
+
+        fn check_layout(vs: &str, layout: &[VertexInput]) -> Vec<String> {
+            messages(
+                &Validator::new()
+                    .stage(Stage::Vertex, "vs", vs)
+                    .vertex_inputs(layout)
+                    .run(),
+            )
+        }
+
+        const FLOAT3: VertexInput = VertexInput::new(0, 3, AttributeKind::Float);
+
+        #[test]
+        fn matching_layouts() {
+            assert_eq!(check_layout(VS, &[FLOAT3]), [""; 0]);
+        }
+
+        #[test]
+        fn missing_and_unused_attributes() {
+            assert_eq!(
+                check_layout(VS, &[VertexInput::new(1, 3, AttributeKind::Float)]),
+                [
+                    "vs:2: error: `in vec3 pos` reads location 0, but the vertex layout has no \
+                     attribute there",
+                    "vs: warning: the vertex layout's attribute at location 1 isn't read by the \
+                     vertex shader",
+                ]
+            );
+        }
+
+        #[test]
+        fn kinds_and_components() {
+            assert_eq!(
+                check_layout(VS, &[VertexInput::new(0, 3, AttributeKind::Integer)]),
+                [
+                    "vs:2: error: `in vec3 pos` reads location 0 as floats, but the vertex layout \
+                  sets it up as integers"
+                ]
+            );
+            assert_eq!(
+                check_layout(VS, &[VertexInput::new(0, 2, AttributeKind::Float)]),
+                [
+                    "vs:2: warning: `in vec3 pos` reads 3 components from location 0, but the \
+                  vertex layout provides 2"
+                ]
+            );
+        }
+
+        #[test]
+        fn inputs_need_locations() {
+            assert_eq!(
+                check_layout("#version 330\nin vec3 pos;", &[]),
+                [
+                    "vs:2: error: `in vec3 pos` has no layout(location), so the driver decides \
+                  which attribute it reads"
+                ]
+            );
+        }
+
+        #[test]
+        fn matrices_and_arrays_span_locations() {
+            let layout: Vec<_> = (0..4)
+                .map(|i| VertexInput::new(i, 4, AttributeKind::Float))
+                .chain([VertexInput::new(4, 2, AttributeKind::Integer)])
+                .collect();
+            assert_eq!(
+                check_layout(
+                    "#version 330\nlayout(location = 0) in mat4 model;\nlayout(location = 4) in ivec2 ids[1];",
+                    &layout
+                ),
+                [""; 0]
+            );
+            assert_eq!(
+                check_layout(
+                    "#version 330\nlayout(location = 0) in mat4x3 m;",
+                    &layout[..3]
+                ),
+                [
+                    "vs:2: warning: `in mat4x3 m` reads 3 components from location 0, but the \
+                     vertex layout provides 4",
+                    "vs:2: warning: `in mat4x3 m` reads 3 components from location 1, but the \
+                     vertex layout provides 4",
+                    "vs:2: warning: `in mat4x3 m` reads 3 components from location 2, but the \
+                     vertex layout provides 4",
+                    "vs:2: error: `in mat4x3 m` reads location 3, but the vertex layout has no \
+                     attribute there",
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn stages_from_extensions() {
+        assert_eq!(Stage::from_extension("vs"), Some(Stage::Vertex));
+        assert_eq!(Stage::from_extension("frag"), Some(Stage::Fragment));
+        assert_eq!(Stage::from_extension("tese"), Some(Stage::TessEvaluation));
+        assert_eq!(Stage::from_extension("glsl"), None);
+    }
+}
@@ -0,0 +1,45 @@
+//! GLX constants.
+//!
+//! Unless otherwise specified, all constants are from `GL/glx.h` and `GL/glxext.h`.
+
+use c_types::CInt;
+
+/// The return value of GLX calls that succeeded.
+pub const Success: CInt = 0;
+
+pub const GLX_DOUBLEBUFFER: CInt = 5;
+pub const GLX_RED_SIZE: CInt = 8;
+pub const GLX_GREEN_SIZE: CInt = 9;
+pub const GLX_BLUE_SIZE: CInt = 10;
+pub const GLX_ALPHA_SIZE: CInt = 11;
+pub const GLX_DEPTH_SIZE: CInt = 12;
+pub const GLX_STENCIL_SIZE: CInt = 13;
+pub const GLX_CONFIG_CAVEAT: CInt = 0x20;
+pub const GLX_SLOW_CONFIG: CInt = 0x8001;
+pub const GLX_DRAWABLE_TYPE: CInt = 0x8010;
+pub const GLX_RENDER_TYPE: CInt = 0x8011;
+pub const GLX_X_RENDERABLE: CInt = 0x8012;
+pub const GLX_FBCONFIG_ID: CInt = 0x8013;
+
+pub const GLX_WINDOW_BIT: CInt = 0x0000_0001;
+pub const GLX_RGBA_BIT: CInt = 0x0000_0001;
+
+/// Defined in [`GLX_ARB_multisample`](https://registry.khronos.org/OpenGL/extensions/ARB/ARB_multisample.txt).
+pub const GLX_SAMPLE_BUFFERS_ARB: CInt = 100000;
+/// Defined in [`GLX_ARB_multisample`](https://registry.khronos.org/OpenGL/extensions/ARB/ARB_multisample.txt).
+pub const GLX_SAMPLES_ARB: CInt = 100001;
+
+/// Defined in [`GLX_ARB_framebuffer_sRGB`](https://registry.khronos.org/OpenGL/extensions/ARB/ARB_framebuffer_sRGB.txt).
+pub const GLX_FRAMEBUFFER_SRGB_CAPABLE_ARB: CInt = 0x20B2;
+
+/// The X screen a context was created for, from [`glXQueryContext`].
+///
+/// [`glXQueryContext`]: super::extern_bindings::glXQueryContext
+pub const GLX_SCREEN: CInt = 0x800C;
+
+/// Defined in [`GLX_EXT_swap_control`](https://registry.khronos.org/OpenGL/extensions/EXT/EXT_swap_control.txt).
+pub const GLX_SWAP_INTERVAL_EXT: CInt = 0x20F1;
+/// Defined in [`GLX_EXT_swap_control`](https://registry.khronos.org/OpenGL/extensions/EXT/EXT_swap_control.txt).
+pub const GLX_MAX_SWAP_INTERVAL_EXT: CInt = 0x20F2;
+/// Defined in [`GLX_EXT_swap_control_tear`](https://registry.khronos.org/OpenGL/extensions/EXT/GLX_EXT_swap_control_tear.txt).
+pub const GLX_LATE_SWAPS_TEAR_EXT: CInt = 0x20F3;
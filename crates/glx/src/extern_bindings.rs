@@ -0,0 +1,72 @@
+//! Bindings to functions exported by `libGL`, and the few from `libX11` that are needed to use
+//! them.
+//!
+//! Extension functions aren't exported, and have to be loaded with [`glXGetProcAddressARB`].
+//! Their types are in [`typedefs`](super::typedefs).
+
+use super::typedefs::*;
+use c_types::*;
+
+#[link(name = "GL")]
+extern "C" {
+    /// See [`glXGetFBConfigAttrib` on the OpenGL registry](https://registry.khronos.org/OpenGL-Refpages/gl2.1/xhtml/glXGetFBConfigAttrib.xml).
+    pub fn glXGetFBConfigAttrib(
+        dpy: *mut Display,
+        config: GLXFBConfig,
+        attribute: CInt,
+        value: *mut CInt,
+    ) -> CInt;
+
+    /// See [`glXGetFBConfigs` on the OpenGL registry](https://registry.khronos.org/OpenGL-Refpages/gl2.1/xhtml/glXGetFBConfigs.xml).
+    pub fn glXGetFBConfigs(
+        dpy: *mut Display,
+        screen: CInt,
+        nelements: *mut CInt,
+    ) -> *mut GLXFBConfig;
+
+    /// See [`glXGetCurrentContext` on the OpenGL registry](https://registry.khronos.org/OpenGL-Refpages/gl2.1/xhtml/glXGetCurrentContext.xml).
+    pub fn glXGetCurrentContext() -> GLXContext;
+
+    /// See [`glXGetCurrentDisplay` on the OpenGL registry](https://registry.khronos.org/OpenGL-Refpages/gl2.1/xhtml/glXGetCurrentDisplay.xml).
+    pub fn glXGetCurrentDisplay() -> *mut Display;
+
+    /// See [`glXGetCurrentDrawable` on the OpenGL registry](https://registry.khronos.org/OpenGL-Refpages/gl2.1/xhtml/glXGetCurrentDrawable.xml).
+    pub fn glXGetCurrentDrawable() -> GLXDrawable;
+
+    /// See [`glXGetProcAddress` on the OpenGL registry](https://registry.khronos.org/OpenGL-Refpages/gl2.1/xhtml/glXGetProcAddress.xml).
+    pub fn glXGetProcAddressARB(procName: *const CUChar) -> __GLXextFuncPtr;
+
+    /// See [`glXQueryContext` on the OpenGL registry](https://registry.khronos.org/OpenGL-Refpages/gl2.1/xhtml/glXQueryContext.xml).
+    pub fn glXQueryContext(
+        dpy: *mut Display,
+        ctx: GLXContext,
+        attribute: CInt,
+        value: *mut CInt,
+    ) -> CInt;
+
+    /// See [`glXQueryDrawable` on the OpenGL registry](https://registry.khronos.org/OpenGL-Refpages/gl2.1/xhtml/glXQueryDrawable.xml).
+    pub fn glXQueryDrawable(
+        dpy: *mut Display,
+        draw: GLXDrawable,
+        attribute: CInt,
+        value: *mut CUInt,
+    );
+
+    /// See [`glXQueryExtensionsString` on the OpenGL registry](https://registry.khronos.org/OpenGL-Refpages/gl2.1/xhtml/glXQueryExtensionsString.xml).
+    pub fn glXQueryExtensionsString(dpy: *mut Display, screen: CInt) -> *const CChar;
+}
+
+#[link(name = "X11")]
+extern "C" {
+    /// See [`XCloseDisplay` in the Xlib manual](https://www.x.org/releases/current/doc/libX11/libX11/libX11.html#XCloseDisplay).
+    pub fn XCloseDisplay(display: *mut Display) -> CInt;
+
+    /// See [`XDefaultScreen` in the Xlib manual](https://www.x.org/releases/current/doc/libX11/libX11/libX11.html#Display_Macros).
+    pub fn XDefaultScreen(display: *mut Display) -> CInt;
+
+    /// See [`XFree` in the Xlib manual](https://www.x.org/releases/current/doc/libX11/libX11/libX11.html#XFree).
+    pub fn XFree(data: *mut core::ffi::c_void) -> CInt;
+
+    /// See [`XOpenDisplay` in the Xlib manual](https://www.x.org/releases/current/doc/libX11/libX11/libX11.html#XOpenDisplay).
+    pub fn XOpenDisplay(display_name: *const CChar) -> *mut Display;
+}
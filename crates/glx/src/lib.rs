@@ -0,0 +1,205 @@
+#![cfg(target_os = "linux")]
+
+//! Bindings to the GLX types, constants, and functions that are needed to work with a GLX context
+//! someone else has created and made current, like its swap interval, and to list a screen's
+//! FBConfigs.
+//!
+//! Nothing here creates a context. GLX needs an Xlib `Display` for that, while the `x11` crate
+//! speaks the protocol itself, so it's left to whoever owns the window. [`XlibDisplay`] is only
+//! for asking the server about its configs.
+
+// GLX names are very incompatible with Rust's default lints, so we have to disable some of them.
+#![allow(non_snake_case, non_camel_case_types, non_upper_case_globals)]
+
+use core::{
+    ffi::{c_void, CStr},
+    ptr::{self, NonNull},
+    slice,
+};
+
+use c_types::*;
+
+pub mod constants;
+pub mod extern_bindings;
+pub mod prelude;
+pub mod typedefs;
+
+use prelude::*;
+
+/// The GLX context that's current on this thread, and what it's drawing to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrentContext {
+    pub display: *mut Display,
+    pub drawable: GLXDrawable,
+    pub context: GLXContext,
+}
+
+/// Gets the GLX context that's current on this thread, or `None` if there isn't one.
+///
+/// See [`glXGetCurrentContext`](https://registry.khronos.org/OpenGL-Refpages/gl2.1/xhtml/glXGetCurrentContext.xml)
+pub fn current_context() -> Option<CurrentContext> {
+    // Safety: these only read this thread's GLX state.
+    unsafe {
+        let context = glXGetCurrentContext();
+        if context.is_null() {
+            return None;
+        }
+        Some(CurrentContext {
+            display: glXGetCurrentDisplay(),
+            drawable: glXGetCurrentDrawable(),
+            context,
+        })
+    }
+}
+
+/// An Xlib connection to the X server, which is closed on drop.
+#[derive(Debug)]
+pub struct XlibDisplay(NonNull<Display>);
+
+impl XlibDisplay {
+    /// Connects to the server named by the `DISPLAY` environment variable, or returns `None` if
+    /// that can't be done.
+    ///
+    /// See [`XOpenDisplay`](https://www.x.org/releases/current/doc/libX11/libX11/libX11.html#XOpenDisplay)
+    pub fn open() -> Option<Self> {
+        // Safety: a null name means $DISPLAY
+        NonNull::new(unsafe { XOpenDisplay(ptr::null()) }).map(Self)
+    }
+
+    pub fn as_ptr(&self) -> *mut Display {
+        self.0.as_ptr()
+    }
+
+    /// The screen that's used unless another is asked for.
+    pub fn default_screen(&self) -> CInt {
+        // Safety: the display is open
+        unsafe { XDefaultScreen(self.as_ptr()) }
+    }
+}
+
+impl Drop for XlibDisplay {
+    fn drop(&mut self) {
+        // Safety: the display was opened in `open`, and nothing can use it after this
+        unsafe { XCloseDisplay(self.as_ptr()) };
+    }
+}
+
+/// Returns every FBConfig that `screen` has.
+///
+/// See [`glXGetFBConfigs`](https://registry.khronos.org/OpenGL-Refpages/gl2.1/xhtml/glXGetFBConfigs.xml)
+///
+/// ## Safety
+///
+/// `display` must be an open display.
+pub unsafe fn get_fb_configs(display: *mut Display, screen: CInt) -> Vec<GLXFBConfig> {
+    let mut count = 0;
+    let configs = glXGetFBConfigs(display, screen, &mut count);
+    if configs.is_null() {
+        return Vec::new();
+    }
+    let out = slice::from_raw_parts(configs, count.max(0) as usize).to_vec();
+    XFree(configs.cast());
+    out
+}
+
+/// Gets the value of one of an FBConfig's attributes, or `None` if it doesn't have it (e.g. it's
+/// from an extension the server doesn't support).
+///
+/// See [`glXGetFBConfigAttrib`](https://registry.khronos.org/OpenGL-Refpages/gl2.1/xhtml/glXGetFBConfigAttrib.xml)
+///
+/// ## Safety
+///
+/// `display` must be an open display, and `config` must have come from it.
+pub unsafe fn get_fb_config_attrib(
+    display: *mut Display,
+    config: GLXFBConfig,
+    attribute: CInt,
+) -> Option<CInt> {
+    let mut value = 0;
+    (glXGetFBConfigAttrib(display, config, attribute, &mut value) == Success).then_some(value)
+}
+
+/// Gets the X screen that `context` was created for.
+///
+/// See [`glXQueryContext`](https://registry.khronos.org/OpenGL-Refpages/gl2.1/xhtml/glXQueryContext.xml)
+///
+/// ## Safety
+///
+/// `display` must be an open display, and `context` must have been created on it.
+pub unsafe fn query_context_screen(display: *mut Display, context: GLXContext) -> Option<CInt> {
+    let mut screen = 0;
+    (glXQueryContext(display, context, GLX_SCREEN, &mut screen) == Success).then_some(screen)
+}
+
+/// Gets the GLX extensions supported on a screen, by both the client library and the server.
+///
+/// See [`glXQueryExtensionsString`](https://registry.khronos.org/OpenGL-Refpages/gl2.1/xhtml/glXQueryExtensionsString.xml)
+///
+/// ## Safety
+///
+/// `display` must be an open display.
+pub unsafe fn query_extensions(display: *mut Display, screen: CInt) -> Vec<String> {
+    let p = glXQueryExtensionsString(display, screen);
+    if p.is_null() {
+        return Vec::new();
+    }
+    CStr::from_ptr(p)
+        .to_string_lossy()
+        .split(' ')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Gets the value of one of a drawable's attributes, like [`GLX_SWAP_INTERVAL_EXT`].
+///
+/// See [`glXQueryDrawable`](https://registry.khronos.org/OpenGL-Refpages/gl2.1/xhtml/glXQueryDrawable.xml)
+///
+/// ## Safety
+///
+/// `display` must be an open display, and `drawable` must be a GLX drawable on it.
+pub unsafe fn query_drawable(
+    display: *mut Display,
+    drawable: GLXDrawable,
+    attribute: CInt,
+) -> CUInt {
+    let mut value = 0;
+    glXQueryDrawable(display, drawable, attribute, &mut value);
+    value
+}
+
+/// Gets the address of a GL or GLX function.
+///
+/// - `func_name` must be a null-terminated ASCII string.
+///
+/// `libGL` hands out an address for any name that starts with `gl`, whether or not the driver
+/// implements it, so check the extension string before calling what comes back.
+///
+/// See [`glXGetProcAddress`](https://registry.khronos.org/OpenGL-Refpages/gl2.1/xhtml/glXGetProcAddress.xml)
+pub fn get_proc_address(func_name: &[u8]) -> Option<*mut c_void> {
+    // check that we end the slice with a \0 as expected
+    if func_name.last() != Some(&b'\0') {
+        return None;
+    }
+
+    // Safety: we've already checked that the end of the slice is null-terminated
+    let proc = unsafe { glXGetProcAddressARB(func_name.as_ptr()) };
+
+    (!proc.is_null()).then_some(proc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nothing_is_current() {
+        assert_eq!(current_context(), None);
+    }
+
+    #[test]
+    fn proc_addresses() {
+        assert_eq!(get_proc_address(b"glXGetCurrentContext"), None);
+        assert!(get_proc_address(b"glXGetCurrentContext\0").is_some());
+    }
+}
@@ -0,0 +1,3 @@
+pub use super::constants::*;
+pub use super::extern_bindings::*;
+pub use super::typedefs::*;
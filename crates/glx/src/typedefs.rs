@@ -0,0 +1,38 @@
+//! Basic GLX type definitions.
+//!
+//! Unless otherwise specified, all type definitions are from `GL/glx.h` and `X11/Xlib.h`, as
+//! shipped by Mesa and libX11.
+
+use core::ffi::c_void;
+
+use c_types::*;
+
+/// An Xlib display connection, which is only ever handled through a pointer.
+#[repr(C)]
+pub struct Display {
+    _private: [u8; 0],
+}
+
+/// An X11 resource ID.
+pub type XID = CULong;
+
+pub type GLXDrawable = XID;
+
+pub type GLXContext = *mut c_void;
+
+pub type GLXFBConfig = *mut c_void;
+
+/// A pointer to a procedure of unknown type, as returned by [`glXGetProcAddressARB`].
+///
+/// [`glXGetProcAddressARB`]: super::extern_bindings::glXGetProcAddressARB
+pub type __GLXextFuncPtr = *mut c_void;
+
+/// Type for [`glXSwapIntervalEXT`](https://registry.khronos.org/OpenGL/extensions/EXT/EXT_swap_control.txt).
+pub type glXSwapIntervalEXT_t =
+    Option<unsafe extern "C" fn(dpy: *mut Display, drawable: GLXDrawable, interval: CInt)>;
+
+/// Type for [`glXSwapIntervalMESA`](https://registry.khronos.org/OpenGL/extensions/MESA/GLX_MESA_swap_control.txt).
+pub type glXSwapIntervalMESA_t = Option<unsafe extern "C" fn(interval: CUInt) -> CInt>;
+
+/// Type for [`glXGetSwapIntervalMESA`](https://registry.khronos.org/OpenGL/extensions/MESA/GLX_MESA_swap_control.txt).
+pub type glXGetSwapIntervalMESA_t = Option<unsafe extern "C" fn() -> CInt>;
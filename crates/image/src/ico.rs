@@ -0,0 +1,209 @@
+//! Reading Windows [`.ico` and `.cur`](https://en.wikipedia.org/wiki/ICO_(file_format)) files.
+//!
+//! A file is a directory of images of different sizes (and, in old files, colour depths). Each
+//! image is either a PNG, or a device-independent bitmap without its file header, followed by a
+//! 1-bit mask that says which pixels are transparent.
+
+use std::fmt;
+
+use crate::{png, RgbaImage};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Error {
+    /// The header isn't one of an icon or a cursor.
+    NotIco,
+    /// The file ends before an image (or the directory) does.
+    Truncated,
+    /// A bitmap's header is inconsistent, e.g. its height isn't that of the image and the mask.
+    BadBitmap,
+    /// A bitmap's pixels are in a format that icons don't use.
+    UnsupportedBitmap {
+        bit_count: u16,
+        compression: u32,
+    },
+    Png(png::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotIco => write!(f, "not an icon or cursor file"),
+            Self::Truncated => write!(f, "truncated icon file"),
+            Self::BadBitmap => write!(f, "bad bitmap in icon file"),
+            Self::UnsupportedBitmap {
+                bit_count,
+                compression,
+            } => write!(
+                f,
+                "unsupported {bit_count}-bit bitmap (compression {compression}) in icon file"
+            ),
+            Self::Png(e) => write!(f, "bad PNG in icon file: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Png(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<png::Error> for Error {
+    fn from(e: png::Error) -> Self {
+        Self::Png(e)
+    }
+}
+
+/// One of the images in a file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Entry {
+    pub image: RgbaImage,
+    /// Where the pointer is in the image, for cursors.
+    pub hotspot: Option<[u16; 2]>,
+}
+
+/// What's in the second word of the header.
+mod kinds {
+    pub const ICON: u16 = 1;
+    pub const CURSOR: u16 = 2;
+}
+
+/// Reads every image in an icon or cursor file, in the order they're listed.
+///
+/// The sizes in the directory are ignored in favour of the images' own, since the directory can
+/// only say up to 256 pixels and isn't always right.
+pub fn parse(bytes: &[u8]) -> Result<Vec<Entry>, Error> {
+    let u16_at = |i: usize| -> Result<u16, Error> {
+        let bytes = bytes.get(i..i + 2).ok_or(Error::Truncated)?;
+        Ok(u16::from_le_bytes(bytes.try_into().unwrap()))
+    };
+    let u32_at = |i: usize| -> Result<u32, Error> {
+        let bytes = bytes.get(i..i + 4).ok_or(Error::Truncated)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    };
+
+    let kind = u16_at(2)?;
+    if u16_at(0)? != 0 || !matches!(kind, kinds::ICON | kinds::CURSOR) {
+        return Err(Error::NotIco);
+    }
+    (0..u16_at(4)? as usize)
+        .map(|i| {
+            let entry = 6 + i * 16;
+            let size = u32_at(entry + 8)? as usize;
+            let offset = u32_at(entry + 12)? as usize;
+            let data = offset
+                .checked_add(size)
+                .and_then(|end| bytes.get(offset..end))
+                .ok_or(Error::Truncated)?;
+            let image = if png::is_png(data) {
+                png::decode(data)?
+            } else {
+                decode_bitmap(data)?
+            };
+            // where icons have their colour planes and bit count
+            let hotspot =
+                (kind == kinds::CURSOR).then_some([u16_at(entry + 4)?, u16_at(entry + 6)?]);
+            Ok(Entry { image, hotspot })
+        })
+        .collect()
+}
+
+/// Decodes a bitmap from an icon, with its mask.
+fn decode_bitmap(data: &[u8]) -> Result<RgbaImage, Error> {
+    let u16_at = |i: usize| u16::from_le_bytes(data[i..i + 2].try_into().unwrap());
+    let u32_at = |i: usize| u32::from_le_bytes(data[i..i + 4].try_into().unwrap());
+    if data.len() < 40 {
+        return Err(Error::Truncated);
+    }
+    // a `BITMAPINFOHEADER`, or one of the longer headers that start the same way
+    let header_size = u32_at(0) as usize;
+    let width = u32_at(4) as i32;
+    let double_height = u32_at(8) as i32;
+    let bit_count = u16_at(14);
+    let compression = u32_at(16);
+    let colors_used = u32_at(32) as usize;
+    if header_size < 40
+        || width <= 0
+        || double_height <= 0
+        || double_height % 2 != 0
+        || u16_at(12) != 1
+    {
+        return Err(Error::BadBitmap);
+    }
+    // `BI_RGB`, the only compression icons have
+    if !matches!(bit_count, 1 | 4 | 8 | 24 | 32) || compression != 0 {
+        return Err(Error::UnsupportedBitmap {
+            bit_count,
+            compression,
+        });
+    }
+    let (width, height) = (width as usize, double_height as usize / 2);
+    if width as u64 * height as u64 > png::MAX_PIXELS {
+        return Err(Error::BadBitmap);
+    }
+
+    let palette_len = match bit_count {
+        1 | 4 | 8 if colors_used == 0 => 1 << bit_count,
+        1 | 4 | 8 => colors_used.min(1 << bit_count),
+        _ => 0,
+    };
+    let palette = data
+        .get(header_size..header_size + palette_len * 4)
+        .ok_or(Error::Truncated)?;
+    let colors_start = header_size + palette.len();
+
+    // rows are padded to 4 bytes, and go from the bottom up
+    let stride = (width * usize::from(bit_count)).div_ceil(32) * 4;
+    let mask_stride = width.div_ceil(32) * 4;
+    let colors = data
+        .get(colors_start..colors_start + stride * height)
+        .ok_or(Error::Truncated)?;
+    let mask_start = colors_start + colors.len();
+    // 32-bit images have alpha, so some files leave the mask out
+    let mask = match data.get(mask_start..mask_start + mask_stride * height) {
+        Some(mask) => Some(mask),
+        None if bit_count == 32 => None,
+        None => return Err(Error::Truncated),
+    };
+
+    let mut pixels = Vec::with_capacity(width * height * 4);
+    for y in (0..height).rev() {
+        let row = &colors[y * stride..][..stride];
+        for x in 0..width {
+            let [b, g, r, a] = match bit_count {
+                32 => row[x * 4..x * 4 + 4].try_into().unwrap(),
+                24 => [row[x * 3], row[x * 3 + 1], row[x * 3 + 2], 255],
+                _ => {
+                    let bit = x * usize::from(bit_count);
+                    let index = (row[bit / 8] >> (8 - usize::from(bit_count) - bit % 8))
+                        & ((1 << bit_count) - 1);
+                    let color = palette
+                        .get(usize::from(index) * 4..usize::from(index) * 4 + 3)
+                        .ok_or(Error::BadBitmap)?;
+                    [color[0], color[1], color[2], 255]
+                }
+            };
+            pixels.extend([r, g, b, a]);
+        }
+    }
+
+    // the mask decides what's transparent, unless there's an alpha channel with anything in it
+    let has_alpha = bit_count == 32 && pixels.chunks(4).any(|p| p[3] != 0);
+    match (mask, has_alpha) {
+        (_, true) => {}
+        (Some(mask), false) => {
+            for (y, row) in mask.chunks(mask_stride).rev().enumerate() {
+                for x in 0..width {
+                    let transparent = row[x / 8] & (0x80 >> (x % 8)) != 0;
+                    pixels[(y * width + x) * 4 + 3] = if transparent { 0 } else { 255 };
+                }
+            }
+        }
+        // neither, so it's all opaque
+        (None, false) => pixels.chunks_mut(4).for_each(|p| p[3] = 255),
+    }
+    Ok(RgbaImage::new(width as u32, height as u32, pixels))
+}
@@ -0,0 +1,397 @@
+//! Decompressing [DEFLATE](https://www.rfc-editor.org/rfc/rfc1951) data, and the
+//! [zlib](https://www.rfc-editor.org/rfc/rfc1950) format that wraps it in PNGs.
+//!
+//! This favours being short over being fast: codes are decoded a bit at a time, the way the RFC
+//! describes them, which is plenty for icons.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Error {
+    /// The data ended in the middle of something.
+    Truncated,
+    /// The zlib header is wrong, or asks for a preset dictionary.
+    BadHeader,
+    /// A block has the reserved type 3.
+    BadBlockType,
+    /// A stored block's length doesn't match its complement.
+    BadStoredLength,
+    /// A set of Huffman code lengths doesn't make a code, or a code isn't in it.
+    BadCode,
+    /// A back reference points before the start of the data.
+    BadDistance,
+    /// The zlib checksum doesn't match the decompressed data.
+    BadChecksum,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Self::Truncated => "compressed data is truncated",
+            Self::BadHeader => "bad zlib header",
+            Self::BadBlockType => "bad DEFLATE block type",
+            Self::BadStoredLength => "bad DEFLATE stored block length",
+            Self::BadCode => "bad Huffman code in DEFLATE data",
+            Self::BadDistance => "DEFLATE back reference is too far back",
+            Self::BadChecksum => "zlib checksum doesn't match",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Decompresses zlib data, checking its checksum.
+pub fn zlib_decompress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let [cmf, flg, ..] = *data else {
+        return Err(Error::Truncated);
+    };
+    // method 8 (DEFLATE) with a window of at most 32K, a header that's a multiple of 31, and no
+    // preset dictionary
+    if cmf & 0x0F != 8
+        || cmf >> 4 > 7
+        || u16::from_be_bytes([cmf, flg]) % 31 != 0
+        || flg & 0x20 != 0
+    {
+        return Err(Error::BadHeader);
+    }
+    let mut bits = Bits::new(&data[2..]);
+    let out = inflate_bits(&mut bits)?;
+    let checksum = bits.rest_after_byte_boundary();
+    let checksum: [u8; 4] = checksum
+        .get(..4)
+        .ok_or(Error::Truncated)?
+        .try_into()
+        .unwrap();
+    if u32::from_be_bytes(checksum) != adler32(&out) {
+        return Err(Error::BadChecksum);
+    }
+    Ok(out)
+}
+
+/// Decompresses raw DEFLATE data.
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, Error> {
+    inflate_bits(&mut Bits::new(data))
+}
+
+/// The zlib checksum.
+pub fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1_u32, 0_u32);
+    // 5552 bytes is as many as can be summed before `b` could overflow
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    b << 16 | a
+}
+
+/// Reads bits from the least significant end of each byte first, as DEFLATE packs them.
+struct Bits<'a> {
+    data: &'a [u8],
+    /// The next byte to read from.
+    pos: usize,
+    /// Bits that have been read from `data` but not used, from the bottom.
+    buffer: u32,
+    count: u32,
+}
+
+impl<'a> Bits<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    fn bits(&mut self, n: u32) -> Result<u32, Error> {
+        while self.count < n {
+            let &byte = self.data.get(self.pos).ok_or(Error::Truncated)?;
+            self.pos += 1;
+            self.buffer |= u32::from(byte) << self.count;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1 << n) - 1);
+        self.buffer >>= n;
+        self.count -= n;
+        Ok(value)
+    }
+
+    /// Throws away the rest of the byte that's being read, and gives back the bytes after it.
+    fn rest_after_byte_boundary(&mut self) -> &'a [u8] {
+        // whole bytes in the buffer were read too early
+        self.pos -= (self.count / 8) as usize;
+        self.buffer = 0;
+        self.count = 0;
+        &self.data[self.pos..]
+    }
+}
+
+/// A canonical Huffman code, as the number of codes of each length and the symbols in code order.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Builds the code from each symbol's code length, where 0 means it isn't used.
+    fn new(lengths: &[u8]) -> Result<Self, Error> {
+        let mut counts = [0_u16; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        // too many codes of some length can't be decoded. Too few is allowed (e.g. a single
+        // distance code), and the missing codes are just never seen in good data.
+        let mut left = 1_i32;
+        for &count in &counts[1..] {
+            left = left * 2 - i32::from(count);
+            if left < 0 {
+                return Err(Error::BadCode);
+            }
+        }
+
+        let mut offsets = [0_u16; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u16, Error> {
+        // the first code of each length, and where its symbols start
+        let (mut code, mut first, mut index) = (0_i32, 0_i32, 0_i32);
+        for &count in &self.counts[1..] {
+            code |= bits.bits(1)? as i32;
+            let count = i32::from(count);
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(Error::BadCode)
+    }
+}
+
+/// The lengths that length symbols 257 to 285 start at, and how many extra bits they have.
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// Likewise for distance symbols 0 to 29.
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// The order the code length code's lengths come in, in a dynamic block.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+fn inflate_bits(bits: &mut Bits) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::new();
+    loop {
+        let last = bits.bits(1)? == 1;
+        match bits.bits(2)? {
+            0 => stored(bits, &mut out)?,
+            1 => {
+                let (literals, distances) = fixed_codes();
+                compressed(bits, &mut out, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(bits)?;
+                compressed(bits, &mut out, &literals, &distances)?;
+            }
+            _ => return Err(Error::BadBlockType),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+fn stored(bits: &mut Bits, out: &mut Vec<u8>) -> Result<(), Error> {
+    let rest = bits.rest_after_byte_boundary();
+    let [l0, l1, n0, n1, ..] = *rest else {
+        return Err(Error::Truncated);
+    };
+    let len = u16::from_le_bytes([l0, l1]);
+    if len != !u16::from_le_bytes([n0, n1]) {
+        return Err(Error::BadStoredLength);
+    }
+    let data = rest.get(4..4 + len as usize).ok_or(Error::Truncated)?;
+    out.extend_from_slice(data);
+    bits.pos += 4 + len as usize;
+    Ok(())
+}
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [0_u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    // both of these are complete codes
+    (
+        Huffman::new(&lengths).unwrap(),
+        Huffman::new(&[5; 30]).unwrap(),
+    )
+}
+
+fn dynamic_codes(bits: &mut Bits) -> Result<(Huffman, Huffman), Error> {
+    let literal_count = bits.bits(5)? as usize + 257;
+    let distance_count = bits.bits(5)? as usize + 1;
+    let code_length_count = bits.bits(4)? as usize + 4;
+
+    let mut code_length_lengths = [0_u8; 19];
+    for &i in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_length_lengths[i] = bits.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_length_lengths)?;
+
+    // the literal and distance lengths are one run, so repeats can cross from one to the other
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (length, repeat) = match code_lengths.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let &previous = lengths.last().ok_or(Error::BadCode)?;
+                (previous, 3 + bits.bits(2)?)
+            }
+            17 => (0, 3 + bits.bits(3)?),
+            _ => (0, 11 + bits.bits(7)?),
+        };
+        if lengths.len() + repeat as usize > literal_count + distance_count {
+            return Err(Error::BadCode);
+        }
+        lengths.extend((0..repeat).map(|_| length));
+    }
+    // a block has to be able to end
+    if lengths[256] == 0 {
+        return Err(Error::BadCode);
+    }
+    let (literals, distances) = lengths.split_at(literal_count);
+    Ok((Huffman::new(literals)?, Huffman::new(distances)?))
+}
+
+fn compressed(
+    bits: &mut Bits,
+    out: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), Error> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let i = symbol - 257;
+                let (&base, &extra) = LENGTH_BASES
+                    .get(i)
+                    .zip(LENGTH_EXTRA.get(i))
+                    .ok_or(Error::BadCode)?;
+                let length = usize::from(base) + bits.bits(u32::from(extra))? as usize;
+
+                let i = distances.decode(bits)? as usize;
+                let (&base, &extra) = DISTANCE_BASES
+                    .get(i)
+                    .zip(DISTANCE_EXTRA.get(i))
+                    .ok_or(Error::BadCode)?;
+                let distance = usize::from(base) + bits.bits(u32::from(extra))? as usize;
+                if distance > out.len() {
+                    return Err(Error::BadDistance);
+                }
+                // byte by byte, since the copy can overlap what it's making
+                let start = out.len() - distance;
+                for i in 0..length {
+                    out.push(out[start + i]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_blocks() {
+        // two stored blocks, the second of them last
+        let data = [0, 3, 0, !3, !0, b'a', b'b', b'c', 1, 1, 0, !1, !0, b'd'];
+        assert_eq!(inflate(&data), Ok(b"abcd".to_vec()));
+
+        assert_eq!(inflate(&data[..6]), Err(Error::Truncated));
+        assert_eq!(inflate(&[1, 3, 0, 3, 0]), Err(Error::BadStoredLength));
+    }
+
+    #[test]
+    fn fixed_codes() {
+        // `zlib.compress(b"hello hello hello", level=9)`, which repeats with a back reference
+        let data = [
+            0x78, 0xDA, 0xCB, 0x48, 0xCD, 0xC9, 0xC9, 0x57, 0xC8, 0x40, 0x90, 0x00, 0x3A, 0x2E,
+            0x06, 0x7D,
+        ];
+        assert_eq!(zlib_decompress(&data), Ok(b"hello hello hello".to_vec()));
+
+        let mut wrong = data;
+        wrong[15] ^= 1;
+        assert_eq!(zlib_decompress(&wrong), Err(Error::BadChecksum));
+        assert_eq!(zlib_decompress(&data[..14]), Err(Error::Truncated));
+        assert_eq!(zlib_decompress(&[0x78, 0x9D]), Err(Error::BadHeader));
+    }
+
+    #[test]
+    fn dynamic_codes() {
+        // `zlib.compress(bytes(sorted(b"the quick brown fox jumps over the lazy dog" * 5)))`
+        let data = [
+            0x78, 0xDA, 0x8D, 0xC1, 0x49, 0x0E, 0x80, 0x20, 0x10, 0x00, 0xC1, 0xAF, 0xCC, 0xD7,
+            0x5C, 0x11, 0x37, 0x40, 0x01, 0x85, 0xD7, 0x9B, 0xBE, 0x4C, 0x62, 0xE2, 0xC1, 0x2A,
+            0x91, 0x7F, 0x1A, 0xB4, 0xE8, 0xD0, 0x63, 0x78, 0x1B, 0x61, 0x30, 0x29, 0x8B, 0x19,
+            0x0B, 0x56, 0x6C, 0xD8, 0xE1, 0x3E, 0x78, 0x04, 0x1C, 0xEA, 0x44, 0x54, 0x49, 0x65,
+            0x5C, 0xB8, 0x51, 0x50, 0xF1, 0x00, 0x9B, 0xB6, 0x4F, 0xDE,
+        ];
+        let mut expected = b"the quick brown fox jumps over the lazy dog".repeat(5);
+        expected.sort();
+        assert_eq!(zlib_decompress(&data), Ok(expected));
+    }
+
+    #[test]
+    fn bad_data() {
+        assert_eq!(inflate(&[0b111]), Err(Error::BadBlockType));
+        // a fixed block that refers back before the start
+        assert_eq!(inflate(&[0x03, 0x02]), Err(Error::BadDistance));
+        assert_eq!(inflate(&[]), Err(Error::Truncated));
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(adler32(&[0xFF; 100_000]), 0x149A_302C);
+    }
+}
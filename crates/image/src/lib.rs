@@ -0,0 +1,260 @@
+//! Decoding and resizing the images that windows and cursors are made of, in plain Rust so it's
+//! available (and tested) everywhere.
+//!
+//! Everything decodes to an [`RgbaImage`]: 8 bits each of red, green, blue and straight (not
+//! premultiplied) alpha, row by row from the top. The platform crates turn that into whatever
+//! their APIs want.
+//!
+//! - [`ico`] reads Windows `.ico` and `.cur` files, whose images are bitmaps or PNGs.
+//! - [`png`] decodes PNGs, using [`inflate`] to decompress them.
+
+pub mod ico;
+pub mod inflate;
+pub mod png;
+
+/// An image with straight RGBA pixels.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    /// `width * height * 4` bytes, row by row from the top.
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Wraps pixels that are already laid out right.
+    ///
+    /// ## Panics
+    ///
+    /// If there aren't `width * height * 4` bytes of pixels.
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        assert_eq!(
+            pixels.len() as u64,
+            u64::from(width) * u64::from(height) * 4,
+            "{width}x{height} RGBA image",
+        );
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// The pixel at `(x, y)`.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        self.pixels[i..i + 4].try_into().unwrap()
+    }
+
+    /// Resamples the image to another size.
+    ///
+    /// Each new pixel is the average of the old pixels it covers, weighted by how much of each it
+    /// covers and by their alpha, so shrinking doesn't alias and transparent pixels don't darken
+    /// the edges of what's left. Growing just makes bigger pixels, blended where they meet.
+    ///
+    /// ## Panics
+    ///
+    /// If either the image or the new size is empty.
+    pub fn resized(&self, width: u32, height: u32) -> Self {
+        assert!(self.width > 0 && self.height > 0, "resizing an empty image");
+        assert!(
+            width > 0 && height > 0,
+            "resizing an image to {width}x{height}"
+        );
+        if (width, height) == (self.width, self.height) {
+            return self.clone();
+        }
+
+        // premultiplied, so that averaging weights the colours by alpha
+        let premultiplied: Vec<[f32; 4]> = self
+            .pixels
+            .chunks_exact(4)
+            .map(|p| {
+                let a = f32::from(p[3]) / 255.0;
+                [
+                    f32::from(p[0]) * a,
+                    f32::from(p[1]) * a,
+                    f32::from(p[2]) * a,
+                    f32::from(p[3]),
+                ]
+            })
+            .collect();
+
+        // resizing rows and then columns is the same as covering rectangles, and much cheaper
+        let columns = coverage(self.width, width);
+        let rows = coverage(self.height, height);
+        let mut wide = vec![[0.0; 4]; width as usize * self.height as usize];
+        for y in 0..self.height as usize {
+            let src = &premultiplied[y * self.width as usize..][..self.width as usize];
+            for (x, weights) in columns.iter().enumerate() {
+                wide[y * width as usize + x] = weighted_sum(weights, |i| src[i]);
+            }
+        }
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+        for weights in &rows {
+            for x in 0..width as usize {
+                let [r, g, b, a] = weighted_sum(weights, |i| wide[i * width as usize + x]);
+                let unpremultiply = if a > 0.0 { 255.0 / a } else { 0.0 };
+                pixels.extend([
+                    to_u8(r * unpremultiply),
+                    to_u8(g * unpremultiply),
+                    to_u8(b * unpremultiply),
+                    to_u8(a),
+                ]);
+            }
+        }
+        Self::new(width, height, pixels)
+    }
+
+    /// Resamples the image to fit in a `size` by `size` square, keeping its aspect ratio, and
+    /// centers it there on transparent pixels.
+    pub fn fitted(&self, size: u32) -> Self {
+        let longest = self.width.max(self.height);
+        let scaled = |n: u32| {
+            ((u64::from(n) * u64::from(size) + u64::from(longest) / 2) / u64::from(longest)).max(1)
+                as u32
+        };
+        let image = self.resized(scaled(self.width), scaled(self.height));
+        if image.width == image.height {
+            return image;
+        }
+
+        let mut pixels = vec![0; size as usize * size as usize * 4];
+        let [left, top] = [(size - image.width) / 2, (size - image.height) / 2];
+        for (y, row) in image.pixels.chunks(image.width as usize * 4).enumerate() {
+            let start = ((top as usize + y) * size as usize + left as usize) * 4;
+            pixels[start..start + row.len()].copy_from_slice(row);
+        }
+        Self::new(size, size, pixels)
+    }
+}
+
+/// Picks the image to scale to `size` pixels across: the smallest that's at least that big, or
+/// the biggest if none are.
+pub fn best_for_size(images: &[RgbaImage], size: u32) -> Option<&RgbaImage> {
+    let across = |image: &&RgbaImage| image.width.max(image.height);
+    images
+        .iter()
+        .filter(|image| across(image) >= size)
+        .min_by_key(across)
+        .or_else(|| images.iter().max_by_key(across))
+}
+
+/// For each of `to` pixels in a row, which of `from` pixels it covers and how much of it.
+fn coverage(from: u32, to: u32) -> Vec<Vec<(usize, f32)>> {
+    let scale = f64::from(from) / f64::from(to);
+    (0..to)
+        .map(|i| {
+            let start = f64::from(i) * scale;
+            let end = start + scale;
+            let mut weights = Vec::new();
+            let mut j = start.floor();
+            while j < end {
+                let covered = end.min(j + 1.0) - start.max(j);
+                if covered > 1e-9 {
+                    weights.push((j as usize, (covered / scale) as f32));
+                }
+                j += 1.0;
+            }
+            weights
+        })
+        .collect()
+}
+
+fn weighted_sum(weights: &[(usize, f32)], pixel: impl Fn(usize) -> [f32; 4]) -> [f32; 4] {
+    let mut sum = [0.0; 4];
+    for &(i, weight) in weights {
+        for (sum, channel) in sum.iter_mut().zip(pixel(i)) {
+            *sum += channel * weight;
+        }
+    }
+    sum
+}
+
+fn to_u8(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: u32, height: u32, pixels: &[[u8; 4]]) -> RgbaImage {
+        RgbaImage::new(width, height, pixels.concat())
+    }
+
+    #[test]
+    fn shrinking_averages() {
+        const R: [u8; 4] = [255, 0, 0, 255];
+        const B: [u8; 4] = [0, 0, 255, 255];
+        #[rustfmt::skip]
+        let checks = image(4, 2, &[
+            R, B, R, R,
+            B, R, R, R,
+        ]);
+        let small = checks.resized(2, 1);
+        assert_eq!(small.pixel(0, 0), [128, 0, 128, 255]);
+        assert_eq!(small.pixel(1, 0), R);
+
+        // a third of a pixel each way
+        let odd = image(3, 1, &[R, B, B]).resized(2, 1);
+        assert_eq!(odd.pixel(0, 0), [170, 0, 85, 255]);
+        assert_eq!(odd.pixel(1, 0), B);
+    }
+
+    #[test]
+    fn transparency_doesnt_bleed() {
+        let edge = image(2, 1, &[[255, 255, 255, 255], [0, 0, 0, 0]]).resized(1, 1);
+        assert_eq!(edge.pixel(0, 0), [255, 255, 255, 128]);
+
+        let clear = image(2, 1, &[[0, 0, 0, 0]; 2]).resized(1, 1);
+        assert_eq!(clear.pixel(0, 0), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn growing() {
+        let gradient = image(2, 1, &[[0, 0, 0, 255], [200, 200, 200, 255]]);
+        let big = gradient.resized(4, 2);
+        assert_eq!((big.width, big.height), (4, 2));
+        assert_eq!(big.pixel(0, 1), [0, 0, 0, 255]);
+        assert_eq!(big.pixel(3, 0), [200, 200, 200, 255]);
+
+        // an exact multiple is just bigger pixels, and the same size is a copy
+        assert_eq!(gradient.resized(6, 1).pixel(2, 0), [0, 0, 0, 255]);
+        assert_eq!(gradient.resized(2, 1), gradient);
+    }
+
+    #[test]
+    fn fitting() {
+        let wide = image(4, 2, &[[10, 20, 30, 255]; 8]);
+        let square = wide.fitted(8);
+        assert_eq!((square.width, square.height), (8, 8));
+        // 8x4 in the middle
+        assert_eq!(square.pixel(0, 1), [0, 0, 0, 0]);
+        assert_eq!(square.pixel(0, 2), [10, 20, 30, 255]);
+        assert_eq!(square.pixel(7, 5), [10, 20, 30, 255]);
+        assert_eq!(square.pixel(7, 6), [0, 0, 0, 0]);
+
+        assert_eq!(
+            image(1, 1, &[[1, 2, 3, 4]]).fitted(3).pixel(2, 2),
+            [1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn choosing_sizes() {
+        let images = [8, 32, 24].map(|size| RgbaImage::new(size, 1, vec![0; size as usize * 4]));
+        let best = |size| best_for_size(&images, size).unwrap().width;
+        assert_eq!(best(8), 8);
+        assert_eq!(best(20), 24);
+        assert_eq!(best(25), 32);
+        assert_eq!(best(64), 32);
+        assert_eq!(best_for_size(&[], 16), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_length() {
+        RgbaImage::new(2, 2, vec![0; 12]);
+    }
+}
@@ -0,0 +1,582 @@
+//! Decoding [PNG](https://www.w3.org/TR/png/) images, of every colour type, bit depth and
+//! interlacing that the format has.
+//!
+//! Only what's needed for the pixels is read: ancillary chunks other than `tRNS` (transparency)
+//! are skipped, so gamma and colour profiles are ignored.
+
+use std::fmt;
+
+use crate::{inflate, RgbaImage};
+
+/// What every PNG file starts with.
+pub const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// The most pixels an image can have, so that a broken header can't ask for gigabytes.
+pub const MAX_PIXELS: u64 = 1 << 26;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Error {
+    /// The data doesn't start with [`SIGNATURE`].
+    NotPng,
+    /// The file ends in the middle of a chunk, before `IEND`, or without enough image data.
+    Truncated,
+    /// A chunk's CRC doesn't match its contents.
+    BadCrc,
+    /// The `IHDR` chunk is missing, or has values that aren't allowed.
+    BadHeader,
+    /// A palette image has no palette, or a pixel that's past its end.
+    BadPalette,
+    /// A row of the image data has an unknown filter type.
+    BadFilter,
+    /// The image is bigger than [`MAX_PIXELS`].
+    TooBig,
+    Inflate(inflate::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotPng => write!(f, "not a PNG file"),
+            Self::Truncated => write!(f, "truncated PNG file"),
+            Self::BadCrc => write!(f, "PNG chunk has the wrong CRC"),
+            Self::BadHeader => write!(f, "bad PNG header"),
+            Self::BadPalette => write!(f, "bad PNG palette"),
+            Self::BadFilter => write!(f, "bad PNG filter type"),
+            Self::TooBig => write!(f, "PNG image is too big"),
+            Self::Inflate(e) => write!(f, "bad PNG image data: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Inflate(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<inflate::Error> for Error {
+    fn from(e: inflate::Error) -> Self {
+        Self::Inflate(e)
+    }
+}
+
+/// Whether `bytes` look like a PNG file.
+pub fn is_png(bytes: &[u8]) -> bool {
+    bytes.starts_with(&SIGNATURE)
+}
+
+mod color_types {
+    pub const GRAY: u8 = 0;
+    pub const RGB: u8 = 2;
+    pub const PALETTE: u8 = 3;
+    pub const GRAY_ALPHA: u8 = 4;
+    pub const RGBA: u8 = 6;
+}
+
+/// The parts of `IHDR` that decoding needs.
+#[derive(Debug, Clone, Copy)]
+struct Header {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: u8,
+    interlaced: bool,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Result<Self, Error> {
+        let data: &[u8; 13] = data.try_into().map_err(|_| Error::BadHeader)?;
+        let width = u32::from_be_bytes(data[0..4].try_into().unwrap());
+        let height = u32::from_be_bytes(data[4..8].try_into().unwrap());
+        let [bit_depth, color_type, compression, filter, interlace] = data[8..].try_into().unwrap();
+
+        let depths: &[u8] = match color_type {
+            color_types::GRAY => &[1, 2, 4, 8, 16],
+            color_types::PALETTE => &[1, 2, 4, 8],
+            color_types::RGB | color_types::GRAY_ALPHA | color_types::RGBA => &[8, 16],
+            _ => &[],
+        };
+        if width == 0
+            || height == 0
+            || !depths.contains(&bit_depth)
+            || compression != 0
+            || filter != 0
+            || interlace > 1
+        {
+            return Err(Error::BadHeader);
+        }
+        if u64::from(width) * u64::from(height) > MAX_PIXELS {
+            return Err(Error::TooBig);
+        }
+        Ok(Self {
+            width,
+            height,
+            bit_depth,
+            color_type,
+            interlaced: interlace == 1,
+        })
+    }
+
+    fn channels(&self) -> usize {
+        match self.color_type {
+            color_types::RGB => 3,
+            color_types::GRAY_ALPHA => 2,
+            color_types::RGBA => 4,
+            _ => 1,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.channels() * usize::from(self.bit_depth)
+    }
+
+    /// The bytes in a row of `width` pixels, not counting the filter type.
+    fn row_bytes(&self, width: u32) -> usize {
+        (width as usize * self.bits_per_pixel()).div_ceil(8)
+    }
+}
+
+/// The colours that pixels are turned into RGBA with.
+struct Colors {
+    palette: Vec<[u8; 4]>,
+    /// From `tRNS`: the one gray level or RGB colour that's transparent, at the image's bit depth.
+    transparent: Option<[u16; 3]>,
+}
+
+/// Decodes a PNG.
+pub fn decode(bytes: &[u8]) -> Result<RgbaImage, Error> {
+    let mut rest = bytes.strip_prefix(&SIGNATURE).ok_or(Error::NotPng)?;
+    let mut header = None;
+    let mut colors = Colors {
+        palette: Vec::new(),
+        transparent: None,
+    };
+    let mut compressed = Vec::new();
+    loop {
+        let (Chunk { kind, data }, after) = chunk(rest)?;
+        rest = after;
+        match &kind {
+            b"IHDR" if header.is_none() => header = Some(Header::parse(data)?),
+            _ if header.is_none() => return Err(Error::BadHeader),
+            b"PLTE" => {
+                if data.len() % 3 != 0 || data.len() > 256 * 3 {
+                    return Err(Error::BadPalette);
+                }
+                colors.palette = data.chunks(3).map(|c| [c[0], c[1], c[2], 255]).collect();
+            }
+            b"tRNS" => {
+                let header = header.unwrap();
+                let sample = |i: usize| -> Result<u16, Error> {
+                    let bytes = data.get(i * 2..i * 2 + 2).ok_or(Error::Truncated)?;
+                    Ok(u16::from_be_bytes(bytes.try_into().unwrap()))
+                };
+                match header.color_type {
+                    color_types::PALETTE => {
+                        for (color, &alpha) in colors.palette.iter_mut().zip(data) {
+                            color[3] = alpha;
+                        }
+                    }
+                    color_types::GRAY => colors.transparent = Some([sample(0)?; 3]),
+                    color_types::RGB => {
+                        colors.transparent = Some([sample(0)?, sample(1)?, sample(2)?]);
+                    }
+                    // images with alpha channels don't have this
+                    _ => {}
+                }
+            }
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+    let header = header.ok_or(Error::BadHeader)?;
+    if header.color_type == color_types::PALETTE && colors.palette.is_empty() {
+        return Err(Error::BadPalette);
+    }
+
+    let data = inflate::zlib_decompress(&compressed)?;
+    let mut pixels = vec![0; header.width as usize * header.height as usize * 4];
+    if header.interlaced {
+        // Adam7: seven passes over smaller and smaller grids, each filtered like its own image
+        const PASSES: [(u32, u32, u32, u32); 7] = [
+            (0, 0, 8, 8),
+            (4, 0, 8, 8),
+            (0, 4, 4, 8),
+            (2, 0, 4, 4),
+            (0, 2, 2, 4),
+            (1, 0, 2, 2),
+            (0, 1, 1, 2),
+        ];
+        let mut data = &data[..];
+        for (x0, y0, dx, dy) in PASSES {
+            let width = (header.width + dx - 1 - x0) / dx;
+            let height = (header.height + dy - 1 - y0) / dy;
+            if width == 0 || height == 0 {
+                continue;
+            }
+            let used = (header.row_bytes(width) + 1) * height as usize;
+            let pass = data.get(..used).ok_or(Error::Truncated)?;
+            data = &data[used..];
+            let rows = unfilter(&header, pass, width, height)?;
+            for y in 0..height {
+                for x in 0..width {
+                    let rgba = pixel(&header, &colors, &rows, width, x, y)?;
+                    let i = ((y0 + y * dy) as usize * header.width as usize
+                        + (x0 + x * dx) as usize)
+                        * 4;
+                    pixels[i..i + 4].copy_from_slice(&rgba);
+                }
+            }
+        }
+    } else {
+        let (width, height) = (header.width, header.height);
+        let rows = unfilter(&header, &data, width, height)?;
+        for y in 0..height {
+            for x in 0..width {
+                let rgba = pixel(&header, &colors, &rows, width, x, y)?;
+                let i = (y as usize * width as usize + x as usize) * 4;
+                pixels[i..i + 4].copy_from_slice(&rgba);
+            }
+        }
+    }
+    Ok(RgbaImage::new(header.width, header.height, pixels))
+}
+
+struct Chunk<'a> {
+    kind: [u8; 4],
+    data: &'a [u8],
+}
+
+/// Splits the next chunk off, checking its CRC.
+///
+/// **Returns:** The chunk, and what comes after it.
+fn chunk(bytes: &[u8]) -> Result<(Chunk<'_>, &[u8]), Error> {
+    let length = bytes.get(..4).ok_or(Error::Truncated)?;
+    let length = u32::from_be_bytes(length.try_into().unwrap()) as usize;
+    let end = length.checked_add(12).ok_or(Error::Truncated)?;
+    let chunk = bytes.get(..end).ok_or(Error::Truncated)?;
+    let (typed, crc) = chunk[4..].split_at(length + 4);
+    if u32::from_be_bytes(crc.try_into().unwrap()) != crc32(typed) {
+        return Err(Error::BadCrc);
+    }
+    let chunk = Chunk {
+        kind: typed[..4].try_into().unwrap(),
+        data: &typed[4..],
+    };
+    Ok((chunk, &bytes[end..]))
+}
+
+/// The CRC that PNG chunks end with (the same one as zip and Ethernet).
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Undoes each row's filter, giving back the rows without their filter types.
+fn unfilter(header: &Header, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>, Error> {
+    let row_bytes = header.row_bytes(width);
+    // filters work on whole bytes, so pixels of less than a byte are as if they were a byte
+    let bpp = header.bits_per_pixel().div_ceil(8);
+    let data = data
+        .get(..(row_bytes + 1) * height as usize)
+        .ok_or(Error::Truncated)?;
+
+    let mut rows = vec![0_u8; row_bytes * height as usize];
+    for (y, filtered) in data.chunks_exact(row_bytes + 1).enumerate() {
+        let (done, rest) = rows.split_at_mut(y * row_bytes);
+        let previous = done.get(done.len().saturating_sub(row_bytes)..);
+        let previous = previous.filter(|_| y > 0);
+        let row = &mut rest[..row_bytes];
+        row.copy_from_slice(&filtered[1..]);
+        for i in 0..row_bytes {
+            let a = if i >= bpp { row[i - bpp] } else { 0 };
+            let b = previous.map_or(0, |p| p[i]);
+            let c = if i >= bpp {
+                previous.map_or(0, |p| p[i - bpp])
+            } else {
+                0
+            };
+            let predicted = match filtered[0] {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((u16::from(a) + u16::from(b)) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(Error::BadFilter),
+            };
+            row[i] = row[i].wrapping_add(predicted);
+        }
+    }
+    Ok(rows)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = i16::from(a) + i16::from(b) - i16::from(c);
+    let (pa, pb, pc) = (
+        (p - i16::from(a)).abs(),
+        (p - i16::from(b)).abs(),
+        (p - i16::from(c)).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// The pixel at `(x, y)` of unfiltered rows, as RGBA.
+fn pixel(
+    header: &Header,
+    colors: &Colors,
+    rows: &[u8],
+    width: u32,
+    x: u32,
+    y: u32,
+) -> Result<[u8; 4], Error> {
+    let row = &rows[header.row_bytes(width) * y as usize..];
+    let depth = usize::from(header.bit_depth);
+    // each sample at the image's bit depth, and scaled to 8 bits
+    let sample = |i: usize| -> (u16, u8) {
+        let bit = (x as usize * header.channels() + i) * depth;
+        match depth {
+            16 => {
+                let value = u16::from_be_bytes([row[bit / 8], row[bit / 8 + 1]]);
+                (value, (value >> 8) as u8)
+            }
+            8 => (u16::from(row[bit / 8]), row[bit / 8]),
+            _ => {
+                // packed from the most significant bit
+                let value = (row[bit / 8] >> (8 - depth - bit % 8)) & ((1 << depth) - 1);
+                (
+                    u16::from(value),
+                    (u16::from(value) * 255 / ((1 << depth) - 1)) as u8,
+                )
+            }
+        }
+    };
+    let opaque_unless = |raw: [u16; 3]| {
+        if colors.transparent == Some(raw) {
+            0
+        } else {
+            255
+        }
+    };
+
+    Ok(match header.color_type {
+        color_types::GRAY => {
+            let (raw, gray) = sample(0);
+            [gray, gray, gray, opaque_unless([raw; 3])]
+        }
+        color_types::RGB => {
+            let [(r, red), (g, green), (b, blue)] = [sample(0), sample(1), sample(2)];
+            [red, green, blue, opaque_unless([r, g, b])]
+        }
+        color_types::PALETTE => *colors
+            .palette
+            .get(usize::from(sample(0).0))
+            .ok_or(Error::BadPalette)?,
+        color_types::GRAY_ALPHA => {
+            let (gray, alpha) = (sample(0).1, sample(1).1);
+            [gray, gray, gray, alpha]
+        }
+        _ => [sample(0).1, sample(1).1, sample(2).1, sample(3).1],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Makes a PNG out of chunks, filling in their lengths and CRCs.
+    fn png(chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let mut bytes = SIGNATURE.to_vec();
+        for (kind, data) in chunks {
+            bytes.extend((data.len() as u32).to_be_bytes());
+            let start = bytes.len();
+            bytes.extend(*kind);
+            bytes.extend(*data);
+            let crc = crc32(&bytes[start..]);
+            bytes.extend(crc.to_be_bytes());
+        }
+        bytes
+    }
+
+    fn ihdr(width: u32, height: u32, bit_depth: u8, color_type: u8, interlace: u8) -> Vec<u8> {
+        let mut data = width.to_be_bytes().to_vec();
+        data.extend(height.to_be_bytes());
+        data.extend([bit_depth, color_type, 0, 0, interlace]);
+        data
+    }
+
+    /// Wraps data in zlib's format, as stored blocks.
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut out = vec![0x78, 0x01];
+        let mut blocks = data.chunks(0xFFFF).peekable();
+        if blocks.peek().is_none() {
+            out.extend([1, 0, 0, 0xFF, 0xFF]);
+        }
+        while let Some(block) = blocks.next() {
+            out.push(blocks.peek().is_none().into());
+            out.extend((block.len() as u16).to_le_bytes());
+            out.extend((!(block.len() as u16)).to_le_bytes());
+            out.extend(block);
+        }
+        out.extend(inflate::adler32(data).to_be_bytes());
+        out
+    }
+
+    fn encode(header: &[u8], extra: &[(&[u8; 4], &[u8])], rows: &[u8]) -> Vec<u8> {
+        let idat = zlib(rows);
+        let mut chunks = vec![(b"IHDR", header)];
+        chunks.extend_from_slice(extra);
+        chunks.extend([(b"IDAT", &idat[..]), (b"IEND", &[][..])]);
+        png(&chunks)
+    }
+
+    #[test]
+    fn rgba() {
+        // 2x2, with each row filtered differently
+        #[rustfmt::skip]
+        let rows = [
+            1, 10, 20, 30, 255, 5, 5, 5, 0, // sub
+            2, 1, 1, 1, 0, 0, 0, 0, 0, // up
+        ];
+        let image = decode(&encode(&ihdr(2, 2, 8, 6, 0), &[], &rows)).unwrap();
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(image.pixel(0, 0), [10, 20, 30, 255]);
+        assert_eq!(image.pixel(1, 0), [15, 25, 35, 255]);
+        assert_eq!(image.pixel(0, 1), [11, 21, 31, 255]);
+        assert_eq!(image.pixel(1, 1), [15, 25, 35, 255]);
+    }
+
+    #[test]
+    fn filters() {
+        // average and Paeth, which look at the pixel to the left and the ones above
+        #[rustfmt::skip]
+        let rows = [
+            0, 100, 200,
+            3, 10, 20, // 100 / 2 + 10, (200 + 60) / 2 + 20
+            4, 1, 1, // Paeth picks 60 (above), then 150 (above)
+        ];
+        let image = decode(&encode(&ihdr(2, 3, 8, 0, 0), &[], &rows)).unwrap();
+        let grays: Vec<u8> = image.pixels.chunks(4).map(|p| p[0]).collect();
+        assert_eq!(grays, [100, 200, 60, 150, 61, 151]);
+
+        let bad = [0, 1, 2, 5, 1, 2, 0, 1, 2];
+        assert_eq!(
+            decode(&encode(&ihdr(2, 3, 8, 0, 0), &[], &bad)),
+            Err(Error::BadFilter)
+        );
+    }
+
+    #[test]
+    fn palettes() {
+        let palette: &[u8] = &[255, 0, 0, 0, 255, 0, 0, 0, 255];
+        // 2 bits a pixel, with the second colour half transparent
+        let rows = [0, 0b00_01_10_00, 0b01_00_00_00];
+        let png = encode(
+            &ihdr(5, 1, 2, 3, 0),
+            &[(b"PLTE", palette), (b"tRNS", &[255, 128])],
+            &rows,
+        );
+        let image = decode(&png).unwrap();
+        assert_eq!(image.pixel(0, 0), [255, 0, 0, 255]);
+        assert_eq!(image.pixel(1, 0), [0, 255, 0, 128]);
+        assert_eq!(image.pixel(2, 0), [0, 0, 255, 255]);
+        assert_eq!(image.pixel(4, 0), [0, 255, 0, 128]);
+
+        // index 3 is past the end
+        let png = encode(&ihdr(1, 1, 2, 3, 0), &[(b"PLTE", palette)], &[0, 0xC0]);
+        assert_eq!(decode(&png), Err(Error::BadPalette));
+        let png = encode(&ihdr(1, 1, 2, 3, 0), &[], &[0, 0]);
+        assert_eq!(decode(&png), Err(Error::BadPalette));
+    }
+
+    #[test]
+    fn depths() {
+        // 1-bit gray scales up to 255
+        let image = decode(&encode(&ihdr(3, 1, 1, 0, 0), &[], &[0, 0b101_00000])).unwrap();
+        assert_eq!(image.pixel(0, 0), [255, 255, 255, 255]);
+        assert_eq!(image.pixel(1, 0), [0, 0, 0, 255]);
+
+        // 16-bit RGB, where 0x1234 0x5678 0x9ABC is transparent
+        #[rustfmt::skip]
+        let rows = [
+            0, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC,
+            0, 0xFF, 0xFF, 0x80, 0x00, 0x00, 0x01,
+        ];
+        let trns = [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC];
+        let png = encode(&ihdr(1, 2, 16, 2, 0), &[(b"tRNS", &trns)], &rows);
+        let image = decode(&png).unwrap();
+        assert_eq!(image.pixel(0, 0), [0x12, 0x56, 0x9A, 0]);
+        assert_eq!(image.pixel(0, 1), [0xFF, 0x80, 0x00, 255]);
+
+        // gray with alpha
+        let image = decode(&encode(&ihdr(1, 1, 8, 4, 0), &[], &[0, 7, 9])).unwrap();
+        assert_eq!(image.pixel(0, 0), [7, 7, 7, 9]);
+    }
+
+    #[test]
+    fn interlacing() {
+        // a 3x3 gray image in Adam7's passes: pass 1 has (0, 0), 4 has (2, 0), 5 has (0, 2) and
+        // (2, 2), 6 has (1, 0) and (1, 2), and 7 has the middle row
+        #[rustfmt::skip]
+        let passes = [
+            0, 1,
+            0, 3,
+            0, 7, 9,
+            0, 2, 0, 8,
+            0, 4, 5, 6,
+        ];
+        let image = decode(&encode(&ihdr(3, 3, 8, 0, 1), &[], &passes)).unwrap();
+        let grays: Vec<u8> = image.pixels.chunks(4).map(|p| p[0]).collect();
+        assert_eq!(grays, [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn broken_files() {
+        let good = encode(&ihdr(1, 1, 8, 0, 0), &[], &[0, 0]);
+        assert!(decode(&good).is_ok());
+        assert_eq!(decode(b"GIF89a"), Err(Error::NotPng));
+        assert_eq!(decode(&good[..good.len() - 1]), Err(Error::Truncated));
+
+        let mut bad_crc = good.clone();
+        bad_crc[20] ^= 1;
+        assert_eq!(decode(&bad_crc), Err(Error::BadCrc));
+
+        // 8-bit RGB, but only one pixel's worth of data
+        let short = encode(&ihdr(2, 1, 8, 2, 0), &[], &[0, 1, 2, 3]);
+        assert_eq!(decode(&short), Err(Error::Truncated));
+
+        assert_eq!(
+            decode(&encode(&ihdr(1, 1, 4, 2, 0), &[], &[0, 0])),
+            Err(Error::BadHeader)
+        );
+        assert_eq!(decode(&png(&[(b"IEND", &[])])), Err(Error::BadHeader));
+        assert_eq!(
+            decode(&encode(&ihdr(1 << 14, 1 << 13, 8, 0, 0), &[], &[])),
+            Err(Error::TooBig)
+        );
+    }
+
+    #[test]
+    fn crcs() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+}
@@ -0,0 +1,107 @@
+//! Reading the icons in `icons`:
+//!
+//! - `app.ico` has a 16px 32-bit bitmap, a 32px 16-colour bitmap, a 24px 24-bit bitmap and a
+//!   256px PNG, in that order.
+//! - `pointer.cur` has a 32px 32-bit bitmap with no alpha, so its mask is used, and an 8px
+//!   black-and-white bitmap.
+//! - `no_mask.ico` has a 1-bit bitmap that's missing its mask.
+
+extern crate triangle_from_scratch_image as image;
+
+use image::ico::{self, Error};
+
+const APP: &[u8] = include_bytes!("icons/app.ico");
+const POINTER: &[u8] = include_bytes!("icons/pointer.cur");
+const NO_MASK: &[u8] = include_bytes!("icons/no_mask.ico");
+
+#[test]
+fn icons() {
+    let entries = ico::parse(APP).unwrap();
+    let sizes: Vec<_> = entries
+        .iter()
+        .map(|e| (e.image.width, e.image.height))
+        .collect();
+    assert_eq!(sizes, [(16, 16), (32, 32), (24, 24), (256, 256)]);
+    assert!(entries.iter().all(|e| e.hotspot.is_none()));
+
+    // the alpha channel is used as it is, and the rows are the right way up
+    let argb = &entries[0].image;
+    assert_eq!(argb.pixel(2, 3), [32, 48, 0x80, 255]);
+    assert_eq!(argb.pixel(12, 15), [192, 240, 0x80, 128]);
+
+    // the palette, with a transparent border from the mask
+    let palette = &entries[1].image;
+    assert_eq!(palette.pixel(0, 5)[3], 0);
+    assert_eq!(palette.pixel(3, 4), [112, 0, 143, 255]);
+    assert_eq!(palette.pixel(30, 30), [192, 0, 63, 255]);
+
+    // no alpha channel, so the mask makes the left half transparent
+    let rgb = &entries[2].image;
+    assert_eq!(rgb.pixel(11, 0), [100, 200, 0, 0]);
+    assert_eq!(rgb.pixel(12, 23), [100, 200, 0, 255]);
+
+    let png = &entries[3].image;
+    assert_eq!(png.pixel(70, 200), [64, 192, 0, 255]);
+}
+
+#[test]
+fn cursors() {
+    let entries = ico::parse(POINTER).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].hotspot, Some([5, 7]));
+    assert_eq!(entries[1].hotspot, Some([1, 2]));
+
+    // the alpha channel is empty, so the mask is used instead: transparent above the diagonal
+    let white = &entries[0].image;
+    assert_eq!(white.pixel(10, 3), [255, 255, 255, 0]);
+    assert_eq!(white.pixel(3, 10), [255, 255, 255, 255]);
+
+    let checks = &entries[1].image;
+    assert_eq!(checks.pixel(0, 0), [0, 0, 0, 255]);
+    assert_eq!(checks.pixel(1, 0), [255, 255, 255, 255]);
+    assert_eq!(checks.pixel(7, 6), [255, 255, 255, 255]);
+}
+
+#[test]
+fn choosing_sizes() {
+    let images: Vec<_> = ico::parse(APP)
+        .unwrap()
+        .into_iter()
+        .map(|e| e.image)
+        .collect();
+    assert_eq!(image::best_for_size(&images, 20).unwrap().width, 24);
+    assert_eq!(image::best_for_size(&images, 48).unwrap().width, 256);
+
+    // the PNG scales down to a big icon size
+    let icon = image::best_for_size(&images, 40).unwrap().resized(40, 40);
+    assert_eq!(icon.pixel(39, 0), [192, 0, 0, 255]);
+}
+
+#[test]
+fn broken_files() {
+    assert_eq!(ico::parse(NO_MASK), Err(Error::Truncated));
+    assert_eq!(ico::parse(&APP[..100]), Err(Error::Truncated));
+    assert_eq!(ico::parse(&APP[..4]), Err(Error::Truncated));
+    assert_eq!(ico::parse(b"\x89PNG\r\n\x1a\n"), Err(Error::NotIco));
+
+    // the PNG's CRCs are checked
+    let mut bad_png = APP.to_vec();
+    let png = u32::from_le_bytes(bad_png[6 + 3 * 16 + 12..][..4].try_into().unwrap()) as usize;
+    bad_png[png + 20] ^= 1;
+    assert_eq!(
+        ico::parse(&bad_png),
+        Err(Error::Png(image::png::Error::BadCrc))
+    );
+
+    // 16-bit bitmaps aren't supported
+    let mut high_color = APP.to_vec();
+    let bitmap = u32::from_le_bytes(high_color[6 + 12..][..4].try_into().unwrap()) as usize;
+    high_color[bitmap + 14] = 16;
+    assert_eq!(
+        ico::parse(&high_color),
+        Err(Error::UnsupportedBitmap {
+            bit_count: 16,
+            compression: 0
+        })
+    );
+}
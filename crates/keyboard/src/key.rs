@@ -0,0 +1,69 @@
+//! Logical keys.
+
+/// What a key means in the current layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// A key that doesn't type anything.
+    Named(NamedKey),
+    /// A key that types a character, with the shift level that the held modifiers select. The
+    /// space bar is `Character(' ')`.
+    Character(char),
+    /// A dead key, which changes the next character typed rather than typing one itself. The
+    /// character is the accent on its own, e.g. `'^'` for a dead circumflex.
+    Dead(char),
+    /// A key that the layout doesn't have anything for, or that isn't supported here.
+    Unidentified,
+}
+
+/// A key that doesn't type anything, named like the `key` of a browser's `KeyboardEvent`.
+///
+/// There's no left and right here: both shift keys are [`Shift`](Self::Shift), and the physical
+/// key says which one it was.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NamedKey {
+    Escape,
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    Shift,
+    Control,
+    Alt,
+    /// The key that selects the third shift level, usually right alt.
+    AltGraph,
+    Super,
+    CapsLock,
+    NumLock,
+    ScrollLock,
+    PrintScreen,
+    Pause,
+    ContextMenu,
+    /// A function key, from 1 to 24.
+    F(u8),
+    AudioVolumeMute,
+    AudioVolumeDown,
+    AudioVolumeUp,
+    MediaPlayPause,
+    MediaStop,
+    MediaTrackNext,
+    MediaTrackPrevious,
+}
+
+impl Key {
+    /// The character this key types, if it types one.
+    pub const fn to_char(self) -> Option<char> {
+        match self {
+            Self::Character(c) => Some(c),
+            _ => None,
+        }
+    }
+}
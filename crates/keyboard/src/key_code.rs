@@ -0,0 +1,419 @@
+//! Physical keys.
+
+use crate::key::NamedKey;
+
+/// A physical key, named after the key in the same position on a US keyboard (like the `code`
+/// of a browser's `KeyboardEvent`).
+///
+/// Keys that are in a different place on other keyboards are named after their US position too:
+/// `KeyQ` is the key labelled "A" on a French keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum KeyCode {
+    /// A key without a name here, or that the platform didn't say anything about.
+    Unidentified,
+
+    // The writing system keys
+    Backquote,
+    Digit1,
+    Digit2,
+    Digit3,
+    Digit4,
+    Digit5,
+    Digit6,
+    Digit7,
+    Digit8,
+    Digit9,
+    Digit0,
+    Minus,
+    Equal,
+    KeyQ,
+    KeyW,
+    KeyE,
+    KeyR,
+    KeyT,
+    KeyY,
+    KeyU,
+    KeyI,
+    KeyO,
+    KeyP,
+    BracketLeft,
+    BracketRight,
+    Backslash,
+    KeyA,
+    KeyS,
+    KeyD,
+    KeyF,
+    KeyG,
+    KeyH,
+    KeyJ,
+    KeyK,
+    KeyL,
+    Semicolon,
+    Quote,
+    /// The extra key next to the left shift key on ISO keyboards.
+    IntlBackslash,
+    KeyZ,
+    KeyX,
+    KeyC,
+    KeyV,
+    KeyB,
+    KeyN,
+    KeyM,
+    Comma,
+    Period,
+    Slash,
+    /// The extra key next to the right shift key on Japanese keyboards.
+    IntlRo,
+    /// The extra key next to backspace on Japanese keyboards.
+    IntlYen,
+    Space,
+
+    // Functional keys
+    Backspace,
+    Tab,
+    Enter,
+    CapsLock,
+    ShiftLeft,
+    ShiftRight,
+    ControlLeft,
+    ControlRight,
+    AltLeft,
+    /// Labelled "AltGr" on many keyboards.
+    AltRight,
+    /// The Windows key, or the command key on a Mac keyboard.
+    SuperLeft,
+    SuperRight,
+    ContextMenu,
+    Convert,
+    NonConvert,
+    KanaMode,
+    Lang1,
+    Lang2,
+
+    // The control pad
+    Insert,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+
+    // The numpad
+    NumLock,
+    Numpad0,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    NumpadAdd,
+    NumpadSubtract,
+    NumpadMultiply,
+    NumpadDivide,
+    NumpadDecimal,
+    NumpadComma,
+    NumpadEqual,
+    NumpadEnter,
+
+    // The function section
+    Escape,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    F21,
+    F22,
+    F23,
+    F24,
+    PrintScreen,
+    ScrollLock,
+    Pause,
+
+    // Media keys
+    AudioVolumeMute,
+    AudioVolumeDown,
+    AudioVolumeUp,
+    MediaPlayPause,
+    MediaStop,
+    MediaTrackNext,
+    MediaTrackPrevious,
+}
+
+impl KeyCode {
+    /// Every key code except [`Unidentified`](Self::Unidentified), in declaration order.
+    pub const ALL: &'static [Self] = {
+        use KeyCode::*;
+        &[
+            Backquote,
+            Digit1,
+            Digit2,
+            Digit3,
+            Digit4,
+            Digit5,
+            Digit6,
+            Digit7,
+            Digit8,
+            Digit9,
+            Digit0,
+            Minus,
+            Equal,
+            KeyQ,
+            KeyW,
+            KeyE,
+            KeyR,
+            KeyT,
+            KeyY,
+            KeyU,
+            KeyI,
+            KeyO,
+            KeyP,
+            BracketLeft,
+            BracketRight,
+            Backslash,
+            KeyA,
+            KeyS,
+            KeyD,
+            KeyF,
+            KeyG,
+            KeyH,
+            KeyJ,
+            KeyK,
+            KeyL,
+            Semicolon,
+            Quote,
+            IntlBackslash,
+            KeyZ,
+            KeyX,
+            KeyC,
+            KeyV,
+            KeyB,
+            KeyN,
+            KeyM,
+            Comma,
+            Period,
+            Slash,
+            IntlRo,
+            IntlYen,
+            Space,
+            Backspace,
+            Tab,
+            Enter,
+            CapsLock,
+            ShiftLeft,
+            ShiftRight,
+            ControlLeft,
+            ControlRight,
+            AltLeft,
+            AltRight,
+            SuperLeft,
+            SuperRight,
+            ContextMenu,
+            Convert,
+            NonConvert,
+            KanaMode,
+            Lang1,
+            Lang2,
+            Insert,
+            Delete,
+            Home,
+            End,
+            PageUp,
+            PageDown,
+            ArrowUp,
+            ArrowDown,
+            ArrowLeft,
+            ArrowRight,
+            NumLock,
+            Numpad0,
+            Numpad1,
+            Numpad2,
+            Numpad3,
+            Numpad4,
+            Numpad5,
+            Numpad6,
+            Numpad7,
+            Numpad8,
+            Numpad9,
+            NumpadAdd,
+            NumpadSubtract,
+            NumpadMultiply,
+            NumpadDivide,
+            NumpadDecimal,
+            NumpadComma,
+            NumpadEqual,
+            NumpadEnter,
+            Escape,
+            F1,
+            F2,
+            F3,
+            F4,
+            F5,
+            F6,
+            F7,
+            F8,
+            F9,
+            F10,
+            F11,
+            F12,
+            F13,
+            F14,
+            F15,
+            F16,
+            F17,
+            F18,
+            F19,
+            F20,
+            F21,
+            F22,
+            F23,
+            F24,
+            PrintScreen,
+            ScrollLock,
+            Pause,
+            AudioVolumeMute,
+            AudioVolumeDown,
+            AudioVolumeUp,
+            MediaPlayPause,
+            MediaStop,
+            MediaTrackNext,
+            MediaTrackPrevious,
+        ]
+    };
+
+    /// Whether this is a shift, control, alt or super key. Lock keys aren't modifiers here.
+    pub const fn is_modifier(self) -> bool {
+        use KeyCode::*;
+        matches!(
+            self,
+            ShiftLeft
+                | ShiftRight
+                | ControlLeft
+                | ControlRight
+                | AltLeft
+                | AltRight
+                | SuperLeft
+                | SuperRight
+        )
+    }
+
+    /// The logical key for keys that mean the same thing in every layout, like the arrow keys.
+    ///
+    /// Keys that type something (including the numpad, which types digits only while num lock is
+    /// on) return `None`, since what they mean depends on the layout. So does [`AltRight`], which
+    /// is AltGr in some layouts and plain Alt in others.
+    ///
+    /// [`AltRight`]: Self::AltRight
+    pub const fn named_key(self) -> Option<NamedKey> {
+        use KeyCode::*;
+        Some(match self {
+            Backspace => NamedKey::Backspace,
+            Tab => NamedKey::Tab,
+            Enter | NumpadEnter => NamedKey::Enter,
+            CapsLock => NamedKey::CapsLock,
+            ShiftLeft | ShiftRight => NamedKey::Shift,
+            ControlLeft | ControlRight => NamedKey::Control,
+            AltLeft => NamedKey::Alt,
+            SuperLeft | SuperRight => NamedKey::Super,
+            ContextMenu => NamedKey::ContextMenu,
+            Insert => NamedKey::Insert,
+            Delete => NamedKey::Delete,
+            Home => NamedKey::Home,
+            End => NamedKey::End,
+            PageUp => NamedKey::PageUp,
+            PageDown => NamedKey::PageDown,
+            ArrowUp => NamedKey::ArrowUp,
+            ArrowDown => NamedKey::ArrowDown,
+            ArrowLeft => NamedKey::ArrowLeft,
+            ArrowRight => NamedKey::ArrowRight,
+            NumLock => NamedKey::NumLock,
+            Escape => NamedKey::Escape,
+            F1 => NamedKey::F(1),
+            F2 => NamedKey::F(2),
+            F3 => NamedKey::F(3),
+            F4 => NamedKey::F(4),
+            F5 => NamedKey::F(5),
+            F6 => NamedKey::F(6),
+            F7 => NamedKey::F(7),
+            F8 => NamedKey::F(8),
+            F9 => NamedKey::F(9),
+            F10 => NamedKey::F(10),
+            F11 => NamedKey::F(11),
+            F12 => NamedKey::F(12),
+            F13 => NamedKey::F(13),
+            F14 => NamedKey::F(14),
+            F15 => NamedKey::F(15),
+            F16 => NamedKey::F(16),
+            F17 => NamedKey::F(17),
+            F18 => NamedKey::F(18),
+            F19 => NamedKey::F(19),
+            F20 => NamedKey::F(20),
+            F21 => NamedKey::F(21),
+            F22 => NamedKey::F(22),
+            F23 => NamedKey::F(23),
+            F24 => NamedKey::F(24),
+            PrintScreen => NamedKey::PrintScreen,
+            ScrollLock => NamedKey::ScrollLock,
+            Pause => NamedKey::Pause,
+            AudioVolumeMute => NamedKey::AudioVolumeMute,
+            AudioVolumeDown => NamedKey::AudioVolumeDown,
+            AudioVolumeUp => NamedKey::AudioVolumeUp,
+            MediaPlayPause => NamedKey::MediaPlayPause,
+            MediaStop => NamedKey::MediaStop,
+            MediaTrackNext => NamedKey::MediaTrackNext,
+            MediaTrackPrevious => NamedKey::MediaTrackPrevious,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_is_complete() {
+        // declaration order is discriminant order, and `Unidentified` comes first
+        for (i, &code) in KeyCode::ALL.iter().enumerate() {
+            assert_eq!(code as usize, i + 1, "{code:?}");
+        }
+        assert_eq!(
+            KeyCode::ALL.last().map(|&code| code as usize),
+            Some(KeyCode::MediaTrackPrevious as usize)
+        );
+    }
+
+    #[test]
+    fn named_keys() {
+        assert_eq!(KeyCode::F11.named_key(), Some(NamedKey::F(11)));
+        assert_eq!(KeyCode::NumpadEnter.named_key(), Some(NamedKey::Enter));
+        assert_eq!(KeyCode::KeyA.named_key(), None);
+        assert_eq!(KeyCode::Numpad7.named_key(), None);
+        assert_eq!(KeyCode::AltRight.named_key(), None);
+        assert!(KeyCode::AltRight.is_modifier());
+        assert!(!KeyCode::CapsLock.is_modifier());
+    }
+}
@@ -0,0 +1,439 @@
+//! Keysyms, the numbers that X11 and XKB (so Wayland too) use for what's on a key.
+//!
+//! Printable keysyms are either Latin-1 code points, which are their own keysym, or any Unicode
+//! code point plus `0x0100_0000`. There are older keysyms for other scripts too, which aren't
+//! supported here except for the euro sign, since layouts still use it. See
+//! `X11/keysymdef.h` for the full list.
+
+use crate::key::{Key, NamedKey};
+
+/// The keysym for "nothing here", in keymaps.
+pub const NO_SYMBOL: u32 = 0;
+
+const UNICODE_OFFSET: u32 = 0x0100_0000;
+const EURO_SIGN: u32 = 0x20AC;
+
+/// Keysyms for keys that don't type anything, or that type control characters.
+#[rustfmt::skip]
+const NAMED: &[(u32, NamedKey)] = &[
+    (0xFF08, NamedKey::Backspace),
+    (0xFF09, NamedKey::Tab),
+    (0xFF0D, NamedKey::Enter),
+    (0xFF13, NamedKey::Pause),
+    (0xFF14, NamedKey::ScrollLock),
+    (0xFF1B, NamedKey::Escape),
+    (0xFF50, NamedKey::Home),
+    (0xFF51, NamedKey::ArrowLeft),
+    (0xFF52, NamedKey::ArrowUp),
+    (0xFF53, NamedKey::ArrowRight),
+    (0xFF54, NamedKey::ArrowDown),
+    (0xFF55, NamedKey::PageUp),
+    (0xFF56, NamedKey::PageDown),
+    (0xFF57, NamedKey::End),
+    (0xFF61, NamedKey::PrintScreen),
+    (0xFF63, NamedKey::Insert),
+    (0xFF67, NamedKey::ContextMenu),
+    (0xFF7E, NamedKey::AltGraph), // Mode_switch
+    (0xFF7F, NamedKey::NumLock),
+    (0xFF89, NamedKey::Tab), // KP_Tab
+    (0xFF8D, NamedKey::Enter), // KP_Enter
+    (0xFF95, NamedKey::Home), // KP_Home, and so on while num lock is off
+    (0xFF96, NamedKey::ArrowLeft),
+    (0xFF97, NamedKey::ArrowUp),
+    (0xFF98, NamedKey::ArrowRight),
+    (0xFF99, NamedKey::ArrowDown),
+    (0xFF9A, NamedKey::PageUp),
+    (0xFF9B, NamedKey::PageDown),
+    (0xFF9C, NamedKey::End),
+    (0xFF9E, NamedKey::Insert),
+    (0xFF9F, NamedKey::Delete),
+    (0xFFE1, NamedKey::Shift),
+    (0xFFE2, NamedKey::Shift),
+    (0xFFE3, NamedKey::Control),
+    (0xFFE4, NamedKey::Control),
+    (0xFFE5, NamedKey::CapsLock),
+    (0xFFE7, NamedKey::Super), // Meta_L
+    (0xFFE8, NamedKey::Super),
+    (0xFFE9, NamedKey::Alt),
+    (0xFFEA, NamedKey::Alt),
+    (0xFFEB, NamedKey::Super),
+    (0xFFEC, NamedKey::Super),
+    (0xFFFF, NamedKey::Delete),
+    (0xFE03, NamedKey::AltGraph), // ISO_Level3_Shift
+    (0xFE20, NamedKey::Tab), // ISO_Left_Tab, which is shift+tab
+    (0x1008_FF11, NamedKey::AudioVolumeDown),
+    (0x1008_FF12, NamedKey::AudioVolumeMute),
+    (0x1008_FF13, NamedKey::AudioVolumeUp),
+    (0x1008_FF14, NamedKey::MediaPlayPause),
+    (0x1008_FF15, NamedKey::MediaStop),
+    (0x1008_FF16, NamedKey::MediaTrackPrevious),
+    (0x1008_FF17, NamedKey::MediaTrackNext),
+];
+
+/// Dead keys, with the accent each of them puts on the next character.
+#[rustfmt::skip]
+const DEAD: &[(u32, char)] = &[
+    (0xFE50, '`'), // dead_grave
+    (0xFE51, '´'), // dead_acute
+    (0xFE52, '^'), // dead_circumflex
+    (0xFE53, '~'), // dead_tilde
+    (0xFE54, '¯'), // dead_macron
+    (0xFE55, '˘'), // dead_breve
+    (0xFE56, '˙'), // dead_abovedot
+    (0xFE57, '¨'), // dead_diaeresis
+    (0xFE58, '°'), // dead_abovering
+    (0xFE59, '˝'), // dead_doubleacute
+    (0xFE5A, 'ˇ'), // dead_caron
+    (0xFE5B, '¸'), // dead_cedilla
+    (0xFE5C, '˛'), // dead_ogonek
+];
+
+/// The function keys, F1 to F35.
+const F1: u32 = 0xFFBE;
+const F35: u32 = 0xFFE0;
+
+/// The names of the printable ASCII keysyms, from `space` (`0x20`) to `asciitilde` (`0x7E`).
+/// Letters and digits are their own names.
+#[rustfmt::skip]
+const ASCII_NAMES: [&str; 95] = [
+    "space", "exclam", "quotedbl", "numbersign", "dollar", "percent", "ampersand", "apostrophe",
+    "parenleft", "parenright", "asterisk", "plus", "comma", "minus", "period", "slash",
+    "0", "1", "2", "3", "4", "5", "6", "7", "8", "9",
+    "colon", "semicolon", "less", "equal", "greater", "question", "at",
+    "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M",
+    "N", "O", "P", "Q", "R", "S", "T", "U", "V", "W", "X", "Y", "Z",
+    "bracketleft", "backslash", "bracketright", "asciicircum", "underscore", "grave",
+    "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m",
+    "n", "o", "p", "q", "r", "s", "t", "u", "v", "w", "x", "y", "z",
+    "braceleft", "bar", "braceright", "asciitilde",
+];
+
+/// The names of the Latin-1 keysyms, from `nobreakspace` (`0xA0`) to `ydiaeresis` (`0xFF`).
+#[rustfmt::skip]
+const LATIN_1_NAMES: [&str; 96] = [
+    "nobreakspace", "exclamdown", "cent", "sterling", "currency", "yen", "brokenbar", "section",
+    "diaeresis", "copyright", "ordfeminine", "guillemotleft", "notsign", "hyphen", "registered",
+    "macron", "degree", "plusminus", "twosuperior", "threesuperior", "acute", "mu", "paragraph",
+    "periodcentered", "cedilla", "onesuperior", "masculine", "guillemotright", "onequarter",
+    "onehalf", "threequarters", "questiondown",
+    "Agrave", "Aacute", "Acircumflex", "Atilde", "Adiaeresis", "Aring", "AE", "Ccedilla",
+    "Egrave", "Eacute", "Ecircumflex", "Ediaeresis", "Igrave", "Iacute", "Icircumflex",
+    "Idiaeresis", "ETH", "Ntilde", "Ograve", "Oacute", "Ocircumflex", "Otilde", "Odiaeresis",
+    "multiply", "Oslash", "Ugrave", "Uacute", "Ucircumflex", "Udiaeresis", "Yacute", "THORN",
+    "ssharp",
+    "agrave", "aacute", "acircumflex", "atilde", "adiaeresis", "aring", "ae", "ccedilla",
+    "egrave", "eacute", "ecircumflex", "ediaeresis", "igrave", "iacute", "icircumflex",
+    "idiaeresis", "eth", "ntilde", "ograve", "oacute", "ocircumflex", "otilde", "odiaeresis",
+    "division", "oslash", "ugrave", "uacute", "ucircumflex", "udiaeresis", "yacute", "thorn",
+    "ydiaeresis",
+];
+
+/// The names of the other keysyms that keymaps commonly use. Names that come later in
+/// `X11/keysymdef.h` for the same keysym (like `Page_Up` for `Prior`) are included too.
+#[rustfmt::skip]
+const OTHER_NAMES: &[(&str, u32)] = &[
+    ("NoSymbol", NO_SYMBOL),
+    ("VoidSymbol", 0xFF_FFFF),
+    ("EuroSign", EURO_SIGN),
+    ("guillemetleft", 0xAB),
+    ("guillemetright", 0xBB),
+    ("ordmasculine", 0xBA),
+    ("BackSpace", 0xFF08),
+    ("Tab", 0xFF09),
+    ("Linefeed", 0xFF0A),
+    ("Clear", 0xFF0B),
+    ("Return", 0xFF0D),
+    ("Pause", 0xFF13),
+    ("Scroll_Lock", 0xFF14),
+    ("Sys_Req", 0xFF15),
+    ("Escape", 0xFF1B),
+    ("Multi_key", 0xFF20),
+    ("Home", 0xFF50),
+    ("Left", 0xFF51),
+    ("Up", 0xFF52),
+    ("Right", 0xFF53),
+    ("Down", 0xFF54),
+    ("Prior", 0xFF55),
+    ("Page_Up", 0xFF55),
+    ("Next", 0xFF56),
+    ("Page_Down", 0xFF56),
+    ("End", 0xFF57),
+    ("Begin", 0xFF58),
+    ("Select", 0xFF60),
+    ("Print", 0xFF61),
+    ("Execute", 0xFF62),
+    ("Insert", 0xFF63),
+    ("Undo", 0xFF65),
+    ("Redo", 0xFF66),
+    ("Menu", 0xFF67),
+    ("Find", 0xFF68),
+    ("Cancel", 0xFF69),
+    ("Help", 0xFF6A),
+    ("Break", 0xFF6B),
+    ("Mode_switch", 0xFF7E),
+    ("ISO_Group_Shift", 0xFF7E),
+    ("Num_Lock", 0xFF7F),
+    ("KP_Space", 0xFF80),
+    ("KP_Tab", 0xFF89),
+    ("KP_Enter", 0xFF8D),
+    ("KP_F1", 0xFF91),
+    ("KP_F2", 0xFF92),
+    ("KP_F3", 0xFF93),
+    ("KP_F4", 0xFF94),
+    ("KP_Home", 0xFF95),
+    ("KP_Left", 0xFF96),
+    ("KP_Up", 0xFF97),
+    ("KP_Right", 0xFF98),
+    ("KP_Down", 0xFF99),
+    ("KP_Prior", 0xFF9A),
+    ("KP_Page_Up", 0xFF9A),
+    ("KP_Next", 0xFF9B),
+    ("KP_Page_Down", 0xFF9B),
+    ("KP_End", 0xFF9C),
+    ("KP_Begin", 0xFF9D),
+    ("KP_Insert", 0xFF9E),
+    ("KP_Delete", 0xFF9F),
+    ("KP_Multiply", 0xFFAA),
+    ("KP_Add", 0xFFAB),
+    ("KP_Separator", 0xFFAC),
+    ("KP_Subtract", 0xFFAD),
+    ("KP_Decimal", 0xFFAE),
+    ("KP_Divide", 0xFFAF),
+    ("KP_0", 0xFFB0),
+    ("KP_1", 0xFFB1),
+    ("KP_2", 0xFFB2),
+    ("KP_3", 0xFFB3),
+    ("KP_4", 0xFFB4),
+    ("KP_5", 0xFFB5),
+    ("KP_6", 0xFFB6),
+    ("KP_7", 0xFFB7),
+    ("KP_8", 0xFFB8),
+    ("KP_9", 0xFFB9),
+    ("KP_Equal", 0xFFBD),
+    ("Shift_L", 0xFFE1),
+    ("Shift_R", 0xFFE2),
+    ("Control_L", 0xFFE3),
+    ("Control_R", 0xFFE4),
+    ("Caps_Lock", 0xFFE5),
+    ("Shift_Lock", 0xFFE6),
+    ("Meta_L", 0xFFE7),
+    ("Meta_R", 0xFFE8),
+    ("Alt_L", 0xFFE9),
+    ("Alt_R", 0xFFEA),
+    ("Super_L", 0xFFEB),
+    ("Super_R", 0xFFEC),
+    ("Hyper_L", 0xFFED),
+    ("Hyper_R", 0xFFEE),
+    ("Delete", 0xFFFF),
+    ("ISO_Lock", 0xFE01),
+    ("ISO_Level2_Latch", 0xFE02),
+    ("ISO_Level3_Shift", 0xFE03),
+    ("ISO_Level3_Latch", 0xFE04),
+    ("ISO_Level3_Lock", 0xFE05),
+    ("ISO_Group_Latch", 0xFE06),
+    ("ISO_Group_Lock", 0xFE07),
+    ("ISO_Next_Group", 0xFE08),
+    ("ISO_Next_Group_Lock", 0xFE09),
+    ("ISO_Prev_Group", 0xFE0A),
+    ("ISO_Prev_Group_Lock", 0xFE0B),
+    ("ISO_First_Group", 0xFE0C),
+    ("ISO_First_Group_Lock", 0xFE0D),
+    ("ISO_Last_Group", 0xFE0E),
+    ("ISO_Last_Group_Lock", 0xFE0F),
+    ("ISO_Level5_Shift", 0xFE11),
+    ("ISO_Level5_Latch", 0xFE12),
+    ("ISO_Level5_Lock", 0xFE13),
+    ("ISO_Left_Tab", 0xFE20),
+    ("dead_grave", 0xFE50),
+    ("dead_acute", 0xFE51),
+    ("dead_circumflex", 0xFE52),
+    ("dead_tilde", 0xFE53),
+    ("dead_macron", 0xFE54),
+    ("dead_breve", 0xFE55),
+    ("dead_abovedot", 0xFE56),
+    ("dead_diaeresis", 0xFE57),
+    ("dead_abovering", 0xFE58),
+    ("dead_doubleacute", 0xFE59),
+    ("dead_caron", 0xFE5A),
+    ("dead_cedilla", 0xFE5B),
+    ("dead_ogonek", 0xFE5C),
+    ("dead_iota", 0xFE5D),
+    ("dead_belowdot", 0xFE60),
+    ("dead_hook", 0xFE61),
+    ("dead_horn", 0xFE62),
+    ("dead_stroke", 0xFE63),
+    ("dead_currency", 0xFE6F),
+    ("dead_greek", 0xFE8C),
+    ("XF86AudioLowerVolume", 0x1008_FF11),
+    ("XF86AudioMute", 0x1008_FF12),
+    ("XF86AudioRaiseVolume", 0x1008_FF13),
+    ("XF86AudioPlay", 0x1008_FF14),
+    ("XF86AudioStop", 0x1008_FF15),
+    ("XF86AudioPrev", 0x1008_FF16),
+    ("XF86AudioNext", 0x1008_FF17),
+];
+
+/// The character a keysym types, if it types one. Control characters like backspace's don't count.
+pub fn to_char(keysym: u32) -> Option<char> {
+    let code_point = match keysym {
+        0x20..=0x7E | 0xA0..=0xFF => keysym,
+        EURO_SIGN => '€'.into(),
+        // the keypad's printable keys
+        0xFF80 => ' '.into(),
+        0xFFAA..=0xFFB9 => keysym - 0xFFAA + u32::from(b'*'),
+        0xFFBD => '='.into(),
+        _ if keysym > UNICODE_OFFSET + 0x9F => keysym - UNICODE_OFFSET,
+        _ => return None,
+    };
+    char::from_u32(code_point).filter(|c| !c.is_control())
+}
+
+/// The keysym for a character: the character itself for Latin-1, or a Unicode keysym.
+pub fn from_char(c: char) -> u32 {
+    match u32::from(c) {
+        code_point @ (0x20..=0x7E | 0xA0..=0xFF) => code_point,
+        code_point => code_point + UNICODE_OFFSET,
+    }
+}
+
+/// The keysym with a name from `X11/keysymdef.h`, like `udiaeresis` or `ISO_Level3_Shift`.
+///
+/// Unicode keysyms can be named as `U` and the code point in hex (`U20AC`), and any keysym can be
+/// given as a hex number (`0x1008FF11`). Only the names of Latin-1 keysyms and of the other
+/// keysyms that keymaps commonly use are known, since the other legacy keysyms aren't supported
+/// anyway.
+pub fn from_name(name: &str) -> Option<u32> {
+    if let Some(i) = ASCII_NAMES.iter().position(|&n| n == name) {
+        return Some(0x20 + i as u32);
+    }
+    if let Some(i) = LATIN_1_NAMES.iter().position(|&n| n == name) {
+        return Some(0xA0 + i as u32);
+    }
+    if let Some(&(_, keysym)) = OTHER_NAMES.iter().find(|&&(n, _)| n == name) {
+        return Some(keysym);
+    }
+    if let Some(hex) = name.strip_prefix('U').filter(|hex| hex.len() >= 4) {
+        let c = char::from_u32(u32::from_str_radix(hex, 16).ok()?)?;
+        return Some(from_char(c));
+    }
+    if let Some(hex) = name.strip_prefix("0x") {
+        return u32::from_str_radix(hex, 16).ok();
+    }
+    match name.strip_prefix('F')?.parse::<u32>() {
+        Ok(n @ 1..=35) => Some(F1 + n - 1),
+        _ => None,
+    }
+}
+
+/// What a keysym means.
+pub fn to_key(keysym: u32) -> Key {
+    if let Some(c) = to_char(keysym) {
+        return Key::Character(c);
+    }
+    if let Some(&(_, named)) = NAMED.iter().find(|&&(k, _)| k == keysym) {
+        return Key::Named(named);
+    }
+    if let Some(&(_, accent)) = DEAD.iter().find(|&&(k, _)| k == keysym) {
+        return Key::Dead(accent);
+    }
+    match keysym {
+        F1..=F35 if keysym - F1 < 24 => Key::Named(NamedKey::F((keysym - F1 + 1) as u8)),
+        _ => Key::Unidentified,
+    }
+}
+
+/// Whether a keysym is on the keypad, where num lock swaps the shift levels.
+pub const fn is_keypad(keysym: u32) -> bool {
+    matches!(keysym, 0xFF80..=0xFFBD)
+}
+
+/// The upper case version of a keysym that types a letter, or the keysym itself.
+pub fn to_upper(keysym: u32) -> u32 {
+    map_case(keysym, char::to_uppercase)
+}
+
+/// The lower case version of a keysym that types a letter, or the keysym itself.
+pub fn to_lower(keysym: u32) -> u32 {
+    map_case(keysym, char::to_lowercase)
+}
+
+fn map_case<I: Iterator<Item = char>>(keysym: u32, f: impl Fn(char) -> I) -> u32 {
+    let Some(c) = to_char(keysym) else {
+        return keysym;
+    };
+    let mut mapped = f(c);
+    match (mapped.next(), mapped.next()) {
+        // `ß` is `SS` in upper case, which isn't one keysym
+        (Some(mapped), None) if mapped != c => from_char(mapped),
+        _ => keysym,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn characters() {
+        assert_eq!(to_char(u32::from(b'a')), Some('a'));
+        assert_eq!(to_char(0xE4), Some('ä'));
+        assert_eq!(to_char(0x0100_20AC), Some('€'));
+        assert_eq!(to_char(EURO_SIGN), Some('€'));
+        assert_eq!(to_char(0x0100_0416), Some('Ж'));
+        assert_eq!(to_char(0xFFB7), Some('7'));
+        assert_eq!(to_char(0xFFAB), Some('+'));
+        // not printable
+        assert_eq!(to_char(0xFF08), None);
+        assert_eq!(to_char(0x0100_0008), None);
+        assert_eq!(to_char(0x1F), None);
+
+        assert_eq!(from_char('a'), 0x61);
+        assert_eq!(from_char('é'), 0xE9);
+        assert_eq!(from_char('Ж'), 0x0100_0416);
+    }
+
+    #[test]
+    fn keys() {
+        assert_eq!(to_key(0x7A), Key::Character('z'));
+        assert_eq!(to_key(0xFF0D), Key::Named(NamedKey::Enter));
+        assert_eq!(to_key(0xFE03), Key::Named(NamedKey::AltGraph));
+        assert_eq!(to_key(0xFFC8), Key::Named(NamedKey::F(11)));
+        assert_eq!(to_key(0xFFD6), Key::Unidentified); // F25
+        assert_eq!(to_key(0xFE52), Key::Dead('^'));
+        assert_eq!(to_key(NO_SYMBOL), Key::Unidentified);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(from_name("a"), Some(0x61));
+        assert_eq!(from_name("Z"), Some(0x5A));
+        assert_eq!(from_name("5"), Some(0x35));
+        assert_eq!(from_name("asciitilde"), Some(0x7E));
+        assert_eq!(from_name("udiaeresis"), Some(0xFC));
+        assert_eq!(from_name("ydiaeresis"), Some(0xFF));
+        assert_eq!(from_name("EuroSign"), Some(EURO_SIGN));
+        assert_eq!(from_name("ISO_Level3_Shift"), Some(0xFE03));
+        assert_eq!(from_name("F11"), Some(0xFFC8));
+        assert_eq!(from_name("U1E9E"), Some(0x0100_1E9E));
+        assert_eq!(from_name("U00E9"), Some(0xE9));
+        assert_eq!(from_name("0x1008FF11"), Some(0x1008_FF11));
+        assert_eq!(from_name("NoSymbol"), Some(NO_SYMBOL));
+        // `U` on its own is a letter, and these aren't anything
+        assert_eq!(from_name("U"), Some(0x55));
+        assert_eq!(from_name("F36"), None);
+        assert_eq!(from_name("UDEAD"), None);
+        assert_eq!(from_name("lstroke"), None);
+    }
+
+    #[test]
+    fn case() {
+        assert_eq!(to_upper(u32::from(b'q')), u32::from(b'Q'));
+        assert_eq!(to_lower(u32::from(b'Q')), u32::from(b'q'));
+        assert_eq!(to_upper(0xE9), 0xC9); // é
+        assert_eq!(to_upper(0x0100_0436), 0x0100_0416); // ж
+        assert_eq!(to_upper(0xDF), 0xDF); // ß
+        assert_eq!(to_upper(u32::from(b'1')), u32::from(b'1'));
+        assert_eq!(to_upper(0xFF0D), 0xFF0D);
+        assert!(is_keypad(0xFFB0) && !is_keypad(0x30));
+    }
+}
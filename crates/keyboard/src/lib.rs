@@ -0,0 +1,55 @@
+//! A keyboard model shared by the platform crates, in plain Rust so it's available (and tested)
+//! everywhere.
+//!
+//! A key press is described twice:
+//!
+//! - Its [`KeyCode`] is the physical key, named after what's printed on it on a US keyboard. It
+//!   doesn't change with the layout, so it's what WASD-style controls should use. Platforms report
+//!   physical keys as scan codes (Win32) or keycodes (evdev and X11), which [`scan_codes`] turns
+//!   into [`KeyCode`]s.
+//! - Its [`Key`] is the logical key: what the key means in the current layout, like
+//!   [`Key::Character('z')`](Key::Character) for the key in the `KeyY` position on a German
+//!   keyboard. That's what shortcuts should use.
+//!
+//! Text isn't part of either. A key press can type nothing (a dead key, a shortcut), and text can
+//! come without any keys at all (an input method), so text is its own stream of committed
+//! characters, which the platform crates report separately from key events.
+
+pub mod key;
+pub mod key_code;
+pub mod keysym;
+pub mod modifiers;
+pub mod scan_codes;
+pub mod xkb;
+
+pub use key::{Key, NamedKey};
+pub use key_code::KeyCode;
+pub use modifiers::{KeyboardState, Modifiers};
+
+/// Whether a key went down or up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyState {
+    Pressed,
+    Released,
+}
+
+impl KeyState {
+    pub const fn is_pressed(self) -> bool {
+        matches!(self, Self::Pressed)
+    }
+}
+
+/// A key was pressed or released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
+    /// The key's position on the keyboard.
+    pub physical_key: KeyCode,
+    /// What the key means in the current layout, with the modifiers that are held.
+    pub logical_key: Key,
+    pub state: KeyState,
+    /// Whether this is an auto-repeat of a key that's being held down. Releases are never repeats.
+    pub repeat: bool,
+    /// The modifiers after this event, e.g. including [`Modifiers::SHIFT`] when this is a shift
+    /// key being pressed.
+    pub modifiers: Modifiers,
+}
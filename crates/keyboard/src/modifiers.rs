@@ -0,0 +1,195 @@
+//! Keeping track of which keys are held, and so which modifiers are active.
+
+use core::ops;
+
+use crate::{KeyCode, KeyState};
+
+/// A set of modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    pub const NONE: Self = Self(0);
+    pub const SHIFT: Self = Self(1 << 0);
+    pub const CONTROL: Self = Self(1 << 1);
+    pub const ALT: Self = Self(1 << 2);
+    pub const SUPER: Self = Self(1 << 3);
+    /// Caps lock is on, not just held.
+    pub const CAPS_LOCK: Self = Self(1 << 4);
+    /// Num lock is on, not just held.
+    pub const NUM_LOCK: Self = Self(1 << 5);
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Returns `true` if every modifier in `other` is also in `self`.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if there aren't any modifiers.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl ops::BitOr for Modifiers {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl ops::BitOrAssign for Modifiers {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Which keys are held, and whether the lock keys are on, built up from key events.
+///
+/// Platforms only send key events to the focused window, so call
+/// [`release_all`](Self::release_all) when the window loses the focus, or keys released elsewhere
+/// will look held forever. The lock keys can be toggled elsewhere too, so sync them with
+/// [`set_locks`](Self::set_locks) when the platform says what they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct KeyboardState {
+    /// A bit for each [`KeyCode`] that's held, by discriminant.
+    pressed: [u64; 4],
+    caps_lock: bool,
+    num_lock: bool,
+}
+
+impl KeyboardState {
+    pub const fn new() -> Self {
+        Self {
+            pressed: [0; 4],
+            caps_lock: false,
+            num_lock: false,
+        }
+    }
+
+    /// Updates the state for a key event.
+    ///
+    /// **Returns:** Whether the event is an auto-repeat, i.e. a press of a key that's already
+    /// held. Unidentified keys are never repeats, since they can't be told apart.
+    pub fn update(&mut self, code: KeyCode, state: KeyState) -> bool {
+        if code == KeyCode::Unidentified {
+            return false;
+        }
+        let (word, bit) = (code as usize / 64, 1 << (code as usize % 64));
+        let repeat = state.is_pressed() && self.pressed[word] & bit != 0;
+        match state {
+            KeyState::Pressed => self.pressed[word] |= bit,
+            KeyState::Released => self.pressed[word] &= !bit,
+        }
+
+        if state.is_pressed() && !repeat {
+            match code {
+                KeyCode::CapsLock => self.caps_lock = !self.caps_lock,
+                KeyCode::NumLock => self.num_lock = !self.num_lock,
+                _ => {}
+            }
+        }
+        repeat
+    }
+
+    pub fn is_pressed(&self, code: KeyCode) -> bool {
+        code != KeyCode::Unidentified
+            && self.pressed[code as usize / 64] & (1 << (code as usize % 64)) != 0
+    }
+
+    /// Forgets about every held key, but not about the lock keys being on.
+    pub fn release_all(&mut self) {
+        self.pressed = [0; 4];
+    }
+
+    pub fn set_locks(&mut self, caps_lock: bool, num_lock: bool) {
+        self.caps_lock = caps_lock;
+        self.num_lock = num_lock;
+    }
+
+    /// The modifiers that are active. Either key of a pair (e.g. left or right shift) counts.
+    pub fn modifiers(&self) -> Modifiers {
+        let mut modifiers = Modifiers::NONE;
+        for (keys, modifier) in [
+            ([KeyCode::ShiftLeft, KeyCode::ShiftRight], Modifiers::SHIFT),
+            (
+                [KeyCode::ControlLeft, KeyCode::ControlRight],
+                Modifiers::CONTROL,
+            ),
+            ([KeyCode::AltLeft, KeyCode::AltRight], Modifiers::ALT),
+            ([KeyCode::SuperLeft, KeyCode::SuperRight], Modifiers::SUPER),
+        ] {
+            if keys.iter().any(|&key| self.is_pressed(key)) {
+                modifiers |= modifier;
+            }
+        }
+        if self.caps_lock {
+            modifiers |= Modifiers::CAPS_LOCK;
+        }
+        if self.num_lock {
+            modifiers |= Modifiers::NUM_LOCK;
+        }
+        modifiers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use KeyState::*;
+
+    #[test]
+    fn modifiers() {
+        let mut state = KeyboardState::new();
+        state.update(KeyCode::ShiftLeft, Pressed);
+        state.update(KeyCode::ControlRight, Pressed);
+        assert_eq!(state.modifiers(), Modifiers::SHIFT | Modifiers::CONTROL);
+
+        // both shift keys down, then one up, is still shifted
+        state.update(KeyCode::ShiftRight, Pressed);
+        state.update(KeyCode::ShiftLeft, Released);
+        assert!(state.modifiers().contains(Modifiers::SHIFT));
+        state.update(KeyCode::ShiftRight, Released);
+        assert_eq!(state.modifiers(), Modifiers::CONTROL);
+
+        state.release_all();
+        assert!(state.modifiers().is_empty());
+    }
+
+    #[test]
+    fn repeats() {
+        let mut state = KeyboardState::new();
+        assert!(!state.update(KeyCode::KeyA, Pressed));
+        assert!(state.update(KeyCode::KeyA, Pressed));
+        assert!(state.is_pressed(KeyCode::KeyA));
+        assert!(!state.update(KeyCode::KeyA, Released));
+        assert!(!state.update(KeyCode::KeyA, Pressed));
+        // the last key code, in the last word
+        assert!(!state.update(KeyCode::MediaTrackPrevious, Pressed));
+        assert!(state.update(KeyCode::MediaTrackPrevious, Pressed));
+
+        assert!(!state.update(KeyCode::Unidentified, Pressed));
+        assert!(!state.update(KeyCode::Unidentified, Pressed));
+        assert!(!state.is_pressed(KeyCode::Unidentified));
+    }
+
+    #[test]
+    fn locks() {
+        let mut state = KeyboardState::new();
+        state.update(KeyCode::CapsLock, Pressed);
+        // repeats don't toggle it back
+        state.update(KeyCode::CapsLock, Pressed);
+        state.update(KeyCode::CapsLock, Released);
+        assert_eq!(state.modifiers(), Modifiers::CAPS_LOCK);
+        state.update(KeyCode::CapsLock, Pressed);
+        assert!(state.modifiers().is_empty());
+
+        state.set_locks(false, true);
+        state.release_all();
+        assert_eq!(state.modifiers(), Modifiers::NUM_LOCK);
+    }
+}
@@ -0,0 +1,241 @@
+//! Turning the platforms' physical key numbers into [`KeyCode`]s, and back.
+//!
+//! - Win32 gives PS/2 "set 1" scan codes in bits 16 to 23 of a key message's `lParam`, with bit 24
+//!   set for keys that are sent with an `0xE0` prefix. Those are written as `0xE0__` here, e.g.
+//!   `0xE04B` for the left arrow.
+//! - Linux gives evdev keycodes (the `KEY_*` constants in `linux/input-event-codes.h`), which
+//!   X11 and Wayland keycodes are 8 more than.
+//!
+//! Windows sends Pause as `0x45`, the scan code that "set 1" gives num lock, and num lock as
+//! `0xE045` instead.
+
+use crate::KeyCode::{self, *};
+
+/// The Win32 scan code and evdev keycode of each key, or `0` where a platform doesn't have one.
+#[rustfmt::skip]
+const TABLE: &[(KeyCode, u16, u16)] = &[
+    (Escape, 0x0001, 1),
+    (Digit1, 0x0002, 2),
+    (Digit2, 0x0003, 3),
+    (Digit3, 0x0004, 4),
+    (Digit4, 0x0005, 5),
+    (Digit5, 0x0006, 6),
+    (Digit6, 0x0007, 7),
+    (Digit7, 0x0008, 8),
+    (Digit8, 0x0009, 9),
+    (Digit9, 0x000A, 10),
+    (Digit0, 0x000B, 11),
+    (Minus, 0x000C, 12),
+    (Equal, 0x000D, 13),
+    (Backspace, 0x000E, 14),
+    (Tab, 0x000F, 15),
+    (KeyQ, 0x0010, 16),
+    (KeyW, 0x0011, 17),
+    (KeyE, 0x0012, 18),
+    (KeyR, 0x0013, 19),
+    (KeyT, 0x0014, 20),
+    (KeyY, 0x0015, 21),
+    (KeyU, 0x0016, 22),
+    (KeyI, 0x0017, 23),
+    (KeyO, 0x0018, 24),
+    (KeyP, 0x0019, 25),
+    (BracketLeft, 0x001A, 26),
+    (BracketRight, 0x001B, 27),
+    (Enter, 0x001C, 28),
+    (ControlLeft, 0x001D, 29),
+    (KeyA, 0x001E, 30),
+    (KeyS, 0x001F, 31),
+    (KeyD, 0x0020, 32),
+    (KeyF, 0x0021, 33),
+    (KeyG, 0x0022, 34),
+    (KeyH, 0x0023, 35),
+    (KeyJ, 0x0024, 36),
+    (KeyK, 0x0025, 37),
+    (KeyL, 0x0026, 38),
+    (Semicolon, 0x0027, 39),
+    (Quote, 0x0028, 40),
+    (Backquote, 0x0029, 41),
+    (ShiftLeft, 0x002A, 42),
+    (Backslash, 0x002B, 43),
+    (KeyZ, 0x002C, 44),
+    (KeyX, 0x002D, 45),
+    (KeyC, 0x002E, 46),
+    (KeyV, 0x002F, 47),
+    (KeyB, 0x0030, 48),
+    (KeyN, 0x0031, 49),
+    (KeyM, 0x0032, 50),
+    (Comma, 0x0033, 51),
+    (Period, 0x0034, 52),
+    (Slash, 0x0035, 53),
+    (ShiftRight, 0x0036, 54),
+    (NumpadMultiply, 0x0037, 55),
+    (AltLeft, 0x0038, 56),
+    (Space, 0x0039, 57),
+    (CapsLock, 0x003A, 58),
+    (F1, 0x003B, 59),
+    (F2, 0x003C, 60),
+    (F3, 0x003D, 61),
+    (F4, 0x003E, 62),
+    (F5, 0x003F, 63),
+    (F6, 0x0040, 64),
+    (F7, 0x0041, 65),
+    (F8, 0x0042, 66),
+    (F9, 0x0043, 67),
+    (F10, 0x0044, 68),
+    (NumLock, 0xE045, 69),
+    (ScrollLock, 0x0046, 70),
+    (Numpad7, 0x0047, 71),
+    (Numpad8, 0x0048, 72),
+    (Numpad9, 0x0049, 73),
+    (NumpadSubtract, 0x004A, 74),
+    (Numpad4, 0x004B, 75),
+    (Numpad5, 0x004C, 76),
+    (Numpad6, 0x004D, 77),
+    (NumpadAdd, 0x004E, 78),
+    (Numpad1, 0x004F, 79),
+    (Numpad2, 0x0050, 80),
+    (Numpad3, 0x0051, 81),
+    (Numpad0, 0x0052, 82),
+    (NumpadDecimal, 0x0053, 83),
+    (IntlBackslash, 0x0056, 86),
+    (F11, 0x0057, 87),
+    (F12, 0x0058, 88),
+    (IntlRo, 0x0073, 89),
+    (Convert, 0x0079, 92),
+    (KanaMode, 0x0070, 93),
+    (NonConvert, 0x007B, 94),
+    (NumpadEnter, 0xE01C, 96),
+    (ControlRight, 0xE01D, 97),
+    (NumpadDivide, 0xE035, 98),
+    (PrintScreen, 0xE037, 99),
+    (AltRight, 0xE038, 100),
+    (Home, 0xE047, 102),
+    (ArrowUp, 0xE048, 103),
+    (PageUp, 0xE049, 104),
+    (ArrowLeft, 0xE04B, 105),
+    (ArrowRight, 0xE04D, 106),
+    (End, 0xE04F, 107),
+    (ArrowDown, 0xE050, 108),
+    (PageDown, 0xE051, 109),
+    (Insert, 0xE052, 110),
+    (Delete, 0xE053, 111),
+    (AudioVolumeMute, 0xE020, 113),
+    (AudioVolumeDown, 0xE02E, 114),
+    (AudioVolumeUp, 0xE030, 115),
+    (NumpadEqual, 0x0059, 117),
+    (Pause, 0x0045, 119),
+    (NumpadComma, 0x007E, 121),
+    (Lang1, 0x0072, 122),
+    (Lang2, 0x0071, 123),
+    (IntlYen, 0x007D, 124),
+    (SuperLeft, 0xE05B, 125),
+    (SuperRight, 0xE05C, 126),
+    (ContextMenu, 0xE05D, 127),
+    (MediaTrackNext, 0xE019, 163),
+    (MediaPlayPause, 0xE022, 164),
+    (MediaTrackPrevious, 0xE010, 165),
+    (MediaStop, 0xE024, 166),
+    (F13, 0x0064, 183),
+    (F14, 0x0065, 184),
+    (F15, 0x0066, 185),
+    (F16, 0x0067, 186),
+    (F17, 0x0068, 187),
+    (F18, 0x0069, 188),
+    (F19, 0x006A, 189),
+    (F20, 0x006B, 190),
+    (F21, 0x006C, 191),
+    (F22, 0x006D, 192),
+    (F23, 0x006E, 193),
+    (F24, 0x0076, 194),
+];
+
+/// The difference between X11 (and Wayland's XKB) keycodes and evdev keycodes.
+pub const EVDEV_OFFSET: u32 = 8;
+
+/// The key with a Win32 scan code, with `0xE000` added for extended keys.
+pub fn from_win32_scan_code(scan_code: u16) -> KeyCode {
+    TABLE
+        .iter()
+        .find(|&&(_, win32, _)| win32 == scan_code)
+        .map_or(Unidentified, |&(code, _, _)| code)
+}
+
+/// The Win32 scan code of a key, with `0xE000` added for extended keys.
+pub fn to_win32_scan_code(code: KeyCode) -> Option<u16> {
+    TABLE
+        .iter()
+        .find(|&&(c, win32, _)| c == code && win32 != 0)
+        .map(|&(_, win32, _)| win32)
+}
+
+/// The key with an evdev keycode.
+pub fn from_evdev(keycode: u32) -> KeyCode {
+    TABLE
+        .iter()
+        .find(|&&(_, _, evdev)| evdev != 0 && u32::from(evdev) == keycode)
+        .map_or(Unidentified, |&(code, _, _)| code)
+}
+
+pub fn to_evdev(code: KeyCode) -> Option<u32> {
+    TABLE
+        .iter()
+        .find(|&&(c, _, evdev)| c == code && evdev != 0)
+        .map(|&(_, _, evdev)| evdev.into())
+}
+
+/// The key with an X11 or XKB keycode.
+pub fn from_x11_keycode(keycode: u32) -> KeyCode {
+    keycode
+        .checked_sub(EVDEV_OFFSET)
+        .map_or(Unidentified, from_evdev)
+}
+
+pub fn to_x11_keycode(code: KeyCode) -> Option<u32> {
+    to_evdev(code).map(|evdev| evdev + EVDEV_OFFSET)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_key_has_codes() {
+        for &code in KeyCode::ALL {
+            let win32 = to_win32_scan_code(code).unwrap_or_else(|| panic!("{code:?}"));
+            let evdev = to_evdev(code).unwrap_or_else(|| panic!("{code:?}"));
+            // and the codes are unique, so they round trip
+            assert_eq!(from_win32_scan_code(win32), code);
+            assert_eq!(from_evdev(evdev), code);
+        }
+        assert_eq!(TABLE.len(), KeyCode::ALL.len());
+    }
+
+    #[test]
+    fn win32() {
+        assert_eq!(from_win32_scan_code(0x1E), KeyA);
+        assert_eq!(from_win32_scan_code(0x1C), Enter);
+        assert_eq!(from_win32_scan_code(0xE01C), NumpadEnter);
+        assert_eq!(from_win32_scan_code(0x4B), Numpad4);
+        assert_eq!(from_win32_scan_code(0xE04B), ArrowLeft);
+        assert_eq!(from_win32_scan_code(0x45), Pause);
+        assert_eq!(from_win32_scan_code(0xE045), NumLock);
+        assert_eq!(from_win32_scan_code(0), Unidentified);
+        assert_eq!(from_win32_scan_code(0x7F), Unidentified);
+    }
+
+    #[test]
+    fn evdev_and_x11() {
+        assert_eq!(from_evdev(30), KeyA);
+        assert_eq!(from_evdev(0), Unidentified);
+        assert_eq!(from_evdev(0x2FF), Unidentified);
+        // X11's well-known keycodes
+        assert_eq!(from_x11_keycode(9), Escape);
+        assert_eq!(from_x11_keycode(38), KeyA);
+        assert_eq!(from_x11_keycode(65), Space);
+        assert_eq!(from_x11_keycode(113), ArrowLeft);
+        assert_eq!(from_x11_keycode(133), SuperLeft);
+        assert_eq!(from_x11_keycode(3), Unidentified);
+        assert_eq!(to_x11_keycode(KeyW), Some(25));
+        assert_eq!(to_x11_keycode(Unidentified), None);
+    }
+}
@@ -0,0 +1,1052 @@
+//! Working out what the statements of a parsed keymap mean.
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::{
+    parser::{parse, Decl, Expr, SectionKind},
+    Error,
+};
+use crate::keysym;
+
+/// The real modifiers, as bits in a modifier mask. Virtual modifiers like `Alt` and `LevelThree`
+/// are each one or more of these, which [`Keymap::mod_mask`] looks up.
+pub mod mod_masks {
+    pub const SHIFT: u8 = 1 << 0;
+    pub const LOCK: u8 = 1 << 1;
+    pub const CONTROL: u8 = 1 << 2;
+    pub const MOD1: u8 = 1 << 3;
+    pub const MOD2: u8 = 1 << 4;
+    pub const MOD3: u8 = 1 << 5;
+    pub const MOD4: u8 = 1 << 6;
+    pub const MOD5: u8 = 1 << 7;
+}
+
+const REAL_MODS: [&str; 8] = [
+    "Shift", "Lock", "Control", "Mod1", "Mod2", "Mod3", "Mod4", "Mod5",
+];
+
+/// A modifier mask before virtual modifiers are resolved: the real modifiers are the low byte, and
+/// virtual modifier `i` is bit `8 + i`.
+type ModMask = u32;
+
+/// In an action's modifiers, whichever real modifiers its key is in the `modifier_map` of.
+const MOD_MAP_MODS: ModMask = 1 << 31;
+
+/// What pressing a key does to the modifiers and the group, besides its keysym.
+///
+/// While the keymap is being built, the modifiers are a [`ModMask`]. Once it's built, they're
+/// only real modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Action {
+    None,
+    /// Holds modifiers down while the key is. `clear_locks` unlocks them if nothing else is
+    /// pressed before the key is released.
+    SetMods {
+        mods: ModMask,
+        clear_locks: bool,
+    },
+    /// Like [`SetMods`](Self::SetMods), but if nothing else is pressed before the key is released
+    /// the modifiers stay on for the next key press. `latch_to_lock` locks them if they were
+    /// already latched.
+    LatchMods {
+        mods: ModMask,
+        clear_locks: bool,
+        latch_to_lock: bool,
+    },
+    /// Holds modifiers down while the key is, and toggles whether they're locked.
+    LockMods {
+        mods: ModMask,
+    },
+    /// Changes the group while the key is held. Groups count from 0.
+    SetGroup {
+        group: i32,
+        absolute: bool,
+        clear_locks: bool,
+    },
+    /// Like [`SetGroup`](Self::SetGroup), but the change stays for the next key press if nothing
+    /// else is pressed before the key is released.
+    LatchGroup {
+        group: i32,
+        absolute: bool,
+    },
+    /// Changes the locked group.
+    LockGroup {
+        group: i32,
+        absolute: bool,
+    },
+}
+
+/// Which modifiers pick which shift level, for the keys that have the type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct KeyType {
+    /// The modifiers the type looks at. Others don't change the level.
+    pub mods: u8,
+    pub entries: Vec<TypeEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct TypeEntry {
+    /// The modifiers, out of the type's, that have to be active (and the only ones that are).
+    pub mods: u8,
+    pub level: usize,
+    /// Modifiers that aren't used up by picking the level, so caps lock can still capitalise it.
+    pub preserve: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Key {
+    pub groups: Vec<Group>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Group {
+    /// An index into [`Keymap::types`].
+    pub key_type: usize,
+    pub levels: Vec<Level>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Level {
+    pub keysym: u32,
+    pub action: Action,
+}
+
+/// A keymap, with its virtual modifiers resolved and the actions of its interprets given to the
+/// keys they apply to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    min_keycode: u32,
+    max_keycode: u32,
+    key_names: HashMap<String, u32>,
+    pub(super) keys: BTreeMap<u32, Key>,
+    pub(super) types: Vec<KeyType>,
+    /// The virtual modifiers' names, and the real modifiers each of them is.
+    virtual_mods: Vec<(String, u8)>,
+    group_names: Vec<String>,
+    pub(super) num_groups: usize,
+}
+
+impl Keymap {
+    /// Parses a complete keymap, like the ones Wayland compositors send.
+    ///
+    /// Keysyms with names that aren't known (see [`keysym::from_name`]) are `NoSymbol`, and actions
+    /// other than the ones that change modifiers or the group do nothing, so that a keymap using
+    /// them still works for everything else.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut sections = parse(text)?;
+        // the sections can come in any order, but each needs the ones before it in this one
+        sections.sort_by_key(|section| section.kind);
+
+        let mut builder = Builder::default();
+        for section in &sections {
+            for decl in &section.decls {
+                match section.kind {
+                    SectionKind::Keycodes => builder.keycodes_decl(decl)?,
+                    SectionKind::Types => builder.types_decl(decl)?,
+                    SectionKind::Compat => builder.compat_decl(decl)?,
+                    SectionKind::Symbols => builder.symbols_decl(decl)?,
+                }
+            }
+        }
+        builder.build()
+    }
+
+    pub fn min_keycode(&self) -> u32 {
+        self.min_keycode
+    }
+
+    pub fn max_keycode(&self) -> u32 {
+        self.max_keycode
+    }
+
+    /// The keycode of a key name (without its angle brackets) or alias, like `AE01`.
+    pub fn keycode(&self, name: &str) -> Option<u32> {
+        self.key_names.get(name).copied()
+    }
+
+    /// The names of the groups, like `English (US)`, by group.
+    pub fn group_names(&self) -> &[String] {
+        &self.group_names
+    }
+
+    /// How many groups (layouts) the keymap has. Keys can have fewer.
+    pub fn num_groups(&self) -> usize {
+        self.num_groups
+    }
+
+    /// The real modifiers that a modifier is, by name, like [`mod_masks::MOD5`] for `LevelThree`
+    /// in most keymaps.
+    pub fn mod_mask(&self, name: &str) -> Option<u8> {
+        if let Some(i) = REAL_MODS.iter().position(|m| m.eq_ignore_ascii_case(name)) {
+            return Some(1 << i);
+        }
+        self.virtual_mods
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|&(_, mask)| mask)
+    }
+
+    /// The keysym at a level of a group of a key, if there is one. Groups and levels count from 0.
+    pub fn keysym(&self, keycode: u32, group: usize, level: usize) -> Option<u32> {
+        let levels = &self.keys.get(&keycode)?.groups.get(group)?.levels;
+        levels
+            .get(level)
+            .map(|level| level.keysym)
+            .filter(|&keysym| keysym != keysym::NO_SYMBOL)
+    }
+}
+
+/// How an `interpret` compares its modifiers with the ones a key is mapped to. They're in the
+/// order in which interprets are tried, most specific first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Condition {
+    Exactly,
+    AllOf,
+    NoneOf,
+    AnyOf,
+    AnyOfOrNone,
+}
+
+impl Condition {
+    fn matches(self, interpret_mods: u8, key_mods: u8) -> bool {
+        match self {
+            Self::Exactly => interpret_mods == key_mods,
+            Self::AllOf => interpret_mods & key_mods == interpret_mods,
+            Self::NoneOf => interpret_mods & key_mods == 0,
+            Self::AnyOf => interpret_mods & key_mods != 0,
+            Self::AnyOfOrNone => key_mods == 0 || interpret_mods & key_mods != 0,
+        }
+    }
+}
+
+/// An `interpret`, which gives an action to the keys with a keysym.
+#[derive(Debug, Clone)]
+struct Interpret {
+    /// `None` for `Any`.
+    keysym: Option<u32>,
+    condition: Condition,
+    mods: u8,
+    action: Action,
+    virtual_mod: Option<usize>,
+    /// Whether only a key's first level is compared with its modifiers.
+    level_one_only: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct TypeDef {
+    mods: ModMask,
+    /// The modifiers, level and preserved modifiers of each `map` entry.
+    entries: Vec<(ModMask, usize, ModMask)>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct KeyDef {
+    line: u32,
+    /// The type of every group, and then of each group.
+    default_type: Option<String>,
+    types: Vec<Option<String>>,
+    symbols: Vec<Vec<u32>>,
+    actions: Vec<Vec<Action>>,
+    virtual_mods: Option<ModMask>,
+}
+
+#[derive(Default)]
+struct Builder {
+    min_keycode: Option<u32>,
+    max_keycode: Option<u32>,
+    key_names: HashMap<String, u32>,
+    /// The virtual modifiers, and the real modifiers they were declared to be.
+    virtual_mods: Vec<(String, ModMask)>,
+    types: Vec<(String, TypeDef)>,
+    interprets: Vec<Interpret>,
+    /// The default for interprets' `useModMapMods`.
+    level_one_only: bool,
+    keys: BTreeMap<u32, KeyDef>,
+    modifier_maps: Vec<(u32, u8, Vec<Expr>)>,
+    group_names: Vec<String>,
+}
+
+fn error(line: u32, message: impl Into<String>) -> Error {
+    Error {
+        line,
+        column: 1,
+        message: message.into(),
+    }
+}
+
+fn var_name(path: &[String]) -> String {
+    path.join(".").to_ascii_lowercase()
+}
+
+/// A boolean, like `True` or `no`.
+fn boolean(expr: &Expr, line: u32) -> Result<bool, Error> {
+    match expr {
+        Expr::Ident(name) => match name.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "on" => Ok(true),
+            "false" | "no" | "off" => Ok(false),
+            _ => Err(error(line, format!("`{name}` isn't true or false"))),
+        },
+        Expr::Not(inner) => Ok(!boolean(inner, line)?),
+        _ => Err(error(line, "expected true or false")),
+    }
+}
+
+/// A level or group number like `Level2`, `Group2` or `2`, counting from 0.
+fn ordinal(expr: &Expr, prefix: &str, line: u32) -> Result<usize, Error> {
+    let n = match expr {
+        Expr::Number(n) => Some(*n as usize),
+        Expr::Ident(name) => name
+            .get(..prefix.len())
+            .filter(|p| p.eq_ignore_ascii_case(prefix))
+            .and_then(|_| name[prefix.len()..].parse().ok()),
+        _ => None,
+    };
+    match n {
+        Some(n @ 1..) => Ok(n - 1),
+        _ => Err(error(line, format!("expected a {}", prefix.to_lowercase()))),
+    }
+}
+
+/// A keysym, by name or number. Numbers from 0 to 9 are the digits.
+fn keysym(expr: &Expr, line: u32) -> Result<u32, Error> {
+    match expr {
+        Expr::Ident(name) => Ok(keysym::from_name(name).unwrap_or(keysym::NO_SYMBOL)),
+        Expr::Number(n @ 0..=9) => Ok(u32::from(b'0') + n),
+        Expr::Number(n) => Ok(*n),
+        _ => Err(error(line, "expected a keysym")),
+    }
+}
+
+fn list(expr: &Expr, line: u32) -> Result<&[Expr], Error> {
+    match expr {
+        Expr::List(items) => Ok(items),
+        _ => Err(error(line, "expected a list")),
+    }
+}
+
+impl Builder {
+    fn keycodes_decl(&mut self, decl: &Decl) -> Result<(), Error> {
+        match decl {
+            Decl::Var {
+                line, path, value, ..
+            } => {
+                let value = match value {
+                    Expr::Number(n) => Some(*n),
+                    _ => None,
+                };
+                match var_name(path).as_str() {
+                    "minimum" => self.min_keycode = value,
+                    "maximum" => self.max_keycode = value,
+                    name => return Err(error(*line, format!("unknown setting `{name}`"))),
+                }
+            }
+            Decl::Keycode { name, value, .. } => {
+                self.key_names.insert(name.clone(), *value);
+            }
+            Decl::Alias { alias, real, .. } => {
+                // aliases of keys that don't exist are left out
+                if let Some(&keycode) = self.key_names.get(real) {
+                    self.key_names.insert(alias.clone(), keycode);
+                }
+            }
+            _ => return Err(self.misplaced(decl, "xkb_keycodes")),
+        }
+        Ok(())
+    }
+
+    fn types_decl(&mut self, decl: &Decl) -> Result<(), Error> {
+        match decl {
+            Decl::VirtualMods { line, mods } => self.virtual_mods_decl(mods, *line),
+            Decl::Type { line, name, body } => {
+                let mut def = TypeDef::default();
+                for decl in body {
+                    let Decl::Var {
+                        path, index, value, ..
+                    } = decl
+                    else {
+                        return Err(error(*line, "expected a setting"));
+                    };
+                    let name = var_name(path);
+                    // `level_name`'s index is a level rather than modifiers
+                    let mods = match (name.as_str(), index) {
+                        ("map" | "preserve", Some(index)) => Some(self.mask(index, *line)?),
+                        _ => None,
+                    };
+                    let mut entry = |mods| match def.entries.iter().position(|e| e.0 == mods) {
+                        Some(i) => i,
+                        None => {
+                            def.entries.push((mods, 0, 0));
+                            def.entries.len() - 1
+                        }
+                    };
+                    match (name.as_str(), mods) {
+                        ("modifiers", None) => def.mods = self.mask(value, *line)?,
+                        ("map", Some(mods)) => {
+                            let level = ordinal(value, "Level", *line)?;
+                            let i = entry(mods);
+                            def.entries[i].1 = level;
+                        }
+                        ("preserve", Some(mods)) => {
+                            let preserve = self.mask(value, *line)?;
+                            let i = entry(mods);
+                            def.entries[i].2 = preserve;
+                        }
+                        ("level_name" | "levelname", _) => {}
+                        (name, _) => {
+                            return Err(error(*line, format!("unknown type setting `{name}`")))
+                        }
+                    }
+                }
+                // later types replace earlier ones with the same name
+                self.types.retain(|(n, _)| n != name);
+                self.types.push((name.clone(), def));
+                Ok(())
+            }
+            _ => Err(self.misplaced(decl, "xkb_types")),
+        }
+    }
+
+    fn compat_decl(&mut self, decl: &Decl) -> Result<(), Error> {
+        match decl {
+            Decl::VirtualMods { line, mods } => self.virtual_mods_decl(mods, *line),
+            Decl::Var {
+                line, path, value, ..
+            } => {
+                // defaults for other statements, of which only this one matters
+                if var_name(path) == "interpret.usemodmapmods" {
+                    self.level_one_only = level_one_only(value, *line)?;
+                }
+                Ok(())
+            }
+            Decl::Interpret {
+                line,
+                matches,
+                body,
+            } => {
+                let line = *line;
+                let (sym, condition) = match matches {
+                    Expr::Add(sym, condition) => (&**sym, Some(&**condition)),
+                    sym => (sym, None),
+                };
+                let keysym = match sym {
+                    Expr::Ident(name) if name.eq_ignore_ascii_case("any") => None,
+                    sym => Some(keysym(sym, line)?),
+                };
+                let (condition, mods) = match condition {
+                    None => (Condition::AnyOfOrNone, 0xFF),
+                    Some(Expr::Call(name, args)) => {
+                        let condition = match name.to_ascii_lowercase().as_str() {
+                            "exactly" => Condition::Exactly,
+                            "allof" => Condition::AllOf,
+                            "noneof" => Condition::NoneOf,
+                            "anyof" => Condition::AnyOf,
+                            "anyofornone" => Condition::AnyOfOrNone,
+                            _ => return Err(error(line, format!("unknown condition `{name}`"))),
+                        };
+                        let [mods] = args.as_slice() else {
+                            return Err(error(line, "expected one set of modifiers"));
+                        };
+                        (condition, self.mask(mods, line)? as u8)
+                    }
+                    Some(_) => return Err(error(line, "expected a condition like `AnyOf(all)`")),
+                };
+
+                let mut interpret = Interpret {
+                    keysym,
+                    condition,
+                    mods,
+                    action: Action::None,
+                    virtual_mod: None,
+                    level_one_only: self.level_one_only,
+                };
+                for decl in body {
+                    let Decl::Var {
+                        line, path, value, ..
+                    } = decl
+                    else {
+                        return Err(error(line, "expected a setting"));
+                    };
+                    match var_name(path).as_str() {
+                        "action" => interpret.action = self.action(value, *line)?,
+                        "virtualmodifier" | "virtualmod" => {
+                            let Expr::Ident(name) = value else {
+                                return Err(error(*line, "expected a virtual modifier"));
+                            };
+                            interpret.virtual_mod = Some(self.virtual_mod(name, *line)?);
+                        }
+                        "usemodmapmods" | "usemodmap" => {
+                            interpret.level_one_only = level_one_only(value, *line)?;
+                        }
+                        // auto-repeat and locking aren't handled here
+                        _ => {}
+                    }
+                }
+                self.interprets.push(interpret);
+                Ok(())
+            }
+            _ => Err(self.misplaced(decl, "xkb_compatibility")),
+        }
+    }
+
+    fn symbols_decl(&mut self, decl: &Decl) -> Result<(), Error> {
+        match decl {
+            Decl::VirtualMods { line, mods } => self.virtual_mods_decl(mods, *line),
+            Decl::Var {
+                line,
+                path,
+                index: Some(index),
+                value,
+            } if var_name(path) == "name" => {
+                let group = ordinal(index, "Group", *line)?;
+                let Expr::String(name) = value else {
+                    return Err(error(*line, "expected a group name"));
+                };
+                if self.group_names.len() <= group {
+                    self.group_names.resize(group + 1, String::new());
+                }
+                self.group_names[group] = name.clone();
+                Ok(())
+            }
+            Decl::Var { .. } => Ok(()),
+            Decl::Key { line, name, body } => {
+                let line = *line;
+                let mut key = KeyDef {
+                    line,
+                    ..KeyDef::default()
+                };
+                for decl in body {
+                    let Decl::Var {
+                        path, index, value, ..
+                    } = decl
+                    else {
+                        return Err(error(line, "expected a setting"));
+                    };
+                    let group = index
+                        .as_ref()
+                        .map(|i| ordinal(i, "Group", line))
+                        .transpose()?;
+                    match var_name(path).as_str() {
+                        "type" => {
+                            let Expr::String(name) = value else {
+                                return Err(error(line, "expected a type name"));
+                            };
+                            match group {
+                                None => key.default_type = Some(name.clone()),
+                                Some(group) => set(&mut key.types, group, Some(name.clone())),
+                            }
+                        }
+                        "symbols" => {
+                            let syms = list(value, line)?
+                                .iter()
+                                .map(|sym| keysym(sym, line))
+                                .collect::<Result<_, _>>()?;
+                            set(&mut key.symbols, group.unwrap_or(0), syms);
+                        }
+                        "actions" => {
+                            let actions = list(value, line)?
+                                .iter()
+                                .map(|action| self.action(action, line))
+                                .collect::<Result<_, _>>()?;
+                            set(&mut key.actions, group.unwrap_or(0), actions);
+                        }
+                        "virtualmods" | "virtualmodifiers" | "vmods" => {
+                            key.virtual_mods = Some(self.mask(value, line)? & !0xFF);
+                        }
+                        // auto-repeat, and what happens with groups past a key's last one, aren't
+                        // handled here
+                        _ => {}
+                    }
+                }
+                // keys that aren't in `xkb_keycodes` can't be pressed
+                if let Some(&keycode) = self.key_names.get(name) {
+                    self.keys.insert(keycode, key);
+                }
+                Ok(())
+            }
+            Decl::ModifierMap {
+                line,
+                modifier,
+                keys,
+            } => {
+                let Some(i) = REAL_MODS
+                    .iter()
+                    .position(|m| m.eq_ignore_ascii_case(modifier))
+                else {
+                    return Err(error(*line, format!("`{modifier}` isn't a real modifier")));
+                };
+                // the keys might be given by keysym, so this has to wait for all the keys
+                self.modifier_maps.push((*line, 1 << i, keys.clone()));
+                Ok(())
+            }
+            _ => Err(self.misplaced(decl, "xkb_symbols")),
+        }
+    }
+
+    fn misplaced(&self, decl: &Decl, section: &str) -> Error {
+        let line = match decl {
+            Decl::Var { line, .. }
+            | Decl::Keycode { line, .. }
+            | Decl::Alias { line, .. }
+            | Decl::VirtualMods { line, .. }
+            | Decl::Type { line, .. }
+            | Decl::Interpret { line, .. }
+            | Decl::Key { line, .. }
+            | Decl::ModifierMap { line, .. } => *line,
+            Decl::Ignored => 0,
+        };
+        error(line, format!("this doesn't belong in `{section}`"))
+    }
+
+    fn virtual_mods_decl(
+        &mut self,
+        mods: &[(String, Option<Expr>)],
+        line: u32,
+    ) -> Result<(), Error> {
+        for (name, value) in mods {
+            let i = match self.virtual_mods.iter().position(|(n, _)| n == name) {
+                Some(i) => i,
+                None if self.virtual_mods.len() < 16 => {
+                    self.virtual_mods.push((name.clone(), 0));
+                    self.virtual_mods.len() - 1
+                }
+                None => return Err(error(line, "there can only be 16 virtual modifiers")),
+            };
+            if let Some(value) = value {
+                self.virtual_mods[i].1 = self.mask(value, line)? & 0xFF;
+            }
+        }
+        Ok(())
+    }
+
+    fn virtual_mod(&self, name: &str, line: u32) -> Result<usize, Error> {
+        self.virtual_mods
+            .iter()
+            .position(|(n, _)| n == name)
+            .ok_or_else(|| error(line, format!("unknown virtual modifier `{name}`")))
+    }
+
+    /// A modifier mask, like `Shift+LevelThree`, `none` or `all`.
+    fn mask(&self, expr: &Expr, line: u32) -> Result<ModMask, Error> {
+        match expr {
+            Expr::Ident(name) => {
+                if name.eq_ignore_ascii_case("none") {
+                    Ok(0)
+                } else if name.eq_ignore_ascii_case("all") {
+                    Ok(0xFF | ((1 << self.virtual_mods.len()) - 1) << 8)
+                } else if let Some(i) = REAL_MODS.iter().position(|m| m.eq_ignore_ascii_case(name))
+                {
+                    Ok(1 << i)
+                } else {
+                    Ok(1 << (8 + self.virtual_mod(name, line)?))
+                }
+            }
+            Expr::Number(n) => Ok(n & 0xFF),
+            Expr::Add(a, b) => Ok(self.mask(a, line)? | self.mask(b, line)?),
+            Expr::Sub(a, b) => Ok(self.mask(a, line)? & !self.mask(b, line)?),
+            _ => Err(error(line, "expected modifiers")),
+        }
+    }
+
+    /// An action, like `SetMods(modifiers=Shift,clearLocks)`.
+    fn action(&self, expr: &Expr, line: u32) -> Result<Action, Error> {
+        let Expr::Call(name, args) = expr else {
+            return Err(error(line, "expected an action"));
+        };
+        let mut mods = 0;
+        let mut clear_locks = false;
+        let mut latch_to_lock = false;
+        let mut group = (0, false);
+        for arg in args {
+            let (field, value, negated) = match arg {
+                Expr::Assign(field, value) => (field, Some(&**value), false),
+                Expr::Ident(field) => (field, None, false),
+                Expr::Not(inner) => match &**inner {
+                    Expr::Ident(field) => (field, None, true),
+                    _ => return Err(error(line, "expected a flag")),
+                },
+                _ => return Err(error(line, "expected an action's field")),
+            };
+            let flag = || match value {
+                Some(value) => boolean(value, line),
+                None => Ok(!negated),
+            };
+            let value = || value.ok_or_else(|| error(line, format!("`{field}` needs a value")));
+            match field.to_ascii_lowercase().as_str() {
+                "modifiers" | "mods" => {
+                    mods = match value()? {
+                        Expr::Ident(name) if name.eq_ignore_ascii_case("modmapmods") => {
+                            MOD_MAP_MODS
+                        }
+                        mask => self.mask(mask, line)?,
+                    }
+                }
+                "clearlocks" => clear_locks = flag()?,
+                "latchtolock" => latch_to_lock = flag()?,
+                "group" => {
+                    group = match value()? {
+                        Expr::Plus(n) => (ordinal(n, "Group", line)? as i32 + 1, false),
+                        Expr::Minus(n) => (-(ordinal(n, "Group", line)? as i32 + 1), false),
+                        n => (ordinal(n, "Group", line)? as i32, true),
+                    }
+                }
+                // the other fields, like which parts of the state are changed, aren't handled
+                _ => {}
+            }
+        }
+
+        let (group, absolute) = group;
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "setmods" => Action::SetMods { mods, clear_locks },
+            "latchmods" => Action::LatchMods {
+                mods,
+                clear_locks,
+                latch_to_lock,
+            },
+            "lockmods" => Action::LockMods { mods },
+            "setgroup" => Action::SetGroup {
+                group,
+                absolute,
+                clear_locks,
+            },
+            "latchgroup" => Action::LatchGroup { group, absolute },
+            "lockgroup" => Action::LockGroup { group, absolute },
+            // `NoAction()`, and actions for things like moving the pointer or switching VTs
+            _ => Action::None,
+        })
+    }
+
+    fn build(self) -> Result<Keymap, Error> {
+        let types: Vec<_> = self.types.iter().map(|(name, _)| name.as_str()).collect();
+
+        // the real modifiers of each key
+        let mut modmap = HashMap::new();
+        for (line, modifier, keys) in &self.modifier_maps {
+            for key in keys {
+                let keycode = match key {
+                    Expr::KeyName(name) => self.key_names.get(name).copied(),
+                    sym => {
+                        let sym = keysym(sym, *line)?;
+                        self.keys
+                            .iter()
+                            .find(|(_, key)| key.symbols.iter().flatten().any(|&s| s == sym))
+                            .map(|(&keycode, _)| keycode)
+                    }
+                };
+                if let Some(keycode) = keycode {
+                    *modmap.entry(keycode).or_insert(0) |= modifier;
+                }
+            }
+        }
+
+        let mut interprets = self.interprets.clone();
+        // stable, so interprets that are as specific as each other keep their order
+        interprets.sort_by_key(|i| (i.keysym.is_none(), i.condition));
+
+        let mut keys = BTreeMap::new();
+        let mut key_virtual_mods = HashMap::new();
+        for (&keycode, def) in &self.keys {
+            let key_mods = modmap.get(&keycode).copied().unwrap_or(0);
+            let num_groups = def
+                .symbols
+                .len()
+                .max(def.actions.len())
+                .max(def.types.len());
+            let mut groups = Vec::with_capacity(num_groups);
+            for group in 0..num_groups {
+                let syms = def.symbols.get(group).map_or(&[][..], |s| s);
+                let actions = def.actions.get(group).map_or(&[][..], |a| a);
+                let name = match def.types.get(group).cloned().flatten() {
+                    Some(name) => name,
+                    None => match &def.default_type {
+                        Some(name) => name.clone(),
+                        None => automatic_type(syms).into(),
+                    },
+                };
+                let Some(key_type) = types.iter().position(|&t| t == name) else {
+                    return Err(error(def.line, format!("there's no `{name}` type")));
+                };
+                let levels = (0..syms.len().max(actions.len()))
+                    .map(|level| Level {
+                        keysym: syms.get(level).copied().unwrap_or(keysym::NO_SYMBOL),
+                        action: actions.get(level).copied().unwrap_or(Action::None),
+                    })
+                    .collect();
+                groups.push(Group { key_type, levels });
+            }
+
+            // keys without their own actions get them from the first interpret that matches each
+            // level's keysym
+            let mut virtual_mods = 0;
+            if def.actions.is_empty() {
+                for (group_index, group) in groups.iter_mut().enumerate() {
+                    for (level_index, level) in group.levels.iter_mut().enumerate() {
+                        if level.keysym == keysym::NO_SYMBOL {
+                            continue;
+                        }
+                        let first_level = group_index == 0 && level_index == 0;
+                        let Some(interpret) = interprets.iter().find(|i| {
+                            let mods = if i.level_one_only && level_index != 0 {
+                                0
+                            } else {
+                                key_mods
+                            };
+                            i.keysym.is_none_or(|sym| sym == level.keysym)
+                                && i.condition.matches(i.mods, mods)
+                        }) else {
+                            continue;
+                        };
+                        if let (Some(i), true) = (
+                            interpret.virtual_mod,
+                            first_level || !interpret.level_one_only,
+                        ) {
+                            virtual_mods |= 1 << (8 + i);
+                        }
+                        level.action = interpret.action;
+                    }
+                }
+            }
+            key_virtual_mods.insert(keycode, def.virtual_mods.unwrap_or(virtual_mods));
+            keys.insert(keycode, Key { groups });
+        }
+
+        // a virtual modifier is the real modifiers of the keys that have it
+        let mut virtual_mods: Vec<_> = self
+            .virtual_mods
+            .iter()
+            .map(|(name, mask)| (name.clone(), *mask as u8))
+            .collect();
+        for (keycode, mask) in &key_virtual_mods {
+            let key_mods = modmap.get(keycode).copied().unwrap_or(0);
+            for (i, (_, real)) in virtual_mods.iter_mut().enumerate() {
+                if mask & (1 << (8 + i)) != 0 {
+                    *real |= key_mods;
+                }
+            }
+        }
+        let resolve = |mask: ModMask| {
+            virtual_mods
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| mask & (1 << (8 + i)) != 0)
+                .fold(mask as u8, |real, (_, (_, vmod))| real | vmod)
+        };
+
+        for (&keycode, key) in &mut keys {
+            let key_mods = modmap.get(&keycode).copied().unwrap_or(0);
+            let resolve_action = |mods: ModMask| {
+                let from_modmap = if mods & MOD_MAP_MODS != 0 {
+                    key_mods
+                } else {
+                    0
+                };
+                ModMask::from(resolve(mods & !MOD_MAP_MODS) | from_modmap)
+            };
+            for level in key.groups.iter_mut().flat_map(|g| &mut g.levels) {
+                match &mut level.action {
+                    Action::SetMods { mods, .. }
+                    | Action::LatchMods { mods, .. }
+                    | Action::LockMods { mods } => *mods = resolve_action(*mods),
+                    _ => {}
+                }
+            }
+        }
+
+        let types = self
+            .types
+            .iter()
+            .map(|(_, def)| {
+                let mods = resolve(def.mods);
+                KeyType {
+                    mods,
+                    entries: def
+                        .entries
+                        .iter()
+                        // entries with only virtual modifiers that aren't any real ones can't
+                        // ever be used
+                        .filter(|(entry_mods, ..)| *entry_mods == 0 || resolve(*entry_mods) != 0)
+                        .map(|&(entry_mods, level, preserve)| TypeEntry {
+                            mods: resolve(entry_mods) & mods,
+                            level,
+                            preserve: resolve(preserve),
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        let keycodes = self.key_names.values();
+        Ok(Keymap {
+            min_keycode: self
+                .min_keycode
+                .or_else(|| keycodes.clone().min().copied())
+                .unwrap_or(8),
+            max_keycode: self
+                .max_keycode
+                .or_else(|| keycodes.max().copied())
+                .unwrap_or(255),
+            num_groups: keys.values().map(|k| k.groups.len()).max().unwrap_or(0),
+            key_names: self.key_names,
+            keys,
+            types,
+            virtual_mods,
+            group_names: self.group_names,
+        })
+    }
+}
+
+/// Sets the value for a group, adding empty groups before it if there aren't any.
+fn set<T: Clone + Default>(groups: &mut Vec<T>, group: usize, value: T) {
+    if groups.len() <= group {
+        groups.resize(group + 1, T::default());
+    }
+    groups[group] = value;
+}
+
+/// `useModMapMods=level1`, or `AnyLevel`.
+fn level_one_only(expr: &Expr, line: u32) -> Result<bool, Error> {
+    match expr {
+        Expr::Ident(name) => match name.to_ascii_lowercase().as_str() {
+            "level1" | "levelone" => Ok(true),
+            "anylevel" | "any" => Ok(false),
+            _ => Err(error(
+                line,
+                format!("`{name}` isn't `level1` or `AnyLevel`"),
+            )),
+        },
+        _ => Err(error(line, "expected `level1` or `AnyLevel`")),
+    }
+}
+
+/// The type that a key without one gets, from its keysyms. Levels past the fourth can only be
+/// reached with a type of their own.
+fn automatic_type(syms: &[u32]) -> &'static str {
+    let at = |i: usize| syms.get(i).copied().unwrap_or(keysym::NO_SYMBOL);
+    let is_lower = |i| keysym::to_upper(at(i)) != at(i);
+    let is_upper = |i| keysym::to_lower(at(i)) != at(i);
+    let alphabetic = is_lower(0) && is_upper(1);
+    let keypad = keysym::is_keypad(at(0)) || keysym::is_keypad(at(1));
+    match syms.len() {
+        0 | 1 => "ONE_LEVEL",
+        2 if alphabetic => "ALPHABETIC",
+        2 if keypad => "KEYPAD",
+        2 => "TWO_LEVEL",
+        _ if alphabetic && is_lower(2) && is_upper(3) => "FOUR_LEVEL_ALPHABETIC",
+        _ if alphabetic => "FOUR_LEVEL_SEMIALPHABETIC",
+        _ if keypad => "FOUR_LEVEL_KEYPAD",
+        _ => "FOUR_LEVEL",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A keymap with the given symbols, and types and compat for them to use.
+    fn keymap(symbols: &str) -> Result<Keymap, Error> {
+        Keymap::parse(&format!(
+            "xkb_keymap {{
+            xkb_keycodes {{ <LFSH> = 50; <AC01> = 38; <AE01> = 10; <RALT> = 108; <LVL3> = 92; }};
+            xkb_types {{
+                virtual_modifiers LevelThree;
+                type \"ONE_LEVEL\" {{ modifiers= none; }};
+                type \"TWO_LEVEL\" {{ modifiers= Shift; map[Shift]= 2; }};
+                type \"ALPHABETIC\" {{ modifiers= Shift+Lock; map[Shift]= 2; map[Lock]= 2; }};
+                type \"THREE\" {{ modifiers= Shift+LevelThree; map[Shift]= 2; map[LevelThree]= 3; }};
+            }};
+            xkb_compatibility {{
+                virtual_modifiers LevelThree;
+                interpret ISO_Level3_Shift {{
+                    virtualModifier= LevelThree;
+                    action= SetMods(modifiers=LevelThree);
+                }};
+                interpret Any+AnyOf(all) {{ action= SetMods(modifiers=modMapMods); }};
+            }};
+            xkb_symbols {{ {symbols} }};
+            }};"
+        ))
+    }
+
+    #[test]
+    fn automatic_types() {
+        let keymap = keymap("key <AC01> { [ a, A ] }; key <AE01> { [ 1, exclam ] };").unwrap();
+        let type_mods = |keycode| {
+            let index = keymap.keys[&keycode].groups[0].key_type;
+            keymap.types[index].mods
+        };
+        assert_eq!(type_mods(38), mod_masks::SHIFT | mod_masks::LOCK);
+        assert_eq!(type_mods(10), mod_masks::SHIFT);
+        assert_eq!(automatic_type(&[u32::from('a')]), "ONE_LEVEL");
+        assert_eq!(automatic_type(&[0xFF95, 0xFFB7]), "KEYPAD");
+        assert_eq!(
+            automatic_type(&[0x61, 0x41, 0xE6, 0xC6]),
+            "FOUR_LEVEL_ALPHABETIC"
+        );
+        assert_eq!(
+            automatic_type(&[0x71, 0x51, 0x40, 0x3A9]),
+            "FOUR_LEVEL_SEMIALPHABETIC"
+        );
+        assert_eq!(automatic_type(&[0x31, 0x21, 0xB9, 0xA1]), "FOUR_LEVEL");
+    }
+
+    #[test]
+    fn virtual_modifiers() {
+        // LevelThree is bound by the key in the modifier map, which gives the type's entry a real
+        // modifier to match
+        let bound = keymap(
+            "key <LVL3> { [ ISO_Level3_Shift ] };
+            key <AC01> { type= \"THREE\", [ a, A, ae ] };
+            modifier_map Mod5 { <LVL3> };",
+        )
+        .unwrap();
+        assert_eq!(bound.mod_mask("LevelThree"), Some(mod_masks::MOD5));
+        let three = &bound.types[bound.keys[&38].groups[0].key_type];
+        assert_eq!(three.mods, mod_masks::SHIFT | mod_masks::MOD5);
+        assert_eq!(three.entries.len(), 2);
+
+        // without it, the entry can never match, so it's left out
+        let unbound = keymap("key <AC01> { type= \"THREE\", [ a, A, ae ] };").unwrap();
+        assert_eq!(unbound.mod_mask("LevelThree"), Some(0));
+        let three = &unbound.types[unbound.keys[&38].groups[0].key_type];
+        assert_eq!(three.mods, mod_masks::SHIFT);
+        assert_eq!(three.entries.len(), 1);
+    }
+
+    #[test]
+    fn symbols() {
+        let keymap = keymap(
+            "name[Group1]= \"One\";
+            name[Group2]= \"Two\";
+            key <AC01> { symbols[Group1]= [ a, A ], symbols[Group2]= [ U0444, U0424 ] };
+            key <AE01> { [ 1, exclam ], [ 2 ] };
+            key <XXXX> { [ x ] };",
+        )
+        .unwrap();
+        assert_eq!(keymap.group_names(), ["One", "Two"]);
+        assert_eq!(keymap.num_groups(), 2);
+        assert_eq!(keymap.keysym(38, 1, 1), Some(0x1000424));
+        assert_eq!(keymap.keysym(10, 1, 0), Some(u32::from('2')));
+        assert_eq!(keymap.keysym(10, 1, 1), None);
+        assert_eq!(keymap.keys.len(), 2);
+    }
+
+    #[test]
+    fn errors() {
+        let message = |symbols| keymap(symbols).unwrap_err().message;
+        assert_eq!(
+            message("key <AC01> { type= \"NOPE\", [ a ] };"),
+            "there's no `NOPE` type"
+        );
+        assert_eq!(
+            message("modifier_map Mod9 { <AC01> };"),
+            "`Mod9` isn't a real modifier"
+        );
+        assert_eq!(
+            message("key <AC01> { vmods= Hyper, [ a ] };"),
+            "unknown virtual modifier `Hyper`"
+        );
+        let error =
+            Keymap::parse("xkb_keymap {\n xkb_types {\n type \"X\" { map[Nope]= 2; };\n};\n};")
+                .unwrap_err();
+        assert_eq!((error.line, error.column), (3, 1));
+    }
+}
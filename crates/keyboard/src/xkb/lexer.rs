@@ -0,0 +1,216 @@
+//! Splits keymap text into tokens.
+//!
+//! Comments (`//`, `#` and `/* */`) are skipped. Numbers are lexed loosely (any run of digits,
+//! letters and dots that starts with a digit), so that the floats in `xkb_geometry` sections don't
+//! stop the parser from skipping them.
+
+use super::Error;
+
+/// What sort of token a [`Token`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// An identifier or keyword, like `key`, `Shift` or `udiaeresis`.
+    Ident,
+    /// A decimal or hex number, like `8` or `0x1008FF11`.
+    Number,
+    /// A string, without its quotes, like `ONE_LEVEL` from `"ONE_LEVEL"`.
+    String,
+    /// A key name, without its angle brackets, like `AE01` from `<AE01>`.
+    KeyName,
+    /// A piece of punctuation, like `{` or `+`.
+    Punct,
+}
+
+/// A token, borrowing its text from the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    /// The 1-based line the token starts on.
+    pub line: u32,
+    /// The 1-based byte column the token starts at.
+    pub column: u32,
+}
+
+impl Token<'_> {
+    /// Returns whether this is the punctuation `p`.
+    pub fn is_punct(&self, p: &str) -> bool {
+        self.kind == TokenKind::Punct && self.text == p
+    }
+
+    /// Returns whether this is the identifier or keyword `name`. Keywords aren't case sensitive.
+    pub fn is_ident(&self, name: &str) -> bool {
+        self.kind == TokenKind::Ident && self.text.eq_ignore_ascii_case(name)
+    }
+}
+
+const PUNCTUATION: &str = "{}[]();,=+-!.*";
+
+/// Splits `source` into tokens.
+pub fn tokenize(source: &str) -> Result<Vec<Token<'_>>, Error> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut line_start = 0;
+    let mut pos = 0;
+    let bytes = source.as_bytes();
+
+    while pos < bytes.len() {
+        let rest = &source[pos..];
+        let column = (pos - line_start) as u32 + 1;
+        let error = move |message: &str| Error {
+            line,
+            column,
+            message: message.into(),
+        };
+        let first = rest.chars().next().unwrap_or_default();
+
+        let (kind, start, len, skip) = if first == '\n' {
+            line += 1;
+            line_start = pos + 1;
+            pos += 1;
+            continue;
+        } else if first.is_whitespace() {
+            pos += first.len_utf8();
+            continue;
+        } else if rest.starts_with("//") || first == '#' {
+            pos += rest.find('\n').unwrap_or(rest.len());
+            continue;
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            let Some(end) = comment.find("*/") else {
+                return Err(error("unterminated block comment"));
+            };
+            for (i, b) in rest[..end + 2].bytes().enumerate() {
+                if b == b'\n' {
+                    line += 1;
+                    line_start = pos + i + 1;
+                }
+            }
+            pos += end + 4;
+            continue;
+        } else if first.is_ascii_alphabetic() || first == '_' {
+            let len = rest
+                .bytes()
+                .take_while(|b| b.is_ascii_alphanumeric() || *b == b'_')
+                .count();
+            (TokenKind::Ident, pos, len, len)
+        } else if first.is_ascii_digit() {
+            let len = rest
+                .bytes()
+                .take_while(|b| b.is_ascii_alphanumeric() || *b == b'.')
+                .count();
+            (TokenKind::Number, pos, len, len)
+        } else if first == '"' {
+            // escapes are kept as they are, since nothing that's compared uses them
+            let mut escaped = false;
+            let Some(len) = rest[1..].find(|c| {
+                let end = c == '"' && !escaped;
+                escaped = c == '\\' && !escaped;
+                end
+            }) else {
+                return Err(error("unterminated string"));
+            };
+            if rest[1..=len].contains('\n') {
+                return Err(error("unterminated string"));
+            }
+            (TokenKind::String, pos + 1, len, len + 2)
+        } else if first == '<' {
+            let Some(len) = rest[1..].find('>') else {
+                return Err(error("unterminated key name"));
+            };
+            if rest[1..=len].contains(char::is_whitespace) {
+                return Err(error("unterminated key name"));
+            }
+            (TokenKind::KeyName, pos + 1, len, len + 2)
+        } else if PUNCTUATION.contains(first) {
+            (TokenKind::Punct, pos, 1, 1)
+        } else {
+            return Err(error(&format!("unexpected character `{first}`")));
+        };
+
+        tokens.push(Token {
+            kind,
+            text: &source[start..start + len],
+            line,
+            column,
+        });
+        pos += skip;
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TokenKind::*;
+
+    fn kinds(source: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.kind, t.text))
+            .collect()
+    }
+
+    #[test]
+    fn tokens() {
+        assert_eq!(
+            kinds(r#"key <AE01> { [ 1, exclam ] }; name[Group1]= "English (US)";"#),
+            [
+                (Ident, "key"),
+                (KeyName, "AE01"),
+                (Punct, "{"),
+                (Punct, "["),
+                (Number, "1"),
+                (Punct, ","),
+                (Ident, "exclam"),
+                (Punct, "]"),
+                (Punct, "}"),
+                (Punct, ";"),
+                (Ident, "name"),
+                (Punct, "["),
+                (Ident, "Group1"),
+                (Punct, "]"),
+                (Punct, "="),
+                (String, "English (US)"),
+                (Punct, ";"),
+            ]
+        );
+        assert_eq!(
+            kinds("LockGroup(group=+1) 0x1008FF11 1.5"),
+            [
+                (Ident, "LockGroup"),
+                (Punct, "("),
+                (Ident, "group"),
+                (Punct, "="),
+                (Punct, "+"),
+                (Number, "1"),
+                (Punct, ")"),
+                (Number, "0x1008FF11"),
+                (Number, "1.5"),
+            ]
+        );
+    }
+
+    #[test]
+    fn comments_and_lines() {
+        let tokens = tokenize("// one\n# two\n/* three\n */ minimum = 8;").unwrap();
+        assert_eq!(tokens[0].text, "minimum");
+        assert_eq!((tokens[0].line, tokens[0].column), (4, 5));
+        assert_eq!(tokens.len(), 4);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            tokenize("\n  \"open").unwrap_err(),
+            Error {
+                line: 2,
+                column: 3,
+                message: "unterminated string".into(),
+            }
+        );
+        assert!(tokenize("<AE01").is_err());
+        assert!(tokenize("/* open").is_err());
+        assert!(tokenize("key @").is_err());
+    }
+}
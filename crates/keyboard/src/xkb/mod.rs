@@ -0,0 +1,72 @@
+//! An XKB keymap interpreter, for the keymaps that Wayland compositors send as text.
+//!
+//! A keymap has four sections that matter:
+//!
+//! - `xkb_keycodes` names the keycodes, like `<AE01> = 10`.
+//! - `xkb_types` says which modifiers pick which shift level, like shift picking level 2 for
+//!   `TWO_LEVEL` keys.
+//! - `xkb_compatibility` says what modifier keys do, by giving keysyms like `Shift_L` actions like
+//!   `SetMods(modifiers=Shift)`.
+//! - `xkb_symbols` gives each key a keysym for each level of each group (layout), and says which
+//!   keys are which modifiers.
+//!
+//! [`Keymap::parse`] reads the text, and a [`State`] follows key presses through it: which
+//! modifiers are held, latched and locked, and which group is active. Modifiers come as a bitmask
+//! of the eight real modifiers in [`mod_masks`], which is what Wayland's `wl_keyboard.modifiers`
+//! uses too.
+//!
+//! ```
+//! use triangle_from_scratch_keyboard::{xkb::{Keymap, State}, Key, KeyState};
+//!
+//! let keymap = Keymap::parse(
+//!     r#"xkb_keymap {
+//!         xkb_keycodes { <AC01> = 38; <LFSH> = 50; };
+//!         xkb_types {
+//!             type "ONE_LEVEL" { modifiers= none; };
+//!             type "ALPHABETIC" { modifiers= Shift+Lock; map[Shift]= 2; map[Lock]= 2; };
+//!         };
+//!         xkb_compatibility {
+//!             interpret Any+AnyOf(all) { action= SetMods(modifiers=modMapMods); };
+//!         };
+//!         xkb_symbols {
+//!             key <AC01> { [ a, A ] };
+//!             key <LFSH> { [ Shift_L ] };
+//!             modifier_map Shift { <LFSH> };
+//!         };
+//!     };"#,
+//! )
+//! .unwrap();
+//!
+//! let mut state = State::new(keymap);
+//! state.update_key(50, KeyState::Pressed);
+//! assert_eq!(state.key_event(38, KeyState::Pressed).logical_key, Key::Character('A'));
+//! ```
+
+use std::fmt;
+
+mod keymap;
+pub mod lexer;
+pub mod parser;
+mod state;
+
+pub use keymap::{mod_masks, Keymap};
+pub use state::State;
+
+/// Keymap text that isn't valid, or that uses something this interpreter doesn't understand.
+///
+/// Errors in what a statement means, rather than in its syntax, point at the start of the line
+/// the statement is on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,748 @@
+//! Parses keymap text into its sections, without working out what any of it means.
+//!
+//! This only handles the statements that appear in complete keymaps, like the ones compositors
+//! send and `xkbcli compile-keymap` prints. `include` statements, which only appear in the
+//! separate files that keymaps are compiled from, are an error. `xkb_geometry` sections are
+//! skipped, since they only describe what the keyboard looks like.
+
+use super::{
+    lexer::{tokenize, Token, TokenKind},
+    Error,
+};
+
+/// Which section a [`Section`] is, in the order they usually come in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SectionKind {
+    Keycodes,
+    Types,
+    Compat,
+    Symbols,
+}
+
+/// One `xkb_*` section of a keymap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    pub kind: SectionKind,
+    pub decls: Vec<Decl>,
+}
+
+/// A statement in a section, or in a `type` or `interpret` block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decl {
+    /// `name = value;`, `name[index] = value;` or `interpret.name = value;`. In a `key` block,
+    /// unnamed lists of keysyms are `symbols[GroupN]`.
+    Var {
+        line: u32,
+        /// The parts of the name, split at dots.
+        path: Vec<String>,
+        index: Option<Expr>,
+        value: Expr,
+    },
+    /// `<AE01> = 10;`
+    Keycode { line: u32, name: String, value: u32 },
+    /// `alias <ALGR> = <RALT>;`
+    Alias {
+        line: u32,
+        alias: String,
+        real: String,
+    },
+    /// `virtual_modifiers NumLock, LevelThree = Mod5;`
+    VirtualMods {
+        line: u32,
+        mods: Vec<(String, Option<Expr>)>,
+    },
+    /// `type "TWO_LEVEL" { ... };`
+    Type {
+        line: u32,
+        name: String,
+        body: Vec<Decl>,
+    },
+    /// `interpret Num_Lock+AnyOf(all) { ... };`, with the keysym and the condition as one
+    /// expression.
+    Interpret {
+        line: u32,
+        matches: Expr,
+        body: Vec<Decl>,
+    },
+    /// `key <AE01> { ... };`
+    Key {
+        line: u32,
+        name: String,
+        body: Vec<Decl>,
+    },
+    /// `modifier_map Shift { <LFSH>, Shift_R };`
+    ModifierMap {
+        line: u32,
+        modifier: String,
+        keys: Vec<Expr>,
+    },
+    /// Something that doesn't change what keys do, like an `indicator`.
+    Ignored,
+}
+
+/// A value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Ident(String),
+    Number(u32),
+    String(String),
+    KeyName(String),
+    /// `[ a, A ]`
+    List(Vec<Expr>),
+    /// `SetMods(modifiers=Shift, clearLocks)`, or `AnyOf(all)` in an `interpret`.
+    Call(String, Vec<Expr>),
+    /// `modifiers=Shift`, as an argument to a [`Call`](Self::Call).
+    Assign(String, Box<Expr>),
+    /// `!clearLocks`
+    Not(Box<Expr>),
+    /// `+1`, which is relative where `1` isn't.
+    Plus(Box<Expr>),
+    /// `-1`
+    Minus(Box<Expr>),
+    /// `Shift+Lock`
+    Add(Box<Expr>, Box<Expr>),
+    /// `Shift-Lock`
+    Sub(Box<Expr>, Box<Expr>),
+}
+
+/// Parses keymap text into its sections, in the order they appear.
+pub fn parse(source: &str) -> Result<Vec<Section>, Error> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        end_line: source.lines().count() as u32,
+    };
+    let mut sections = Vec::new();
+    while parser.peek(0).is_some() {
+        parser.skip_flags();
+        if parser.eat_ident("xkb_keymap") {
+            parser.eat_kind(TokenKind::String);
+            parser.expect_punct("{")?;
+            while !parser.eat_punct("}") {
+                parser.skip_flags();
+                sections.extend(parser.section()?);
+            }
+            parser.eat_punct(";");
+        } else {
+            sections.extend(parser.section()?);
+        }
+    }
+    Ok(sections)
+}
+
+/// Words that can come before a section's keyword in the files keymaps are compiled from.
+const FLAGS: &[&str] = &[
+    "default",
+    "partial",
+    "hidden",
+    "alphanumeric_keys",
+    "modifier_keys",
+    "keypad_keys",
+    "function_keys",
+    "alternate_group",
+];
+
+/// Words that can come before a statement to say how it merges with earlier ones. There's
+/// nothing to merge with in a complete keymap, so they're ignored.
+const MERGE_MODES: &[&str] = &["augment", "override", "replace", "alternate"];
+
+fn error_at(token: &Token<'_>, message: impl Into<String>) -> Error {
+    Error {
+        line: token.line,
+        column: token.column,
+        message: message.into(),
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    /// The last line of the source, for reporting errors at the end of the input.
+    end_line: u32,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self, n: usize) -> Option<&Token<'a>> {
+        self.tokens.get(self.pos + n)
+    }
+
+    fn error_here(&self, message: impl Into<String>) -> Error {
+        match self.peek(0) {
+            Some(t) => error_at(t, message),
+            None => Error {
+                line: self.end_line,
+                column: 1,
+                message: message.into(),
+            },
+        }
+    }
+
+    fn next(&mut self) -> Result<Token<'a>, Error> {
+        let token = *self
+            .peek(0)
+            .ok_or_else(|| self.error_here("unexpected end of input"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn line(&self) -> u32 {
+        self.peek(0).map_or(self.end_line, |t| t.line)
+    }
+
+    fn eat_punct(&mut self, p: &str) -> bool {
+        let found = self.peek(0).is_some_and(|t| t.is_punct(p));
+        self.pos += usize::from(found);
+        found
+    }
+
+    fn eat_ident(&mut self, name: &str) -> bool {
+        let found = self.peek(0).is_some_and(|t| t.is_ident(name));
+        self.pos += usize::from(found);
+        found
+    }
+
+    fn eat_kind(&mut self, kind: TokenKind) -> Option<Token<'a>> {
+        let token = *self.peek(0).filter(|t| t.kind == kind)?;
+        self.pos += 1;
+        Some(token)
+    }
+
+    fn expect_punct(&mut self, p: &str) -> Result<(), Error> {
+        match self.peek(0) {
+            Some(t) if t.is_punct(p) => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(t) => Err(error_at(t, format!("expected `{p}`, found `{}`", t.text))),
+            None => Err(self.error_here(format!("expected `{p}`, found the end of the input"))),
+        }
+    }
+
+    fn expect_kind(&mut self, kind: TokenKind, what: &str) -> Result<Token<'a>, Error> {
+        match self.peek(0) {
+            Some(t) if t.kind == kind => self.next(),
+            Some(t) => Err(error_at(t, format!("expected {what}, found `{}`", t.text))),
+            None => Err(self.error_here(format!("expected {what}, found the end of the input"))),
+        }
+    }
+
+    fn skip_flags(&mut self) {
+        while self
+            .peek(0)
+            .is_some_and(|t| FLAGS.iter().any(|flag| t.is_ident(flag)))
+        {
+            self.pos += 1;
+        }
+    }
+
+    /// Parses a section, or skips it if it's `xkb_geometry`.
+    fn section(&mut self) -> Result<Option<Section>, Error> {
+        let token = self.expect_kind(TokenKind::Ident, "a section")?;
+        let kind = match token.text.to_ascii_lowercase().as_str() {
+            "xkb_keycodes" => Some(SectionKind::Keycodes),
+            "xkb_types" => Some(SectionKind::Types),
+            "xkb_compatibility" | "xkb_compatibility_map" | "xkb_compat" | "xkb_compat_map" => {
+                Some(SectionKind::Compat)
+            }
+            "xkb_symbols" => Some(SectionKind::Symbols),
+            "xkb_geometry" => None,
+            _ => {
+                return Err(error_at(
+                    &token,
+                    format!("unknown section `{}`", token.text),
+                ))
+            }
+        };
+        self.eat_kind(TokenKind::String);
+        self.expect_punct("{")?;
+
+        let Some(kind) = kind else {
+            self.skip_block()?;
+            self.eat_punct(";");
+            return Ok(None);
+        };
+        let decls = self.decls()?;
+        self.eat_punct(";");
+        Ok(Some(Section { kind, decls }))
+    }
+
+    /// Skips everything up to and including the `}` that closes a block whose `{` was just parsed.
+    fn skip_block(&mut self) -> Result<(), Error> {
+        let mut depth = 1;
+        while depth > 0 {
+            let token = self.next()?;
+            if token.is_punct("{") {
+                depth += 1;
+            } else if token.is_punct("}") {
+                depth -= 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses statements up to and including the `}` that closes a block whose `{` was just parsed.
+    fn decls(&mut self) -> Result<Vec<Decl>, Error> {
+        let mut decls = Vec::new();
+        while !self.eat_punct("}") {
+            let decl = self.decl()?;
+            if decl != Decl::Ignored {
+                decls.push(decl);
+            }
+        }
+        Ok(decls)
+    }
+
+    fn decl(&mut self) -> Result<Decl, Error> {
+        let token = self.next()?;
+        let line = token.line;
+        if token.is_ident("include")
+            || (MERGE_MODES.iter().any(|m| token.is_ident(m))
+                && self.peek(0).is_some_and(|t| t.kind == TokenKind::String))
+        {
+            return Err(error_at(
+                &token,
+                "includes aren't supported, so the keymap must be a complete one",
+            ));
+        }
+        if MERGE_MODES.iter().any(|m| token.is_ident(m)) {
+            return self.decl();
+        }
+
+        let decl = match token.kind {
+            TokenKind::KeyName => {
+                self.expect_punct("=")?;
+                let value = self.expect_kind(TokenKind::Number, "a keycode")?;
+                Decl::Keycode {
+                    line,
+                    name: token.text.into(),
+                    value: parse_number(&value)?,
+                }
+            }
+            TokenKind::Ident if token.is_ident("alias") => {
+                let alias = self.expect_kind(TokenKind::KeyName, "a key name")?;
+                self.expect_punct("=")?;
+                let real = self.expect_kind(TokenKind::KeyName, "a key name")?;
+                Decl::Alias {
+                    line,
+                    alias: alias.text.into(),
+                    real: real.text.into(),
+                }
+            }
+            TokenKind::Ident if token.is_ident("virtual_modifiers") => {
+                let mut mods = Vec::new();
+                loop {
+                    let name = self.expect_kind(TokenKind::Ident, "a modifier")?;
+                    let value = if self.eat_punct("=") {
+                        Some(self.expr()?)
+                    } else {
+                        None
+                    };
+                    mods.push((name.text.into(), value));
+                    if !self.eat_punct(",") {
+                        break;
+                    }
+                }
+                Decl::VirtualMods { line, mods }
+            }
+            TokenKind::Ident if token.is_ident("type") => {
+                let name = self.expect_kind(TokenKind::String, "a type name")?;
+                self.expect_punct("{")?;
+                Decl::Type {
+                    line,
+                    name: name.text.into(),
+                    body: self.decls()?,
+                }
+            }
+            TokenKind::Ident if token.is_ident("interpret") && !self.peek_is_var() => {
+                let matches = self.expr()?;
+                self.expect_punct("{")?;
+                Decl::Interpret {
+                    line,
+                    matches,
+                    body: self.decls()?,
+                }
+            }
+            TokenKind::Ident if token.is_ident("key") && !self.peek_is_var() => {
+                let name = self.expect_kind(TokenKind::KeyName, "a key name")?;
+                self.expect_punct("{")?;
+                Decl::Key {
+                    line,
+                    name: name.text.into(),
+                    body: self.key_body()?,
+                }
+            }
+            TokenKind::Ident
+                if ["modifier_map", "modmap", "mod_map"]
+                    .iter()
+                    .any(|m| token.is_ident(m)) =>
+            {
+                let modifier = self.expect_kind(TokenKind::Ident, "a modifier")?;
+                self.expect_punct("{")?;
+                let keys = self.list("}")?;
+                Decl::ModifierMap {
+                    line,
+                    modifier: modifier.text.into(),
+                    keys,
+                }
+            }
+            TokenKind::Ident
+                if (token.is_ident("indicator") || token.is_ident("group"))
+                    && !self.peek_is_var() =>
+            {
+                // `indicator "Caps Lock" { ... }`, `indicator 1 = "Caps Lock"` or
+                // `group 2 = AltGr`, none of which change what keys do
+                while !self.peek(0).is_some_and(|t| t.is_punct(";")) {
+                    if self.next()?.is_punct("{") {
+                        self.skip_block()?;
+                    }
+                }
+                Decl::Ignored
+            }
+            TokenKind::Ident => {
+                self.pos -= 1;
+                self.var()?
+            }
+            TokenKind::Punct if token.is_punct("!") => {
+                // `!name;` sets a flag to false, which nothing here needs
+                self.expect_kind(TokenKind::Ident, "a name")?;
+                Decl::Ignored
+            }
+            _ => return Err(error_at(&token, format!("unexpected `{}`", token.text))),
+        };
+        self.expect_punct(";")?;
+        Ok(decl)
+    }
+
+    /// Whether the next tokens are the rest of a variable's name, for keywords that are also
+    /// used as the start of a default like `key.repeat = True;`.
+    fn peek_is_var(&self) -> bool {
+        self.peek(0)
+            .is_some_and(|t| t.is_punct(".") || t.is_punct("=") || t.is_punct("["))
+    }
+
+    /// Parses `name = value`, `name[index] = value` or `a.b = value`, without the `;`. A name on
+    /// its own is a flag that's set to true.
+    fn var(&mut self) -> Result<Decl, Error> {
+        let line = self.line();
+        let mut path = vec![self
+            .expect_kind(TokenKind::Ident, "a name")?
+            .text
+            .to_string()];
+        while self.eat_punct(".") {
+            path.push(self.expect_kind(TokenKind::Ident, "a name")?.text.into());
+        }
+        let index = if self.eat_punct("[") {
+            let index = self.expr()?;
+            self.expect_punct("]")?;
+            Some(index)
+        } else {
+            None
+        };
+        let value = if self.eat_punct("=") {
+            self.expr()?
+        } else {
+            Expr::Ident("true".into())
+        };
+        Ok(Decl::Var {
+            line,
+            path,
+            index,
+            value,
+        })
+    }
+
+    /// Parses what's inside a `key` block, up to and including its `}`.
+    fn key_body(&mut self) -> Result<Vec<Decl>, Error> {
+        let mut body = Vec::new();
+        let mut groups = 0;
+        while !self.eat_punct("}") {
+            if self.peek(0).is_some_and(|t| t.is_punct("[")) {
+                // a list on its own is the symbols of the next group
+                groups += 1;
+                let line = self.line();
+                body.push(Decl::Var {
+                    line,
+                    path: vec!["symbols".into()],
+                    index: Some(Expr::Number(groups)),
+                    value: self.expr()?,
+                });
+            } else {
+                body.push(self.var()?);
+            }
+            if !self.eat_punct(",") {
+                self.expect_punct("}")?;
+                break;
+            }
+        }
+        Ok(body)
+    }
+
+    /// Parses a comma-separated list up to and including `close`.
+    fn list(&mut self, close: &str) -> Result<Vec<Expr>, Error> {
+        let mut items = Vec::new();
+        if self.eat_punct(close) {
+            return Ok(items);
+        }
+        loop {
+            items.push(self.expr()?);
+            if !self.eat_punct(",") {
+                self.expect_punct(close)?;
+                return Ok(items);
+            }
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, Error> {
+        let mut lhs = self.unary()?;
+        loop {
+            if self.eat_punct("+") {
+                lhs = Expr::Add(Box::new(lhs), Box::new(self.unary()?));
+            } else if self.eat_punct("-") {
+                lhs = Expr::Sub(Box::new(lhs), Box::new(self.unary()?));
+            } else {
+                return Ok(lhs);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, Error> {
+        let token = self.next()?;
+        Ok(match token.kind {
+            TokenKind::Punct if token.is_punct("!") => Expr::Not(Box::new(self.unary()?)),
+            TokenKind::Punct if token.is_punct("+") => Expr::Plus(Box::new(self.unary()?)),
+            TokenKind::Punct if token.is_punct("-") => Expr::Minus(Box::new(self.unary()?)),
+            TokenKind::Punct if token.is_punct("[") => Expr::List(self.list("]")?),
+            TokenKind::Punct if token.is_punct("(") => {
+                let inner = self.expr()?;
+                self.expect_punct(")")?;
+                inner
+            }
+            TokenKind::Number => Expr::Number(parse_number(&token)?),
+            TokenKind::String => Expr::String(token.text.into()),
+            TokenKind::KeyName => Expr::KeyName(token.text.into()),
+            TokenKind::Ident if self.eat_punct("(") => {
+                let mut args = Vec::new();
+                if !self.eat_punct(")") {
+                    loop {
+                        args.push(self.arg()?);
+                        if !self.eat_punct(",") {
+                            self.expect_punct(")")?;
+                            break;
+                        }
+                    }
+                }
+                Expr::Call(token.text.into(), args)
+            }
+            TokenKind::Ident => Expr::Ident(token.text.into()),
+            _ => return Err(error_at(&token, format!("unexpected `{}`", token.text))),
+        })
+    }
+
+    /// Parses an argument to a call, which can be `name=value`.
+    fn arg(&mut self) -> Result<Expr, Error> {
+        if self.peek(0).is_some_and(|t| t.kind == TokenKind::Ident)
+            && self.peek(1).is_some_and(|t| t.is_punct("="))
+        {
+            let name = self.next()?.text;
+            self.pos += 1;
+            return Ok(Expr::Assign(name.into(), Box::new(self.expr()?)));
+        }
+        self.expr()
+    }
+}
+
+fn parse_number(token: &Token<'_>) -> Result<u32, Error> {
+    let parsed = match token.text.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => token.text.parse(),
+    };
+    parsed.map_err(|_| error_at(token, format!("`{}` isn't a whole number", token.text)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ident(name: &str) -> Expr {
+        Expr::Ident(name.into())
+    }
+
+    #[test]
+    fn sections() {
+        let sections = parse(
+            r#"xkb_keymap {
+                xkb_keycodes "evdev" { minimum = 8; <AE01> = 10; alias <ALGR> = <RALT>;
+                    indicator 1 = "Caps Lock"; };
+                xkb_types "complete" { virtual_modifiers NumLock, LevelThree = Mod5; };
+                xkb_geometry "pc(pc105)" { shape "NORM" { { [ 18.0, 18.0 ] } }; };
+                default partial xkb_symbols "us" { };
+            };"#,
+        )
+        .unwrap();
+        assert_eq!(
+            sections,
+            [
+                Section {
+                    kind: SectionKind::Keycodes,
+                    decls: vec![
+                        Decl::Var {
+                            line: 2,
+                            path: vec!["minimum".into()],
+                            index: None,
+                            value: Expr::Number(8),
+                        },
+                        Decl::Keycode {
+                            line: 2,
+                            name: "AE01".into(),
+                            value: 10,
+                        },
+                        Decl::Alias {
+                            line: 2,
+                            alias: "ALGR".into(),
+                            real: "RALT".into(),
+                        },
+                    ],
+                },
+                Section {
+                    kind: SectionKind::Types,
+                    decls: vec![Decl::VirtualMods {
+                        line: 4,
+                        mods: vec![
+                            ("NumLock".into(), None),
+                            ("LevelThree".into(), Some(ident("Mod5"))),
+                        ],
+                    }],
+                },
+                Section {
+                    kind: SectionKind::Symbols,
+                    decls: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn keys() {
+        let sections = parse(
+            r#"xkb_symbols {
+                key <AE01> { [ 1, exclam ], [ Cyrillic_a ] };
+                key <RALT> { type= "TWO_LEVEL", symbols[Group1]= [ ISO_Level3_Shift, Multi_key ] };
+                modifier_map Shift { <LFSH>, Shift_R };
+            };"#,
+        )
+        .unwrap();
+        let symbols = |line, group, syms: &[Expr]| Decl::Var {
+            line,
+            path: vec!["symbols".into()],
+            index: Some(group),
+            value: Expr::List(syms.to_vec()),
+        };
+        assert_eq!(
+            sections[0].decls,
+            [
+                Decl::Key {
+                    line: 2,
+                    name: "AE01".into(),
+                    body: vec![
+                        symbols(2, Expr::Number(1), &[Expr::Number(1), ident("exclam")]),
+                        symbols(2, Expr::Number(2), &[ident("Cyrillic_a")]),
+                    ],
+                },
+                Decl::Key {
+                    line: 3,
+                    name: "RALT".into(),
+                    body: vec![
+                        Decl::Var {
+                            line: 3,
+                            path: vec!["type".into()],
+                            index: None,
+                            value: Expr::String("TWO_LEVEL".into()),
+                        },
+                        symbols(
+                            3,
+                            ident("Group1"),
+                            &[ident("ISO_Level3_Shift"), ident("Multi_key")]
+                        ),
+                    ],
+                },
+                Decl::ModifierMap {
+                    line: 4,
+                    modifier: "Shift".into(),
+                    keys: vec![Expr::KeyName("LFSH".into()), ident("Shift_R")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn interprets() {
+        let sections = parse(
+            "xkb_compat {
+                interpret.useModMapMods= AnyLevel;
+                interpret Num_Lock+AnyOf(all) {
+                    action= LockGroup(group=+1, !clearLocks);
+                };
+            };",
+        )
+        .unwrap();
+        assert_eq!(
+            sections[0].decls,
+            [
+                Decl::Var {
+                    line: 2,
+                    path: vec!["interpret".into(), "useModMapMods".into()],
+                    index: None,
+                    value: ident("AnyLevel"),
+                },
+                Decl::Interpret {
+                    line: 3,
+                    matches: Expr::Add(
+                        Box::new(ident("Num_Lock")),
+                        Box::new(Expr::Call("AnyOf".into(), vec![ident("all")])),
+                    ),
+                    body: vec![Decl::Var {
+                        line: 4,
+                        path: vec!["action".into()],
+                        index: None,
+                        value: Expr::Call(
+                            "LockGroup".into(),
+                            vec![
+                                Expr::Assign(
+                                    "group".into(),
+                                    Box::new(Expr::Plus(Box::new(Expr::Number(1)))),
+                                ),
+                                Expr::Not(Box::new(ident("clearLocks"))),
+                            ],
+                        ),
+                    }],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = parse("xkb_symbols {\n include \"us\" };").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+        assert!(error.message.contains("include"));
+
+        assert_eq!(
+            parse("xkb_types { type \"A\" { modifiers= Shift } };")
+                .unwrap_err()
+                .message,
+            "expected `;`, found `}`"
+        );
+        assert_eq!(
+            parse("xkb_keycodes { <A> = ; };").unwrap_err().message,
+            "expected a keycode, found `;`"
+        );
+        assert_eq!(
+            parse("xkb_symbols { key <A> { [ a ] };")
+                .unwrap_err()
+                .message,
+            "unexpected end of input"
+        );
+        assert!(parse("xkb_foo { };").is_err());
+    }
+}
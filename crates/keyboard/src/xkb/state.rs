@@ -0,0 +1,429 @@
+//! Following key presses through a keymap.
+
+use super::keymap::{mod_masks, Action, Group, Keymap, Level, TypeEntry};
+use crate::{keysym, scan_codes, KeyEvent, KeyState, Modifiers};
+
+/// A key that's held, and what pressing it did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HeldKey {
+    keycode: u32,
+    action: Action,
+    /// For [`Action::LockMods`], which of its modifiers were already locked.
+    was_locked: u8,
+    /// Whether another key has been pressed since this one was.
+    interrupted: bool,
+}
+
+/// Which modifiers are held, latched and locked, and which group is active, with the keymap they
+/// apply to.
+///
+/// There are two ways to keep it up to date:
+///
+/// - [`update_key`](Self::update_key) with every key press and release, which runs the keys'
+///   actions like the X server does.
+/// - [`update_mask`](Self::update_mask), with the masks from the compositor's
+///   `wl_keyboard.modifiers` events, which is what Wayland clients should do.
+///
+/// Look up a key's keysym before updating the state with it, so a latched modifier applies to the
+/// key that uses it up. [`key_event`](Self::key_event) does both in the right order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct State {
+    keymap: Keymap,
+    held: Vec<HeldKey>,
+    depressed_mods: u8,
+    latched_mods: u8,
+    locked_mods: u8,
+    base_group: i32,
+    latched_group: i32,
+    locked_group: i32,
+}
+
+impl State {
+    /// A state with nothing held, latched or locked, in the first group.
+    pub fn new(keymap: Keymap) -> Self {
+        Self {
+            keymap,
+            held: Vec::new(),
+            depressed_mods: 0,
+            latched_mods: 0,
+            locked_mods: 0,
+            base_group: 0,
+            latched_group: 0,
+            locked_group: 0,
+        }
+    }
+
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    /// The real modifiers that are active, whether they're held, latched or locked.
+    pub fn mods(&self) -> u8 {
+        self.depressed_mods | self.latched_mods | self.locked_mods
+    }
+
+    pub fn locked_mods(&self) -> u8 {
+        self.locked_mods
+    }
+
+    /// The active group, counting from 0.
+    pub fn group(&self) -> usize {
+        wrap(
+            self.base_group + self.latched_group + self.locked_group,
+            self.keymap.num_groups,
+        )
+    }
+
+    /// Sets the state from a `wl_keyboard.modifiers` event.
+    pub fn update_mask(&mut self, depressed: u32, latched: u32, locked: u32, group: u32) {
+        self.held.clear();
+        self.depressed_mods = depressed as u8;
+        self.latched_mods = latched as u8;
+        self.locked_mods = locked as u8;
+        self.base_group = 0;
+        self.latched_group = 0;
+        self.locked_group = wrap(group as i32, self.keymap.num_groups) as i32;
+    }
+
+    /// Runs the action of a key that was pressed or released.
+    ///
+    /// **Returns:** Whether the event is an auto-repeat, i.e. a press of a key that's already
+    /// held. Repeats don't run the action again.
+    pub fn update_key(&mut self, keycode: u32, state: KeyState) -> bool {
+        let held = self.held.iter().position(|key| key.keycode == keycode);
+        match (state, held) {
+            (KeyState::Pressed, Some(_)) => return true,
+            (KeyState::Pressed, None) => self.press(keycode),
+            (KeyState::Released, Some(i)) => {
+                let key = self.held.remove(i);
+                self.release(key);
+            }
+            (KeyState::Released, None) => {}
+        }
+        self.update_derived();
+        false
+    }
+
+    fn press(&mut self, keycode: u32) {
+        for key in &mut self.held {
+            key.interrupted = true;
+        }
+        let action = self.level(keycode).map_or(Action::None, |l| l.action);
+        let mut was_locked = 0;
+        match action {
+            Action::None => {
+                // an ordinary key uses up the latches
+                self.latched_mods = 0;
+                self.latched_group = 0;
+            }
+            Action::LockMods { mods } => {
+                was_locked = self.locked_mods & mods as u8;
+                self.locked_mods |= mods as u8;
+            }
+            Action::LockGroup { group, absolute } => {
+                let group = if absolute {
+                    group
+                } else {
+                    self.locked_group + group
+                };
+                self.locked_group = wrap(group, self.keymap.num_groups) as i32;
+            }
+            _ => {}
+        }
+        self.held.push(HeldKey {
+            keycode,
+            action,
+            was_locked,
+            interrupted: false,
+        });
+    }
+
+    fn release(&mut self, key: HeldKey) {
+        match key.action {
+            Action::SetMods { mods, clear_locks } if clear_locks && !key.interrupted => {
+                self.locked_mods &= !(mods as u8);
+            }
+            Action::LatchMods {
+                mods,
+                clear_locks,
+                latch_to_lock,
+            } if !key.interrupted => {
+                let mods = mods as u8;
+                if clear_locks && self.locked_mods & mods != 0 {
+                    self.locked_mods &= !mods;
+                } else if latch_to_lock && self.latched_mods & mods != 0 {
+                    self.latched_mods &= !mods;
+                    self.locked_mods |= mods;
+                } else {
+                    self.latched_mods |= mods;
+                }
+            }
+            Action::LockMods { .. } => self.locked_mods &= !key.was_locked,
+            Action::SetGroup { clear_locks, .. } if clear_locks && !key.interrupted => {
+                self.locked_group = 0;
+            }
+            Action::LatchGroup { group, absolute } if !key.interrupted => {
+                self.latched_group = if absolute {
+                    group
+                } else {
+                    group + self.latched_group
+                };
+            }
+            _ => {}
+        }
+    }
+
+    /// Works out the held modifiers and group from the keys that are held.
+    fn update_derived(&mut self) {
+        self.depressed_mods = 0;
+        self.base_group = 0;
+        for key in &self.held {
+            match key.action {
+                Action::SetMods { mods, .. }
+                | Action::LatchMods { mods, .. }
+                | Action::LockMods { mods } => self.depressed_mods |= mods as u8,
+                Action::SetGroup {
+                    group,
+                    absolute: true,
+                    ..
+                }
+                | Action::LatchGroup {
+                    group,
+                    absolute: true,
+                } => self.base_group = group,
+                Action::SetGroup { group, .. } | Action::LatchGroup { group, .. } => {
+                    self.base_group += group;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The group of a key that's active, and the entry of its type that the modifiers match.
+    fn lookup(&self, keycode: u32) -> Option<(&Group, u8, Option<&TypeEntry>)> {
+        let key = self.keymap.keys.get(&keycode)?;
+        let group = key
+            .groups
+            .get(wrap(self.group() as i32, key.groups.len()))?;
+        let key_type = &self.keymap.types[group.key_type];
+        let mods = self.mods() & key_type.mods;
+        let entry = key_type.entries.iter().find(|entry| entry.mods == mods);
+        Some((group, key_type.mods, entry))
+    }
+
+    fn level(&self, keycode: u32) -> Option<&Level> {
+        let (group, _, entry) = self.lookup(keycode)?;
+        group.levels.get(entry.map_or(0, |e| e.level))
+    }
+
+    /// The shift level that a key is at with the active modifiers, counting from 0.
+    pub fn key_level(&self, keycode: u32) -> usize {
+        self.lookup(keycode)
+            .and_then(|(_, _, entry)| entry)
+            .map_or(0, |entry| entry.level)
+    }
+
+    /// The modifiers that were used up picking a key's level, so shouldn't count towards a
+    /// shortcut with it. Shift is used up by `!` on a US keyboard, for example, so shift+1 is `!`
+    /// and not shift+`!`.
+    pub fn consumed_mods(&self, keycode: u32) -> u8 {
+        match self.lookup(keycode) {
+            Some((_, type_mods, entry)) => type_mods & !entry.map_or(0, |e| e.preserve),
+            None => 0,
+        }
+    }
+
+    /// The keysym a key types with the active modifiers and group.
+    ///
+    /// Caps lock capitalises it if the key's type doesn't use caps lock itself, like for `é` on a
+    /// French keyboard.
+    pub fn keysym(&self, keycode: u32) -> u32 {
+        let Some(level) = self.level(keycode) else {
+            return keysym::NO_SYMBOL;
+        };
+        let lock_unused = self.consumed_mods(keycode) & mod_masks::LOCK == 0;
+        if self.mods() & mod_masks::LOCK != 0 && lock_unused {
+            keysym::to_upper(level.keysym)
+        } else {
+            level.keysym
+        }
+    }
+
+    /// The text a key press types, if it types any. Nothing's typed while control or alt is held,
+    /// since those are shortcuts.
+    pub fn text(&self, keycode: u32) -> Option<char> {
+        let shortcut = Modifiers::CONTROL | Modifiers::ALT;
+        if self.modifiers().bits() & shortcut.bits() != 0 {
+            return None;
+        }
+        keysym::to_char(self.keysym(keycode))
+    }
+
+    /// The active modifiers, as the platform-independent [`Modifiers`]. Alt, super and num lock
+    /// are whichever real modifiers the keymap makes them.
+    pub fn modifiers(&self) -> Modifiers {
+        let mask = |name, default| match self.keymap.mod_mask(name) {
+            Some(mask) if mask != 0 => mask,
+            _ => default,
+        };
+        let (mods, locked) = (self.mods(), self.locked_mods);
+        let mut modifiers = Modifiers::NONE;
+        for (active, modifier) in [
+            (mods & mod_masks::SHIFT, Modifiers::SHIFT),
+            (mods & mod_masks::CONTROL, Modifiers::CONTROL),
+            (mods & mask("Alt", mod_masks::MOD1), Modifiers::ALT),
+            (mods & mask("Super", mod_masks::MOD4), Modifiers::SUPER),
+            (locked & mod_masks::LOCK, Modifiers::CAPS_LOCK),
+            (
+                locked & mask("NumLock", mod_masks::MOD2),
+                Modifiers::NUM_LOCK,
+            ),
+        ] {
+            if active != 0 {
+                modifiers |= modifier;
+            }
+        }
+        modifiers
+    }
+
+    /// Decodes a key press or release, and then updates the state with it.
+    ///
+    /// Keycodes are XKB's, which are evdev's plus 8 (the same as X11's).
+    pub fn key_event(&mut self, keycode: u32, state: KeyState) -> KeyEvent {
+        let logical_key = keysym::to_key(self.keysym(keycode));
+        let repeat = self.update_key(keycode, state);
+        KeyEvent {
+            physical_key: scan_codes::from_x11_keycode(keycode),
+            logical_key,
+            state,
+            repeat,
+            modifiers: self.modifiers(),
+        }
+    }
+}
+
+/// Wraps a group into the range of `count` groups, like XKB does by default.
+fn wrap(group: i32, count: usize) -> usize {
+    match count {
+        0 => 0,
+        count => group.rem_euclid(count as i32) as usize,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use KeyState::*;
+
+    const LFSH: u32 = 50;
+    const LATCH: u32 = 51;
+    const CAPS: u32 = 66;
+    const GROUP: u32 = 52;
+    const A: u32 = 38;
+
+    fn state() -> State {
+        State::new(
+            Keymap::parse(
+                "xkb_keymap {
+                xkb_keycodes { <LFSH> = 50; <BKSL> = 51; <AB01> = 52; <CAPS> = 66; <AC01> = 38; };
+                xkb_types {
+                    type \"ONE_LEVEL\" { modifiers= none; };
+                    type \"ALPHABETIC\" { modifiers= Shift+Lock; map[Shift]= 2; map[Lock]= 2; };
+                };
+                xkb_compatibility {
+                    interpret ISO_Level2_Latch {
+                        action= LatchMods(modifiers=Shift,clearLocks,latchToLock);
+                    };
+                    interpret ISO_Group_Latch { action= LatchGroup(group=+1); };
+                    interpret Caps_Lock { action= LockMods(modifiers=Lock); };
+                    interpret Any+AnyOf(all) { action= SetMods(modifiers=modMapMods,clearLocks); };
+                };
+                xkb_symbols {
+                    key <AC01> { [ a, A ], [ b, B ] };
+                    key <LFSH> { [ Shift_L ] };
+                    key <BKSL> { [ ISO_Level2_Latch ] };
+                    key <AB01> { [ ISO_Group_Latch ] };
+                    key <CAPS> { [ Caps_Lock ] };
+                    modifier_map Shift { <LFSH> };
+                };
+                };",
+            )
+            .unwrap(),
+        )
+    }
+
+    fn tap(state: &mut State, keycode: u32) {
+        state.update_key(keycode, Pressed);
+        state.update_key(keycode, Released);
+    }
+
+    #[test]
+    fn latches() {
+        let mut state = state();
+        tap(&mut state, LATCH);
+        assert_eq!(state.mods(), mod_masks::SHIFT);
+        assert_eq!(state.locked_mods(), 0);
+        // the latch applies to the next key, and then it's used up
+        assert_eq!(
+            state.key_event(A, Pressed).logical_key,
+            crate::Key::Character('A')
+        );
+        state.update_key(A, Released);
+        assert_eq!(state.mods(), 0);
+
+        // latching twice locks, and latching again unlocks
+        tap(&mut state, LATCH);
+        tap(&mut state, LATCH);
+        assert_eq!(state.locked_mods(), mod_masks::SHIFT);
+        tap(&mut state, A);
+        assert_eq!(state.mods(), mod_masks::SHIFT);
+        tap(&mut state, LATCH);
+        assert_eq!(state.mods(), 0);
+
+        // a latch key that's used as a modifier doesn't latch
+        state.update_key(LATCH, Pressed);
+        assert_eq!(state.keysym(A), u32::from('A'));
+        tap(&mut state, A);
+        state.update_key(LATCH, Released);
+        assert_eq!(state.mods(), 0);
+
+        tap(&mut state, GROUP);
+        assert_eq!(state.group(), 1);
+        assert_eq!(state.keysym(A), u32::from('b'));
+        tap(&mut state, A);
+        assert_eq!(state.group(), 0);
+    }
+
+    #[test]
+    fn locks() {
+        let mut state = state();
+        tap(&mut state, CAPS);
+        assert_eq!(state.locked_mods(), mod_masks::LOCK);
+        // the lock counts as held while its key is down, even while unlocking it
+        state.update_key(CAPS, Pressed);
+        assert_eq!(state.mods(), mod_masks::LOCK);
+        state.update_key(CAPS, Released);
+        assert_eq!(state.mods(), 0);
+
+        // shift clears the locks it sets, when it's tapped on its own
+        tap(&mut state, LATCH);
+        tap(&mut state, LATCH);
+        assert_eq!(state.locked_mods(), mod_masks::SHIFT);
+        state.update_key(LFSH, Pressed);
+        tap(&mut state, A);
+        state.update_key(LFSH, Released);
+        assert_eq!(state.locked_mods(), mod_masks::SHIFT);
+        tap(&mut state, LFSH);
+        assert_eq!(state.locked_mods(), 0);
+    }
+
+    #[test]
+    fn releases_without_presses() {
+        let mut state = state();
+        assert!(!state.update_key(LFSH, Released));
+        assert_eq!(state.mods(), 0);
+        assert!(!state.update_key(LFSH, Pressed));
+        assert!(state.update_key(LFSH, Pressed));
+        assert_eq!(state.mods(), mod_masks::SHIFT);
+    }
+}
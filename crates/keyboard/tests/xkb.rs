@@ -0,0 +1,314 @@
+//! The XKB interpreter against whole keymaps, as `xkbcli compile-keymap` (and so a Wayland
+//! compositor) would write them.
+
+extern crate triangle_from_scratch_keyboard as keyboard;
+
+use keyboard::{
+    keysym,
+    xkb::{mod_masks, Keymap, State},
+    Key, KeyCode, KeyState, Modifiers, NamedKey,
+};
+
+const US: &str = include_str!("keymaps/us.xkb");
+/// German and US layouts, with caps lock switching between them and shift+caps lock being caps
+/// lock.
+const DE_US: &str = include_str!("keymaps/de.xkb");
+const FR_AZERTY: &str = include_str!("keymaps/fr-azerty.xkb");
+
+/// Presses and releases keys by name.
+struct Keyboard(State);
+
+impl Keyboard {
+    fn new(text: &str) -> Self {
+        Self(State::new(Keymap::parse(text).unwrap()))
+    }
+
+    fn keycode(&self, name: &str) -> u32 {
+        self.0.keymap().keycode(name).unwrap()
+    }
+
+    fn press(&mut self, name: &str) -> Key {
+        let keycode = self.keycode(name);
+        self.0.key_event(keycode, KeyState::Pressed).logical_key
+    }
+
+    fn release(&mut self, name: &str) {
+        let keycode = self.keycode(name);
+        self.0.key_event(keycode, KeyState::Released);
+    }
+
+    /// Presses and releases a key, and returns what it meant.
+    fn tap(&mut self, name: &str) -> Key {
+        let key = self.press(name);
+        self.release(name);
+        key
+    }
+
+    /// Taps a key with others held.
+    fn tap_with(&mut self, held: &[&str], name: &str) -> Key {
+        for held in held {
+            self.press(held);
+        }
+        let key = self.tap(name);
+        for held in held.iter().rev() {
+            self.release(held);
+        }
+        key
+    }
+
+    /// Taps each key, and returns the text they typed.
+    fn type_keys(&mut self, names: &[&str]) -> String {
+        names
+            .iter()
+            .filter_map(|name| {
+                let keycode = self.keycode(name);
+                let text = self.0.text(keycode);
+                self.tap(name);
+                text
+            })
+            .collect()
+    }
+}
+
+fn ch(c: char) -> Key {
+    Key::Character(c)
+}
+
+#[test]
+fn us_keymap() {
+    let keymap = Keymap::parse(US).unwrap();
+    assert_eq!((keymap.min_keycode(), keymap.max_keycode()), (8, 255));
+    assert_eq!(keymap.group_names(), ["English (US)"]);
+    assert_eq!(keymap.num_groups(), 1);
+    assert_eq!(keymap.keycode("AC01"), Some(38));
+    assert_eq!(keymap.keycode("ALGR"), Some(108));
+    assert_eq!(keymap.keysym(38, 0, 1), Some(u32::from('A')));
+    assert_eq!(keymap.keysym(38, 0, 2), None);
+    assert_eq!(keymap.keysym(38, 1, 0), None);
+
+    // the virtual modifiers get bound through the modifier keys' interprets
+    assert_eq!(keymap.mod_mask("Shift"), Some(mod_masks::SHIFT));
+    assert_eq!(keymap.mod_mask("Alt"), Some(mod_masks::MOD1));
+    assert_eq!(keymap.mod_mask("Meta"), Some(mod_masks::MOD1));
+    assert_eq!(keymap.mod_mask("NumLock"), Some(mod_masks::MOD2));
+    assert_eq!(keymap.mod_mask("Super"), Some(mod_masks::MOD4));
+    assert_eq!(keymap.mod_mask("LevelThree"), Some(mod_masks::MOD5));
+    assert_eq!(keymap.mod_mask("Hyper"), Some(0));
+    assert_eq!(keymap.mod_mask("Nonsense"), None);
+}
+
+#[test]
+fn us_typing() {
+    let mut kb = Keyboard::new(US);
+    assert_eq!(kb.tap("AC01"), ch('a'));
+    assert_eq!(kb.tap_with(&["LFSH"], "AC01"), ch('A'));
+    assert_eq!(kb.tap_with(&["RTSH"], "AE01"), ch('!'));
+    assert_eq!(kb.tap("SPCE"), ch(' '));
+    assert_eq!(kb.tap("RTRN"), Key::Named(NamedKey::Enter));
+    assert_eq!(kb.tap("FK05"), Key::Named(NamedKey::F(5)));
+    assert_eq!(
+        kb.type_keys(&["AD05", "AD03", "AC02", "AD05", "AE01"]),
+        "test1"
+    );
+
+    // shift is used up by `!`, but not by keys that are the same with or without it
+    kb.press("LFSH");
+    assert_eq!(kb.0.consumed_mods(kb.keycode("AE01")), mod_masks::SHIFT);
+    assert_eq!(kb.0.consumed_mods(kb.keycode("SPCE")), 0);
+    assert_eq!(kb.0.key_level(kb.keycode("AE01")), 1);
+    kb.release("LFSH");
+
+    // the physical key doesn't depend on the layout
+    let event = kb.0.key_event(38, KeyState::Pressed);
+    assert_eq!(event.physical_key, KeyCode::KeyA);
+    assert!(!event.repeat);
+    assert!(kb.0.key_event(38, KeyState::Pressed).repeat);
+    assert!(!kb.0.key_event(38, KeyState::Released).repeat);
+}
+
+#[test]
+fn us_caps_lock() {
+    let mut kb = Keyboard::new(US);
+    kb.tap("CAPS");
+    assert_eq!(kb.0.locked_mods(), mod_masks::LOCK);
+    assert_eq!(kb.0.modifiers(), Modifiers::CAPS_LOCK);
+    assert_eq!(kb.tap("AC01"), ch('A'));
+    assert_eq!(kb.tap("AE01"), ch('1'));
+    // shift undoes caps lock on letters, but not on anything else
+    assert_eq!(kb.tap_with(&["LFSH"], "AC01"), ch('a'));
+    assert_eq!(kb.tap_with(&["LFSH"], "AE01"), ch('!'));
+
+    kb.tap("CAPS");
+    assert_eq!(kb.0.locked_mods(), 0);
+    assert_eq!(kb.tap("AC01"), ch('a'));
+}
+
+#[test]
+fn us_keypad() {
+    let mut kb = Keyboard::new(US);
+    assert_eq!(kb.tap("KP7"), Key::Named(NamedKey::Home));
+    assert_eq!(kb.tap("KPAD"), ch('+'));
+
+    kb.tap("NMLK");
+    assert_eq!(kb.0.locked_mods(), mod_masks::MOD2);
+    assert_eq!(kb.0.modifiers(), Modifiers::NUM_LOCK);
+    assert_eq!(kb.tap("KP7"), ch('7'));
+    assert_eq!(kb.tap("KPDL"), ch('.'));
+    // shift undoes num lock
+    assert_eq!(kb.tap_with(&["LFSH"], "KP7"), Key::Named(NamedKey::Home));
+
+    kb.tap("NMLK");
+    assert_eq!(kb.tap("KP7"), Key::Named(NamedKey::Home));
+}
+
+#[test]
+fn us_shortcuts() {
+    let mut kb = Keyboard::new(US);
+    kb.press("LCTL");
+    kb.press("LALT");
+    assert_eq!(kb.0.modifiers(), Modifiers::CONTROL | Modifiers::ALT);
+    // shortcuts don't type anything, but still have their keys
+    assert_eq!(kb.0.text(kb.keycode("AC01")), None);
+    assert_eq!(kb.press("AC01"), ch('a'));
+    // the keysym that ctrl+alt+F1 would switch terminals with isn't one that's known
+    assert_eq!(kb.press("FK01"), Key::Unidentified);
+    kb.release("FK01");
+    kb.release("AC01");
+    kb.release("LALT");
+    assert_eq!(kb.0.modifiers(), Modifiers::CONTROL);
+    kb.release("LCTL");
+    assert_eq!(kb.0.modifiers(), Modifiers::NONE);
+
+    // types can use virtual modifiers, like alt picking print screen's second level
+    assert_eq!(kb.tap("PRSC"), Key::Named(NamedKey::PrintScreen));
+    kb.press("RALT");
+    assert_eq!(
+        kb.0.keysym(kb.keycode("PRSC")),
+        keysym::from_name("Sys_Req").unwrap()
+    );
+    kb.release("RALT");
+
+    assert_eq!(kb.tap_with(&["LWIN"], "AC01"), ch('a'));
+    kb.press("RWIN");
+    assert_eq!(kb.0.modifiers(), Modifiers::SUPER);
+    kb.release("RWIN");
+}
+
+#[test]
+fn us_update_mask() {
+    let mut kb = Keyboard::new(US);
+    kb.press("LCTL");
+    // the compositor's masks replace everything, including the keys that were held
+    kb.0.update_mask(u32::from(mod_masks::SHIFT), 0, 0, 0);
+    assert_eq!(kb.0.modifiers(), Modifiers::SHIFT);
+    assert_eq!(kb.0.keysym(38), u32::from('A'));
+    kb.0.update_mask(0, 0, u32::from(mod_masks::LOCK | mod_masks::MOD2), 0);
+    assert_eq!(kb.0.modifiers(), Modifiers::CAPS_LOCK | Modifiers::NUM_LOCK);
+    assert_eq!(kb.0.keysym(38), u32::from('A'));
+    assert_eq!(
+        kb.0.keysym(kb.keycode("KP7")),
+        keysym::from_name("KP_7").unwrap()
+    );
+    // a group past the last one wraps around
+    kb.0.update_mask(0, 0, 0, 3);
+    assert_eq!(kb.0.group(), 0);
+}
+
+#[test]
+fn de_keymap() {
+    let keymap = Keymap::parse(DE_US).unwrap();
+    assert_eq!(keymap.group_names(), ["German", "English (US)"]);
+    assert_eq!(keymap.num_groups(), 2);
+    assert_eq!(keymap.mod_mask("LevelThree"), Some(mod_masks::MOD5));
+}
+
+#[test]
+fn de_levels() {
+    let mut kb = Keyboard::new(DE_US);
+    assert_eq!(kb.tap("AD06"), ch('z'));
+    assert_eq!(kb.tap("AB01"), ch('y'));
+    assert_eq!(kb.tap("AE11"), ch('ß'));
+    assert_eq!(kb.tap_with(&["LFSH"], "AE11"), ch('?'));
+    // right alt is AltGr, which is level three through its interpret rather than the modifier map
+    assert_eq!(kb.tap_with(&["RALT"], "AD01"), ch('@'));
+    assert_eq!(kb.tap_with(&["RALT"], "AD03"), ch('€'));
+    assert_eq!(kb.tap_with(&["RALT", "LFSH"], "AD01"), ch('Ω'));
+    assert_eq!(kb.tap_with(&["LVL3"], "AE07"), ch('{'));
+    assert_eq!(kb.tap_with(&["RALT"], "AE11"), ch('\\'));
+    // AltGr isn't alt, so it types
+    kb.press("RALT");
+    assert_eq!(kb.0.modifiers(), Modifiers::NONE);
+    assert_eq!(kb.0.text(kb.keycode("AD06")), Some('←'));
+    kb.release("RALT");
+
+    assert_eq!(kb.tap("TLDE"), Key::Dead('^'));
+    assert_eq!(kb.tap("AE12"), Key::Dead('´'));
+    assert_eq!(kb.tap_with(&["LFSH"], "AE12"), Key::Dead('`'));
+    assert_eq!(kb.tap_with(&["RALT"], "AD11"), Key::Dead('¨'));
+    assert_eq!(kb.0.text(kb.keycode("TLDE")), None);
+}
+
+#[test]
+fn de_groups() {
+    let mut kb = Keyboard::new(DE_US);
+    assert_eq!(kb.0.group(), 0);
+    kb.tap("CAPS");
+    assert_eq!(kb.0.group(), 1);
+    assert_eq!(kb.0.locked_mods(), 0);
+    assert_eq!(kb.tap("AD06"), ch('y'));
+    assert_eq!(kb.tap("TLDE"), ch('`'));
+    assert_eq!(kb.tap_with(&["LFSH"], "AE02"), ch('@'));
+    // the US layout has no third level
+    assert_eq!(kb.tap_with(&["RALT"], "AD01"), ch('q'));
+    // the keys with one group are the same in both
+    assert_eq!(kb.tap("SPCE"), ch(' '));
+    assert_eq!(kb.tap("KPAD"), ch('+'));
+
+    // the last group wraps around to the first
+    kb.tap("CAPS");
+    assert_eq!(kb.0.group(), 0);
+    assert_eq!(kb.tap("AD06"), ch('z'));
+}
+
+#[test]
+fn de_caps_lock() {
+    let mut kb = Keyboard::new(DE_US);
+    kb.tap_with(&["LFSH"], "CAPS");
+    assert_eq!(kb.0.group(), 0);
+    assert_eq!(kb.0.locked_mods(), mod_masks::LOCK);
+    assert_eq!(kb.type_keys(&["AD11", "AC01", "AE11", "AE01"]), "ÜAß1");
+    // level three keeps caps lock on semi-alphabetic keys, so it capitalises `ł`
+    assert_eq!(kb.tap_with(&["RALT"], "AD02"), ch('Ł'));
+    assert_eq!(kb.tap_with(&["RALT"], "AC01"), ch('Æ'));
+
+    kb.tap_with(&["LFSH"], "CAPS");
+    assert_eq!(kb.0.locked_mods(), 0);
+    assert_eq!(kb.tap("AD11"), ch('ü'));
+}
+
+#[test]
+fn fr_azerty() {
+    let mut kb = Keyboard::new(FR_AZERTY);
+    assert_eq!(kb.0.keymap().group_names(), ["French (AZERTY)"]);
+    assert_eq!(
+        kb.type_keys(&["AD01", "AC10", "AD02", "AB01", "AC01"]),
+        "amzwq"
+    );
+    let event = kb.0.key_event(kb.keycode("AD01"), KeyState::Pressed);
+    assert_eq!(event.physical_key, KeyCode::KeyQ);
+    assert_eq!(event.logical_key, ch('a'));
+
+    // the digits are on the shift level
+    assert_eq!(kb.type_keys(&["AE01", "AE02", "AE03", "AE07"]), "&é\"è");
+    assert_eq!(kb.tap_with(&["LFSH"], "AE01"), ch('1'));
+    assert_eq!(kb.tap_with(&["LFSH"], "AE02"), ch('2'));
+    assert_eq!(kb.tap_with(&["RALT"], "AE10"), ch('@'));
+    assert_eq!(kb.tap_with(&["RALT"], "AE02"), ch('~'));
+    assert_eq!(kb.tap("AD11"), Key::Dead('^'));
+    assert_eq!(kb.tap_with(&["LFSH"], "AD11"), Key::Dead('¨'));
+
+    // caps lock isn't part of the digit keys' type, so it capitalises the accented letters on them
+    kb.tap("CAPS");
+    assert_eq!(kb.type_keys(&["AE02", "AE07", "AE09", "AD01"]), "ÉÈÇA");
+    assert_eq!(kb.tap_with(&["LFSH"], "AE02"), ch('2'));
+}
@@ -0,0 +1,55 @@
+//! Linux constants.
+//!
+//! Unless otherwise specified, all constants are from the glibc and kernel UAPI headers
+//! (`sys/inotify.h`, `bits/fcntl-linux.h`, `bits/poll.h`, `linux/time.h`).
+
+use c_types::*;
+
+use crate::typedefs::*;
+
+// inotify_init1 flags
+pub const IN_NONBLOCK: CInt = 0o4000;
+pub const IN_CLOEXEC: CInt = 0o2000000;
+
+// inotify events
+pub const IN_ACCESS: u32 = 0x00000001;
+pub const IN_MODIFY: u32 = 0x00000002;
+pub const IN_ATTRIB: u32 = 0x00000004;
+pub const IN_CLOSE_WRITE: u32 = 0x00000008;
+pub const IN_CLOSE_NOWRITE: u32 = 0x00000010;
+pub const IN_CLOSE: u32 = IN_CLOSE_WRITE | IN_CLOSE_NOWRITE;
+pub const IN_OPEN: u32 = 0x00000020;
+pub const IN_MOVED_FROM: u32 = 0x00000040;
+pub const IN_MOVED_TO: u32 = 0x00000080;
+pub const IN_MOVE: u32 = IN_MOVED_FROM | IN_MOVED_TO;
+pub const IN_CREATE: u32 = 0x00000100;
+pub const IN_DELETE: u32 = 0x00000200;
+pub const IN_DELETE_SELF: u32 = 0x00000400;
+pub const IN_MOVE_SELF: u32 = 0x00000800;
+
+// inotify events sent by the kernel on its own
+pub const IN_UNMOUNT: u32 = 0x00002000;
+pub const IN_Q_OVERFLOW: u32 = 0x00004000;
+pub const IN_IGNORED: u32 = 0x00008000;
+
+// inotify_add_watch flags
+pub const IN_ONLYDIR: u32 = 0x01000000;
+pub const IN_DONT_FOLLOW: u32 = 0x02000000;
+pub const IN_EXCL_UNLINK: u32 = 0x04000000;
+pub const IN_MASK_CREATE: u32 = 0x10000000;
+pub const IN_MASK_ADD: u32 = 0x20000000;
+pub const IN_ISDIR: u32 = 0x40000000;
+pub const IN_ONESHOT: u32 = 0x80000000;
+
+// poll events
+pub const POLLIN: CShort = 0x001;
+pub const POLLPRI: CShort = 0x002;
+pub const POLLOUT: CShort = 0x004;
+pub const POLLERR: CShort = 0x008;
+pub const POLLHUP: CShort = 0x010;
+pub const POLLNVAL: CShort = 0x020;
+
+// clocks
+/// A clock that only ever goes forwards, from some unspecified point (usually boot), and isn't
+/// affected by changes to the system time.
+pub const CLOCK_MONOTONIC: clockid_t = 1;
@@ -0,0 +1,22 @@
+//! Bindings to functions exported by the C library.
+
+use super::{structs::*, typedefs::*};
+use c_types::*;
+
+#[link(name = "c")]
+extern "C" {
+    /// See [`clock_gettime(2)`](https://man7.org/linux/man-pages/man2/clock_gettime.2.html).
+    pub fn clock_gettime(clockid: clockid_t, tp: *mut timespec) -> CInt;
+
+    /// See [`inotify_add_watch(2)`](https://man7.org/linux/man-pages/man2/inotify_add_watch.2.html).
+    pub fn inotify_add_watch(fd: CInt, pathname: *const CChar, mask: u32) -> CInt;
+
+    /// See [`inotify_init1(2)`](https://man7.org/linux/man-pages/man2/inotify_init1.2.html).
+    pub fn inotify_init1(flags: CInt) -> CInt;
+
+    /// See [`inotify_rm_watch(2)`](https://man7.org/linux/man-pages/man2/inotify_rm_watch.2.html).
+    pub fn inotify_rm_watch(fd: CInt, wd: CInt) -> CInt;
+
+    /// See [`poll(2)`](https://man7.org/linux/man-pages/man2/poll.2.html).
+    pub fn poll(fds: *mut pollfd, nfds: nfds_t, timeout: CInt) -> CInt;
+}
@@ -0,0 +1,340 @@
+#![cfg(target_os = "linux")]
+
+//! Bindings to the bits of the Linux kernel and C library that the standard library doesn't
+//! already cover, like `inotify`.
+
+// C names are very incompatible with Rust's default lints, so we have to disable some of them.
+#![allow(non_camel_case_types, non_upper_case_globals)]
+
+use std::{
+    ffi::{CString, OsStr, OsString},
+    fs::File,
+    io::{self, Read},
+    os::{
+        fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+        unix::ffi::{OsStrExt, OsStringExt},
+    },
+    path::Path,
+    time::Duration,
+};
+
+use c_types::*;
+
+pub mod constants;
+pub mod extern_bindings;
+pub mod prelude;
+pub mod structs;
+pub mod typedefs;
+
+use prelude::*;
+
+/// Turns a `-1` return value into the current `errno`.
+fn check(ret: CInt) -> io::Result<CInt> {
+    if ret == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+/// The time on [`CLOCK_MONOTONIC`], since some unspecified point before the process started.
+///
+/// See [`clock_gettime(2)`](https://man7.org/linux/man-pages/man2/clock_gettime.2.html)
+pub fn monotonic_time() -> Duration {
+    let mut time = timespec::default();
+    // Safety: `time` is valid to write to, and the monotonic clock always exists, so this can't
+    // fail
+    unsafe { clock_gettime(CLOCK_MONOTONIC, &mut time) };
+    Duration::new(time.tv_sec as u64, time.tv_nsec as u32)
+}
+
+/// Waits until `fd` has data to read, or until `timeout` passes.
+///
+/// - A `timeout` of `None` waits forever, and `Some(Duration::ZERO)` returns immediately.
+/// - Returns whether the file descriptor is readable.
+///
+/// See [`poll(2)`](https://man7.org/linux/man-pages/man2/poll.2.html)
+pub fn poll_readable(fd: RawFd, timeout: Option<Duration>) -> io::Result<bool> {
+    let timeout = match timeout {
+        None => -1,
+        // round up, so that tiny timeouts don't turn into a busy loop
+        Some(t) => t
+            .as_nanos()
+            .div_ceil(1_000_000)
+            .try_into()
+            .unwrap_or(CInt::MAX),
+    };
+    let mut fds = [pollfd {
+        fd,
+        events: POLLIN,
+        revents: 0,
+    }];
+
+    loop {
+        // Safety: `fds` is a valid array of the length we pass in.
+        match check(unsafe { poll(fds.as_mut_ptr(), fds.len(), timeout) }) {
+            Ok(0) => return Ok(false),
+            Ok(_) => return Ok(fds[0].revents & POLLIN != 0),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// A single event read from an [`Inotify`] instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InotifyEvent {
+    /// The watch descriptor the event is for, as returned by [`Inotify::add_watch`].
+    pub wd: CInt,
+    /// A mask of `IN_*` bits describing the event.
+    pub mask: u32,
+    /// Connects the two halves of a rename (`IN_MOVED_FROM` and `IN_MOVED_TO`).
+    pub cookie: u32,
+    /// The name of the file inside a watched directory that the event refers to, if any.
+    pub name: Option<OsString>,
+}
+
+impl InotifyEvent {
+    /// Parses every event in a buffer filled by a `read` from an inotify file descriptor.
+    ///
+    /// The kernel only ever hands out whole events, so a truncated event at the end of the buffer
+    /// means the buffer didn't come from inotify. It's ignored.
+    pub fn parse_all(mut buf: &[u8]) -> Vec<Self> {
+        const HEADER_LEN: usize = core::mem::size_of::<inotify_event>();
+
+        let mut events = Vec::new();
+        while buf.len() >= HEADER_LEN {
+            // Safety: we just checked there are enough bytes, and `inotify_event` is plain old
+            // data. The buffer is a byte slice, so it might not be aligned.
+            let header: inotify_event =
+                unsafe { buf.as_ptr().cast::<inotify_event>().read_unaligned() };
+            let Some(name) = buf.get(HEADER_LEN..HEADER_LEN + header.len as usize) else {
+                break;
+            };
+
+            // the name is padded with nulls up to an alignment boundary
+            let name = match name.iter().position(|&b| b == 0) {
+                Some(end) => &name[..end],
+                None => name,
+            };
+            events.push(Self {
+                wd: header.wd,
+                mask: header.mask,
+                cookie: header.cookie,
+                name: (!name.is_empty()).then(|| OsString::from_vec(name.to_vec())),
+            });
+
+            buf = &buf[HEADER_LEN + header.len as usize..];
+        }
+        events
+    }
+}
+
+/// An inotify instance, which reports changes to the files and directories it's watching.
+///
+/// The file descriptor is non-blocking and closed on drop (and on `exec`).
+///
+/// See [`inotify(7)`](https://man7.org/linux/man-pages/man7/inotify.7.html)
+#[derive(Debug)]
+pub struct Inotify {
+    fd: File,
+}
+
+impl Inotify {
+    /// Creates a new, non-blocking inotify instance.
+    ///
+    /// See [`inotify_init1(2)`](https://man7.org/linux/man-pages/man2/inotify_init1.2.html)
+    pub fn new() -> io::Result<Self> {
+        // Safety: inotify_init1 has no preconditions.
+        let fd = check(unsafe { inotify_init1(IN_NONBLOCK | IN_CLOEXEC) })?;
+        // Safety: the file descriptor was just created, and nothing else owns it.
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        Ok(Self { fd: fd.into() })
+    }
+
+    /// Starts watching `path` for the events in `mask`, returning a watch descriptor.
+    ///
+    /// Watching a path that's already watched replaces its mask (unless `mask` contains
+    /// [`IN_MASK_ADD`]) and returns the same descriptor.
+    ///
+    /// See [`inotify_add_watch(2)`](https://man7.org/linux/man-pages/man2/inotify_add_watch.2.html)
+    pub fn add_watch(&self, path: &Path, mask: u32) -> io::Result<CInt> {
+        let path = cstring(path.as_os_str())?;
+        // Safety: `path` is null-terminated and outlives the call.
+        check(unsafe { inotify_add_watch(self.fd.as_raw_fd(), path.as_ptr(), mask) })
+    }
+
+    /// Stops watching the path associated with `wd`.
+    ///
+    /// The kernel queues an [`IN_IGNORED`] event for the watch afterwards.
+    ///
+    /// See [`inotify_rm_watch(2)`](https://man7.org/linux/man-pages/man2/inotify_rm_watch.2.html)
+    pub fn rm_watch(&self, wd: CInt) -> io::Result<()> {
+        // Safety: bad watch descriptors are reported as EINVAL.
+        check(unsafe { inotify_rm_watch(self.fd.as_raw_fd(), wd) }).map(drop)
+    }
+
+    /// Reads every event that's currently queued, without blocking.
+    ///
+    /// Returns an empty list if nothing has happened since the last read.
+    pub fn read_events(&self) -> io::Result<Vec<InotifyEvent>> {
+        // Big enough for plenty of events with maximum-length names. Anything left over is picked
+        // up by the next iteration.
+        let mut buf = vec![0u8; 64 * 1024];
+        let mut events = Vec::new();
+        loop {
+            match (&self.fd).read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => events.extend(InotifyEvent::parse_all(&buf[..n])),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(events)
+    }
+
+    /// Waits until there are events to read, or until `timeout` passes.
+    ///
+    /// See [`poll_readable`].
+    pub fn wait(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        poll_readable(self.fd.as_raw_fd(), timeout)
+    }
+}
+
+impl AsRawFd for Inotify {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+/// Converts an `OsStr` into a null-terminated string for passing to C.
+fn cstring(s: &OsStr) -> io::Result<CString> {
+    CString::new(s.as_bytes()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod inotify_event {
+        use super::*;
+
+        fn raw_event(wd: CInt, mask: u32, cookie: u32, name: &[u8]) -> Vec<u8> {
+            let len = if name.is_empty() {
+                0
+            } else {
+                // names are null-terminated, then padded out to 16 bytes
+                (name.len() + 1).next_multiple_of(16)
+            };
+            let mut out = Vec::new();
+            out.extend(wd.to_ne_bytes());
+            out.extend(mask.to_ne_bytes());
+            out.extend(cookie.to_ne_bytes());
+            out.extend((len as u32).to_ne_bytes());
+            out.extend(name);
+            out.resize(out.len() + len - name.len(), 0);
+            out
+        }
+
+        #[test]
+        fn parses_consecutive_events() {
+            let mut buf = raw_event(1, IN_CLOSE_WRITE, 0, b"vertex.vs");
+            buf.extend(raw_event(2, IN_DELETE_SELF, 0, b""));
+            buf.extend(raw_event(
+                1,
+                IN_MOVED_TO,
+                7,
+                b"a-name-longer-than-sixteen-bytes",
+            ));
+
+            assert_eq!(
+                InotifyEvent::parse_all(&buf),
+                [
+                    InotifyEvent {
+                        wd: 1,
+                        mask: IN_CLOSE_WRITE,
+                        cookie: 0,
+                        name: Some("vertex.vs".into()),
+                    },
+                    InotifyEvent {
+                        wd: 2,
+                        mask: IN_DELETE_SELF,
+                        cookie: 0,
+                        name: None,
+                    },
+                    InotifyEvent {
+                        wd: 1,
+                        mask: IN_MOVED_TO,
+                        cookie: 7,
+                        name: Some("a-name-longer-than-sixteen-bytes".into()),
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn ignores_truncated_events() {
+            let mut buf = raw_event(1, IN_MODIFY, 0, b"kept");
+            let second = raw_event(1, IN_MODIFY, 0, b"cut-off");
+            buf.extend(&second[..second.len() - 1]);
+
+            let events = InotifyEvent::parse_all(&buf);
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].name.as_deref(), Some(OsStr::new("kept")));
+
+            assert_eq!(InotifyEvent::parse_all(&buf[..8]), []);
+        }
+    }
+
+    mod inotify {
+        use super::*;
+
+        #[test]
+        fn reports_writes_in_watched_directory() {
+            let dir = std::env::temp_dir().join(format!(
+                "triangle-from-scratch-inotify-{}",
+                std::process::id()
+            ));
+            std::fs::create_dir_all(&dir).unwrap();
+
+            let inotify = Inotify::new().unwrap();
+            let wd = inotify.add_watch(&dir, IN_CLOSE_WRITE).unwrap();
+            assert!(!inotify.wait(Some(Duration::ZERO)).unwrap());
+            assert_eq!(inotify.read_events().unwrap(), []);
+
+            std::fs::write(dir.join("touched"), b"hi").unwrap();
+            assert!(inotify.wait(Some(Duration::from_secs(5))).unwrap());
+            let events = inotify.read_events().unwrap();
+            assert!(
+                events.iter().any(|e| e.wd == wd
+                    && e.mask & IN_CLOSE_WRITE != 0
+                    && e.name.as_deref() == Some(OsStr::new("touched"))),
+                "{events:?}"
+            );
+
+            inotify.rm_watch(wd).unwrap();
+            std::fs::remove_dir_all(&dir).ok();
+        }
+
+        #[test]
+        fn paths_with_nul_bytes_are_rejected() {
+            let inotify = Inotify::new().unwrap();
+            let err = inotify
+                .add_watch(Path::new("bad\0path"), IN_MODIFY)
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn monotonic_time_goes_forwards() {
+        let before = monotonic_time();
+        std::thread::sleep(Duration::from_millis(5));
+        let after = monotonic_time();
+        assert!(
+            after - before >= Duration::from_millis(5),
+            "{before:?} {after:?}"
+        );
+    }
+}
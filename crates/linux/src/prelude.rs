@@ -0,0 +1,4 @@
+pub use super::constants::*;
+pub use super::extern_bindings::*;
+pub use super::structs::*;
+pub use super::typedefs::*;
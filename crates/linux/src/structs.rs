@@ -0,0 +1,48 @@
+//! Linux structures.
+
+use c_types::*;
+
+use crate::typedefs::*;
+
+/// The fixed-size header of an event read from an inotify file descriptor.
+///
+/// Each header is followed by `len` bytes holding the null-padded name of the file the event
+/// refers to, if the watch is on a directory.
+///
+/// [See `inotify(7)`](https://man7.org/linux/man-pages/man7/inotify.7.html).
+#[derive(Debug, Clone, Copy, Default)]
+#[repr(C)]
+pub struct inotify_event {
+    /// The watch descriptor the event is for, as returned by `inotify_add_watch`.
+    pub wd: CInt,
+    /// A mask of `IN_*` bits describing the event.
+    pub mask: u32,
+    /// Connects the two halves of a rename (`IN_MOVED_FROM` and `IN_MOVED_TO`).
+    pub cookie: u32,
+    /// The length of the name that follows, including null padding.
+    pub len: u32,
+}
+
+/// A file descriptor to wait on with `poll`.
+///
+/// [See `poll(2)`](https://man7.org/linux/man-pages/man2/poll.2.html).
+#[derive(Debug, Clone, Copy, Default)]
+#[repr(C)]
+pub struct pollfd {
+    pub fd: CInt,
+    /// The `POLL*` events to wait for.
+    pub events: CShort,
+    /// The `POLL*` events that actually occurred, filled in by `poll`.
+    pub revents: CShort,
+}
+
+/// A time, as seconds and nanoseconds.
+///
+/// [See `clock_gettime(2)`](https://man7.org/linux/man-pages/man2/clock_gettime.2.html).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct timespec {
+    pub tv_sec: time_t,
+    /// Always less than a billion.
+    pub tv_nsec: CLong,
+}
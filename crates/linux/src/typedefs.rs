@@ -0,0 +1,14 @@
+//! Basic Linux/glibc type definitions.
+
+/// The type used for the number of entries passed to [`poll`](super::extern_bindings::poll).
+///
+/// This is an `unsigned long`, which is pointer-sized on every Linux target.
+pub type nfds_t = usize;
+
+/// Identifies a clock for [`clock_gettime`](super::extern_bindings::clock_gettime), like
+/// [`CLOCK_MONOTONIC`](super::constants::CLOCK_MONOTONIC).
+pub type clockid_t = c_types::CInt;
+
+/// Seconds, as a `long` (which is also what the 32-bit targets use, unless glibc is built with
+/// 64-bit times).
+pub type time_t = c_types::CLong;
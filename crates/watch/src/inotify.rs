@@ -0,0 +1,60 @@
+//! The Linux backend, using `inotify`.
+
+use std::{collections::HashMap, io, path::Path, time::Duration};
+
+use c_types::CInt;
+use linux::{prelude::*, Inotify};
+
+use super::{Change, DirKey};
+
+/// The events that mean a file now has new contents.
+///
+/// - `IN_CLOSE_WRITE` covers editors that write in place, and is sent once they're done (unlike
+///   `IN_MODIFY`, which is sent for every `write`).
+/// - `IN_MOVED_TO` covers editors that write a temporary file and rename it over the original.
+/// - `IN_ATTRIB` covers `touch`.
+const MASK: u32 = IN_CLOSE_WRITE | IN_MOVED_TO | IN_ATTRIB | IN_ONLYDIR;
+
+#[derive(Debug)]
+pub struct Backend {
+    inotify: Inotify,
+    /// Maps watch descriptors to the keys handed out for them. The kernel hands out the same
+    /// descriptor for the same directory, however it's spelled.
+    dirs: HashMap<CInt, DirKey>,
+}
+
+impl Backend {
+    pub fn new() -> io::Result<Self> {
+        Ok(Self {
+            inotify: Inotify::new()?,
+            dirs: HashMap::new(),
+        })
+    }
+
+    pub fn add_dir(&mut self, dir: &Path) -> io::Result<DirKey> {
+        let wd = self.inotify.add_watch(dir, MASK)?;
+        let next = self.dirs.len();
+        Ok(*self.dirs.entry(wd).or_insert(next))
+    }
+
+    pub fn read(&mut self, timeout: Option<Duration>) -> io::Result<Vec<Change>> {
+        if !self.inotify.wait(timeout)? {
+            return Ok(Vec::new());
+        }
+
+        let mut changes = Vec::new();
+        for event in self.inotify.read_events()? {
+            if event.mask & IN_Q_OVERFLOW != 0 {
+                changes.extend(self.dirs.values().map(|&dir| Change::Overflow(dir)));
+                continue;
+            }
+            let (Some(&dir), Some(name)) = (self.dirs.get(&event.wd), event.name) else {
+                continue;
+            };
+            if event.mask & IN_ISDIR == 0 {
+                changes.push(Change::File(dir, name));
+            }
+        }
+        Ok(changes)
+    }
+}
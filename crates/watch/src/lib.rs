@@ -0,0 +1,309 @@
+//! Watches files for changes, so that things like shaders can be reloaded while the program is
+//! running.
+//!
+//! Files are watched by watching the directory they live in and filtering by name. That way,
+//! editors that save by writing a temporary file and renaming it over the original are handled the
+//! same as ones that write in place. On Linux this uses `inotify`, and on Windows it uses
+//! `ReadDirectoryChangesW`.
+
+use std::{
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+#[cfg(target_os = "linux")]
+#[path = "inotify.rs"]
+mod backend;
+
+#[cfg(windows)]
+#[path = "read_directory_changes.rs"]
+mod backend;
+
+#[cfg(not(any(target_os = "linux", windows)))]
+#[path = "unsupported.rs"]
+mod backend;
+
+/// Identifies a watched directory within a backend.
+type DirKey = usize;
+
+/// Something a backend noticed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Change {
+    /// The file with this name in the directory was written to or replaced.
+    File(DirKey, OsString),
+    /// Events were dropped, so anything in the directory might have changed.
+    Overflow(DirKey),
+}
+
+/// Reports changes to a set of files.
+///
+/// ```no_run
+/// use std::{path::Path, time::Duration};
+/// use triangle_from_scratch_watch::FileWatcher;
+///
+/// let mut watcher = FileWatcher::new()?;
+/// watcher.watch(Path::new("src/vertex.vs"))?;
+/// watcher.watch(Path::new("src/fragment.fs"))?;
+///
+/// loop {
+///     for path in watcher.wait(Some(Duration::from_secs(1)))? {
+///         println!("{} changed", path.display());
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct FileWatcher {
+    backend: backend::Backend,
+    files: Vec<WatchedFile>,
+}
+
+#[derive(Debug)]
+struct WatchedFile {
+    dir: DirKey,
+    name: OsString,
+    /// The path as it was given to [`FileWatcher::watch`], which is what gets reported back.
+    path: PathBuf,
+}
+
+impl FileWatcher {
+    /// Creates a watcher that isn't watching anything yet.
+    pub fn new() -> io::Result<Self> {
+        Ok(Self {
+            backend: backend::Backend::new()?,
+            files: Vec::new(),
+        })
+    }
+
+    /// Starts watching the file at `path`.
+    ///
+    /// - The directory containing the file must exist, but the file itself doesn't have to.
+    /// - Watching the same path twice does nothing.
+    pub fn watch(&mut self, path: &Path) -> io::Result<()> {
+        let name = path.file_name().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} doesn't name a file", path.display()),
+            )
+        })?;
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        if self.files.iter().any(|f| f.path == path) {
+            return Ok(());
+        }
+        let dir = self.backend.add_dir(dir)?;
+        self.files.push(WatchedFile {
+            dir,
+            name: name.to_owned(),
+            path: path.to_owned(),
+        });
+        Ok(())
+    }
+
+    /// The paths being watched, in the order they were added.
+    pub fn watched(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|f| f.path.as_path())
+    }
+
+    /// Returns the watched files that have changed since the last call, without blocking.
+    ///
+    /// Each path is reported at most once per call, in the order it was passed to
+    /// [`watch`](Self::watch).
+    pub fn poll(&mut self) -> io::Result<Vec<PathBuf>> {
+        self.wait(Some(Duration::ZERO))
+    }
+
+    /// Waits until at least one watched file changes, or until `timeout` passes, and returns the
+    /// files that changed.
+    ///
+    /// - A `timeout` of `None` waits forever.
+    /// - Changes to other files in the same directories don't end the wait early, but it may end
+    ///   with an empty list if they arrive close to the timeout.
+    pub fn wait(&mut self, timeout: Option<Duration>) -> io::Result<Vec<PathBuf>> {
+        let deadline = timeout.map(|t| std::time::Instant::now() + t);
+        loop {
+            let remaining =
+                deadline.map(|d| d.saturating_duration_since(std::time::Instant::now()));
+            let changes = self.backend.read(remaining)?;
+            let changed = self.match_changes(&changes);
+
+            let timed_out = remaining.is_some_and(|r| r.is_zero());
+            if !changed.is_empty() || changes.is_empty() || timed_out {
+                return Ok(changed);
+            }
+        }
+    }
+
+    /// Works out which watched files a batch of changes refers to.
+    fn match_changes(&self, changes: &[Change]) -> Vec<PathBuf> {
+        self.files
+            .iter()
+            .filter(|file| {
+                changes.iter().any(|change| match change {
+                    Change::File(dir, name) => *dir == file.dir && *name == file.name,
+                    Change::Overflow(dir) => *dir == file.dir,
+                })
+            })
+            .map(|file| file.path.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// A fresh, empty directory that's removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "triangle-from-scratch-watch-{name}-{}",
+                std::process::id()
+            ));
+            fs::remove_dir_all(&dir).ok();
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            fs::remove_dir_all(&self.0).ok();
+        }
+    }
+
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+    mod file_watcher {
+        use super::*;
+
+        #[test]
+        fn reports_writes_to_watched_files() {
+            let dir = TempDir::new("writes");
+            let vert = dir.0.join("vertex.vs");
+            let frag = dir.0.join("fragment.fs");
+            fs::write(&vert, "a").unwrap();
+            fs::write(&frag, "a").unwrap();
+
+            let mut watcher = FileWatcher::new().unwrap();
+            watcher.watch(&vert).unwrap();
+            watcher.watch(&frag).unwrap();
+            assert_eq!(watcher.poll().unwrap(), Vec::<PathBuf>::new());
+
+            fs::write(&frag, "b").unwrap();
+            assert_eq!(watcher.wait(TIMEOUT).unwrap(), std::slice::from_ref(&frag));
+            assert_eq!(watcher.poll().unwrap(), Vec::<PathBuf>::new());
+
+            // several writes between polls are reported once, in the order the files were watched
+            fs::write(&frag, "c").unwrap();
+            fs::write(&vert, "b").unwrap();
+            fs::write(&frag, "d").unwrap();
+            let mut changed = watcher.wait(TIMEOUT).unwrap();
+            if changed.len() < 2 {
+                changed.extend(watcher.wait(TIMEOUT).unwrap());
+            }
+            assert_eq!(changed, [vert, frag]);
+        }
+
+        #[test]
+        fn ignores_other_files_in_the_directory() {
+            let dir = TempDir::new("others");
+            let watched = dir.0.join("watched.glsl");
+
+            let mut watcher = FileWatcher::new().unwrap();
+            watcher.watch(&watched).unwrap();
+
+            fs::write(dir.0.join("unrelated.glsl"), "a").unwrap();
+            assert_eq!(
+                watcher.wait(Some(Duration::from_millis(100))).unwrap(),
+                Vec::<PathBuf>::new()
+            );
+
+            // the watched file didn't exist to begin with
+            fs::write(&watched, "a").unwrap();
+            assert_eq!(watcher.wait(TIMEOUT).unwrap(), [watched]);
+        }
+
+        #[test]
+        fn reports_files_replaced_by_renaming() {
+            let dir = TempDir::new("rename");
+            let watched = dir.0.join("shader.fs");
+            fs::write(&watched, "old").unwrap();
+
+            let mut watcher = FileWatcher::new().unwrap();
+            watcher.watch(&watched).unwrap();
+
+            // what editors that save atomically do
+            let tmp = dir.0.join(".shader.fs.swp");
+            fs::write(&tmp, "new").unwrap();
+            fs::rename(&tmp, &watched).unwrap();
+
+            assert_eq!(
+                watcher.wait(TIMEOUT).unwrap(),
+                std::slice::from_ref(&watched)
+            );
+            assert_eq!(fs::read_to_string(&watched).unwrap(), "new");
+        }
+
+        #[test]
+        fn watching_twice_is_harmless() {
+            let dir = TempDir::new("twice");
+            let watched = dir.0.join("twice.vs");
+
+            let mut watcher = FileWatcher::new().unwrap();
+            watcher.watch(&watched).unwrap();
+            watcher.watch(&watched).unwrap();
+            assert_eq!(watcher.watched().count(), 1);
+
+            fs::write(&watched, "a").unwrap();
+            assert_eq!(watcher.wait(TIMEOUT).unwrap(), [watched]);
+        }
+
+        #[test]
+        fn missing_directories_are_an_error() {
+            let dir = TempDir::new("missing");
+            let mut watcher = FileWatcher::new().unwrap();
+            let err = watcher
+                .watch(&dir.0.join("does-not-exist").join("shader.vs"))
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::NotFound);
+            assert_eq!(watcher.watched().count(), 0);
+        }
+
+        #[test]
+        fn paths_without_a_file_name_are_rejected() {
+            let mut watcher = FileWatcher::new().unwrap();
+            let err = watcher.watch(Path::new("/")).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    mod match_changes {
+        use super::*;
+
+        #[test]
+        fn overflow_reports_everything_in_the_directory() {
+            let dir = TempDir::new("overflow");
+            let other = TempDir::new("overflow-other");
+
+            let mut watcher = FileWatcher::new().unwrap();
+            let a = dir.0.join("a");
+            let b = dir.0.join("b");
+            let c = other.0.join("c");
+            for path in [&a, &b, &c] {
+                watcher.watch(path).unwrap();
+            }
+
+            let key = watcher.files[0].dir;
+            assert_eq!(watcher.match_changes(&[Change::Overflow(key)]), [a, b]);
+        }
+    }
+}
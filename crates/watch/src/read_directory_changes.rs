@@ -0,0 +1,219 @@
+//! The Windows backend, using `ReadDirectoryChangesW` with overlapped I/O.
+
+use std::{
+    ffi::OsString,
+    io,
+    os::windows::ffi::{OsStrExt, OsStringExt},
+    path::{Path, PathBuf},
+    ptr,
+    time::Duration,
+};
+
+use win32::prelude::*;
+
+use super::{Change, DirKey};
+
+/// The changes that mean a file now has new contents, or was renamed into place.
+const FILTER: DWORD = FILE_NOTIFY_CHANGE_FILE_NAME | FILE_NOTIFY_CHANGE_LAST_WRITE;
+
+/// How many `DWORD`s of notifications to buffer per directory. The buffer has to be
+/// `DWORD`-aligned, hence not just using bytes.
+const BUFFER_DWORDS: usize = 16 * 1024;
+
+#[derive(Debug, Default)]
+pub struct Backend {
+    dirs: Vec<Dir>,
+}
+
+/// A directory with a `ReadDirectoryChangesW` call that's always in flight.
+#[derive(Debug)]
+struct Dir {
+    /// The canonical path, so the same directory isn't watched twice.
+    path: PathBuf,
+    handle: HANDLE,
+    /// Boxed, along with the buffer, so their addresses stay put while the system writes to them.
+    overlapped: Box<OVERLAPPED>,
+    buffer: Box<[DWORD; BUFFER_DWORDS]>,
+}
+
+impl Dir {
+    fn open(path: PathBuf) -> io::Result<Self> {
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+
+        // Safety: `wide` is null-terminated and outlives the call, and every other argument is a
+        // constant or null.
+        let handle = unsafe {
+            CreateFileW(
+                wide.as_ptr(),
+                FILE_LIST_DIRECTORY,
+                FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+                ptr::null_mut(),
+                OPEN_EXISTING,
+                FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OVERLAPPED,
+                ptr::null_mut(),
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+
+        // A manual-reset event, as overlapped I/O requires. Starting a read resets it.
+        // Safety: all of the arguments are constants or null.
+        let event = unsafe { CreateEventW(ptr::null_mut(), 1, 0, ptr::null()) };
+        if event.is_null() {
+            let e = io::Error::last_os_error();
+            // Safety: we just opened the handle and nothing else has it.
+            unsafe { CloseHandle(handle) };
+            return Err(e);
+        }
+
+        let mut dir = Self {
+            path,
+            handle,
+            overlapped: Box::new(OVERLAPPED {
+                hEvent: event,
+                ..Default::default()
+            }),
+            buffer: Box::new([0; BUFFER_DWORDS]),
+        };
+        dir.start_read()?;
+        Ok(dir)
+    }
+
+    /// Asks for the next batch of changes. This completes in the background, signaling the event.
+    fn start_read(&mut self) -> io::Result<()> {
+        // Safety: the buffer and the OVERLAPPED are boxed, so they won't move until `self` is
+        // dropped, and dropping cancels the read and waits for it to finish.
+        let ok = unsafe {
+            ReadDirectoryChangesW(
+                self.handle,
+                self.buffer.as_mut_ptr().cast(),
+                (BUFFER_DWORDS * core::mem::size_of::<DWORD>()) as DWORD,
+                0,
+                FILTER,
+                ptr::null_mut(),
+                &mut *self.overlapped,
+                None,
+            )
+        };
+        if ok == 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Collects the results of the read in flight if it's finished, and starts the next one.
+    fn collect(&mut self, key: DirKey, changes: &mut Vec<Change>) -> io::Result<()> {
+        let mut bytes = 0;
+        // Safety: the handle and the OVERLAPPED belong to the same read.
+        let ok = unsafe { GetOverlappedResult(self.handle, &mut *self.overlapped, &mut bytes, 0) };
+        if ok == 0 {
+            // Safety: per MSDN, this should always work.
+            match unsafe { GetLastError() } {
+                ERROR_IO_INCOMPLETE => return Ok(()),
+                ERROR_NOTIFY_ENUM_DIR => changes.push(Change::Overflow(key)),
+                e => return Err(io::Error::from_raw_os_error(e as i32)),
+            }
+        } else if bytes == 0 {
+            // the buffer was too small to hold everything that happened
+            changes.push(Change::Overflow(key));
+        } else {
+            // Safety: the system just finished writing `bytes` bytes of records into the buffer.
+            unsafe { parse_notifications(self.buffer.as_ptr().cast(), key, changes) };
+        }
+        self.start_read()
+    }
+}
+
+impl Drop for Dir {
+    fn drop(&mut self) {
+        // Safety: the handles are ours. The read has to be finished (or cancelled) before the
+        // buffer and OVERLAPPED can be freed, so we wait for it.
+        unsafe {
+            CancelIoEx(self.handle, &mut *self.overlapped);
+            let mut bytes = 0;
+            GetOverlappedResult(self.handle, &mut *self.overlapped, &mut bytes, 1);
+            CloseHandle(self.handle);
+            CloseHandle(self.overlapped.hEvent);
+        }
+    }
+}
+
+/// Walks the chain of [`FILE_NOTIFY_INFORMATION`] records at `p`.
+///
+/// ## Safety
+///
+/// `p` must point to a valid, `DWORD`-aligned chain of records written by
+/// `ReadDirectoryChangesW`.
+unsafe fn parse_notifications(mut p: *const u8, key: DirKey, changes: &mut Vec<Change>) {
+    loop {
+        let info = p.cast::<FILE_NOTIFY_INFORMATION>();
+        let name = core::slice::from_raw_parts(
+            ptr::addr_of!((*info).FileName).cast::<u16>(),
+            (*info).FileNameLength as usize / 2,
+        );
+        match (*info).Action {
+            FILE_ACTION_ADDED | FILE_ACTION_MODIFIED | FILE_ACTION_RENAMED_NEW_NAME => {
+                changes.push(Change::File(key, OsString::from_wide(name)));
+            }
+            _ => (),
+        }
+
+        match (*info).NextEntryOffset {
+            0 => break,
+            offset => p = p.add(offset as usize),
+        }
+    }
+}
+
+impl Backend {
+    pub fn new() -> io::Result<Self> {
+        Ok(Self::default())
+    }
+
+    pub fn add_dir(&mut self, dir: &Path) -> io::Result<DirKey> {
+        let path = dir.canonicalize()?;
+        if let Some(key) = self.dirs.iter().position(|d| d.path == path) {
+            return Ok(key);
+        }
+        if self.dirs.len() >= MAXIMUM_WAIT_OBJECTS as usize {
+            return Err(io::Error::other("too many directories are being watched"));
+        }
+        self.dirs.push(Dir::open(path)?);
+        Ok(self.dirs.len() - 1)
+    }
+
+    pub fn read(&mut self, timeout: Option<Duration>) -> io::Result<Vec<Change>> {
+        if self.dirs.is_empty() {
+            std::thread::sleep(timeout.unwrap_or_default());
+            return Ok(Vec::new());
+        }
+
+        let timeout = match timeout {
+            None => INFINITE,
+            // round up, so that tiny timeouts don't turn into a busy loop
+            Some(t) => t
+                .as_nanos()
+                .div_ceil(1_000_000)
+                .try_into()
+                .unwrap_or(INFINITE - 1)
+                .min(INFINITE - 1),
+        };
+        let events: Vec<HANDLE> = self.dirs.iter().map(|d| d.overlapped.hEvent).collect();
+        // Safety: the events are valid, and there are no more than MAXIMUM_WAIT_OBJECTS of them.
+        match unsafe { WaitForMultipleObjects(events.len() as DWORD, events.as_ptr(), 0, timeout) }
+        {
+            WAIT_TIMEOUT => return Ok(Vec::new()),
+            WAIT_FAILED => return Err(io::Error::last_os_error()),
+            _ => (),
+        }
+
+        // more than one directory may be ready, so check them all
+        let mut changes = Vec::new();
+        for (key, dir) in self.dirs.iter_mut().enumerate() {
+            dir.collect(key, &mut changes)?;
+        }
+        Ok(changes)
+    }
+}
@@ -0,0 +1,25 @@
+//! The fallback for platforms without a backend. Creating a watcher always fails.
+
+use std::{io, path::Path, time::Duration};
+
+use super::{Change, DirKey};
+
+#[derive(Debug)]
+pub struct Backend(());
+
+impl Backend {
+    pub fn new() -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "file watching isn't supported on this platform",
+        ))
+    }
+
+    pub fn add_dir(&mut self, _dir: &Path) -> io::Result<DirKey> {
+        unreachable!("a Backend can't be created on this platform")
+    }
+
+    pub fn read(&mut self, _timeout: Option<Duration>) -> io::Result<Vec<Change>> {
+        unreachable!("a Backend can't be created on this platform")
+    }
+}
@@ -0,0 +1,310 @@
+//! A connection to a Wayland compositor over its Unix socket.
+//!
+//! Requests are written straight away. Events are queued as they're read, and
+//! [`roundtrip`](Connection::roundtrip) waits for the compositor to have handled every request so
+//! far. Errors sent to the `wl_display` are fatal, so they're returned from whichever call reads
+//! them.
+//!
+//! No interface bound here sends file descriptors, so they're never received.
+
+use core::{fmt, time::Duration};
+use std::{
+    collections::VecDeque,
+    env,
+    io::{self, Read, Write},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use crate::{
+    protocol::{self, parse_callback_done, DisplayEvent, RegistryEvent, DISPLAY},
+    wire::{Message, ObjectId, ParseError},
+};
+
+/// Anything that can go wrong while talking to a compositor.
+#[derive(Debug)]
+pub enum Error {
+    /// `$XDG_RUNTIME_DIR` isn't set, so there's nowhere to look for the compositor's socket.
+    NoRuntimeDir,
+    Io(io::Error),
+    /// The compositor sent something that couldn't be decoded.
+    Parse(ParseError),
+    /// The compositor reported a fatal error with a request to `object`, and has closed the
+    /// connection.
+    Protocol {
+        object: ObjectId,
+        code: u32,
+        message: String,
+    },
+    /// The compositor doesn't have a global that's needed.
+    MissingGlobal(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoRuntimeDir => write!(f, "no Wayland socket ($XDG_RUNTIME_DIR isn't set)"),
+            Self::Io(e) => write!(f, "Wayland connection: {e}"),
+            Self::Parse(e) => e.fmt(f),
+            Self::Protocol {
+                object,
+                code,
+                message,
+            } => write!(
+                f,
+                "Wayland protocol error {code} for object {object}: {message}"
+            ),
+            Self::MissingGlobal(interface) => {
+                write!(f, "Wayland compositor doesn't have {interface}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+/// A global object the compositor offers, from the registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Global {
+    pub name: u32,
+    pub interface: String,
+    pub version: u32,
+}
+
+/// The registry, with the globals it listed when it was created.
+#[derive(Debug, Clone)]
+pub struct Registry {
+    pub id: ObjectId,
+    pub globals: Vec<Global>,
+}
+
+impl Registry {
+    /// Binds the first global with `interface`, at its version or `max_version`, whichever is
+    /// lower.
+    ///
+    /// **Returns:** The new object, and the version it was bound at.
+    pub fn bind(
+        &self,
+        conn: &mut Connection,
+        interface: &'static str,
+        max_version: u32,
+    ) -> Result<(ObjectId, u32), Error> {
+        let global = self
+            .globals
+            .iter()
+            .find(|global| global.interface == interface)
+            .ok_or(Error::MissingGlobal(interface))?;
+        let version = global.version.min(max_version);
+        let id = conn.new_id();
+        conn.send(&protocol::registry_bind(
+            self.id,
+            global.name,
+            interface,
+            version,
+            id,
+        ))?;
+        Ok((id, version))
+    }
+}
+
+/// A connection to a Wayland compositor.
+pub struct Connection {
+    stream: UnixStream,
+    /// The ID the next new object gets. IDs aren't reused.
+    next_id: ObjectId,
+    /// Bytes read from the compositor that don't make up a whole message yet.
+    input: Vec<u8>,
+    /// Events that have been read but not handed out, in the order they arrived.
+    events: VecDeque<Message>,
+}
+
+impl fmt::Debug for Connection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Connection")
+            .field("next_id", &self.next_id)
+            .field("queued", &self.events.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Connection {
+    /// Connects to the compositor named by `$WAYLAND_DISPLAY` (or `wayland-0`), whose socket is
+    /// in `$XDG_RUNTIME_DIR` unless the name is an absolute path.
+    pub fn connect() -> Result<Self, Error> {
+        let name = env::var_os("WAYLAND_DISPLAY").unwrap_or_else(|| "wayland-0".into());
+        let path = if Path::new(&name).is_absolute() {
+            PathBuf::from(name)
+        } else {
+            let dir = env::var_os("XDG_RUNTIME_DIR").ok_or(Error::NoRuntimeDir)?;
+            Path::new(&dir).join(name)
+        };
+        Self::connect_to(&path)
+    }
+
+    /// Connects to the compositor listening on the socket at `path`.
+    pub fn connect_to(path: &Path) -> Result<Self, Error> {
+        Ok(Self {
+            stream: UnixStream::connect(path)?,
+            next_id: DISPLAY + 1,
+            input: Vec::new(),
+            events: VecDeque::new(),
+        })
+    }
+
+    /// Hands out an ID for a new object.
+    pub fn new_id(&mut self) -> ObjectId {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// Sends a request.
+    pub fn send(&mut self, request: &[u8]) -> Result<(), Error> {
+        self.stream.write_all(request)?;
+        Ok(())
+    }
+
+    /// Waits until the compositor has handled every request sent so far, and sent the events they
+    /// caused. Those events are queued for [`poll_message`](Self::poll_message) and similar.
+    pub fn roundtrip(&mut self) -> Result<(), Error> {
+        let callback = self.new_id();
+        self.send(&protocol::display_sync(callback))?;
+        let mut checked = self.events.len();
+        loop {
+            self.fill(None)?;
+            while let Some(message) = self.events.get(checked) {
+                if message.object == callback && parse_callback_done(message)?.is_some() {
+                    self.events.remove(checked);
+                    return Ok(());
+                }
+                checked += 1;
+            }
+        }
+    }
+
+    /// Creates the registry, and waits for it to list the globals.
+    pub fn registry(&mut self) -> Result<Registry, Error> {
+        let id = self.new_id();
+        self.send(&protocol::display_get_registry(id))?;
+        self.roundtrip()?;
+        let mut globals = Vec::new();
+        let mut i = 0;
+        while let Some(message) = self.events.get(i) {
+            if message.object != id {
+                i += 1;
+                continue;
+            }
+            let message = self.events.remove(i).unwrap();
+            match RegistryEvent::parse(&message)? {
+                Some(RegistryEvent::Global {
+                    name,
+                    interface,
+                    version,
+                }) => globals.push(Global {
+                    name,
+                    interface,
+                    version,
+                }),
+                Some(RegistryEvent::GlobalRemove(name)) => globals.retain(|g| g.name != name),
+                None => {}
+            }
+        }
+        Ok(Registry { id, globals })
+    }
+
+    /// Gets the next event, if there is one, without waiting.
+    pub fn poll_message(&mut self) -> Result<Option<Message>, Error> {
+        self.wait_message_timeout(Duration::ZERO)
+    }
+
+    /// Waits up to `timeout` for the next event.
+    pub fn wait_message_timeout(&mut self, timeout: Duration) -> Result<Option<Message>, Error> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(message) = self.events.pop_front() {
+                return Ok(Some(message));
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if !self.fill(Some(remaining))? {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Reads whatever the compositor has sent, waiting up to `timeout` (or forever) for something
+    /// to arrive, and queues the events in it. Events for the `wl_display` are dealt with here.
+    ///
+    /// **Returns:** Whether anything was read.
+    fn fill(&mut self, timeout: Option<Duration>) -> Result<bool, Error> {
+        let mut buffer = [0; 4096];
+        let result = match timeout {
+            Some(timeout) if timeout.is_zero() => {
+                self.stream.set_nonblocking(true)?;
+                let result = self.stream.read(&mut buffer);
+                self.stream.set_nonblocking(false)?;
+                result
+            }
+            timeout => {
+                self.stream.set_read_timeout(timeout)?;
+                self.stream.read(&mut buffer)
+            }
+        };
+        match result {
+            Ok(0) => return Err(Error::Io(io::ErrorKind::UnexpectedEof.into())),
+            Ok(n) => self.input.extend_from_slice(&buffer[..n]),
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                return Ok(false)
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => return Ok(true),
+            Err(e) => return Err(e.into()),
+        }
+
+        while let Some((message, len)) = Message::split(&self.input)? {
+            self.input.drain(..len);
+            if message.object != DISPLAY {
+                self.events.push_back(message);
+                continue;
+            }
+            // IDs aren't reused, so there's nothing to do with a deleted one
+            if let Some(DisplayEvent::Error {
+                object,
+                code,
+                message,
+            }) = DisplayEvent::parse(&message)?
+            {
+                return Err(Error::Protocol {
+                    object,
+                    code,
+                    message,
+                });
+            }
+        }
+        Ok(true)
+    }
+}
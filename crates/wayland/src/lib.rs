@@ -0,0 +1,18 @@
+//! A small Wayland client, speaking the wire protocol directly over the compositor's Unix socket
+//! instead of going through `libwayland-client`.
+//!
+//! [`wire`] and [`protocol`] are plain Rust that only encode and decode bytes, so they're
+//! available (and tested) everywhere. [`connection`] and [`xdg`] need a Unix socket.
+//!
+//! Only what's needed to put a window in and out of fullscreen is here: the registry, a
+//! `wl_surface` and its `xdg_toplevel`, and `xdg_toplevel.set_fullscreen`.
+
+#[cfg(unix)]
+pub mod connection;
+pub mod protocol;
+pub mod wire;
+#[cfg(unix)]
+pub mod xdg;
+
+#[cfg(unix)]
+pub use connection::{Connection, Error};
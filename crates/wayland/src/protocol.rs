@@ -0,0 +1,351 @@
+//! The parts of the core Wayland protocol and `xdg-shell` that putting a window in and out of
+//! fullscreen needs.
+//!
+//! Requests are encoded by plain functions that return the bytes to send, and events are decoded
+//! from [`Message`]s, so none of this needs a compositor to test. Events don't say what interface
+//! they're for, so each interface has its own decoder, for the caller to use on messages for
+//! objects it knows to be of that interface. See the
+//! [protocol documentation](https://wayland.app/protocols/) for what each request and event
+//! means.
+
+use crate::wire::{Message, MessageBuilder, ObjectId, ParseError};
+
+/// The `wl_display`, which always exists and always has this ID.
+pub const DISPLAY: ObjectId = 1;
+
+/// The names of the interfaces that are bound from the registry.
+pub mod interfaces {
+    pub const COMPOSITOR: &str = "wl_compositor";
+    pub const XDG_WM_BASE: &str = "xdg_wm_base";
+}
+
+/// Request opcodes, by interface.
+pub mod opcodes {
+    pub const DISPLAY_SYNC: u16 = 0;
+    pub const DISPLAY_GET_REGISTRY: u16 = 1;
+
+    pub const REGISTRY_BIND: u16 = 0;
+
+    pub const COMPOSITOR_CREATE_SURFACE: u16 = 0;
+
+    pub const SURFACE_DESTROY: u16 = 0;
+    pub const SURFACE_COMMIT: u16 = 6;
+
+    pub const WM_BASE_GET_XDG_SURFACE: u16 = 2;
+    pub const WM_BASE_PONG: u16 = 3;
+
+    pub const XDG_SURFACE_DESTROY: u16 = 0;
+    pub const XDG_SURFACE_GET_TOPLEVEL: u16 = 1;
+    pub const XDG_SURFACE_ACK_CONFIGURE: u16 = 4;
+
+    pub const TOPLEVEL_DESTROY: u16 = 0;
+    pub const TOPLEVEL_SET_TITLE: u16 = 2;
+    pub const TOPLEVEL_SET_FULLSCREEN: u16 = 11;
+    pub const TOPLEVEL_UNSET_FULLSCREEN: u16 = 12;
+}
+
+/// The values in an `xdg_toplevel.configure`'s `states` array.
+pub mod toplevel_states {
+    pub const MAXIMIZED: u32 = 1;
+    pub const FULLSCREEN: u32 = 2;
+    pub const RESIZING: u32 = 3;
+    pub const ACTIVATED: u32 = 4;
+}
+
+pub fn display_sync(callback: ObjectId) -> Vec<u8> {
+    MessageBuilder::new(DISPLAY, opcodes::DISPLAY_SYNC)
+        .object(Some(callback))
+        .finish()
+}
+
+pub fn display_get_registry(registry: ObjectId) -> Vec<u8> {
+    MessageBuilder::new(DISPLAY, opcodes::DISPLAY_GET_REGISTRY)
+        .object(Some(registry))
+        .finish()
+}
+
+/// Binds the global called `name` as `id`. The new ID goes with its interface and version, since
+/// `wl_registry.bind` can make an object of any interface.
+pub fn registry_bind(
+    registry: ObjectId,
+    name: u32,
+    interface: &str,
+    version: u32,
+    id: ObjectId,
+) -> Vec<u8> {
+    MessageBuilder::new(registry, opcodes::REGISTRY_BIND)
+        .u32(name)
+        .string(interface)
+        .u32(version)
+        .object(Some(id))
+        .finish()
+}
+
+pub fn compositor_create_surface(compositor: ObjectId, surface: ObjectId) -> Vec<u8> {
+    MessageBuilder::new(compositor, opcodes::COMPOSITOR_CREATE_SURFACE)
+        .object(Some(surface))
+        .finish()
+}
+
+pub fn surface_destroy(surface: ObjectId) -> Vec<u8> {
+    MessageBuilder::new(surface, opcodes::SURFACE_DESTROY).finish()
+}
+
+pub fn surface_commit(surface: ObjectId) -> Vec<u8> {
+    MessageBuilder::new(surface, opcodes::SURFACE_COMMIT).finish()
+}
+
+pub fn wm_base_get_xdg_surface(
+    wm_base: ObjectId,
+    xdg_surface: ObjectId,
+    surface: ObjectId,
+) -> Vec<u8> {
+    MessageBuilder::new(wm_base, opcodes::WM_BASE_GET_XDG_SURFACE)
+        .object(Some(xdg_surface))
+        .object(Some(surface))
+        .finish()
+}
+
+pub fn wm_base_pong(wm_base: ObjectId, serial: u32) -> Vec<u8> {
+    MessageBuilder::new(wm_base, opcodes::WM_BASE_PONG)
+        .u32(serial)
+        .finish()
+}
+
+pub fn xdg_surface_destroy(xdg_surface: ObjectId) -> Vec<u8> {
+    MessageBuilder::new(xdg_surface, opcodes::XDG_SURFACE_DESTROY).finish()
+}
+
+pub fn xdg_surface_get_toplevel(xdg_surface: ObjectId, toplevel: ObjectId) -> Vec<u8> {
+    MessageBuilder::new(xdg_surface, opcodes::XDG_SURFACE_GET_TOPLEVEL)
+        .object(Some(toplevel))
+        .finish()
+}
+
+pub fn xdg_surface_ack_configure(xdg_surface: ObjectId, serial: u32) -> Vec<u8> {
+    MessageBuilder::new(xdg_surface, opcodes::XDG_SURFACE_ACK_CONFIGURE)
+        .u32(serial)
+        .finish()
+}
+
+pub fn toplevel_destroy(toplevel: ObjectId) -> Vec<u8> {
+    MessageBuilder::new(toplevel, opcodes::TOPLEVEL_DESTROY).finish()
+}
+
+pub fn toplevel_set_title(toplevel: ObjectId, title: &str) -> Vec<u8> {
+    MessageBuilder::new(toplevel, opcodes::TOPLEVEL_SET_TITLE)
+        .string(title)
+        .finish()
+}
+
+/// Asks for the toplevel to be made fullscreen on `output`, or on whichever output the compositor
+/// picks (usually the one it's on) if that's `None`.
+pub fn toplevel_set_fullscreen(toplevel: ObjectId, output: Option<ObjectId>) -> Vec<u8> {
+    MessageBuilder::new(toplevel, opcodes::TOPLEVEL_SET_FULLSCREEN)
+        .object(output)
+        .finish()
+}
+
+pub fn toplevel_unset_fullscreen(toplevel: ObjectId) -> Vec<u8> {
+    MessageBuilder::new(toplevel, opcodes::TOPLEVEL_UNSET_FULLSCREEN).finish()
+}
+
+/// An event for the `wl_display`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisplayEvent {
+    /// A fatal error, after which the compositor closes the connection.
+    Error {
+        object: ObjectId,
+        code: u32,
+        message: String,
+    },
+    /// An ID the client deleted an object with is free to use again.
+    DeleteId(ObjectId),
+}
+
+impl DisplayEvent {
+    /// Decodes a message for the [`DISPLAY`], or returns `None` for unknown opcodes.
+    pub fn parse(message: &Message) -> Result<Option<Self>, ParseError> {
+        let mut r = message.reader();
+        Ok(match message.opcode {
+            0 => Some(Self::Error {
+                object: r.u32()?,
+                code: r.u32()?,
+                message: r.string()?,
+            }),
+            1 => Some(Self::DeleteId(r.u32()?)),
+            _ => None,
+        })
+    }
+}
+
+/// An event for a `wl_registry`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryEvent {
+    /// A global object that can be bound.
+    Global {
+        name: u32,
+        interface: String,
+        version: u32,
+    },
+    /// A global went away.
+    GlobalRemove(u32),
+}
+
+impl RegistryEvent {
+    pub fn parse(message: &Message) -> Result<Option<Self>, ParseError> {
+        let mut r = message.reader();
+        Ok(match message.opcode {
+            0 => Some(Self::Global {
+                name: r.u32()?,
+                interface: r.string()?,
+                version: r.u32()?,
+            }),
+            1 => Some(Self::GlobalRemove(r.u32()?)),
+            _ => None,
+        })
+    }
+}
+
+/// Decodes a `wl_callback.done`, the callback's only event, into its data.
+pub fn parse_callback_done(message: &Message) -> Result<Option<u32>, ParseError> {
+    match message.opcode {
+        0 => message.reader().u32().map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Decodes an `xdg_wm_base.ping`, its only event, into the serial to answer with.
+pub fn parse_wm_base_ping(message: &Message) -> Result<Option<u32>, ParseError> {
+    match message.opcode {
+        0 => message.reader().u32().map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Decodes an `xdg_surface.configure`, its only event, into the serial to acknowledge.
+pub fn parse_xdg_surface_configure(message: &Message) -> Result<Option<u32>, ParseError> {
+    match message.opcode {
+        0 => message.reader().u32().map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// An event for an `xdg_toplevel`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToplevelEvent {
+    /// The state the toplevel should be in, which takes effect with the next
+    /// `xdg_surface.configure`. A size of zero means the client picks.
+    Configure {
+        width: i32,
+        height: i32,
+        /// Values from [`toplevel_states`].
+        states: Vec<u32>,
+    },
+    /// The user asked to close the window.
+    Close,
+}
+
+impl ToplevelEvent {
+    pub fn parse(message: &Message) -> Result<Option<Self>, ParseError> {
+        let mut r = message.reader();
+        Ok(match message.opcode {
+            0 => Some(Self::Configure {
+                width: r.i32()?,
+                height: r.i32()?,
+                states: r.u32_array()?,
+            }),
+            1 => Some(Self::Close),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(bytes: &[u8]) -> Message {
+        Message::split(bytes).unwrap().unwrap().0
+    }
+
+    #[test]
+    fn fullscreen_requests() {
+        let on_any = split(&toplevel_set_fullscreen(7, None));
+        assert_eq!((on_any.object, on_any.opcode), (7, 11));
+        assert_eq!(on_any.reader().object(), Ok(None));
+
+        let on_output = split(&toplevel_set_fullscreen(7, Some(12)));
+        assert_eq!(on_output.reader().object(), Ok(Some(12)));
+
+        let unset = split(&toplevel_unset_fullscreen(7));
+        assert_eq!((unset.object, unset.opcode), (7, 12));
+        assert!(unset.args.is_empty());
+    }
+
+    #[test]
+    fn binding_a_global() {
+        let bind = split(&registry_bind(2, 5, interfaces::XDG_WM_BASE, 1, 9));
+        let mut r = bind.reader();
+        assert_eq!(r.u32(), Ok(5));
+        assert_eq!(r.string().as_deref(), Ok("xdg_wm_base"));
+        assert_eq!(r.u32(), Ok(1));
+        assert_eq!(r.object(), Ok(Some(9)));
+    }
+
+    #[test]
+    fn events() {
+        let global = MessageBuilder::new(2, 0)
+            .u32(3)
+            .string("wl_compositor")
+            .u32(6)
+            .finish();
+        assert_eq!(
+            RegistryEvent::parse(&split(&global)),
+            Ok(Some(RegistryEvent::Global {
+                name: 3,
+                interface: "wl_compositor".into(),
+                version: 6,
+            }))
+        );
+
+        let states: Vec<u8> = [toplevel_states::FULLSCREEN, toplevel_states::ACTIVATED]
+            .iter()
+            .flat_map(|state| state.to_ne_bytes())
+            .collect();
+        let configure = MessageBuilder::new(8, 0)
+            .i32(1920)
+            .i32(1080)
+            .array(&states)
+            .finish();
+        assert_eq!(
+            ToplevelEvent::parse(&split(&configure)),
+            Ok(Some(ToplevelEvent::Configure {
+                width: 1920,
+                height: 1080,
+                states: vec![2, 4],
+            }))
+        );
+
+        let error = MessageBuilder::new(DISPLAY, 0)
+            .u32(8)
+            .u32(1)
+            .string("bad")
+            .finish();
+        assert_eq!(
+            DisplayEvent::parse(&split(&error)),
+            Ok(Some(DisplayEvent::Error {
+                object: 8,
+                code: 1,
+                message: "bad".into(),
+            }))
+        );
+
+        let ping = MessageBuilder::new(4, 0).u32(77).finish();
+        assert_eq!(parse_wm_base_ping(&split(&ping)), Ok(Some(77)));
+        assert_eq!(
+            parse_wm_base_ping(&split(&MessageBuilder::new(4, 3).finish())),
+            Ok(None)
+        );
+    }
+}
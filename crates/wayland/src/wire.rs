@@ -0,0 +1,287 @@
+//! Encoding and decoding the bytes of the Wayland protocol.
+//!
+//! Every message starts with the ID of the object it's for, then a word holding its size in bytes
+//! (in the high 16 bits) and its opcode (in the low 16 bits). Arguments follow, each padded to a
+//! multiple of four bytes. Everything is in the machine's native byte order, since both ends are
+//! always on the same machine.
+
+use core::fmt;
+
+/// The ID of a protocol object. `0` means "no object", for nullable arguments.
+pub type ObjectId = u32;
+
+/// How long a message header is: the object ID, and the size and opcode.
+pub const HEADER_LEN: usize = 8;
+
+/// How many bytes are needed to pad `len` to a multiple of four.
+pub const fn pad(len: usize) -> usize {
+    (4 - len % 4) % 4
+}
+
+/// Builds a message: a request when sent by a client, or an event when sent by a compositor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageBuilder {
+    bytes: Vec<u8>,
+}
+
+impl MessageBuilder {
+    pub fn new(object: ObjectId, opcode: u16) -> Self {
+        let mut bytes = Vec::with_capacity(32);
+        bytes.extend_from_slice(&object.to_ne_bytes());
+        // the size is filled in by `finish`
+        bytes.extend_from_slice(&u32::from(opcode).to_ne_bytes());
+        Self { bytes }
+    }
+
+    pub fn u32(mut self, value: u32) -> Self {
+        self.bytes.extend_from_slice(&value.to_ne_bytes());
+        self
+    }
+
+    pub fn i32(self, value: i32) -> Self {
+        self.u32(value as u32)
+    }
+
+    /// An object, or a new object's ID. `None` is the null object.
+    pub fn object(self, object: Option<ObjectId>) -> Self {
+        self.u32(object.unwrap_or(0))
+    }
+
+    /// A string, which goes with its length (including the null terminator) in front.
+    pub fn string(mut self, value: &str) -> Self {
+        self = self.u32(value.len() as u32 + 1);
+        self.bytes.extend_from_slice(value.as_bytes());
+        self.bytes.push(0);
+        self.align()
+    }
+
+    /// An array of bytes, which goes with its length in front.
+    pub fn array(mut self, value: &[u8]) -> Self {
+        self = self.u32(value.len() as u32);
+        self.bytes.extend_from_slice(value);
+        self.align()
+    }
+
+    fn align(mut self) -> Self {
+        let n = pad(self.bytes.len());
+        self.bytes.resize(self.bytes.len() + n, 0);
+        self
+    }
+
+    /// Fills in the message's size.
+    ///
+    /// ## Panics
+    ///
+    /// If the message is longer than its 16-bit size can describe.
+    pub fn finish(mut self) -> Vec<u8> {
+        let size = u16::try_from(self.bytes.len()).expect("Wayland message too long");
+        let opcode = u32::from_ne_bytes(self.bytes[4..8].try_into().unwrap());
+        let word = u32::from(size) << 16 | opcode;
+        self.bytes[4..8].copy_from_slice(&word.to_ne_bytes());
+        self.bytes
+    }
+}
+
+/// A message that's been split off the bytes received, with its arguments still encoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub object: ObjectId,
+    pub opcode: u16,
+    /// The arguments, after the header.
+    pub args: Vec<u8>,
+}
+
+impl Message {
+    /// Splits the first whole message off the front of `bytes`.
+    ///
+    /// **Returns:** The message and how many bytes it took up, or `None` if there isn't a whole
+    /// message yet.
+    pub fn split(bytes: &[u8]) -> Result<Option<(Self, usize)>, ParseError> {
+        let Some(header) = bytes.get(..HEADER_LEN) else {
+            return Ok(None);
+        };
+        let object = u32::from_ne_bytes(header[..4].try_into().unwrap());
+        let word = u32::from_ne_bytes(header[4..].try_into().unwrap());
+        let size = (word >> 16) as usize;
+        if size < HEADER_LEN || !size.is_multiple_of(4) {
+            return Err(ParseError::BadSize(size));
+        }
+        let Some(message) = bytes.get(..size) else {
+            return Ok(None);
+        };
+        let message = Self {
+            object,
+            opcode: word as u16,
+            args: message[HEADER_LEN..].to_vec(),
+        };
+        Ok(Some((message, size)))
+    }
+
+    /// A reader for the message's arguments.
+    pub fn reader(&self) -> Reader<'_> {
+        Reader::new(&self.args)
+    }
+}
+
+/// Something that couldn't be decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// Ran out of bytes at `offset`, when `wanted` more were needed.
+    Truncated { offset: usize, wanted: usize },
+    /// A message header gave a size that's too small, or not a multiple of four.
+    BadSize(usize),
+    /// A string wasn't null-terminated UTF-8.
+    BadString { offset: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated { offset, wanted } => write!(
+                f,
+                "truncated Wayland message: wanted {wanted} bytes at offset {offset}"
+            ),
+            Self::BadSize(size) => write!(f, "Wayland message with a bad size of {size} bytes"),
+            Self::BadString { offset } => {
+                write!(f, "Wayland message with a bad string at offset {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Reads a message's arguments, in order.
+#[derive(Debug, Clone)]
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], ParseError> {
+        let error = ParseError::Truncated {
+            offset: self.offset,
+            wanted: n,
+        };
+        let end = self.offset.checked_add(n).ok_or(error)?;
+        let bytes = self.bytes.get(self.offset..end).ok_or(error)?;
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    pub fn u32(&mut self) -> Result<u32, ParseError> {
+        Ok(u32::from_ne_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    pub fn i32(&mut self) -> Result<i32, ParseError> {
+        self.u32().map(|value| value as i32)
+    }
+
+    /// An object, which is `None` for the null object.
+    pub fn object(&mut self) -> Result<Option<ObjectId>, ParseError> {
+        self.u32().map(|id| (id != 0).then_some(id))
+    }
+
+    pub fn string(&mut self) -> Result<String, ParseError> {
+        let offset = self.offset;
+        let bad = ParseError::BadString { offset };
+        let bytes = self.array()?;
+        let (&0, text) = bytes.split_last().ok_or(bad)? else {
+            return Err(bad);
+        };
+        String::from_utf8(text.to_vec()).map_err(|_| bad)
+    }
+
+    pub fn array(&mut self) -> Result<&'a [u8], ParseError> {
+        let len = self.u32()? as usize;
+        let bytes = self.bytes(len)?;
+        self.bytes(pad(len))?;
+        Ok(bytes)
+    }
+
+    /// An array of 32-bit values, like an `xdg_toplevel`'s states.
+    pub fn u32_array(&mut self) -> Result<Vec<u32>, ParseError> {
+        Ok(self
+            .array()?
+            .chunks_exact(4)
+            .map(|value| u32::from_ne_bytes(value.try_into().unwrap()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_ne_bytes()).collect()
+    }
+
+    #[test]
+    fn messages_are_padded_and_sized() {
+        let message = MessageBuilder::new(2, 0)
+            .u32(7)
+            .string("wl_compositor")
+            .u32(4)
+            .object(Some(3))
+            .finish();
+        let mut expected = words(&[2, 40 << 16, 7, 14]);
+        expected.extend_from_slice(b"wl_compositor\0\0\0");
+        expected.extend_from_slice(&words(&[4, 3]));
+        assert_eq!(message, expected);
+
+        assert_eq!(
+            MessageBuilder::new(5, 11).object(None).finish(),
+            words(&[5, 12 << 16 | 11, 0])
+        );
+        assert_eq!(
+            MessageBuilder::new(1, 0).array(&[1, 2]).finish(),
+            words(&[1, 16 << 16, 2, u32::from_ne_bytes([1, 2, 0, 0])])
+        );
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        let mut bytes = MessageBuilder::new(9, 0)
+            .i32(-5)
+            .string("hi")
+            .array(&words(&[2, 4]))
+            .object(None)
+            .finish();
+        let len = bytes.len();
+        // and the start of another one
+        bytes.extend_from_slice(&words(&[1]));
+
+        let (message, size) = Message::split(&bytes).unwrap().unwrap();
+        assert_eq!(size, len);
+        assert_eq!((message.object, message.opcode), (9, 0));
+        let mut r = message.reader();
+        assert_eq!(r.i32(), Ok(-5));
+        assert_eq!(r.string().as_deref(), Ok("hi"));
+        assert_eq!(r.u32_array(), Ok(vec![2, 4]));
+        assert_eq!(r.object(), Ok(None));
+        assert!(r.u32().is_err());
+
+        assert_eq!(Message::split(&bytes[size..]), Ok(None));
+        assert_eq!(Message::split(&bytes[..size - 1]), Ok(None));
+    }
+
+    #[test]
+    fn bad_messages() {
+        assert_eq!(
+            Message::split(&words(&[1, 6 << 16])),
+            Err(ParseError::BadSize(6))
+        );
+        let unterminated = MessageBuilder::new(1, 0).array(b"abc").finish();
+        let (message, _) = Message::split(&unterminated).unwrap().unwrap();
+        assert_eq!(
+            message.reader().string(),
+            Err(ParseError::BadString { offset: 0 })
+        );
+    }
+}
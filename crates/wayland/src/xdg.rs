@@ -0,0 +1,194 @@
+//! Windows, which `xdg-shell` calls toplevels, and switching them in and out of fullscreen.
+
+use crate::{
+    connection::{Connection, Error},
+    protocol::{
+        self, interfaces, parse_wm_base_ping, parse_xdg_surface_configure, toplevel_states,
+        ToplevelEvent,
+    },
+    wire::{Message, ObjectId},
+};
+
+/// The newest versions of the globals that are bound. Nothing newer than the first version of
+/// each is used.
+const COMPOSITOR_VERSION: u32 = 1;
+const WM_BASE_VERSION: u32 = 1;
+
+/// A `wl_surface` with the `xdg_toplevel` role, which keeps track of how the compositor has
+/// configured it.
+///
+/// Hand every message to [`handle_message`](Self::handle_message), which acknowledges the
+/// compositor's configures and answers its pings (a client that doesn't is assumed to be hung).
+#[derive(Debug)]
+pub struct Toplevel {
+    wm_base: ObjectId,
+    surface: ObjectId,
+    xdg_surface: ObjectId,
+    toplevel: ObjectId,
+    /// The size and states from the last `xdg_toplevel.configure`, which take effect with the
+    /// next `xdg_surface.configure`.
+    pending: Option<([i32; 2], Vec<u32>)>,
+    size: [i32; 2],
+    states: Vec<u32>,
+    close_requested: bool,
+}
+
+impl Toplevel {
+    /// Creates a toplevel called `title`, and commits it so that the compositor configures it.
+    pub fn new(conn: &mut Connection, title: &str) -> Result<Self, Error> {
+        let registry = conn.registry()?;
+        let (compositor, _) = registry.bind(conn, interfaces::COMPOSITOR, COMPOSITOR_VERSION)?;
+        let (wm_base, _) = registry.bind(conn, interfaces::XDG_WM_BASE, WM_BASE_VERSION)?;
+
+        let surface = conn.new_id();
+        conn.send(&protocol::compositor_create_surface(compositor, surface))?;
+        let xdg_surface = conn.new_id();
+        conn.send(&protocol::wm_base_get_xdg_surface(
+            wm_base,
+            xdg_surface,
+            surface,
+        ))?;
+        let toplevel = conn.new_id();
+        conn.send(&protocol::xdg_surface_get_toplevel(xdg_surface, toplevel))?;
+        conn.send(&protocol::toplevel_set_title(toplevel, title))?;
+        conn.send(&protocol::surface_commit(surface))?;
+
+        Ok(Self {
+            wm_base,
+            surface,
+            xdg_surface,
+            toplevel,
+            pending: None,
+            size: [0, 0],
+            states: Vec::new(),
+            close_requested: false,
+        })
+    }
+
+    /// The `xdg_toplevel`.
+    pub fn id(&self) -> ObjectId {
+        self.toplevel
+    }
+
+    /// The `wl_surface`, for attaching buffers (or an EGL window) to.
+    pub fn surface(&self) -> ObjectId {
+        self.surface
+    }
+
+    /// The size the compositor last asked for. Zero means it's up to the client.
+    pub fn size(&self) -> [i32; 2] {
+        self.size
+    }
+
+    /// Whether the compositor has made the toplevel fullscreen.
+    pub fn is_fullscreen(&self) -> bool {
+        self.states.contains(&toplevel_states::FULLSCREEN)
+    }
+
+    /// Whether the user has asked to close the window.
+    pub fn close_requested(&self) -> bool {
+        self.close_requested
+    }
+
+    /// Acknowledges configures and answers pings.
+    ///
+    /// **Returns:** Whether the message was for the toplevel. Other messages are left alone.
+    pub fn handle_message(
+        &mut self,
+        conn: &mut Connection,
+        message: &Message,
+    ) -> Result<bool, Error> {
+        if message.object == self.wm_base {
+            if let Some(serial) = parse_wm_base_ping(message)? {
+                conn.send(&protocol::wm_base_pong(self.wm_base, serial))?;
+            }
+        } else if message.object == self.toplevel {
+            match ToplevelEvent::parse(message)? {
+                Some(ToplevelEvent::Configure {
+                    width,
+                    height,
+                    states,
+                }) => self.pending = Some(([width, height], states)),
+                Some(ToplevelEvent::Close) => self.close_requested = true,
+                None => {}
+            }
+        } else if message.object == self.xdg_surface {
+            if let Some(serial) = parse_xdg_surface_configure(message)? {
+                if let Some((size, states)) = self.pending.take() {
+                    self.size = size;
+                    self.states = states;
+                }
+                conn.send(&protocol::xdg_surface_ack_configure(
+                    self.xdg_surface,
+                    serial,
+                ))?;
+                conn.send(&protocol::surface_commit(self.surface))?;
+            }
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Destroys the toplevel and its surface.
+    pub fn destroy(self, conn: &mut Connection) -> Result<(), Error> {
+        conn.send(&protocol::toplevel_destroy(self.toplevel))?;
+        conn.send(&protocol::xdg_surface_destroy(self.xdg_surface))?;
+        conn.send(&protocol::surface_destroy(self.surface))
+    }
+}
+
+/// Switches a toplevel in and out of borderless fullscreen.
+///
+/// The compositor does all the work: it picks the output the window is on, drops any decorations,
+/// and puts the window back where it was afterwards. So there's nothing to remember but whether
+/// fullscreen was asked for. The compositor says when it's actually happened, with a configure
+/// that [`Toplevel::is_fullscreen`] follows.
+#[derive(Debug, Default)]
+pub struct Fullscreen {
+    requested: bool,
+}
+
+impl Fullscreen {
+    pub const fn new() -> Self {
+        Self { requested: false }
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.requested
+    }
+
+    /// Asks for the toplevel to cover its output. Does nothing if it's already been asked.
+    pub fn enter(&mut self, conn: &mut Connection, toplevel: ObjectId) -> Result<(), Error> {
+        if self.requested {
+            return Ok(());
+        }
+        conn.send(&protocol::toplevel_set_fullscreen(toplevel, None))?;
+        self.requested = true;
+        Ok(())
+    }
+
+    /// Asks for the toplevel to go back to how it was before [`enter`](Self::enter). Does nothing
+    /// if it isn't fullscreen.
+    pub fn exit(&mut self, conn: &mut Connection, toplevel: ObjectId) -> Result<(), Error> {
+        if !self.requested {
+            return Ok(());
+        }
+        conn.send(&protocol::toplevel_unset_fullscreen(toplevel))?;
+        self.requested = false;
+        Ok(())
+    }
+
+    /// Enters fullscreen if the toplevel is windowed, and exits it otherwise, like an F11 key
+    /// does.
+    ///
+    /// **Returns:** Whether the toplevel is now fullscreen.
+    pub fn toggle(&mut self, conn: &mut Connection, toplevel: ObjectId) -> Result<bool, Error> {
+        if self.is_fullscreen() {
+            self.exit(conn, toplevel)?;
+        } else {
+            self.enter(conn, toplevel)?;
+        }
+        Ok(self.is_fullscreen())
+    }
+}
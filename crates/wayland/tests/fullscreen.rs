@@ -0,0 +1,334 @@
+#![cfg(unix)]
+
+extern crate triangle_from_scratch_wayland as wayland;
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+    thread::{self, JoinHandle},
+};
+
+use wayland::{
+    protocol::{interfaces, toplevel_states, DISPLAY},
+    wire::{Message, MessageBuilder, ObjectId},
+    xdg::{Fullscreen, Toplevel},
+    Connection, Error,
+};
+
+/// The size the fake compositor's only output is.
+const OUTPUT_SIZE: [i32; 2] = [1280, 1024];
+
+/// A compositor that does just enough for a toplevel to be configured and made fullscreen, and
+/// keeps a log of the requests it got, written like `xdg_toplevel.set_fullscreen(None)`.
+struct FakeCompositor {
+    socket: PathBuf,
+    thread: Option<JoinHandle<Vec<String>>>,
+}
+
+impl FakeCompositor {
+    /// Starts listening on a socket named after `name`, advertising `globals`, for one client.
+    fn start(name: &str, globals: &'static [&'static str]) -> Self {
+        let socket = std::env::temp_dir().join(format!(
+            "triangle-from-scratch-wayland-{name}-{}",
+            std::process::id()
+        ));
+        fs::remove_file(&socket).ok();
+        let listener = UnixListener::bind(&socket).unwrap();
+        let thread = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            Client::new(stream, globals).run()
+        });
+        Self {
+            socket,
+            thread: Some(thread),
+        }
+    }
+
+    fn connect(&self) -> Connection {
+        Connection::connect_to(&self.socket).unwrap()
+    }
+
+    /// Waits for the client to hang up, and returns the requests it sent.
+    fn finish(mut self) -> Vec<String> {
+        self.thread.take().unwrap().join().unwrap()
+    }
+}
+
+impl Drop for FakeCompositor {
+    fn drop(&mut self) {
+        fs::remove_file(&self.socket).ok();
+    }
+}
+
+/// The compositor's end of a connection.
+struct Client {
+    stream: UnixStream,
+    globals: &'static [&'static str],
+    /// The interface of every object the client made.
+    objects: HashMap<ObjectId, &'static str>,
+    fullscreen: bool,
+    configured: bool,
+    serial: u32,
+    log: Vec<String>,
+}
+
+impl Client {
+    fn new(stream: UnixStream, globals: &'static [&'static str]) -> Self {
+        Self {
+            stream,
+            globals,
+            objects: HashMap::from([(DISPLAY, "wl_display")]),
+            fullscreen: false,
+            configured: false,
+            serial: 0,
+            log: Vec::new(),
+        }
+    }
+
+    fn run(mut self) -> Vec<String> {
+        let mut input = Vec::new();
+        let mut buffer = [0; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) | Err(_) => return self.log,
+                Ok(n) => input.extend_from_slice(&buffer[..n]),
+            }
+            while let Some((message, len)) = Message::split(&input).unwrap() {
+                input.drain(..len);
+                self.handle(&message);
+            }
+        }
+    }
+
+    fn send(&mut self, event: Vec<u8>) {
+        self.stream.write_all(&event).unwrap();
+    }
+
+    fn next_serial(&mut self) -> u32 {
+        self.serial += 1;
+        self.serial
+    }
+
+    /// Sends the toplevel's state, starting with a ping that has to be answered.
+    fn configure(&mut self, toplevel: ObjectId) {
+        let (wm_base, xdg_surface) = (self.find("xdg_wm_base"), self.find("xdg_surface"));
+        let serial = self.next_serial();
+        self.send(MessageBuilder::new(wm_base, 0).u32(serial).finish());
+
+        let (size, states) = if self.fullscreen {
+            (OUTPUT_SIZE, vec![toplevel_states::FULLSCREEN])
+        } else {
+            ([0, 0], vec![])
+        };
+        let states: Vec<u8> = states.iter().flat_map(|s| s.to_ne_bytes()).collect();
+        self.send(
+            MessageBuilder::new(toplevel, 0)
+                .i32(size[0])
+                .i32(size[1])
+                .array(&states)
+                .finish(),
+        );
+        let serial = self.next_serial();
+        self.send(MessageBuilder::new(xdg_surface, 0).u32(serial).finish());
+    }
+
+    fn find(&self, interface: &str) -> ObjectId {
+        self.objects
+            .iter()
+            .find(|(_, i)| **i == interface)
+            .map(|(id, _)| *id)
+            .unwrap()
+    }
+
+    fn handle(&mut self, message: &Message) {
+        let interface = self
+            .objects
+            .get(&message.object)
+            .copied()
+            .unwrap_or("unknown");
+        let mut r = message.reader();
+        let request = match (interface, message.opcode) {
+            ("wl_display", 0) => {
+                let callback = r.object().unwrap().unwrap();
+                self.send(MessageBuilder::new(callback, 0).u32(0).finish());
+                "sync".to_owned()
+            }
+            ("wl_display", 1) => {
+                let registry = r.object().unwrap().unwrap();
+                self.objects.insert(registry, "wl_registry");
+                for (name, global) in self.globals.iter().enumerate() {
+                    self.send(
+                        MessageBuilder::new(registry, 0)
+                            .u32(name as u32 + 1)
+                            .string(global)
+                            .u32(1)
+                            .finish(),
+                    );
+                }
+                "get_registry".to_owned()
+            }
+            ("wl_registry", 0) => {
+                let name = r.u32().unwrap();
+                let interface = r.string().unwrap();
+                let version = r.u32().unwrap();
+                let id = r.object().unwrap().unwrap();
+                assert_eq!(interface, self.globals[name as usize - 1]);
+                let interface = self.globals[name as usize - 1];
+                self.objects.insert(id, interface);
+                format!("bind({interface}, {version})")
+            }
+            ("wl_compositor", 0) => {
+                self.objects
+                    .insert(r.object().unwrap().unwrap(), "wl_surface");
+                "create_surface".to_owned()
+            }
+            ("wl_surface", 0) => "destroy".to_owned(),
+            ("wl_surface", 6) => {
+                if !self.configured {
+                    self.configured = true;
+                    self.configure(self.find("xdg_toplevel"));
+                }
+                "commit".to_owned()
+            }
+            ("xdg_wm_base", 2) => {
+                self.objects
+                    .insert(r.object().unwrap().unwrap(), "xdg_surface");
+                "get_xdg_surface".to_owned()
+            }
+            ("xdg_wm_base", 3) => format!("pong({})", r.u32().unwrap()),
+            ("xdg_surface", 0) => "destroy".to_owned(),
+            ("xdg_surface", 1) => {
+                self.objects
+                    .insert(r.object().unwrap().unwrap(), "xdg_toplevel");
+                "get_toplevel".to_owned()
+            }
+            ("xdg_surface", 4) => format!("ack_configure({})", r.u32().unwrap()),
+            ("xdg_toplevel", 0) => "destroy".to_owned(),
+            ("xdg_toplevel", 2) => format!("set_title({})", r.string().unwrap()),
+            ("xdg_toplevel", 11) => {
+                let output = r.object().unwrap();
+                self.fullscreen = true;
+                self.configure(message.object);
+                format!("set_fullscreen({output:?})")
+            }
+            ("xdg_toplevel", 12) => {
+                self.fullscreen = false;
+                self.configure(message.object);
+                "unset_fullscreen".to_owned()
+            }
+            (interface, opcode) => {
+                // a real compositor would disconnect the client too
+                let message_object = message.object;
+                let message = format!("unexpected request {interface}.{opcode}");
+                self.send(
+                    MessageBuilder::new(DISPLAY, 0)
+                        .u32(message_object)
+                        .u32(0)
+                        .string(&message)
+                        .finish(),
+                );
+                return;
+            }
+        };
+        self.log.push(format!("{interface}.{request}"));
+    }
+}
+
+/// Handles everything the compositor has sent in response to the requests so far.
+fn dispatch(conn: &mut Connection, toplevel: &mut Toplevel) {
+    conn.roundtrip().unwrap();
+    while let Some(message) = conn.poll_message().unwrap() {
+        assert!(toplevel.handle_message(conn, &message).unwrap());
+    }
+}
+
+#[test]
+fn toggling_fullscreen() {
+    let compositor = FakeCompositor::start(
+        "toggle",
+        &[interfaces::COMPOSITOR, "wl_seat", interfaces::XDG_WM_BASE],
+    );
+    let mut conn = compositor.connect();
+    let mut toplevel = Toplevel::new(&mut conn, "fullscreen").unwrap();
+    dispatch(&mut conn, &mut toplevel);
+    assert!(!toplevel.is_fullscreen());
+    assert_eq!(toplevel.size(), [0, 0]);
+
+    let mut fullscreen = Fullscreen::new();
+    assert!(fullscreen.toggle(&mut conn, toplevel.id()).unwrap());
+    dispatch(&mut conn, &mut toplevel);
+    assert!(toplevel.is_fullscreen());
+    assert_eq!(toplevel.size(), OUTPUT_SIZE);
+
+    // asking twice is harmless, and doesn't send anything
+    fullscreen.enter(&mut conn, toplevel.id()).unwrap();
+    assert!(fullscreen.is_fullscreen());
+
+    assert!(!fullscreen.toggle(&mut conn, toplevel.id()).unwrap());
+    dispatch(&mut conn, &mut toplevel);
+    assert!(!toplevel.is_fullscreen());
+    assert_eq!(toplevel.size(), [0, 0]);
+    fullscreen.exit(&mut conn, toplevel.id()).unwrap();
+
+    toplevel.destroy(&mut conn).unwrap();
+    drop(conn);
+
+    let log = compositor.finish();
+    let requests: Vec<&str> = log
+        .iter()
+        .map(String::as_str)
+        .filter(|request| *request != "wl_display.sync")
+        .collect();
+    assert_eq!(
+        requests,
+        [
+            "wl_display.get_registry",
+            "wl_registry.bind(wl_compositor, 1)",
+            "wl_registry.bind(xdg_wm_base, 1)",
+            "wl_compositor.create_surface",
+            "xdg_wm_base.get_xdg_surface",
+            "xdg_surface.get_toplevel",
+            "xdg_toplevel.set_title(fullscreen)",
+            "wl_surface.commit",
+            "xdg_wm_base.pong(1)",
+            "xdg_surface.ack_configure(2)",
+            "wl_surface.commit",
+            "xdg_toplevel.set_fullscreen(None)",
+            "xdg_wm_base.pong(3)",
+            "xdg_surface.ack_configure(4)",
+            "wl_surface.commit",
+            "xdg_toplevel.unset_fullscreen",
+            "xdg_wm_base.pong(5)",
+            "xdg_surface.ack_configure(6)",
+            "wl_surface.commit",
+            "xdg_toplevel.destroy",
+            "xdg_surface.destroy",
+            "wl_surface.destroy",
+        ]
+    );
+}
+
+#[test]
+fn without_xdg_shell() {
+    let compositor = FakeCompositor::start("no-xdg-shell", &[interfaces::COMPOSITOR]);
+    let mut conn = compositor.connect();
+    assert!(matches!(
+        Toplevel::new(&mut conn, "fullscreen"),
+        Err(Error::MissingGlobal("xdg_wm_base"))
+    ));
+}
+
+#[test]
+fn protocol_errors() {
+    let compositor = FakeCompositor::start("error", &[interfaces::COMPOSITOR]);
+    let mut conn = compositor.connect();
+    // the fake compositor doesn't know any objects the client hasn't made
+    conn.send(&wayland::protocol::surface_commit(40)).unwrap();
+    match conn.roundtrip() {
+        Err(Error::Protocol { object, .. }) => assert_eq!(object, 40),
+        other => panic!("expected a protocol error, got {other:?}"),
+    }
+}
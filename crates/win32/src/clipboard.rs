@@ -0,0 +1,129 @@
+//! Copying and pasting text through the
+//! [clipboard](https://docs.microsoft.com/en-us/windows/win32/dataxchg/using-the-clipboard).
+//!
+//! ```no_run
+//! # use triangle_from_scratch_win32::{clipboard::Clipboard, prelude::*};
+//! # fn f(hwnd: HWND) -> Result<(), Win32Error> {
+//! let mut clipboard = unsafe { Clipboard::open(hwnd) }?;
+//! clipboard.set_text("hello")?;
+//! assert_eq!(clipboard.text()?.as_deref(), Some("hello"));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Text goes as `CF_UNICODETEXT`, and Windows converts it for programs that ask for other text
+//! formats.
+
+use core::{marker::PhantomData, ptr, slice};
+use std::{thread, time::Duration};
+
+use crate::{
+    get_last_error,
+    prelude::*,
+    str_util::{decode_utf16_lossy, wide_null, WideStr},
+};
+
+/// How many times [`Clipboard::open`] tries, since another program may have it open for a moment.
+const OPEN_ATTEMPTS: u32 = 5;
+const OPEN_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+/// The open clipboard. Only one window can have it open at a time, so keep it open briefly.
+///
+/// Dropping it closes the clipboard. It isn't `Send`, since the clipboard has to be closed by the
+/// thread that opened it.
+#[derive(Debug)]
+pub struct Clipboard {
+    _not_send: PhantomData<*mut ()>,
+}
+
+impl Clipboard {
+    /// Opens the clipboard for `hwnd`, trying a few times if another program has it open.
+    ///
+    /// `hwnd` can be null to only read the clipboard, but then [`Clipboard::set_text`] fails.
+    ///
+    /// ## Safety
+    ///
+    /// `hwnd` must be null, or a valid window owned by this thread.
+    pub unsafe fn open(hwnd: HWND) -> Result<Self, Win32Error> {
+        let mut attempt = 1;
+        while OpenClipboard(hwnd) == 0 {
+            if attempt == OPEN_ATTEMPTS {
+                return Err(get_last_error());
+            }
+            attempt += 1;
+            thread::sleep(OPEN_RETRY_DELAY);
+        }
+        Ok(Self {
+            _not_send: PhantomData,
+        })
+    }
+
+    /// The text on the clipboard, or `None` if there's no text on it. Text that isn't valid UTF-16
+    /// is decoded lossily.
+    pub fn text(&self) -> Result<Option<String>, Win32Error> {
+        // Safety: the clipboard is open, and the handle it gives back stays valid until it's
+        // closed, which needs `&mut self`
+        unsafe {
+            if IsClipboardFormatAvailable(CF_UNICODETEXT) == 0 {
+                return Ok(None);
+            }
+            let handle = GetClipboardData(CF_UNICODETEXT);
+            if handle.is_null() {
+                return Err(get_last_error());
+            }
+            let data = GlobalLock(handle) as *const u16;
+            if data.is_null() {
+                return Err(get_last_error());
+            }
+            let units = slice::from_raw_parts(data, GlobalSize(handle) / 2);
+            let text = match WideStr::from_units_until_null(units) {
+                Ok(text) => text.to_string_lossy(),
+                // the owner didn't put a null on the end, so the whole block is the text
+                Err(_) => decode_utf16_lossy(units),
+            };
+            GlobalUnlock(handle);
+            Ok(Some(text))
+        }
+    }
+
+    /// Replaces what's on the clipboard with `text`.
+    ///
+    /// This fails if the clipboard was opened without a window. Text after an interior null is
+    /// cut off by whatever reads it.
+    pub fn set_text(&mut self, text: &str) -> Result<(), Win32Error> {
+        let wide = wide_null(text);
+        // Safety: the clipboard is open, and the block is big enough for `wide`. Once
+        // `SetClipboardData` succeeds the system owns the block, so it's only freed on failure.
+        unsafe {
+            if EmptyClipboard() == 0 {
+                return Err(get_last_error());
+            }
+            let size = wide.len() * 2;
+            let handle = GlobalAlloc(GMEM_MOVEABLE, size);
+            if handle.is_null() {
+                return Err(get_last_error());
+            }
+            let data = GlobalLock(handle) as *mut u16;
+            if data.is_null() {
+                let err = get_last_error();
+                GlobalFree(handle);
+                return Err(err);
+            }
+            ptr::copy_nonoverlapping(wide.as_ptr(), data, wide.len());
+            GlobalUnlock(handle);
+            if SetClipboardData(CF_UNICODETEXT, handle).is_null() {
+                let err = get_last_error();
+                GlobalFree(handle);
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Clipboard {
+    fn drop(&mut self) {
+        // Safety: it's open, since this is the only way it's closed
+        unsafe { CloseClipboard() };
+    }
+}
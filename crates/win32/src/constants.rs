@@ -5,6 +5,21 @@
 use super::typedefs::*;
 use c_types::*;
 
+/// Per-monitor DPI awareness, v2: the window is told when its DPI changes (with
+/// [`WM_DPICHANGED`]), and its non-client area is scaled by Windows. See
+/// [MSDN](https://docs.microsoft.com/en-us/windows/win32/hidpi/dpi-awareness-context).
+pub const DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2: DPI_AWARENESS_CONTEXT =
+    -4_isize as DPI_AWARENESS_CONTEXT;
+
+/// For `SetProcessDpiAwareness`: the process is aware of each monitor's DPI.
+pub const PROCESS_PER_MONITOR_DPI_AWARE: CInt = 2;
+
+/// A [standard clipboard format](https://docs.microsoft.com/en-us/windows/win32/dataxchg/standard-clipboard-formats).
+///
+/// > Unicode text format. Each line ends with a carriage return/linefeed (CR-LF) combination. A
+/// > null character signals the end of the data.
+pub const CF_UNICODETEXT: UINT = 13;
+
 /// A [window class style](https://docs.microsoft.com/en-us/windows/win32/winmsg/window-class-styles).
 ///
 /// > Aligns the window's client area on a byte boundary (in the x-direction). This style affects the
@@ -93,6 +108,79 @@ pub const CS_VREDRAW: UINT = 0x0000_0001;
 
 pub const CW_USEDEFAULT: CInt = 0x80000000_u32 as CInt;
 
+/// A [system error code](https://docs.microsoft.com/en-us/windows/win32/debug/system-error-codes--500-999-).
+///
+/// > Overlapped I/O event is not in a signaled state.
+pub const ERROR_IO_INCOMPLETE: DWORD = 996;
+
+/// A [system error code](https://docs.microsoft.com/en-us/windows/win32/debug/system-error-codes--500-999-).
+///
+/// > Overlapped I/O operation is in progress.
+pub const ERROR_IO_PENDING: DWORD = 997;
+
+/// A [system error code](https://docs.microsoft.com/en-us/windows/win32/debug/system-error-codes--1000-1299-).
+///
+/// > A notify change request is being completed and the information is not being returned in the
+/// > caller's buffer. The caller now needs to enumerate the files to find the changes.
+pub const ERROR_NOTIFY_ENUM_DIR: DWORD = 1022;
+
+/// A [system error code](https://docs.microsoft.com/en-us/windows/win32/debug/system-error-codes--500-999-).
+///
+/// > The I/O operation has been aborted because of either a thread exit or an application request.
+pub const ERROR_OPERATION_ABORTED: DWORD = 995;
+
+/// A [`FILE_NOTIFY_INFORMATION` action][`super::FILE_NOTIFY_INFORMATION::Action`]: the file was
+/// added to the directory.
+pub const FILE_ACTION_ADDED: DWORD = 0x0000_0001;
+/// A [`FILE_NOTIFY_INFORMATION` action][`super::FILE_NOTIFY_INFORMATION::Action`]: the file was
+/// removed from the directory.
+pub const FILE_ACTION_REMOVED: DWORD = 0x0000_0002;
+/// A [`FILE_NOTIFY_INFORMATION` action][`super::FILE_NOTIFY_INFORMATION::Action`]: the file was
+/// modified. This can be a change in the time stamp or attributes.
+pub const FILE_ACTION_MODIFIED: DWORD = 0x0000_0003;
+/// A [`FILE_NOTIFY_INFORMATION` action][`super::FILE_NOTIFY_INFORMATION::Action`]: the file was
+/// renamed, and this is the old name.
+pub const FILE_ACTION_RENAMED_OLD_NAME: DWORD = 0x0000_0004;
+/// A [`FILE_NOTIFY_INFORMATION` action][`super::FILE_NOTIFY_INFORMATION::Action`]: the file was
+/// renamed, and this is the new name.
+pub const FILE_ACTION_RENAMED_NEW_NAME: DWORD = 0x0000_0005;
+
+/// A [`CreateFileW`][super::CreateFileW] flag. Required to open a handle to a directory.
+pub const FILE_FLAG_BACKUP_SEMANTICS: DWORD = 0x0200_0000;
+/// A [`CreateFileW`][super::CreateFileW] flag. The handle is opened for asynchronous
+/// ("overlapped") I/O.
+pub const FILE_FLAG_OVERLAPPED: DWORD = 0x4000_0000;
+
+/// A [file access right](https://docs.microsoft.com/en-us/windows/win32/fileio/file-access-rights-constants).
+///
+/// > For a directory, the right to list the contents of the directory.
+pub const FILE_LIST_DIRECTORY: DWORD = 0x0000_0001;
+
+/// A [`ReadDirectoryChangesW`][super::ReadDirectoryChangesW] filter. Reports renaming, creating,
+/// or deleting a file.
+pub const FILE_NOTIFY_CHANGE_FILE_NAME: DWORD = 0x0000_0001;
+/// A [`ReadDirectoryChangesW`][super::ReadDirectoryChangesW] filter. Reports renaming, creating,
+/// or deleting a directory.
+pub const FILE_NOTIFY_CHANGE_DIR_NAME: DWORD = 0x0000_0002;
+/// A [`ReadDirectoryChangesW`][super::ReadDirectoryChangesW] filter. Reports attribute changes.
+pub const FILE_NOTIFY_CHANGE_ATTRIBUTES: DWORD = 0x0000_0004;
+/// A [`ReadDirectoryChangesW`][super::ReadDirectoryChangesW] filter. Reports file size changes,
+/// once the file is written to disk.
+pub const FILE_NOTIFY_CHANGE_SIZE: DWORD = 0x0000_0008;
+/// A [`ReadDirectoryChangesW`][super::ReadDirectoryChangesW] filter. Reports changes to the last
+/// write time, once the file is written to disk.
+pub const FILE_NOTIFY_CHANGE_LAST_WRITE: DWORD = 0x0000_0010;
+
+/// A [`CreateFileW`][super::CreateFileW] share mode. Lets others delete or rename the file while
+/// we have it open.
+pub const FILE_SHARE_DELETE: DWORD = 0x0000_0004;
+/// A [`CreateFileW`][super::CreateFileW] share mode. Lets others read the file while we have it
+/// open.
+pub const FILE_SHARE_READ: DWORD = 0x0000_0001;
+/// A [`CreateFileW`][super::CreateFileW] share mode. Lets others write to the file while we have
+/// it open.
+pub const FILE_SHARE_WRITE: DWORD = 0x0000_0002;
+
 /// For use with [FormatMessageW][msdn-format-message-w].
 ///
 /// Allocates a buffer large enough to hold the formatted message.
@@ -149,6 +237,14 @@ pub const FORMAT_MESSAGE_FROM_SYSTEM: DWORD = 0x0000_1000;
 /// [msdn-format-message-w]: https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-formatmessagew
 pub const FORMAT_MESSAGE_IGNORE_INSERTS: DWORD = 0x0000_0200;
 
+/// For use with [`super::GlobalAlloc()`].
+///
+/// [From MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-globalalloc#parameters):
+///
+/// > Allocates movable memory. Memory blocks are never moved in physical memory, but they can be
+/// > moved within the default heap.
+pub const GMEM_MOVEABLE: UINT = 0x0002;
+
 /// For use with [`super::SetWindowLongPtrW()`].
 ///
 /// [From MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setwindowlongptrw#parameters):
@@ -157,12 +253,103 @@ pub const FORMAT_MESSAGE_IGNORE_INSERTS: DWORD = 0x0000_0200;
 /// > application that created the window. Its value is initially zero.
 pub const GWLP_USERDATA: CInt = -21;
 
+/// For use with [`super::GetWindowLongPtrW()`], to get the address of the window procedure.
+pub const GWLP_WNDPROC: CInt = -4;
+/// For use with [`super::GetWindowLongPtrW()`] and [`super::SetWindowLongPtrW()`]: the window
+/// style.
+pub const GWL_STYLE: CInt = -16;
+/// For use with [`super::GetWindowLongPtrW()`] and [`super::SetWindowLongPtrW()`]: the extended
+/// window style.
+pub const GWL_EXSTYLE: CInt = -20;
+
+/// For [`super::SetWindowPos()`]: keep the window's current position.
+pub const SWP_NOMOVE: UINT = 0x0002;
+/// For [`super::SetWindowPos()`]: keep the window's current size.
+pub const SWP_NOSIZE: UINT = 0x0001;
+/// For [`super::SetWindowPos()`]: keep the window's place in the Z order.
+pub const SWP_NOZORDER: UINT = 0x0004;
+/// For [`super::SetWindowPos()`]: don't activate the window.
+pub const SWP_NOACTIVATE: UINT = 0x0010;
+/// For [`super::SetWindowPos()`]: recalculate the window's frame, after its style has changed.
+pub const SWP_FRAMECHANGED: UINT = 0x0020;
+/// For [`super::SetWindowPos()`]: don't change the owner window's place in the Z order.
+pub const SWP_NOOWNERZORDER: UINT = 0x0200;
+
+/// For [`super::MonitorFromWindow()`]: if the window isn't on any monitor, get the nearest one.
+pub const MONITOR_DEFAULTTONEAREST: DWORD = 0x0000_0002;
+/// For [`super::MonitorFromWindow()`]: if the window isn't on any monitor, get the primary one.
+pub const MONITOR_DEFAULTTOPRIMARY: DWORD = 0x0000_0001;
+
+/// For [`MONITORINFOEXW::dwFlags`](super::MONITORINFOEXW::dwFlags): this is the primary monitor.
+pub const MONITORINFOF_PRIMARY: DWORD = 0x0000_0001;
+
+/// For [`super::EnumDisplaySettingsW()`]: get the current mode instead of one from the list.
+pub const ENUM_CURRENT_SETTINGS: DWORD = 0xFFFF_FFFF;
+
+/// For [`GetDpiForMonitor_t`](super::GetDpiForMonitor_t): the DPI that the user's scaling setting
+/// gives the monitor, as opposed to its actual DPI.
+pub const MDT_EFFECTIVE_DPI: CInt = 0;
+
+/// For [`super::ChangeDisplaySettingsExW()`]: the mode change is temporary, and is undone when the
+/// process exits or changes the mode back.
+pub const CDS_FULLSCREEN: DWORD = 0x0000_0004;
+/// Returned by [`super::ChangeDisplaySettingsExW()`] when the mode was changed.
+pub const DISP_CHANGE_SUCCESSFUL: LONG = 0;
+
+/// For [`DEVMODEW::dmFields`](super::DEVMODEW::dmFields): `dmBitsPerPel` is set.
+pub const DM_BITSPERPEL: DWORD = 0x0004_0000;
+/// For [`DEVMODEW::dmFields`](super::DEVMODEW::dmFields): `dmPelsWidth` is set.
+pub const DM_PELSWIDTH: DWORD = 0x0008_0000;
+/// For [`DEVMODEW::dmFields`](super::DEVMODEW::dmFields): `dmPelsHeight` is set.
+pub const DM_PELSHEIGHT: DWORD = 0x0010_0000;
+/// For [`DEVMODEW::dmFields`](super::DEVMODEW::dmFields): `dmDisplayFrequency` is set.
+pub const DM_DISPLAYFREQUENCY: DWORD = 0x0040_0000;
+
+/// For [`super::GetSystemMetrics()`]: the width of the primary monitor.
+pub const SM_CXSCREEN: CInt = 0;
+/// For [`super::GetSystemMetrics()`]: the height of the primary monitor.
+pub const SM_CYSCREEN: CInt = 1;
+/// For [`super::GetSystemMetrics()`]: the width of the virtual screen, which covers every monitor.
+pub const SM_CXVIRTUALSCREEN: CInt = 78;
+/// For [`super::GetSystemMetrics()`]: the height of the virtual screen.
+pub const SM_CYVIRTUALSCREEN: CInt = 79;
+/// For [`super::GetSystemMetrics()`]: the width of a big icon, as in the task switcher.
+pub const SM_CXICON: CInt = 11;
+/// For [`super::GetSystemMetrics()`]: the height of a big icon.
+pub const SM_CYICON: CInt = 12;
+/// For [`super::GetSystemMetrics()`]: the width of a small icon, as in a title bar.
+pub const SM_CXSMICON: CInt = 49;
+/// For [`super::GetSystemMetrics()`]: the height of a small icon.
+pub const SM_CYSMICON: CInt = 50;
+
+/// For [`super::GetDeviceCaps()`]: the number of pixels per logical inch along the screen width,
+/// which is the system DPI.
+pub const LOGPIXELSX: CInt = 88;
+/// For [`super::GetDeviceCaps()`]: the width of the physical screen, in millimeters.
+pub const HORZSIZE: CInt = 4;
+/// For [`super::GetDeviceCaps()`]: the height of the physical screen, in millimeters.
+pub const VERTSIZE: CInt = 6;
+
 /// The id of the "Ok" button on a message box.
 pub const IDOK: CInt = 1;
 
+/// A timeout that never elapses, for use with [`WaitForSingleObject`][super::WaitForSingleObject].
+pub const INFINITE: DWORD = 0xFFFF_FFFF;
+
+/// Returned by [`CreateFileW`][super::CreateFileW] on failure. Note that this is *not* null.
+pub const INVALID_HANDLE_VALUE: HANDLE = -1_isize as HANDLE;
+
+/// The largest number of handles [`WaitForMultipleObjects`][super::WaitForMultipleObjects] can
+/// wait on at once.
+pub const MAXIMUM_WAIT_OBJECTS: DWORD = 64;
+
 /// Display "Ok" and "Cancel" buttons on a message box.
 pub const MB_OKCANCEL: u32 = 1;
 
+/// A [`CreateFileW`][super::CreateFileW] creation disposition. Opens the file or device only if
+/// it exists.
+pub const OPEN_EXISTING: DWORD = 3;
+
 /// A [`PIXELFORMATDESCRIPTOR` flag][`super::PIXELFORMATDESCRIPTOR::dwFlags`].
 /// Allows the buffer to draw to a window or device surface.
 pub const PFD_DRAW_TO_WINDOW: DWORD = 0x0000_0004;
@@ -285,6 +472,20 @@ pub const PFD_OVERLAY_PLANE: BYTE = 1;
 pub const PFD_UNDERLAY_PLANE: BYTE = u8::MAX; // was (-1) in the windows headers
 
 pub const SW_SHOW: CInt = 5;
+/// Shows and activates a window, restoring it if it's minimized or maximized.
+pub const SW_SHOWNORMAL: CInt = 1;
+/// Shows and maximizes a window.
+pub const SW_SHOWMAXIMIZED: CInt = 3;
+
+/// Returned by [`WaitForSingleObject`][super::WaitForSingleObject] when the object is signaled.
+/// [`WaitForMultipleObjects`][super::WaitForMultipleObjects] returns `WAIT_OBJECT_0 + i` when the
+/// `i`th object is signaled.
+pub const WAIT_OBJECT_0: DWORD = 0x0000_0000;
+/// Returned by [`WaitForSingleObject`][super::WaitForSingleObject] when the timeout elapses before
+/// the object is signaled.
+pub const WAIT_TIMEOUT: DWORD = 0x0000_0102;
+/// Returned by [`WaitForSingleObject`][super::WaitForSingleObject] on failure.
+pub const WAIT_FAILED: DWORD = 0xFFFF_FFFF;
 
 pub const WS_OVERLAPPED: u32 = 0x00000000;
 pub const WS_CAPTION: u32 = 0x00C00000;
@@ -305,6 +506,8 @@ pub const WS_CLIPCHILDREN: u32 = 0x02000000;
 /// neighboring child window.
 pub const WS_CLIPSIBLINGS: u32 = 0x04000000;
 
+/// A pop-up window, which has no frame unless other styles add one. Used for borderless windows.
+pub const WS_POPUP: u32 = 0x80000000;
 pub const WS_SYSMENU: u32 = 0x00080000;
 pub const WS_THICKFRAME: u32 = 0x00040000;
 pub const WS_MINIMIZEBOX: u32 = 0x00020000;
@@ -312,6 +515,16 @@ pub const WS_MAXIMIZEBOX: u32 = 0x00010000;
 pub const WS_OVERLAPPEDWINDOW: u32 =
     WS_OVERLAPPED | WS_CAPTION | WS_SYSMENU | WS_THICKFRAME | WS_MINIMIZEBOX | WS_MAXIMIZEBOX;
 
+/// An extended window style: the window stays above all non-topmost windows.
+pub const WS_EX_TOPMOST: u32 = 0x00000008;
+/// An extended window style: a tool window, with a small title bar and no taskbar button.
+pub const WS_EX_TOOLWINDOW: u32 = 0x00000080;
+
+/// Sent when the DPI of a window changes, e.g. because it's been moved to another monitor. The
+/// new DPI is in both words of `wParam`, and `lParam` points to a suggested new window [`RECT`].
+///
+/// [`RECT`]: crate::structs::RECT
+pub const WM_DPICHANGED: u32 = 0x02E0;
 /// Sent when the window is closed.
 pub const WM_CLOSE: u32 = 0x0010;
 /// Sent when an application requests a window be created. The window procedure will receive this
@@ -321,10 +534,99 @@ pub const WM_CREATE: u32 = 0x0001;
 pub const WM_DESTROY: u32 = 0x0002;
 /// Sent prior to the [`WM_CREATE`] message when a window is first created.
 pub const WM_NCCREATE: u32 = 0x0081;
+/// Sent after [`WM_DESTROY`], as the very last message a window receives.
+pub const WM_NCDESTROY: u32 = 0x0082;
 /// Sent when the window should be painted.
 pub const WM_PAINT: u32 = 0x000F;
+/// Sent when a key is pressed while the Alt key isn't held. `wParam` is the virtual key code, and
+/// bit 30 of `lParam` is set if the key was already down (i.e. this is a repeat).
+pub const WM_KEYDOWN: u32 = 0x0100;
+/// Sent when a key is released while the Alt key isn't held. Like [`WM_KEYDOWN`], the scan code
+/// is in bits 16 to 23 of `lParam`, and bit 24 is set for extended keys.
+pub const WM_KEYUP: u32 = 0x0101;
+/// Sent by [`super::TranslateMessage()`] for the text a [`WM_KEYDOWN`] types. `wParam` is one
+/// UTF-16 unit, so characters outside the BMP come as two of these.
+pub const WM_CHAR: u32 = 0x0102;
+/// Like [`WM_KEYDOWN`], but for F10 and keys pressed while Alt is held. These should be passed on
+/// to [`super::DefWindowProcW()`], which handles Alt+F4 and the window menu.
+pub const WM_SYSKEYDOWN: u32 = 0x0104;
+/// Like [`WM_KEYUP`], but for F10 and keys released while Alt is held.
+pub const WM_SYSKEYUP: u32 = 0x0105;
+/// Sent when raw input arrives from a device registered with [`super::RegisterRawInputDevices()`].
+/// `lParam` is an [`HRAWINPUT`](super::HRAWINPUT) to read with [`super::GetRawInputData()`], and
+/// the message must be passed on to [`super::DefWindowProcW()`] afterwards so Windows can free it.
+pub const WM_INPUT: u32 = 0x00FF;
+/// Sent when a window is activated or deactivated. The low word of `wParam` is [`WA_INACTIVE`]
+/// when it's deactivated.
+pub const WM_ACTIVATE: u32 = 0x0006;
+/// Sent when the user drops files on a window that accepts them (see [`super::DragAcceptFiles()`]).
+/// `wParam` is the [`HDROP`] to read them from, which has to be freed with
+/// [`super::DragFinish()`].
+pub const WM_DROPFILES: u32 = 0x0233;
 /// Indicates a request to termiante the application.
 pub const WM_QUIT: u32 = 0x0012;
+/// For [`super::PeekMessageW()`]: remove the message from the queue after it's been peeked at.
+pub const PM_REMOVE: UINT = 0x0001;
+/// Sent after a window's size has changed. The new size of the client area is in `lParam`, and
+/// `wParam` is one of the `SIZE_*` constants.
+pub const WM_SIZE: u32 = 0x0005;
+/// Sent to give a window a new icon. `wParam` is [`ICON_BIG`] or [`ICON_SMALL`], and `lParam` is
+/// the [`HICON`], which the window doesn't take ownership of.
+pub const WM_SETICON: u32 = 0x0080;
+
+/// [`WM_SIZE`]: the window was resized, but not minimized or maximized.
+pub const SIZE_RESTORED: WPARAM = 0;
+/// [`WM_SIZE`]: the window was minimized.
+pub const SIZE_MINIMIZED: WPARAM = 1;
+/// [`WM_SIZE`]: the window was maximized.
+pub const SIZE_MAXIMIZED: WPARAM = 2;
+
+/// [`WM_ACTIVATE`]: the window was deactivated.
+pub const WA_INACTIVE: WPARAM = 0;
+
+/// [`WM_SETICON`]: the small icon, which is in the title bar.
+pub const ICON_SMALL: WPARAM = 0;
+/// [`WM_SETICON`]: the big icon, which is in the task switcher.
+pub const ICON_BIG: WPARAM = 1;
+
+/// For [`super::GetRawInputData()`]: read the whole [`RAWINPUT`](super::RAWINPUT), not just its
+/// header.
+pub const RID_INPUT: UINT = 0x1000_0003;
+/// For [`RAWINPUTHEADER::dwType`](super::RAWINPUTHEADER::dwType): the input is from a mouse.
+pub const RIM_TYPEMOUSE: DWORD = 0;
+/// For [`RAWINPUTDEVICE::dwFlags`](super::RAWINPUTDEVICE::dwFlags): stop getting input from the
+/// device. The target window has to be null.
+pub const RIDEV_REMOVE: DWORD = 0x0000_0001;
+/// For [`RAWINPUTDEVICE::usUsagePage`](super::RAWINPUTDEVICE::usUsagePage): generic desktop
+/// controls, which mice and keyboards are.
+pub const HID_USAGE_PAGE_GENERIC: USHORT = 0x01;
+/// For [`RAWINPUTDEVICE::usUsage`](super::RAWINPUTDEVICE::usUsage): a mouse.
+pub const HID_USAGE_GENERIC_MOUSE: USHORT = 0x02;
+/// For [`RAWMOUSE::usFlags`](super::RAWMOUSE::usFlags): the position is absolute (e.g. from a
+/// tablet or a remote desktop), from 0 to 65535 across the screen, instead of relative.
+pub const MOUSE_MOVE_ABSOLUTE: USHORT = 0x01;
+/// For [`RAWMOUSE::usFlags`](super::RAWMOUSE::usFlags): an absolute position is across the whole
+/// virtual screen rather than the primary monitor.
+pub const MOUSE_VIRTUAL_DESKTOP: USHORT = 0x02;
+
+/// The virtual key code of the F11 key, which conventionally toggles fullscreen.
+pub const VK_F11: u32 = 0x7A;
+/// The virtual key code of either shift key.
+pub const VK_SHIFT: u32 = 0x10;
+/// The virtual key code of either control key.
+pub const VK_CONTROL: u32 = 0x11;
+/// The virtual key code of either alt key.
+pub const VK_MENU: u32 = 0x12;
+pub const VK_CAPITAL: u32 = 0x14;
+pub const VK_NUMLOCK: u32 = 0x90;
+pub const VK_LCONTROL: u32 = 0xA2;
+pub const VK_RCONTROL: u32 = 0xA3;
+/// The virtual key code of the right alt key, which is AltGr in layouts that have it.
+pub const VK_RMENU: u32 = 0xA5;
+
+/// For [`super::MapVirtualKeyW()`]: turn a virtual key code into a scan code, with `0xE000`
+/// added for extended keys.
+pub const MAPVK_VK_TO_VSC_EX: UINT = 4;
 
 pub use wgl_pixel_format::*;
 /// Base constants for use with the [`WGL_ARB_pixel_format`](https://www.khronos.org/registry/OpenGL/extensions/ARB/WGL_ARB_pixel_format.txt)
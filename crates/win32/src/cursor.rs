@@ -0,0 +1,237 @@
+//! Hiding the cursor, locking it in place for first-person cameras, and making cursors out of
+//! images.
+//!
+//! ```no_run
+//! # use triangle_from_scratch_win32::{cursor::{CursorGrab, CursorMode}, prelude::*};
+//! # fn f(hwnd: HWND, grab: &mut CursorGrab) -> Result<(), Win32Error> {
+//! // e.g. when the user clicks into the viewport, in a `WindowHandler`
+//! unsafe { grab.set_mode(hwnd, CursorMode::Locked) }?;
+//! // ...and in `WindowHandler::activate`, since Windows lets go of the cursor when another
+//! // window is activated
+//! unsafe { grab.activated(hwnd, true) }?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Motion is read from [`raw_input`](crate::raw_input) while the cursor is hidden or locked, so it
+//! keeps coming even though the cursor can't move.
+//!
+//! Cursors made from images ([`ImageCursor`]) are shown with [`SetCursor`] when the window gets
+//! `WM_SETCURSOR`, or by putting them in the window class's `hCursor`.
+
+use core::ptr;
+
+use crate::{
+    get_last_error,
+    icon::create_icon_indirect,
+    prelude::*,
+    raw_input::{register_raw_mouse, unregister_raw_mouse},
+};
+
+/// What the cursor does over a window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CursorMode {
+    /// Visible, and free to move.
+    #[default]
+    Normal,
+    /// Invisible, but free to move, e.g. while dragging to rotate something.
+    Hidden,
+    /// Invisible, and held in the middle of the window. Only relative motion comes through.
+    Locked,
+}
+
+impl CursorMode {
+    /// Whether raw mouse motion should be read in this mode.
+    pub const fn wants_raw_motion(self) -> bool {
+        !matches!(self, Self::Normal)
+    }
+}
+
+/// Puts a window's cursor into a [`CursorMode`], and back.
+///
+/// Dropping it puts the cursor back to normal.
+#[derive(Debug, Default)]
+pub struct CursorGrab {
+    mode: CursorMode,
+    /// Whether `ShowCursor` has been told to hide the cursor, which is a counter that has to be
+    /// balanced.
+    hidden: bool,
+    /// Whether the cursor is clipped right now.
+    clipped: bool,
+}
+
+impl CursorGrab {
+    pub const fn new() -> Self {
+        Self {
+            mode: CursorMode::Normal,
+            hidden: false,
+            clipped: false,
+        }
+    }
+
+    pub fn mode(&self) -> CursorMode {
+        self.mode
+    }
+
+    /// Puts the cursor into `mode`, registering `hwnd` for raw mouse input if the mode needs it.
+    ///
+    /// ## Safety
+    ///
+    /// `hwnd` must be a valid window, and the same one every time.
+    pub unsafe fn set_mode(&mut self, hwnd: HWND, mode: CursorMode) -> Result<(), Win32Error> {
+        if mode.wants_raw_motion() && !self.mode.wants_raw_motion() {
+            register_raw_mouse(hwnd)?;
+        } else if !mode.wants_raw_motion() && self.mode.wants_raw_motion() {
+            unregister_raw_mouse()?;
+        }
+        self.mode = mode;
+
+        self.set_hidden(mode != CursorMode::Normal);
+        self.activated(hwnd, true)
+    }
+
+    /// Takes hold of the cursor again when the window is activated, and lets go of it when it's
+    /// deactivated. Call this from [`WindowHandler::activate`], and whenever the window moves or
+    /// is resized while the cursor's locked.
+    ///
+    /// ## Safety
+    ///
+    /// `hwnd` must be the window that [`set_mode`](Self::set_mode) was called with.
+    ///
+    /// [`WindowHandler::activate`]: crate::window_handler::WindowHandler::activate
+    pub unsafe fn activated(&mut self, hwnd: HWND, active: bool) -> Result<(), Win32Error> {
+        if active && self.mode == CursorMode::Locked {
+            let center = client_center(hwnd)?;
+            let rect = RECT {
+                left: center.x,
+                top: center.y,
+                right: center.x + 1,
+                bottom: center.y + 1,
+            };
+            if ClipCursor(&rect) == 0 {
+                return Err(get_last_error());
+            }
+            self.clipped = true;
+        } else if self.clipped {
+            self.clipped = false;
+            if ClipCursor(ptr::null()) == 0 {
+                return Err(get_last_error());
+            }
+        }
+        Ok(())
+    }
+
+    fn set_hidden(&mut self, hidden: bool) {
+        if hidden != self.hidden {
+            // Safety: just changes a counter, which is kept balanced by `self.hidden`
+            unsafe { ShowCursor(BOOL::from(!hidden)) };
+            self.hidden = hidden;
+        }
+    }
+}
+
+impl Drop for CursorGrab {
+    fn drop(&mut self) {
+        self.set_hidden(false);
+        if self.clipped {
+            // Safety: a null rect just lets go of the cursor
+            unsafe { ClipCursor(ptr::null()) };
+        }
+        if self.mode.wants_raw_motion() {
+            let _ = unregister_raw_mouse();
+        }
+    }
+}
+
+/// The middle of a window's client area, in screen coordinates.
+unsafe fn client_center(hwnd: HWND) -> Result<POINT, Win32Error> {
+    let mut client = RECT::default();
+    if GetClientRect(hwnd, &mut client) == 0 {
+        return Err(get_last_error());
+    }
+    let mut center = POINT {
+        x: (client.left + client.right) / 2,
+        y: (client.top + client.bottom) / 2,
+    };
+    if ClientToScreen(hwnd, &mut center) == 0 {
+        return Err(get_last_error());
+    }
+    Ok(center)
+}
+
+/// A cursor made from an image, which is destroyed when dropped.
+#[derive(Debug)]
+pub struct ImageCursor {
+    hcursor: HCURSOR,
+}
+
+impl ImageCursor {
+    /// Makes a cursor from straight (not premultiplied) RGBA pixels, row by row. The hotspot is
+    /// the pixel under the pointer's actual position.
+    ///
+    /// Fails with an application error if `rgba` isn't `width * height * 4` bytes long, or the
+    /// image is empty.
+    pub fn from_rgba(
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        hotspot: [u32; 2],
+    ) -> Result<Self, Win32Error> {
+        let hcursor = create_icon_indirect(rgba, width, height, Some(hotspot))?;
+        Ok(Self { hcursor })
+    }
+
+    pub fn hcursor(&self) -> HCURSOR {
+        self.hcursor
+    }
+}
+
+impl Drop for ImageCursor {
+    fn drop(&mut self) {
+        // Safety: the cursor was made by `CreateIconIndirect`, and nothing else destroys it
+        unsafe { DestroyCursor(self.hcursor) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        handles::{register_hidden_class, Window},
+        window_builder::WindowBuilder,
+    };
+
+    #[test]
+    fn modes_round_trip() {
+        let class = register_hidden_class("triangle-from-scratch cursor test").unwrap();
+        let window: Window<'_> = unsafe {
+            WindowBuilder::new()
+                .inner_size([320, 240])
+                .build(&class, ptr::null_mut())
+        }
+        .unwrap();
+        let hwnd = window.hwnd();
+
+        let mut grab = CursorGrab::new();
+        for mode in [CursorMode::Locked, CursorMode::Hidden, CursorMode::Normal] {
+            unsafe { grab.set_mode(hwnd, mode) }.unwrap();
+            assert_eq!(grab.mode(), mode);
+            assert_eq!(grab.clipped, mode == CursorMode::Locked);
+            assert_eq!(grab.hidden, mode != CursorMode::Normal);
+        }
+
+        unsafe { grab.set_mode(hwnd, CursorMode::Locked) }.unwrap();
+        unsafe { grab.activated(hwnd, false) }.unwrap();
+        assert!(!grab.clipped);
+    }
+
+    #[test]
+    fn image_cursors() {
+        let rgba: Vec<u8> = (0..20 * 30).flat_map(|i| [0xFF, 0, 0, i as u8]).collect();
+        let cursor = ImageCursor::from_rgba(&rgba, 20, 30, [3, 4]).unwrap();
+        assert!(!cursor.hcursor().is_null());
+
+        assert!(ImageCursor::from_rgba(&rgba, 20, 20, [0, 0]).is_err());
+        assert!(ImageCursor::from_rgba(&[], 0, 0, [0, 0]).is_err());
+    }
+}
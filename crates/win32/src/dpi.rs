@@ -0,0 +1,234 @@
+//! Per-monitor DPI awareness, using whichever API the running version of Windows has.
+//!
+//! The newer DPI functions are looked up at runtime (once), so the same executable still runs on
+//! versions of Windows that don't have them. It just gets a coarser kind of DPI awareness there.
+
+use core::{mem, ptr};
+use std::sync::OnceLock;
+
+use c_types::{c_str, CInt};
+
+use crate::{
+    geometry::{scale_factor, FrameInsets, Rect, USER_DEFAULT_SCREEN_DPI},
+    get_last_error,
+    handles::{Library, Window},
+    prelude::*,
+};
+
+/// How the process handles monitors with different DPIs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DpiAwareness {
+    /// Per-monitor v2 (Windows 10 version 1703 and later): windows get [`WM_DPICHANGED`], and
+    /// their frames are scaled by Windows.
+    PerMonitorV2,
+    /// Per-monitor (Windows 8.1 and later): windows get [`WM_DPICHANGED`], but their frames aren't
+    /// scaled.
+    PerMonitor,
+    /// System (Windows Vista and later): everything is drawn at the DPI of the primary monitor,
+    /// and bitmap-stretched on others.
+    System,
+    /// Nothing worked, so Windows bitmap-stretches everything.
+    Unaware,
+}
+
+/// The DPI functions that this version of Windows has.
+struct DpiFunctions {
+    adjust_window_rect_ex_for_dpi: AdjustWindowRectExForDpi_t,
+    get_dpi_for_monitor: GetDpiForMonitor_t,
+    get_dpi_for_window: GetDpiForWindow_t,
+    get_system_metrics_for_dpi: GetSystemMetricsForDpi_t,
+    set_process_dpi_awareness_context: SetProcessDpiAwarenessContext_t,
+    set_process_dpi_awareness: SetProcessDpiAwareness_t,
+    set_process_dpi_aware: SetProcessDPIAware_t,
+}
+
+fn functions() -> &'static DpiFunctions {
+    static FUNCTIONS: OnceLock<DpiFunctions> = OnceLock::new();
+    FUNCTIONS.get_or_init(|| {
+        let user32 = Library::load("User32.dll").ok();
+        let shcore = Library::load("Shcore.dll").ok();
+
+        // Safety: each type matches the function's signature, and the libraries are never
+        // unloaded (below), so the pointers stay valid
+        let functions = unsafe {
+            DpiFunctions {
+                adjust_window_rect_ex_for_dpi: user32.as_ref().and_then(|l| {
+                    l.get_proc(c_str!("AdjustWindowRectExForDpi"))
+                        .ok()
+                        .flatten()
+                }),
+                get_dpi_for_monitor: shcore
+                    .as_ref()
+                    .and_then(|l| l.get_proc(c_str!("GetDpiForMonitor")).ok().flatten()),
+                get_dpi_for_window: user32
+                    .as_ref()
+                    .and_then(|l| l.get_proc(c_str!("GetDpiForWindow")).ok().flatten()),
+                get_system_metrics_for_dpi: user32
+                    .as_ref()
+                    .and_then(|l| l.get_proc(c_str!("GetSystemMetricsForDpi")).ok().flatten()),
+                set_process_dpi_awareness_context: user32.as_ref().and_then(|l| {
+                    l.get_proc(c_str!("SetProcessDpiAwarenessContext"))
+                        .ok()
+                        .flatten()
+                }),
+                set_process_dpi_awareness: shcore
+                    .as_ref()
+                    .and_then(|l| l.get_proc(c_str!("SetProcessDpiAwareness")).ok().flatten()),
+                set_process_dpi_aware: user32
+                    .as_ref()
+                    .and_then(|l| l.get_proc(c_str!("SetProcessDPIAware")).ok().flatten()),
+            }
+        };
+
+        // Keep both loaded for the rest of the process, since the function pointers point into them
+        mem::forget(user32);
+        mem::forget(shcore);
+        functions
+    })
+}
+
+/// Makes the process per-monitor DPI aware, falling back to older kinds of awareness on older
+/// versions of Windows. This only does anything the first time it's called.
+///
+/// It should be called before any windows are created. [`WindowBuilder`] calls it for you.
+///
+/// **Returns:** The kind of awareness that was set. If the awareness had already been set (e.g.
+/// by the application manifest), this is [`DpiAwareness::Unaware`] even though the process may
+/// well be aware.
+///
+/// [`WindowBuilder`]: crate::window_builder::WindowBuilder
+pub fn enable_per_monitor_dpi_awareness() -> DpiAwareness {
+    static AWARENESS: OnceLock<DpiAwareness> = OnceLock::new();
+    *AWARENESS.get_or_init(|| {
+        let f = functions();
+        // Safety: all of these take plain values
+        unsafe {
+            if let Some(set) = f.set_process_dpi_awareness_context {
+                if set(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2) != 0 {
+                    return DpiAwareness::PerMonitorV2;
+                }
+            }
+            if let Some(set) = f.set_process_dpi_awareness {
+                if set(PROCESS_PER_MONITOR_DPI_AWARE) >= 0 {
+                    return DpiAwareness::PerMonitor;
+                }
+            }
+            if let Some(set) = f.set_process_dpi_aware {
+                if set() != 0 {
+                    return DpiAwareness::System;
+                }
+            }
+        }
+        DpiAwareness::Unaware
+    })
+}
+
+/// The DPI of the primary monitor when the process started, which is what everything is drawn at
+/// if the process isn't per-monitor aware.
+pub fn system_dpi() -> u32 {
+    // Safety: a null window gets the DC for the whole screen, which is released straight away
+    unsafe {
+        let hdc = GetDC(ptr::null_mut());
+        if hdc.is_null() {
+            return USER_DEFAULT_SCREEN_DPI;
+        }
+        let dpi = GetDeviceCaps(hdc, LOGPIXELSX);
+        ReleaseDC(ptr::null_mut(), hdc);
+        if dpi > 0 {
+            dpi as u32
+        } else {
+            USER_DEFAULT_SCREEN_DPI
+        }
+    }
+}
+
+/// The size of the frame that a window with the given styles has at `dpi`.
+///
+/// Uses `AdjustWindowRectExForDpi` if it's available, and otherwise scales what
+/// [`AdjustWindowRectEx`] says for the system DPI.
+pub fn frame_insets(style: DWORD, ex_style: DWORD, dpi: u32) -> Result<FrameInsets, Win32Error> {
+    // Any client rect will do, since it's only the difference that matters
+    let client = RECT {
+        left: 0,
+        top: 0,
+        right: 100,
+        bottom: 100,
+    };
+    let mut window = client;
+
+    // Safety: the rect is valid, and the styles are plain values
+    let (adjusted, from_dpi) = unsafe {
+        match functions().adjust_window_rect_ex_for_dpi {
+            Some(adjust) => (adjust(&mut window, style, 0, ex_style, dpi), dpi),
+            None => (
+                AdjustWindowRectEx(&mut window, style, 0, ex_style),
+                system_dpi(),
+            ),
+        }
+    };
+    if adjusted == 0 {
+        return Err(get_last_error());
+    }
+
+    let insets = FrameInsets::between(rect_from_win32(client), rect_from_win32(window));
+    Ok(insets.scale(from_dpi, dpi))
+}
+
+/// A [`GetSystemMetrics`] value, like the size of an icon, as it is at `dpi`.
+///
+/// Uses `GetSystemMetricsForDpi` if it's available, and otherwise scales what
+/// [`GetSystemMetrics`] says for the system DPI.
+pub fn system_metric_for_dpi(index: CInt, dpi: u32) -> CInt {
+    // Safety: both take plain values
+    unsafe {
+        if let Some(get_metric) = functions().get_system_metrics_for_dpi {
+            return get_metric(index, dpi);
+        }
+        let system_dpi = system_dpi();
+        let metric = GetSystemMetrics(index);
+        (i64::from(metric) * i64::from(dpi) / i64::from(system_dpi)) as CInt
+    }
+}
+
+/// The effective DPI of a monitor, or of the system if that can't be found out (before Windows
+/// 8.1, or if the process isn't per-monitor DPI aware).
+///
+/// ## Safety
+///
+/// `monitor` must be a valid monitor handle.
+pub unsafe fn monitor_dpi(monitor: HMONITOR) -> u32 {
+    if let Some(get_dpi) = functions().get_dpi_for_monitor {
+        let (mut x, mut y) = (0, 0);
+        if get_dpi(monitor, MDT_EFFECTIVE_DPI, &mut x, &mut y) >= 0 && x != 0 {
+            return x;
+        }
+    }
+    system_dpi()
+}
+
+pub(crate) fn rect_from_win32(rect: RECT) -> Rect {
+    Rect::new(rect.left, rect.top, rect.right, rect.bottom)
+}
+
+impl Window<'_> {
+    /// The DPI of the monitor that the window is on, or of the system if the process isn't
+    /// per-monitor DPI aware (see [`enable_per_monitor_dpi_awareness`]).
+    pub fn dpi(&self) -> u32 {
+        let dpi = match functions().get_dpi_for_window {
+            // Safety: the window is valid as long as `self` is
+            Some(get_dpi) => unsafe { get_dpi(self.hwnd()) },
+            None => 0,
+        };
+        if dpi == 0 {
+            system_dpi()
+        } else {
+            dpi
+        }
+    }
+
+    /// How many physical pixels there are for each logical pixel in the window, e.g. `1.5` on a
+    /// monitor with 150% scaling.
+    pub fn scale_factor(&self) -> f64 {
+        scale_factor(self.dpi())
+    }
+}
@@ -0,0 +1,49 @@
+//! Files dragged onto a window from Explorer and other programs.
+//!
+//! A window only gets [`WM_DROPFILES`] once it's said it accepts files, with
+//! [`Window::accept_files`](crate::handles::Window::accept_files). Windows with a
+//! [`WindowHandler`](crate::window_handler::WindowHandler) then get an
+//! [`Event::FileDropped`](event::Event::FileDropped) for each file, through
+//! [`WindowHandler::event`](crate::window_handler::WindowHandler::event).
+
+use core::ptr;
+use std::path::PathBuf;
+
+use crate::{prelude::*, str_util::WideStr};
+
+/// Asks `DragQueryFileW` for the number of files, instead of one of them.
+const FILE_COUNT: UINT = 0xFFFF_FFFF;
+
+/// Sets whether `hwnd` gets [`WM_DROPFILES`] when files are dropped on it.
+///
+/// ## Safety
+///
+/// `hwnd` must be a valid window.
+pub unsafe fn accept_files(hwnd: HWND, accept: bool) {
+    DragAcceptFiles(hwnd, accept.into());
+}
+
+/// Reads the paths out of the handle from a [`WM_DROPFILES`] message, and frees it.
+///
+/// ## Safety
+///
+/// `hdrop` must be the `wParam` of a [`WM_DROPFILES`] message that hasn't been freed yet.
+pub unsafe fn take_dropped_files(hdrop: HDROP) -> Vec<PathBuf> {
+    let count = DragQueryFileW(hdrop, FILE_COUNT, ptr::null_mut(), 0);
+    let mut paths = Vec::with_capacity(count as usize);
+    let mut buffer = Vec::new();
+    for i in 0..count {
+        // the length doesn't include the null on the end
+        let len = DragQueryFileW(hdrop, i, ptr::null_mut(), 0) + 1;
+        buffer.clear();
+        buffer.resize(len as usize, 0);
+        if DragQueryFileW(hdrop, i, buffer.as_mut_ptr(), len) == 0 {
+            continue;
+        }
+        if let Ok(path) = WideStr::from_units_until_null(&buffer) {
+            paths.push(path.to_os_string().into());
+        }
+    }
+    DragFinish(hdrop);
+    paths
+}
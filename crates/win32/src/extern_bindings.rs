@@ -8,6 +8,29 @@ extern "system" {
     /// See [`ChoosePixelFormat` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-choosepixelformat).
     pub fn ChoosePixelFormat(hdc: HDC, ppfd: *const PIXELFORMATDESCRIPTOR) -> CInt;
 
+    /// See [`CreateBitmap` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-createbitmap).
+    pub fn CreateBitmap(
+        nWidth: CInt,
+        nHeight: CInt,
+        nPlanes: UINT,
+        nBitCount: UINT,
+        lpBits: *const core::ffi::c_void,
+    ) -> HBITMAP;
+
+    /// See [`CreateDCW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-createdcw).
+    pub fn CreateDCW(
+        pwszDriver: LPCWSTR,
+        pwszDevice: LPCWSTR,
+        pszPort: LPCWSTR,
+        pdm: *const DEVMODEW,
+    ) -> HDC;
+
+    /// See [`DeleteDC` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-deletedc).
+    pub fn DeleteDC(hdc: HDC) -> BOOL;
+
+    /// See [`DeleteObject` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-deleteobject).
+    pub fn DeleteObject(ho: HGDIOBJ) -> BOOL;
+
     // See [`DescribePixelFormat` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-describepixelformat).
     pub fn DescribePixelFormat(
         hdc: HDC,
@@ -16,6 +39,9 @@ extern "system" {
         ppfd: LPPIXELFORMATDESCRIPTOR,
     ) -> CInt;
 
+    /// See [`GetDeviceCaps` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-getdevicecaps).
+    pub fn GetDeviceCaps(hdc: HDC, index: CInt) -> CInt;
+
     /// See [`SetPixelFormat` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-setpixelformat).
     pub fn SetPixelFormat(hdc: HDC, format: CInt, ppfd: *const PIXELFORMATDESCRIPTOR) -> BOOL;
 
@@ -25,6 +51,31 @@ extern "system" {
 
 #[link(name = "Kernel32")]
 extern "system" {
+    /// See [`CancelIoEx` on MSDN](https://docs.microsoft.com/en-us/windows/win32/fileio/cancelioex-func).
+    pub fn CancelIoEx(hFile: HANDLE, lpOverlapped: LPOVERLAPPED) -> BOOL;
+
+    /// See [`CloseHandle` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/handleapi/nf-handleapi-closehandle).
+    pub fn CloseHandle(hObject: HANDLE) -> BOOL;
+
+    /// See [`CreateEventW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/synchapi/nf-synchapi-createeventw).
+    pub fn CreateEventW(
+        lpEventAttributes: LPSECURITY_ATTRIBUTES,
+        bManualReset: BOOL,
+        bInitialState: BOOL,
+        lpName: LPCWSTR,
+    ) -> HANDLE;
+
+    /// See [`CreateFileW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-createfilew).
+    pub fn CreateFileW(
+        lpFileName: LPCWSTR,
+        dwDesiredAccess: DWORD,
+        dwShareMode: DWORD,
+        lpSecurityAttributes: LPSECURITY_ATTRIBUTES,
+        dwCreationDisposition: DWORD,
+        dwFlagsAndAttributes: DWORD,
+        hTemplateFile: HANDLE,
+    ) -> HANDLE;
+
     /// See [`FormatMessageW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-formatmessagew).
     pub fn FormatMessageW(
         dwFlags: DWORD,
@@ -45,17 +96,69 @@ extern "system" {
     /// See [`GetModuleHandleW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/libloaderapi/nf-libloaderapi-getmodulehandlew).
     pub fn GetModuleHandleW(lpModuleName: LPCWSTR) -> HMODULE;
 
+    /// See [`GetOverlappedResult` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/ioapiset/nf-ioapiset-getoverlappedresult).
+    pub fn GetOverlappedResult(
+        hFile: HANDLE,
+        lpOverlapped: LPOVERLAPPED,
+        lpNumberOfBytesTransferred: LPDWORD,
+        bWait: BOOL,
+    ) -> BOOL;
+
     /// See [`GetProcAddress` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/libloaderapi/nf-libloaderapi-getprocaddress).
     pub fn GetProcAddress(hModule: HMODULE, lpProcName: LPCSTR) -> FARPROC;
 
+    /// See [`GlobalAlloc` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-globalalloc).
+    pub fn GlobalAlloc(uFlags: UINT, dwBytes: SIZE_T) -> HGLOBAL;
+
+    /// See [`GlobalFree` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-globalfree).
+    pub fn GlobalFree(hMem: HGLOBAL) -> HGLOBAL;
+
+    /// See [`GlobalLock` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-globallock).
+    pub fn GlobalLock(hMem: HGLOBAL) -> LPVOID;
+
+    /// See [`GlobalSize` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-globalsize).
+    pub fn GlobalSize(hMem: HGLOBAL) -> SIZE_T;
+
+    /// See [`GlobalUnlock` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-globalunlock).
+    pub fn GlobalUnlock(hMem: HGLOBAL) -> BOOL;
+
     /// See [`LoadLibraryW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/libloaderapi/nf-libloaderapi-loadlibraryw).
     pub fn LoadLibraryW(lpLibFileName: LPCWSTR) -> HMODULE;
 
     /// See [`LocalFree` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-localfree).
     pub fn LocalFree(hMem: HLOCAL) -> HLOCAL;
 
+    /// See [`QueryPerformanceCounter` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/profileapi/nf-profileapi-queryperformancecounter).
+    pub fn QueryPerformanceCounter(lpPerformanceCount: *mut LARGE_INTEGER) -> BOOL;
+
+    /// See [`QueryPerformanceFrequency` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/profileapi/nf-profileapi-queryperformancefrequency).
+    pub fn QueryPerformanceFrequency(lpFrequency: *mut LARGE_INTEGER) -> BOOL;
+
+    /// See [`ReadDirectoryChangesW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-readdirectorychangesw).
+    pub fn ReadDirectoryChangesW(
+        hDirectory: HANDLE,
+        lpBuffer: LPVOID,
+        nBufferLength: DWORD,
+        bWatchSubtree: BOOL,
+        dwNotifyFilter: DWORD,
+        lpBytesReturned: LPDWORD,
+        lpOverlapped: LPOVERLAPPED,
+        lpCompletionRoutine: LPOVERLAPPED_COMPLETION_ROUTINE,
+    ) -> BOOL;
+
     /// See [`SetLastError` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/errhandlingapi/nf-errhandlingapi-setlasterror).
     pub fn SetLastError(dwErrCode: DWORD);
+
+    /// See [`WaitForMultipleObjects` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/synchapi/nf-synchapi-waitformultipleobjects).
+    pub fn WaitForMultipleObjects(
+        nCount: DWORD,
+        lpHandles: *const HANDLE,
+        bWaitAll: BOOL,
+        dwMilliseconds: DWORD,
+    ) -> DWORD;
+
+    /// See [`WaitForSingleObject` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/synchapi/nf-synchapi-waitforsingleobject).
+    pub fn WaitForSingleObject(hHandle: HANDLE, dwMilliseconds: DWORD) -> DWORD;
 }
 
 #[link(name = "Opengl32")]
@@ -93,14 +196,72 @@ pub type wglCreateContextAttribsARB_t = Option<
 /// Type for [`wglGetExtensionsStringARB`](https://www.khronos.org/registry/OpenGL/extensions/ARB/WGL_ARB_extensions_string.txt).
 pub type wglGetExtensionsStringARB_t = Option<unsafe extern "system" fn(HDC) -> *const CChar>;
 
+/// Type for [wglGetPixelFormatAttribivARB](https://www.khronos.org/registry/OpenGL/extensions/ARB/WGL_ARB_pixel_format.txt).
+pub type wglGetPixelFormatAttribivARB_t = Option<
+    unsafe extern "system" fn(
+        hdc: HDC,
+        iPixelFormat: CInt,
+        iLayerPlane: CInt,
+        nAttributes: UINT,
+        piAttributes: *const CInt,
+        piValues: *mut CInt,
+    ) -> BOOL,
+>;
+
+/// Type for [wglGetSwapIntervalEXT](https://www.khronos.org/registry/OpenGL/extensions/EXT/WGL_EXT_swap_control.txt)
+pub type wglGetSwapIntervalEXT_t = Option<unsafe extern "system" fn() -> CInt>;
+
 /// Type for [wglSwapIntervalEXT](https://www.khronos.org/registry/OpenGL/extensions/EXT/WGL_EXT_swap_control.txt)
 pub type wglSwapIntervalEXT_t = Option<unsafe extern "system" fn(interval: CInt) -> BOOL>;
 
+#[link(name = "Shell32")]
+extern "system" {
+    /// See [`DragAcceptFiles` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/shellapi/nf-shellapi-dragacceptfiles).
+    pub fn DragAcceptFiles(hWnd: HWND, fAccept: BOOL);
+
+    /// See [`DragFinish` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/shellapi/nf-shellapi-dragfinish).
+    pub fn DragFinish(hDrop: HDROP);
+
+    /// See [`DragQueryFileW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/shellapi/nf-shellapi-dragqueryfilew).
+    pub fn DragQueryFileW(hDrop: HDROP, iFile: UINT, lpszFile: LPWSTR, cch: UINT) -> UINT;
+}
+
 #[link(name = "User32")]
 extern "system" {
+    /// See [`AdjustWindowRectEx` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-adjustwindowrectex).
+    pub fn AdjustWindowRectEx(
+        lpRect: LPRECT,
+        dwStyle: DWORD,
+        bMenu: BOOL,
+        dwExStyle: DWORD,
+    ) -> BOOL;
+
     /// See [`BeginPaint` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-beginpaint).
     pub fn BeginPaint(hWnd: HWND, lpPaint: LPPAINTSTRUCT) -> HDC;
 
+    /// See [`ChangeDisplaySettingsExW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-changedisplaysettingsexw).
+    pub fn ChangeDisplaySettingsExW(
+        lpszDeviceName: LPCWSTR,
+        lpDevMode: *const DEVMODEW,
+        hwnd: HWND,
+        dwflags: DWORD,
+        lParam: LPVOID,
+    ) -> LONG;
+
+    /// See [`ClientToScreen` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-clienttoscreen).
+    pub fn ClientToScreen(hWnd: HWND, lpPoint: LPPOINT) -> BOOL;
+
+    /// See [`ClipCursor` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-clipcursor).
+    ///
+    /// A null rect lets the cursor move anywhere again.
+    pub fn ClipCursor(lpRect: *const RECT) -> BOOL;
+
+    /// See [`CloseClipboard` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-closeclipboard).
+    pub fn CloseClipboard() -> BOOL;
+
+    /// See [`CreateIconIndirect` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createiconindirect).
+    pub fn CreateIconIndirect(piconinfo: PICONINFO) -> HICON;
+
     /// See [`CreateWindowExW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createwindowexw).
     pub fn CreateWindowExW(
         dwExStyle: DWORD,
@@ -120,54 +281,240 @@ extern "system" {
     /// See [`DefWindowProcW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-defwindowprocw)
     pub fn DefWindowProcW(hWnd: HWND, Msg: UINT, wParam: WPARAM, lParam: LPARAM) -> LRESULT;
 
+    /// See [`DestroyCursor` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-destroycursor).
+    pub fn DestroyCursor(hCursor: HCURSOR) -> BOOL;
+
+    /// See [`DestroyIcon` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-destroyicon).
+    pub fn DestroyIcon(hIcon: HICON) -> BOOL;
+
     /// See [`DestroyWindow` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-destroywindow).
     pub fn DestroyWindow(hWnd: HWND) -> BOOL;
 
     /// See [`DispatchMessageW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-dispatchmessagew).
     pub fn DispatchMessageW(lpMsg: *const MSG) -> LRESULT;
 
+    /// See [`EmptyClipboard` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-emptyclipboard).
+    pub fn EmptyClipboard() -> BOOL;
+
     /// See [`EndPaint` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-endpaint).
     pub fn EndPaint(hWnd: HWND, lpPaint: *const PAINTSTRUCT) -> BOOL;
 
+    /// See [`EnumDisplayMonitors` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-enumdisplaymonitors).
+    pub fn EnumDisplayMonitors(
+        hdc: HDC,
+        lprcClip: *const RECT,
+        lpfnEnum: MONITORENUMPROC,
+        dwData: LPARAM,
+    ) -> BOOL;
+
+    /// See [`EnumDisplaySettingsW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-enumdisplaysettingsw).
+    ///
+    /// `iModeNum` is an index into the device's modes, or [`ENUM_CURRENT_SETTINGS`].
+    pub fn EnumDisplaySettingsW(
+        lpszDeviceName: LPCWSTR,
+        iModeNum: DWORD,
+        lpDevMode: *mut DEVMODEW,
+    ) -> BOOL;
+
     /// See [`FillRect` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-fillrect).
     pub fn FillRect(hDC: HDC, lprc: *const RECT, hbr: HBRUSH) -> CInt;
 
+    /// See [`GetClientRect` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getclientrect).
+    pub fn GetClientRect(hWnd: HWND, lpRect: LPRECT) -> BOOL;
+
+    /// See [`GetClipboardData` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getclipboarddata).
+    pub fn GetClipboardData(uFormat: UINT) -> HANDLE;
+
     /// See [`GetDC` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getdc).
     pub fn GetDC(hWnd: HWND) -> HDC;
 
+    /// See [`GetKeyboardLayout` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getkeyboardlayout).
+    pub fn GetKeyboardLayout(idThread: DWORD) -> HKL;
+
+    /// See [`GetKeyboardState` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getkeyboardstate).
+    ///
+    /// `lpKeyState` must point to 256 bytes, one for each virtual key: the high bit is set while
+    /// the key is held, and the low bit while it's toggled on.
+    pub fn GetKeyboardState(lpKeyState: *mut BYTE) -> BOOL;
+
+    /// See [`GetKeyState` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getkeystate).
+    pub fn GetKeyState(nVirtKey: CInt) -> SHORT;
+
     /// See [`GetMessageW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmessagew).
     pub fn GetMessageW(lpMsg: LPMSG, hWnd: HWND, wMsgFilterMin: UINT, wMsgFilterMax: UINT) -> BOOL;
 
+    /// See [`GetMonitorInfoW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmonitorinfow).
+    ///
+    /// `lpmi` can point to a `MONITORINFO` or a [`MONITORINFOEXW`], as told apart by its size.
+    pub fn GetMonitorInfoW(hMonitor: HMONITOR, lpmi: *mut MONITORINFOEXW) -> BOOL;
+
+    /// See [`GetRawInputData` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getrawinputdata).
+    pub fn GetRawInputData(
+        hRawInput: HRAWINPUT,
+        uiCommand: UINT,
+        pData: LPVOID,
+        pcbSize: *mut UINT,
+        cbSizeHeader: UINT,
+    ) -> UINT;
+
+    /// See [`GetSystemMetrics` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getsystemmetrics).
+    pub fn GetSystemMetrics(nIndex: CInt) -> CInt;
+
     /// See [`GetWindowLongPtrW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getwindowlongptrw).
     pub fn GetWindowLongPtrW(hWnd: HWND, nIndex: CInt) -> LONG_PTR;
 
+    /// See [`GetWindowPlacement` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getwindowplacement).
+    pub fn GetWindowPlacement(hWnd: HWND, lpwndpl: *mut WINDOWPLACEMENT) -> BOOL;
+
+    /// See [`GetWindowRect` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getwindowrect).
+    pub fn GetWindowRect(hWnd: HWND, lpRect: LPRECT) -> BOOL;
+
     /// See [`InvalidateRect` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-invalidaterect).
     pub fn InvalidateRect(hWnd: HWND, lpRect: *const RECT, bErase: BOOL) -> BOOL;
 
+    /// See [`IsClipboardFormatAvailable` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-isclipboardformatavailable).
+    pub fn IsClipboardFormatAvailable(format: UINT) -> BOOL;
+
     /// See [`LoadCursorW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-loadcursorw).
     pub fn LoadCursorW(hInstance: HINSTANCE, lpCursorName: LPCWSTR) -> HCURSOR;
 
+    /// See [`MapVirtualKeyW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-mapvirtualkeyw).
+    pub fn MapVirtualKeyW(uCode: UINT, uMapType: UINT) -> UINT;
+
     /// See [`MessageBoxW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-messageboxw).
     pub fn MessageBoxW(hWnd: HWND, lpText: LPCWSTR, lpCaption: LPCWSTR, uType: UINT) -> CInt;
 
+    /// See [`MonitorFromWindow` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-monitorfromwindow).
+    pub fn MonitorFromWindow(hwnd: HWND, dwFlags: DWORD) -> HMONITOR;
+
+    /// See [`OpenClipboard` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-openclipboard).
+    pub fn OpenClipboard(hWndNewOwner: HWND) -> BOOL;
+
+    /// See [`PeekMessageW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-peekmessagew).
+    pub fn PeekMessageW(
+        lpMsg: LPMSG,
+        hWnd: HWND,
+        wMsgFilterMin: UINT,
+        wMsgFilterMax: UINT,
+        wRemoveMsg: UINT,
+    ) -> BOOL;
+
     /// See [`PostQuitMessage` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-postquitmessage).
     pub fn PostQuitMessage(nExitCode: CInt);
 
     /// See [`RegisterClassW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerclassw).
     pub fn RegisterClassW(lpWndClass: *const WNDCLASSW) -> ATOM;
 
+    /// See [`RegisterRawInputDevices` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerrawinputdevices).
+    pub fn RegisterRawInputDevices(
+        pRawInputDevices: *const RAWINPUTDEVICE,
+        uiNumDevices: UINT,
+        cbSize: UINT,
+    ) -> BOOL;
+
     /// See [`ReleaseDC` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-releasedc).
     pub fn ReleaseDC(hWnd: HWND, hDC: HDC) -> CInt;
 
+    /// See [`SendMessageW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-sendmessagew).
+    pub fn SendMessageW(hWnd: HWND, Msg: UINT, wParam: WPARAM, lParam: LPARAM) -> LRESULT;
+
+    /// See [`SetClipboardData` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setclipboarddata).
+    pub fn SetClipboardData(uFormat: UINT, hMem: HANDLE) -> HANDLE;
+
+    /// See [`SetCursor` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setcursor).
+    pub fn SetCursor(hCursor: HCURSOR) -> HCURSOR;
+
     /// See [`SetWindowLongPtrW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setwindowlongptrw).
     pub fn SetWindowLongPtrW(hWnd: HWND, nIndex: CInt, dwNewLong: LONG_PTR) -> LONG_PTR;
 
+    /// See [`SetWindowPlacement` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setwindowplacement).
+    pub fn SetWindowPlacement(hWnd: HWND, lpwndpl: *const WINDOWPLACEMENT) -> BOOL;
+
+    /// See [`SetWindowPos` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setwindowpos).
+    pub fn SetWindowPos(
+        hWnd: HWND,
+        hWndInsertAfter: HWND,
+        X: CInt,
+        Y: CInt,
+        cx: CInt,
+        cy: CInt,
+        uFlags: UINT,
+    ) -> BOOL;
+
+    /// See [`ShowCursor` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-showcursor).
+    ///
+    /// This changes a counter rather than setting visibility directly: the cursor shows while the
+    /// counter is at least zero.
+    ///
+    /// **Returns:** The new counter.
+    pub fn ShowCursor(bShow: BOOL) -> CInt;
+
     /// See [`ShowWindow` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-showwindow).
     pub fn ShowWindow(hWnd: HWND, nCmdShow: CInt) -> BOOL;
 
+    /// See [`ToUnicodeEx` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-tounicodeex).
+    ///
+    /// **Returns:** How many UTF-16 units were written, or a negative number for a dead key.
+    pub fn ToUnicodeEx(
+        wVirtKey: UINT,
+        wScanCode: UINT,
+        lpKeyState: *const BYTE,
+        pwszBuff: LPWSTR,
+        cchBuff: CInt,
+        wFlags: UINT,
+        dwhkl: HKL,
+    ) -> CInt;
+
     /// See [`TranslateMessage` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-translatemessage).
     pub fn TranslateMessage(lpMsg: *const MSG) -> BOOL;
 
     /// See [`UnregisterClassW` on MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterclassw).
     pub fn UnregisterClassW(lpClassName: LPCWSTR, hInstance: HINSTANCE) -> BOOL;
 }
+
+// These are only in newer versions of Windows, so they're loaded at runtime by [`crate::dpi`].
+
+/// Type for [`AdjustWindowRectExForDpi`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-adjustwindowrectexfordpi)
+/// from `User32.dll`, in Windows 10 version 1607 and later.
+pub type AdjustWindowRectExForDpi_t = Option<
+    unsafe extern "system" fn(
+        lpRect: LPRECT,
+        dwStyle: DWORD,
+        bMenu: BOOL,
+        dwExStyle: DWORD,
+        dpi: UINT,
+    ) -> BOOL,
+>;
+
+/// Type for [`GetDpiForMonitor`](https://docs.microsoft.com/en-us/windows/win32/api/shellscalingapi/nf-shellscalingapi-getdpiformonitor)
+/// from `Shcore.dll`, in Windows 8.1 and later.
+pub type GetDpiForMonitor_t = Option<
+    unsafe extern "system" fn(
+        hmonitor: HMONITOR,
+        dpiType: CInt,
+        dpiX: *mut UINT,
+        dpiY: *mut UINT,
+    ) -> HRESULT,
+>;
+
+/// Type for [`GetDpiForWindow`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getdpiforwindow)
+/// from `User32.dll`, in Windows 10 version 1607 and later.
+pub type GetDpiForWindow_t = Option<unsafe extern "system" fn(hwnd: HWND) -> UINT>;
+
+/// Type for [`GetSystemMetricsForDpi`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getsystemmetricsfordpi)
+/// from `User32.dll`, in Windows 10 version 1607 and later.
+pub type GetSystemMetricsForDpi_t =
+    Option<unsafe extern "system" fn(nIndex: CInt, dpi: UINT) -> CInt>;
+
+/// Type for [`SetProcessDpiAwarenessContext`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setprocessdpiawarenesscontext)
+/// from `User32.dll`, in Windows 10 version 1703 and later.
+pub type SetProcessDpiAwarenessContext_t =
+    Option<unsafe extern "system" fn(value: DPI_AWARENESS_CONTEXT) -> BOOL>;
+
+/// Type for [`SetProcessDpiAwareness`](https://docs.microsoft.com/en-us/windows/win32/api/shellscalingapi/nf-shellscalingapi-setprocessdpiawareness)
+/// from `Shcore.dll`, in Windows 8.1 and later.
+pub type SetProcessDpiAwareness_t = Option<unsafe extern "system" fn(value: CInt) -> HRESULT>;
+
+/// Type for [`SetProcessDPIAware`](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setprocessdpiaware)
+/// from `User32.dll`, in Windows Vista and later.
+pub type SetProcessDPIAware_t = Option<unsafe extern "system" fn() -> BOOL>;
@@ -0,0 +1,269 @@
+//! Switching windows between windowed and fullscreen, like an F11 key does.
+//!
+//! Fullscreen windows are borderless windows covering the monitor they're on, which is how
+//! browsers and most games do it: switching is instant, and other windows can still go on top.
+//! [`FullscreenMode::Exclusive`] changes the monitor's display mode first as well.
+//!
+//! ```no_run
+//! # use triangle_from_scratch_win32::{fullscreen::{Fullscreen, FullscreenMode}, prelude::*};
+//! # fn f(hwnd: HWND, fullscreen: &mut Fullscreen) -> Result<(), Win32Error> {
+//! // e.g. on F11, in a `WindowHandler`
+//! unsafe { fullscreen.toggle(hwnd, FullscreenMode::Borderless) }?;
+//! # Ok(())
+//! # }
+//! ```
+
+use core::ptr;
+
+use crate::{dpi::rect_from_win32, geometry::Rect, get_last_error, prelude::*};
+
+/// How a window covers its monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FullscreenMode {
+    /// The window loses its frame and covers the monitor, which keeps its display mode.
+    Borderless,
+    /// The monitor is switched to another display mode, and then the window covers it like
+    /// [`Borderless`](Self::Borderless). The mode is switched back when fullscreen is exited, or
+    /// when the process exits.
+    Exclusive {
+        /// The resolution, in physical pixels.
+        size: [u32; 2],
+        /// The refresh rate in hertz, or `None` to let Windows choose.
+        refresh_rate: Option<u32>,
+    },
+}
+
+/// The window style to use while fullscreen: the windowed style without a title bar or frame.
+pub const fn fullscreen_style(style: DWORD) -> DWORD {
+    style & !WS_OVERLAPPEDWINDOW
+}
+
+/// What's needed to put a window back the way it was.
+#[derive(Debug, Clone, Copy)]
+struct Saved {
+    style: LONG_PTR,
+    placement: WINDOWPLACEMENT,
+    /// The device name of the monitor whose display mode was changed, if it was.
+    changed_device: Option<[WCHAR; 32]>,
+}
+
+/// Switches a window in and out of fullscreen, remembering where it was.
+#[derive(Debug, Default)]
+pub struct Fullscreen {
+    saved: Option<Saved>,
+}
+
+impl Fullscreen {
+    pub const fn new() -> Self {
+        Self { saved: None }
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.saved.is_some()
+    }
+
+    /// Makes the window cover the monitor that it's (mostly) on. Does nothing if it's already
+    /// fullscreen.
+    ///
+    /// Changing the display mode for [`FullscreenMode::Exclusive`] fails with an application error
+    /// whose low bits are the negated `DISP_CHANGE_*` code, e.g. `2` for `DISP_CHANGE_BADMODE`.
+    ///
+    /// ## Safety
+    ///
+    /// `hwnd` must be a valid top-level window.
+    pub unsafe fn enter(&mut self, hwnd: HWND, mode: FullscreenMode) -> Result<(), Win32Error> {
+        if self.saved.is_some() {
+            return Ok(());
+        }
+
+        let mut placement = WINDOWPLACEMENT::default();
+        if GetWindowPlacement(hwnd, &mut placement) == 0 {
+            return Err(get_last_error());
+        }
+        let style = GetWindowLongPtrW(hwnd, GWL_STYLE);
+
+        let mut changed_device = None;
+        let mut monitor = monitor_info(hwnd)?;
+        if let FullscreenMode::Exclusive { size, refresh_rate } = mode {
+            let mut dev_mode = DEVMODEW {
+                dmFields: DM_PELSWIDTH | DM_PELSHEIGHT,
+                dmPelsWidth: size[0],
+                dmPelsHeight: size[1],
+                ..Default::default()
+            };
+            if let Some(refresh_rate) = refresh_rate {
+                dev_mode.dmFields |= DM_DISPLAYFREQUENCY;
+                dev_mode.dmDisplayFrequency = refresh_rate;
+            }
+            change_display_mode(&monitor.szDevice, &dev_mode)?;
+            changed_device = Some(monitor.szDevice);
+            // the monitor's rect changes with its resolution
+            monitor = monitor_info(hwnd)?;
+        }
+
+        self.saved = Some(Saved {
+            style,
+            placement,
+            changed_device,
+        });
+        SetWindowLongPtrW(
+            hwnd,
+            GWL_STYLE,
+            fullscreen_style(style as DWORD) as LONG_PTR,
+        );
+        set_window_rect(hwnd, rect_from_win32(monitor.rcMonitor))
+    }
+
+    /// Puts the window back where it was before [`enter`](Self::enter), with its frame, and
+    /// restores the monitor's display mode if it was changed. Does nothing if the window isn't
+    /// fullscreen.
+    ///
+    /// ## Safety
+    ///
+    /// `hwnd` must be the window that [`enter`](Self::enter) was called with.
+    pub unsafe fn exit(&mut self, hwnd: HWND) -> Result<(), Win32Error> {
+        let Some(saved) = self.saved.take() else {
+            return Ok(());
+        };
+
+        if let Some(device) = &saved.changed_device {
+            // a null mode switches back to the one in the registry
+            let result = ChangeDisplaySettingsExW(
+                device.as_ptr(),
+                ptr::null(),
+                ptr::null_mut(),
+                0,
+                ptr::null_mut(),
+            );
+            if result != DISP_CHANGE_SUCCESSFUL {
+                return Err(display_change_error(result));
+            }
+        }
+
+        SetWindowLongPtrW(hwnd, GWL_STYLE, saved.style);
+        if SetWindowPlacement(hwnd, &saved.placement) == 0 {
+            return Err(get_last_error());
+        }
+        // the frame has to be recalculated for the style change to show
+        if SetWindowPos(
+            hwnd,
+            ptr::null_mut(),
+            0,
+            0,
+            0,
+            0,
+            SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOOWNERZORDER | SWP_FRAMECHANGED,
+        ) == 0
+        {
+            return Err(get_last_error());
+        }
+        Ok(())
+    }
+
+    /// Enters fullscreen if the window is windowed, and exits it otherwise.
+    ///
+    /// **Returns:** Whether the window is now fullscreen.
+    ///
+    /// ## Safety
+    ///
+    /// As for [`enter`](Self::enter) and [`exit`](Self::exit).
+    pub unsafe fn toggle(&mut self, hwnd: HWND, mode: FullscreenMode) -> Result<bool, Win32Error> {
+        if self.is_fullscreen() {
+            self.exit(hwnd)?;
+        } else {
+            self.enter(hwnd, mode)?;
+        }
+        Ok(self.is_fullscreen())
+    }
+}
+
+/// Gets information about the monitor that a window is (mostly) on.
+unsafe fn monitor_info(hwnd: HWND) -> Result<MONITORINFOEXW, Win32Error> {
+    let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+    let mut info = MONITORINFOEXW::default();
+    if GetMonitorInfoW(monitor, &mut info) == 0 {
+        return Err(get_last_error());
+    }
+    Ok(info)
+}
+
+unsafe fn change_display_mode(device: &[WCHAR; 32], mode: &DEVMODEW) -> Result<(), Win32Error> {
+    let result = ChangeDisplaySettingsExW(
+        device.as_ptr(),
+        mode,
+        ptr::null_mut(),
+        CDS_FULLSCREEN,
+        ptr::null_mut(),
+    );
+    if result != DISP_CHANGE_SUCCESSFUL {
+        return Err(display_change_error(result));
+    }
+    Ok(())
+}
+
+fn display_change_error(result: LONG) -> Win32Error {
+    Win32Error(Win32Error::APPLICATION_ERROR_BIT | result.unsigned_abs())
+}
+
+/// Moves a window to cover `rect` exactly, on top of other non-topmost windows.
+unsafe fn set_window_rect(hwnd: HWND, rect: Rect) -> Result<(), Win32Error> {
+    if SetWindowPos(
+        hwnd,
+        ptr::null_mut(),
+        rect.left,
+        rect.top,
+        rect.width(),
+        rect.height(),
+        SWP_NOOWNERZORDER | SWP_FRAMECHANGED,
+    ) == 0
+    {
+        return Err(get_last_error());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        handles::{register_hidden_class, Window},
+        window_builder::WindowBuilder,
+    };
+
+    #[test]
+    fn styles() {
+        let style = WS_OVERLAPPEDWINDOW | WS_CLIPCHILDREN | WS_CLIPSIBLINGS;
+        assert_eq!(fullscreen_style(style), WS_CLIPCHILDREN | WS_CLIPSIBLINGS);
+        assert_eq!(fullscreen_style(WS_POPUP), WS_POPUP);
+    }
+
+    #[test]
+    fn borderless_round_trip() {
+        let class = register_hidden_class("triangle-from-scratch fullscreen test").unwrap();
+        let window: Window<'_> = unsafe {
+            WindowBuilder::new()
+                .position([100, 100])
+                .inner_size([320, 240])
+                .build(&class, ptr::null_mut())
+        }
+        .unwrap();
+        let hwnd = window.hwnd();
+        let style = unsafe { GetWindowLongPtrW(hwnd, GWL_STYLE) };
+
+        let mut fullscreen = Fullscreen::new();
+        assert!(unsafe { fullscreen.toggle(hwnd, FullscreenMode::Borderless) }.unwrap());
+        let monitor = unsafe { monitor_info(hwnd) }.unwrap();
+        let mut rect = RECT::default();
+        assert_ne!(unsafe { GetWindowRect(hwnd, &mut rect) }, 0);
+        assert_eq!(rect_from_win32(rect), rect_from_win32(monitor.rcMonitor));
+        assert_eq!(
+            unsafe { GetWindowLongPtrW(hwnd, GWL_STYLE) } as DWORD & WS_CAPTION,
+            0
+        );
+
+        assert!(!unsafe { fullscreen.toggle(hwnd, FullscreenMode::Borderless) }.unwrap());
+        assert_eq!(unsafe { GetWindowLongPtrW(hwnd, GWL_STYLE) }, style);
+        assert_ne!(unsafe { GetWindowRect(hwnd, &mut rect) }, 0);
+        assert_eq!((rect.left, rect.top), (100, 100));
+    }
+}
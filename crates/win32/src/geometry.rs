@@ -0,0 +1,250 @@
+//! Rectangles and DPI scaling, for sizing windows.
+//!
+//! Like [`str_util`](crate::str_util), this is plain Rust, so it's available (and tested)
+//! everywhere. The Win32 calls that feed it live in [`dpi`](crate::dpi) and
+//! [`window_builder`](crate::window_builder).
+//!
+//! Sizes are either _logical_, which is what the application asks for and is the same on every
+//! monitor, or _physical_, which is in actual pixels. A monitor's DPI says how many physical pixels
+//! there are per logical one, relative to [`USER_DEFAULT_SCREEN_DPI`].
+
+/// The DPI of a monitor with 100% scaling, where logical and physical pixels are the same.
+pub const USER_DEFAULT_SCREEN_DPI: u32 = 96;
+
+/// How many physical pixels there are for each logical pixel at `dpi`, e.g. `1.5` at 144 DPI.
+pub fn scale_factor(dpi: u32) -> f64 {
+    f64::from(dpi) / f64::from(USER_DEFAULT_SCREEN_DPI)
+}
+
+/// Scales `value` from one DPI to another, rounding to the nearest pixel (and halves away from
+/// zero), like `MulDiv` does.
+pub const fn scale(value: i32, from_dpi: u32, to_dpi: u32) -> i32 {
+    let numerator = value as i64 * to_dpi as i64;
+    let denominator = from_dpi as i64;
+    let half = denominator / 2;
+    let rounded = if numerator < 0 {
+        (numerator - half) / denominator
+    } else {
+        (numerator + half) / denominator
+    };
+    rounded as i32
+}
+
+/// Converts a logical size to physical pixels at `dpi`.
+pub const fn to_physical([width, height]: [i32; 2], dpi: u32) -> [i32; 2] {
+    [
+        scale(width, USER_DEFAULT_SCREEN_DPI, dpi),
+        scale(height, USER_DEFAULT_SCREEN_DPI, dpi),
+    ]
+}
+
+/// Converts a physical size at `dpi` to logical pixels.
+pub const fn to_logical([width, height]: [i32; 2], dpi: u32) -> [i32; 2] {
+    [
+        scale(width, dpi, USER_DEFAULT_SCREEN_DPI),
+        scale(height, dpi, USER_DEFAULT_SCREEN_DPI),
+    ]
+}
+
+/// A rectangle in screen coordinates, with `left` and `top` inclusive and `right` and `bottom`
+/// exclusive, like a Win32 `RECT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl Rect {
+    pub const fn new(left: i32, top: i32, right: i32, bottom: i32) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    /// A rectangle of the given size, with its top left corner at `[x, y]`.
+    pub const fn from_position_size([x, y]: [i32; 2], [width, height]: [i32; 2]) -> Self {
+        Self::new(x, y, x + width, y + height)
+    }
+
+    pub const fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    pub const fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+
+    pub const fn position(&self) -> [i32; 2] {
+        [self.left, self.top]
+    }
+
+    pub const fn size(&self) -> [i32; 2] {
+        [self.width(), self.height()]
+    }
+
+    /// A rectangle of the given size, centered on this one.
+    pub const fn center(&self, [width, height]: [i32; 2]) -> Self {
+        let x = self.left + (self.width() - width) / 2;
+        let y = self.top + (self.height() - height) / 2;
+        Self::from_position_size([x, y], [width, height])
+    }
+}
+
+/// How far a window's frame (its borders and title bar) sticks out from its client area on each
+/// side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FrameInsets {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl FrameInsets {
+    /// The insets between a client area and the window rect that `AdjustWindowRectEx` (or
+    /// `AdjustWindowRectExForDpi`) made from it.
+    pub const fn between(client: Rect, window: Rect) -> Self {
+        Self {
+            left: client.left - window.left,
+            top: client.top - window.top,
+            right: window.right - client.right,
+            bottom: window.bottom - client.bottom,
+        }
+    }
+
+    /// Scales the insets to a different DPI.
+    ///
+    /// `AdjustWindowRectEx` only knows about the system DPI, so where `AdjustWindowRectExForDpi`
+    /// isn't available, this approximates it.
+    pub const fn scale(self, from_dpi: u32, to_dpi: u32) -> Self {
+        Self {
+            left: scale(self.left, from_dpi, to_dpi),
+            top: scale(self.top, from_dpi, to_dpi),
+            right: scale(self.right, from_dpi, to_dpi),
+            bottom: scale(self.bottom, from_dpi, to_dpi),
+        }
+    }
+
+    /// The window rect for a client area.
+    pub const fn outer(self, client: Rect) -> Rect {
+        Rect::new(
+            client.left - self.left,
+            client.top - self.top,
+            client.right + self.right,
+            client.bottom + self.bottom,
+        )
+    }
+
+    /// The outer size of a window with the given client area size.
+    pub const fn outer_size(self, [width, height]: [i32; 2]) -> [i32; 2] {
+        [
+            width + self.left + self.right,
+            height + self.top + self.bottom,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod scaling {
+        use super::*;
+
+        #[test]
+        fn scale_factors() {
+            assert_eq!(scale_factor(96), 1.0);
+            assert_eq!(scale_factor(120), 1.25);
+            assert_eq!(scale_factor(144), 1.5);
+            assert_eq!(scale_factor(192), 2.0);
+        }
+
+        #[test]
+        fn rounds_to_nearest_like_muldiv() {
+            // 125%: 1 -> 1.25, 2 -> 2.5, 3 -> 3.75
+            assert_eq!(scale(1, 96, 120), 1);
+            assert_eq!(scale(2, 96, 120), 3);
+            assert_eq!(scale(3, 96, 120), 4);
+            assert_eq!(scale(-2, 96, 120), -3);
+            assert_eq!(scale(0, 96, 144), 0);
+            assert_eq!(scale(i32::MAX, 96, 96), i32::MAX);
+        }
+
+        #[test]
+        fn round_trips() {
+            for dpi in [96, 120, 144, 168, 192, 288] {
+                for size in [[800, 600], [1, 1], [1920, 1080]] {
+                    let physical = to_physical(size, dpi);
+                    // scaling up never loses anything
+                    assert_eq!(to_logical(physical, dpi), size, "{dpi} DPI, {size:?}");
+                }
+            }
+            assert_eq!(to_physical([800, 600], 144), [1200, 900]);
+            assert_eq!(to_logical([1200, 900], 144), [800, 600]);
+        }
+    }
+
+    mod rects {
+        use super::*;
+
+        #[test]
+        fn sizes_and_centering() {
+            let work_area = Rect::new(0, 40, 1920, 1080);
+            assert_eq!(work_area.size(), [1920, 1040]);
+
+            let window = work_area.center([800, 600]);
+            assert_eq!(window, Rect::new(560, 260, 1360, 860));
+            assert_eq!(window.size(), [800, 600]);
+            assert_eq!(
+                Rect::from_position_size(window.position(), window.size()),
+                window
+            );
+        }
+
+        #[test]
+        fn frame_insets() {
+            // what AdjustWindowRectEx gives for WS_OVERLAPPEDWINDOW at 96 DPI on Windows 10
+            let client = Rect::new(0, 0, 800, 600);
+            let window = Rect::new(-8, -31, 808, 608);
+            let insets = FrameInsets::between(client, window);
+            assert_eq!(
+                insets,
+                FrameInsets {
+                    left: 8,
+                    top: 31,
+                    right: 8,
+                    bottom: 8
+                }
+            );
+            assert_eq!(insets.outer(client), window);
+            assert_eq!(insets.outer_size([800, 600]), [816, 639]);
+
+            // at 150%, without AdjustWindowRectExForDpi
+            let scaled = insets.scale(96, 144);
+            assert_eq!(
+                scaled,
+                FrameInsets {
+                    left: 12,
+                    top: 47,
+                    right: 12,
+                    bottom: 12
+                }
+            );
+            let client = to_physical([800, 600], 144);
+            assert_eq!(scaled.outer_size(client), [1224, 959]);
+        }
+
+        #[test]
+        fn borderless_windows_have_no_frame() {
+            let client = Rect::from_position_size([100, 100], [640, 480]);
+            let insets = FrameInsets::between(client, client);
+            assert_eq!(insets, FrameInsets::default());
+            assert_eq!(insets.scale(96, 192).outer(client), client);
+        }
+    }
+}
@@ -0,0 +1,491 @@
+//! Owned Win32 handles that clean up after themselves.
+//!
+//! Each type frees its handle when dropped, and borrows whatever its handle depends on, so the
+//! borrow checker enforces the order things have to be torn down in:
+//!
+//! ```text
+//! WindowClass <- Window <- DeviceContext <- GlRenderContext <- CurrentContextGuard
+//! ```
+//!
+//! A window can't outlive its class, a DC can't outlive its window, and so on. Dropping them in
+//! reverse order (which is what happens to locals at the end of a scope) makes the current context
+//! not current, deletes the context, releases the DC, destroys the window and then unregisters
+//! the class, without any of that having to be written out by hand.
+//!
+//! [`Library`] stands on its own, and unloads its DLL when dropped.
+
+use core::{marker::PhantomData, mem, ptr};
+
+use c_types::{CInt, CStrRef};
+use image::RgbaImage;
+
+use crate::{
+    create_window_ex_w, destroy_window, get_dc, get_last_error, get_process_handle,
+    icon::WindowIcon, load_library, prelude::*, register_class, release_dc, str_util::wide_null,
+    unregister_class_by_atom, wgl_create_context, wgl_delete_context, wgl_make_current,
+};
+
+/// A registered window class, which is unregistered when dropped.
+///
+/// **See**: [`register_class`], [`unregister_class_by_atom`]
+#[derive(Debug)]
+pub struct WindowClass {
+    atom: ATOM,
+    instance: HINSTANCE,
+    window_procedure: WNDPROC,
+}
+
+impl WindowClass {
+    /// Registers a window class.
+    ///
+    /// ## Safety
+    ///
+    /// All pointers in the struct's fields *must* be valid.
+    pub unsafe fn register(window_class: &WNDCLASSW) -> Result<Self, Win32Error> {
+        let atom = register_class(window_class)?;
+        Ok(Self {
+            atom,
+            instance: window_class.hInstance,
+            window_procedure: window_class.lpfnWndProc,
+        })
+    }
+
+    pub fn atom(&self) -> ATOM {
+        self.atom
+    }
+
+    pub fn instance(&self) -> HINSTANCE {
+        self.instance
+    }
+
+    /// The window procedure that windows of this class start out with.
+    pub fn window_procedure(&self) -> WNDPROC {
+        self.window_procedure
+    }
+
+    /// The class's atom, disguised as a class name as `MAKEINTATOM` would, for functions that
+    /// take either.
+    pub fn as_class_name(&self) -> LPCWSTR {
+        self.atom as ULONG_PTR as LPCWSTR
+    }
+}
+
+impl Drop for WindowClass {
+    fn drop(&mut self) {
+        // Safety: the instance came from a successful registration
+        if let Err(e) = unsafe { unregister_class_by_atom(self.atom, self.instance) } {
+            eprintln!("Unable to unregister window class {}: {e}", self.atom);
+        }
+    }
+}
+
+/// A window, which is destroyed when dropped.
+///
+/// Don't destroy the window any other way (e.g. by calling [`DestroyWindow`] when handling
+/// `WM_CLOSE`); drop the `Window` instead.
+///
+/// **See**: [`create_window_ex_w`], [`destroy_window`]
+#[derive(Debug)]
+pub struct Window<'class> {
+    hwnd: HWND,
+    /// The icons from [`set_icon`](Self::set_icon), which are destroyed after the window is.
+    icon: Option<WindowIcon>,
+    _class: PhantomData<&'class WindowClass>,
+}
+
+impl<'class> Window<'class> {
+    /// Creates a window of the given class.
+    ///
+    /// `position` and `size` are the outer position and size, with `None` and [`CW_USEDEFAULT`]
+    /// letting Windows choose. `create_param` is passed to the window procedure as part of
+    /// `WM_NCCREATE` and `WM_CREATE`.
+    ///
+    /// ## Safety
+    ///
+    /// The class's window procedure must cope with whatever `create_param` is.
+    pub unsafe fn create(
+        class: &'class WindowClass,
+        window_name: &str,
+        style: DWORD,
+        ex_style: DWORD,
+        position: Option<[i32; 2]>,
+        [width, height]: [i32; 2],
+        create_param: LPVOID,
+    ) -> Result<Self, Win32Error> {
+        let [x, y] = position.unwrap_or([CW_USEDEFAULT, CW_USEDEFAULT]);
+
+        let hwnd = create_window_ex_w(
+            ex_style,
+            class.as_class_name(),
+            wide_null(window_name).as_ptr(),
+            style,
+            x,
+            y,
+            width,
+            height,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            class.instance(),
+            create_param,
+        )?;
+        Ok(Self::from_raw(hwnd))
+    }
+
+    /// Creates an overlapped window of the given class, like [`create_app_window`] does.
+    ///
+    /// For other styles, or to size the window by its client area, use
+    /// [`WindowBuilder`](crate::window_builder::WindowBuilder).
+    ///
+    /// ## Safety
+    ///
+    /// The class's window procedure must cope with whatever `create_param` is.
+    ///
+    /// [`create_app_window`]: crate::create_app_window
+    pub unsafe fn create_app(
+        class: &'class WindowClass,
+        window_name: &str,
+        position: Option<[i32; 2]>,
+        size: [i32; 2],
+        create_param: LPVOID,
+    ) -> Result<Self, Win32Error> {
+        Self::create(
+            class,
+            window_name,
+            WS_OVERLAPPEDWINDOW | WS_CLIPCHILDREN | WS_CLIPSIBLINGS,
+            0,
+            position,
+            size,
+            create_param,
+        )
+    }
+
+    /// Takes ownership of a window.
+    ///
+    /// ## Safety
+    ///
+    /// - `hwnd` must be a valid handle to a window of a class that outlives `'class`.
+    /// - Nothing else may destroy the window.
+    pub unsafe fn from_raw(hwnd: HWND) -> Self {
+        Self {
+            hwnd,
+            icon: None,
+            _class: PhantomData,
+        }
+    }
+
+    pub fn hwnd(&self) -> HWND {
+        self.hwnd
+    }
+
+    /// Shows (or hides, or minimizes, ...) the window. `cmd` is one of the `SW_*` constants.
+    ///
+    /// Returns whether the window was visible beforehand.
+    pub fn show(&self, cmd: CInt) -> bool {
+        // Safety: the handle is valid as long as `self` is
+        unsafe { ShowWindow(self.hwnd, cmd) != 0 }
+    }
+
+    /// Sets whether files can be dropped on the window.
+    ///
+    /// **See**: [`drop_files`](crate::drop_files)
+    pub fn accept_files(&self, accept: bool) {
+        // Safety: the handle is valid as long as `self` is
+        unsafe { crate::drop_files::accept_files(self.hwnd, accept) }
+    }
+
+    /// Sets the window's icons from straight RGBA pixels, row by row, scaled to the big and small
+    /// icon sizes at the window's DPI.
+    ///
+    /// Fails with an application error if `rgba` isn't `width * height * 4` bytes long, or the
+    /// image is empty.
+    pub fn set_icon(&mut self, rgba: &[u8], width: u32, height: u32) -> Result<(), Win32Error> {
+        if width == 0
+            || height == 0
+            || rgba.len() as u64 != u64::from(width) * u64::from(height) * 4
+        {
+            return Err(Win32Error(Win32Error::APPLICATION_ERROR_BIT));
+        }
+        self.set_icon_images(&[RgbaImage::new(width, height, rgba.to_vec())])
+    }
+
+    /// Sets the window's icons from images of different sizes, like the ones in an `.ico` file.
+    /// Each icon is made from the image that suits its size best.
+    ///
+    /// The icons are made for the window's DPI when this is called, so call it again when the DPI
+    /// changes.
+    ///
+    /// **See**: [`icon`](crate::icon)
+    pub fn set_icon_images(&mut self, images: &[RgbaImage]) -> Result<(), Win32Error> {
+        let icon = WindowIcon::new(images, self.dpi())?;
+        // Safety: the handle is valid as long as `self` is, and the icons are kept until the
+        // window is destroyed or gets others
+        unsafe { icon.apply(self.hwnd) };
+        self.icon = Some(icon);
+        Ok(())
+    }
+}
+
+impl Drop for Window<'_> {
+    fn drop(&mut self) {
+        // Safety: nothing else destroys the window
+        if let Err(e) = unsafe { destroy_window(self.hwnd) } {
+            eprintln!("Unable to destroy window: {e}");
+        }
+    }
+}
+
+/// A handle to a window's device context, which is released when dropped.
+///
+/// **See**: [`get_dc`], [`release_dc`]
+#[derive(Debug)]
+pub struct DeviceContext<'window> {
+    hwnd: HWND,
+    hdc: HDC,
+    _window: PhantomData<&'window Window<'window>>,
+}
+
+impl<'window> DeviceContext<'window> {
+    /// Gets the device context for a window's client area.
+    pub fn get(window: &'window Window<'_>) -> Result<Self, Win32Error> {
+        // Safety: the window is alive for as long as it's borrowed
+        let hdc = unsafe { get_dc(window.hwnd()) }
+            .ok_or(Win32Error(Win32Error::APPLICATION_ERROR_BIT))?;
+        Ok(Self {
+            hwnd: window.hwnd(),
+            hdc,
+            _window: PhantomData,
+        })
+    }
+
+    pub fn hdc(&self) -> HDC {
+        self.hdc
+    }
+
+    /// Swaps the front and back buffers, if the DC's pixel format is double buffered.
+    ///
+    /// **See**: [`SwapBuffers`]
+    pub fn swap_buffers(&self) -> Result<(), Win32Error> {
+        // Safety: the DC is valid as long as `self` is
+        if unsafe { SwapBuffers(self.hdc) } != 0 {
+            Ok(())
+        } else {
+            Err(get_last_error())
+        }
+    }
+}
+
+impl Drop for DeviceContext<'_> {
+    fn drop(&mut self) {
+        // Safety: the window outlives `self`, and the DC came from it
+        if !unsafe { release_dc(self.hwnd, self.hdc) } {
+            eprintln!("Unable to release device context.");
+        }
+    }
+}
+
+/// An OpenGL rendering context, which is deleted when dropped.
+///
+/// **See**: [`wgl_create_context`], [`wgl_delete_context`]
+#[derive(Debug)]
+pub struct GlRenderContext<'dc> {
+    hdc: HDC,
+    hglrc: HGLRC,
+    _dc: PhantomData<&'dc DeviceContext<'dc>>,
+}
+
+impl<'dc> GlRenderContext<'dc> {
+    /// Creates an OpenGL 1.1 context for a device context, whose pixel format must already be set.
+    pub fn create(dc: &'dc DeviceContext<'_>) -> Result<Self, Win32Error> {
+        // Safety: the DC is alive for as long as it's borrowed
+        let hglrc = unsafe { wgl_create_context(dc.hdc()) }?;
+        Ok(Self {
+            hdc: dc.hdc(),
+            hglrc,
+            _dc: PhantomData,
+        })
+    }
+
+    /// Takes ownership of a context that was created some other way, like with
+    /// [`do_wgl_create_context_attribs_arb`](crate::do_wgl_create_context_attribs_arb).
+    ///
+    /// ## Safety
+    ///
+    /// - `hglrc` must be a valid handle to an OpenGL context that was created for `dc`.
+    /// - Nothing else may delete the context.
+    pub unsafe fn from_raw(dc: &'dc DeviceContext<'_>, hglrc: HGLRC) -> Self {
+        Self {
+            hdc: dc.hdc(),
+            hglrc,
+            _dc: PhantomData,
+        }
+    }
+
+    pub fn hglrc(&self) -> HGLRC {
+        self.hglrc
+    }
+
+    /// Makes the context current on this thread, targeting the device context it was created for.
+    ///
+    /// The context stays current until the returned guard is dropped.
+    pub fn make_current(&self) -> Result<CurrentContextGuard<'_>, Win32Error> {
+        // Safety: both handles are valid as long as `self` is
+        unsafe { wgl_make_current(self.hdc, self.hglrc) }?;
+        Ok(CurrentContextGuard {
+            _context: PhantomData,
+            _not_send: PhantomData,
+        })
+    }
+
+    /// Swaps the front and back buffers of the device context the context was created for, which
+    /// (unlike [`DeviceContext::swap_buffers`]) can be done from whichever thread is rendering.
+    ///
+    /// **See**: [`SwapBuffers`]
+    pub fn swap_buffers(&self) -> Result<(), Win32Error> {
+        // Safety: the DC is valid as long as `self` is
+        if unsafe { SwapBuffers(self.hdc) } != 0 {
+            Ok(())
+        } else {
+            Err(get_last_error())
+        }
+    }
+}
+
+// Safety: a context can be made current on any thread (`wglMakeCurrent` fails if it's already
+// current on another), and deleted from any thread once it isn't current. Its DC stays valid until
+// the `DeviceContext` it borrows is dropped, whichever thread renders to it.
+unsafe impl Send for GlRenderContext<'_> {}
+unsafe impl Sync for GlRenderContext<'_> {}
+
+impl Drop for GlRenderContext<'_> {
+    fn drop(&mut self) {
+        // Safety: any guard has already made the context not current, and nothing else deletes it
+        if let Err(e) = unsafe { wgl_delete_context(self.hglrc) } {
+            eprintln!("GL context deletion error: {e}");
+        }
+    }
+}
+
+/// Keeps a [`GlRenderContext`] current on this thread, and makes no context current when dropped.
+///
+/// This can't be sent to other threads, since being current is a per-thread thing.
+#[derive(Debug)]
+pub struct CurrentContextGuard<'context> {
+    _context: PhantomData<&'context GlRenderContext<'context>>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for CurrentContextGuard<'_> {
+    fn drop(&mut self) {
+        // Safety: passing nulls is always fine
+        if let Err(e) = unsafe { wgl_make_current(ptr::null_mut(), ptr::null_mut()) } {
+            eprintln!("Unable to make the GL context not current: {e}");
+        }
+    }
+}
+
+/// A loaded DLL, which is unloaded when dropped.
+///
+/// **See**: [`load_library`], [`FreeLibrary`]
+#[derive(Debug)]
+pub struct Library {
+    hmodule: HMODULE,
+}
+
+impl Library {
+    /// Loads a DLL. See [`load_library`] for how `name` is looked up.
+    pub fn load(name: &str) -> Result<Self, Win32Error> {
+        Ok(Self {
+            hmodule: load_library(name)?,
+        })
+    }
+
+    pub fn hmodule(&self) -> HMODULE {
+        self.hmodule
+    }
+
+    /// Gets the address of an exported symbol.
+    ///
+    /// **See**: [`GetProcAddress`]
+    pub fn get_proc_address(&self, name: CStrRef<'_>) -> Result<FARPROC, Win32Error> {
+        // Safety: the module is loaded as long as `self` is, and the name is null-terminated
+        let proc = unsafe { GetProcAddress(self.hmodule, name.as_ptr()) };
+        if proc.is_null() {
+            Err(get_last_error())
+        } else {
+            Ok(proc)
+        }
+    }
+
+    /// Gets an exported function as a function pointer of type `F`.
+    ///
+    /// ## Safety
+    ///
+    /// - `F` must be a function pointer type (e.g. `unsafe extern "system" fn(...)`) matching the
+    ///   signature of the exported function.
+    /// - The function pointer mustn't be called after `self` is dropped.
+    ///
+    /// ## Panics
+    ///
+    /// If `F` isn't the size of a pointer.
+    pub unsafe fn get_proc<F: Copy>(&self, name: CStrRef<'_>) -> Result<F, Win32Error> {
+        assert_eq!(
+            mem::size_of::<F>(),
+            mem::size_of::<FARPROC>(),
+            "get_proc can only produce function pointers"
+        );
+        let proc = self.get_proc_address(name)?;
+        Ok(mem::transmute_copy(&proc))
+    }
+}
+
+impl Drop for Library {
+    fn drop(&mut self) {
+        // Safety: the module was loaded by `load`, and is only freed here
+        if unsafe { FreeLibrary(self.hmodule) } == 0 {
+            eprintln!("Unable to free library: {}", get_last_error());
+        }
+    }
+}
+
+/// Registers an invisible window class for the tests in this module and [`get_wgl_basics`].
+///
+/// [`get_wgl_basics`]: crate::get_wgl_basics
+pub(crate) fn register_hidden_class(name: &str) -> Result<WindowClass, Win32Error> {
+    let name_wn = wide_null(name);
+    let wc = WNDCLASSW {
+        style: CS_OWNDC,
+        lpfnWndProc: Some(DefWindowProcW),
+        hInstance: get_process_handle(),
+        lpszClassName: name_wn.as_ptr(),
+        ..Default::default()
+    };
+    // Safety: the class name is copied by `RegisterClassW`, so it only has to outlive the call
+    unsafe { WindowClass::register(&wc) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c_types::c_str;
+
+    #[test]
+    fn handles_drop_in_order() {
+        let class = register_hidden_class("triangle-from-scratch handles test").unwrap();
+        let window =
+            unsafe { Window::create_app(&class, "test", None, [1, 1], ptr::null_mut()) }.unwrap();
+        let dc = DeviceContext::get(&window).unwrap();
+        assert!(!dc.hdc().is_null());
+        // dropping everything at the end of the scope mustn't print any errors (or crash)
+    }
+
+    #[test]
+    fn library_procs() {
+        let kernel32 = Library::load("kernel32.dll").unwrap();
+        let get_tick_count: unsafe extern "system" fn() -> DWORD =
+            unsafe { kernel32.get_proc(c_str!("GetTickCount")) }.unwrap();
+        let _ = unsafe { get_tick_count() };
+        assert!(kernel32
+            .get_proc_address(c_str!("NotARealFunctionName"))
+            .is_err());
+    }
+}
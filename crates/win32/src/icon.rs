@@ -0,0 +1,168 @@
+//! Window icons made from images, at the sizes the window's DPI calls for.
+//!
+//! A window has two icons: a big one for the task switcher, and a small one for the title bar and
+//! taskbar. Their sizes depend on the DPI, so [`WindowIcon`] scales the images it's given to both
+//! sizes for a particular DPI. [`Window::set_icon`](crate::handles::Window::set_icon) does all of
+//! that for the window's current DPI, and keeps the icons alive.
+//!
+//! The images can come from an `.ico` file, with [`image::ico`].
+
+use c_types::CInt;
+use image::RgbaImage;
+
+use crate::{dpi::system_metric_for_dpi, get_last_error, prelude::*};
+
+/// An icon, which is destroyed when dropped.
+#[derive(Debug)]
+pub struct Icon {
+    hicon: HICON,
+}
+
+impl Icon {
+    /// Makes an icon from an image, at the image's size.
+    pub fn from_image(image: &RgbaImage) -> Result<Self, Win32Error> {
+        let hicon = create_icon_indirect(&image.pixels, image.width, image.height, None)?;
+        Ok(Self { hicon })
+    }
+
+    pub fn hicon(&self) -> HICON {
+        self.hicon
+    }
+}
+
+impl Drop for Icon {
+    fn drop(&mut self) {
+        // Safety: the icon was made by `CreateIconIndirect`, and nothing else destroys it
+        unsafe { DestroyIcon(self.hicon) };
+    }
+}
+
+/// The sizes of big and small icons at `dpi`, in that order.
+pub fn icon_sizes(dpi: u32) -> [u32; 2] {
+    [SM_CXICON, SM_CXSMICON].map(|metric| system_metric_for_dpi(metric, dpi).max(1) as u32)
+}
+
+/// A window's big and small icons, made for one DPI.
+///
+/// Windows doesn't take ownership of the icons, so this has to be kept until the window has
+/// other icons or is destroyed.
+#[derive(Debug)]
+pub struct WindowIcon {
+    big: Icon,
+    small: Icon,
+}
+
+impl WindowIcon {
+    /// Makes the icons out of whichever of `images` suits each size best, scaled to fit.
+    ///
+    /// Fails with an application error if there aren't any images.
+    pub fn new(images: &[RgbaImage], dpi: u32) -> Result<Self, Win32Error> {
+        let [big, small] = icon_sizes(dpi).map(|size| {
+            let image = image::best_for_size(images, size)
+                .ok_or(Win32Error(Win32Error::APPLICATION_ERROR_BIT))?;
+            Icon::from_image(&image.fitted(size))
+        });
+        Ok(Self {
+            big: big?,
+            small: small?,
+        })
+    }
+
+    pub fn big(&self) -> &Icon {
+        &self.big
+    }
+
+    pub fn small(&self) -> &Icon {
+        &self.small
+    }
+
+    /// Gives the icons to a window, e.g. again from
+    /// [`WindowHandler::dpi_changed`](crate::window_handler::WindowHandler::dpi_changed) once
+    /// they've been remade for the new DPI.
+    ///
+    /// ## Safety
+    ///
+    /// `hwnd` must be a valid window handle, and `self` must outlive the window's use of the icons.
+    pub unsafe fn apply(&self, hwnd: HWND) {
+        SendMessageW(hwnd, WM_SETICON, ICON_BIG, self.big.hicon as LPARAM);
+        SendMessageW(hwnd, WM_SETICON, ICON_SMALL, self.small.hicon as LPARAM);
+    }
+}
+
+/// Makes an icon, or a cursor if there's a hotspot, from straight RGBA pixels, row by row.
+///
+/// Fails with an application error if `rgba` isn't `width * height * 4` bytes long, or the image
+/// is empty.
+pub(crate) fn create_icon_indirect(
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    hotspot: Option<[u32; 2]>,
+) -> Result<HICON, Win32Error> {
+    if width == 0
+        || height == 0
+        || width > CInt::MAX as u32
+        || height > CInt::MAX as u32
+        || rgba.len() as u64 != u64::from(width) * u64::from(height) * 4
+    {
+        return Err(Win32Error(Win32Error::APPLICATION_ERROR_BIT));
+    }
+    let bgra: Vec<u8> = rgba
+        .chunks_exact(4)
+        .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]])
+        .collect();
+    // the mask is ignored where there's alpha, but has to be there. Its rows are padded to 16
+    // bits.
+    let mask = vec![0_u8; (width as usize).div_ceil(16) * 2 * height as usize];
+
+    // Safety: the bitmaps' bits are big enough for their sizes, and the system copies them into
+    // the icon, so they're deleted whether or not it was made
+    unsafe {
+        let color = CreateBitmap(width as CInt, height as CInt, 1, 32, bgra.as_ptr().cast());
+        if color.is_null() {
+            return Err(get_last_error());
+        }
+        let mask = CreateBitmap(width as CInt, height as CInt, 1, 1, mask.as_ptr().cast());
+        if mask.is_null() {
+            let err = get_last_error();
+            DeleteObject(color);
+            return Err(err);
+        }
+        let [x, y] = hotspot.unwrap_or_default();
+        let mut info = ICONINFO {
+            fIcon: hotspot.is_none().into(),
+            xHotspot: x.min(width - 1),
+            yHotspot: y.min(height - 1),
+            hbmMask: mask,
+            hbmColor: color,
+        };
+        let hicon = CreateIconIndirect(&mut info);
+        let err = get_last_error();
+        DeleteObject(color);
+        DeleteObject(mask);
+        if hicon.is_null() {
+            return Err(err);
+        }
+        Ok(hicon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_icons() {
+        let [big, small] = icon_sizes(96);
+        assert!(big > small && small > 0);
+        assert!(icon_sizes(192)[1] > small);
+
+        let image = RgbaImage::new(64, 32, [0xFF, 0, 0, 0xFF].repeat(64 * 32));
+        let icon = WindowIcon::new(&[image], 144).unwrap();
+        assert!(!icon.big().hicon().is_null());
+        assert!(!icon.small().hicon().is_null());
+
+        assert!(WindowIcon::new(&[], 96).is_err());
+        assert!(create_icon_indirect(&[0; 12], 2, 2, None).is_err());
+    }
+}
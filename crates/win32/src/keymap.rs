@@ -0,0 +1,128 @@
+//! Turning key messages into the [`keyboard`] model: physical keys from their scan codes, and
+//! logical keys from the current keyboard layout.
+//!
+//! Windows created with a [`WindowHandler`](crate::window_handler::WindowHandler) have this done
+//! for them, and get [`key`](crate::window_handler::WindowHandler::key) calls with the result.
+//! Text comes separately, from `WM_CHAR`, as
+//! [`text`](crate::window_handler::WindowHandler::text) calls.
+
+use c_types::CInt;
+use keyboard::{scan_codes, Key, KeyCode, NamedKey};
+
+use crate::prelude::*;
+
+/// For [`ToUnicodeEx`]: don't change the keyboard state, so dead keys still work in the text
+/// that `TranslateMessage` makes. This works in Windows 10 version 1607 and later.
+const DONT_CHANGE_KEYBOARD_STATE: UINT = 1 << 2;
+
+/// The scan code in a key message's `lParam`, with `0xE000` added for extended keys.
+pub fn scan_code(lparam: LPARAM) -> u16 {
+    let scan_code = ((lparam >> 16) & 0xFF) as u16;
+    if lparam & (1 << 24) != 0 {
+        0xE000 | scan_code
+    } else {
+        scan_code
+    }
+}
+
+/// The physical key of a key message.
+///
+/// Some messages (e.g. ones made by `SendInput`) don't have a scan code, so it's looked up from the
+/// virtual key code instead.
+pub fn physical_key(virtual_key: u32, lparam: LPARAM) -> KeyCode {
+    let scan_code = match scan_code(lparam) {
+        // Safety: just a lookup
+        0 => (unsafe { MapVirtualKeyW(virtual_key, MAPVK_VK_TO_VSC_EX) }) as u16,
+        scan_code => scan_code,
+    };
+    scan_codes::from_win32_scan_code(scan_code)
+}
+
+/// What a key means in the current keyboard layout, with the modifiers that are held right now.
+///
+/// Control is ignored, since it makes letters type control characters, unless right alt is held
+/// too: that's AltGr, which Windows reports as control and right alt together.
+pub fn logical_key(virtual_key: u32, lparam: LPARAM, physical_key: KeyCode) -> Key {
+    if let Some(named) = physical_key.named_key() {
+        return Key::Named(named);
+    }
+
+    // Safety: the buffers are as long as the functions want
+    unsafe {
+        let mut state = [0u8; 256];
+        if GetKeyboardState(state.as_mut_ptr()) == 0 {
+            return Key::Unidentified;
+        }
+        let alt_graph = state[VK_RMENU as usize] & 0x80 != 0;
+        if physical_key == KeyCode::AltRight {
+            // AltGr layouts send a left control press just before right alt
+            return Key::Named(if state[VK_LCONTROL as usize] & 0x80 != 0 {
+                NamedKey::AltGraph
+            } else {
+                NamedKey::Alt
+            });
+        }
+        if !alt_graph {
+            for vk in [VK_CONTROL, VK_LCONTROL, VK_RCONTROL] {
+                state[vk as usize] &= !0x80;
+            }
+        }
+
+        let mut text = [0u16; 8];
+        let len = ToUnicodeEx(
+            virtual_key,
+            u32::from(scan_code(lparam)),
+            state.as_ptr(),
+            text.as_mut_ptr(),
+            text.len() as CInt,
+            DONT_CHANGE_KEYBOARD_STATE,
+            GetKeyboardLayout(0),
+        );
+        let first = || char::decode_utf16(text).next()?.ok();
+        match len {
+            // dead keys give the accent on its own
+            ..=-1 => first().map_or(Key::Unidentified, Key::Dead),
+            0 => Key::Unidentified,
+            _ => first()
+                .filter(|c| !c.is_control())
+                .map_or(Key::Unidentified, Key::Character),
+        }
+    }
+}
+
+/// Whether caps lock and num lock are on right now.
+pub fn lock_state() -> (bool, bool) {
+    // Safety: just reads the state; the low bit is whether the key is toggled on
+    unsafe {
+        (
+            GetKeyState(VK_CAPITAL as CInt) & 1 != 0,
+            GetKeyState(VK_NUMLOCK as CInt) & 1 != 0,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan_codes() {
+        // the A key, pressed once
+        assert_eq!(scan_code(0x001E_0001), 0x1E);
+        assert_eq!(physical_key(0x41, 0x001E_0001), KeyCode::KeyA);
+        // the left arrow is extended, unlike numpad 4
+        assert_eq!(scan_code(0x014B_0001), 0xE04B);
+        assert_eq!(physical_key(0x25, 0x014B_0001), KeyCode::ArrowLeft);
+        assert_eq!(physical_key(0x25, 0x004B_0001), KeyCode::Numpad4);
+        // no scan code, so it comes from the virtual key
+        assert_eq!(physical_key(VK_F11, 1), KeyCode::F11);
+    }
+
+    #[test]
+    fn named_keys_skip_the_layout() {
+        assert_eq!(
+            logical_key(VK_F11, 0x0057_0001, KeyCode::F11),
+            Key::Named(NamedKey::F(11))
+        );
+    }
+}
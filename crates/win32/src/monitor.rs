@@ -0,0 +1,187 @@
+//! Listing the connected monitors, and the display modes each of them supports.
+//!
+//! ```no_run
+//! # use triangle_from_scratch_win32::monitor::monitors;
+//! for monitor in monitors()? {
+//!     println!("{}: {:?} at {:?}", monitor.name, monitor.size, monitor.position);
+//! }
+//! # Ok::<(), triangle_from_scratch_win32::Win32Error>(())
+//! ```
+
+use core::ptr;
+
+use crate::{
+    dpi::{monitor_dpi, rect_from_win32},
+    geometry::scale_factor,
+    get_last_error,
+    prelude::*,
+    str_util::WideStr,
+};
+
+/// A display mode that a monitor can be switched to, e.g. with
+/// [`FullscreenMode::Exclusive`](crate::fullscreen::FullscreenMode::Exclusive).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VideoMode {
+    /// The resolution, in physical pixels.
+    pub size: [u32; 2],
+    pub bit_depth: u32,
+    /// The refresh rate, or `None` if the hardware's default is used.
+    pub refresh_rate_millihertz: Option<u32>,
+}
+
+impl VideoMode {
+    fn from_dev_mode(mode: &DEVMODEW) -> Self {
+        Self {
+            size: [mode.dmPelsWidth, mode.dmPelsHeight],
+            bit_depth: mode.dmBitsPerPel,
+            // 0 and 1 both mean "the hardware's default"
+            refresh_rate_millihertz: match mode.dmDisplayFrequency {
+                0 | 1 => None,
+                hz => Some(hz * 1000),
+            },
+        }
+    }
+}
+
+/// A connected monitor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Monitor {
+    /// The device name, e.g. `\\.\DISPLAY1`.
+    pub name: String,
+    /// Where the monitor's top left corner is on the virtual screen, in physical pixels. The
+    /// primary monitor is at `[0, 0]`.
+    pub position: [i32; 2],
+    /// The size in physical pixels.
+    pub size: [u32; 2],
+    /// The size in millimeters, or zero if the monitor doesn't say.
+    pub physical_size_mm: [u32; 2],
+    /// The current display mode's refresh rate.
+    pub refresh_rate_millihertz: Option<u32>,
+    /// How many physical pixels there are for each logical pixel, e.g. `1.5` with 150% scaling.
+    pub scale_factor: f64,
+    pub is_primary: bool,
+    /// Every display mode the monitor supports, largest and fastest first.
+    pub modes: Vec<VideoMode>,
+}
+
+impl Monitor {
+    /// Describes the monitor that a window is (mostly) on.
+    ///
+    /// ## Safety
+    ///
+    /// `hwnd` must be a valid window.
+    pub unsafe fn from_window(hwnd: HWND) -> Result<Self, Win32Error> {
+        Self::from_handle(MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST))
+    }
+
+    /// ## Safety
+    ///
+    /// `monitor` must be a valid monitor handle.
+    unsafe fn from_handle(monitor: HMONITOR) -> Result<Self, Win32Error> {
+        let mut info = MONITORINFOEXW::default();
+        if GetMonitorInfoW(monitor, &mut info) == 0 {
+            return Err(get_last_error());
+        }
+        let device = WideStr::from_units_until_null(&info.szDevice)
+            .expect("device names are null-terminated");
+        let rect = rect_from_win32(info.rcMonitor);
+
+        let mut current = DEVMODEW::default();
+        let refresh_rate_millihertz =
+            if EnumDisplaySettingsW(device.as_ptr(), ENUM_CURRENT_SETTINGS, &mut current) != 0 {
+                VideoMode::from_dev_mode(&current).refresh_rate_millihertz
+            } else {
+                None
+            };
+
+        Ok(Self {
+            name: device.to_string_lossy(),
+            position: rect.position(),
+            size: [rect.width() as u32, rect.height() as u32],
+            physical_size_mm: physical_size_mm(device),
+            refresh_rate_millihertz,
+            scale_factor: scale_factor(monitor_dpi(monitor)),
+            is_primary: info.dwFlags & MONITORINFOF_PRIMARY != 0,
+            modes: video_modes(device),
+        })
+    }
+}
+
+/// Lists the connected monitors, in no particular order.
+pub fn monitors() -> Result<Vec<Monitor>, Win32Error> {
+    unsafe extern "system" fn push(
+        monitor: HMONITOR,
+        _hdc: HDC,
+        _rect: *mut RECT,
+        data: LPARAM,
+    ) -> BOOL {
+        // Safety: `data` is the `Vec` below, which outlives the enumeration
+        let handles = &mut *(data as *mut Vec<HMONITOR>);
+        handles.push(monitor);
+        1
+    }
+
+    let mut handles: Vec<HMONITOR> = Vec::new();
+    // Safety: a null DC and clip rect enumerate every monitor on the virtual screen
+    let enumerated = unsafe {
+        EnumDisplayMonitors(
+            ptr::null_mut(),
+            ptr::null(),
+            Some(push),
+            &mut handles as *mut Vec<HMONITOR> as LPARAM,
+        )
+    };
+    if enumerated == 0 {
+        return Err(get_last_error());
+    }
+    handles
+        .into_iter()
+        // Safety: the handles came from Windows just now
+        .map(|monitor| unsafe { Monitor::from_handle(monitor) })
+        .collect()
+}
+
+/// Lists the display modes of a display device, without duplicates.
+fn video_modes(device: &WideStr) -> Vec<VideoMode> {
+    let mut modes = Vec::new();
+    let mut dev_mode = DEVMODEW::default();
+    // Safety: the device name is null-terminated, and the struct's size is set
+    while unsafe { EnumDisplaySettingsW(device.as_ptr(), modes.len() as DWORD, &mut dev_mode) } != 0
+    {
+        modes.push(VideoMode::from_dev_mode(&dev_mode));
+    }
+    // Windows lists each mode again for every way it can be stretched or centered
+    modes.sort_unstable_by(|a, b| b.cmp(a));
+    modes.dedup();
+    modes
+}
+
+/// The physical size of a display device, which Windows gets from the monitor's EDID.
+fn physical_size_mm(device: &WideStr) -> [u32; 2] {
+    // Safety: the device name is null-terminated, and the DC is deleted straight away
+    unsafe {
+        let hdc = CreateDCW(device.as_ptr(), device.as_ptr(), ptr::null(), ptr::null());
+        if hdc.is_null() {
+            return [0, 0];
+        }
+        let size = [GetDeviceCaps(hdc, HORZSIZE), GetDeviceCaps(hdc, VERTSIZE)];
+        DeleteDC(hdc);
+        size.map(|mm| mm.max(0) as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn there_is_one_primary_monitor() {
+        let monitors = monitors().unwrap();
+        assert_eq!(monitors.iter().filter(|m| m.is_primary).count(), 1);
+
+        let primary = monitors.iter().find(|m| m.is_primary).unwrap();
+        assert_eq!(primary.position, [0, 0]);
+        assert!(primary.scale_factor >= 1.0);
+        assert!(primary.modes.iter().any(|mode| mode.size == primary.size));
+    }
+}
@@ -0,0 +1,184 @@
+//! Relative mouse motion from [Raw Input](https://docs.microsoft.com/en-us/windows/win32/inputdev/raw-input),
+//! which comes straight from the mouse: it isn't accelerated, and doesn't stop at the edges of the
+//! screen, so it's what a first-person camera wants.
+//!
+//! Once a window has called [`register_raw_mouse`], it gets a [`WM_INPUT`] for every movement.
+//! Windows created with a [`WindowHandler`](crate::window_handler::WindowHandler) have these
+//! decoded into [`raw_mouse_motion`](crate::window_handler::WindowHandler::raw_mouse_motion) calls.
+
+use core::{mem, ptr};
+
+use crate::{get_last_error, prelude::*};
+
+/// Has the mouse's raw input sent to `hwnd` while it's in the foreground. Nothing comes while
+/// another window is active, so a camera driven by it stops when the user switches away.
+///
+/// ## Safety
+///
+/// `hwnd` must be a valid window.
+pub unsafe fn register_raw_mouse(hwnd: HWND) -> Result<(), Win32Error> {
+    register(RAWINPUTDEVICE {
+        usUsagePage: HID_USAGE_PAGE_GENERIC,
+        usUsage: HID_USAGE_GENERIC_MOUSE,
+        dwFlags: 0,
+        hwndTarget: hwnd,
+    })
+}
+
+/// Stops sending the mouse's raw input to the window it was registered for.
+pub fn unregister_raw_mouse() -> Result<(), Win32Error> {
+    // Safety: the target has to be null when removing
+    unsafe {
+        register(RAWINPUTDEVICE {
+            usUsagePage: HID_USAGE_PAGE_GENERIC,
+            usUsage: HID_USAGE_GENERIC_MOUSE,
+            dwFlags: RIDEV_REMOVE,
+            hwndTarget: ptr::null_mut(),
+        })
+    }
+}
+
+unsafe fn register(device: RAWINPUTDEVICE) -> Result<(), Win32Error> {
+    if RegisterRawInputDevices(&device, 1, mem::size_of::<RAWINPUTDEVICE>() as UINT) == 0 {
+        return Err(get_last_error());
+    }
+    Ok(())
+}
+
+/// Reads the raw input that a [`WM_INPUT`] message is about.
+///
+/// **Returns:** The mouse's input, or `None` if the input is from another kind of device.
+///
+/// ## Safety
+///
+/// `lparam` must be the `lParam` of a [`WM_INPUT`] message that's being handled right now.
+pub unsafe fn read_raw_mouse(lparam: LPARAM) -> Result<Option<RAWMOUSE>, Win32Error> {
+    let mut input = RAWINPUT::default();
+    let mut size = mem::size_of::<RAWINPUT>() as UINT;
+    let read = GetRawInputData(
+        lparam as HRAWINPUT,
+        RID_INPUT,
+        ptr::addr_of_mut!(input).cast(),
+        &mut size,
+        mem::size_of::<RAWINPUTHEADER>() as UINT,
+    );
+    if read == UINT::MAX {
+        return Err(get_last_error());
+    }
+    Ok((input.header.dwType == RIM_TYPEMOUSE).then_some(input.mouse))
+}
+
+/// Turns raw mouse input into relative motion.
+///
+/// Most mice report relative motion already. Tablets, touchscreens and remote desktop sessions
+/// report absolute positions instead, which are turned into motion relative to the last one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RawMouseDecoder {
+    /// The last absolute position, in pixels.
+    last_absolute: Option<[i32; 2]>,
+}
+
+impl RawMouseDecoder {
+    pub const fn new() -> Self {
+        Self {
+            last_absolute: None,
+        }
+    }
+
+    /// How far the mouse moved, in (unaccelerated) mouse units for relative mice, and in pixels
+    /// for absolute ones. `screen_size` is the size of the primary monitor and of the virtual
+    /// screen, in pixels, which absolute positions are scaled to.
+    pub fn delta(&mut self, mouse: &RAWMOUSE, screen_size: ScreenSize) -> [i32; 2] {
+        if mouse.usFlags & MOUSE_MOVE_ABSOLUTE == 0 {
+            self.last_absolute = None;
+            return [mouse.lLastX, mouse.lLastY];
+        }
+
+        let [width, height] = if mouse.usFlags & MOUSE_VIRTUAL_DESKTOP != 0 {
+            screen_size.virtual_screen
+        } else {
+            screen_size.primary
+        };
+        // absolute positions go from 0 to 65535 across the screen
+        let scale = |value: LONG, size: i32| (i64::from(value) * i64::from(size) / 65535) as i32;
+        let position = [scale(mouse.lLastX, width), scale(mouse.lLastY, height)];
+        let delta = match self.last_absolute {
+            Some([x, y]) => [position[0] - x, position[1] - y],
+            None => [0, 0],
+        };
+        self.last_absolute = Some(position);
+        delta
+    }
+}
+
+/// The sizes that [`RawMouseDecoder::delta`] scales absolute positions to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScreenSize {
+    pub primary: [i32; 2],
+    pub virtual_screen: [i32; 2],
+}
+
+impl ScreenSize {
+    /// The sizes of the monitors right now.
+    pub fn current() -> Self {
+        // Safety: these just read numbers
+        unsafe {
+            Self {
+                primary: [GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN)],
+                virtual_screen: [
+                    GetSystemMetrics(SM_CXVIRTUALSCREEN),
+                    GetSystemMetrics(SM_CYVIRTUALSCREEN),
+                ],
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mouse(flags: USHORT, x: LONG, y: LONG) -> RAWMOUSE {
+        RAWMOUSE {
+            usFlags: flags,
+            lLastX: x,
+            lLastY: y,
+            ..Default::default()
+        }
+    }
+
+    const SCREENS: ScreenSize = ScreenSize {
+        primary: [1920, 1080],
+        virtual_screen: [3840, 1080],
+    };
+
+    #[test]
+    fn relative_motion() {
+        let mut decoder = RawMouseDecoder::new();
+        assert_eq!(decoder.delta(&mouse(0, 5, -3), SCREENS), [5, -3]);
+        // not clamped to anything
+        assert_eq!(decoder.delta(&mouse(0, 100_000, 0), SCREENS), [100_000, 0]);
+    }
+
+    #[test]
+    fn absolute_motion() {
+        let mut decoder = RawMouseDecoder::new();
+        let absolute = MOUSE_MOVE_ABSOLUTE;
+        assert_eq!(decoder.delta(&mouse(absolute, 0, 0), SCREENS), [0, 0]);
+        assert_eq!(
+            decoder.delta(&mouse(absolute, 65535, 32768), SCREENS),
+            [1920, 540]
+        );
+        assert_eq!(
+            decoder.delta(
+                &mouse(absolute | MOUSE_VIRTUAL_DESKTOP, 65535, 32768),
+                SCREENS
+            ),
+            [1920, 0]
+        );
+
+        // relative input in between starts absolute motion over
+        decoder.delta(&mouse(0, 1, 1), SCREENS);
+        assert_eq!(decoder.delta(&mouse(absolute, 100, 100), SCREENS), [0, 0]);
+    }
+}
@@ -1,4 +1,14 @@
+//! Conversions between Rust's UTF-8 strings and the null-terminated UTF-16 strings Win32 uses.
+
+use core::{borrow::Borrow, fmt, ops::Deref};
+use std::ffi::{OsStr, OsString};
+#[cfg(windows)]
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+
 /// Turns a Rust string slice into a null-terminated utf-16 vector.
+///
+/// Any nulls already in `s` are kept, so Win32 will see the string as ending at the first one. Use
+/// [`WideString::new`] to reject them instead.
 pub fn wide_null(s: &str) -> Vec<u16> {
     s.encode_utf16().chain(Some(0)).collect()
 }
@@ -20,7 +30,7 @@ pub fn min_alloc_lossy_into_string(bytes: Vec<u8>) -> String {
 /// **See:** [UTF-8 encoding reference on Wikipedia](https://en.wikipedia.org/wiki/UTF-8#Encoding)
 ///
 /// ```
-/// # use triangle_from_scratch::str_util::break_off_code_point;
+/// # use triangle_from_scratch_win32::str_util::break_off_code_point;
 /// // code point gets lopped off of a string
 /// let test_str = "𐍈rigin".to_string();
 /// assert_eq!(
@@ -143,15 +153,496 @@ macro_rules! utf16_null {
 
 /// Convert a UTF-8 rust string literal into a null-terminated `&[u8]`.
 ///
+/// Literals containing a null are rejected at compile time, like [`c_types::c_str!`].
+///
 /// **Note**: This macro can only be passed string _literals_ (not variables or constants!) due to
 /// its internal use of [`concat`] to add a null byte to the end of the string.
 #[macro_export]
 macro_rules! c_str {
     ($text:expr) => {{
-        concat!($text, '\0').as_bytes()
+        $crate::__c_types::c_str!($text).to_bytes_with_null()
     }};
 }
 
+/// Given a buffer of UTF-16 code units, break off one code point worth of units and return it
+/// along with the remaining units.
+///
+/// A surrogate that isn't part of a high-low pair can't be decoded, and is returned as an `Err`
+/// holding the surrogate.
+///
+/// **See:** [UTF-16 encoding reference on Wikipedia](https://en.wikipedia.org/wiki/UTF-16#Code_points_from_U+010000_to_U+10FFFF)
+///
+/// ```
+/// # use triangle_from_scratch_win32::str_util::break_off_utf16_code_point;
+/// let units: Vec<u16> = "𐍈!".encode_utf16().collect();
+/// assert_eq!(
+///     break_off_utf16_code_point(&units),
+///     Some((Ok('𐍈' as u32), &['!' as u16][..]))
+/// );
+/// assert_eq!(break_off_utf16_code_point(&[0xDC00]), Some((Err(0xDC00), &[][..])));
+/// ```
+pub const fn break_off_utf16_code_point(utf16: &[u16]) -> Option<(Result<u32, u16>, &[u16])> {
+    match utf16 {
+        // A high surrogate followed by a low surrogate encodes a code point above U+FFFF.
+        [high @ 0xD800..=0xDBFF, low @ 0xDC00..=0xDFFF, rest @ ..] => {
+            let high = (*high & 0x03FF) as u32;
+            let low = (*low & 0x03FF) as u32;
+            Some((Ok(0x1_0000 + (high << 10 | low)), rest))
+        }
+
+        // Any other surrogate is unpaired.
+        [unpaired @ 0xD800..=0xDFFF, rest @ ..] => Some((Err(*unpaired), rest)),
+
+        // Everything else is its own code point.
+        [unit, rest @ ..] => Some((Ok(*unit as u32), rest)),
+
+        [] => None,
+    }
+}
+
+/// An unpaired surrogate, found while decoding UTF-16.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Utf16Error {
+    index: usize,
+    unit: u16,
+}
+
+impl Utf16Error {
+    /// The index of the surrogate, in code units.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The unpaired surrogate.
+    pub fn unit(&self) -> u16 {
+        self.unit
+    }
+}
+
+impl fmt::Display for Utf16Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unpaired surrogate 0x{:04X} at code unit {}",
+            self.unit, self.index
+        )
+    }
+}
+
+impl std::error::Error for Utf16Error {}
+
+/// An iterator over the [`char`]s of some UTF-16, created by [`decode_utf16_chars`].
+#[derive(Debug, Clone)]
+pub struct DecodeUtf16<'a> {
+    rest: &'a [u16],
+    index: usize,
+}
+
+impl Iterator for DecodeUtf16<'_> {
+    type Item = Result<char, Utf16Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (decoded, rest) = break_off_utf16_code_point(self.rest)?;
+        let index = self.index;
+        self.index += self.rest.len() - rest.len();
+        self.rest = rest;
+        Some(match decoded {
+            // Safety: surrogates are the only code points that aren't chars, and they're never
+            // returned as `Ok`.
+            Ok(code_point) => Ok(unsafe { char::from_u32_unchecked(code_point) }),
+            Err(unit) => Err(Utf16Error { index, unit }),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.rest.len().div_ceil(2), Some(self.rest.len()))
+    }
+}
+
+/// Decodes UTF-16 one [`char`] at a time, without stopping at unpaired surrogates.
+pub fn decode_utf16_chars(utf16: &[u16]) -> DecodeUtf16<'_> {
+    DecodeUtf16 {
+        rest: utf16,
+        index: 0,
+    }
+}
+
+/// Decodes UTF-16 into a `String`, failing at the first unpaired surrogate.
+pub fn decode_utf16(utf16: &[u16]) -> Result<String, Utf16Error> {
+    decode_utf16_chars(utf16).collect()
+}
+
+/// Decodes UTF-16 into a `String`, replacing unpaired surrogates with U+FFFD (�).
+pub fn decode_utf16_lossy(utf16: &[u16]) -> String {
+    decode_utf16_chars(utf16)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+/// Returns the index of the first null in `utf16`, if it has one.
+fn find_null(utf16: &[u16]) -> Option<usize> {
+    utf16.iter().position(|&u| u == 0)
+}
+
+/// A string that would be cut short by a null before its end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InteriorNullError {
+    position: usize,
+}
+
+impl InteriorNullError {
+    /// The index of the first null, in code units.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for InteriorNullError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "interior null at code unit {}", self.position)
+    }
+}
+
+impl std::error::Error for InteriorNullError {}
+
+/// Why some code units couldn't be used as a [`WideStr`] or [`WideString`] as-is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FromUnitsWithNullError {
+    /// There's a null before the last code unit.
+    InteriorNull(InteriorNullError),
+    /// The last code unit isn't a null (or there are no code units at all).
+    NotNullTerminated,
+}
+
+impl fmt::Display for FromUnitsWithNullError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InteriorNull(e) => e.fmt(f),
+            Self::NotNullTerminated => f.write_str("not null-terminated"),
+        }
+    }
+}
+
+impl std::error::Error for FromUnitsWithNullError {}
+
+/// Some code units that don't contain a null at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MissingNullError;
+
+impl fmt::Display for MissingNullError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("no null terminator")
+    }
+}
+
+impl std::error::Error for MissingNullError {}
+
+/// A borrowed, null-terminated UTF-16 string, like the ones Win32 takes as `LPCWSTR`s.
+///
+/// This is to [`WideString`] what [`CStr`](std::ffi::CStr) is to [`CString`](std::ffi::CString):
+/// it always ends in exactly one null, with no nulls before it, so [`as_ptr`](Self::as_ptr) can
+/// be handed straight to Win32. The contents aren't guaranteed to be valid UTF-16, since Windows
+/// doesn't guarantee that of anything it hands back (file names in particular).
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct WideStr {
+    /// The code units, including the null terminator.
+    units: [u16],
+}
+
+impl WideStr {
+    /// Wraps code units that end in a null, with no other nulls.
+    pub fn from_units_with_null(units: &[u16]) -> Result<&Self, FromUnitsWithNullError> {
+        match find_null(units) {
+            Some(position) if position + 1 == units.len() => {
+                // Safety: just checked
+                Ok(unsafe { Self::from_units_with_null_unchecked(units) })
+            }
+            Some(position) => Err(FromUnitsWithNullError::InteriorNull(InteriorNullError {
+                position,
+            })),
+            None => Err(FromUnitsWithNullError::NotNullTerminated),
+        }
+    }
+
+    /// Wraps code units up to (and including) the first null, ignoring anything after it. This
+    /// suits fixed-size buffers that Win32 has written a string into.
+    pub fn from_units_until_null(units: &[u16]) -> Result<&Self, MissingNullError> {
+        let end = find_null(units).ok_or(MissingNullError)?;
+        // Safety: `units[end]` is the first null
+        Ok(unsafe { Self::from_units_with_null_unchecked(&units[..=end]) })
+    }
+
+    /// Wraps code units without checking them.
+    ///
+    /// ## Safety
+    ///
+    /// `units` must end in a null, and contain no other nulls.
+    pub const unsafe fn from_units_with_null_unchecked(units: &[u16]) -> &Self {
+        // Safety: `WideStr` is a `repr(transparent)` wrapper around `[u16]`
+        &*(units as *const [u16] as *const Self)
+    }
+
+    /// Wraps a null-terminated string that Win32 has handed back.
+    ///
+    /// ## Safety
+    ///
+    /// - `ptr` must point to a null-terminated UTF-16 string.
+    /// - The string must stay alive and unchanged for `'a`.
+    pub unsafe fn from_ptr<'a>(ptr: *const u16) -> &'a Self {
+        let mut len = 0;
+        while *ptr.add(len) != 0 {
+            len += 1;
+        }
+        Self::from_units_with_null_unchecked(core::slice::from_raw_parts(ptr, len + 1))
+    }
+
+    /// A pointer to the first code unit, which can be passed to Win32 as an `LPCWSTR`.
+    pub const fn as_ptr(&self) -> *const u16 {
+        self.units.as_ptr()
+    }
+
+    /// The code units, without the null terminator.
+    pub fn as_units(&self) -> &[u16] {
+        &self.units[..self.units.len() - 1]
+    }
+
+    /// The code units, including the null terminator.
+    pub const fn as_units_with_null(&self) -> &[u16] {
+        &self.units
+    }
+
+    /// The length in code units, not counting the null terminator.
+    pub const fn len(&self) -> usize {
+        self.units.len() - 1
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Decodes the string one [`char`] at a time.
+    pub fn chars(&self) -> DecodeUtf16<'_> {
+        decode_utf16_chars(self.as_units())
+    }
+
+    /// Decodes the string, failing if it has any unpaired surrogates.
+    pub fn try_to_string(&self) -> Result<String, Utf16Error> {
+        decode_utf16(self.as_units())
+    }
+
+    /// Decodes the string, replacing unpaired surrogates with U+FFFD (�).
+    pub fn to_string_lossy(&self) -> String {
+        decode_utf16_lossy(self.as_units())
+    }
+
+    /// Converts the string to an `OsString`.
+    ///
+    /// On Windows this is lossless, even for unpaired surrogates. Elsewhere, unpaired surrogates
+    /// are replaced with U+FFFD (�).
+    pub fn to_os_string(&self) -> OsString {
+        #[cfg(windows)]
+        {
+            OsString::from_wide(self.as_units())
+        }
+        #[cfg(not(windows))]
+        {
+            OsString::from(self.to_string_lossy())
+        }
+    }
+}
+
+impl fmt::Debug for WideStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"")?;
+        for c in self.chars() {
+            match c {
+                Ok(c) => write!(f, "{}", c.escape_debug())?,
+                Err(e) => write!(f, "\\u{{{:x}}}", e.unit())?,
+            }
+        }
+        f.write_str("\"")
+    }
+}
+
+impl Default for &WideStr {
+    fn default() -> Self {
+        // Safety: a lone null is the empty string
+        unsafe { WideStr::from_units_with_null_unchecked(&[0]) }
+    }
+}
+
+impl AsRef<WideStr> for WideStr {
+    fn as_ref(&self) -> &WideStr {
+        self
+    }
+}
+
+impl ToOwned for WideStr {
+    type Owned = WideString;
+
+    fn to_owned(&self) -> WideString {
+        WideString {
+            units: self.units.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<&WideStr> for String {
+    type Error = Utf16Error;
+
+    fn try_from(s: &WideStr) -> Result<Self, Utf16Error> {
+        s.try_to_string()
+    }
+}
+
+impl From<&WideStr> for OsString {
+    fn from(s: &WideStr) -> Self {
+        s.to_os_string()
+    }
+}
+
+/// An owned, null-terminated UTF-16 string.
+///
+/// Like [`WideStr`], it always ends in exactly one null with no nulls before it. Creating one from
+/// a Rust string fails if the string contains a null, rather than silently truncating it the way
+/// [`wide_null`] does.
+///
+/// ```
+/// # use triangle_from_scratch_win32::str_util::WideString;
+/// let class_name = WideString::new("Sample Window Class").unwrap();
+/// assert_eq!(class_name.as_units_with_null().last(), Some(&0));
+/// assert_eq!(class_name.to_string_lossy(), "Sample Window Class");
+///
+/// assert_eq!(WideString::new("nu\0ll").unwrap_err().position(), 2);
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WideString {
+    /// The code units, including the null terminator.
+    units: Vec<u16>,
+}
+
+impl WideString {
+    /// Encodes `s` as UTF-16, failing if it contains a null.
+    pub fn new(s: &str) -> Result<Self, InteriorNullError> {
+        let mut units = Vec::with_capacity(count_utf16_code_units(s) + 1);
+        units.extend(s.encode_utf16());
+        Self::from_units(units)
+    }
+
+    /// Adds a null terminator to `units`, failing if they already contain a null.
+    pub fn from_units(units: impl Into<Vec<u16>>) -> Result<Self, InteriorNullError> {
+        let mut units = units.into();
+        if let Some(position) = find_null(&units) {
+            return Err(InteriorNullError { position });
+        }
+        units.push(0);
+        Ok(Self { units })
+    }
+
+    /// Takes code units that already end in a null, failing if there are any other nulls.
+    pub fn from_units_with_null(units: Vec<u16>) -> Result<Self, FromUnitsWithNullError> {
+        WideStr::from_units_with_null(&units)?;
+        Ok(Self { units })
+    }
+
+    /// Converts an `OsStr`, failing if it contains a null.
+    ///
+    /// On Windows this is lossless, even for unpaired surrogates. Elsewhere, anything that isn't
+    /// valid Unicode is replaced with U+FFFD (�).
+    pub fn from_os_str(s: &OsStr) -> Result<Self, InteriorNullError> {
+        #[cfg(windows)]
+        let units: Vec<u16> = s.encode_wide().collect();
+        #[cfg(not(windows))]
+        let units: Vec<u16> = s.to_string_lossy().encode_utf16().collect();
+        Self::from_units(units)
+    }
+
+    pub fn as_wide_str(&self) -> &WideStr {
+        // Safety: `units` upholds the same guarantees as `WideStr`
+        unsafe { WideStr::from_units_with_null_unchecked(&self.units) }
+    }
+
+    /// Gives back the code units, including the null terminator.
+    pub fn into_units_with_null(self) -> Vec<u16> {
+        self.units
+    }
+}
+
+impl Default for WideString {
+    fn default() -> Self {
+        Self { units: vec![0] }
+    }
+}
+
+impl Deref for WideString {
+    type Target = WideStr;
+
+    fn deref(&self) -> &WideStr {
+        self.as_wide_str()
+    }
+}
+
+impl AsRef<WideStr> for WideString {
+    fn as_ref(&self) -> &WideStr {
+        self
+    }
+}
+
+impl Borrow<WideStr> for WideString {
+    fn borrow(&self) -> &WideStr {
+        self
+    }
+}
+
+impl fmt::Debug for WideString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_wide_str(), f)
+    }
+}
+
+impl From<&WideStr> for WideString {
+    fn from(s: &WideStr) -> Self {
+        s.to_owned()
+    }
+}
+
+impl From<WideString> for Vec<u16> {
+    fn from(s: WideString) -> Self {
+        s.into_units_with_null()
+    }
+}
+
+impl TryFrom<&str> for WideString {
+    type Error = InteriorNullError;
+
+    fn try_from(s: &str) -> Result<Self, InteriorNullError> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<&OsStr> for WideString {
+    type Error = InteriorNullError;
+
+    fn try_from(s: &OsStr) -> Result<Self, InteriorNullError> {
+        Self::from_os_str(s)
+    }
+}
+
+impl TryFrom<WideString> for String {
+    type Error = Utf16Error;
+
+    fn try_from(s: WideString) -> Result<Self, Utf16Error> {
+        s.try_to_string()
+    }
+}
+
+impl From<WideString> for OsString {
+    fn from(s: WideString) -> Self {
+        s.to_os_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -196,6 +687,311 @@ mod tests {
         }
     }
 
+    /// A small xorshift generator, so the property tests are reproducible without any dependencies.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// A random code unit, biased towards the interesting ones: nulls and surrogates.
+        fn unit(&mut self) -> u16 {
+            let n = self.next();
+            match n % 8 {
+                0 => 0,
+                1 | 2 => 0xD800 + (n >> 8) as u16 % 0x800,
+                _ => (n >> 8) as u16,
+            }
+        }
+
+        fn units(&mut self) -> Vec<u16> {
+            let len = self.next() % 16;
+            (0..len).map(|_| self.unit()).collect()
+        }
+    }
+
+    mod break_off_utf16_code_point {
+        use super::*;
+
+        #[test]
+        fn every_single_unit() {
+            for unit in 0..=u16::MAX {
+                let expected = match unit {
+                    0xD800..=0xDFFF => Err(unit),
+                    _ => Ok(unit as u32),
+                };
+                assert_eq!(
+                    break_off_utf16_code_point(&[unit]),
+                    Some((expected, &[][..]))
+                );
+            }
+        }
+
+        #[test]
+        fn every_code_point() {
+            let mut buffer = [0; 2];
+            for c in (0..=0x10FFFF).filter_map(char::from_u32) {
+                let units = c.encode_utf16(&mut buffer);
+                assert_eq!(
+                    break_off_utf16_code_point(units),
+                    Some((Ok(c as u32), &[][..])),
+                    "{c:?}"
+                );
+            }
+        }
+
+        #[test]
+        fn unpaired_surrogates() {
+            // a high surrogate followed by something other than a low surrogate
+            assert_eq!(
+                break_off_utf16_code_point(&[0xD800, 'a' as u16]),
+                Some((Err(0xD800), &['a' as u16][..]))
+            );
+            assert_eq!(
+                break_off_utf16_code_point(&[0xDBFF, 0xDBFF, 0xDC00]),
+                Some((Err(0xDBFF), &[0xDBFF, 0xDC00][..]))
+            );
+            // a low surrogate first
+            assert_eq!(
+                break_off_utf16_code_point(&[0xDC00, 0xD800]),
+                Some((Err(0xDC00), &[0xD800][..]))
+            );
+        }
+
+        #[test]
+        fn handles_empty_strings() {
+            assert!(break_off_utf16_code_point(&[]).is_none());
+        }
+    }
+
+    mod decode_utf16 {
+        use super::*;
+
+        #[test]
+        fn round_trips_international_symbols() {
+            let s = "$¢ह€한𐍈, 漢字, ひらがな / 平仮名, カタカナ / 片仮名";
+            let units: Vec<u16> = s.encode_utf16().collect();
+            assert_eq!(decode_utf16(&units).as_deref(), Ok(s));
+            assert_eq!(decode_utf16_lossy(&units), s);
+        }
+
+        #[test]
+        fn reports_the_first_unpaired_surrogate() {
+            let units = ['a' as u16, 0xD83D, 0xDE00, 'b' as u16, 0xDE00, 0xD800];
+            let e = decode_utf16(&units).unwrap_err();
+            assert_eq!((e.index(), e.unit()), (4, 0xDE00));
+            assert_eq!(e.to_string(), "unpaired surrogate 0xDE00 at code unit 4");
+            assert_eq!(decode_utf16_lossy(&units), "a😀b��");
+        }
+
+        #[test]
+        fn matches_std_on_random_input() {
+            let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+            for _ in 0..100_000 {
+                let units = rng.units();
+                assert_eq!(
+                    decode_utf16(&units).ok(),
+                    String::from_utf16(&units).ok(),
+                    "{units:04X?}"
+                );
+                assert_eq!(
+                    decode_utf16_lossy(&units),
+                    String::from_utf16_lossy(&units),
+                    "{units:04X?}"
+                );
+                let ours: Vec<_> = decode_utf16_chars(&units)
+                    .map(|c| c.map_err(|e| e.unit()))
+                    .collect();
+                let std: Vec<_> = char::decode_utf16(units.iter().copied())
+                    .map(|c| c.map_err(|e| e.unpaired_surrogate()))
+                    .collect();
+                assert_eq!(ours, std, "{units:04X?}");
+            }
+        }
+    }
+
+    mod wide_str {
+        use super::*;
+
+        #[test]
+        fn checks_null_termination() {
+            let s = WideStr::from_units_with_null(&[104, 105, 0]).unwrap();
+            assert_eq!(s.as_units(), [104, 105]);
+            assert_eq!(s.as_units_with_null(), [104, 105, 0]);
+            assert_eq!(s.len(), 2);
+            assert_eq!(s.to_string_lossy(), "hi");
+
+            assert_eq!(
+                WideStr::from_units_with_null(&[104, 105]),
+                Err(FromUnitsWithNullError::NotNullTerminated)
+            );
+            assert_eq!(
+                WideStr::from_units_with_null(&[]),
+                Err(FromUnitsWithNullError::NotNullTerminated)
+            );
+            assert_eq!(
+                WideStr::from_units_with_null(&[104, 0, 105, 0]),
+                Err(FromUnitsWithNullError::InteriorNull(InteriorNullError {
+                    position: 1
+                }))
+            );
+        }
+
+        #[test]
+        fn stops_at_the_first_null() {
+            let buffer = [104, 105, 0, 33, 0, 0];
+            let s = WideStr::from_units_until_null(&buffer).unwrap();
+            assert_eq!(s.as_units_with_null(), [104, 105, 0]);
+            assert_eq!(
+                WideStr::from_units_until_null(&[104, 105]),
+                Err(MissingNullError)
+            );
+        }
+
+        #[test]
+        fn from_ptr() {
+            let units = utf16_null!("hello");
+            let s = unsafe { WideStr::from_ptr(units.as_ptr()) };
+            assert_eq!(s.as_units_with_null(), units);
+            assert_eq!(s.as_ptr(), units.as_ptr());
+        }
+
+        #[test]
+        fn empty() {
+            let s = <&WideStr>::default();
+            assert!(s.is_empty());
+            assert_eq!(s.as_units_with_null(), [0]);
+            assert_eq!(s.try_to_string().as_deref(), Ok(""));
+        }
+
+        #[test]
+        fn decoding() {
+            let units = [0xD83D, 0xDE00, 0xD800, '!' as u16, 0];
+            let s = WideStr::from_units_with_null(&units).unwrap();
+            assert_eq!(s.try_to_string().unwrap_err().index(), 2);
+            assert_eq!(String::try_from(s).unwrap_err().unit(), 0xD800);
+            assert_eq!(s.to_string_lossy(), "😀�!");
+            assert_eq!(format!("{s:?}"), r#""😀\u{d800}!""#);
+        }
+
+        #[test]
+        fn os_strings() {
+            let units = utf16_null!("Ünïcödé");
+            let s = WideStr::from_units_with_null(&units).unwrap();
+            assert_eq!(s.to_os_string(), OsStr::new("Ünïcödé"));
+            assert_eq!(OsString::from(s), OsStr::new("Ünïcödé"));
+        }
+
+        #[cfg(windows)]
+        #[test]
+        fn os_strings_keep_unpaired_surrogates_on_windows() {
+            let s = WideStr::from_units_with_null(&[0xD800, 0]).unwrap();
+            let os = s.to_os_string();
+            assert_eq!(os.encode_wide().collect::<Vec<_>>(), [0xD800]);
+            assert_eq!(WideString::from_os_str(&os).unwrap().as_wide_str(), s);
+        }
+
+        #[test]
+        fn random_units_are_accepted_exactly_when_they_are_terminated_once() {
+            let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+            for _ in 0..100_000 {
+                let units = rng.units();
+                let first_null = units.iter().position(|&u| u == 0);
+                let valid = first_null.is_some() && first_null == Some(units.len() - 1);
+                assert_eq!(
+                    WideStr::from_units_with_null(&units).is_ok(),
+                    valid,
+                    "{units:04X?}"
+                );
+
+                match WideStr::from_units_until_null(&units) {
+                    Ok(s) => assert_eq!(s.len(), first_null.unwrap()),
+                    Err(MissingNullError) => assert_eq!(first_null, None),
+                }
+            }
+        }
+    }
+
+    mod wide_string {
+        use super::*;
+
+        #[test]
+        fn matches_wide_null() {
+            let s = "$¢ह€한𐍈, 漢字, ひらがな / 平仮名, カタカナ / 片仮名";
+            let wide = WideString::new(s).unwrap();
+            assert_eq!(wide.as_units_with_null(), wide_null(s));
+            assert_eq!(wide.try_to_string().as_deref(), Ok(s));
+            assert_eq!(Vec::from(wide), wide_null(s));
+        }
+
+        #[test]
+        fn rejects_interior_nulls() {
+            assert_eq!(WideString::new("a\0b").unwrap_err().position(), 1);
+            assert_eq!(WideString::new("\0").unwrap_err().position(), 0);
+            assert_eq!(
+                WideString::from_units(vec![1, 2, 0])
+                    .unwrap_err()
+                    .position(),
+                2,
+                "from_units adds its own terminator"
+            );
+            assert!(WideString::try_from("ok").is_ok());
+            assert!(WideString::try_from(OsStr::new("n\0")).is_err());
+            assert_eq!(
+                WideString::from_units_with_null(vec![0, 0]),
+                Err(FromUnitsWithNullError::InteriorNull(InteriorNullError {
+                    position: 0
+                }))
+            );
+            assert_eq!(
+                WideString::from_units_with_null(vec![]),
+                Err(FromUnitsWithNullError::NotNullTerminated)
+            );
+        }
+
+        #[test]
+        fn borrowing_and_owning() {
+            let owned = WideString::new("hello").unwrap();
+            let borrowed: &WideStr = &owned;
+            assert_eq!(borrowed.to_owned(), owned);
+            assert_eq!(WideString::from(borrowed), owned);
+            assert_eq!(WideString::default().as_wide_str(), <&WideStr>::default());
+            assert_eq!(format!("{owned:?}"), r#""hello""#);
+        }
+
+        #[test]
+        fn os_strings() {
+            let wide = WideString::from_os_str(OsStr::new("Ünïcödé")).unwrap();
+            assert_eq!(wide.to_string_lossy(), "Ünïcödé");
+            assert_eq!(OsString::from(wide), OsStr::new("Ünïcödé"));
+        }
+
+        #[test]
+        fn random_strings_round_trip() {
+            let mut rng = Rng(0xD1B5_4A32_D192_ED03);
+            for _ in 0..10_000 {
+                let s: String = (0..rng.next() % 12)
+                    .filter_map(|_| char::from_u32((rng.next() % 0x11_0000) as u32))
+                    .collect();
+                match WideString::new(&s) {
+                    Ok(wide) => {
+                        assert!(!s.contains('\0'));
+                        assert_eq!(wide.len(), s.encode_utf16().count());
+                        assert_eq!(wide.try_to_string().as_ref(), Ok(&s));
+                        assert_eq!(String::try_from(wide), Ok(s));
+                    }
+                    Err(e) => {
+                        assert_eq!(e.position(), s.encode_utf16().position(|u| u == 0).unwrap())
+                    }
+                }
+            }
+        }
+    }
+
     mod utf16 {
         #[test]
         fn basic_usage() {
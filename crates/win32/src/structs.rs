@@ -2,7 +2,10 @@
 
 use core::{fmt, ptr};
 
-use super::{constants::*, extern_bindings::FormatMessageW, typedefs::*, LocalFree};
+use super::{
+    constants::*, extern_bindings::FormatMessageW, str_util::decode_utf16_lossy, typedefs::*,
+    LocalFree,
+};
 use c_types::*;
 
 /// Implements zero-initialization for C-style structs.
@@ -192,6 +195,224 @@ pub type LPRECT = *mut RECT;
 
 unsafe_impl_default_zeroed! { RECT }
 
+/// Like [`POINT`], but always with 32-bit coordinates.
+///
+/// [See MSDN](https://docs.microsoft.com/en-us/windows/win32/api/windef/ns-windef-pointl).
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct POINTL {
+    pub x: LONG,
+    pub y: LONG,
+}
+
+unsafe_impl_default_zeroed! { POINTL }
+
+/// Where a window goes when it's minimized, maximized and restored.
+///
+/// [See MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/ns-winuser-windowplacement).
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct WINDOWPLACEMENT {
+    /// The size of this struct. Use this struct's [`Default::default()`] implementation, which sets
+    /// this field properly for you.
+    pub length: UINT,
+    pub flags: UINT,
+    /// How the window is shown, e.g. [`SW_SHOWMAXIMIZED`].
+    pub showCmd: UINT,
+    pub ptMinPosition: POINT,
+    pub ptMaxPosition: POINT,
+    /// Where the window is when it's neither minimized nor maximized, in workspace coordinates.
+    pub rcNormalPosition: RECT,
+}
+
+impl Default for WINDOWPLACEMENT {
+    fn default() -> Self {
+        let mut out: Self = unsafe { core::mem::MaybeUninit::<Self>::zeroed().assume_init() };
+        out.length = core::mem::size_of::<Self>() as UINT;
+        out
+    }
+}
+
+/// Information about a display monitor, including its device name.
+///
+/// [See MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/ns-winuser-monitorinfoexw).
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct MONITORINFOEXW {
+    /// The size of this struct. Use this struct's [`Default::default()`] implementation, which sets
+    /// this field properly for you.
+    pub cbSize: DWORD,
+    /// The monitor's rectangle, in virtual-screen coordinates.
+    pub rcMonitor: RECT,
+    /// The part of the monitor not covered by the taskbar and other app bars.
+    pub rcWork: RECT,
+    /// `MONITORINFOF_PRIMARY` for the primary monitor.
+    pub dwFlags: DWORD,
+    /// The monitor's device name, e.g. `\\.\DISPLAY1`, null-terminated.
+    pub szDevice: [WCHAR; 32],
+}
+
+impl Default for MONITORINFOEXW {
+    fn default() -> Self {
+        let mut out: Self = unsafe { core::mem::MaybeUninit::<Self>::zeroed().assume_init() };
+        out.cbSize = core::mem::size_of::<Self>() as DWORD;
+        out
+    }
+}
+
+/// Describes a display device's mode: its resolution, color depth and refresh rate.
+///
+/// This is the display version of the struct. The C header has unions where printers and displays
+/// use the same bytes for different things, and only the display fields are given here.
+///
+/// [See MSDN](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/ns-wingdi-devmodew).
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct DEVMODEW {
+    pub dmDeviceName: [WCHAR; 32],
+    pub dmSpecVersion: WORD,
+    pub dmDriverVersion: WORD,
+    /// The size of this struct. Use this struct's [`Default::default()`] implementation, which sets
+    /// this field properly for you.
+    pub dmSize: WORD,
+    pub dmDriverExtra: WORD,
+    /// Which of the other fields are set, as `DM_*` flags like [`DM_PELSWIDTH`].
+    pub dmFields: DWORD,
+    /// Where the monitor is in the virtual screen.
+    pub dmPosition: POINTL,
+    pub dmDisplayOrientation: DWORD,
+    pub dmDisplayFixedOutput: DWORD,
+    pub dmColor: CShort,
+    pub dmDuplex: CShort,
+    pub dmYResolution: CShort,
+    pub dmTTOption: CShort,
+    pub dmCollate: CShort,
+    pub dmFormName: [WCHAR; 32],
+    pub dmLogPixels: WORD,
+    pub dmBitsPerPel: DWORD,
+    pub dmPelsWidth: DWORD,
+    pub dmPelsHeight: DWORD,
+    pub dmDisplayFlags: DWORD,
+    /// The refresh rate in hertz. `0` or `1` mean the hardware's default.
+    pub dmDisplayFrequency: DWORD,
+    pub dmICMMethod: DWORD,
+    pub dmICMIntent: DWORD,
+    pub dmMediaType: DWORD,
+    pub dmDitherType: DWORD,
+    pub dmReserved1: DWORD,
+    pub dmReserved2: DWORD,
+    pub dmPanningWidth: DWORD,
+    pub dmPanningHeight: DWORD,
+}
+
+impl Default for DEVMODEW {
+    fn default() -> Self {
+        let mut out: Self = unsafe { core::mem::MaybeUninit::<Self>::zeroed().assume_init() };
+        out.dmSize = core::mem::size_of::<Self>() as WORD;
+        out
+    }
+}
+
+// The display fields have to line up with the printer fields they share bytes with
+const _: () = assert!(core::mem::size_of::<DEVMODEW>() == 220);
+const _: () = assert!(core::mem::offset_of!(DEVMODEW, dmBitsPerPel) == 168);
+
+/// Asks for raw input from a kind of device, with [`super::RegisterRawInputDevices()`].
+///
+/// [See MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/ns-winuser-rawinputdevice).
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct RAWINPUTDEVICE {
+    /// The HID usage page, e.g. [`HID_USAGE_PAGE_GENERIC`].
+    pub usUsagePage: USHORT,
+    /// The HID usage within the page, e.g. [`HID_USAGE_GENERIC_MOUSE`].
+    pub usUsage: USHORT,
+    /// `RIDEV_*` flags.
+    pub dwFlags: DWORD,
+    /// The window that gets [`WM_INPUT`], or null for whichever has the keyboard focus.
+    pub hwndTarget: HWND,
+}
+
+unsafe_impl_default_zeroed! { RAWINPUTDEVICE }
+
+/// The start of every [`RAWINPUT`].
+///
+/// [See MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/ns-winuser-rawinputheader).
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct RAWINPUTHEADER {
+    /// `RIM_TYPE*`, e.g. [`RIM_TYPEMOUSE`].
+    pub dwType: DWORD,
+    pub dwSize: DWORD,
+    pub hDevice: HANDLE,
+    pub wParam: WPARAM,
+}
+
+unsafe_impl_default_zeroed! { RAWINPUTHEADER }
+
+/// What a mouse did.
+///
+/// [See MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/ns-winuser-rawmouse).
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct RAWMOUSE {
+    /// `MOUSE_MOVE_*` flags, which say how to read [`lLastX`](Self::lLastX) and
+    /// [`lLastY`](Self::lLastY).
+    pub usFlags: USHORT,
+    /// A union in the C header: the low word is `usButtonFlags` (`RI_MOUSE_*` button
+    /// transitions), and the high word is `usButtonData` (the wheel delta, if the flags say the
+    /// wheel moved).
+    pub ulButtons: ULONG,
+    pub ulRawButtons: ULONG,
+    /// How far the mouse moved, or where it is if it reports absolute positions.
+    pub lLastX: LONG,
+    pub lLastY: LONG,
+    pub ulExtraInformation: ULONG,
+}
+
+unsafe_impl_default_zeroed! { RAWMOUSE }
+
+/// Raw input from a mouse, as read by [`super::GetRawInputData()`].
+///
+/// This is the mouse version of the struct. The C header has a union of the mouse, keyboard and
+/// HID versions, and the mouse one is the largest.
+///
+/// [See MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/ns-winuser-rawinput).
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct RAWINPUT {
+    pub header: RAWINPUTHEADER,
+    pub mouse: RAWMOUSE,
+}
+
+unsafe_impl_default_zeroed! { RAWINPUT }
+
+const _: () = assert!(core::mem::offset_of!(RAWMOUSE, lLastX) == 12);
+const _: () = assert!(core::mem::size_of::<RAWMOUSE>() == 24);
+
+/// Describes an icon or cursor, for [`CreateIconIndirect`](super::CreateIconIndirect).
+///
+/// See [MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/ns-winuser-iconinfo).
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct ICONINFO {
+    /// `TRUE` for an icon, or `FALSE` for a cursor.
+    pub fIcon: BOOL,
+    /// The x coordinate of a cursor's hotspot. Icons' hotspots are always in their middle.
+    pub xHotspot: DWORD,
+    /// The y coordinate of a cursor's hotspot.
+    pub yHotspot: DWORD,
+    /// The monochrome mask. With a color bitmap, this only has the AND mask, and is the same size
+    /// as the color bitmap.
+    pub hbmMask: HBITMAP,
+    /// The color bitmap, which can have an alpha channel.
+    pub hbmColor: HBITMAP,
+}
+
+unsafe_impl_default_zeroed! { ICONINFO }
+
+pub type PICONINFO = *mut ICONINFO;
+
 /// Contains message information from a thread's message queue.
 ///
 /// See [MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winuser/ns-winuser-msg).
@@ -304,6 +525,53 @@ impl Default for PIXELFORMATDESCRIPTOR {
     }
 }
 
+/// Holds the state of an asynchronous ("overlapped") I/O operation.
+///
+/// The structure must stay at the same address until the operation completes, since the system
+/// writes to it in the background.
+///
+/// [See MSDN](https://docs.microsoft.com/en-us/windows/win32/api/minwinbase/ns-minwinbase-overlapped).
+#[derive(Debug)]
+#[repr(C)]
+pub struct OVERLAPPED {
+    /// The status code of the operation. Reserved for the system.
+    pub Internal: ULONG_PTR,
+    /// The number of bytes transferred. Reserved for the system.
+    pub InternalHigh: ULONG_PTR,
+    /// The low half of the file position to start at. This is in a union with a `PVOID` in the C
+    /// headers, which is never bigger than these two fields together.
+    pub Offset: DWORD,
+    /// The high half of the file position to start at.
+    pub OffsetHigh: DWORD,
+    /// An event that's set to the signaled state when the operation completes.
+    pub hEvent: HANDLE,
+}
+
+unsafe_impl_default_zeroed! { OVERLAPPED }
+
+/// Describes a change to a file in a directory watched with
+/// [`ReadDirectoryChangesW`](super::ReadDirectoryChangesW).
+///
+/// The buffer filled by `ReadDirectoryChangesW` holds a chain of these, each followed by the rest
+/// of its file name, so this is only ever used through pointers.
+///
+/// [See MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winnt/ns-winnt-file_notify_information).
+#[derive(Debug)]
+#[repr(C)]
+pub struct FILE_NOTIFY_INFORMATION {
+    /// The number of bytes to skip to get to the next record, or zero for the last record.
+    pub NextEntryOffset: DWORD,
+    /// What happened to the file. One of the `FILE_ACTION_*` constants.
+    pub Action: DWORD,
+    /// The length of the file name, in *bytes*.
+    pub FileNameLength: DWORD,
+    /// The first character of the file name, which is relative to the watched directory and not
+    /// null-terminated.
+    pub FileName: [WCHAR; 1],
+}
+
+pub type PFILE_NOTIFY_INFORMATION = *mut FILE_NOTIFY_INFORMATION;
+
 /// Frees a local block of memory upon being dropped.
 #[derive(Debug)]
 pub struct OnDropLocalFree(HLOCAL);
@@ -405,13 +673,9 @@ impl fmt::Display for Win32Error {
         // it is a valid HLOCAL.
         let _buffer_on_drop = unsafe { OnDropLocalFree::from_raw_handle(buffer as HLOCAL) };
 
-        for decode_result in core::char::decode_utf16(buffer_slice.iter().copied()) {
-            match decode_result {
-                Ok('\r') | Ok('\n') => write!(f, " ")?, // eat newlines
-                Ok(chr) => write!(f, "{chr}")?,
-                Err(_) => write!(f, "�")?,
-            }
-        }
+        // eat newlines
+        let message = decode_utf16_lossy(buffer_slice).replace(['\r', '\n'], " ");
+        f.write_str(&message)?;
 
         Ok(())
     }
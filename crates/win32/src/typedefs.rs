@@ -25,6 +25,18 @@ pub type BOOL = CInt;
 /// ```
 pub type BYTE = u8;
 
+/// A handle to a DPI awareness mode, like [`DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2`].
+///
+/// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/hidpi/dpi-awareness-context), this is
+/// defined in WinDef.h as follows:
+///
+/// ```c
+/// DECLARE_HANDLE(DPI_AWARENESS_CONTEXT);
+/// ```
+///
+/// [`DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2`]: crate::constants::DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2
+pub type DPI_AWARENESS_CONTEXT = HANDLE;
+
 /// A 32-bit unsigned integer. The range is 0 through 4294967295 decimal.
 ///
 /// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types), this is
@@ -41,6 +53,16 @@ pub type FARPROC = *mut core::ffi::c_void;
 /// Win32 float definition
 pub type FLOAT = CFloat;
 
+/// A COM/OLE result code. Negative values are errors.
+///
+/// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types), this is
+/// defined in WinNT.h as follows:
+///
+/// ```c
+/// typedef LONG HRESULT;
+/// ```
+pub type HRESULT = LONG;
+
 /// A handle to a win32 object.
 ///
 /// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types), this is
@@ -51,6 +73,16 @@ pub type FLOAT = CFloat;
 /// ```
 pub type HANDLE = PVOID;
 
+/// A handle to a [bitmap](https://docs.microsoft.com/en-us/windows/win32/gdi/bitmaps).
+///
+/// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types), this is
+/// defined in WinDef.h as follows:
+///
+/// ```c
+/// typedef HANDLE HBITMAP;
+/// ```
+pub type HBITMAP = HANDLE;
+
 /// A handle to a [brush](https://docs.microsoft.com/en-us/windows/win32/gdi/brushes).
 ///
 /// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types), this is
@@ -81,6 +113,36 @@ pub type HCURSOR = HICON;
 /// ```
 pub type HDC = HANDLE;
 
+/// A handle to a GDI object, like a bitmap or a brush.
+///
+/// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types), this is
+/// defined in WinDef.h as follows:
+///
+/// ```c
+/// typedef HANDLE HGDIOBJ;
+/// ```
+pub type HGDIOBJ = HANDLE;
+
+/// A handle to a global memory block.
+///
+/// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types#hglobal),
+/// this is defined in WinDef.h as follows:
+///
+/// ```c
+/// typedef HANDLE HGLOBAL;
+/// ```
+pub type HGLOBAL = HANDLE;
+
+/// A handle to the files dropped on a window, from [`WM_DROPFILES`](super::constants::WM_DROPFILES).
+///
+/// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/shell/hdrop), this is defined in
+/// ShellAPI.h as follows:
+///
+/// ```c
+/// DECLARE_HANDLE(HDROP);
+/// ```
+pub type HDROP = HANDLE;
+
 /// A handle to a GL rendering context.
 pub type HGLRC = HANDLE;
 
@@ -104,6 +166,16 @@ pub type HICON = HANDLE;
 /// ```
 pub type HINSTANCE = HANDLE;
 
+/// A handle to a keyboard layout.
+///
+/// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types), this is
+/// defined in WinDef.h as follows:
+///
+/// ```c
+/// typedef HANDLE HKL;
+/// ```
+pub type HKL = HANDLE;
+
 /// A handle to a local memory block.
 ///
 /// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types#hlocal),
@@ -124,9 +196,29 @@ pub type HLOCAL = HANDLE;
 /// ```
 pub type HMENU = HANDLE;
 
+/// A handle to a [display monitor](https://docs.microsoft.com/en-us/windows/win32/gdi/hmonitor-and-the-device-context).
+///
+/// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types), this is
+/// defined in WinDef.h as follows:
+///
+/// ```c
+/// DECLARE_HANDLE(HMONITOR);
+/// ```
+pub type HMONITOR = HANDLE;
+
 /// On 16-bit Windows, HMODULE and HINSTANCE were different types. Now they're the same thing.
 pub type HMODULE = HINSTANCE;
 
+/// A handle to the raw input in a [`WM_INPUT`](crate::constants::WM_INPUT) message's `lParam`.
+///
+/// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types), this is
+/// defined in WinUser.h as follows:
+///
+/// ```c
+/// DECLARE_HANDLE(HRAWINPUT);
+/// ```
+pub type HRAWINPUT = HANDLE;
+
 /// A handle to a [window](https://docs.microsoft.com/en-us/windows/win32/winmsg/windows).
 ///
 /// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types), this is
@@ -137,6 +229,10 @@ pub type HMODULE = HINSTANCE;
 /// ```
 pub type HWND = HANDLE;
 
+/// A 64-bit signed integer, which C code can also get at as two 32-bit halves. See
+/// [MSDN](https://docs.microsoft.com/en-us/windows/win32/api/winnt/ns-winnt-large_integer-r1).
+pub type LARGE_INTEGER = i64;
+
 /// A 32-bit signed integer. See [MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types#long).
 pub type LONG = CLong;
 
@@ -192,6 +288,27 @@ pub type LPCVOID = *const core::ffi::c_void;
 /// ```
 pub type LPCWSTR = *const WCHAR;
 
+/// A pointer to a [`DWORD`].
+pub type LPDWORD = *mut DWORD;
+
+/// A pointer to an [`OVERLAPPED`](super::structs::OVERLAPPED) structure.
+pub type LPOVERLAPPED = *mut super::structs::OVERLAPPED;
+
+/// A nullable pointer to a callback that's run when an overlapped I/O operation completes.
+///
+/// See [MSDN's explanation](https://docs.microsoft.com/en-us/windows/win32/api/minwinbase/nc-minwinbase-lpoverlapped_completion_routine).
+pub type LPOVERLAPPED_COMPLETION_ROUTINE = Option<
+    unsafe extern "system" fn(
+        dwErrorCode: DWORD,
+        dwNumberOfBytesTransfered: DWORD,
+        lpOverlapped: LPOVERLAPPED,
+    ),
+>;
+
+/// A pointer to a `SECURITY_ATTRIBUTES` structure. We never fill one in, so this is left opaque;
+/// pass null to get the default security descriptor and a non-inheritable handle.
+pub type LPSECURITY_ATTRIBUTES = *mut core::ffi::c_void;
+
 /// A pointer to any type. Basically a c-style void pointer.
 ///
 /// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types), this is
@@ -235,6 +352,19 @@ pub type PROC = *mut core::ffi::c_void;
 /// ```
 pub type PVOID = *mut core::ffi::c_void;
 
+/// A 16-bit signed integer. See [MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types#short).
+pub type SHORT = CShort;
+
+/// The maximum number of bytes to which a pointer can point.
+///
+/// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types#size_t),
+/// this is defined in BaseTsd.h as follows:
+///
+/// ```c
+/// typedef ULONG_PTR SIZE_T;
+/// ```
+pub type SIZE_T = ULONG_PTR;
+
 /// Corresponds to [the following typedef in windows.h](https://docs.microsoft.com/en-us/openspecs/windows_protocols/ms-dtyp/52ddd4c3-55b9-4e03-8287-5392aac0627f):
 ///
 /// ```c
@@ -259,6 +389,9 @@ pub type UINT = CUInt;
 /// on other platforms. Rust has this functionality built in in the form of the [`usize`] type.
 pub type UINT_PTR = usize;
 
+/// A 32-bit unsigned integer. See [MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types#ulong).
+pub type ULONG = CULong;
+
 /// An unsigned long type for pointer precision. [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types#ulong_ptr),
 /// this should be used when casting a pointer to an `unsigned long` to perform pointer arithmetic.
 ///
@@ -310,6 +443,23 @@ pub type WNDPROC = Option<
     unsafe extern "system" fn(hwnd: HWND, uMsg: UINT, wParam: WPARAM, lParam: LPARAM) -> LRESULT,
 >;
 
+/// A nullable pointer to a callback function that's called for each monitor by
+/// [`EnumDisplayMonitors`](crate::extern_bindings::EnumDisplayMonitors). Return nonzero to carry
+/// on enumerating.
+///
+/// See [MSDN's explanation](https://docs.microsoft.com/en-us/windows/win32/api/winuser/nc-winuser-monitorenumproc).
+pub type MONITORENUMPROC = Option<
+    unsafe extern "system" fn(
+        hmonitor: HMONITOR,
+        hdc: HDC,
+        rect: *mut crate::structs::RECT,
+        data: LPARAM,
+    ) -> BOOL,
+>;
+
+/// An unsigned short. See [MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types#ushort).
+pub type USHORT = CUShort;
+
 /// A 16-bit unsigned integer. The range is 0 through 65535 decimal.
 ///
 /// [Per MSDN](https://docs.microsoft.com/en-us/windows/win32/winprog/windows-data-types), this is
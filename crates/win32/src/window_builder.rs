@@ -0,0 +1,231 @@
+//! Creating windows with a choice of styles, sized by their client area.
+//!
+//! ```no_run
+//! # use triangle_from_scratch_win32::{handles::WindowClass, window_builder::WindowBuilder};
+//! # fn f(class: &WindowClass) -> Result<(), triangle_from_scratch_win32::prelude::Win32Error> {
+//! // 800x600 logical pixels to draw in, whatever the monitor's scaling is
+//! let window = unsafe {
+//!     WindowBuilder::new()
+//!         .title("Tools")
+//!         .inner_size([800, 600])
+//!         .resizable(false)
+//!         .tool_window(true)
+//!         .build(class, core::ptr::null_mut())
+//! }?;
+//! # Ok(())
+//! # }
+//! ```
+
+use core::ptr;
+
+use crate::{
+    dpi::{enable_per_monitor_dpi_awareness, frame_insets},
+    geometry::{to_physical, USER_DEFAULT_SCREEN_DPI},
+    get_last_error,
+    handles::{Window, WindowClass},
+    prelude::*,
+    window_handler::WindowHandler,
+};
+
+/// Describes a window to create.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowBuilder {
+    title: String,
+    position: Option<[i32; 2]>,
+    inner_size: [i32; 2],
+    resizable: bool,
+    borderless: bool,
+    topmost: bool,
+    tool_window: bool,
+}
+
+impl Default for WindowBuilder {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            position: None,
+            inner_size: [800, 600],
+            resizable: true,
+            borderless: false,
+            topmost: false,
+            tool_window: false,
+        }
+    }
+}
+
+impl WindowBuilder {
+    /// A resizable 800x600 window with a title bar, at a position chosen by Windows.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// Where the top left corner of the window (including its frame) goes, in physical screen
+    /// coordinates.
+    pub fn position(mut self, position: [i32; 2]) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    /// The size of the window's client area, in logical pixels. It's scaled by the DPI of the
+    /// monitor the window ends up on.
+    pub fn inner_size(mut self, size: [i32; 2]) -> Self {
+        self.inner_size = size;
+        self
+    }
+
+    /// Whether the window can be resized and maximized by the user. Borderless windows never
+    /// can be.
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    /// Whether the window has no frame or title bar at all.
+    pub fn borderless(mut self, borderless: bool) -> Self {
+        self.borderless = borderless;
+        self
+    }
+
+    /// Whether the window stays above all non-topmost windows.
+    pub fn topmost(mut self, topmost: bool) -> Self {
+        self.topmost = topmost;
+        self
+    }
+
+    /// Whether the window is a tool window, with a smaller title bar and no taskbar button.
+    pub fn tool_window(mut self, tool_window: bool) -> Self {
+        self.tool_window = tool_window;
+        self
+    }
+
+    /// The window style and extended window style that the window will be created with.
+    pub fn styles(&self) -> (DWORD, DWORD) {
+        let mut style = WS_CLIPCHILDREN | WS_CLIPSIBLINGS;
+        if self.borderless {
+            style |= WS_POPUP;
+        } else {
+            style |= WS_OVERLAPPED | WS_CAPTION | WS_SYSMENU | WS_MINIMIZEBOX;
+            if self.resizable {
+                style |= WS_THICKFRAME | WS_MAXIMIZEBOX;
+            }
+        }
+
+        let mut ex_style = 0;
+        if self.topmost {
+            ex_style |= WS_EX_TOPMOST;
+        }
+        if self.tool_window {
+            ex_style |= WS_EX_TOOLWINDOW;
+        }
+        (style, ex_style)
+    }
+
+    /// The outer size the window needs at `dpi` for its client area to be
+    /// [`inner_size`](Self::inner_size).
+    pub fn outer_size(&self, dpi: u32) -> Result<[i32; 2], Win32Error> {
+        let (style, ex_style) = self.styles();
+        let insets = frame_insets(style, ex_style, dpi)?;
+        Ok(insets.outer_size(to_physical(self.inner_size, dpi)))
+    }
+
+    /// Creates the window. It isn't shown until [`Window::show`] is called.
+    ///
+    /// This makes the process per-monitor DPI aware first, with
+    /// [`enable_per_monitor_dpi_awareness`].
+    ///
+    /// ## Safety
+    ///
+    /// The class's window procedure must cope with whatever `create_param` is.
+    pub unsafe fn build<'class>(
+        &self,
+        class: &'class WindowClass,
+        create_param: LPVOID,
+    ) -> Result<Window<'class>, Win32Error> {
+        enable_per_monitor_dpi_awareness();
+
+        // The DPI isn't known until the window exists and Windows has decided which monitor it's
+        // on, so create it at the default DPI and then fix its size
+        let (style, ex_style) = self.styles();
+        let window = Window::create(
+            class,
+            &self.title,
+            style,
+            ex_style,
+            self.position,
+            self.outer_size(USER_DEFAULT_SCREEN_DPI)?,
+            create_param,
+        )?;
+
+        let [width, height] = self.outer_size(window.dpi())?;
+        if SetWindowPos(
+            window.hwnd(),
+            ptr::null_mut(),
+            0,
+            0,
+            width,
+            height,
+            SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE,
+        ) == 0
+        {
+            return Err(get_last_error());
+        }
+        Ok(window)
+    }
+
+    /// Creates the window like [`build`](Self::build), with its messages handled by `handler`.
+    ///
+    /// The class's window procedure must be
+    /// [`window_procedure_trampoline`](crate::window_handler::window_procedure_trampoline), as for
+    /// [`Window::create_with_handler`].
+    pub fn build_with_handler<'class>(
+        &self,
+        class: &'class WindowClass,
+        handler: Box<dyn WindowHandler>,
+    ) -> Result<Window<'class>, Win32Error> {
+        // Safety: the create parameter is passed straight through
+        Window::create_with_handler_param(class, handler, |param| unsafe {
+            self.build(class, param)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn styles() {
+        let (style, ex_style) = WindowBuilder::new().styles();
+        assert_eq!(style & WS_OVERLAPPEDWINDOW, WS_OVERLAPPEDWINDOW);
+        assert_eq!(ex_style, 0);
+
+        let (style, _) = WindowBuilder::new().resizable(false).styles();
+        assert_eq!(style & (WS_THICKFRAME | WS_MAXIMIZEBOX), 0);
+        assert_ne!(style & WS_CAPTION, 0);
+
+        let (style, ex_style) = WindowBuilder::new()
+            .borderless(true)
+            .topmost(true)
+            .tool_window(true)
+            .styles();
+        assert_eq!(style & (WS_CAPTION | WS_THICKFRAME), 0);
+        assert_ne!(style & WS_POPUP, 0);
+        assert_eq!(ex_style, WS_EX_TOPMOST | WS_EX_TOOLWINDOW);
+    }
+
+    #[test]
+    fn sizes_by_client_area() {
+        let borderless = WindowBuilder::new().borderless(true).inner_size([640, 480]);
+        assert_eq!(borderless.outer_size(96).unwrap(), [640, 480]);
+        assert_eq!(borderless.outer_size(192).unwrap(), [1280, 960]);
+
+        let framed = WindowBuilder::new().inner_size([640, 480]);
+        let [width, height] = framed.outer_size(96).unwrap();
+        assert!(width > 640 && height > 480);
+    }
+}
@@ -0,0 +1,889 @@
+//! A window procedure that hands messages to a [`WindowHandler`], so application code doesn't have
+//! to deal with `GWLP_USERDATA`, raw pointers or unwinding out of `extern "system"` functions.
+//!
+//! Register a window class with [`window_procedure_trampoline`] as its window procedure, create
+//! windows of that class with [`Window::create_with_handler`], and run [`run_message_loop`] (or
+//! [`run_app_loop`], to render continuously between batches of messages):
+//!
+//! - The handler is owned by the window, and dropped when the window gets `WM_NCDESTROY`.
+//! - Messages are decoded into a [`Message`] and passed to the matching [`WindowHandler`] method by
+//!   [`dispatch`], which doesn't touch Win32 at all and so can be driven by synthetic messages.
+//! - Panics in the handler are caught before they can unwind into Windows, and then re-raised by
+//!   [`resume_pending_panic`] once control is back in Rust (which [`run_message_loop`] does after
+//!   every message). Until then, the window's messages all go to [`DefWindowProcW`].
+
+use core::{any::Any, cell::RefCell, ptr};
+use std::{
+    cell::Cell,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+};
+
+use c_types::CInt;
+use event::Event;
+use keyboard::{Key, KeyEvent, KeyState, KeyboardState, Modifiers};
+
+use crate::{
+    drop_files::take_dropped_files,
+    get_any_message, get_window_userdata,
+    handles::{Window, WindowClass},
+    keymap, peek_any_message, post_quit_message,
+    prelude::*,
+    raw_input::{read_raw_mouse, RawMouseDecoder, ScreenSize},
+    set_window_userdata, translate_message,
+};
+
+/// Handles the messages sent to a window.
+///
+/// Every method has a default, so only the messages you care about need implementing.
+pub trait WindowHandler: Any {
+    /// `WM_CREATE`: the window has been created, but isn't visible yet. Return `false` to destroy
+    /// the window, failing its creation.
+    fn create(&mut self, _hwnd: HWND) -> bool {
+        true
+    }
+
+    /// `WM_PAINT`: (part of) the window needs painting. Return `false` to let
+    /// [`DefWindowProcW`] validate the window without painting anything.
+    fn paint(&mut self, _hwnd: HWND) -> bool {
+        false
+    }
+
+    /// `WM_SIZE`: the size of the window's client area changed.
+    fn resize(&mut self, _hwnd: HWND, _size: Size) {}
+
+    /// `WM_DPICHANGED`: the window's DPI changed, usually because it moved to another monitor.
+    ///
+    /// By the time this is called, the window has already been moved and resized to the rect that
+    /// Windows suggested, and [`resize`](Self::resize) has been called.
+    fn dpi_changed(&mut self, _hwnd: HWND, _dpi: u32) {}
+
+    /// `WM_KEYDOWN`: a key was pressed while Alt wasn't held. `repeat` is set for the auto-repeats
+    /// that come while the key is held down.
+    ///
+    /// This is called after [`key`](Self::key), for the same press.
+    fn key_down(&mut self, _hwnd: HWND, _virtual_key: u32, _repeat: bool) {}
+
+    /// `WM_KEYDOWN`, `WM_KEYUP`, `WM_SYSKEYDOWN` or `WM_SYSKEYUP`: a key was pressed or released.
+    fn key(&mut self, _hwnd: HWND, _event: &KeyEvent) {}
+
+    /// `WM_CHAR`: text was typed. Control characters (like the ones backspace and enter type)
+    /// don't count, since they're keys rather than text.
+    fn text(&mut self, _hwnd: HWND, _text: char) {}
+
+    /// `WM_INPUT` from the mouse: it moved by `delta`, which isn't accelerated or stopped by the
+    /// edges of the screen. This only comes after [`register_raw_mouse`], e.g. from a
+    /// [`CursorGrab`](crate::cursor::CursorGrab) that isn't in the normal mode.
+    ///
+    /// [`register_raw_mouse`]: crate::raw_input::register_raw_mouse
+    fn raw_mouse_motion(&mut self, _hwnd: HWND, _delta: [i32; 2]) {}
+
+    /// An [`Event`] that every platform reports the same way. On Win32 these are:
+    ///
+    /// - [`Event::FileDropped`], from `WM_DROPFILES`, once for each of the files dropped together.
+    ///   These only come after [`Window::accept_files`](crate::handles::Window::accept_files).
+    fn event(&mut self, _hwnd: HWND, _event: Event) {}
+
+    /// `WM_ACTIVATE`: the window was activated or deactivated.
+    fn activate(&mut self, _hwnd: HWND, _active: bool) {}
+
+    /// `WM_CLOSE`: the user asked to close the window, e.g. by clicking its close button.
+    ///
+    /// This quits the message loop by default, so that whoever owns the [`Window`] can drop it.
+    fn close(&mut self, _hwnd: HWND) {
+        post_quit_message(0);
+    }
+
+    /// `WM_DESTROY`: the window is being destroyed. The handler is dropped soon after.
+    fn destroy(&mut self, _hwnd: HWND) {}
+
+    /// Any other message. Return `None` to pass it on to [`DefWindowProcW`].
+    fn other(
+        &mut self,
+        _hwnd: HWND,
+        _msg: UINT,
+        _wparam: WPARAM,
+        _lparam: LPARAM,
+    ) -> Option<LRESULT> {
+        None
+    }
+}
+
+/// The new size of a window, from `WM_SIZE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Size {
+    /// The width of the client area.
+    pub width: u16,
+    /// The height of the client area.
+    pub height: u16,
+    /// One of the `SIZE_*` constants, e.g. [`SIZE_MINIMIZED`].
+    pub kind: WPARAM,
+}
+
+/// A decoded window message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    Create,
+    Paint,
+    Size(Size),
+    DpiChanged {
+        dpi: u32,
+    },
+    /// A key was pressed or released. [`decode`](Self::decode) only knows the logical key for
+    /// keys that mean the same in every layout, and nothing about modifiers, which the window
+    /// procedure fills in.
+    Key {
+        virtual_key: u32,
+        /// Whether this is `WM_SYSKEYDOWN` or `WM_SYSKEYUP`.
+        system: bool,
+        event: KeyEvent,
+    },
+    /// A character from `WM_CHAR`. [`decode`](Self::decode) leaves surrogates as
+    /// [`Other`](Self::Other), since the window procedure has to pair them up.
+    Text(char),
+    /// Decoded from `WM_INPUT` by the window procedure, since it has to be read with
+    /// `GetRawInputData`. [`decode`](Self::decode) leaves `WM_INPUT` as [`Other`](Self::Other).
+    RawMouseMotion {
+        delta: [i32; 2],
+    },
+    /// Read from `WM_DROPFILES` by the window procedure, since the files have to be queried with
+    /// `DragQueryFileW`. [`decode`](Self::decode) leaves `WM_DROPFILES` as [`Other`](Self::Other).
+    FilesDropped(Vec<PathBuf>),
+    Activate {
+        active: bool,
+    },
+    Close,
+    Destroy,
+    /// A message that isn't decoded (yet), with its parameters untouched.
+    Other {
+        msg: UINT,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    },
+}
+
+impl Message {
+    pub fn decode(msg: UINT, wparam: WPARAM, lparam: LPARAM) -> Self {
+        match msg {
+            WM_CREATE => Self::Create,
+            WM_PAINT => Self::Paint,
+            WM_SIZE => Self::Size(Size {
+                width: (lparam & 0xFFFF) as u16,
+                height: ((lparam >> 16) & 0xFFFF) as u16,
+                kind: wparam,
+            }),
+            WM_DPICHANGED => Self::DpiChanged {
+                dpi: (wparam & 0xFFFF) as u32,
+            },
+            WM_KEYDOWN | WM_KEYUP | WM_SYSKEYDOWN | WM_SYSKEYUP => {
+                let virtual_key = wparam as u32;
+                let physical_key = keymap::physical_key(virtual_key, lparam);
+                let state = if matches!(msg, WM_KEYDOWN | WM_SYSKEYDOWN) {
+                    KeyState::Pressed
+                } else {
+                    KeyState::Released
+                };
+                Self::Key {
+                    virtual_key,
+                    system: matches!(msg, WM_SYSKEYDOWN | WM_SYSKEYUP),
+                    event: KeyEvent {
+                        physical_key,
+                        logical_key: physical_key
+                            .named_key()
+                            .map_or(Key::Unidentified, Key::Named),
+                        state,
+                        repeat: state.is_pressed() && lparam & (1 << 30) != 0,
+                        modifiers: Modifiers::NONE,
+                    },
+                }
+            }
+            WM_CHAR => match char::from_u32(wparam as u32) {
+                Some(c) if !c.is_control() => Self::Text(c),
+                _ => Self::Other {
+                    msg,
+                    wparam,
+                    lparam,
+                },
+            },
+            WM_ACTIVATE => Self::Activate {
+                active: wparam & 0xFFFF != WA_INACTIVE,
+            },
+            WM_CLOSE => Self::Close,
+            WM_DESTROY => Self::Destroy,
+            msg => Self::Other {
+                msg,
+                wparam,
+                lparam,
+            },
+        }
+    }
+}
+
+/// Passes a message to the matching method of `handler`.
+///
+/// **Returns:** What the window procedure should return, or `None` if the message should be
+/// passed on to [`DefWindowProcW`].
+pub fn dispatch(handler: &mut dyn WindowHandler, hwnd: HWND, message: Message) -> Option<LRESULT> {
+    match message {
+        Message::Create => Some(if handler.create(hwnd) { 0 } else { -1 }),
+        Message::Paint => handler.paint(hwnd).then_some(0),
+        Message::Size(size) => {
+            handler.resize(hwnd, size);
+            Some(0)
+        }
+        Message::DpiChanged { dpi } => {
+            handler.dpi_changed(hwnd, dpi);
+            Some(0)
+        }
+        Message::Key {
+            virtual_key,
+            system,
+            event,
+        } => {
+            handler.key(hwnd, &event);
+            if system {
+                // Windows handles Alt+F4 and the window menu in `DefWindowProcW`
+                return None;
+            }
+            if event.state.is_pressed() {
+                handler.key_down(hwnd, virtual_key, event.repeat);
+            }
+            Some(0)
+        }
+        Message::Text(text) => {
+            handler.text(hwnd, text);
+            Some(0)
+        }
+        Message::RawMouseMotion { delta } => {
+            handler.raw_mouse_motion(hwnd, delta);
+            // Windows frees the input in `DefWindowProcW`
+            None
+        }
+        Message::FilesDropped(paths) => {
+            for path in paths {
+                handler.event(hwnd, Event::FileDropped(path));
+            }
+            Some(0)
+        }
+        Message::Activate { active } => {
+            handler.activate(hwnd, active);
+            Some(0)
+        }
+        Message::Close => {
+            handler.close(hwnd);
+            Some(0)
+        }
+        Message::Destroy => {
+            handler.destroy(hwnd);
+            Some(0)
+        }
+        Message::Other {
+            msg,
+            wparam,
+            lparam,
+        } => handler.other(hwnd, msg, wparam, lparam),
+    }
+}
+
+/// What's stored in a window's `GWLP_USERDATA`.
+///
+/// The handler is behind a `RefCell`, since handlers can cause messages to be sent to their own
+/// window. Those nested messages go to [`DefWindowProcW`] instead.
+struct HandlerState {
+    handler: RefCell<Box<dyn WindowHandler>>,
+    raw_mouse: Cell<RawMouseDecoder>,
+    keyboard: Cell<KeyboardState>,
+    /// The first half of a surrogate pair from `WM_CHAR`, waiting for the second.
+    high_surrogate: Cell<Option<u16>>,
+}
+
+thread_local! {
+    /// A panic caught in a window procedure on this thread, waiting to be resumed.
+    static PENDING_PANIC: Cell<Option<Box<dyn Any + Send>>> = const { Cell::new(None) };
+}
+
+/// Runs `f`, catching any panic and keeping it for [`resume_pending_panic`].
+fn catch_panic<R>(f: impl FnOnce() -> R) -> Option<R> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(r) => Some(r),
+        Err(payload) => {
+            // the first panic is the interesting one
+            PENDING_PANIC.with(|pending| {
+                let first = pending.take().unwrap_or(payload);
+                pending.set(Some(first));
+            });
+            None
+        }
+    }
+}
+
+fn panic_pending() -> bool {
+    PENDING_PANIC.with(|pending| {
+        let payload = pending.take();
+        let is_pending = payload.is_some();
+        pending.set(payload);
+        is_pending
+    })
+}
+
+/// Re-raises a panic that was caught in [`window_procedure_trampoline`] on this thread, if there
+/// is one.
+///
+/// [`run_message_loop`] calls this after every message. Call it yourself after anything else that
+/// sends messages, like creating or destroying a window outside of the message loop.
+pub fn resume_pending_panic() {
+    if let Some(payload) = PENDING_PANIC.with(Cell::take) {
+        panic::resume_unwind(payload);
+    }
+}
+
+/// The window procedure for windows created with [`Window::create_with_handler`].
+///
+/// Windows of a class using this procedure must only be created with
+/// [`Window::create_with_handler`], since the trampoline relies on what it passes as the create
+/// parameter.
+///
+/// ## Safety
+///
+/// This is only meant to be called by Windows.
+pub unsafe extern "system" fn window_procedure_trampoline(
+    hwnd: HWND,
+    msg: UINT,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if msg == WM_NCCREATE {
+        // Take the handler from `create_with_handler`. If it isn't there, the window was created
+        // some other way and won't have a handler.
+        let createstruct: LPCREATESTRUCTW = lparam as _;
+        let slot = (*createstruct).lpCreateParams as *mut Option<Box<HandlerState>>;
+        if let Some(state) = slot.as_mut().and_then(Option::take) {
+            let state = Box::into_raw(state);
+            if set_window_userdata(hwnd, state).is_err() {
+                drop(Box::from_raw(state));
+                return 0;
+            }
+        }
+        return DefWindowProcW(hwnd, msg, wparam, lparam);
+    }
+
+    let state = match get_window_userdata::<HandlerState>(hwnd) {
+        Ok(state) if !state.is_null() => state,
+        _ => return DefWindowProcW(hwnd, msg, wparam, lparam),
+    };
+
+    if msg == WM_NCDESTROY {
+        // This is the last message the window gets, so the handler can go
+        let _ = set_window_userdata::<HandlerState>(hwnd, ptr::null_mut());
+        let state = Box::from_raw(state);
+        catch_panic(move || drop(state));
+        return DefWindowProcW(hwnd, msg, wparam, lparam);
+    }
+
+    if msg == WM_DPICHANGED {
+        // Move to where Windows suggests, before the handler hears about it
+        let suggested = *(lparam as *const RECT);
+        SetWindowPos(
+            hwnd,
+            ptr::null_mut(),
+            suggested.left,
+            suggested.top,
+            suggested.right - suggested.left,
+            suggested.bottom - suggested.top,
+            SWP_NOZORDER | SWP_NOACTIVATE,
+        );
+    }
+
+    let state = &*state;
+    let message = match msg {
+        WM_INPUT => match read_raw_mouse(lparam) {
+            Ok(Some(mouse)) => {
+                let mut decoder = state.raw_mouse.get();
+                let delta = decoder.delta(&mouse, ScreenSize::current());
+                state.raw_mouse.set(decoder);
+                Message::RawMouseMotion { delta }
+            }
+            _ => Message::decode(msg, wparam, lparam),
+        },
+        WM_DROPFILES => Message::FilesDropped(take_dropped_files(wparam as HDROP)),
+        WM_CHAR => match (state.high_surrogate.take(), wparam as u16) {
+            (None, high @ 0xD800..=0xDBFF) => {
+                state.high_surrogate.set(Some(high));
+                return 0;
+            }
+            (Some(high), low @ 0xDC00..=0xDFFF) => match char::decode_utf16([high, low]).next() {
+                Some(Ok(c)) => Message::Text(c),
+                _ => Message::decode(msg, wparam, lparam),
+            },
+            _ => Message::decode(msg, wparam, lparam),
+        },
+        _ => match Message::decode(msg, wparam, lparam) {
+            Message::Key {
+                virtual_key,
+                system,
+                mut event,
+            } => {
+                let mut keyboard = state.keyboard.get();
+                keyboard.update(event.physical_key, event.state);
+                event.modifiers = keyboard.modifiers();
+                event.logical_key = keymap::logical_key(virtual_key, lparam, event.physical_key);
+                state.keyboard.set(keyboard);
+                Message::Key {
+                    virtual_key,
+                    system,
+                    event,
+                }
+            }
+            Message::Activate { active } => {
+                let mut keyboard = state.keyboard.get();
+                if active {
+                    let (caps_lock, num_lock) = keymap::lock_state();
+                    keyboard.set_locks(caps_lock, num_lock);
+                } else {
+                    // keys released in other windows never come back here
+                    keyboard.release_all();
+                }
+                state.keyboard.set(keyboard);
+                Message::Activate { active }
+            }
+            message => message,
+        },
+    };
+
+    let result = match state.handler.try_borrow_mut() {
+        Ok(mut handler) if !panic_pending() => {
+            catch_panic(|| dispatch(&mut **handler, hwnd, message)).flatten()
+        }
+        _ => None,
+    };
+    result.unwrap_or_else(|| DefWindowProcW(hwnd, msg, wparam, lparam))
+}
+
+impl<'class> Window<'class> {
+    /// Creates an overlapped window like [`Window::create_app`], whose messages are handled by
+    /// `handler`.
+    ///
+    /// The class's window procedure must be [`window_procedure_trampoline`], or this fails with an
+    /// application error (without creating a window).
+    ///
+    /// Panics from the handler's `create` are re-raised before this returns.
+    pub fn create_with_handler(
+        class: &'class WindowClass,
+        window_name: &str,
+        position: Option<[i32; 2]>,
+        size: [i32; 2],
+        handler: Box<dyn WindowHandler>,
+    ) -> Result<Self, Win32Error> {
+        // Safety: the create parameter is passed straight through
+        Self::create_with_handler_param(class, handler, |param| unsafe {
+            Self::create_app(class, window_name, position, size, param)
+        })
+    }
+
+    /// Creates a window with `create`, which must pass the create parameter it's given on to
+    /// `CreateWindowExW`, and hands its messages to `handler`.
+    pub(crate) fn create_with_handler_param(
+        class: &'class WindowClass,
+        handler: Box<dyn WindowHandler>,
+        create: impl FnOnce(LPVOID) -> Result<Self, Win32Error>,
+    ) -> Result<Self, Win32Error> {
+        if class.window_procedure().map(|f| f as usize)
+            != Some(window_procedure_trampoline as *const () as usize)
+        {
+            return Err(Win32Error(Win32Error::APPLICATION_ERROR_BIT));
+        }
+
+        // The trampoline takes the state out of the slot when it gets `WM_NCCREATE`, so if creation
+        // fails before that it's dropped here instead.
+        let mut slot = Some(Box::new(HandlerState {
+            handler: RefCell::new(handler),
+            raw_mouse: Cell::new(RawMouseDecoder::new()),
+            keyboard: Cell::new(KeyboardState::new()),
+            high_surrogate: Cell::new(None),
+        }));
+        // The trampoline knows what the create parameter is, and the slot outlives the call
+        let window = create(ptr::addr_of_mut!(slot).cast());
+        resume_pending_panic();
+        window
+    }
+}
+
+/// Runs `f` on the handler of a window created with [`Window::create_with_handler`].
+///
+/// Returns `None` if the window doesn't have a handler of type `H`, or if the handler is busy
+/// (because it's handling the message that this is being called from).
+pub fn with_handler<H: WindowHandler, R>(
+    window: &Window<'_>,
+    f: impl FnOnce(&mut H) -> R,
+) -> Option<R> {
+    // Safety: the window is valid as long as it's borrowed, and if its window procedure is the
+    // trampoline then its userdata is either null or a `HandlerState`
+    unsafe {
+        let window_procedure = GetWindowLongPtrW(window.hwnd(), GWLP_WNDPROC) as usize;
+        if window_procedure != window_procedure_trampoline as *const () as usize {
+            return None;
+        }
+        let state = get_window_userdata::<HandlerState>(window.hwnd()).ok()?;
+        let mut handler = state.as_ref()?.handler.try_borrow_mut().ok()?;
+        let handler: &mut dyn Any = &mut **handler;
+        handler.downcast_mut().map(f)
+    }
+}
+
+/// Dispatches messages until `WM_QUIT`, re-raising any panics from window handlers as it goes.
+///
+/// **Returns:** The exit code passed to [`post_quit_message`].
+pub fn run_message_loop() -> Result<CInt, Win32Error> {
+    resume_pending_panic();
+    loop {
+        let msg = get_any_message()?;
+        if msg.message == WM_QUIT {
+            return Ok(msg.wParam as CInt);
+        }
+
+        dispatch_queued(&msg);
+    }
+}
+
+/// Dispatches every message that's already queued, without waiting for more, re-raising any panics
+/// from window handlers as it goes.
+///
+/// **Returns:** The exit code passed to [`post_quit_message`] if `WM_QUIT` came up, in which case
+/// the messages after it are left in the queue.
+pub fn pump_messages() -> Option<CInt> {
+    resume_pending_panic();
+    while let Some(msg) = peek_any_message() {
+        if msg.message == WM_QUIT {
+            return Some(msg.wParam as CInt);
+        }
+        dispatch_queued(&msg);
+    }
+    None
+}
+
+/// Runs `frame` over and over until `WM_QUIT`, dispatching whatever messages have been queued
+/// before each call.
+///
+/// This is [`run_message_loop`] for things that render continuously: `frame` is called even when
+/// there aren't any messages, so it should wait for the next frame itself (or be throttled by
+/// vsync). It isn't called while Windows is running a modal loop, like when the window is being
+/// resized or moved, since [`pump_messages`] doesn't return until that's over.
+///
+/// **Returns:** The exit code passed to [`post_quit_message`].
+pub fn run_app_loop(mut frame: impl FnMut()) -> CInt {
+    loop {
+        if let Some(exit_code) = pump_messages() {
+            return exit_code;
+        }
+        frame();
+    }
+}
+
+fn dispatch_queued(msg: &MSG) {
+    translate_message(msg);
+    // Safety: the message came straight from the queue
+    unsafe { DispatchMessageW(msg) };
+    resume_pending_panic();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keyboard::{KeyCode, NamedKey};
+    use std::rc::Rc;
+
+    /// Records the messages it gets.
+    #[derive(Default)]
+    struct Recorder {
+        log: Rc<RefCell<Vec<String>>>,
+        panic_on_create: bool,
+    }
+
+    impl WindowHandler for Recorder {
+        fn create(&mut self, _hwnd: HWND) -> bool {
+            self.log.borrow_mut().push("create".into());
+            if self.panic_on_create {
+                panic!("oh no");
+            }
+            true
+        }
+
+        fn resize(&mut self, _hwnd: HWND, size: Size) {
+            self.log
+                .borrow_mut()
+                .push(format!("resize {}x{}", size.width, size.height));
+        }
+
+        fn dpi_changed(&mut self, _hwnd: HWND, dpi: u32) {
+            self.log.borrow_mut().push(format!("dpi {dpi}"));
+        }
+
+        fn key_down(&mut self, _hwnd: HWND, virtual_key: u32, repeat: bool) {
+            self.log
+                .borrow_mut()
+                .push(format!("key {virtual_key:#X} {repeat}"));
+        }
+
+        fn key(&mut self, _hwnd: HWND, event: &KeyEvent) {
+            self.log
+                .borrow_mut()
+                .push(format!("{:?} {:?}", event.physical_key, event.state));
+        }
+
+        fn text(&mut self, _hwnd: HWND, text: char) {
+            self.log.borrow_mut().push(format!("text {text}"));
+        }
+
+        fn raw_mouse_motion(&mut self, _hwnd: HWND, [dx, dy]: [i32; 2]) {
+            self.log.borrow_mut().push(format!("motion {dx},{dy}"));
+        }
+
+        fn event(&mut self, _hwnd: HWND, event: Event) {
+            match event {
+                Event::FileDropped(path) => self
+                    .log
+                    .borrow_mut()
+                    .push(format!("dropped {}", path.display())),
+            }
+        }
+
+        fn activate(&mut self, _hwnd: HWND, active: bool) {
+            self.log.borrow_mut().push(format!("activate {active}"));
+        }
+
+        fn close(&mut self, _hwnd: HWND) {
+            self.log.borrow_mut().push("close".into());
+        }
+
+        fn destroy(&mut self, _hwnd: HWND) {
+            self.log.borrow_mut().push("destroy".into());
+        }
+    }
+
+    impl Drop for Recorder {
+        fn drop(&mut self) {
+            self.log.borrow_mut().push("drop".into());
+        }
+    }
+
+    mod dispatch {
+        use super::*;
+
+        #[test]
+        fn decodes_sizes() {
+            assert_eq!(
+                Message::decode(WM_SIZE, SIZE_MAXIMIZED, (600 << 16) | 800),
+                Message::Size(Size {
+                    width: 800,
+                    height: 600,
+                    kind: SIZE_MAXIMIZED
+                })
+            );
+            assert_eq!(
+                Message::decode(0x1234, 1, 2),
+                Message::Other {
+                    msg: 0x1234,
+                    wparam: 1,
+                    lparam: 2
+                }
+            );
+        }
+
+        #[test]
+        fn decodes_keys() {
+            // the left arrow, which is an extended key, held down
+            let Message::Key {
+                virtual_key,
+                system,
+                event,
+            } = Message::decode(WM_KEYDOWN, 0x25, (1 << 30) | 0x014B_0001)
+            else {
+                panic!("not a key");
+            };
+            assert_eq!((virtual_key, system), (0x25, false));
+            assert_eq!(event.physical_key, KeyCode::ArrowLeft);
+            assert_eq!(event.logical_key, Key::Named(NamedKey::ArrowLeft));
+            assert!(event.repeat);
+
+            // releases are never repeats, even though their bit 30 is always set
+            let Message::Key { event, .. } =
+                Message::decode(WM_KEYUP, 0x41, (3 << 30) | 0x001E_0001)
+            else {
+                panic!("not a key");
+            };
+            assert_eq!(event.physical_key, KeyCode::KeyA);
+            assert_eq!(event.state, KeyState::Released);
+            assert_eq!(event.logical_key, Key::Unidentified);
+            assert!(!event.repeat);
+
+            // control characters aren't text, and surrogates need pairing
+            assert_eq!(Message::decode(WM_CHAR, 0x61, 1), Message::Text('a'));
+            assert!(matches!(
+                Message::decode(WM_CHAR, 0x08, 1),
+                Message::Other { .. }
+            ));
+            assert!(matches!(
+                Message::decode(WM_CHAR, 0xD83D, 1),
+                Message::Other { .. }
+            ));
+        }
+
+        #[test]
+        fn synthetic_messages() {
+            let mut recorder = Recorder::default();
+            let log = recorder.log.clone();
+            let hwnd = ptr::null_mut();
+
+            for (msg, wparam, lparam) in [
+                (WM_CREATE, 0, 0),
+                (WM_SIZE, SIZE_RESTORED, (2 << 16) | 3),
+                (WM_DPICHANGED, (144 << 16) | 144, 0),
+                (WM_KEYDOWN, VK_F11 as WPARAM, 0x0057_0001),
+                (WM_KEYDOWN, VK_F11 as WPARAM, (1 << 30) | 0x0057_0001),
+                (WM_KEYUP, VK_F11 as WPARAM, (3 << 30) | 0x0057_0001),
+                (WM_CHAR, 0xE9, 0x0008_0001),
+                (WM_ACTIVATE, WA_INACTIVE, 0),
+                // `WA_CLICKACTIVE`, with the minimized flag in the high word
+                (WM_ACTIVATE, (1 << 16) | 2, 0),
+                (WM_CLOSE, 0, 0),
+                (WM_DESTROY, 0, 0),
+            ] {
+                let result = dispatch(&mut recorder, hwnd, Message::decode(msg, wparam, lparam));
+                assert_eq!(result, Some(0));
+            }
+            // unhandled messages go to `DefWindowProcW`, and so do raw input so it can be freed and
+            // system keys so Alt+F4 works
+            assert_eq!(dispatch(&mut recorder, hwnd, Message::Paint), None);
+            assert_eq!(
+                dispatch(
+                    &mut recorder,
+                    hwnd,
+                    Message::decode(WM_SYSKEYDOWN, 0x73, (1 << 29) | 0x003E_0001)
+                ),
+                None
+            );
+            assert_eq!(
+                dispatch(
+                    &mut recorder,
+                    hwnd,
+                    Message::RawMouseMotion { delta: [-4, 7] }
+                ),
+                None
+            );
+            assert_eq!(
+                dispatch(
+                    &mut recorder,
+                    hwnd,
+                    Message::FilesDropped(vec!["a.obj".into(), "b.png".into()])
+                ),
+                Some(0)
+            );
+
+            assert_eq!(
+                *log.borrow(),
+                [
+                    "create",
+                    "resize 3x2",
+                    "dpi 144",
+                    "F11 Pressed",
+                    "key 0x7A false",
+                    "F11 Pressed",
+                    "key 0x7A true",
+                    "F11 Released",
+                    "text é",
+                    "activate false",
+                    "activate true",
+                    "close",
+                    "destroy",
+                    "F4 Pressed",
+                    "motion -4,7",
+                    "dropped a.obj",
+                    "dropped b.png"
+                ]
+            );
+        }
+    }
+
+    mod trampoline {
+        use super::*;
+        use crate::get_process_handle;
+
+        fn register(name: &str) -> WindowClass {
+            let name_wn = crate::str_util::wide_null(name);
+            let wc = WNDCLASSW {
+                lpfnWndProc: Some(window_procedure_trampoline),
+                hInstance: get_process_handle(),
+                lpszClassName: name_wn.as_ptr(),
+                ..Default::default()
+            };
+            unsafe { WindowClass::register(&wc) }.unwrap()
+        }
+
+        #[test]
+        fn owns_the_handler() {
+            let class = register("triangle-from-scratch trampoline test");
+            let recorder = Recorder::default();
+            let log = recorder.log.clone();
+
+            let window =
+                Window::create_with_handler(&class, "test", None, [1, 1], Box::new(recorder))
+                    .unwrap();
+            assert_eq!(
+                with_handler(&window, |r: &mut Recorder| r.panic_on_create),
+                Some(false)
+            );
+            drop(window);
+
+            let log = log.borrow();
+            assert_eq!(log.first().map(String::as_str), Some("create"));
+            assert!(log.ends_with(&["destroy".into(), "drop".into()]), "{log:?}");
+        }
+
+        #[test]
+        fn panics_are_resumed() {
+            let class = register("triangle-from-scratch trampoline panic test");
+            let recorder = Recorder {
+                log: Default::default(),
+                panic_on_create: true,
+            };
+
+            let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+                Window::create_with_handler(&class, "test", None, [1, 1], Box::new(recorder))
+            }))
+            .unwrap_err();
+            assert_eq!(payload.downcast_ref::<&str>(), Some(&"oh no"));
+        }
+
+        #[test]
+        fn other_window_procedures_are_refused() {
+            let class = crate::handles::register_hidden_class(
+                "triangle-from-scratch trampoline refusal test",
+            )
+            .unwrap();
+            assert!(Window::create_with_handler(
+                &class,
+                "test",
+                None,
+                [1, 1],
+                Box::<Recorder>::default()
+            )
+            .is_err());
+        }
+
+        #[test]
+        fn app_loop_stops_at_quit() {
+            post_quit_message(3);
+            assert_eq!(pump_messages(), Some(3));
+            assert_eq!(pump_messages(), None);
+
+            let mut frames = 0;
+            let exit_code = run_app_loop(|| {
+                frames += 1;
+                if frames == 2 {
+                    post_quit_message(7);
+                }
+            });
+            assert_eq!((exit_code, frames), (7, 2));
+        }
+    }
+}
@@ -0,0 +1,482 @@
+//! Copying and pasting text through the `CLIPBOARD` selection, following the
+//! [ICCCM](https://x.org/releases/X11R7.7/doc/xorg-docs/icccm/icccm.html#Peer_to_Peer_Communication_by_Means_of_Selections).
+//!
+//! X11 has no clipboard that holds anything. Copying makes one of the copier's windows the owner
+//! of the `CLIPBOARD` selection, and pasting asks the owner to convert the selection to text and
+//! put it in a property on the paster's window. So copied text is only there while the client
+//! that copied it is running and handing its events to [`Clipboard::handle_event`].
+//!
+//! Text goes as `UTF8_STRING`. Text that's too big for one request goes in pieces, with the
+//! `INCR` protocol.
+
+use core::time::Duration;
+use std::{io, time::Instant};
+
+use crate::{
+    connection::{Connection, Error},
+    protocol::{
+        self, atoms, event_masks, window_class, Atom, CreateWindow, Event, Property, PropertyMode,
+        SelectionRequest, Timestamp, Window, WindowAttributes, CURRENT_TIME, NONE,
+    },
+};
+
+/// The property that pasted text is put in, on the [`Clipboard`]'s window.
+const PROPERTY: &str = "_TRIANGLE_FROM_SCRATCH_SELECTION";
+
+/// How long to wait for the server to say what time it is.
+const SERVER_TIME_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy)]
+struct Atoms {
+    property: Atom,
+    targets: Atom,
+    timestamp: Atom,
+    utf8_string: Atom,
+    incr: Atom,
+}
+
+/// Text being sent to a requestor in pieces. The next piece is sent each time the requestor
+/// deletes the property to say it has read the last one.
+#[derive(Debug)]
+struct Transfer {
+    requestor: Window,
+    property: Atom,
+    data: Vec<u8>,
+    sent: usize,
+}
+
+/// Copies text to, and pastes it from, a selection (usually `CLIPBOARD`), with a hidden window of
+/// its own to own the selection and to receive pasted text on.
+///
+/// Hand every event to [`handle_event`](Self::handle_event) so that other clients can paste
+/// what's been copied.
+#[derive(Debug)]
+pub struct Clipboard {
+    window: Window,
+    selection: Atom,
+    atoms: Atoms,
+    /// What was copied, and when, while this client owns the selection.
+    owned: Option<(String, Timestamp)>,
+    transfers: Vec<Transfer>,
+}
+
+impl Clipboard {
+    /// The `CLIPBOARD` selection, which is what copy and paste use.
+    pub fn new(conn: &mut Connection) -> Result<Self, Error> {
+        Self::for_selection(conn, "CLIPBOARD")
+    }
+
+    /// Another selection, like `PRIMARY` (the text that's highlighted, which a middle click
+    /// pastes).
+    pub fn for_selection(conn: &mut Connection, selection: &str) -> Result<Self, Error> {
+        Ok(Self {
+            window: create_requestor_window(conn)?,
+            selection: conn.intern_atom(selection)?,
+            atoms: Atoms {
+                property: conn.intern_atom(PROPERTY)?,
+                targets: conn.intern_atom("TARGETS")?,
+                timestamp: conn.intern_atom("TIMESTAMP")?,
+                utf8_string: conn.intern_atom("UTF8_STRING")?,
+                incr: conn.intern_atom("INCR")?,
+            },
+            owned: None,
+            transfers: Vec::new(),
+        })
+    }
+
+    /// The hidden window that owns the selection.
+    pub fn window(&self) -> Window {
+        self.window
+    }
+
+    /// Whether this client still owns the selection, i.e. nobody has copied anything since
+    /// [`set_text`](Self::set_text).
+    pub fn owns(&self) -> bool {
+        self.owned.is_some()
+    }
+
+    /// Copies text, by taking ownership of the selection.
+    ///
+    /// **Returns:** Whether ownership was taken. It isn't if another client took it at the same
+    /// time, later by the server's clock.
+    pub fn set_text(&mut self, conn: &mut Connection, text: &str) -> Result<bool, Error> {
+        let time = self.server_time(conn)?;
+        conn.request_checked(&protocol::set_selection_owner(
+            self.window,
+            self.selection,
+            time,
+        ))?;
+        let reply = conn.request_with_reply(&protocol::get_selection_owner(self.selection))?;
+        let owned = protocol::parse_get_selection_owner(&reply)? == self.window;
+        self.owned = owned.then(|| (text.to_string(), time));
+        Ok(owned)
+    }
+
+    /// Pastes text, waiting up to `timeout` for the owner to send it. Events that arrive in the
+    /// meantime are kept for [`Connection::poll_event`] and similar.
+    ///
+    /// Text that isn't valid UTF-8 has the invalid parts replaced with `U+FFFD`. Owners that
+    /// can't convert to `UTF8_STRING` are asked for Latin-1 `STRING`s instead.
+    ///
+    /// **Returns:** The text, or `None` if nobody owns the selection, the owner couldn't convert
+    /// it to text, or it didn't answer in time.
+    pub fn text(
+        &mut self,
+        conn: &mut Connection,
+        timeout: Duration,
+    ) -> Result<Option<String>, Error> {
+        if let Some((text, _)) = &self.owned {
+            return Ok(Some(text.clone()));
+        }
+        let deadline = Instant::now() + timeout;
+        if let Some(bytes) = self.receive(conn, self.atoms.utf8_string, deadline)? {
+            return Ok(Some(String::from_utf8_lossy(&bytes).into_owned()));
+        }
+        let latin1 = self.receive(conn, atoms::STRING, deadline)?;
+        Ok(latin1.map(|bytes| bytes.into_iter().map(char::from).collect()))
+    }
+
+    /// Answers other clients' requests for the selection, and notices when another client takes
+    /// it.
+    ///
+    /// **Returns:** Whether the event was for the clipboard. Other events are left alone.
+    pub fn handle_event(&mut self, conn: &mut Connection, event: &Event) -> Result<bool, Error> {
+        match *event {
+            Event::SelectionClear {
+                owner, selection, ..
+            } if owner == self.window && selection == self.selection => {
+                self.owned = None;
+                Ok(true)
+            }
+            Event::SelectionRequest(request)
+                if request.owner == self.window && request.selection == self.selection =>
+            {
+                let property = match self.convert(conn, &request) {
+                    Ok(property) => property,
+                    // the requestor went away before it could be answered
+                    Err(Error::Protocol(e)) if e.name() == "BadWindow" => return Ok(true),
+                    Err(e) => return Err(e),
+                };
+                let notify = protocol::selection_notify(
+                    request.time,
+                    request.requestor,
+                    request.selection,
+                    request.target,
+                    property,
+                );
+                ignore_bad_window(conn.request_checked(&protocol::send_event(
+                    false,
+                    request.requestor,
+                    0,
+                    &notify,
+                )))?;
+                Ok(true)
+            }
+            Event::PropertyNotify {
+                window,
+                atom,
+                deleted,
+                ..
+            } => {
+                let Some(i) = self
+                    .transfers
+                    .iter()
+                    .position(|t| t.requestor == window && t.property == atom)
+                else {
+                    return Ok(false);
+                };
+                if deleted {
+                    self.send_next_piece(conn, i)?;
+                }
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Gives up the selection (if it's still owned) and destroys the hidden window.
+    pub fn destroy(self, conn: &mut Connection) -> Result<(), Error> {
+        conn.request_checked(&protocol::destroy_window(self.window))
+    }
+
+    /// Puts the selection into the requestor's property, as the request's target.
+    ///
+    /// **Returns:** The property it was put in, or [`NONE`] if it couldn't be converted.
+    fn convert(
+        &mut self,
+        conn: &mut Connection,
+        request: &SelectionRequest,
+    ) -> Result<Atom, Error> {
+        let Some((text, time)) = &self.owned else {
+            return Ok(NONE);
+        };
+        // requests from before this client took the selection are for the previous owner
+        if request.time != CURRENT_TIME && request.time < *time {
+            return Ok(NONE);
+        }
+        let property = match request.property {
+            NONE => request.target,
+            property => property,
+        };
+        let (requestor, target) = (request.requestor, request.target);
+        let atoms = self.atoms;
+
+        if target == atoms.targets {
+            let targets = [atoms.targets, atoms.timestamp, atoms.utf8_string];
+            conn.request_checked(&protocol::change_property32(
+                PropertyMode::Replace,
+                requestor,
+                property,
+                atoms::ATOM,
+                &targets,
+            ))?;
+        } else if target == atoms.timestamp {
+            conn.request_checked(&protocol::change_property32(
+                PropertyMode::Replace,
+                requestor,
+                property,
+                atoms::INTEGER,
+                &[*time],
+            ))?;
+        } else if target == atoms.utf8_string {
+            let data = text.as_bytes().to_vec();
+            if data.len() <= max_piece_len(conn) {
+                conn.request_checked(&protocol::change_property(
+                    PropertyMode::Replace,
+                    requestor,
+                    property,
+                    atoms.utf8_string,
+                    8,
+                    &data,
+                ))?;
+            } else {
+                // the requestor deletes the property to ask for each piece, so this client needs
+                // to hear about its property changes
+                conn.request_checked(&protocol::change_window_attributes(
+                    requestor,
+                    &WindowAttributes {
+                        event_mask: Some(event_masks::PROPERTY_CHANGE),
+                        ..Default::default()
+                    },
+                ))?;
+                conn.request_checked(&protocol::change_property32(
+                    PropertyMode::Replace,
+                    requestor,
+                    property,
+                    atoms.incr,
+                    &[data.len() as u32],
+                ))?;
+                self.transfers
+                    .retain(|t| t.requestor != requestor || t.property != property);
+                self.transfers.push(Transfer {
+                    requestor,
+                    property,
+                    data,
+                    sent: 0,
+                });
+            }
+        } else {
+            return Ok(NONE);
+        }
+        Ok(property)
+    }
+
+    /// Sends the next piece of an `INCR` transfer, or the empty piece that ends it.
+    fn send_next_piece(&mut self, conn: &mut Connection, i: usize) -> Result<(), Error> {
+        let max_len = max_piece_len(conn);
+        let transfer = &mut self.transfers[i];
+        let end = transfer.data.len().min(transfer.sent + max_len);
+        let piece = &transfer.data[transfer.sent..end];
+        let finished = piece.is_empty();
+        let result = conn.request_checked(&protocol::change_property(
+            PropertyMode::Replace,
+            transfer.requestor,
+            transfer.property,
+            self.atoms.utf8_string,
+            8,
+            piece,
+        ));
+        transfer.sent = end;
+
+        if finished || result.is_err() {
+            let transfer = self.transfers.remove(i);
+            if result.is_ok() {
+                ignore_bad_window(conn.request_checked(&protocol::change_window_attributes(
+                    transfer.requestor,
+                    &WindowAttributes {
+                        event_mask: Some(0),
+                        ..Default::default()
+                    },
+                )))?;
+            }
+        }
+        ignore_bad_window(result)
+    }
+
+    /// Asks the owner for the selection as `target`, and reads it from the property it's put in,
+    /// in pieces if the owner sends it with `INCR`.
+    ///
+    /// **Returns:** The property's bytes, or `None` if nobody owns the selection, it couldn't be
+    /// converted, or the owner didn't answer by `deadline`.
+    fn receive(
+        &mut self,
+        conn: &mut Connection,
+        target: Atom,
+        deadline: Instant,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let (window, selection, property) = (self.window, self.selection, self.atoms.property);
+        let time = self.server_time(conn)?;
+        conn.request_checked(&protocol::convert_selection(
+            window, selection, target, property, time,
+        ))?;
+        let notify = conn.wait_for_event(remaining(deadline), |event| {
+            matches!(*event, Event::SelectionNotify { requestor, selection: s, .. }
+                if requestor == window && s == selection)
+        })?;
+        let Some(Event::SelectionNotify { property, .. }) = notify else {
+            return Ok(None);
+        };
+        if property == NONE {
+            return Ok(None);
+        }
+        let received = read_selection(conn, window, property, self.atoms.incr, deadline)?;
+        Ok(received.map(|(_, data)| data))
+    }
+
+    /// Gets the server's current time, which selections need instead of [`CURRENT_TIME`], from
+    /// the notification of a (zero-length) change to a property.
+    fn server_time(&self, conn: &mut Connection) -> Result<Timestamp, Error> {
+        let (window, property) = (self.window, self.atoms.property);
+        conn.request_checked(&protocol::change_property(
+            PropertyMode::Append,
+            window,
+            property,
+            self.atoms.utf8_string,
+            8,
+            &[],
+        ))?;
+        let notify = conn.wait_for_event(SERVER_TIME_TIMEOUT, |event| {
+            matches!(*event, Event::PropertyNotify { window: w, atom, .. }
+                if w == window && atom == property)
+        })?;
+        match notify {
+            Some(Event::PropertyNotify { time, .. }) => Ok(time),
+            _ => Err(Error::Io(io::ErrorKind::TimedOut.into())),
+        }
+    }
+}
+
+/// The most text that fits in one `ChangeProperty` request, whose header is 24 bytes.
+fn max_piece_len(conn: &Connection) -> usize {
+    usize::from(conn.setup().maximum_request_length) * 4 - 24
+}
+
+/// Creates a hidden window that hears about changes to its properties, for selections to be put
+/// in.
+pub(crate) fn create_requestor_window(conn: &mut Connection) -> Result<Window, Error> {
+    let window = conn.generate_id();
+    let root = conn.root();
+    conn.request_checked(&protocol::create_window(&CreateWindow {
+        depth: 0,
+        id: window,
+        parent: root,
+        x: -10,
+        y: -10,
+        width: 1,
+        height: 1,
+        border_width: 0,
+        class: window_class::INPUT_ONLY,
+        visual: 0,
+        attributes: WindowAttributes {
+            event_mask: Some(event_masks::PROPERTY_CHANGE),
+            ..Default::default()
+        },
+    }))?;
+    Ok(window)
+}
+
+/// Reads a selection that its owner has put in `property` on `window` (made with
+/// [`create_requestor_window`]), in pieces if the owner sends it with `INCR`, and deletes it.
+///
+/// **Returns:** The selection's type and bytes, or `None` if the owner didn't send every piece by
+/// `deadline`.
+pub(crate) fn read_selection(
+    conn: &mut Connection,
+    window: Window,
+    property: Atom,
+    incr: Atom,
+    deadline: Instant,
+) -> Result<Option<(Atom, Vec<u8>)>, Error> {
+    // setting the property made a notification, which mustn't be mistaken for a piece
+    discard_property_notifies(conn, window, property)?;
+    let first = read_property(conn, window, property)?;
+    if first.type_ != incr {
+        discard_property_notifies(conn, window, property)?;
+        return Ok(Some((first.type_, first.value)));
+    }
+
+    // reading (and so deleting) the INCR property asked for the first piece
+    let mut data = Vec::new();
+    loop {
+        let piece = conn.wait_for_event(remaining(deadline), |event| {
+            matches!(*event, Event::PropertyNotify { window: w, atom, deleted: false, .. }
+                if w == window && atom == property)
+        })?;
+        if piece.is_none() {
+            discard_property_notifies(conn, window, property)?;
+            return Ok(None);
+        }
+        // the empty piece that ends the transfer has the selection's type too
+        let piece = read_property(conn, window, property)?;
+        if piece.value.is_empty() {
+            discard_property_notifies(conn, window, property)?;
+            return Ok(Some((piece.type_, data)));
+        }
+        data.extend_from_slice(&piece.value);
+    }
+}
+
+/// Reads all of a property of any type, and deletes it.
+fn read_property(conn: &mut Connection, window: Window, property: Atom) -> Result<Property, Error> {
+    let reply = conn.request_with_reply(&protocol::get_property(
+        true,
+        window,
+        property,
+        NONE,
+        0,
+        u32::MAX / 4,
+    ))?;
+    Ok(Property::parse(&reply)?)
+}
+
+/// Drops the notifications about a property so they don't reach the application, after waiting
+/// for the server to send any that are on their way. (A deletion by `GetProperty` is only
+/// reported after its reply.)
+fn discard_property_notifies(
+    conn: &mut Connection,
+    window: Window,
+    property: Atom,
+) -> Result<(), Error> {
+    conn.sync()?;
+    while conn
+        .wait_for_event(Duration::ZERO, |event| {
+            matches!(*event, Event::PropertyNotify { window: w, atom, .. }
+                if w == window && atom == property)
+        })?
+        .is_some()
+    {}
+    Ok(())
+}
+
+fn remaining(deadline: Instant) -> Duration {
+    deadline.saturating_duration_since(Instant::now())
+}
+
+/// Treats a requestor's window having been destroyed as nothing to worry about, since there's
+/// nobody left to answer.
+pub(crate) fn ignore_bad_window(result: Result<(), Error>) -> Result<(), Error> {
+    match result {
+        Err(Error::Protocol(e)) if e.name() == "BadWindow" => Ok(()),
+        result => result,
+    }
+}
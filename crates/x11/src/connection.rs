@@ -0,0 +1,496 @@
+//! A connection to an X server over its local Unix socket.
+//!
+//! Requests are written straight away. Requests with replies wait for them, queueing any events
+//! that arrive first, so replies never have to be matched up later.
+
+use core::{fmt, time::Duration};
+use std::{
+    collections::{HashMap, VecDeque},
+    env, fs,
+    io::{self, Read, Write},
+    os::unix::{
+        io::{AsRawFd, RawFd},
+        net::UnixStream,
+    },
+    path::PathBuf,
+    time::Instant,
+};
+
+use crate::{
+    display::{find_cookie, parse_xauthority, BadDisplayName, DisplayName, MIT_MAGIC_COOKIE},
+    protocol::{
+        self, event_codes, setup_reply_len, Atom, Event, ExtensionInfo, GrabStatus, ProtocolError,
+        Screen, Setup, SetupError, Window, SETUP_HEADER_LEN,
+    },
+    wire::ParseError,
+};
+
+/// Anything that can go wrong while talking to an X server.
+#[derive(Debug)]
+pub enum Error {
+    /// `$DISPLAY` isn't set, so there's no X server to connect to.
+    NoDisplay,
+    DisplayName(BadDisplayName),
+    /// The display is on another host. Only local connections are supported.
+    RemoteDisplay(String),
+    Io(io::Error),
+    Setup(SetupError),
+    /// The server sent something that couldn't be decoded.
+    Parse(ParseError),
+    /// The server reported an error for a request.
+    Protocol(ProtocolError),
+    /// The server doesn't have an extension that's needed.
+    MissingExtension(&'static str),
+    /// The pointer couldn't be grabbed.
+    PointerGrab(GrabStatus),
+    /// An icon's RGBA pixels aren't `width * height * 4` bytes long, or the icon is empty.
+    BadIcon {
+        width: u32,
+        height: u32,
+        len: usize,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoDisplay => write!(f, "no X display ($DISPLAY isn't set)"),
+            Self::DisplayName(e) => e.fmt(f),
+            Self::RemoteDisplay(host) => {
+                write!(
+                    f,
+                    "can't connect to X display on \"{host}\": only local displays are supported"
+                )
+            }
+            Self::Io(e) => write!(f, "X11 connection: {e}"),
+            Self::Setup(e) => e.fmt(f),
+            Self::Parse(e) => e.fmt(f),
+            Self::Protocol(e) => write!(f, "X11 protocol error: {e}"),
+            Self::MissingExtension(name) => write!(f, "X server doesn't have the {name} extension"),
+            Self::PointerGrab(status) => write!(f, "couldn't grab the pointer: {status}"),
+            Self::BadIcon { width, height, len } => {
+                write!(f, "{len} bytes of RGBA can't be a {width}x{height} icon")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::DisplayName(e) => Some(e),
+            Self::Io(e) => Some(e),
+            Self::Setup(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::Protocol(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+impl From<ProtocolError> for Error {
+    fn from(e: ProtocolError) -> Self {
+        Self::Protocol(e)
+    }
+}
+
+/// A packet from the server, which is always at least 32 bytes.
+enum Packet {
+    Reply { sequence: u16, bytes: Vec<u8> },
+    Error(ProtocolError),
+    Event(Vec<u8>),
+}
+
+/// A connection to an X server.
+pub struct Connection {
+    stream: UnixStream,
+    setup: Setup,
+    screen: usize,
+    /// How many resource IDs have been handed out.
+    ids_generated: u32,
+    /// The (low 16 bits of the) sequence number of the last request sent.
+    sequence: u16,
+    /// Bytes read from the server that don't make up a whole packet yet.
+    input: Vec<u8>,
+    /// Events, and errors for requests that weren't waited on, in the order they arrived.
+    events: VecDeque<Result<Vec<u8>, ProtocolError>>,
+    atoms: HashMap<String, Atom>,
+    extensions: HashMap<String, Option<ExtensionInfo>>,
+}
+
+impl fmt::Debug for Connection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Connection")
+            .field("vendor", &self.setup.vendor)
+            .field("screen", &self.screen)
+            .field("sequence", &self.sequence)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Connection {
+    /// Connects to the display in `$DISPLAY`.
+    pub fn connect() -> Result<Self, Error> {
+        let name = env::var("DISPLAY").map_err(|_| Error::NoDisplay)?;
+        Self::connect_to(&DisplayName::parse(&name).map_err(Error::DisplayName)?)
+    }
+
+    /// Connects to a local display, authorizing with a cookie from the `Xauthority` file if
+    /// there's one for the display.
+    pub fn connect_to(display: &DisplayName) -> Result<Self, Error> {
+        if let Some(host) = &display.host {
+            return Err(Error::RemoteDisplay(host.clone()));
+        }
+        let mut stream = connect_socket(display)?;
+
+        let cookie = read_cookie(display.display);
+        let (auth_name, auth_data) = match &cookie {
+            Some(cookie) => (MIT_MAGIC_COOKIE.as_bytes(), &cookie[..]),
+            None => (&b""[..], &b""[..]),
+        };
+        stream.write_all(&protocol::setup_request(auth_name, auth_data))?;
+
+        let mut header = [0; SETUP_HEADER_LEN];
+        stream.read_exact(&mut header)?;
+        let mut reply = header.to_vec();
+        reply.resize(setup_reply_len(&header), 0);
+        stream.read_exact(&mut reply[SETUP_HEADER_LEN..])?;
+        let setup = Setup::parse(&reply).map_err(Error::Setup)?;
+
+        let screen = display.screen as usize;
+        if screen >= setup.screens.len() {
+            return Err(Error::DisplayName(BadDisplayName(format!(
+                ":{}.{}",
+                display.display, display.screen
+            ))));
+        }
+
+        Ok(Self {
+            stream,
+            setup,
+            screen,
+            ids_generated: 0,
+            sequence: 0,
+            input: Vec::new(),
+            events: VecDeque::new(),
+            atoms: HashMap::new(),
+            extensions: HashMap::new(),
+        })
+    }
+
+    pub fn setup(&self) -> &Setup {
+        &self.setup
+    }
+
+    /// The screen that was asked for in the display name.
+    pub fn screen(&self) -> &Screen {
+        &self.setup.screens[self.screen]
+    }
+
+    /// The root window of [`screen`](Self::screen).
+    pub fn root(&self) -> Window {
+        self.screen().root
+    }
+
+    /// Makes an ID for a new window, pixmap or other resource.
+    ///
+    /// ## Panics
+    ///
+    /// If the connection has run out of IDs. (The `XC-MISC` extension, which finds unused ones,
+    /// isn't supported.)
+    pub fn generate_id(&mut self) -> u32 {
+        let mask = self.setup.resource_id_mask;
+        let step = mask & mask.wrapping_neg();
+        let id = self
+            .ids_generated
+            .checked_mul(step)
+            .filter(|id| id & !mask == 0)
+            .expect("ran out of X11 resource IDs");
+        self.ids_generated += 1;
+        self.setup.resource_id_base | id
+    }
+
+    /// Sends a request without waiting for it to be processed. Any error it causes is returned by
+    /// a later call to [`poll_event`](Self::poll_event) or similar.
+    ///
+    /// **Returns:** The request's sequence number.
+    pub fn send(&mut self, request: &[u8]) -> Result<u16, Error> {
+        self.stream.write_all(request)?;
+        self.sequence = self.sequence.wrapping_add(1);
+        Ok(self.sequence)
+    }
+
+    /// Sends a request and waits for its reply.
+    pub fn request_with_reply(&mut self, request: &[u8]) -> Result<Vec<u8>, Error> {
+        let sequence = self.send(request)?;
+        self.wait_for_reply(sequence, None)
+    }
+
+    /// Sends a request that has no reply, and waits until it has been processed so that any error
+    /// it causes can be returned.
+    pub fn request_checked(&mut self, request: &[u8]) -> Result<(), Error> {
+        let sequence = self.send(request)?;
+        let mut failure = None;
+        let sync = self.send(&protocol::get_input_focus())?;
+        self.wait_for_reply(sync, Some((sequence, &mut failure)))?;
+        match failure {
+            Some(e) => Err(Error::Protocol(e)),
+            None => Ok(()),
+        }
+    }
+
+    /// Waits until the server has processed every request sent so far.
+    pub fn sync(&mut self) -> Result<(), Error> {
+        self.request_with_reply(&protocol::get_input_focus())
+            .map(drop)
+    }
+
+    /// Reads packets until the reply with the given sequence number arrives. Errors for
+    /// `checked`'s request are put in its slot instead of being queued.
+    fn wait_for_reply(
+        &mut self,
+        sequence: u16,
+        mut checked: Option<(u16, &mut Option<ProtocolError>)>,
+    ) -> Result<Vec<u8>, Error> {
+        loop {
+            let Some(packet) = self.take_packet() else {
+                self.fill(None)?;
+                continue;
+            };
+            match packet {
+                Packet::Reply { sequence: s, bytes } if s == sequence => return Ok(bytes),
+                // a reply nobody is waiting for can only be for a request sent with `send`
+                Packet::Reply { .. } => {}
+                Packet::Error(e) if e.sequence == sequence => return Err(Error::Protocol(e)),
+                Packet::Error(e) => match &mut checked {
+                    Some((s, slot)) if e.sequence == *s => **slot = Some(e),
+                    _ => self.events.push_back(Err(e)),
+                },
+                Packet::Event(bytes) => self.events.push_back(Ok(bytes)),
+            }
+        }
+    }
+
+    /// Splits the next whole packet off the bytes read so far.
+    fn take_packet(&mut self) -> Option<Packet> {
+        let header = self.input.get(..8)?;
+        let code = header[0];
+        let len = if code == 1 || code & 0x7F == event_codes::GENERIC_EVENT {
+            32 + 4 * u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize
+        } else {
+            32
+        };
+        if self.input.len() < len {
+            return None;
+        }
+        let bytes: Vec<u8> = self.input.drain(..len).collect();
+        Some(match code {
+            0 => match ProtocolError::parse(&bytes) {
+                Ok(e) => Packet::Error(e),
+                // can't happen, since there are 32 bytes
+                Err(_) => Packet::Event(bytes),
+            },
+            1 => Packet::Reply {
+                sequence: u16::from_le_bytes([bytes[2], bytes[3]]),
+                bytes,
+            },
+            _ => Packet::Event(bytes),
+        })
+    }
+
+    /// Reads whatever the server has sent, waiting up to `timeout` (or forever) for something to
+    /// arrive.
+    ///
+    /// **Returns:** Whether anything was read.
+    fn fill(&mut self, timeout: Option<Duration>) -> Result<bool, Error> {
+        let mut buffer = [0; 4096];
+        let result = match timeout {
+            Some(timeout) if timeout.is_zero() => {
+                self.stream.set_nonblocking(true)?;
+                let result = self.stream.read(&mut buffer);
+                self.stream.set_nonblocking(false)?;
+                result
+            }
+            timeout => {
+                self.stream.set_read_timeout(timeout)?;
+                self.stream.read(&mut buffer)
+            }
+        };
+        match result {
+            Ok(0) => Err(Error::Io(io::ErrorKind::UnexpectedEof.into())),
+            Ok(n) => {
+                self.input.extend_from_slice(&buffer[..n]);
+                Ok(true)
+            }
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                Ok(false)
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => Ok(true),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Gets the next event's bytes, waiting up to `timeout` (or forever) for one.
+    fn next_raw_event(&mut self, timeout: Option<Duration>) -> Result<Option<Vec<u8>>, Error> {
+        let deadline = timeout.map(|t| Instant::now() + t);
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Ok(Some(event?));
+            }
+            match self.take_packet() {
+                Some(Packet::Event(bytes)) => return Ok(Some(bytes)),
+                Some(Packet::Error(e)) => return Err(Error::Protocol(e)),
+                Some(Packet::Reply { .. }) => continue,
+                None => {}
+            }
+            let remaining = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+            if !self.fill(remaining)? && remaining.is_some() {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Gets the next event's raw bytes without waiting, for events from extensions that
+    /// [`Event`] doesn't decode.
+    pub fn poll_raw_event(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        self.next_raw_event(Some(Duration::ZERO))
+    }
+
+    /// Gets the next event, if there is one, without waiting.
+    ///
+    /// Errors for requests that weren't waited on are returned from here (or
+    /// [`wait_event`](Self::wait_event)) when they arrive, like events.
+    pub fn poll_event(&mut self) -> Result<Option<Event>, Error> {
+        self.wait_event_timeout(Duration::ZERO)
+    }
+
+    /// Waits for the next event.
+    pub fn wait_event(&mut self) -> Result<Event, Error> {
+        let bytes = self.next_raw_event(None)?.expect("waited forever");
+        Ok(Event::parse(&bytes)?)
+    }
+
+    /// Waits up to `timeout` for the next event.
+    pub fn wait_event_timeout(&mut self, timeout: Duration) -> Result<Option<Event>, Error> {
+        match self.next_raw_event(Some(timeout))? {
+            Some(bytes) => Ok(Some(Event::parse(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Waits up to `timeout` for an event that `wanted` picks out, like the answer to a request
+    /// that's sent as an event. Every other event (and error) stays queued, in order, for
+    /// [`poll_event`](Self::poll_event) and similar.
+    pub fn wait_for_event(
+        &mut self,
+        timeout: Duration,
+        mut wanted: impl FnMut(&Event) -> bool,
+    ) -> Result<Option<Event>, Error> {
+        let deadline = Instant::now() + timeout;
+        let mut checked = 0;
+        loop {
+            while let Some(queued) = self.events.get(checked) {
+                if let Ok(bytes) = queued {
+                    let event = Event::parse(bytes)?;
+                    if wanted(&event) {
+                        self.events.remove(checked);
+                        return Ok(Some(event));
+                    }
+                }
+                checked += 1;
+            }
+            match self.take_packet() {
+                Some(Packet::Event(bytes)) => self.events.push_back(Ok(bytes)),
+                Some(Packet::Error(e)) => self.events.push_back(Err(e)),
+                Some(Packet::Reply { .. }) => {}
+                None => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if !self.fill(Some(remaining))? {
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Gets the atom for a name, creating it if needed. Atoms are cached, so this only asks the
+    /// server once per name.
+    pub fn intern_atom(&mut self, name: &str) -> Result<Atom, Error> {
+        if let Some(&atom) = self.atoms.get(name) {
+            return Ok(atom);
+        }
+        let reply = self.request_with_reply(&protocol::intern_atom(false, name))?;
+        let atom = protocol::parse_intern_atom(&reply)?;
+        self.atoms.insert(name.to_string(), atom);
+        Ok(atom)
+    }
+
+    /// Finds out whether the server has an extension, and what its opcode and first event and
+    /// error codes are. The answers are cached.
+    pub fn query_extension(&mut self, name: &str) -> Result<Option<ExtensionInfo>, Error> {
+        if let Some(&info) = self.extensions.get(name) {
+            return Ok(info);
+        }
+        let reply = self.request_with_reply(&protocol::query_extension(name))?;
+        let info = Some(ExtensionInfo::parse(&reply)?).filter(|info| info.present);
+        self.extensions.insert(name.to_string(), info);
+        Ok(info)
+    }
+}
+
+impl AsRawFd for Connection {
+    /// The socket, which becomes readable when the server sends something. Check
+    /// [`poll_event`](Connection::poll_event) before waiting on it, since events may already have
+    /// been read and queued.
+    fn as_raw_fd(&self) -> RawFd {
+        self.stream.as_raw_fd()
+    }
+}
+
+fn connect_socket(display: &DisplayName) -> io::Result<UnixStream> {
+    let path = display.socket_path();
+    match UnixStream::connect(&path) {
+        Ok(stream) => Ok(stream),
+        #[cfg(target_os = "linux")]
+        Err(e) => {
+            // Linux servers also listen on an abstract socket with the same name, which works
+            // even when /tmp isn't shared (e.g. in containers)
+            use std::os::{linux::net::SocketAddrExt, unix::net::SocketAddr};
+            SocketAddr::from_abstract_name(path.as_bytes())
+                .and_then(|addr| UnixStream::connect_addr(&addr))
+                .map_err(|_| e)
+        }
+        #[cfg(not(target_os = "linux"))]
+        Err(e) => Err(e),
+    }
+}
+
+/// Looks up the `MIT-MAGIC-COOKIE-1` for a local display, from `$XAUTHORITY` or `~/.Xauthority`.
+fn read_cookie(display: u32) -> Option<Vec<u8>> {
+    let path = match env::var_os("XAUTHORITY") {
+        Some(path) => PathBuf::from(path),
+        None => PathBuf::from(env::var_os("HOME")?).join(".Xauthority"),
+    };
+    let entries = parse_xauthority(&fs::read(path).ok()?);
+    let hostname = fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| fs::read_to_string("/etc/hostname"))
+        .unwrap_or_default();
+    find_cookie(&entries, hostname.trim(), display).map(|e| e.data.clone())
+}
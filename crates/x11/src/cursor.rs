@@ -0,0 +1,368 @@
+//! Hiding the pointer's cursor, locking it in place for first-person cameras, and making cursors
+//! out of images.
+//!
+//! Motion is read from [`xinput`](crate::xinput) raw events while the cursor is hidden or locked,
+//! so it keeps coming even though the pointer can't move. Hand every event to
+//! [`CursorGrab::raw_motion`] to pick them out.
+//!
+//! Cursors made from images ([`ImageCursors`]) need the [`render`](crate::render) extension.
+//! They're shown over a window by setting its `cursor` attribute with
+//! [`change_window_attributes`](protocol::change_window_attributes).
+
+use crate::{
+    connection::{Connection, Error},
+    protocol::{
+        self, event_masks, Cursor, Event, GrabStatus, Window, WindowAttributes, CURRENT_TIME, NONE,
+    },
+    render::{self, PictFormat},
+    xcursor::{self, ThemeLoader},
+    xinput::{self, devices, event_types, RawMotion},
+};
+
+/// What the cursor does over a window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CursorMode {
+    /// Visible, and free to move.
+    #[default]
+    Normal,
+    /// Invisible, but free to move, e.g. while dragging to rotate something.
+    Hidden,
+    /// Invisible, and kept inside the window by a pointer grab. Only relative motion means
+    /// anything.
+    Locked,
+}
+
+impl CursorMode {
+    /// Whether raw motion should be selected in this mode.
+    pub const fn wants_raw_motion(self) -> bool {
+        !matches!(self, Self::Normal)
+    }
+}
+
+/// Puts a window's cursor into a [`CursorMode`], and back.
+///
+/// Since it needs the connection to undo anything, put it back into [`CursorMode::Normal`] with
+/// [`release`](Self::release) before dropping it. (Closing the connection undoes everything too.)
+#[derive(Debug, Default)]
+pub struct CursorGrab {
+    mode: CursorMode,
+    /// An invisible cursor, created the first time it's needed.
+    invisible: Option<Cursor>,
+    grabbed: bool,
+    /// XInput2's major opcode, while raw motion is selected.
+    xinput: Option<u8>,
+}
+
+impl CursorGrab {
+    pub const fn new() -> Self {
+        Self {
+            mode: CursorMode::Normal,
+            invisible: None,
+            grabbed: false,
+            xinput: None,
+        }
+    }
+
+    pub fn mode(&self) -> CursorMode {
+        self.mode
+    }
+
+    /// Puts the cursor into `mode`, selecting raw motion if the mode needs it.
+    ///
+    /// Locking fails with [`Error::PointerGrab`] if the window isn't viewable, or another client
+    /// has grabbed the pointer. It also fails with [`Error::MissingExtension`] if the server
+    /// doesn't have XInput 2.0, in which case the mode is left as it was.
+    pub fn set_mode(
+        &mut self,
+        conn: &mut Connection,
+        window: Window,
+        mode: CursorMode,
+    ) -> Result<(), Error> {
+        if mode.wants_raw_motion() && self.xinput.is_none() {
+            let major = xinput_opcode(conn)?;
+            select_raw_motion(conn, major, true)?;
+            self.xinput = Some(major);
+        } else if !mode.wants_raw_motion() {
+            if let Some(major) = self.xinput.take() {
+                select_raw_motion(conn, major, false)?;
+            }
+        }
+
+        let cursor = match mode {
+            CursorMode::Normal => NONE,
+            CursorMode::Hidden | CursorMode::Locked => self.invisible_cursor(conn, window)?,
+        };
+        conn.request_checked(&protocol::change_window_attributes(
+            window,
+            &WindowAttributes {
+                cursor: Some(cursor),
+                ..Default::default()
+            },
+        ))?;
+
+        self.mode = mode;
+        self.focus_changed(conn, window, true)
+    }
+
+    /// Grabs the pointer again when the window gets the focus, and lets go of it when it loses
+    /// it, so the user can still use other windows. Call this for `FocusIn` and `FocusOut`
+    /// events while the cursor is locked.
+    pub fn focus_changed(
+        &mut self,
+        conn: &mut Connection,
+        window: Window,
+        focused: bool,
+    ) -> Result<(), Error> {
+        if self.grabbed {
+            conn.request_checked(&protocol::ungrab_pointer(CURRENT_TIME))?;
+            self.grabbed = false;
+        }
+        if focused && self.mode == CursorMode::Locked {
+            let mask = event_masks::BUTTON_PRESS
+                | event_masks::BUTTON_RELEASE
+                | event_masks::POINTER_MOTION;
+            let cursor = self.invisible_cursor(conn, window)?;
+            let reply = conn.request_with_reply(&protocol::grab_pointer(
+                true,
+                window,
+                mask as u16,
+                window,
+                cursor,
+                CURRENT_TIME,
+            ))?;
+            match GrabStatus::parse(&reply)? {
+                GrabStatus::Success => self.grabbed = true,
+                status => return Err(Error::PointerGrab(status)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Puts the cursor back to normal, and frees the invisible cursor.
+    pub fn release(&mut self, conn: &mut Connection, window: Window) -> Result<(), Error> {
+        self.set_mode(conn, window, CursorMode::Normal)?;
+        if let Some(cursor) = self.invisible.take() {
+            conn.request_checked(&protocol::free_cursor(cursor))?;
+        }
+        Ok(())
+    }
+
+    /// How far the mouse moved (before acceleration), if `event` is raw motion.
+    pub fn raw_motion(&self, event: &Event) -> Option<[f64; 2]> {
+        let major = self.xinput?;
+        let Event::Other { bytes, .. } = event else {
+            return None;
+        };
+        Some(RawMotion::parse(major, bytes).ok()??.delta())
+    }
+
+    fn invisible_cursor(&mut self, conn: &mut Connection, window: Window) -> Result<Cursor, Error> {
+        if let Some(cursor) = self.invisible {
+            return Ok(cursor);
+        }
+        let cursor = create_invisible_cursor(conn, window)?;
+        self.invisible = Some(cursor);
+        Ok(cursor)
+    }
+}
+
+/// Creates a cursor without any visible pixels. X has no "no cursor" cursor, so this is a 1×1
+/// cursor whose mask is cleared.
+pub fn create_invisible_cursor(conn: &mut Connection, window: Window) -> Result<Cursor, Error> {
+    let pixmap = conn.generate_id();
+    let gc = conn.generate_id();
+    let cursor = conn.generate_id();
+    conn.send(&protocol::create_pixmap(1, pixmap, window, 1, 1))?;
+    // pixmaps start out with undefined contents
+    conn.send(&protocol::create_gc(gc, pixmap))?;
+    conn.send(&protocol::put_image(pixmap, gc, 1, 1, 1, &[0; 4]))?;
+    conn.send(&protocol::free_gc(gc))?;
+    conn.send(&protocol::create_cursor(
+        cursor,
+        pixmap,
+        pixmap,
+        [0; 3],
+        [0; 3],
+        [0, 0],
+    ))?;
+    conn.request_checked(&protocol::free_pixmap(pixmap))?;
+    Ok(cursor)
+}
+
+/// Makes cursors out of ARGB images, with the RENDER extension.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageCursors {
+    major: u8,
+    /// The 32-bit ARGB picture format.
+    format: PictFormat,
+}
+
+impl ImageCursors {
+    /// Checks that the server has RENDER 0.5, and finds its ARGB format.
+    pub fn new(conn: &mut Connection) -> Result<Self, Error> {
+        let Some(extension) = conn.query_extension("RENDER")? else {
+            return Err(Error::MissingExtension("RENDER"));
+        };
+        let major = extension.major_opcode;
+        let version =
+            render::parse_query_version(&conn.request_with_reply(&render::query_version(major))?)?;
+        if version < render::VERSION {
+            return Err(Error::MissingExtension("RENDER 0.5"));
+        }
+        let formats = render::parse_query_pict_formats(
+            &conn.request_with_reply(&render::query_pict_formats(major))?,
+        )?;
+        let Some(format) = formats.iter().find(|format| format.is_argb32()) else {
+            return Err(Error::MissingExtension("RENDER's ARGB32 format"));
+        };
+        Ok(Self {
+            major,
+            format: format.id,
+        })
+    }
+
+    /// Makes a cursor from straight (not premultiplied) RGBA pixels, row by row. The hotspot is
+    /// the pixel under the pointer's actual position.
+    ///
+    /// ## Panics
+    ///
+    /// If `rgba` isn't `width * height * 4` bytes long.
+    pub fn create_from_rgba(
+        &self,
+        conn: &mut Connection,
+        rgba: &[u8],
+        width: u16,
+        height: u16,
+        hotspot: [u16; 2],
+    ) -> Result<Cursor, Error> {
+        assert_eq!(
+            rgba.len(),
+            usize::from(width) * usize::from(height) * 4,
+            "the image should be {width}x{height} RGBA pixels",
+        );
+        let pixels: Vec<u32> = rgba
+            .chunks_exact(4)
+            .map(|pixel| {
+                let [r, g, b, a] = [pixel[0], pixel[1], pixel[2], pixel[3]];
+                let premultiply = |c: u8| ((u32::from(c) * u32::from(a) + 127) / 255) as u8;
+                u32::from_le_bytes([premultiply(b), premultiply(g), premultiply(r), a])
+            })
+            .collect();
+        self.create_from_argb(conn, &pixels, width, height, hotspot)
+    }
+
+    /// Makes a cursor from an image out of an Xcursor file.
+    pub fn create_from_xcursor(
+        &self,
+        conn: &mut Connection,
+        image: &xcursor::Image,
+    ) -> Result<Cursor, Error> {
+        // images bigger than this are refused by the parser
+        let [width, height, x, y] = [
+            image.width,
+            image.height,
+            image.hotspot[0],
+            image.hotspot[1],
+        ]
+        .map(|n| n as u16);
+        self.create_from_argb(conn, &image.pixels, width, height, [x, y])
+    }
+
+    /// Loads the cursor called `name` from a theme, at the size closest to `size`. Animated
+    /// cursors only get their first frame.
+    ///
+    /// **Returns:** The cursor, or `None` if the theme doesn't have it.
+    pub fn load_themed(
+        &self,
+        conn: &mut Connection,
+        loader: &ThemeLoader,
+        theme: &str,
+        name: &str,
+        size: u32,
+    ) -> Result<Option<Cursor>, Error> {
+        let Some(frames) = loader.load(theme, name, size) else {
+            return Ok(None);
+        };
+        self.create_from_xcursor(conn, &frames[0]).map(Some)
+    }
+
+    /// Makes a cursor from premultiplied ARGB pixels, by drawing them into a 32-bit pixmap and
+    /// making a picture of it.
+    fn create_from_argb(
+        &self,
+        conn: &mut Connection,
+        pixels: &[u32],
+        width: u16,
+        height: u16,
+        hotspot: [u16; 2],
+    ) -> Result<Cursor, Error> {
+        let root = conn.root();
+        let pixmap = conn.generate_id();
+        let gc = conn.generate_id();
+        let picture = conn.generate_id();
+        let cursor = conn.generate_id();
+        conn.send(&protocol::create_pixmap(32, pixmap, root, width, height))?;
+        conn.send(&protocol::create_gc(gc, pixmap))?;
+
+        // as many rows as fit in a request, after PutImage's 24-byte header
+        let row_len = usize::from(width) * 4;
+        let max_len = usize::from(conn.setup().maximum_request_length) * 4 - 24;
+        let rows_per_request = (max_len / row_len).max(1);
+        let bytes: Vec<u8> = pixels.iter().flat_map(|p| p.to_le_bytes()).collect();
+        for (i, rows) in bytes.chunks(rows_per_request * row_len).enumerate() {
+            let y = (i * rows_per_request) as i16;
+            let height = (rows.len() / row_len) as u16;
+            conn.send(&protocol::put_image_at(
+                pixmap,
+                gc,
+                32,
+                [0, y],
+                width,
+                height,
+                rows,
+            ))?;
+        }
+        conn.send(&protocol::free_gc(gc))?;
+
+        conn.send(&render::create_picture(
+            self.major,
+            picture,
+            pixmap,
+            self.format,
+        ))?;
+        conn.send(&protocol::free_pixmap(pixmap))?;
+        conn.send(&render::create_cursor(self.major, cursor, picture, hotspot))?;
+        conn.request_checked(&render::free_picture(self.major, picture))?;
+        Ok(cursor)
+    }
+}
+
+/// Finds XInput's major opcode, and checks that the server speaks XInput2.
+fn xinput_opcode(conn: &mut Connection) -> Result<u8, Error> {
+    let Some(extension) = conn.query_extension("XInputExtension")? else {
+        return Err(Error::MissingExtension("XInputExtension"));
+    };
+    let major = extension.major_opcode;
+    let version =
+        xinput::parse_query_version(&conn.request_with_reply(&xinput::query_version(major))?)?;
+    if version < xinput::VERSION {
+        return Err(Error::MissingExtension("XInputExtension 2.0"));
+    }
+    Ok(major)
+}
+
+/// Selects (or deselects) raw motion from the master pointer. Raw events only go to the root
+/// window.
+fn select_raw_motion(conn: &mut Connection, major: u8, select: bool) -> Result<(), Error> {
+    let mask = if select {
+        1 << event_types::RAW_MOTION
+    } else {
+        0
+    };
+    let root = conn.root();
+    conn.request_checked(&xinput::select_events(
+        major,
+        root,
+        &[(devices::ALL_MASTER, mask)],
+    ))
+}
@@ -0,0 +1,213 @@
+//! Finding the X server: parsing display names like `:1.0`, and looking up the cookie that
+//! authorizes connecting to it in the `Xauthority` file.
+
+use core::fmt;
+
+/// A parsed display name, as found in `$DISPLAY`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplayName {
+    /// The host to connect to over TCP, or `None` for the local Unix socket.
+    pub host: Option<String>,
+    pub display: u32,
+    pub screen: u32,
+}
+
+/// A display name that couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadDisplayName(pub String);
+
+impl fmt::Display for BadDisplayName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid X display name \"{}\"", self.0)
+    }
+}
+
+impl std::error::Error for BadDisplayName {}
+
+impl DisplayName {
+    /// Parses `[host]:display[.screen]`. A host of `unix` means the local socket, the same as no
+    /// host.
+    pub fn parse(name: &str) -> Result<Self, BadDisplayName> {
+        let bad = || BadDisplayName(name.to_string());
+        let (host, rest) = name.rsplit_once(':').ok_or_else(bad)?;
+        let (display, screen) = match rest.split_once('.') {
+            Some((display, screen)) => (display, Some(screen)),
+            None => (rest, None),
+        };
+        Ok(Self {
+            host: match host {
+                "" | "unix" => None,
+                host => Some(host.to_string()),
+            },
+            display: display.parse().map_err(|_| bad())?,
+            screen: match screen {
+                Some(screen) => screen.parse().map_err(|_| bad())?,
+                None => 0,
+            },
+        })
+    }
+
+    /// The path of the local server's socket.
+    pub fn socket_path(&self) -> String {
+        format!("/tmp/.X11-unix/X{}", self.display)
+    }
+}
+
+/// Values of [`XauthEntry::family`].
+pub mod families {
+    pub const INTERNET: u16 = 0;
+    /// A local connection. The address is the host name.
+    pub const LOCAL: u16 = 256;
+    /// Matches any address.
+    pub const WILD: u16 = 65535;
+}
+
+/// One entry in an `Xauthority` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XauthEntry {
+    pub family: u16,
+    pub address: Vec<u8>,
+    /// The display number, as a string. Empty matches any display.
+    pub number: String,
+    /// The authorization protocol, e.g. `MIT-MAGIC-COOKIE-1`.
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+/// Parses an `Xauthority` file. Every field is big-endian and prefixed by a two-byte length, unlike
+/// the X11 protocol.
+///
+/// A truncated last entry is ignored, like Xlib does.
+pub fn parse_xauthority(mut bytes: &[u8]) -> Vec<XauthEntry> {
+    fn field<'a>(bytes: &mut &'a [u8]) -> Option<&'a [u8]> {
+        let len = u16::from_be_bytes([*bytes.first()?, *bytes.get(1)?]) as usize;
+        let field = bytes.get(2..2 + len)?;
+        *bytes = &bytes[2 + len..];
+        Some(field)
+    }
+
+    let mut entries = Vec::new();
+    while bytes.len() >= 2 {
+        let family = u16::from_be_bytes([bytes[0], bytes[1]]);
+        bytes = &bytes[2..];
+        let mut fields = || field(&mut bytes);
+        let (Some(address), Some(number), Some(name), Some(data)) =
+            (fields(), fields(), fields(), fields())
+        else {
+            break;
+        };
+        entries.push(XauthEntry {
+            family,
+            address: address.to_vec(),
+            number: String::from_utf8_lossy(number).into_owned(),
+            name: String::from_utf8_lossy(name).into_owned(),
+            data: data.to_vec(),
+        });
+    }
+    entries
+}
+
+/// The protocol that [`find_cookie`] looks for, which is the only one supported.
+pub const MIT_MAGIC_COOKIE: &str = "MIT-MAGIC-COOKIE-1";
+
+/// Finds the `MIT-MAGIC-COOKIE-1` for a local display on the host called `hostname`.
+pub fn find_cookie<'a>(
+    entries: &'a [XauthEntry],
+    hostname: &str,
+    display: u32,
+) -> Option<&'a XauthEntry> {
+    let display = display.to_string();
+    entries.iter().find(|e| {
+        let address_matches = match e.family {
+            families::LOCAL => e.address == hostname.as_bytes(),
+            families::WILD => true,
+            _ => false,
+        };
+        address_matches
+            && (e.number.is_empty() || e.number == display)
+            && e.name == MIT_MAGIC_COOKIE
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_names() {
+        assert_eq!(
+            DisplayName::parse(":1"),
+            Ok(DisplayName {
+                host: None,
+                display: 1,
+                screen: 0
+            })
+        );
+        assert_eq!(
+            DisplayName::parse("unix:10.2"),
+            Ok(DisplayName {
+                host: None,
+                display: 10,
+                screen: 2
+            })
+        );
+        assert_eq!(
+            DisplayName::parse("example.com:0").unwrap().host.as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            DisplayName::parse(":99").unwrap().socket_path(),
+            "/tmp/.X11-unix/X99"
+        );
+
+        for bad in ["", "1", ":", ":x", ":1.", ":1.y"] {
+            assert!(DisplayName::parse(bad).is_err(), "{bad:?}");
+        }
+    }
+
+    fn entry(family: u16, address: &[u8], number: &str, name: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = family.to_be_bytes().to_vec();
+        for field in [address, number.as_bytes(), name.as_bytes(), data] {
+            bytes.extend_from_slice(&(field.len() as u16).to_be_bytes());
+            bytes.extend_from_slice(field);
+        }
+        bytes
+    }
+
+    #[test]
+    fn xauthority() {
+        let mut file = entry(
+            families::LOCAL,
+            b"otherhost",
+            "0",
+            MIT_MAGIC_COOKIE,
+            &[1; 16],
+        );
+        file.extend(entry(
+            families::LOCAL,
+            b"myhost",
+            "1",
+            MIT_MAGIC_COOKIE,
+            &[2; 16],
+        ));
+        file.extend(entry(
+            families::LOCAL,
+            b"myhost",
+            "0",
+            "XDM-AUTHORIZATION-1",
+            &[3; 8],
+        ));
+        file.extend(entry(families::WILD, b"", "0", MIT_MAGIC_COOKIE, &[4; 16]));
+        // truncated
+        file.extend(&entry(families::LOCAL, b"myhost", "2", MIT_MAGIC_COOKIE, &[5; 16])[..20]);
+
+        let entries = parse_xauthority(&file);
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].address, b"otherhost");
+        assert_eq!(entries[2].name, "XDM-AUTHORIZATION-1");
+
+        assert_eq!(find_cookie(&entries, "myhost", 1).unwrap().data, [2; 16]);
+        assert_eq!(find_cookie(&entries, "myhost", 0).unwrap().data, [4; 16]);
+        assert!(find_cookie(&entries, "myhost", 2).is_none());
+    }
+}
@@ -0,0 +1,286 @@
+//! Files dragged onto a window from file managers and other programs, following the
+//! [XDND protocol](https://freedesktop.org/wiki/Specifications/XDND/) (version 5).
+//!
+//! The program the drag comes from (the source) sends `ClientMessage`s to the window under the
+//! pointer (the target): `XdndEnter` with the types it offers, `XdndPosition` as the pointer
+//! moves, and `XdndDrop` or `XdndLeave` at the end. The target answers each position with
+//! `XdndStatus`, and after a drop it converts the `XdndSelection` selection to `text/uri-list`
+//! and sends `XdndFinished`.
+//!
+//! The list is read the same way as pasted text from the [`clipboard`](crate::clipboard), through
+//! a hidden window of the target's own, so that long lists can come in pieces with `INCR`.
+
+use core::time::Duration;
+use std::time::Instant;
+
+use event::Event as WindowEvent;
+
+use crate::{
+    clipboard::{create_requestor_window, ignore_bad_window, read_selection},
+    connection::{Connection, Error},
+    protocol::{self, atoms, Atom, ClientMessage, Event, Property, PropertyMode, Window, NONE},
+    uri_list,
+};
+
+/// The newest version of XDND that [`DropTarget`] speaks.
+pub const VERSION: u32 = 5;
+
+/// The property the dropped URI list is put in, on the target window.
+const PROPERTY: &str = "_TRIANGLE_FROM_SCRATCH_DROP";
+
+/// How long a list that's sent in pieces with `INCR` has to arrive.
+const INCR_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy)]
+struct Atoms {
+    aware: Atom,
+    enter: Atom,
+    position: Atom,
+    status: Atom,
+    leave: Atom,
+    drop: Atom,
+    finished: Atom,
+    selection: Atom,
+    type_list: Atom,
+    action_copy: Atom,
+    uri_list: Atom,
+    incr: Atom,
+    property: Atom,
+}
+
+/// A drag that's over the window.
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    source: Window,
+    version: u32,
+    /// Whether the source offers `text/uri-list`.
+    has_files: bool,
+    /// Whether it's been dropped, and the list has been asked for.
+    dropped: bool,
+}
+
+/// Lets files be dropped on a window.
+///
+/// Hand every event to [`handle_event`](Self::handle_event), which answers the source and gives
+/// back the dropped files.
+#[derive(Debug)]
+pub struct DropTarget {
+    window: Window,
+    /// The hidden window that the dropped list is put on.
+    requestor: Window,
+    atoms: Atoms,
+    drag: Option<Drag>,
+}
+
+impl DropTarget {
+    /// Tells sources that `window` accepts drops, by setting its `XdndAware` property, and creates
+    /// a hidden window to read them through.
+    pub fn new(conn: &mut Connection, window: Window) -> Result<Self, Error> {
+        let atoms = Atoms {
+            aware: conn.intern_atom("XdndAware")?,
+            enter: conn.intern_atom("XdndEnter")?,
+            position: conn.intern_atom("XdndPosition")?,
+            status: conn.intern_atom("XdndStatus")?,
+            leave: conn.intern_atom("XdndLeave")?,
+            drop: conn.intern_atom("XdndDrop")?,
+            finished: conn.intern_atom("XdndFinished")?,
+            selection: conn.intern_atom("XdndSelection")?,
+            type_list: conn.intern_atom("XdndTypeList")?,
+            action_copy: conn.intern_atom("XdndActionCopy")?,
+            uri_list: conn.intern_atom("text/uri-list")?,
+            incr: conn.intern_atom("INCR")?,
+            property: conn.intern_atom(PROPERTY)?,
+        };
+        conn.request_checked(&protocol::change_property32(
+            PropertyMode::Replace,
+            window,
+            atoms.aware,
+            atoms::ATOM,
+            &[VERSION],
+        ))?;
+        Ok(Self {
+            window,
+            requestor: create_requestor_window(conn)?,
+            atoms,
+            drag: None,
+        })
+    }
+
+    pub fn window(&self) -> Window {
+        self.window
+    }
+
+    /// Whether something that can be dropped is being dragged over the window.
+    pub fn is_dragging_files(&self) -> bool {
+        self.drag.is_some_and(|drag| drag.has_files)
+    }
+
+    /// Destroys the hidden window that drops are read through. The window that accepted them is
+    /// left alone.
+    pub fn destroy(self, conn: &mut Connection) -> Result<(), Error> {
+        conn.request_checked(&protocol::destroy_window(self.requestor))
+    }
+
+    /// Answers drag sources, and reads the files once they're dropped. A list that's sent in
+    /// pieces is waited for, for up to five seconds.
+    ///
+    /// **Returns:** A [`FileDropped`](WindowEvent::FileDropped) for each dropped file, once a drop
+    /// has finished. Every other event, including the rest of the protocol, gives back none.
+    pub fn handle_event(
+        &mut self,
+        conn: &mut Connection,
+        event: &Event,
+    ) -> Result<Vec<WindowEvent>, Error> {
+        match event {
+            Event::ClientMessage(message) if message.window == self.window => {
+                self.handle_message(conn, message)?;
+                Ok(Vec::new())
+            }
+            &Event::SelectionNotify {
+                requestor,
+                selection,
+                property,
+                ..
+            } if requestor == self.requestor && selection == self.atoms.selection => {
+                let Some(drag) = self.drag.filter(|drag| drag.dropped) else {
+                    return Ok(Vec::new());
+                };
+                self.drag = None;
+                let list = match property {
+                    NONE => None,
+                    property => read_selection(
+                        conn,
+                        self.requestor,
+                        property,
+                        self.atoms.incr,
+                        Instant::now() + INCR_TIMEOUT,
+                    )?,
+                };
+                // anything but the type that was asked for can't be trusted to be a list
+                let paths = match list {
+                    Some((type_, list)) if type_ == self.atoms.uri_list => {
+                        uri_list::parse(&String::from_utf8_lossy(&list))
+                    }
+                    _ => Vec::new(),
+                };
+                self.finish(conn, drag, !paths.is_empty())?;
+                Ok(paths.into_iter().map(WindowEvent::FileDropped).collect())
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    fn handle_message(
+        &mut self,
+        conn: &mut Connection,
+        message: &ClientMessage,
+    ) -> Result<(), Error> {
+        let atoms = self.atoms;
+        let data = message.data32();
+        let source = data[0];
+        let type_ = message.type_;
+
+        if type_ == atoms.enter {
+            let version = data[1] >> 24;
+            let has_files = if data[1] & 1 != 0 {
+                // more than three types, so they're in a property on the source
+                self.type_list(conn, source)?.contains(&atoms.uri_list)
+            } else {
+                data[2..].contains(&atoms.uri_list)
+            };
+            self.drag = Some(Drag {
+                source,
+                version,
+                has_files,
+                dropped: false,
+            });
+            return Ok(());
+        }
+
+        // the rest are only for the drag that entered
+        let Some(drag) = self
+            .drag
+            .filter(|drag| drag.source == source && !drag.dropped)
+        else {
+            return Ok(());
+        };
+        if type_ == atoms.position {
+            let action = if drag.has_files {
+                atoms.action_copy
+            } else {
+                NONE
+            };
+            // accepted or not, and an empty rectangle so that every movement is sent
+            let status = protocol::client_message32(
+                source,
+                atoms.status,
+                [self.window, drag.has_files.into(), 0, 0, action],
+            );
+            ignore_bad_window(
+                conn.request_checked(&protocol::send_event(false, source, 0, &status)),
+            )?;
+        } else if type_ == atoms.leave {
+            self.drag = None;
+        } else if type_ == atoms.drop {
+            if !drag.has_files {
+                self.drag = None;
+                return self.finish(conn, drag, false);
+            }
+            // sources older than version 1 don't send the time
+            let time = if drag.version >= 1 {
+                data[2]
+            } else {
+                protocol::CURRENT_TIME
+            };
+            conn.request_checked(&protocol::convert_selection(
+                self.requestor,
+                atoms.selection,
+                atoms.uri_list,
+                atoms.property,
+                time,
+            ))?;
+            self.drag = Some(Drag {
+                dropped: true,
+                ..drag
+            });
+        }
+        Ok(())
+    }
+
+    /// The types a source offers, from its `XdndTypeList` property.
+    fn type_list(&self, conn: &mut Connection, source: Window) -> Result<Vec<Atom>, Error> {
+        let reply = conn.request_with_reply(&protocol::get_property(
+            false,
+            source,
+            self.atoms.type_list,
+            atoms::ATOM,
+            0,
+            1024,
+        ));
+        match reply {
+            Ok(reply) => Ok(Property::parse(&reply)?.as_u32s().unwrap_or_default()),
+            Err(Error::Protocol(e)) if e.name() == "BadWindow" => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Tells the source the drop is over, and whether the files were taken.
+    fn finish(&self, conn: &mut Connection, drag: Drag, accepted: bool) -> Result<(), Error> {
+        let action = if accepted {
+            self.atoms.action_copy
+        } else {
+            NONE
+        };
+        let finished = protocol::client_message32(
+            drag.source,
+            self.atoms.finished,
+            [self.window, accepted.into(), action, 0, 0],
+        );
+        ignore_bad_window(conn.request_checked(&protocol::send_event(
+            false,
+            drag.source,
+            0,
+            &finished,
+        )))
+    }
+}
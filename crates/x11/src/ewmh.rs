@@ -0,0 +1,397 @@
+//! Talking to the window manager, following the
+//! [Extended Window Manager Hints](https://specifications.freedesktop.org/wm-spec/latest/).
+
+use image::RgbaImage;
+
+use crate::{
+    connection::{Connection, Error},
+    monitor,
+    protocol::{
+        self, atoms, event_masks, Geometry, GetWindowAttributesReply, MapState, PropertyMode,
+        TranslatedCoordinates, Window, WindowChanges, NONE, STACK_MODE_ABOVE,
+    },
+};
+
+/// What a `_NET_WM_STATE` message does to the states in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum StateAction {
+    Remove = 0,
+    Add = 1,
+    Toggle = 2,
+}
+
+/// The message that asks the window manager to change up to two of a mapped window's states. It's
+/// sent to the root window with [`send_state_message`].
+pub fn state_message(
+    net_wm_state: protocol::Atom,
+    window: Window,
+    action: StateAction,
+    first: protocol::Atom,
+    second: protocol::Atom,
+) -> [u8; 32] {
+    // the last value says the request comes from a normal application
+    protocol::client_message32(window, net_wm_state, [action as u32, first, second, 1, 0])
+}
+
+/// Sends a [`state_message`] to the window manager.
+pub fn send_state_message(
+    conn: &mut Connection,
+    window: Window,
+    action: StateAction,
+    state: &str,
+) -> Result<(), Error> {
+    let net_wm_state = conn.intern_atom("_NET_WM_STATE")?;
+    let state = conn.intern_atom(state)?;
+    let event = state_message(net_wm_state, window, action, state, NONE);
+    let root = conn.root();
+    conn.request_checked(&protocol::send_event(
+        false,
+        root,
+        event_masks::SUBSTRUCTURE_REDIRECT | event_masks::SUBSTRUCTURE_NOTIFY,
+        &event,
+    ))
+}
+
+/// Reads a property made of 32-bit values, like a list of atoms or a window.
+///
+/// **Returns:** The values, or an empty list if the property doesn't exist or has the wrong type.
+pub fn get_property32(
+    conn: &mut Connection,
+    window: Window,
+    property: &str,
+    type_: protocol::Atom,
+) -> Result<Vec<u32>, Error> {
+    let property = conn.intern_atom(property)?;
+    let reply = conn.request_with_reply(&protocol::get_property(
+        false, window, property, type_, 0, 1024,
+    ))?;
+    let property = protocol::Property::parse(&reply)?;
+    Ok(property.as_u32s().unwrap_or_default())
+}
+
+/// Whether an EWMH-compliant window manager is running.
+///
+/// The window manager says so by pointing `_NET_SUPPORTING_WM_CHECK` on the root window at a
+/// child window which has the same property pointing at itself. Checking both sides catches a
+/// window manager that crashed and left the root window's property behind.
+pub fn has_window_manager(conn: &mut Connection) -> Result<bool, Error> {
+    let root = conn.root();
+    let Some(&check) =
+        get_property32(conn, root, "_NET_SUPPORTING_WM_CHECK", atoms::WINDOW)?.first()
+    else {
+        return Ok(false);
+    };
+    match get_property32(conn, check, "_NET_SUPPORTING_WM_CHECK", atoms::WINDOW) {
+        Ok(values) => Ok(values.first() == Some(&check)),
+        // the window is gone
+        Err(Error::Protocol(e)) if e.name() == "BadWindow" => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// A window's position and size, relative to the root window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    /// The position of the inside of the window, within its border.
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub border_width: u32,
+}
+
+impl Placement {
+    /// Where a window is on the screen. Its position is translated to root coordinates, since a
+    /// window manager will have reparented it into a frame.
+    pub fn of(conn: &mut Connection, window: Window) -> Result<Self, Error> {
+        let geometry = Geometry::parse(&conn.request_with_reply(&protocol::get_geometry(window))?)?;
+        let translated = TranslatedCoordinates::parse(&conn.request_with_reply(
+            &protocol::translate_coordinates(window, geometry.root, 0, 0),
+        )?)?;
+        Ok(Self {
+            x: translated.x.into(),
+            y: translated.y.into(),
+            width: geometry.width.into(),
+            height: geometry.height.into(),
+            border_width: geometry.border_width.into(),
+        })
+    }
+
+    /// The changes that put a (top-level, unmanaged) window back here. Windows are positioned by
+    /// the outside of their border.
+    fn changes(&self) -> WindowChanges {
+        let border = self.border_width as i32;
+        WindowChanges {
+            x: Some(self.x - border),
+            y: Some(self.y - border),
+            width: Some(self.width),
+            height: Some(self.height),
+            border_width: Some(self.border_width),
+            ..Default::default()
+        }
+    }
+}
+
+/// Switches a window in and out of borderless fullscreen, remembering where it was.
+///
+/// With a window manager, this asks it for `_NET_WM_STATE_FULLSCREEN`, and the window manager
+/// picks the monitor, removes the frame and puts the window back afterwards. Without one (e.g.
+/// on a bare Xvfb), the window is moved to cover its RandR monitor (or the whole screen) and moved
+/// back by hand.
+#[derive(Debug, Default)]
+pub struct Fullscreen {
+    /// Where the window was before it went fullscreen, and whether a window manager is doing the
+    /// work.
+    saved: Option<(Placement, bool)>,
+}
+
+impl Fullscreen {
+    pub const fn new() -> Self {
+        Self { saved: None }
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.saved.is_some()
+    }
+
+    /// Makes the window cover its monitor. Does nothing if it's already fullscreen.
+    pub fn enter(&mut self, conn: &mut Connection, window: Window) -> Result<(), Error> {
+        if self.saved.is_some() {
+            return Ok(());
+        }
+        let placement = Placement::of(conn, window)?;
+        let managed = has_window_manager(conn)?;
+
+        let attributes = GetWindowAttributesReply::parse(
+            &conn.request_with_reply(&protocol::get_window_attributes(window))?,
+        )?;
+        let net_wm_state = conn.intern_atom("_NET_WM_STATE")?;
+        let fullscreen = conn.intern_atom("_NET_WM_STATE_FULLSCREEN")?;
+        if managed && attributes.map_state != MapState::Unmapped {
+            send_state_message(conn, window, StateAction::Add, "_NET_WM_STATE_FULLSCREEN")?;
+        } else {
+            // the window manager reads the property when the window is mapped, and without one
+            // it's just there for anyone who asks
+            let mut states = get_property32(conn, window, "_NET_WM_STATE", atoms::ATOM)?;
+            if !states.contains(&fullscreen) {
+                states.push(fullscreen);
+            }
+            conn.request_checked(&protocol::change_property32(
+                PropertyMode::Replace,
+                window,
+                net_wm_state,
+                atoms::ATOM,
+                &states,
+            ))?;
+        }
+
+        if !managed {
+            let cover = monitor_placement(conn, &placement)?;
+            conn.request_checked(&protocol::configure_window(
+                window,
+                &WindowChanges {
+                    stack_mode: Some(STACK_MODE_ABOVE),
+                    ..cover.changes()
+                },
+            ))?;
+        }
+
+        self.saved = Some((placement, managed));
+        Ok(())
+    }
+
+    /// Puts the window back where it was before [`enter`](Self::enter). Does nothing if it isn't
+    /// fullscreen.
+    pub fn exit(&mut self, conn: &mut Connection, window: Window) -> Result<(), Error> {
+        let Some((placement, managed)) = self.saved.take() else {
+            return Ok(());
+        };
+        if managed {
+            return send_state_message(
+                conn,
+                window,
+                StateAction::Remove,
+                "_NET_WM_STATE_FULLSCREEN",
+            );
+        }
+
+        let net_wm_state = conn.intern_atom("_NET_WM_STATE")?;
+        let fullscreen = conn.intern_atom("_NET_WM_STATE_FULLSCREEN")?;
+        let mut states = get_property32(conn, window, "_NET_WM_STATE", atoms::ATOM)?;
+        states.retain(|&state| state != fullscreen);
+        conn.request_checked(&protocol::change_property32(
+            PropertyMode::Replace,
+            window,
+            net_wm_state,
+            atoms::ATOM,
+            &states,
+        ))?;
+        conn.request_checked(&protocol::configure_window(window, &placement.changes()))
+    }
+
+    /// Enters fullscreen if the window is windowed, and exits it otherwise, like an F11 key does.
+    ///
+    /// **Returns:** Whether the window is now fullscreen.
+    pub fn toggle(&mut self, conn: &mut Connection, window: Window) -> Result<bool, Error> {
+        if self.is_fullscreen() {
+            self.exit(conn, window)?;
+        } else {
+            self.enter(conn, window)?;
+        }
+        Ok(self.is_fullscreen())
+    }
+}
+
+/// Where a window covering the monitor that `placement`'s center is on would be. Without RandR
+/// (or any monitors turned on), that's the whole screen.
+fn monitor_placement(conn: &mut Connection, placement: &Placement) -> Result<Placement, Error> {
+    let center = [
+        placement.x + (placement.width / 2) as i32,
+        placement.y + (placement.height / 2) as i32,
+    ];
+    let monitors = match monitor::monitors(conn) {
+        Ok(monitors) => monitors,
+        Err(Error::MissingExtension(_)) => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    let monitor = monitors
+        .iter()
+        .find(|m| m.contains(center))
+        .or_else(|| monitors.iter().find(|m| m.is_primary))
+        .or(monitors.first());
+    Ok(match monitor {
+        Some(monitor) => Placement {
+            x: monitor.position[0],
+            y: monitor.position[1],
+            width: monitor.size[0],
+            height: monitor.size[1],
+            border_width: 0,
+        },
+        None => {
+            let screen = conn.screen();
+            Placement {
+                x: 0,
+                y: 0,
+                width: screen.width_in_pixels.into(),
+                height: screen.height_in_pixels.into(),
+                border_width: 0,
+            }
+        }
+    })
+}
+
+/// The sizes that [`set_icon`] scales icons down to, for window managers that don't scale them
+/// well themselves.
+pub const ICON_SIZES: [u32; 3] = [16, 32, 48];
+
+/// Encodes icons for `_NET_WM_ICON`: each one's width and height, and then its pixels as ARGB,
+/// row by row.
+pub fn icon_property(images: &[RgbaImage]) -> Vec<u32> {
+    let mut data = Vec::new();
+    for image in images {
+        data.extend([image.width, image.height]);
+        data.extend(
+            image
+                .pixels
+                .chunks_exact(4)
+                .map(|p| u32::from_be_bytes([p[3], p[0], p[1], p[2]])),
+        );
+    }
+    data
+}
+
+/// Sets the icon that the window manager shows for the window, from straight RGBA pixels, row by
+/// row.
+///
+/// The image is sent at its own size, and scaled down to each of [`ICON_SIZES`] that's smaller,
+/// and the window manager picks whichever it likes.
+///
+/// Fails with [`Error::BadIcon`] if `rgba` isn't `width * height * 4` bytes long, or the image is
+/// empty.
+pub fn set_icon(
+    conn: &mut Connection,
+    window: Window,
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+) -> Result<(), Error> {
+    if width == 0 || height == 0 || rgba.len() as u64 != u64::from(width) * u64::from(height) * 4 {
+        return Err(Error::BadIcon {
+            width,
+            height,
+            len: rgba.len(),
+        });
+    }
+    let image = RgbaImage::new(width, height, rgba.to_vec());
+    let mut images: Vec<RgbaImage> = ICON_SIZES
+        .iter()
+        .filter(|&&size| size < width.max(height))
+        .map(|&size| {
+            // keeping the aspect ratio
+            let scale = f64::from(size) / f64::from(width.max(height));
+            let scaled = |n: u32| ((f64::from(n) * scale).round() as u32).max(1);
+            image.resized(scaled(width), scaled(height))
+        })
+        .collect();
+    images.push(image);
+    set_icon_images(conn, window, &images)
+}
+
+/// Sets the window's icon to images that are already the sizes they should be, e.g. the ones in
+/// an `.ico` file (see [`image::ico`]). No images at all removes the icon.
+pub fn set_icon_images(
+    conn: &mut Connection,
+    window: Window,
+    images: &[RgbaImage],
+) -> Result<(), Error> {
+    let property = conn.intern_atom("_NET_WM_ICON")?;
+    let data = icon_property(images);
+    // big icons don't fit in one request, so the rest are appended. ChangeProperty's header is
+    // 24 bytes.
+    let per_request = usize::from(conn.setup().maximum_request_length) - 6;
+    let mut mode = PropertyMode::Replace;
+    for chunk in data.chunks(per_request) {
+        conn.request_checked(&protocol::change_property32(
+            mode,
+            window,
+            property,
+            atoms::CARDINAL,
+            chunk,
+        ))?;
+        mode = PropertyMode::Append;
+    }
+    if data.is_empty() {
+        conn.request_checked(&protocol::delete_property(window, property))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_messages() {
+        let event = state_message(300, 0x20_0001, StateAction::Toggle, 301, NONE);
+        let protocol::Event::ClientMessage(message) = protocol::Event::parse(&event).unwrap()
+        else {
+            panic!("not a client message");
+        };
+        assert_eq!(message.format, 32);
+        assert_eq!((message.window, message.type_), (0x20_0001, 300));
+        assert_eq!(message.data32(), [2, 301, 0, 1, 0]);
+    }
+
+    #[test]
+    fn icons() {
+        let images = [
+            RgbaImage::new(1, 1, vec![0x11, 0x22, 0x33, 0x80]),
+            RgbaImage::new(2, 1, vec![0xFF, 0, 0, 0xFF, 0, 0, 0, 0]),
+        ];
+        assert_eq!(
+            icon_property(&images),
+            [1, 1, 0x8011_2233, 2, 1, 0xFFFF_0000, 0]
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Turning `KeyPress` and `KeyRelease` events into [`KeyEvent`]s and text, using the server's
+//! keyboard mapping.
+//!
+//! This is the core protocol's idea of a keyboard: each keycode has a list of keysyms, and shift,
+//! caps lock, num lock and `Mod5` pick between them. There's no compose key or input method, so
+//! text is whatever the pressed key's keysym types.
+//!
+//! Everything but loading the mapping is plain Rust, so [`Keymap`] can be tested without a server.
+
+use keyboard::{keysym, scan_codes, KeyEvent, KeyState, KeyboardState, Modifiers};
+
+use crate::protocol::{key_masks, InputEvent, KeyboardMapping, Keycode, Keysym};
+#[cfg(unix)]
+use crate::{
+    connection::{Connection, Error},
+    protocol, xkb,
+};
+
+/// A keyboard mapping, and which keys are held.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    min_keycode: Keycode,
+    mapping: KeyboardMapping,
+    state: KeyboardState,
+}
+
+impl Keymap {
+    /// A keymap for the mapping that was asked for starting at `min_keycode`.
+    pub fn new(min_keycode: Keycode, mapping: KeyboardMapping) -> Self {
+        Self {
+            min_keycode,
+            mapping,
+            state: KeyboardState::new(),
+        }
+    }
+
+    /// The keysym that `keycode` gives with the modifiers in `state`, by the core protocol's rules.
+    ///
+    /// `Mod5` is taken to select the third and fourth shift levels, which XKB puts fifth and sixth
+    /// in the core mapping.
+    pub fn keysym(&self, keycode: Keycode, state: u16) -> Keysym {
+        let keysyms = self.mapping.keysyms(self.min_keycode, keycode);
+        let at = |i: usize| keysyms.get(i).copied().unwrap_or(keysym::NO_SYMBOL);
+        let first = if state & key_masks::MOD5 != 0 && at(4) != keysym::NO_SYMBOL {
+            4
+        } else {
+            0
+        };
+        let (lower, upper) = match (at(first), at(first + 1)) {
+            // a lone keysym is both levels, in lower and upper case if it's a letter
+            (lower, keysym::NO_SYMBOL) => (keysym::to_lower(lower), keysym::to_upper(lower)),
+            pair => pair,
+        };
+
+        let shift = state & key_masks::SHIFT != 0;
+        if state & key_masks::MOD2 != 0 && keysym::is_keypad(upper) {
+            // num lock makes the keypad type digits, unless shift is held
+            return if shift { lower } else { upper };
+        }
+        match (shift, state & key_masks::LOCK != 0) {
+            (false, false) => lower,
+            (false, true) => keysym::to_upper(lower),
+            (true, false) => upper,
+            (true, true) => keysym::to_upper(upper),
+        }
+    }
+
+    /// Decodes a `KeyPress` or `KeyRelease`, and updates which keys are held.
+    ///
+    /// A press of a key that's already held is a repeat, which is only how repeats look after
+    /// [`enable_detectable_auto_repeat`]. Otherwise they look like a release and a new press.
+    pub fn key_event(&mut self, event: &InputEvent, key_state: KeyState) -> KeyEvent {
+        let physical_key = scan_codes::from_x11_keycode(event.detail.into());
+        // the lock keys might have been toggled while another window had the focus
+        self.state.set_locks(
+            event.state & key_masks::LOCK != 0,
+            event.state & key_masks::MOD2 != 0,
+        );
+        let repeat = self.state.update(physical_key, key_state);
+        KeyEvent {
+            physical_key,
+            logical_key: keysym::to_key(self.keysym(event.detail, event.state)),
+            state: key_state,
+            repeat,
+            modifiers: self.state.modifiers(),
+        }
+    }
+
+    /// The text that a `KeyPress` types, if it types any. Nothing's typed while control or alt is
+    /// held, since those are shortcuts.
+    pub fn text(&self, event: &InputEvent) -> Option<char> {
+        if event.state & (key_masks::CONTROL | key_masks::MOD1) != 0 {
+            return None;
+        }
+        keysym::to_char(self.keysym(event.detail, event.state))
+    }
+
+    /// The modifiers that are active, after the last key event.
+    pub fn modifiers(&self) -> Modifiers {
+        self.state.modifiers()
+    }
+
+    /// Forgets about the held keys, which won't get release events while the window doesn't have
+    /// the focus. Call this for `FocusOut` events.
+    pub fn focus_out(&mut self) {
+        self.state.release_all();
+    }
+
+    /// Replaces the mapping, e.g. after a `MappingNotify`.
+    pub fn set_mapping(&mut self, min_keycode: Keycode, mapping: KeyboardMapping) {
+        self.min_keycode = min_keycode;
+        self.mapping = mapping;
+    }
+}
+
+#[cfg(unix)]
+impl Keymap {
+    /// Reads the server's keyboard mapping. Read it again with [`reload`](Self::reload) when a
+    /// `MappingNotify` comes.
+    pub fn load(conn: &mut Connection) -> Result<Self, Error> {
+        let (min_keycode, mapping) = get_mapping(conn)?;
+        Ok(Self::new(min_keycode, mapping))
+    }
+
+    pub fn reload(&mut self, conn: &mut Connection) -> Result<(), Error> {
+        let (min_keycode, mapping) = get_mapping(conn)?;
+        self.set_mapping(min_keycode, mapping);
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn get_mapping(conn: &mut Connection) -> Result<(Keycode, KeyboardMapping), Error> {
+    let (min, max) = (conn.setup().min_keycode, conn.setup().max_keycode);
+    let reply = conn.request_with_reply(&protocol::get_keyboard_mapping(min, max - min + 1))?;
+    Ok((min, KeyboardMapping::parse(&reply)?))
+}
+
+/// Asks the server to leave out the releases of auto-repeating keys, so that [`Keymap`] can tell
+/// repeats apart from new presses.
+///
+/// **Returns:** Whether the server agreed. It won't if it doesn't have XKB.
+#[cfg(unix)]
+pub fn enable_detectable_auto_repeat(conn: &mut Connection) -> Result<bool, Error> {
+    let Some(extension) = conn.query_extension("XKEYBOARD")? else {
+        return Ok(false);
+    };
+    let major = extension.major_opcode;
+    let (supported, _) =
+        xkb::parse_use_extension(&conn.request_with_reply(&xkb::use_extension(major))?)?;
+    if !supported {
+        return Ok(false);
+    }
+    let flag = xkb::per_client::DETECTABLE_AUTO_REPEAT;
+    let (_, value) = xkb::parse_per_client_flags(
+        &conn.request_with_reply(&xkb::per_client_flags(major, flag, flag))?,
+    )?;
+    Ok(value & flag != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use keyboard::{Key, KeyCode, NamedKey};
+
+    use super::*;
+
+    /// A few keys of a German layout, as XKB puts them in the core mapping: two levels of two
+    /// groups, and then the third and fourth levels of the first group.
+    #[rustfmt::skip]
+    fn german() -> Keymap {
+        let mut keysyms = vec![0; 256 * 6];
+        let mut key = |keycode: usize, syms: [Keysym; 6]| {
+            keysyms[(keycode - 8) * 6..][..6].copy_from_slice(&syms);
+        };
+        key(24, [0x71, 0x51, 0x71, 0x51, 0x40, 0x0100_03A9]); // q Q q Q @ Ω
+        key(29, [0x7A, 0x5A, 0x7A, 0x5A, 0x0100_2190, 0xA5]); // z Z z Z ← ¥, where US has y
+        key(34, [0xFC, 0xDC, 0xFC, 0xDC, 0xFE57, 0xFE58]); // ü Ü ü Ü dead_diaeresis dead_abovering
+        key(20, [0xDF, 0x3F, 0xDF, 0x3F, 0x5C, 0x0100_1E9E]); // ß ? ß ? \ ẞ
+        key(50, [0xFFE1, 0, 0, 0, 0, 0]); // Shift_L
+        key(79, [0xFF95, 0xFFB7, 0, 0, 0, 0]); // KP_Home KP_7
+        key(38, [0x61, 0, 0, 0, 0, 0]); // a, with only one keysym
+        Keymap::new(
+            8,
+            KeyboardMapping {
+                keysyms_per_keycode: 6,
+                keysyms,
+            },
+        )
+    }
+
+    fn event(keycode: Keycode, state: u16) -> InputEvent {
+        InputEvent {
+            detail: keycode,
+            time: 0,
+            root: 1,
+            event: 2,
+            child: 0,
+            root_x: 0,
+            root_y: 0,
+            event_x: 0,
+            event_y: 0,
+            state,
+            same_screen: true,
+        }
+    }
+
+    #[test]
+    fn levels() {
+        let keymap = german();
+        assert_eq!(keymap.keysym(24, 0), 0x71);
+        assert_eq!(keymap.keysym(24, key_masks::SHIFT), 0x51);
+        assert_eq!(keymap.keysym(24, key_masks::MOD5), 0x40);
+        assert_eq!(
+            keymap.keysym(24, key_masks::MOD5 | key_masks::SHIFT),
+            0x0100_03A9
+        );
+        // caps lock only changes letters, and shift doesn't undo it
+        assert_eq!(keymap.keysym(34, key_masks::LOCK), 0xDC);
+        assert_eq!(keymap.keysym(20, key_masks::LOCK), 0xDF);
+        assert_eq!(keymap.keysym(24, key_masks::LOCK | key_masks::SHIFT), 0x51);
+        // a lone keysym has an upper case level
+        assert_eq!(keymap.keysym(38, 0), 0x61);
+        assert_eq!(keymap.keysym(38, key_masks::SHIFT), 0x41);
+        // num lock, and shift undoing it
+        assert_eq!(keymap.keysym(79, 0), 0xFF95);
+        assert_eq!(keymap.keysym(79, key_masks::MOD2), 0xFFB7);
+        assert_eq!(
+            keymap.keysym(79, key_masks::MOD2 | key_masks::SHIFT),
+            0xFF95
+        );
+        // keycodes outside the mapping
+        assert_eq!(keymap.keysym(3, 0), keysym::NO_SYMBOL);
+    }
+
+    #[test]
+    fn key_events() {
+        let mut keymap = german();
+        let z = keymap.key_event(&event(29, 0), KeyState::Pressed);
+        assert_eq!(z.physical_key, KeyCode::KeyY);
+        assert_eq!(z.logical_key, Key::Character('z'));
+        assert!(!z.repeat);
+        assert!(keymap.key_event(&event(29, 0), KeyState::Pressed).repeat);
+
+        let shift = keymap.key_event(&event(50, 0), KeyState::Pressed);
+        assert_eq!(shift.logical_key, Key::Named(NamedKey::Shift));
+        assert_eq!(shift.modifiers, Modifiers::SHIFT);
+        let released = keymap.key_event(&event(29, key_masks::SHIFT), KeyState::Released);
+        assert_eq!(released.logical_key, Key::Character('Z'));
+        assert!(!released.repeat);
+
+        let dead = keymap.key_event(&event(34, key_masks::MOD5), KeyState::Pressed);
+        assert_eq!(dead.logical_key, Key::Dead('¨'));
+
+        // caps lock comes from the event's state
+        let caps = keymap.key_event(
+            &event(24, key_masks::LOCK | key_masks::SHIFT),
+            KeyState::Pressed,
+        );
+        assert_eq!(caps.modifiers, Modifiers::SHIFT | Modifiers::CAPS_LOCK);
+
+        // which keys are held is forgotten, but not that caps lock is on
+        keymap.focus_out();
+        assert_eq!(keymap.modifiers(), Modifiers::CAPS_LOCK);
+    }
+
+    #[test]
+    fn text() {
+        let keymap = german();
+        assert_eq!(keymap.text(&event(29, key_masks::SHIFT)), Some('Z'));
+        assert_eq!(keymap.text(&event(24, key_masks::MOD5)), Some('@'));
+        assert_eq!(keymap.text(&event(79, key_masks::MOD2)), Some('7'));
+        // shortcuts, keys that don't type anything, and dead keys
+        assert_eq!(keymap.text(&event(24, key_masks::CONTROL)), None);
+        assert_eq!(keymap.text(&event(24, key_masks::MOD1)), None);
+        assert_eq!(keymap.text(&event(50, 0)), None);
+        assert_eq!(keymap.text(&event(34, key_masks::MOD5)), None);
+    }
+}
@@ -0,0 +1,37 @@
+//! A small X11 client, speaking the wire protocol directly over the server's Unix socket instead
+//! of going through Xlib or xcb.
+//!
+//! [`wire`], [`protocol`], [`randr`], [`render`], [`xinput`], [`xkb`], [`uri_list`] and
+//! [`display`] are plain Rust that only encode and decode bytes, so they're available (and
+//! tested) everywhere. So are [`keymap`], apart from loading the mapping from a server, and
+//! [`xcursor`], which reads cursor themes from disk. [`connection`], [`clipboard`],
+//! [`cursor`], [`dnd`], [`ewmh`] and [`monitor`] need a Unix socket.
+//!
+//! This is only X11: fullscreen on Wayland is `xdg_toplevel.set_fullscreen`, which the `wayland`
+//! crate's `xdg::Fullscreen` sends, with the same `enter`/`exit`/`toggle` as [`ewmh::Fullscreen`].
+
+#[cfg(unix)]
+pub mod clipboard;
+#[cfg(unix)]
+pub mod connection;
+#[cfg(unix)]
+pub mod cursor;
+pub mod display;
+#[cfg(unix)]
+pub mod dnd;
+#[cfg(unix)]
+pub mod ewmh;
+pub mod keymap;
+#[cfg(unix)]
+pub mod monitor;
+pub mod protocol;
+pub mod randr;
+pub mod render;
+pub mod uri_list;
+pub mod wire;
+pub mod xcursor;
+pub mod xinput;
+pub mod xkb;
+
+#[cfg(unix)]
+pub use connection::{Connection, Error};
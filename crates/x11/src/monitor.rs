@@ -0,0 +1,153 @@
+//! Listing the monitors plugged into the screen, and the display modes each of them supports,
+//! using [`randr`](crate::randr).
+
+use crate::{
+    connection::{Connection, Error},
+    protocol::{self, atoms, Property, NONE},
+    randr::{self, CrtcInfo, OutputInfo, ScreenResources},
+};
+
+/// The DPI that a scale factor of `1.0` corresponds to.
+pub const DEFAULT_DPI: f64 = 96.0;
+
+/// A display mode that a monitor supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VideoMode {
+    /// The resolution, in pixels.
+    pub size: [u32; 2],
+    pub bit_depth: u32,
+    /// The refresh rate, or `None` if the mode's timings don't say.
+    pub refresh_rate_millihertz: Option<u32>,
+}
+
+/// A monitor that's plugged in and turned on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Monitor {
+    /// The RandR output it's plugged into.
+    pub output: randr::Output,
+    /// The output's name, e.g. `HDMI-1`.
+    pub name: String,
+    /// Where the monitor's top left corner is on the screen, in pixels.
+    pub position: [i32; 2],
+    /// The size in pixels, after rotation.
+    pub size: [u32; 2],
+    /// The size in millimeters, or zero if the monitor doesn't say.
+    pub physical_size_mm: [u32; 2],
+    /// The current mode's refresh rate.
+    pub refresh_rate_millihertz: Option<u32>,
+    /// How many pixels there are for each logical pixel, from the `Xft.dpi` setting. X11 has no
+    /// per-monitor scaling, so this is the same for every monitor.
+    pub scale_factor: f64,
+    pub is_primary: bool,
+    /// Every display mode the monitor supports, largest and fastest first.
+    pub modes: Vec<VideoMode>,
+}
+
+impl Monitor {
+    /// Whether a point on the screen is on this monitor.
+    pub fn contains(&self, [x, y]: [i32; 2]) -> bool {
+        let [x, y, left, top] = [x, y, self.position[0], self.position[1]].map(i64::from);
+        let [width, height] = self.size.map(i64::from);
+        (left..left + width).contains(&x) && (top..top + height).contains(&y)
+    }
+}
+
+/// Lists the monitors that are plugged in and turned on, in the order RandR lists their outputs.
+///
+/// Fails with [`Error::MissingExtension`] if the server doesn't have RandR 1.3 or later.
+pub fn monitors(conn: &mut Connection) -> Result<Vec<Monitor>, Error> {
+    let major = randr_opcode(conn)?;
+    let root = conn.root();
+    let bit_depth = conn.screen().root_depth.into();
+    let scale_factor = xft_dpi(conn)?.unwrap_or(DEFAULT_DPI) / DEFAULT_DPI;
+
+    let resources = ScreenResources::parse(
+        &conn.request_with_reply(&randr::get_screen_resources_current(major, root))?,
+    )?;
+    let primary = randr::parse_output_primary(
+        &conn.request_with_reply(&randr::get_output_primary(major, root))?,
+    )?;
+
+    let mut monitors = Vec::new();
+    for &output in &resources.outputs {
+        let info = OutputInfo::parse(&conn.request_with_reply(&randr::get_output_info(
+            major,
+            output,
+            resources.config_timestamp,
+        ))?)?;
+        // a nonzero status means the configuration changed under us, and the output is skipped
+        if info.status != 0 || info.connection != randr::Connection::Connected || info.crtc == NONE
+        {
+            continue;
+        }
+        let crtc = CrtcInfo::parse(&conn.request_with_reply(&randr::get_crtc_info(
+            major,
+            info.crtc,
+            resources.config_timestamp,
+        ))?)?;
+        if crtc.status != 0 || crtc.mode == NONE {
+            continue;
+        }
+
+        let mut modes: Vec<VideoMode> = info
+            .modes
+            .iter()
+            .filter_map(|&id| resources.mode(id))
+            .map(|mode| VideoMode {
+                size: [mode.width.into(), mode.height.into()],
+                bit_depth,
+                refresh_rate_millihertz: mode.refresh_rate_millihertz(),
+            })
+            .collect();
+        modes.sort_unstable_by(|a, b| b.cmp(a));
+        modes.dedup();
+
+        monitors.push(Monitor {
+            output,
+            name: info.name,
+            position: [crtc.x.into(), crtc.y.into()],
+            size: [crtc.width.into(), crtc.height.into()],
+            physical_size_mm: [info.mm_width, info.mm_height],
+            refresh_rate_millihertz: resources
+                .mode(crtc.mode)
+                .and_then(|mode| mode.refresh_rate_millihertz()),
+            scale_factor,
+            is_primary: output == primary,
+            modes,
+        });
+    }
+    Ok(monitors)
+}
+
+/// Finds RandR's major opcode, and checks that the server speaks a new enough version of it.
+fn randr_opcode(conn: &mut Connection) -> Result<u8, Error> {
+    let Some(extension) = conn.query_extension("RANDR")? else {
+        return Err(Error::MissingExtension("RANDR"));
+    };
+    let major = extension.major_opcode;
+    let version =
+        randr::parse_query_version(&conn.request_with_reply(&randr::query_version(major))?)?;
+    if version < randr::VERSION {
+        return Err(Error::MissingExtension("RANDR 1.3"));
+    }
+    Ok(major)
+}
+
+/// Reads the `Xft.dpi` setting from the X resources, if it's set.
+fn xft_dpi(conn: &mut Connection) -> Result<Option<f64>, Error> {
+    let root = conn.root();
+    let resource_manager = conn.intern_atom("RESOURCE_MANAGER")?;
+    let reply = conn.request_with_reply(&protocol::get_property(
+        false,
+        root,
+        resource_manager,
+        atoms::STRING,
+        0,
+        // in four-byte units
+        64 * 1024,
+    ))?;
+    let property = Property::parse(&reply)?;
+    Ok(randr::parse_xft_dpi(&String::from_utf8_lossy(
+        &property.value,
+    )))
+}
@@ -0,0 +1,1941 @@
+//! The core X11 protocol: the connection setup, requests, replies, events and errors.
+//!
+//! Requests are encoded by plain functions that return the bytes to send, and replies and events
+//! are decoded from the bytes received, so none of this needs a server to test. See the
+//! [protocol specification](https://www.x.org/releases/X11R7.7/doc/xproto/x11protocol.html) for
+//! what each field means.
+
+use core::fmt;
+
+use crate::wire::{pad, ParseError, Reader, RequestBuilder};
+
+pub type Atom = u32;
+pub type Colormap = u32;
+pub type Cursor = u32;
+pub type Drawable = u32;
+pub type Gcontext = u32;
+pub type Keycode = u8;
+pub type Keysym = u32;
+pub type Pixmap = u32;
+pub type Timestamp = u32;
+pub type VisualId = u32;
+pub type Window = u32;
+
+/// The "nothing" value for windows, atoms, pixmaps and so on.
+pub const NONE: u32 = 0;
+/// The time value meaning "now".
+pub const CURRENT_TIME: Timestamp = 0;
+
+pub mod opcodes {
+    pub const CREATE_WINDOW: u8 = 1;
+    pub const CHANGE_WINDOW_ATTRIBUTES: u8 = 2;
+    pub const GET_WINDOW_ATTRIBUTES: u8 = 3;
+    pub const DESTROY_WINDOW: u8 = 4;
+    pub const MAP_WINDOW: u8 = 8;
+    pub const UNMAP_WINDOW: u8 = 10;
+    pub const CONFIGURE_WINDOW: u8 = 12;
+    pub const GET_GEOMETRY: u8 = 14;
+    pub const INTERN_ATOM: u8 = 16;
+    pub const CHANGE_PROPERTY: u8 = 18;
+    pub const DELETE_PROPERTY: u8 = 19;
+    pub const GET_PROPERTY: u8 = 20;
+    pub const SET_SELECTION_OWNER: u8 = 22;
+    pub const GET_SELECTION_OWNER: u8 = 23;
+    pub const CONVERT_SELECTION: u8 = 24;
+    pub const SEND_EVENT: u8 = 25;
+    pub const GRAB_POINTER: u8 = 26;
+    pub const UNGRAB_POINTER: u8 = 27;
+    pub const TRANSLATE_COORDINATES: u8 = 40;
+    pub const GET_INPUT_FOCUS: u8 = 43;
+    pub const CREATE_PIXMAP: u8 = 53;
+    pub const FREE_PIXMAP: u8 = 54;
+    pub const CREATE_GC: u8 = 55;
+    pub const FREE_GC: u8 = 60;
+    pub const PUT_IMAGE: u8 = 72;
+    pub const CREATE_CURSOR: u8 = 93;
+    pub const FREE_CURSOR: u8 = 95;
+    pub const QUERY_EXTENSION: u8 = 98;
+    pub const GET_KEYBOARD_MAPPING: u8 = 101;
+}
+
+pub mod event_codes {
+    pub const KEY_PRESS: u8 = 2;
+    pub const KEY_RELEASE: u8 = 3;
+    pub const BUTTON_PRESS: u8 = 4;
+    pub const BUTTON_RELEASE: u8 = 5;
+    pub const MOTION_NOTIFY: u8 = 6;
+    pub const FOCUS_IN: u8 = 9;
+    pub const FOCUS_OUT: u8 = 10;
+    pub const EXPOSE: u8 = 12;
+    pub const DESTROY_NOTIFY: u8 = 17;
+    pub const UNMAP_NOTIFY: u8 = 18;
+    pub const MAP_NOTIFY: u8 = 19;
+    pub const REPARENT_NOTIFY: u8 = 21;
+    pub const CONFIGURE_NOTIFY: u8 = 22;
+    pub const PROPERTY_NOTIFY: u8 = 28;
+    pub const SELECTION_CLEAR: u8 = 29;
+    pub const SELECTION_REQUEST: u8 = 30;
+    pub const SELECTION_NOTIFY: u8 = 31;
+    pub const CLIENT_MESSAGE: u8 = 33;
+    pub const MAPPING_NOTIFY: u8 = 34;
+    pub const GENERIC_EVENT: u8 = 35;
+}
+
+/// Which events a window reports, for [`WindowAttributes::event_mask`] and [`send_event`].
+pub mod event_masks {
+    pub const KEY_PRESS: u32 = 1 << 0;
+    pub const KEY_RELEASE: u32 = 1 << 1;
+    pub const BUTTON_PRESS: u32 = 1 << 2;
+    pub const BUTTON_RELEASE: u32 = 1 << 3;
+    pub const ENTER_WINDOW: u32 = 1 << 4;
+    pub const LEAVE_WINDOW: u32 = 1 << 5;
+    pub const POINTER_MOTION: u32 = 1 << 6;
+    pub const EXPOSURE: u32 = 1 << 15;
+    pub const STRUCTURE_NOTIFY: u32 = 1 << 17;
+    pub const SUBSTRUCTURE_NOTIFY: u32 = 1 << 19;
+    pub const SUBSTRUCTURE_REDIRECT: u32 = 1 << 20;
+    pub const FOCUS_CHANGE: u32 = 1 << 21;
+    pub const PROPERTY_CHANGE: u32 = 1 << 22;
+}
+
+/// The bits of an [`InputEvent`]'s `state`: which modifiers and buttons were held just before the
+/// event.
+///
+/// Which keys the `MOD*` bits are is up to the server's modifier mapping, but these are what
+/// every common setup uses.
+pub mod key_masks {
+    pub const SHIFT: u16 = 1 << 0;
+    /// Caps lock is on.
+    pub const LOCK: u16 = 1 << 1;
+    pub const CONTROL: u16 = 1 << 2;
+    /// Usually alt.
+    pub const MOD1: u16 = 1 << 3;
+    /// Usually num lock being on.
+    pub const MOD2: u16 = 1 << 4;
+    /// Usually super.
+    pub const MOD4: u16 = 1 << 6;
+    /// Usually AltGr, i.e. the third shift level.
+    pub const MOD5: u16 = 1 << 7;
+}
+
+/// Predefined atoms, which don't need interning.
+pub mod atoms {
+    use super::Atom;
+
+    pub const PRIMARY: Atom = 1;
+    pub const ATOM: Atom = 4;
+    pub const CARDINAL: Atom = 6;
+    pub const INTEGER: Atom = 19;
+    pub const STRING: Atom = 31;
+    pub const WINDOW: Atom = 33;
+    pub const WM_NAME: Atom = 39;
+}
+
+/// Window classes, for [`create_window`].
+pub mod window_class {
+    pub const COPY_FROM_PARENT: u16 = 0;
+    pub const INPUT_OUTPUT: u16 = 1;
+    pub const INPUT_ONLY: u16 = 2;
+}
+
+/// How [`change_property`] combines the new data with what's already there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum PropertyMode {
+    Replace = 0,
+    Prepend = 1,
+    Append = 2,
+}
+
+/// The byte order that [`setup_request`] asks for: little-endian.
+pub const BYTE_ORDER_LSB_FIRST: u8 = b'l';
+
+/// The first thing a client sends: which protocol version and byte order it wants, and how it's
+/// authorized.
+pub fn setup_request(auth_name: &[u8], auth_data: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![BYTE_ORDER_LSB_FIRST, 0];
+    bytes.extend_from_slice(&11u16.to_le_bytes());
+    bytes.extend_from_slice(&0u16.to_le_bytes());
+    bytes.extend_from_slice(&(auth_name.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(&(auth_data.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(&[0, 0]);
+    for field in [auth_name, auth_data] {
+        bytes.extend_from_slice(field);
+        bytes.resize(bytes.len() + pad(field.len()), 0);
+    }
+    bytes
+}
+
+/// The length of the fixed part of the server's reply to [`setup_request`].
+pub const SETUP_HEADER_LEN: usize = 8;
+
+/// How long the whole setup reply is, given its first [`SETUP_HEADER_LEN`] bytes.
+pub fn setup_reply_len(header: &[u8; SETUP_HEADER_LEN]) -> usize {
+    SETUP_HEADER_LEN + 4 * u16::from_le_bytes([header[6], header[7]]) as usize
+}
+
+/// Why the server didn't accept a connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SetupError {
+    /// The server refused the connection, e.g. because the client isn't authorized.
+    Failed {
+        reason: String,
+    },
+    /// The server wants more authentication, which isn't supported.
+    Authenticate {
+        reason: String,
+    },
+    Parse(ParseError),
+}
+
+impl fmt::Display for SetupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Failed { reason } => write!(f, "X server refused the connection: {reason}"),
+            Self::Authenticate { reason } => {
+                write!(f, "X server wants further authentication: {reason}")
+            }
+            Self::Parse(e) => write!(f, "malformed X11 connection setup: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SetupError {}
+
+impl From<ParseError> for SetupError {
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+/// What the server says about itself when a client connects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setup {
+    pub protocol_major_version: u16,
+    pub protocol_minor_version: u16,
+    pub release_number: u32,
+    /// Added to [`resource_id_mask`](Self::resource_id_mask) bits to make IDs for new resources.
+    pub resource_id_base: u32,
+    pub resource_id_mask: u32,
+    /// The longest request the server accepts, in four-byte units.
+    pub maximum_request_length: u16,
+    pub min_keycode: u8,
+    pub max_keycode: u8,
+    pub vendor: String,
+    pub screens: Vec<Screen>,
+}
+
+/// One of the server's screens, which is usually all the monitors together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screen {
+    pub root: Window,
+    pub default_colormap: Colormap,
+    pub white_pixel: u32,
+    pub black_pixel: u32,
+    pub width_in_pixels: u16,
+    pub height_in_pixels: u16,
+    pub width_in_millimeters: u16,
+    pub height_in_millimeters: u16,
+    pub root_visual: VisualId,
+    pub root_depth: u8,
+    pub depths: Vec<Depth>,
+}
+
+/// The visuals a screen supports at one depth.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Depth {
+    pub depth: u8,
+    pub visuals: Vec<Visual>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Visual {
+    pub id: VisualId,
+    /// E.g. `4` for TrueColor.
+    pub class: u8,
+    pub bits_per_rgb_value: u8,
+    pub colormap_entries: u16,
+    pub red_mask: u32,
+    pub green_mask: u32,
+    pub blue_mask: u32,
+}
+
+impl Setup {
+    /// Parses the server's whole reply to [`setup_request`].
+    pub fn parse(bytes: &[u8]) -> Result<Self, SetupError> {
+        let mut r = Reader::new(bytes);
+        let status = r.u8()?;
+        let reason_len = r.u8()? as usize;
+        let protocol_major_version = r.u16()?;
+        let protocol_minor_version = r.u16()?;
+        r.skip(2)?;
+        match status {
+            1 => {}
+            0 | 2 => {
+                // `Authenticate` replies don't have a length byte, and give the reason a length
+                // implied by the message's length instead
+                let reason_len = if status == 0 {
+                    reason_len
+                } else {
+                    r.remaining()
+                };
+                let reason = String::from_utf8_lossy(r.bytes(reason_len)?)
+                    .trim_end_matches(['\0', '\n'])
+                    .to_string();
+                return Err(if status == 0 {
+                    SetupError::Failed { reason }
+                } else {
+                    SetupError::Authenticate { reason }
+                });
+            }
+            _ => {
+                return Err(SetupError::Failed {
+                    reason: format!("unknown setup status {status}"),
+                })
+            }
+        }
+
+        let release_number = r.u32()?;
+        let resource_id_base = r.u32()?;
+        let resource_id_mask = r.u32()?;
+        let _motion_buffer_size = r.u32()?;
+        let vendor_len = r.u16()? as usize;
+        let maximum_request_length = r.u16()?;
+        let screen_count = r.u8()?;
+        let format_count = r.u8()? as usize;
+        // image byte order, bitmap bit order, bitmap scanline unit and pad
+        r.skip(4)?;
+        let min_keycode = r.u8()?;
+        let max_keycode = r.u8()?;
+        r.skip(4)?;
+        let vendor = String::from_utf8_lossy(r.bytes(vendor_len)?).into_owned();
+        r.align()?;
+        // pixmap formats aren't needed for anything yet
+        r.skip(8 * format_count)?;
+
+        let screens = (0..screen_count)
+            .map(|_| Screen::parse(&mut r))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            protocol_major_version,
+            protocol_minor_version,
+            release_number,
+            resource_id_base,
+            resource_id_mask,
+            maximum_request_length,
+            min_keycode,
+            max_keycode,
+            vendor,
+            screens,
+        })
+    }
+}
+
+impl Screen {
+    fn parse(r: &mut Reader<'_>) -> Result<Self, ParseError> {
+        let root = r.u32()?;
+        let default_colormap = r.u32()?;
+        let white_pixel = r.u32()?;
+        let black_pixel = r.u32()?;
+        let _current_input_masks = r.u32()?;
+        let width_in_pixels = r.u16()?;
+        let height_in_pixels = r.u16()?;
+        let width_in_millimeters = r.u16()?;
+        let height_in_millimeters = r.u16()?;
+        let _min_installed_maps = r.u16()?;
+        let _max_installed_maps = r.u16()?;
+        let root_visual = r.u32()?;
+        let _backing_stores = r.u8()?;
+        let _save_unders = r.u8()?;
+        let root_depth = r.u8()?;
+        let depth_count = r.u8()?;
+
+        let depths = (0..depth_count)
+            .map(|_| {
+                let depth = r.u8()?;
+                r.skip(1)?;
+                let visual_count = r.u16()?;
+                r.skip(4)?;
+                let visuals = (0..visual_count)
+                    .map(|_| {
+                        let visual = Visual {
+                            id: r.u32()?,
+                            class: r.u8()?,
+                            bits_per_rgb_value: r.u8()?,
+                            colormap_entries: r.u16()?,
+                            red_mask: r.u32()?,
+                            green_mask: r.u32()?,
+                            blue_mask: r.u32()?,
+                        };
+                        r.skip(4)?;
+                        Ok(visual)
+                    })
+                    .collect::<Result<_, ParseError>>()?;
+                Ok(Depth { depth, visuals })
+            })
+            .collect::<Result<_, ParseError>>()?;
+
+        Ok(Self {
+            root,
+            default_colormap,
+            white_pixel,
+            black_pixel,
+            width_in_pixels,
+            height_in_pixels,
+            width_in_millimeters,
+            height_in_millimeters,
+            root_visual,
+            root_depth,
+            depths,
+        })
+    }
+}
+
+/// Optional window attributes, for [`create_window`] and [`change_window_attributes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WindowAttributes {
+    pub background_pixel: Option<u32>,
+    pub border_pixel: Option<u32>,
+    /// Whether the window manager should leave the window alone.
+    pub override_redirect: Option<bool>,
+    /// Which events the window reports to this client. See [`event_masks`].
+    pub event_mask: Option<u32>,
+    pub colormap: Option<Colormap>,
+    pub cursor: Option<Cursor>,
+}
+
+impl WindowAttributes {
+    /// The value mask and list for the attributes that are set.
+    fn values(&self) -> (u32, Vec<u32>) {
+        let fields = [
+            (1 << 1, self.background_pixel),
+            (1 << 3, self.border_pixel),
+            (1 << 9, self.override_redirect.map(u32::from)),
+            (1 << 11, self.event_mask),
+            (1 << 13, self.colormap),
+            (1 << 14, self.cursor),
+        ];
+        let mut mask = 0;
+        let mut values = Vec::new();
+        for (bit, value) in fields {
+            if let Some(value) = value {
+                mask |= bit;
+                values.push(value);
+            }
+        }
+        (mask, values)
+    }
+}
+
+/// Optional changes to a window's geometry, for [`configure_window`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WindowChanges {
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub border_width: Option<u32>,
+    /// Where the window goes in the stacking order, e.g. [`STACK_MODE_ABOVE`].
+    pub stack_mode: Option<u32>,
+}
+
+/// Puts a window on top of its siblings, with [`WindowChanges::stack_mode`].
+pub const STACK_MODE_ABOVE: u32 = 0;
+
+impl WindowChanges {
+    fn values(&self) -> (u16, Vec<u32>) {
+        let fields = [
+            (1 << 0, self.x.map(|x| x as u32)),
+            (1 << 1, self.y.map(|y| y as u32)),
+            (1 << 2, self.width),
+            (1 << 3, self.height),
+            (1 << 4, self.border_width),
+            (1 << 6, self.stack_mode),
+        ];
+        let mut mask = 0;
+        let mut values = Vec::new();
+        for (bit, value) in fields {
+            if let Some(value) = value {
+                mask |= bit;
+                values.push(value);
+            }
+        }
+        (mask, values)
+    }
+}
+
+/// Everything needed to create a window, for [`create_window`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CreateWindow {
+    pub depth: u8,
+    pub id: Window,
+    pub parent: Window,
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
+    pub border_width: u16,
+    /// One of the [`window_class`] constants.
+    pub class: u16,
+    pub visual: VisualId,
+    pub attributes: WindowAttributes,
+}
+
+pub fn create_window(window: &CreateWindow) -> Vec<u8> {
+    let (mask, values) = window.attributes.values();
+    values
+        .into_iter()
+        .fold(
+            RequestBuilder::new(opcodes::CREATE_WINDOW, window.depth)
+                .u32(window.id)
+                .u32(window.parent)
+                .i16(window.x)
+                .i16(window.y)
+                .u16(window.width)
+                .u16(window.height)
+                .u16(window.border_width)
+                .u16(window.class)
+                .u32(window.visual)
+                .u32(mask),
+            RequestBuilder::u32,
+        )
+        .finish()
+}
+
+pub fn change_window_attributes(window: Window, attributes: &WindowAttributes) -> Vec<u8> {
+    let (mask, values) = attributes.values();
+    values
+        .into_iter()
+        .fold(
+            RequestBuilder::new(opcodes::CHANGE_WINDOW_ATTRIBUTES, 0)
+                .u32(window)
+                .u32(mask),
+            RequestBuilder::u32,
+        )
+        .finish()
+}
+
+pub fn get_window_attributes(window: Window) -> Vec<u8> {
+    RequestBuilder::new(opcodes::GET_WINDOW_ATTRIBUTES, 0)
+        .u32(window)
+        .finish()
+}
+
+pub fn destroy_window(window: Window) -> Vec<u8> {
+    RequestBuilder::new(opcodes::DESTROY_WINDOW, 0)
+        .u32(window)
+        .finish()
+}
+
+pub fn map_window(window: Window) -> Vec<u8> {
+    RequestBuilder::new(opcodes::MAP_WINDOW, 0)
+        .u32(window)
+        .finish()
+}
+
+pub fn unmap_window(window: Window) -> Vec<u8> {
+    RequestBuilder::new(opcodes::UNMAP_WINDOW, 0)
+        .u32(window)
+        .finish()
+}
+
+pub fn configure_window(window: Window, changes: &WindowChanges) -> Vec<u8> {
+    let (mask, values) = changes.values();
+    values
+        .into_iter()
+        .fold(
+            RequestBuilder::new(opcodes::CONFIGURE_WINDOW, 0)
+                .u32(window)
+                .u16(mask)
+                .skip(2),
+            RequestBuilder::u32,
+        )
+        .finish()
+}
+
+pub fn get_geometry(drawable: Drawable) -> Vec<u8> {
+    RequestBuilder::new(opcodes::GET_GEOMETRY, 0)
+        .u32(drawable)
+        .finish()
+}
+
+/// Looks up the atom for `name`, creating it unless `only_if_exists` is set.
+pub fn intern_atom(only_if_exists: bool, name: &str) -> Vec<u8> {
+    RequestBuilder::new(opcodes::INTERN_ATOM, only_if_exists.into())
+        .u16(name.len() as u16)
+        .skip(2)
+        .bytes(name.as_bytes())
+        .finish()
+}
+
+/// Sets a property to some data, made of 8-, 16- or 32-bit values depending on `format`.
+pub fn change_property(
+    mode: PropertyMode,
+    window: Window,
+    property: Atom,
+    type_: Atom,
+    format: u8,
+    data: &[u8],
+) -> Vec<u8> {
+    let unit = usize::from(format / 8).max(1);
+    RequestBuilder::new(opcodes::CHANGE_PROPERTY, mode as u8)
+        .u32(window)
+        .u32(property)
+        .u32(type_)
+        .u8(format)
+        .skip(3)
+        .u32((data.len() / unit) as u32)
+        .bytes(data)
+        .finish()
+}
+
+/// [`change_property`] with a list of 32-bit values, like atoms or cardinals.
+pub fn change_property32(
+    mode: PropertyMode,
+    window: Window,
+    property: Atom,
+    type_: Atom,
+    data: &[u32],
+) -> Vec<u8> {
+    let bytes: Vec<u8> = data.iter().flat_map(|v| v.to_le_bytes()).collect();
+    change_property(mode, window, property, type_, 32, &bytes)
+}
+
+pub fn delete_property(window: Window, property: Atom) -> Vec<u8> {
+    RequestBuilder::new(opcodes::DELETE_PROPERTY, 0)
+        .u32(window)
+        .u32(property)
+        .finish()
+}
+
+/// Reads (part of) a property. `offset` and `length` are in four-byte units, and `type_` can be
+/// [`NONE`] to accept any type.
+pub fn get_property(
+    delete: bool,
+    window: Window,
+    property: Atom,
+    type_: Atom,
+    offset: u32,
+    length: u32,
+) -> Vec<u8> {
+    RequestBuilder::new(opcodes::GET_PROPERTY, delete.into())
+        .u32(window)
+        .u32(property)
+        .u32(type_)
+        .u32(offset)
+        .u32(length)
+        .finish()
+}
+
+/// Sends a 32-byte event to a window, or to the clients selecting `event_mask` on it.
+pub fn send_event(
+    propagate: bool,
+    destination: Window,
+    event_mask: u32,
+    event: &[u8; 32],
+) -> Vec<u8> {
+    RequestBuilder::new(opcodes::SEND_EVENT, propagate.into())
+        .u32(destination)
+        .u32(event_mask)
+        .bytes(event)
+        .finish()
+}
+
+/// Makes `owner` the owner of a selection, or gives it up with [`NONE`]. Nothing happens if
+/// `time` is earlier than when the selection last changed owner, so check with
+/// [`get_selection_owner`] afterwards.
+pub fn set_selection_owner(owner: Window, selection: Atom, time: Timestamp) -> Vec<u8> {
+    RequestBuilder::new(opcodes::SET_SELECTION_OWNER, 0)
+        .u32(owner)
+        .u32(selection)
+        .u32(time)
+        .finish()
+}
+
+pub fn get_selection_owner(selection: Atom) -> Vec<u8> {
+    RequestBuilder::new(opcodes::GET_SELECTION_OWNER, 0)
+        .u32(selection)
+        .finish()
+}
+
+/// Asks a selection's owner to put its contents, converted to `target`, into `property` on
+/// `requestor`. The owner answers with a [`SelectionNotify`](Event::SelectionNotify) event (and
+/// the server answers for it if there's no owner).
+pub fn convert_selection(
+    requestor: Window,
+    selection: Atom,
+    target: Atom,
+    property: Atom,
+    time: Timestamp,
+) -> Vec<u8> {
+    RequestBuilder::new(opcodes::CONVERT_SELECTION, 0)
+        .u32(requestor)
+        .u32(selection)
+        .u32(target)
+        .u32(property)
+        .u32(time)
+        .finish()
+}
+
+pub fn translate_coordinates(source: Window, destination: Window, x: i16, y: i16) -> Vec<u8> {
+    RequestBuilder::new(opcodes::TRANSLATE_COORDINATES, 0)
+        .u32(source)
+        .u32(destination)
+        .i16(x)
+        .i16(y)
+        .finish()
+}
+
+/// Makes the pointer's events go to `window` (or to whichever of the client's windows the pointer
+/// is in, with `owner_events`), and optionally keeps the pointer inside `confine_to` and changes
+/// its cursor while it's grabbed.
+///
+/// The reply's data byte is a [`GrabStatus`].
+pub fn grab_pointer(
+    owner_events: bool,
+    window: Window,
+    event_mask: u16,
+    confine_to: Window,
+    cursor: Cursor,
+    time: Timestamp,
+) -> Vec<u8> {
+    RequestBuilder::new(opcodes::GRAB_POINTER, owner_events.into())
+        .u32(window)
+        .u16(event_mask)
+        // pointer and keyboard modes: asynchronous, i.e. events aren't frozen
+        .u8(1)
+        .u8(1)
+        .u32(confine_to)
+        .u32(cursor)
+        .u32(time)
+        .finish()
+}
+
+pub fn ungrab_pointer(time: Timestamp) -> Vec<u8> {
+    RequestBuilder::new(opcodes::UNGRAB_POINTER, 0)
+        .u32(time)
+        .finish()
+}
+
+pub fn create_pixmap(
+    depth: u8,
+    id: Pixmap,
+    drawable: Drawable,
+    width: u16,
+    height: u16,
+) -> Vec<u8> {
+    RequestBuilder::new(opcodes::CREATE_PIXMAP, depth)
+        .u32(id)
+        .u32(drawable)
+        .u16(width)
+        .u16(height)
+        .finish()
+}
+
+pub fn free_pixmap(pixmap: Pixmap) -> Vec<u8> {
+    RequestBuilder::new(opcodes::FREE_PIXMAP, 0)
+        .u32(pixmap)
+        .finish()
+}
+
+/// Creates a graphics context with the default values, which is all that [`put_image`] needs.
+pub fn create_gc(id: Gcontext, drawable: Drawable) -> Vec<u8> {
+    RequestBuilder::new(opcodes::CREATE_GC, 0)
+        .u32(id)
+        .u32(drawable)
+        .u32(0)
+        .finish()
+}
+
+pub fn free_gc(gc: Gcontext) -> Vec<u8> {
+    RequestBuilder::new(opcodes::FREE_GC, 0).u32(gc).finish()
+}
+
+/// Draws an image in `ZPixmap` format, where each scanline is padded to the screen's scanline pad
+/// (32 bits on every server in practice).
+pub fn put_image(
+    drawable: Drawable,
+    gc: Gcontext,
+    depth: u8,
+    width: u16,
+    height: u16,
+    data: &[u8],
+) -> Vec<u8> {
+    put_image_at(drawable, gc, depth, [0, 0], width, height, data)
+}
+
+/// Like [`put_image`], but with the image's top left corner at `[x, y]` in the drawable. Images
+/// too big for one request can be drawn a few rows at a time with this.
+pub fn put_image_at(
+    drawable: Drawable,
+    gc: Gcontext,
+    depth: u8,
+    [x, y]: [i16; 2],
+    width: u16,
+    height: u16,
+    data: &[u8],
+) -> Vec<u8> {
+    RequestBuilder::new(opcodes::PUT_IMAGE, 2)
+        .u32(drawable)
+        .u32(gc)
+        .u16(width)
+        .u16(height)
+        .i16(x)
+        .i16(y)
+        // the left pad
+        .u8(0)
+        .u8(depth)
+        .skip(2)
+        .bytes(data)
+        .finish()
+}
+
+/// Creates a cursor from two 1-bit pixmaps: `source` picks the foreground or background color for
+/// each pixel, and `mask` says which pixels are shown at all. Colors are 16-bit RGB.
+pub fn create_cursor(
+    id: Cursor,
+    source: Pixmap,
+    mask: Pixmap,
+    foreground: [u16; 3],
+    background: [u16; 3],
+    hotspot: [u16; 2],
+) -> Vec<u8> {
+    let request = RequestBuilder::new(opcodes::CREATE_CURSOR, 0)
+        .u32(id)
+        .u32(source)
+        .u32(mask);
+    foreground
+        .into_iter()
+        .chain(background)
+        .chain(hotspot)
+        .fold(request, RequestBuilder::u16)
+        .finish()
+}
+
+pub fn free_cursor(cursor: Cursor) -> Vec<u8> {
+    RequestBuilder::new(opcodes::FREE_CURSOR, 0)
+        .u32(cursor)
+        .finish()
+}
+
+/// A request with a trivial reply, which is handy for waiting until the server has processed
+/// everything before it.
+pub fn get_input_focus() -> Vec<u8> {
+    RequestBuilder::new(opcodes::GET_INPUT_FOCUS, 0).finish()
+}
+
+pub fn query_extension(name: &str) -> Vec<u8> {
+    RequestBuilder::new(opcodes::QUERY_EXTENSION, 0)
+        .u16(name.len() as u16)
+        .skip(2)
+        .bytes(name.as_bytes())
+        .finish()
+}
+
+/// Asks for the keysyms of `count` keycodes, starting at `first`. Every keycode from the setup's
+/// `min_keycode` to its `max_keycode` can be asked for at once.
+pub fn get_keyboard_mapping(first: Keycode, count: u8) -> Vec<u8> {
+    RequestBuilder::new(opcodes::GET_KEYBOARD_MAPPING, 0)
+        .u8(first)
+        .u8(count)
+        .skip(2)
+        .finish()
+}
+
+/// Encodes a `ClientMessage` event with 32-bit data, for [`send_event`].
+pub fn client_message32(window: Window, type_: Atom, data: [u32; 5]) -> [u8; 32] {
+    let mut event = [0; 32];
+    event[0] = event_codes::CLIENT_MESSAGE;
+    event[1] = 32;
+    event[4..8].copy_from_slice(&window.to_le_bytes());
+    event[8..12].copy_from_slice(&type_.to_le_bytes());
+    for (i, value) in data.into_iter().enumerate() {
+        event[12 + 4 * i..16 + 4 * i].copy_from_slice(&value.to_le_bytes());
+    }
+    event
+}
+
+/// Encodes the `SelectionNotify` event that a selection owner sends to a requestor, for
+/// [`send_event`]. `property` is [`NONE`] if the selection couldn't be converted.
+pub fn selection_notify(
+    time: Timestamp,
+    requestor: Window,
+    selection: Atom,
+    target: Atom,
+    property: Atom,
+) -> [u8; 32] {
+    let mut event = [0; 32];
+    event[0] = event_codes::SELECTION_NOTIFY;
+    for (i, value) in [time, requestor, selection, target, property]
+        .into_iter()
+        .enumerate()
+    {
+        event[4 + 4 * i..8 + 4 * i].copy_from_slice(&value.to_le_bytes());
+    }
+    event
+}
+
+/// Starts reading a reply, skipping its header (the reply marker, sequence number and length).
+///
+/// **Returns:** The byte of request-specific data in the header, and a reader positioned after the
+/// header.
+pub fn reply_reader(reply: &[u8]) -> Result<(u8, Reader<'_>), ParseError> {
+    let mut r = Reader::new(reply);
+    r.skip(1)?;
+    let data = r.u8()?;
+    r.skip(6)?;
+    Ok((data, r))
+}
+
+/// Parses the reply to [`intern_atom`]. The atom is [`NONE`] if it didn't exist and
+/// `only_if_exists` was set.
+pub fn parse_intern_atom(reply: &[u8]) -> Result<Atom, ParseError> {
+    reply_reader(reply)?.1.u32()
+}
+
+/// Parses the reply to [`get_selection_owner`], which is [`NONE`] if nobody owns the selection.
+pub fn parse_get_selection_owner(reply: &[u8]) -> Result<Window, ParseError> {
+    reply_reader(reply)?.1.u32()
+}
+
+/// Whether [`grab_pointer`] worked, from its reply's data byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GrabStatus {
+    Success,
+    /// Another client has grabbed the pointer.
+    AlreadyGrabbed,
+    InvalidTime,
+    /// The window (or the one to confine the pointer to) isn't mapped, or is off the screen.
+    NotViewable,
+    /// Another client has frozen the pointer.
+    Frozen,
+}
+
+impl GrabStatus {
+    pub fn parse(reply: &[u8]) -> Result<Self, ParseError> {
+        Ok(match reply_reader(reply)?.0 {
+            0 => Self::Success,
+            1 => Self::AlreadyGrabbed,
+            2 => Self::InvalidTime,
+            3 => Self::NotViewable,
+            _ => Self::Frozen,
+        })
+    }
+}
+
+impl fmt::Display for GrabStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Success => "success",
+            Self::AlreadyGrabbed => "already grabbed by another client",
+            Self::InvalidTime => "invalid time",
+            Self::NotViewable => "window not viewable",
+            Self::Frozen => "frozen by another client",
+        })
+    }
+}
+
+/// Whether a window is mapped, from [`GetWindowAttributesReply::map_state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MapState {
+    Unmapped,
+    /// Mapped, but an ancestor isn't.
+    Unviewable,
+    Viewable,
+}
+
+/// The reply to [`get_window_attributes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GetWindowAttributesReply {
+    pub visual: VisualId,
+    /// One of the [`window_class`] constants.
+    pub class: u16,
+    pub map_state: MapState,
+    pub override_redirect: bool,
+    pub colormap: Colormap,
+    /// The events that any client has selected on the window.
+    pub all_event_masks: u32,
+    /// The events that this client has selected on the window.
+    pub your_event_mask: u32,
+}
+
+impl GetWindowAttributesReply {
+    pub fn parse(reply: &[u8]) -> Result<Self, ParseError> {
+        let (_backing_store, mut r) = reply_reader(reply)?;
+        let visual = r.u32()?;
+        let class = r.u16()?;
+        // gravities, backing planes and pixel, save-under and whether the colormap is installed
+        r.skip(12)?;
+        let map_state = match r.u8()? {
+            0 => MapState::Unmapped,
+            1 => MapState::Unviewable,
+            _ => MapState::Viewable,
+        };
+        let override_redirect = r.u8()? != 0;
+        Ok(Self {
+            visual,
+            class,
+            map_state,
+            override_redirect,
+            colormap: r.u32()?,
+            all_event_masks: r.u32()?,
+            your_event_mask: r.u32()?,
+        })
+    }
+}
+
+/// The reply to [`get_geometry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Geometry {
+    pub depth: u8,
+    pub root: Window,
+    /// The position of the top left corner of the border, relative to the parent window.
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
+    pub border_width: u16,
+}
+
+impl Geometry {
+    pub fn parse(reply: &[u8]) -> Result<Self, ParseError> {
+        let (depth, mut r) = reply_reader(reply)?;
+        Ok(Self {
+            depth,
+            root: r.u32()?,
+            x: r.i16()?,
+            y: r.i16()?,
+            width: r.u16()?,
+            height: r.u16()?,
+            border_width: r.u16()?,
+        })
+    }
+}
+
+/// The reply to [`translate_coordinates`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TranslatedCoordinates {
+    pub same_screen: bool,
+    pub child: Window,
+    pub x: i16,
+    pub y: i16,
+}
+
+impl TranslatedCoordinates {
+    pub fn parse(reply: &[u8]) -> Result<Self, ParseError> {
+        let (same_screen, mut r) = reply_reader(reply)?;
+        Ok(Self {
+            same_screen: same_screen != 0,
+            child: r.u32()?,
+            x: r.i16()?,
+            y: r.i16()?,
+        })
+    }
+}
+
+/// The reply to [`get_property`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    /// `0` if the property doesn't exist, or else `8`, `16` or `32`.
+    pub format: u8,
+    /// [`NONE`] if the property doesn't exist.
+    pub type_: Atom,
+    /// How many bytes of the property weren't read.
+    pub bytes_after: u32,
+    pub value: Vec<u8>,
+}
+
+impl Property {
+    pub fn parse(reply: &[u8]) -> Result<Self, ParseError> {
+        let (format, mut r) = reply_reader(reply)?;
+        let type_ = r.u32()?;
+        let bytes_after = r.u32()?;
+        let len = r.u32()? as usize;
+        r.skip(12)?;
+        let value = r.bytes(len * usize::from(format / 8))?.to_vec();
+        Ok(Self {
+            format,
+            type_,
+            bytes_after,
+            value,
+        })
+    }
+
+    /// The value as 32-bit values (e.g. atoms), or `None` if it's in another format.
+    pub fn as_u32s(&self) -> Option<Vec<u32>> {
+        (self.format == 32).then(|| {
+            self.value
+                .chunks_exact(4)
+                .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+                .collect()
+        })
+    }
+}
+
+/// The reply to [`query_extension`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtensionInfo {
+    pub present: bool,
+    /// The opcode of the extension's requests, which go in their first byte.
+    pub major_opcode: u8,
+    /// The code of the extension's first event, if it has any.
+    pub first_event: u8,
+    /// The code of the extension's first error, if it has any.
+    pub first_error: u8,
+}
+
+impl ExtensionInfo {
+    pub fn parse(reply: &[u8]) -> Result<Self, ParseError> {
+        let (_, mut r) = reply_reader(reply)?;
+        Ok(Self {
+            present: r.u8()? != 0,
+            major_opcode: r.u8()?,
+            first_event: r.u8()?,
+            first_error: r.u8()?,
+        })
+    }
+}
+
+/// The reply to [`get_keyboard_mapping`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyboardMapping {
+    /// How many keysyms each keycode has. Some of them are usually [`NONE`].
+    pub keysyms_per_keycode: u8,
+    /// The keysyms of each keycode in turn.
+    pub keysyms: Vec<Keysym>,
+}
+
+impl KeyboardMapping {
+    pub fn parse(reply: &[u8]) -> Result<Self, ParseError> {
+        let (keysyms_per_keycode, mut r) = reply_reader(reply)?;
+        r.skip(24)?;
+        // the keysyms are all that's after the header
+        Ok(Self {
+            keysyms_per_keycode,
+            keysyms: r.u32s(r.remaining() / 4)?,
+        })
+    }
+
+    /// The keysyms of `keycode`, where the first keycode asked for is `first`.
+    pub fn keysyms(&self, first: Keycode, keycode: Keycode) -> &[Keysym] {
+        let per = usize::from(self.keysyms_per_keycode);
+        let start = usize::from(keycode.wrapping_sub(first)) * per;
+        self.keysyms.get(start..start + per).unwrap_or(&[])
+    }
+}
+
+/// An error reported by the server for one of the client's requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolError {
+    pub code: u8,
+    /// The (low 16 bits of the) sequence number of the request that failed.
+    pub sequence: u16,
+    /// The resource ID or value that was bad, for errors where that applies.
+    pub bad_value: u32,
+    pub minor_opcode: u16,
+    pub major_opcode: u8,
+}
+
+impl ProtocolError {
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParseError> {
+        let mut r = Reader::new(bytes);
+        r.skip(1)?;
+        Ok(Self {
+            code: r.u8()?,
+            sequence: r.u16()?,
+            bad_value: r.u32()?,
+            minor_opcode: r.u16()?,
+            major_opcode: r.u8()?,
+        })
+    }
+
+    /// The name of the error code, as given in the protocol specification.
+    pub fn name(&self) -> &'static str {
+        match self.code {
+            1 => "BadRequest",
+            2 => "BadValue",
+            3 => "BadWindow",
+            4 => "BadPixmap",
+            5 => "BadAtom",
+            6 => "BadCursor",
+            7 => "BadFont",
+            8 => "BadMatch",
+            9 => "BadDrawable",
+            10 => "BadAccess",
+            11 => "BadAlloc",
+            12 => "BadColor",
+            13 => "BadGC",
+            14 => "BadIDChoice",
+            15 => "BadName",
+            16 => "BadLength",
+            17 => "BadImplementation",
+            _ => "extension error",
+        }
+    }
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}) for request {}.{} (bad value {:#x})",
+            self.name(),
+            self.code,
+            self.major_opcode,
+            self.minor_opcode,
+            self.bad_value
+        )
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+/// A `ConfigureNotify` event: a window's size, position or stacking changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigureNotify {
+    pub event: Window,
+    pub window: Window,
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
+    pub border_width: u16,
+    pub override_redirect: bool,
+}
+
+/// A `ClientMessage` event, with its data left as bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientMessage {
+    pub format: u8,
+    pub window: Window,
+    pub type_: Atom,
+    pub data: [u8; 20],
+}
+
+impl ClientMessage {
+    /// The data as five 32-bit values, for messages with format 32.
+    pub fn data32(&self) -> [u32; 5] {
+        core::array::from_fn(|i| {
+            u32::from_le_bytes(self.data[4 * i..4 * i + 4].try_into().unwrap())
+        })
+    }
+}
+
+/// A `SelectionRequest` event: another client wants a selection this client owns.
+///
+/// The answer is to put the selection into `property` on `requestor` as `target`, and then to
+/// send it a [`selection_notify`] event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelectionRequest {
+    pub time: Timestamp,
+    pub owner: Window,
+    pub requestor: Window,
+    pub selection: Atom,
+    pub target: Atom,
+    /// [`NONE`] from clients that are older than the ICCCM, which means to use `target` as the
+    /// property.
+    pub property: Atom,
+}
+
+/// A `KeyPress`, `KeyRelease`, `ButtonPress`, `ButtonRelease` or `MotionNotify` event, which all
+/// have the same fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEvent {
+    /// The keycode or button, or for motion, whether it's a hint.
+    pub detail: u8,
+    pub time: Timestamp,
+    pub root: Window,
+    pub event: Window,
+    pub child: Window,
+    pub root_x: i16,
+    pub root_y: i16,
+    pub event_x: i16,
+    pub event_y: i16,
+    /// The modifiers and buttons held just before the event, in [`key_masks`] bits.
+    pub state: u16,
+    pub same_screen: bool,
+}
+
+/// A decoded event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    Expose {
+        window: Window,
+        /// How many more `Expose` events for the window follow this one.
+        count: u16,
+    },
+    DestroyNotify {
+        window: Window,
+    },
+    UnmapNotify {
+        window: Window,
+    },
+    MapNotify {
+        window: Window,
+    },
+    ConfigureNotify(ConfigureNotify),
+    PropertyNotify {
+        window: Window,
+        atom: Atom,
+        time: Timestamp,
+        /// Whether the property was deleted rather than changed.
+        deleted: bool,
+    },
+    ClientMessage(ClientMessage),
+    /// Another client took ownership of a selection this client owned.
+    SelectionClear {
+        time: Timestamp,
+        owner: Window,
+        selection: Atom,
+    },
+    SelectionRequest(SelectionRequest),
+    /// The answer to [`convert_selection`].
+    SelectionNotify {
+        time: Timestamp,
+        requestor: Window,
+        selection: Atom,
+        target: Atom,
+        /// [`NONE`] if the selection couldn't be converted.
+        property: Atom,
+    },
+    KeyPress(InputEvent),
+    KeyRelease(InputEvent),
+    FocusIn {
+        window: Window,
+        /// E.g. `1` if the focus moved because of a keyboard grab.
+        mode: u8,
+    },
+    FocusOut {
+        window: Window,
+        mode: u8,
+    },
+    /// The keyboard or modifier mapping changed, so anything read from it should be read again.
+    MappingNotify {
+        /// `0` for the modifier mapping, `1` for the keyboard mapping, or `2` for the pointer.
+        request: u8,
+        first_keycode: Keycode,
+        count: u8,
+    },
+    /// An event that isn't decoded (yet), with its code (without the "sent" bit) and bytes.
+    Other {
+        code: u8,
+        bytes: Vec<u8>,
+    },
+}
+
+impl Event {
+    /// Decodes an event. `bytes` is 32 bytes long, except for `GenericEvent`s which may be longer.
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParseError> {
+        let mut r = Reader::new(bytes);
+        let code = r.u8()? & 0x7F;
+        let detail = r.u8()?;
+        let _sequence = r.u16()?;
+        Ok(match code {
+            event_codes::EXPOSE => {
+                let window = r.u32()?;
+                r.skip(8)?;
+                Self::Expose {
+                    window,
+                    count: r.u16()?,
+                }
+            }
+            event_codes::DESTROY_NOTIFY => {
+                r.skip(4)?;
+                Self::DestroyNotify { window: r.u32()? }
+            }
+            event_codes::UNMAP_NOTIFY => {
+                r.skip(4)?;
+                Self::UnmapNotify { window: r.u32()? }
+            }
+            event_codes::MAP_NOTIFY => {
+                r.skip(4)?;
+                Self::MapNotify { window: r.u32()? }
+            }
+            event_codes::CONFIGURE_NOTIFY => {
+                let event = r.u32()?;
+                let window = r.u32()?;
+                let _above_sibling = r.u32()?;
+                Self::ConfigureNotify(ConfigureNotify {
+                    event,
+                    window,
+                    x: r.i16()?,
+                    y: r.i16()?,
+                    width: r.u16()?,
+                    height: r.u16()?,
+                    border_width: r.u16()?,
+                    override_redirect: r.u8()? != 0,
+                })
+            }
+            event_codes::PROPERTY_NOTIFY => Self::PropertyNotify {
+                window: r.u32()?,
+                atom: r.u32()?,
+                time: r.u32()?,
+                deleted: r.u8()? != 0,
+            },
+            event_codes::SELECTION_CLEAR => Self::SelectionClear {
+                time: r.u32()?,
+                owner: r.u32()?,
+                selection: r.u32()?,
+            },
+            event_codes::SELECTION_REQUEST => Self::SelectionRequest(SelectionRequest {
+                time: r.u32()?,
+                owner: r.u32()?,
+                requestor: r.u32()?,
+                selection: r.u32()?,
+                target: r.u32()?,
+                property: r.u32()?,
+            }),
+            event_codes::SELECTION_NOTIFY => Self::SelectionNotify {
+                time: r.u32()?,
+                requestor: r.u32()?,
+                selection: r.u32()?,
+                target: r.u32()?,
+                property: r.u32()?,
+            },
+            event_codes::CLIENT_MESSAGE => Self::ClientMessage(ClientMessage {
+                format: detail,
+                window: r.u32()?,
+                type_: r.u32()?,
+                data: r.bytes(20)?.try_into().unwrap(),
+            }),
+            event_codes::KEY_PRESS | event_codes::KEY_RELEASE => {
+                let time = r.u32()?;
+                let event = InputEvent {
+                    detail,
+                    time,
+                    root: r.u32()?,
+                    event: r.u32()?,
+                    child: r.u32()?,
+                    root_x: r.i16()?,
+                    root_y: r.i16()?,
+                    event_x: r.i16()?,
+                    event_y: r.i16()?,
+                    state: r.u16()?,
+                    same_screen: r.u8()? != 0,
+                };
+                if code == event_codes::KEY_PRESS {
+                    Self::KeyPress(event)
+                } else {
+                    Self::KeyRelease(event)
+                }
+            }
+            event_codes::FOCUS_IN => Self::FocusIn {
+                window: r.u32()?,
+                mode: r.u8()?,
+            },
+            event_codes::FOCUS_OUT => Self::FocusOut {
+                window: r.u32()?,
+                mode: r.u8()?,
+            },
+            event_codes::MAPPING_NOTIFY => Self::MappingNotify {
+                request: r.u8()?,
+                first_keycode: r.u8()?,
+                count: r.u8()?,
+            },
+            code => Self::Other {
+                code,
+                bytes: bytes.to_vec(),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A setup reply like Xvfb's, with one screen that has one 24-bit TrueColor visual.
+    pub(crate) fn setup_fixture() -> Vec<u8> {
+        let vendor = b"The X.Org Foundation";
+        let mut body = RequestBuilder::new(0, 0)
+            .u32(12101004) // release
+            .u32(0x0020_0000) // resource ID base
+            .u32(0x001F_FFFF) // resource ID mask
+            .u32(256) // motion buffer
+            .u16(vendor.len() as u16)
+            .u16(0xFFFF) // maximum request length
+            .u8(1) // screens
+            .u8(1) // formats
+            .u8(0)
+            .u8(0)
+            .u8(32)
+            .u8(32)
+            .u8(8) // min keycode
+            .u8(255) // max keycode
+            .skip(4)
+            .bytes(vendor)
+            .align()
+            // format: depth 24, 32 bits per pixel, pad 32
+            .u8(24)
+            .u8(32)
+            .u8(32)
+            .skip(5)
+            // screen
+            .u32(0x4A6) // root
+            .u32(0x21) // colormap
+            .u32(0xFF_FFFF)
+            .u32(0)
+            .u32(0)
+            .u16(1280)
+            .u16(1024)
+            .u16(338)
+            .u16(270)
+            .u16(1)
+            .u16(1)
+            .u32(0x21) // root visual
+            .u8(0)
+            .u8(0)
+            .u8(24) // root depth
+            .u8(1) // depths
+            .u8(24)
+            .skip(1)
+            .u16(1)
+            .skip(4)
+            .u32(0x21)
+            .u8(4)
+            .u8(8)
+            .u16(256)
+            .u32(0xFF_0000)
+            .u32(0xFF00)
+            .u32(0xFF)
+            .skip(4)
+            .finish();
+        // drop the request header that `RequestBuilder` adds
+        body.drain(..4);
+
+        let mut reply = vec![1, 0];
+        reply.extend_from_slice(&11u16.to_le_bytes());
+        reply.extend_from_slice(&0u16.to_le_bytes());
+        reply.extend_from_slice(&((body.len() / 4) as u16).to_le_bytes());
+        reply.extend_from_slice(&body);
+        reply
+    }
+
+    mod setup {
+        use super::*;
+
+        #[test]
+        fn request() {
+            assert_eq!(
+                setup_request(b"MIT-MAGIC-COOKIE-1", &[0xAB; 16]).len(),
+                12 + 20 + 16
+            );
+            assert_eq!(
+                setup_request(b"", b""),
+                [b'l', 0, 11, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+            );
+        }
+
+        #[test]
+        fn accepted() {
+            let reply = setup_fixture();
+            assert_eq!(setup_reply_len(reply[..8].try_into().unwrap()), reply.len());
+
+            let setup = Setup::parse(&reply).unwrap();
+            assert_eq!(setup.protocol_major_version, 11);
+            assert_eq!(setup.resource_id_base, 0x0020_0000);
+            assert_eq!(setup.vendor, "The X.Org Foundation");
+            assert_eq!((setup.min_keycode, setup.max_keycode), (8, 255));
+            assert_eq!(setup.screens.len(), 1);
+
+            let screen = &setup.screens[0];
+            assert_eq!(screen.root, 0x4A6);
+            assert_eq!(
+                (screen.width_in_pixels, screen.height_in_pixels),
+                (1280, 1024)
+            );
+            assert_eq!(screen.root_depth, 24);
+            assert_eq!(screen.depths[0].visuals[0].red_mask, 0xFF_0000);
+        }
+
+        #[test]
+        fn refused() {
+            let reason = b"No protocol specified\n";
+            let mut reply = vec![0, reason.len() as u8, 11, 0, 0, 0, 6, 0];
+            reply.extend_from_slice(reason);
+            reply.resize(8 + 24, 0);
+            assert_eq!(
+                Setup::parse(&reply),
+                Err(SetupError::Failed {
+                    reason: "No protocol specified".into()
+                })
+            );
+
+            assert!(matches!(
+                Setup::parse(&setup_fixture()[..40]),
+                Err(SetupError::Parse(_))
+            ));
+        }
+    }
+
+    mod requests {
+        use super::*;
+
+        #[test]
+        fn intern_atom_request() {
+            let request = intern_atom(true, "_NET_WM_STATE");
+            assert_eq!(&request[..8], [16, 1, 6, 0, 13, 0, 0, 0]);
+            assert_eq!(&request[8..21], b"_NET_WM_STATE");
+            assert_eq!(request.len(), 24);
+        }
+
+        #[test]
+        fn value_lists() {
+            let request = create_window(&CreateWindow {
+                depth: 0,
+                id: 0x20_0001,
+                parent: 0x4A6,
+                x: -10,
+                y: 20,
+                width: 640,
+                height: 480,
+                border_width: 0,
+                class: window_class::INPUT_OUTPUT,
+                visual: 0,
+                attributes: WindowAttributes {
+                    background_pixel: Some(0),
+                    event_mask: Some(event_masks::STRUCTURE_NOTIFY),
+                    ..Default::default()
+                },
+            });
+            assert_eq!(request.len(), 40);
+            assert_eq!(u16::from_le_bytes([request[2], request[3]]), 10);
+            let mut r = Reader::new(&request[4..]);
+            assert_eq!(r.u32(), Ok(0x20_0001));
+            assert_eq!(r.u32(), Ok(0x4A6));
+            assert_eq!(r.i16(), Ok(-10));
+            r.skip(10).unwrap();
+            assert_eq!(r.u32(), Ok(0));
+            assert_eq!(r.u32(), Ok((1 << 1) | (1 << 11)));
+            assert_eq!(r.u32s(2), Ok(vec![0, event_masks::STRUCTURE_NOTIFY]));
+
+            let request = configure_window(
+                7,
+                &WindowChanges {
+                    x: Some(-1),
+                    height: Some(300),
+                    ..Default::default()
+                },
+            );
+            assert_eq!(
+                request,
+                [12, 0, 5, 0, 7, 0, 0, 0, 0b1001, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 44, 1, 0, 0]
+            );
+        }
+
+        #[test]
+        fn properties() {
+            let request =
+                change_property32(PropertyMode::Replace, 7, 300, atoms::ATOM, &[301, 302]);
+            assert_eq!(request.len(), 24 + 8);
+            assert_eq!(request[16], 32);
+            assert_eq!(u32::from_le_bytes(request[20..24].try_into().unwrap()), 2);
+
+            let request = change_property(
+                PropertyMode::Append,
+                7,
+                atoms::WM_NAME,
+                atoms::STRING,
+                8,
+                b"hello",
+            );
+            assert_eq!(request[1], PropertyMode::Append as u8);
+            assert_eq!(u32::from_le_bytes(request[20..24].try_into().unwrap()), 5);
+            assert_eq!(request.len(), 24 + 8);
+        }
+
+        #[test]
+        fn client_messages() {
+            let event = client_message32(7, 300, [1, 301, 0, 1, 0]);
+            let request = send_event(false, 0x4A6, event_masks::SUBSTRUCTURE_REDIRECT, &event);
+            assert_eq!(request.len(), 44);
+            assert_eq!(&request[12..], &event);
+
+            let Event::ClientMessage(message) = Event::parse(&event).unwrap() else {
+                panic!("not a client message");
+            };
+            assert_eq!((message.window, message.type_), (7, 300));
+            assert_eq!(message.data32(), [1, 301, 0, 1, 0]);
+        }
+
+        #[test]
+        fn selections() {
+            assert_eq!(
+                set_selection_owner(7, atoms::PRIMARY, 1000),
+                [22, 0, 4, 0, 7, 0, 0, 0, 1, 0, 0, 0, 0xE8, 3, 0, 0]
+            );
+            assert_eq!(get_selection_owner(300), [23, 0, 2, 0, 0x2C, 1, 0, 0]);
+            let request = convert_selection(7, 300, 301, 302, CURRENT_TIME);
+            assert_eq!(request.len(), 24);
+            assert_eq!(&request[..4], [24, 0, 6, 0]);
+            assert_eq!(&request[16..20], 302u32.to_le_bytes());
+
+            let event = selection_notify(1000, 7, 300, 301, 302);
+            let request = send_event(false, 7, 0, &event);
+            assert_eq!(&request[12..], &event);
+            assert_eq!(
+                Event::parse(&event),
+                Ok(Event::SelectionNotify {
+                    time: 1000,
+                    requestor: 7,
+                    selection: 300,
+                    target: 301,
+                    property: 302,
+                })
+            );
+        }
+
+        #[test]
+        fn pointer_grabs() {
+            assert_eq!(
+                grab_pointer(true, 7, 0x44, 7, 9, CURRENT_TIME),
+                [26, 1, 6, 0, 7, 0, 0, 0, 0x44, 0, 1, 1, 7, 0, 0, 0, 9, 0, 0, 0, 0, 0, 0, 0]
+            );
+            assert_eq!(ungrab_pointer(CURRENT_TIME), [27, 0, 2, 0, 0, 0, 0, 0]);
+        }
+
+        #[test]
+        fn cursors() {
+            let request = put_image(8, 9, 1, 1, 1, &[0; 4]);
+            assert_eq!(request.len(), 24 + 4);
+            assert_eq!(&request[..4], [72, 2, 7, 0]);
+            assert_eq!(request[21], 1);
+
+            let request = put_image_at(8, 9, 32, [0, 5], 1, 1, &[0; 4]);
+            assert_eq!(&request[16..22], [0, 0, 5, 0, 0, 32]);
+
+            let request = create_cursor(10, 8, 8, [0xFFFF; 3], [0; 3], [3, 4]);
+            assert_eq!(request.len(), 32);
+            assert_eq!(&request[16..22], [0xFF; 6]);
+            assert_eq!(&request[28..], [3, 0, 4, 0]);
+        }
+
+        #[test]
+        fn keyboard_mapping() {
+            assert_eq!(get_keyboard_mapping(8, 248), [101, 0, 2, 0, 8, 248, 0, 0]);
+        }
+    }
+
+    mod replies {
+        use super::*;
+
+        fn reply(data: u8, body: &[u8]) -> Vec<u8> {
+            let mut reply = vec![1, data, 5, 0];
+            let extra = body.len().saturating_sub(24);
+            reply.extend_from_slice(&((extra / 4) as u32).to_le_bytes());
+            reply.extend_from_slice(body);
+            reply.resize(reply.len().max(32), 0);
+            reply
+        }
+
+        #[test]
+        fn keyboard_mapping() {
+            // two keycodes with two keysyms each: a, A and then Return
+            let mut body = vec![0; 24];
+            for keysym in [0x61u32, 0x41, 0xFF0D, 0] {
+                body.extend_from_slice(&keysym.to_le_bytes());
+            }
+            let mapping = KeyboardMapping::parse(&reply(2, &body)).unwrap();
+            assert_eq!(mapping.keysyms_per_keycode, 2);
+            assert_eq!(mapping.keysyms(38, 38), [0x61, 0x41]);
+            assert_eq!(mapping.keysyms(38, 39), [0xFF0D, 0]);
+            assert_eq!(mapping.keysyms(38, 40), []);
+            assert_eq!(mapping.keysyms(38, 37), []);
+        }
+
+        #[test]
+        fn simple_replies() {
+            assert_eq!(parse_intern_atom(&reply(0, &[0x2C, 1, 0, 0])), Ok(300));
+            assert_eq!(
+                parse_get_selection_owner(&reply(0, &[7, 0, 0x20, 0])),
+                Ok(0x20_0007)
+            );
+
+            let geometry = Geometry::parse(&reply(
+                24,
+                &[0xA6, 4, 0, 0, 10, 0, 0xF6, 0xFF, 0x80, 2, 0xE0, 1, 2, 0],
+            ))
+            .unwrap();
+            assert_eq!(
+                geometry,
+                Geometry {
+                    depth: 24,
+                    root: 0x4A6,
+                    x: 10,
+                    y: -10,
+                    width: 640,
+                    height: 480,
+                    border_width: 2,
+                }
+            );
+
+            assert_eq!(
+                GrabStatus::parse(&reply(3, &[])),
+                Ok(GrabStatus::NotViewable)
+            );
+
+            let info = ExtensionInfo::parse(&reply(0, &[1, 140, 89, 147])).unwrap();
+            assert!(info.present);
+            assert_eq!(info.major_opcode, 140);
+        }
+
+        #[test]
+        fn properties() {
+            let mut body = Vec::new();
+            body.extend_from_slice(&atoms::ATOM.to_le_bytes());
+            body.extend_from_slice(&0u32.to_le_bytes());
+            body.extend_from_slice(&2u32.to_le_bytes());
+            body.extend_from_slice(&[0; 12]);
+            body.extend_from_slice(&301u32.to_le_bytes());
+            body.extend_from_slice(&302u32.to_le_bytes());
+            let property = Property::parse(&reply(32, &body)).unwrap();
+            assert_eq!(property.type_, atoms::ATOM);
+            assert_eq!(property.as_u32s(), Some(vec![301, 302]));
+
+            // a property that doesn't exist
+            let property = Property::parse(&reply(0, &[0; 24])).unwrap();
+            assert_eq!((property.format, property.type_), (0, NONE));
+            assert!(property.value.is_empty());
+        }
+
+        #[test]
+        fn errors() {
+            let bytes = [
+                0, 3, 9, 0, 0xEF, 0xBE, 0, 0, 0, 0, 12, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                0, 0, 0, 0, 0, 0, 0,
+            ];
+            let error = ProtocolError::parse(&bytes).unwrap();
+            assert_eq!(error.name(), "BadWindow");
+            assert_eq!(error.sequence, 9);
+            assert_eq!(error.bad_value, 0xBEEF);
+            assert_eq!(error.major_opcode, opcodes::CONFIGURE_WINDOW);
+        }
+    }
+
+    mod events {
+        use super::*;
+
+        #[test]
+        fn configure_notify() {
+            let mut bytes = [0; 32];
+            bytes[0] = event_codes::CONFIGURE_NOTIFY | 0x80;
+            bytes[4..8].copy_from_slice(&7u32.to_le_bytes());
+            bytes[8..12].copy_from_slice(&7u32.to_le_bytes());
+            bytes[16..18].copy_from_slice(&(-5i16).to_le_bytes());
+            bytes[20..22].copy_from_slice(&1280u16.to_le_bytes());
+            bytes[22..24].copy_from_slice(&1024u16.to_le_bytes());
+            assert_eq!(
+                Event::parse(&bytes),
+                Ok(Event::ConfigureNotify(ConfigureNotify {
+                    event: 7,
+                    window: 7,
+                    x: -5,
+                    y: 0,
+                    width: 1280,
+                    height: 1024,
+                    border_width: 0,
+                    override_redirect: false,
+                }))
+            );
+        }
+
+        #[test]
+        fn key_press() {
+            #[rustfmt::skip]
+            let bytes = [
+                2, 38, 0x10, 0, 0x40, 0xE2, 0x01, 0x00, // KeyPress, keycode 38, sequence 16, time
+                0xA6, 4, 0, 0, 1, 0, 0x20, 0, 0, 0, 0, 0, // root, event window, no child
+                100, 0, 50, 0, 10, 0, 20, 0, // root and event positions
+                0x01, 0x00, 1, 0, // shift held, same screen
+            ];
+            assert_eq!(
+                Event::parse(&bytes),
+                Ok(Event::KeyPress(InputEvent {
+                    detail: 38,
+                    time: 123_456,
+                    root: 0x4A6,
+                    event: 0x20_0001,
+                    child: NONE,
+                    root_x: 100,
+                    root_y: 50,
+                    event_x: 10,
+                    event_y: 20,
+                    state: key_masks::SHIFT,
+                    same_screen: true,
+                }))
+            );
+
+            let mut bytes = bytes;
+            bytes[0] = event_codes::KEY_RELEASE;
+            assert!(matches!(Event::parse(&bytes), Ok(Event::KeyRelease(_))));
+        }
+
+        #[test]
+        fn keyboard_events() {
+            let mut bytes = [0; 32];
+            bytes[0] = event_codes::FOCUS_OUT;
+            bytes[4..8].copy_from_slice(&7u32.to_le_bytes());
+            bytes[8] = 1;
+            assert_eq!(
+                Event::parse(&bytes),
+                Ok(Event::FocusOut { window: 7, mode: 1 })
+            );
+
+            let mut bytes = [0; 32];
+            bytes[0] = event_codes::MAPPING_NOTIFY;
+            bytes[4..7].copy_from_slice(&[1, 8, 248]);
+            assert_eq!(
+                Event::parse(&bytes),
+                Ok(Event::MappingNotify {
+                    request: 1,
+                    first_keycode: 8,
+                    count: 248
+                })
+            );
+        }
+
+        #[test]
+        fn selection_events() {
+            let mut bytes = [0; 32];
+            bytes[0] = event_codes::SELECTION_REQUEST;
+            for (i, value) in [1000u32, 7, 8, 300, 301, 302].into_iter().enumerate() {
+                bytes[4 + 4 * i..8 + 4 * i].copy_from_slice(&value.to_le_bytes());
+            }
+            assert_eq!(
+                Event::parse(&bytes),
+                Ok(Event::SelectionRequest(SelectionRequest {
+                    time: 1000,
+                    owner: 7,
+                    requestor: 8,
+                    selection: 300,
+                    target: 301,
+                    property: 302,
+                }))
+            );
+
+            bytes[0] = event_codes::SELECTION_CLEAR;
+            assert_eq!(
+                Event::parse(&bytes),
+                Ok(Event::SelectionClear {
+                    time: 1000,
+                    owner: 7,
+                    selection: 8,
+                })
+            );
+        }
+
+        #[test]
+        fn unknown_events_are_kept() {
+            let mut bytes = [0; 32];
+            bytes[0] = 90;
+            assert_eq!(
+                Event::parse(&bytes),
+                Ok(Event::Other {
+                    code: 90,
+                    bytes: bytes.to_vec()
+                })
+            );
+        }
+    }
+}
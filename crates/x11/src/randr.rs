@@ -0,0 +1,502 @@
+//! Encoding and decoding the RandR ("resize and rotate") extension, which describes the monitors
+//! plugged into the screen.
+//!
+//! RandR splits a monitor into an *output* (the connector, e.g. `HDMI-1`, which knows the
+//! monitor's name, physical size and modes) and a *CRTC* (the scanout engine driving it, which
+//! knows where on the screen it is and which mode it's in). Everything here needs version 1.3.
+//!
+//! Extension requests start with the extension's major opcode, which the server picks, so every
+//! encoder takes it as `major`.
+
+use crate::{
+    protocol::{reply_reader, Timestamp, Window},
+    wire::{ParseError, RequestBuilder},
+};
+
+/// An output ID.
+pub type Output = u32;
+/// A CRTC ID.
+pub type Crtc = u32;
+/// A mode ID.
+pub type Mode = u32;
+
+/// The version that's asked for and needed.
+pub const VERSION: (u32, u32) = (1, 3);
+
+/// The minor opcodes of the RandR requests, which go in their second byte.
+pub mod minor_opcodes {
+    pub const QUERY_VERSION: u8 = 0;
+    pub const GET_OUTPUT_INFO: u8 = 9;
+    pub const GET_CRTC_INFO: u8 = 20;
+    pub const GET_SCREEN_RESOURCES_CURRENT: u8 = 25;
+    pub const GET_OUTPUT_PRIMARY: u8 = 31;
+}
+
+/// Bits of [`ModeInfo::flags`].
+pub mod mode_flags {
+    pub const INTERLACE: u32 = 0x10;
+    pub const DOUBLE_SCAN: u32 = 0x20;
+}
+
+/// Tells the server which version the client speaks.
+pub fn query_version(major: u8) -> Vec<u8> {
+    RequestBuilder::new(major, minor_opcodes::QUERY_VERSION)
+        .u32(VERSION.0)
+        .u32(VERSION.1)
+        .finish()
+}
+
+/// Lists the screen's CRTCs, outputs and modes, without polling for newly plugged-in monitors
+/// (which can take the server a noticeable while).
+pub fn get_screen_resources_current(major: u8, window: Window) -> Vec<u8> {
+    RequestBuilder::new(major, minor_opcodes::GET_SCREEN_RESOURCES_CURRENT)
+        .u32(window)
+        .finish()
+}
+
+/// Describes an output. `config_timestamp` comes from [`ScreenResources`].
+pub fn get_output_info(major: u8, output: Output, config_timestamp: Timestamp) -> Vec<u8> {
+    RequestBuilder::new(major, minor_opcodes::GET_OUTPUT_INFO)
+        .u32(output)
+        .u32(config_timestamp)
+        .finish()
+}
+
+/// Describes a CRTC. `config_timestamp` comes from [`ScreenResources`].
+pub fn get_crtc_info(major: u8, crtc: Crtc, config_timestamp: Timestamp) -> Vec<u8> {
+    RequestBuilder::new(major, minor_opcodes::GET_CRTC_INFO)
+        .u32(crtc)
+        .u32(config_timestamp)
+        .finish()
+}
+
+/// Finds out which output the user made the primary one, if any.
+pub fn get_output_primary(major: u8, window: Window) -> Vec<u8> {
+    RequestBuilder::new(major, minor_opcodes::GET_OUTPUT_PRIMARY)
+        .u32(window)
+        .finish()
+}
+
+/// Parses the reply to [`query_version`]: the version the server will speak.
+pub fn parse_query_version(reply: &[u8]) -> Result<(u32, u32), ParseError> {
+    let (_, mut r) = reply_reader(reply)?;
+    Ok((r.u32()?, r.u32()?))
+}
+
+/// A display mode's timings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModeInfo {
+    pub id: Mode,
+    pub width: u16,
+    pub height: u16,
+    /// The pixel clock, in hertz.
+    pub dot_clock: u32,
+    pub hsync_start: u16,
+    pub hsync_end: u16,
+    /// The number of pixel clocks per line, including blanking.
+    pub htotal: u16,
+    pub hskew: u16,
+    pub vsync_start: u16,
+    pub vsync_end: u16,
+    /// The number of lines per frame, including blanking.
+    pub vtotal: u16,
+    pub flags: u32,
+    pub name: String,
+}
+
+impl ModeInfo {
+    /// How many frames the mode shows per second, in millihertz, or `None` if the timings don't
+    /// say (which some virtual outputs do).
+    pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+        let mut millicycles = u64::from(self.dot_clock) * 1000;
+        let mut clocks_per_frame = u64::from(self.htotal) * u64::from(self.vtotal);
+        // interlaced modes show half the lines per field, and double-scanned ones show each twice
+        if self.flags & mode_flags::INTERLACE != 0 {
+            millicycles *= 2;
+        }
+        if self.flags & mode_flags::DOUBLE_SCAN != 0 {
+            clocks_per_frame *= 2;
+        }
+        if clocks_per_frame == 0 {
+            return None;
+        }
+        let millihertz = (millicycles + clocks_per_frame / 2) / clocks_per_frame;
+        u32::try_from(millihertz).ok().filter(|&mhz| mhz != 0)
+    }
+}
+
+/// The reply to [`get_screen_resources_current`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreenResources {
+    pub timestamp: Timestamp,
+    /// When the configuration last changed, which requests about it have to quote.
+    pub config_timestamp: Timestamp,
+    pub crtcs: Vec<Crtc>,
+    pub outputs: Vec<Output>,
+    pub modes: Vec<ModeInfo>,
+}
+
+impl ScreenResources {
+    pub fn parse(reply: &[u8]) -> Result<Self, ParseError> {
+        let (_, mut r) = reply_reader(reply)?;
+        let timestamp = r.u32()?;
+        let config_timestamp = r.u32()?;
+        let crtc_count = r.u16()?.into();
+        let output_count = r.u16()?.into();
+        let mode_count = r.u16()?.into();
+        let _names_len = r.u16()?;
+        r.skip(8)?;
+        let crtcs = r.u32s(crtc_count)?;
+        let outputs = r.u32s(output_count)?;
+
+        let mut modes = Vec::with_capacity(mode_count);
+        let mut name_lens = Vec::with_capacity(mode_count);
+        for _ in 0..mode_count {
+            let id = r.u32()?;
+            let width = r.u16()?;
+            let height = r.u16()?;
+            let dot_clock = r.u32()?;
+            let hsync_start = r.u16()?;
+            let hsync_end = r.u16()?;
+            let htotal = r.u16()?;
+            let hskew = r.u16()?;
+            let vsync_start = r.u16()?;
+            let vsync_end = r.u16()?;
+            let vtotal = r.u16()?;
+            name_lens.push(usize::from(r.u16()?));
+            let flags = r.u32()?;
+            modes.push(ModeInfo {
+                id,
+                width,
+                height,
+                dot_clock,
+                hsync_start,
+                hsync_end,
+                htotal,
+                hskew,
+                vsync_start,
+                vsync_end,
+                vtotal,
+                flags,
+                name: String::new(),
+            });
+        }
+        // the names are packed together after the modes, in the same order
+        for (mode, len) in modes.iter_mut().zip(name_lens) {
+            mode.name = String::from_utf8_lossy(r.bytes(len)?).into_owned();
+        }
+
+        Ok(Self {
+            timestamp,
+            config_timestamp,
+            crtcs,
+            outputs,
+            modes,
+        })
+    }
+
+    pub fn mode(&self, id: Mode) -> Option<&ModeInfo> {
+        self.modes.iter().find(|mode| mode.id == id)
+    }
+}
+
+/// Whether a monitor is plugged into an output, from [`OutputInfo::connection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Connection {
+    Connected,
+    Disconnected,
+    Unknown,
+}
+
+/// The reply to [`get_output_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputInfo {
+    /// `0` on success. Anything else means the configuration changed since the config timestamp.
+    pub status: u8,
+    pub timestamp: Timestamp,
+    /// The CRTC driving the output, or [`NONE`](crate::protocol::NONE) if it's off.
+    pub crtc: Crtc,
+    pub mm_width: u32,
+    pub mm_height: u32,
+    pub connection: Connection,
+    pub subpixel_order: u8,
+    /// The CRTCs that could drive the output.
+    pub crtcs: Vec<Crtc>,
+    pub modes: Vec<Mode>,
+    /// How many of the first [`modes`](Self::modes) the monitor prefers.
+    pub preferred_count: u16,
+    /// The outputs that can show the same thing as this one at the same time.
+    pub clones: Vec<Output>,
+    pub name: String,
+}
+
+impl OutputInfo {
+    pub fn parse(reply: &[u8]) -> Result<Self, ParseError> {
+        let (status, mut r) = reply_reader(reply)?;
+        let timestamp = r.u32()?;
+        let crtc = r.u32()?;
+        let mm_width = r.u32()?;
+        let mm_height = r.u32()?;
+        let connection = match r.u8()? {
+            0 => Connection::Connected,
+            1 => Connection::Disconnected,
+            _ => Connection::Unknown,
+        };
+        let subpixel_order = r.u8()?;
+        let crtc_count = r.u16()?.into();
+        let mode_count = r.u16()?.into();
+        let preferred_count = r.u16()?;
+        let clone_count = r.u16()?.into();
+        let name_len = r.u16()?.into();
+        Ok(Self {
+            status,
+            timestamp,
+            crtc,
+            mm_width,
+            mm_height,
+            connection,
+            subpixel_order,
+            crtcs: r.u32s(crtc_count)?,
+            modes: r.u32s(mode_count)?,
+            preferred_count,
+            clones: r.u32s(clone_count)?,
+            name: String::from_utf8_lossy(r.bytes(name_len)?).into_owned(),
+        })
+    }
+}
+
+/// The reply to [`get_crtc_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrtcInfo {
+    /// `0` on success. Anything else means the configuration changed since the config timestamp.
+    pub status: u8,
+    pub timestamp: Timestamp,
+    /// Where the CRTC's top left corner is on the screen.
+    pub x: i16,
+    pub y: i16,
+    /// The size on the screen, after rotation.
+    pub width: u16,
+    pub height: u16,
+    /// The current mode, or [`NONE`](crate::protocol::NONE) if the CRTC is off.
+    pub mode: Mode,
+    pub rotation: u16,
+    pub rotations: u16,
+    pub outputs: Vec<Output>,
+    pub possible_outputs: Vec<Output>,
+}
+
+impl CrtcInfo {
+    pub fn parse(reply: &[u8]) -> Result<Self, ParseError> {
+        let (status, mut r) = reply_reader(reply)?;
+        let timestamp = r.u32()?;
+        let x = r.i16()?;
+        let y = r.i16()?;
+        let width = r.u16()?;
+        let height = r.u16()?;
+        let mode = r.u32()?;
+        let rotation = r.u16()?;
+        let rotations = r.u16()?;
+        let output_count = r.u16()?.into();
+        let possible_count = r.u16()?.into();
+        Ok(Self {
+            status,
+            timestamp,
+            x,
+            y,
+            width,
+            height,
+            mode,
+            rotation,
+            rotations,
+            outputs: r.u32s(output_count)?,
+            possible_outputs: r.u32s(possible_count)?,
+        })
+    }
+}
+
+/// Parses the reply to [`get_output_primary`]. The output is [`NONE`](crate::protocol::NONE) if
+/// there isn't a primary one.
+pub fn parse_output_primary(reply: &[u8]) -> Result<Output, ParseError> {
+    reply_reader(reply)?.1.u32()
+}
+
+/// Finds the `Xft.dpi` setting in the X resources (the root window's `RESOURCE_MANAGER`
+/// property), which is where desktop environments put the user's scaling setting.
+pub fn parse_xft_dpi(resources: &str) -> Option<f64> {
+    resources.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        (name.trim() == "Xft.dpi")
+            .then(|| value.trim().parse().ok())
+            .flatten()
+            .filter(|&dpi: &f64| dpi > 0.0)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `GetScreenResourcesCurrent` reply from a server with one CRTC, two outputs and three
+    /// modes.
+    #[rustfmt::skip]
+    const SCREEN_RESOURCES: &[u8] = &[
+        1, 0, 0x07, 0x00, 0x22, 0x00, 0x00, 0x00, // reply, sequence 7, 34 extra units
+        0x40, 0xE2, 0x01, 0x00, 0x80, 0x96, 0x98, 0x00, // timestamp, config timestamp
+        1, 0, 2, 0, 3, 0, 27, 0, // 1 CRTC, 2 outputs, 3 modes, 27 bytes of names
+        0, 0, 0, 0, 0, 0, 0, 0,
+        0x3F, 0x00, 0x00, 0x00, // CRTC 0x3f
+        0x41, 0x00, 0x00, 0x00, 0x42, 0x00, 0x00, 0x00, // outputs 0x41 and 0x42
+        // 1920x1080 @ 60: 148.5 MHz, 2200x1125
+        0x46, 0, 0, 0, 0x80, 0x07, 0x38, 0x04, 0x20, 0xEE, 0xD9, 0x08,
+        0xD8, 0x07, 0x04, 0x08, 0x98, 0x08, 0, 0,
+        0x3C, 0x04, 0x41, 0x04, 0x65, 0x04, 9, 0, 0x05, 0, 0, 0,
+        // 1920x1080 @ 59.94: 148.352 MHz, 2200x1125
+        0x47, 0, 0, 0, 0x80, 0x07, 0x38, 0x04, 0x00, 0xAC, 0xD7, 0x08,
+        0xD8, 0x07, 0x04, 0x08, 0x98, 0x08, 0, 0,
+        0x3C, 0x04, 0x41, 0x04, 0x65, 0x04, 9, 0, 0x05, 0, 0, 0,
+        // 1024x768i @ 86.96 (interlaced): 44.9 MHz, 1264x817
+        0x48, 0, 0, 0, 0x00, 0x04, 0x00, 0x03, 0xA0, 0x1E, 0xAD, 0x02,
+        0x08, 0x04, 0xB8, 0x04, 0xF0, 0x04, 0, 0,
+        0x00, 0x03, 0x08, 0x03, 0x31, 0x03, 9, 0, 0x15, 0, 0, 0,
+        b'1', b'9', b'2', b'0', b'x', b'1', b'0', b'8', b'0',
+        b'1', b'9', b'2', b'0', b'x', b'1', b'0', b'8', b'0',
+        b'1', b'0', b'2', b'4', b'x', b'7', b'6', b'8', b'i',
+        0,
+    ];
+
+    /// A `GetOutputInfo` reply for a connected 527x296 mm monitor called `HDMI-1`.
+    #[rustfmt::skip]
+    const OUTPUT_INFO: &[u8] = &[
+        1, 0, 0x08, 0x00, 0x07, 0x00, 0x00, 0x00, // reply, success, sequence 8, 7 extra units
+        0x40, 0xE2, 0x01, 0x00, 0x3F, 0x00, 0x00, 0x00, // timestamp, CRTC 0x3f
+        0x0F, 0x02, 0x00, 0x00, 0x28, 0x01, 0x00, 0x00, // 527 x 296 mm
+        0, 1, 1, 0, 3, 0, 1, 0, // connected, horizontal RGB, 1 CRTC, 3 modes, 1 preferred
+        0, 0, 6, 0, // no clones, 6 bytes of name
+        0x3F, 0x00, 0x00, 0x00,
+        0x46, 0x00, 0x00, 0x00, 0x47, 0x00, 0x00, 0x00, 0x48, 0x00, 0x00, 0x00,
+        b'H', b'D', b'M', b'I', b'-', b'1', 0, 0,
+    ];
+
+    /// A `GetCrtcInfo` reply for a CRTC showing 1920x1080 at (1280, 0) on output 0x41.
+    #[rustfmt::skip]
+    const CRTC_INFO: &[u8] = &[
+        1, 0, 0x09, 0x00, 0x03, 0x00, 0x00, 0x00, // reply, success, sequence 9, 3 extra units
+        0x40, 0xE2, 0x01, 0x00, 0x00, 0x05, 0x00, 0x00, // timestamp, x = 1280, y = 0
+        0x80, 0x07, 0x38, 0x04, 0x46, 0x00, 0x00, 0x00, // 1920x1080, mode 0x46
+        0x01, 0x00, 0x3F, 0x00, 0x01, 0x00, 0x02, 0x00, // normal rotation, any rotation, 1 output, 2 possible
+        0x41, 0x00, 0x00, 0x00,
+        0x41, 0x00, 0x00, 0x00, 0x42, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn requests() {
+        assert_eq!(query_version(140), [140, 0, 3, 0, 1, 0, 0, 0, 3, 0, 0, 0]);
+        assert_eq!(
+            get_screen_resources_current(140, 0x123),
+            [140, 25, 2, 0, 0x23, 0x01, 0, 0]
+        );
+        assert_eq!(
+            get_output_info(140, 0x41, 0x98_9680),
+            [140, 9, 3, 0, 0x41, 0, 0, 0, 0x80, 0x96, 0x98, 0]
+        );
+        assert_eq!(get_crtc_info(140, 0x3F, 0)[..2], [140, 20]);
+        assert_eq!(get_output_primary(140, 0x123)[..2], [140, 31]);
+    }
+
+    #[test]
+    fn query_version_reply() {
+        let mut reply = vec![1, 0, 1, 0, 0, 0, 0, 0, 1, 0, 0, 0, 6, 0, 0, 0];
+        reply.resize(32, 0);
+        assert_eq!(parse_query_version(&reply), Ok((1, 6)));
+    }
+
+    #[test]
+    fn screen_resources() {
+        assert_eq!(SCREEN_RESOURCES.len(), 32 + 34 * 4);
+        let resources = ScreenResources::parse(SCREEN_RESOURCES).unwrap();
+        assert_eq!(resources.timestamp, 123_456);
+        assert_eq!(resources.config_timestamp, 10_000_000);
+        assert_eq!(resources.crtcs, [0x3F]);
+        assert_eq!(resources.outputs, [0x41, 0x42]);
+        assert_eq!(resources.modes.len(), 3);
+
+        let mode = resources.mode(0x46).unwrap();
+        assert_eq!((mode.width, mode.height), (1920, 1080));
+        assert_eq!((mode.htotal, mode.vtotal), (2200, 1125));
+        assert_eq!(mode.dot_clock, 148_500_000);
+        assert_eq!(mode.name, "1920x1080");
+        assert_eq!(mode.refresh_rate_millihertz(), Some(60_000));
+
+        let mode = resources.mode(0x47).unwrap();
+        assert_eq!(mode.refresh_rate_millihertz(), Some(59_940));
+
+        let mode = resources.mode(0x48).unwrap();
+        assert_eq!(mode.name, "1024x768i");
+        assert_ne!(mode.flags & mode_flags::INTERLACE, 0);
+        assert_eq!(mode.refresh_rate_millihertz(), Some(86_958));
+
+        assert!(resources.mode(0x49).is_none());
+        assert!(ScreenResources::parse(&SCREEN_RESOURCES[..100]).is_err());
+    }
+
+    #[test]
+    fn refresh_rates() {
+        let mut mode = ScreenResources::parse(SCREEN_RESOURCES).unwrap().modes[0].clone();
+        mode.flags |= mode_flags::DOUBLE_SCAN;
+        assert_eq!(mode.refresh_rate_millihertz(), Some(30_000));
+        mode.htotal = 0;
+        assert_eq!(mode.refresh_rate_millihertz(), None);
+    }
+
+    #[test]
+    fn output_info() {
+        let output = OutputInfo::parse(OUTPUT_INFO).unwrap();
+        assert_eq!(
+            output,
+            OutputInfo {
+                status: 0,
+                timestamp: 123_456,
+                crtc: 0x3F,
+                mm_width: 527,
+                mm_height: 296,
+                connection: Connection::Connected,
+                subpixel_order: 1,
+                crtcs: vec![0x3F],
+                modes: vec![0x46, 0x47, 0x48],
+                preferred_count: 1,
+                clones: vec![],
+                name: "HDMI-1".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn crtc_info() {
+        let crtc = CrtcInfo::parse(CRTC_INFO).unwrap();
+        assert_eq!(
+            (crtc.x, crtc.y, crtc.width, crtc.height),
+            (1280, 0, 1920, 1080)
+        );
+        assert_eq!(crtc.mode, 0x46);
+        assert_eq!(crtc.rotation, 1);
+        assert_eq!(crtc.outputs, [0x41]);
+        assert_eq!(crtc.possible_outputs, [0x41, 0x42]);
+    }
+
+    #[test]
+    fn output_primary() {
+        let mut reply = vec![1, 0, 2, 0, 0, 0, 0, 0, 0x41, 0, 0, 0];
+        reply.resize(32, 0);
+        assert_eq!(parse_output_primary(&reply), Ok(0x41));
+    }
+
+    #[test]
+    fn xft_dpi() {
+        let resources = "Xcursor.size:\t24\nXft.antialias:\t1\nXft.dpi:\t144\n";
+        assert_eq!(parse_xft_dpi(resources), Some(144.0));
+        assert_eq!(parse_xft_dpi("Xft.dpi: 120.5"), Some(120.5));
+        assert_eq!(parse_xft_dpi("Xcursor.size:\t24\n"), None);
+        assert_eq!(parse_xft_dpi("Xft.dpi:\tlots\n"), None);
+        assert_eq!(parse_xft_dpi("Xft.dpi:\t0\n"), None);
+    }
+}
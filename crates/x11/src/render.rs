@@ -0,0 +1,207 @@
+//! Encoding and decoding the parts of the RENDER extension that make cursors from ARGB images.
+//!
+//! The core protocol's cursors only have two colours. RENDER makes a cursor from a *picture*
+//! instead, which is a pixmap together with a *picture format* saying how its pixels are laid
+//! out. Cursors need version 0.5.
+//!
+//! Like [`randr`](crate::randr), every encoder takes the extension's major opcode as `major`.
+
+use crate::{
+    protocol::{reply_reader, Cursor, Drawable},
+    wire::{ParseError, RequestBuilder},
+};
+
+/// A picture ID.
+pub type Picture = u32;
+/// A picture format ID.
+pub type PictFormat = u32;
+
+/// The version that's asked for and needed.
+pub const VERSION: (u32, u32) = (0, 5);
+
+/// The minor opcodes of the RENDER requests, which go in their second byte.
+pub mod minor_opcodes {
+    pub const QUERY_VERSION: u8 = 0;
+    pub const QUERY_PICT_FORMATS: u8 = 1;
+    pub const CREATE_PICTURE: u8 = 4;
+    pub const FREE_PICTURE: u8 = 7;
+    pub const CREATE_CURSOR: u8 = 27;
+}
+
+/// Tells the server which version the client speaks.
+pub fn query_version(major: u8) -> Vec<u8> {
+    RequestBuilder::new(major, minor_opcodes::QUERY_VERSION)
+        .u32(VERSION.0)
+        .u32(VERSION.1)
+        .finish()
+}
+
+/// Lists the picture formats the server supports.
+pub fn query_pict_formats(major: u8) -> Vec<u8> {
+    RequestBuilder::new(major, minor_opcodes::QUERY_PICT_FORMATS).finish()
+}
+
+/// Makes a picture of `drawable`, whose depth has to match `format`'s.
+pub fn create_picture(major: u8, id: Picture, drawable: Drawable, format: PictFormat) -> Vec<u8> {
+    RequestBuilder::new(major, minor_opcodes::CREATE_PICTURE)
+        .u32(id)
+        .u32(drawable)
+        .u32(format)
+        // no attributes
+        .u32(0)
+        .finish()
+}
+
+pub fn free_picture(major: u8, picture: Picture) -> Vec<u8> {
+    RequestBuilder::new(major, minor_opcodes::FREE_PICTURE)
+        .u32(picture)
+        .finish()
+}
+
+/// Makes a cursor out of a picture, which can be freed straight afterwards.
+pub fn create_cursor(major: u8, id: Cursor, source: Picture, hotspot: [u16; 2]) -> Vec<u8> {
+    RequestBuilder::new(major, minor_opcodes::CREATE_CURSOR)
+        .u32(id)
+        .u32(source)
+        .u16(hotspot[0])
+        .u16(hotspot[1])
+        .finish()
+}
+
+pub fn parse_query_version(reply: &[u8]) -> Result<(u32, u32), ParseError> {
+    let (_, mut r) = reply_reader(reply)?;
+    Ok((r.u32()?, r.u32()?))
+}
+
+/// How one channel sits in a pixel of a [`PictFormatInfo`]: `(pixel >> shift) & mask`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Channel {
+    pub shift: u16,
+    pub mask: u16,
+}
+
+/// A picture format from [`parse_query_pict_formats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PictFormatInfo {
+    pub id: PictFormat,
+    /// Whether pixels are colours, rather than indices into a colormap.
+    pub direct: bool,
+    pub depth: u8,
+    pub red: Channel,
+    pub green: Channel,
+    pub blue: Channel,
+    pub alpha: Channel,
+}
+
+impl PictFormatInfo {
+    /// Whether this is the standard 32-bit format with 8 bits of alpha, red, green and blue, from
+    /// the top of the pixel down. Pixels of this format in a little-endian image are in BGRA
+    /// order.
+    pub fn is_argb32(&self) -> bool {
+        let byte = |shift| Channel { shift, mask: 0xFF };
+        self.direct
+            && self.depth == 32
+            && self.alpha == byte(24)
+            && self.red == byte(16)
+            && self.green == byte(8)
+            && self.blue == byte(0)
+    }
+}
+
+/// Parses the formats out of the reply to [`query_pict_formats`]. The rest of the reply (which
+/// formats go with which screens and visuals) isn't needed for cursors, so it's skipped.
+pub fn parse_query_pict_formats(reply: &[u8]) -> Result<Vec<PictFormatInfo>, ParseError> {
+    let (_, mut r) = reply_reader(reply)?;
+    let num_formats = r.u32()?;
+    // the numbers of screens, depths, visuals and subpixel orders, and padding
+    r.skip(20)?;
+    (0..num_formats)
+        .map(|_| {
+            let id = r.u32()?;
+            let direct = r.u8()? == 1;
+            let depth = r.u8()?;
+            r.skip(2)?;
+            let mut channel = || -> Result<Channel, ParseError> {
+                Ok(Channel {
+                    shift: r.u16()?,
+                    mask: r.u16()?,
+                })
+            };
+            let (red, green, blue, alpha) = (channel()?, channel()?, channel()?, channel()?);
+            // the colormap, for indexed formats
+            r.skip(4)?;
+            Ok(PictFormatInfo {
+                id,
+                direct,
+                depth,
+                red,
+                green,
+                blue,
+                alpha,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `QueryPictFormats` reply with an 8-bit alpha format and the ARGB32 format, and no
+    /// screens.
+    #[rustfmt::skip]
+    const PICT_FORMATS: &[u8] = &[
+        1, 0, 0x05, 0x00, 0x0E, 0x00, 0x00, 0x00, // reply, sequence 5, 14 extra units
+        2, 0, 0, 0, 0, 0, 0, 0, // 2 formats, no screens
+        0, 0, 0, 0, 0, 0, 0, 0, // no depths or visuals
+        0, 0, 0, 0, 0, 0, 0, 0, // no subpixel orders
+        // A8
+        0x25, 0, 0, 0, 1, 8, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xFF, 0, 0, 0, 0, 0,
+        // ARGB32
+        0x26, 0, 0, 0, 1, 32, 0, 0,
+        16, 0, 0xFF, 0, 8, 0, 0xFF, 0, 0, 0, 0xFF, 0, 24, 0, 0xFF, 0, 0, 0, 0, 0,
+    ];
+
+    #[test]
+    fn requests() {
+        assert_eq!(query_version(139), [139, 0, 3, 0, 0, 0, 0, 0, 5, 0, 0, 0]);
+        assert_eq!(query_pict_formats(139), [139, 1, 1, 0]);
+        assert_eq!(
+            create_picture(139, 0x200001, 0x200000, 0x26),
+            [139, 4, 5, 0, 1, 0, 0x20, 0, 0, 0, 0x20, 0, 0x26, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(free_picture(139, 0x200001), [139, 7, 2, 0, 1, 0, 0x20, 0]);
+        assert_eq!(
+            create_cursor(139, 0x200002, 0x200001, [3, 4]),
+            [139, 27, 4, 0, 2, 0, 0x20, 0, 1, 0, 0x20, 0, 3, 0, 4, 0]
+        );
+    }
+
+    #[test]
+    fn query_version_reply() {
+        let mut reply = vec![1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 11, 0, 0, 0];
+        reply.resize(32, 0);
+        assert_eq!(parse_query_version(&reply), Ok((0, 11)));
+    }
+
+    #[test]
+    fn pict_formats() {
+        assert_eq!(PICT_FORMATS.len(), 32 + 14 * 4);
+        let formats = parse_query_pict_formats(PICT_FORMATS).unwrap();
+        assert_eq!(formats.len(), 2);
+        assert_eq!((formats[0].id, formats[0].depth), (0x25, 8));
+        assert!(!formats[0].is_argb32());
+        assert_eq!(formats[1].id, 0x26);
+        assert_eq!(
+            formats[1].red,
+            Channel {
+                shift: 16,
+                mask: 0xFF
+            }
+        );
+        assert!(formats[1].is_argb32());
+
+        assert!(parse_query_pict_formats(&PICT_FORMATS[..60]).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+//! Reading the `text/uri-list` format
+//! ([RFC 2483](https://www.rfc-editor.org/rfc/rfc2483#section-5)) that dragged files come in.
+//!
+//! A list is one URI per line, with lines ending in CRLF and comments starting with `#`. Files
+//! are `file:` URIs, with their paths percent-encoded.
+
+use std::{ffi::OsString, path::PathBuf};
+
+/// The local files in a URI list, in order. Other URIs, like `http:` ones or files on other
+/// hosts, are left out.
+///
+/// Bare LFs are accepted as line endings too, since plenty of programs send them.
+pub fn parse(list: &str) -> Vec<PathBuf> {
+    list.lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(file_path)
+        .collect()
+}
+
+/// The path in a `file:` URI, or `None` if it isn't one, or is for a file on another host.
+///
+/// `file:///path`, `file://localhost/path` and `file:/path` are all accepted. The path isn't
+/// checked, so `..` and such are left in.
+pub fn file_path(uri: &str) -> Option<PathBuf> {
+    let (scheme, rest) = uri.split_once(':')?;
+    if !scheme.eq_ignore_ascii_case("file") {
+        return None;
+    }
+    let path = match rest.strip_prefix("//") {
+        Some(authority_and_path) => {
+            let slash = authority_and_path.find('/')?;
+            let host = &authority_and_path[..slash];
+            if !host.is_empty() && !host.eq_ignore_ascii_case("localhost") {
+                return None;
+            }
+            &authority_and_path[slash..]
+        }
+        None if rest.starts_with('/') => rest,
+        None => return None,
+    };
+    Some(bytes_to_path(percent_decode(path)))
+}
+
+/// Decodes `%XX` escapes. A `%` that isn't followed by two hex digits is kept as it is.
+pub fn percent_decode(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match bytes[i..] {
+            [b'%', high, low, ..] => hex_value(high).zip(hex_value(low)),
+            _ => None,
+        };
+        match escaped {
+            Some((high, low)) => {
+                decoded.push(high << 4 | low);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+fn hex_value(digit: u8) -> Option<u8> {
+    char::from(digit).to_digit(16).map(|v| v as u8)
+}
+
+/// Paths on Unix are bytes, so they don't have to be UTF-8. Elsewhere, invalid UTF-8 is replaced.
+fn bytes_to_path(bytes: Vec<u8>) -> PathBuf {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        OsString::from_vec(bytes).into()
+    }
+    #[cfg(not(unix))]
+    {
+        OsString::from(String::from_utf8_lossy(&bytes).into_owned()).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_decoding() {
+        assert_eq!(percent_decode("a%20b"), b"a b");
+        assert_eq!(percent_decode("%E2%9c%93"), "✓".as_bytes());
+        // not escapes, so left alone
+        assert_eq!(percent_decode("100%"), b"100%");
+        assert_eq!(percent_decode("%zz%4"), b"%zz%4");
+        assert_eq!(percent_decode("%%41"), b"%A");
+    }
+
+    #[test]
+    fn file_uris() {
+        assert_eq!(
+            file_path("file:///home/me/a%20mesh.obj"),
+            Some("/home/me/a mesh.obj".into())
+        );
+        assert_eq!(
+            file_path("file://localhost/tmp/x.png"),
+            Some("/tmp/x.png".into())
+        );
+        assert_eq!(file_path("FILE:/tmp/x.png"), Some("/tmp/x.png".into()));
+
+        assert_eq!(file_path("file://elsewhere/tmp/x.png"), None);
+        assert_eq!(file_path("https://example.com/x.png"), None);
+        assert_eq!(file_path("file:x.png"), None);
+        assert_eq!(file_path("/tmp/x.png"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn paths_need_not_be_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+        let path = file_path("file:///tmp/%FF.bin").unwrap();
+        assert_eq!(path.as_os_str().as_bytes(), b"/tmp/\xFF.bin");
+    }
+
+    #[test]
+    fn lists() {
+        let list = "# dragged from a file manager\r\n\
+                    file:///home/me/cube.obj\r\n\
+                    \r\n\
+                    http://example.com/not-a-file.png\r\n\
+                    file:///home/me/brick%23wall.png\n\
+                    file:///home/me/last";
+        assert_eq!(
+            parse(list),
+            [
+                PathBuf::from("/home/me/cube.obj"),
+                "/home/me/brick#wall.png".into(),
+                "/home/me/last".into(),
+            ]
+        );
+        assert_eq!(parse(""), [] as [PathBuf; 0]);
+    }
+}
@@ -0,0 +1,222 @@
+//! Encoding and decoding the bytes of the X11 protocol.
+//!
+//! Everything is little-endian, since that's the byte order [`Connection`] asks the server for
+//! when it connects. Requests, replies and events are all padded to multiples of four bytes.
+//!
+//! [`Connection`]: crate::connection::Connection
+
+use core::fmt;
+
+/// How many bytes are needed to pad `len` to a multiple of four.
+pub const fn pad(len: usize) -> usize {
+    (4 - len % 4) % 4
+}
+
+/// Builds a request: a one-byte major opcode, one byte of request-specific data, and a two-byte
+/// length (in four-byte units, including the header), followed by the request's fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestBuilder {
+    bytes: Vec<u8>,
+}
+
+impl RequestBuilder {
+    pub fn new(opcode: u8, data: u8) -> Self {
+        Self {
+            // the length is filled in by `finish`
+            bytes: vec![opcode, data, 0, 0],
+        }
+    }
+
+    pub fn u8(mut self, value: u8) -> Self {
+        self.bytes.push(value);
+        self
+    }
+
+    pub fn u16(mut self, value: u16) -> Self {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn u32(mut self, value: u32) -> Self {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn i16(self, value: i16) -> Self {
+        self.u16(value as u16)
+    }
+
+    pub fn i32(self, value: i32) -> Self {
+        self.u32(value as u32)
+    }
+
+    /// Appends raw bytes, without any padding.
+    pub fn bytes(mut self, bytes: &[u8]) -> Self {
+        self.bytes.extend_from_slice(bytes);
+        self
+    }
+
+    /// Appends `n` unused bytes.
+    pub fn skip(mut self, n: usize) -> Self {
+        self.bytes.resize(self.bytes.len() + n, 0);
+        self
+    }
+
+    /// Pads the request to a multiple of four bytes, which strings and lists are followed by.
+    pub fn align(self) -> Self {
+        let n = pad(self.bytes.len());
+        self.skip(n)
+    }
+
+    /// Pads the request and fills in its length.
+    ///
+    /// ## Panics
+    ///
+    /// If the request is longer than a two-byte length can describe. (The `BIG-REQUESTS`
+    /// extension isn't supported.)
+    pub fn finish(self) -> Vec<u8> {
+        let mut bytes = self.align().bytes;
+        let units = u16::try_from(bytes.len() / 4).expect("X11 request too long");
+        bytes[2..4].copy_from_slice(&units.to_le_bytes());
+        bytes
+    }
+}
+
+/// Ran out of bytes while decoding something.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    /// The offset of the field that couldn't be read.
+    pub offset: usize,
+    /// How many bytes the field needed.
+    pub wanted: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "truncated X11 message: wanted {} bytes at offset {}",
+            self.wanted, self.offset
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Reads fields out of a reply, event or setup message, in order.
+#[derive(Debug, Clone)]
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    /// How far into the bytes the reader is.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    pub fn bytes(&mut self, n: usize) -> Result<&'a [u8], ParseError> {
+        let error = ParseError {
+            offset: self.offset,
+            wanted: n,
+        };
+        let end = self.offset.checked_add(n).ok_or(error)?;
+        let bytes = self.bytes.get(self.offset..end).ok_or(error)?;
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ParseError> {
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    pub fn u8(&mut self) -> Result<u8, ParseError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, ParseError> {
+        self.array().map(u16::from_le_bytes)
+    }
+
+    pub fn u32(&mut self) -> Result<u32, ParseError> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    pub fn u64(&mut self) -> Result<u64, ParseError> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    pub fn i16(&mut self) -> Result<i16, ParseError> {
+        self.array().map(i16::from_le_bytes)
+    }
+
+    pub fn i32(&mut self) -> Result<i32, ParseError> {
+        self.array().map(i32::from_le_bytes)
+    }
+
+    /// Skips `n` unused bytes.
+    pub fn skip(&mut self, n: usize) -> Result<(), ParseError> {
+        self.bytes(n).map(drop)
+    }
+
+    /// Skips the padding after a string or list, up to a multiple of four bytes from the start.
+    pub fn align(&mut self) -> Result<(), ParseError> {
+        self.skip(pad(self.offset))
+    }
+
+    /// Reads `n` four-byte values.
+    pub fn u32s(&mut self, n: usize) -> Result<Vec<u32>, ParseError> {
+        (0..n).map(|_| self.u32()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_are_padded_and_sized() {
+        let request = RequestBuilder::new(16, 1)
+            .u16(3)
+            .skip(2)
+            .bytes(b"abc")
+            .finish();
+        assert_eq!(request, [16, 1, 3, 0, 3, 0, 0, 0, b'a', b'b', b'c', 0]);
+
+        assert_eq!(RequestBuilder::new(43, 0).finish(), [43, 0, 1, 0]);
+        assert_eq!(pad(0), 0);
+        assert_eq!(pad(5), 3);
+    }
+
+    #[test]
+    fn reading() {
+        let bytes = [
+            1, 0x34, 0x12, 0xFF, 0xFF, 0xFF, 0xFF, b'h', b'i', 0, 0, 0, 9,
+        ];
+        let mut reader = Reader::new(&bytes);
+        assert_eq!(reader.u8(), Ok(1));
+        assert_eq!(reader.u16(), Ok(0x1234));
+        assert_eq!(reader.i32(), Ok(-1));
+        assert_eq!(reader.bytes(2), Ok(&b"hi"[..]));
+        reader.align().unwrap();
+        assert_eq!(reader.offset(), 12);
+        assert_eq!(
+            reader.u32(),
+            Err(ParseError {
+                offset: 12,
+                wanted: 4
+            })
+        );
+        assert_eq!(reader.u8(), Ok(9));
+        assert_eq!(reader.remaining(), 0);
+    }
+}
@@ -0,0 +1,373 @@
+//! Reading cursor themes in the Xcursor format, which is what Linux desktops install their
+//! cursors as.
+//!
+//! A theme is a directory (like `/usr/share/icons/Adwaita`) with a `cursors` directory of files
+//! named after the cursors in them (`left_ptr`, `xterm`, `watch`, ...), and an `index.theme` that
+//! can name other themes to fall back to. Each file has images at a few nominal sizes, and an
+//! animated cursor has several images at each size.
+//!
+//! The file format is described in
+//! [`Xcursor(3)`](https://www.x.org/releases/current/doc/man/man3/Xcursor.3.xhtml).
+
+use std::{
+    collections::HashSet,
+    env, fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::wire::Reader;
+
+/// `Xcur`, at the start of every file.
+const MAGIC: &[u8; 4] = b"Xcur";
+/// The table-of-contents type of image chunks.
+const IMAGE_TYPE: u32 = 0xFFFD_0002;
+/// Images bigger than this (in either direction) are refused, as libXcursor does.
+pub const MAX_IMAGE_SIZE: u32 = 0x7FFF;
+
+/// Where themes are looked for when `$XCURSOR_PATH` isn't set, with `~` for the home directory.
+pub const DEFAULT_PATH: &[&str] = &[
+    "~/.local/share/icons",
+    "~/.icons",
+    "/usr/share/icons",
+    "/usr/share/pixmaps",
+];
+
+/// The size cursors are drawn at when `$XCURSOR_SIZE` isn't set.
+pub const DEFAULT_SIZE: u32 = 24;
+
+/// Why an Xcursor file couldn't be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The file doesn't start with `Xcur`.
+    NotXcursor,
+    /// A chunk or header ends past the end of the file.
+    Truncated,
+    /// An image is empty, or bigger than [`MAX_IMAGE_SIZE`] in either direction.
+    BadImageSize { width: u32, height: u32 },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotXcursor => write!(f, "not an Xcursor file"),
+            Self::Truncated => write!(f, "truncated Xcursor file"),
+            Self::BadImageSize { width, height } => {
+                write!(f, "Xcursor image has an invalid size of {width}x{height}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// One image from an Xcursor file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    /// The size this image is meant to be used at, which is usually its width and height.
+    pub nominal_size: u32,
+    pub width: u32,
+    pub height: u32,
+    /// The pixel under the pointer's actual position.
+    pub hotspot: [u32; 2],
+    /// How long to show this image for, in an animated cursor.
+    pub delay_ms: u32,
+    /// Premultiplied ARGB pixels, row by row, with alpha in the top byte.
+    pub pixels: Vec<u32>,
+}
+
+impl Image {
+    /// The pixels as straight (not premultiplied) RGBA bytes.
+    pub fn to_rgba(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|&pixel| {
+                let [b, g, r, a] = pixel.to_le_bytes();
+                let unpremultiply = |c: u8| match a {
+                    0 => 0,
+                    a => ((u32::from(c) * 255 + u32::from(a) / 2) / u32::from(a)).min(255) as u8,
+                };
+                [unpremultiply(r), unpremultiply(g), unpremultiply(b), a]
+            })
+            .collect()
+    }
+}
+
+/// Reads every image out of an Xcursor file, in the order of its table of contents. Chunks that
+/// aren't images (like comments) are skipped.
+pub fn parse(bytes: &[u8]) -> Result<Vec<Image>, Error> {
+    if !bytes.starts_with(MAGIC) {
+        return Err(Error::NotXcursor);
+    }
+    let mut r = Reader::new(bytes);
+    let truncated = |_| Error::Truncated;
+    r.skip(4).map_err(truncated)?;
+    let header_size = r.u32().map_err(truncated)?;
+    let _version = r.u32().map_err(truncated)?;
+    let count = r.u32().map_err(truncated)?;
+
+    let mut toc = Reader::new(bytes);
+    toc.skip(header_size as usize).map_err(truncated)?;
+    let mut images = Vec::new();
+    for _ in 0..count {
+        let type_ = toc.u32().map_err(truncated)?;
+        let _subtype = toc.u32().map_err(truncated)?;
+        let position = toc.u32().map_err(truncated)?;
+        if type_ == IMAGE_TYPE {
+            images.push(parse_image(bytes, position as usize)?);
+        }
+    }
+    Ok(images)
+}
+
+fn parse_image(bytes: &[u8], position: usize) -> Result<Image, Error> {
+    let mut r = Reader::new(bytes);
+    let truncated = |_| Error::Truncated;
+    r.skip(position).map_err(truncated)?;
+    let header_size = r.u32().map_err(truncated)?;
+    let _type = r.u32().map_err(truncated)?;
+    let nominal_size = r.u32().map_err(truncated)?;
+    let _version = r.u32().map_err(truncated)?;
+    let width = r.u32().map_err(truncated)?;
+    let height = r.u32().map_err(truncated)?;
+    let hotspot = [r.u32().map_err(truncated)?, r.u32().map_err(truncated)?];
+    let delay_ms = r.u32().map_err(truncated)?;
+    if width == 0 || height == 0 || width > MAX_IMAGE_SIZE || height > MAX_IMAGE_SIZE {
+        return Err(Error::BadImageSize { width, height });
+    }
+
+    // the pixels come straight after the header, which may be longer in later versions
+    let mut r = Reader::new(bytes);
+    r.skip(position + header_size as usize).map_err(truncated)?;
+    let pixels = r
+        .u32s(width as usize * height as usize)
+        .map_err(truncated)?;
+    Ok(Image {
+        nominal_size,
+        width,
+        height,
+        // libXcursor moves hotspots that are outside the image onto its edge
+        hotspot: [hotspot[0].min(width - 1), hotspot[1].min(height - 1)],
+        delay_ms,
+        pixels,
+    })
+}
+
+/// The frames of the size closest to `size`, in order. There's one frame unless the cursor is
+/// animated. Ties go to the smaller size.
+pub fn frames_for_size(images: &[Image], size: u32) -> Vec<&Image> {
+    let Some(best) = images
+        .iter()
+        .map(|image| image.nominal_size)
+        .min_by_key(|&nominal| (nominal.abs_diff(size), nominal))
+    else {
+        return Vec::new();
+    };
+    images
+        .iter()
+        .filter(|image| image.nominal_size == best)
+        .collect()
+}
+
+/// The themes a theme inherits from, from the `Inherits` key of its `index.theme`. Names can be
+/// separated by commas or semicolons.
+pub fn parse_inherits(index_theme: &str) -> Vec<String> {
+    let mut in_icon_theme = false;
+    for line in index_theme.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_icon_theme = line == "[Icon Theme]";
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if in_icon_theme && key.trim() == "Inherits" {
+            return value
+                .split([',', ';'])
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(String::from)
+                .collect();
+        }
+    }
+    Vec::new()
+}
+
+/// Where to look for cursor themes, in order: `$XCURSOR_PATH` (colon-separated) if it's set, or
+/// else [`DEFAULT_PATH`]. A leading `~` is expanded to `home`, and entries that need a home
+/// directory are left out without one.
+pub fn search_path(xcursor_path: Option<&str>, home: Option<&Path>) -> Vec<PathBuf> {
+    let entries: Vec<&str> = match xcursor_path {
+        Some(path) => path.split(':').filter(|dir| !dir.is_empty()).collect(),
+        None => DEFAULT_PATH.to_vec(),
+    };
+    entries
+        .into_iter()
+        .filter_map(|dir| match dir.strip_prefix('~') {
+            Some(rest) => home.map(|home| home.join(rest.trim_start_matches('/'))),
+            None => Some(PathBuf::from(dir)),
+        })
+        .collect()
+}
+
+/// Finds cursor files in themes on disk.
+#[derive(Debug, Clone)]
+pub struct ThemeLoader {
+    search_path: Vec<PathBuf>,
+}
+
+impl ThemeLoader {
+    pub fn new(search_path: Vec<PathBuf>) -> Self {
+        Self { search_path }
+    }
+
+    /// Searches where libXcursor does, from `$XCURSOR_PATH` and `$HOME`.
+    pub fn from_env() -> Self {
+        let xcursor_path = env::var("XCURSOR_PATH").ok();
+        let home = env::var_os("HOME").map(PathBuf::from);
+        Self::new(search_path(xcursor_path.as_deref(), home.as_deref()))
+    }
+
+    /// The file for the cursor called `name` in `theme`, or in the themes it inherits from.
+    pub fn find(&self, theme: &str, name: &str) -> Option<PathBuf> {
+        self.find_inner(theme, name, &mut HashSet::new())
+    }
+
+    /// Finds and reads a cursor, and picks the frames for `size`. `None` if the theme doesn't
+    /// have the cursor, or its file can't be read.
+    pub fn load(&self, theme: &str, name: &str, size: u32) -> Option<Vec<Image>> {
+        let bytes = fs::read(self.find(theme, name)?).ok()?;
+        let images = parse(&bytes).ok()?;
+        let frames: Vec<Image> = frames_for_size(&images, size)
+            .into_iter()
+            .cloned()
+            .collect();
+        (!frames.is_empty()).then_some(frames)
+    }
+
+    fn find_inner(
+        &self,
+        theme: &str,
+        name: &str,
+        visited: &mut HashSet<String>,
+    ) -> Option<PathBuf> {
+        // themes can inherit from each other in a loop
+        if !visited.insert(theme.to_string()) {
+            return None;
+        }
+        let dirs = self.search_path.iter().map(|dir| dir.join(theme));
+        if let Some(file) = dirs
+            .clone()
+            .map(|dir| dir.join("cursors").join(name))
+            .find(|file| file.is_file())
+        {
+            return Some(file);
+        }
+        for dir in dirs {
+            let Ok(index) = fs::read_to_string(dir.join("index.theme")) else {
+                continue;
+            };
+            for parent in parse_inherits(&index) {
+                if let Some(file) = self.find_inner(&parent, name, visited) {
+                    return Some(file);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// The theme and size that cursors should be loaded with, from `$XCURSOR_THEME` and
+/// `$XCURSOR_SIZE`, falling back to the `default` theme and [`DEFAULT_SIZE`].
+///
+/// Desktops also put these in the `Xcursor.theme` and `Xcursor.size` X resources, which this
+/// doesn't read.
+pub fn theme_from_env() -> (String, u32) {
+    let theme = env::var("XCURSOR_THEME")
+        .ok()
+        .filter(|theme| !theme.is_empty())
+        .unwrap_or_else(|| "default".into());
+    let size = env::var("XCURSOR_SIZE")
+        .ok()
+        .and_then(|size| size.trim().parse().ok())
+        .filter(|&size| size > 0)
+        .unwrap_or(DEFAULT_SIZE);
+    (theme, size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(nominal_size: u32) -> Image {
+        Image {
+            nominal_size,
+            width: 1,
+            height: 1,
+            hotspot: [0, 0],
+            delay_ms: 0,
+            pixels: vec![0],
+        }
+    }
+
+    #[test]
+    fn sizes() {
+        let images = [image(16), image(32), image(32), image(48)];
+        let sizes = |size| -> Vec<u32> {
+            frames_for_size(&images, size)
+                .iter()
+                .map(|image| image.nominal_size)
+                .collect()
+        };
+        assert_eq!(sizes(32), [32, 32]);
+        assert_eq!(sizes(24), [16]);
+        assert_eq!(sizes(42), [48]);
+        assert_eq!(sizes(1000), [48]);
+        assert!(frames_for_size(&[], 24).is_empty());
+    }
+
+    #[test]
+    fn unpremultiplying() {
+        let image = Image {
+            pixels: vec![0xFF10_2030, 0x8040_0000, 0x0000_0000],
+            width: 3,
+            ..image(3)
+        };
+        assert_eq!(
+            image.to_rgba(),
+            [0x10, 0x20, 0x30, 0xFF, 0x80, 0, 0, 0x80, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn inherits() {
+        let index = "[Icon Theme]\nName=Child\nInherits = base, other;third\n";
+        assert_eq!(parse_inherits(index), ["base", "other", "third"]);
+        // only the icon theme section counts
+        assert!(parse_inherits("[Other]\nInherits=base\n").is_empty());
+        assert!(parse_inherits("").is_empty());
+    }
+
+    #[test]
+    fn search_paths() {
+        let home = Path::new("/home/me");
+        assert_eq!(
+            search_path(None, Some(home)),
+            [
+                PathBuf::from("/home/me/.local/share/icons"),
+                "/home/me/.icons".into(),
+                "/usr/share/icons".into(),
+                "/usr/share/pixmaps".into(),
+            ]
+        );
+        assert_eq!(
+            search_path(Some("~/cursors::/opt/icons"), None),
+            [PathBuf::from("/opt/icons")]
+        );
+    }
+
+    #[test]
+    fn not_xcursor() {
+        assert_eq!(parse(b"\x89PNG"), Err(Error::NotXcursor));
+        assert_eq!(parse(b"Xcur\x10\0\0\0"), Err(Error::Truncated));
+    }
+}
@@ -0,0 +1,221 @@
+//! Encoding and decoding the parts of the XInput2 extension that give raw mouse motion.
+//!
+//! Raw events come straight from the device: they aren't accelerated, and don't stop at the edges
+//! of the screen, so they're what a first-person camera wants. They're only sent to the root
+//! window, and arrive as `GenericEvent`s.
+//!
+//! Like [`randr`](crate::randr), every encoder takes the extension's major opcode as `major`.
+
+use crate::{
+    protocol::{event_codes, reply_reader, Timestamp, Window},
+    wire::{ParseError, Reader, RequestBuilder},
+};
+
+/// The version that's asked for and needed.
+pub const VERSION: (u16, u16) = (2, 0);
+
+/// The minor opcodes of the XInput2 requests, which go in their second byte.
+pub mod minor_opcodes {
+    pub const XI_SELECT_EVENTS: u8 = 46;
+    pub const XI_QUERY_VERSION: u8 = 47;
+}
+
+/// Special device IDs, for [`select_events`].
+pub mod devices {
+    pub const ALL: u16 = 0;
+    /// The master pointer and keyboard, which all the physical devices feed into.
+    pub const ALL_MASTER: u16 = 1;
+}
+
+/// XInput2 event types, in the `evtype` field of their `GenericEvent`s.
+pub mod event_types {
+    pub const RAW_MOTION: u16 = 17;
+}
+
+/// Tells the server which version the client speaks. The reply has the version it'll speak.
+pub fn query_version(major: u8) -> Vec<u8> {
+    RequestBuilder::new(major, minor_opcodes::XI_QUERY_VERSION)
+        .u16(VERSION.0)
+        .u16(VERSION.1)
+        .finish()
+}
+
+pub fn parse_query_version(reply: &[u8]) -> Result<(u16, u16), ParseError> {
+    let (_, mut r) = reply_reader(reply)?;
+    Ok((r.u16()?, r.u16()?))
+}
+
+/// Selects which events of each device the client gets on `window`. Each mask has bit `n` set for
+/// event type `n`, and an empty mask deselects everything.
+pub fn select_events(major: u8, window: Window, masks: &[(u16, u32)]) -> Vec<u8> {
+    masks
+        .iter()
+        .fold(
+            RequestBuilder::new(major, minor_opcodes::XI_SELECT_EVENTS)
+                .u32(window)
+                .u16(masks.len() as u16)
+                .skip(2),
+            // each mask is one four-byte unit long
+            |request, &(device, mask)| request.u16(device).u16(1).u32(mask),
+        )
+        .finish()
+}
+
+/// A `RawMotion` event: how far each of a device's axes moved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawMotion {
+    pub device: u16,
+    pub time: Timestamp,
+    /// The physical device it came from, as opposed to the master device.
+    pub source: u16,
+    /// The axes that moved, with how far they moved after acceleration, and before it.
+    pub axes: Vec<RawAxis>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RawAxis {
+    pub index: u16,
+    pub value: f64,
+    pub raw_value: f64,
+}
+
+impl RawMotion {
+    /// Decodes a raw motion event, or returns `Ok(None)` if `bytes` is some other event.
+    /// `major` is XInput2's major opcode, which `GenericEvent`s from it carry.
+    pub fn parse(major: u8, bytes: &[u8]) -> Result<Option<Self>, ParseError> {
+        let mut r = Reader::new(bytes);
+        let code = r.u8()? & 0x7F;
+        let extension = r.u8()?;
+        let _sequence = r.u16()?;
+        let _length = r.u32()?;
+        let event_type = r.u16()?;
+        if code != event_codes::GENERIC_EVENT
+            || extension != major
+            || event_type != event_types::RAW_MOTION
+        {
+            return Ok(None);
+        }
+
+        let device = r.u16()?;
+        let time = r.u32()?;
+        let _detail = r.u32()?;
+        let source = r.u16()?;
+        let mask_len = r.u16()?.into();
+        let _flags = r.u32()?;
+        r.skip(4)?;
+        let mask = r.u32s(mask_len)?;
+
+        // only the axes in the mask have values, first all accelerated and then all raw
+        let indices: Vec<u16> = (0..mask_len * 32)
+            .filter(|&bit| mask[bit / 32] & (1 << (bit % 32)) != 0)
+            .map(|bit| bit as u16)
+            .collect();
+        let values = (0..indices.len())
+            .map(|_| fp3232(&mut r))
+            .collect::<Result<Vec<_>, _>>()?;
+        let raw_values = (0..indices.len())
+            .map(|_| fp3232(&mut r))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Some(Self {
+            device,
+            time,
+            source,
+            axes: indices
+                .into_iter()
+                .zip(values.into_iter().zip(raw_values))
+                .map(|(index, (value, raw_value))| RawAxis {
+                    index,
+                    value,
+                    raw_value,
+                })
+                .collect(),
+        }))
+    }
+
+    /// How far the mouse moved, before acceleration: the raw values of the first two axes, which
+    /// are relative X and Y motion for mice.
+    pub fn delta(&self) -> [f64; 2] {
+        let axis = |index| {
+            self.axes
+                .iter()
+                .find(|axis| axis.index == index)
+                .map_or(0.0, |axis| axis.raw_value)
+        };
+        [axis(0), axis(1)]
+    }
+}
+
+/// Reads a fixed-point number with a 32-bit integer part and a 32-bit fraction.
+fn fp3232(r: &mut Reader<'_>) -> Result<f64, ParseError> {
+    let integral = r.i32()?;
+    let fraction = r.u32()?;
+    Ok(f64::from(integral) + f64::from(fraction) / 4_294_967_296.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `RawMotion` event from a mouse that moved 3.5 across and 2 up (before acceleration),
+    /// with XInput2 at major opcode 131.
+    #[rustfmt::skip]
+    const RAW_MOTION: &[u8] = &[
+        35, 131, 0x10, 0x00, 0x09, 0x00, 0x00, 0x00, // GenericEvent, sequence 16, 9 extra units
+        17, 0, 2, 0, 0x40, 0xE2, 0x01, 0x00, // RawMotion, device 2 (the master pointer), time
+        0, 0, 0, 0, 9, 0, 1, 0, // detail, source device 9, 1 mask unit
+        0, 0, 0, 0, 0, 0, 0, 0, // flags
+        0b11, 0, 0, 0, // axes 0 and 1
+        7, 0, 0, 0, 0, 0, 0, 0, // accelerated: 7, -4
+        0xFC, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0,
+        3, 0, 0, 0, 0, 0, 0, 0x80, // raw: 3.5, -2
+        0xFE, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0,
+    ];
+
+    #[test]
+    fn requests() {
+        assert_eq!(query_version(131), [131, 47, 2, 0, 2, 0, 0, 0]);
+        assert_eq!(
+            select_events(
+                131,
+                0x4A6,
+                &[(devices::ALL_MASTER, 1 << event_types::RAW_MOTION)]
+            ),
+            [131, 46, 5, 0, 0xA6, 4, 0, 0, 1, 0, 0, 0, 1, 0, 1, 0, 0, 0, 2, 0]
+        );
+    }
+
+    #[test]
+    fn query_version_reply() {
+        let mut reply = vec![1, 0, 1, 0, 0, 0, 0, 0, 2, 0, 4, 0];
+        reply.resize(32, 0);
+        assert_eq!(parse_query_version(&reply), Ok((2, 4)));
+    }
+
+    #[test]
+    fn raw_motion() {
+        assert_eq!(RAW_MOTION.len(), 32 + 9 * 4);
+        let motion = RawMotion::parse(131, RAW_MOTION).unwrap().unwrap();
+        assert_eq!((motion.device, motion.source), (2, 9));
+        assert_eq!(motion.time, 123_456);
+        assert_eq!(motion.axes.len(), 2);
+        assert_eq!(motion.axes[0].value, 7.0);
+        assert_eq!(motion.axes[1].value, -4.0);
+        assert_eq!(motion.delta(), [3.5, -2.0]);
+
+        // other extensions' generic events, and other events, aren't raw motion
+        assert_eq!(RawMotion::parse(132, RAW_MOTION), Ok(None));
+        assert_eq!(RawMotion::parse(131, &[12; 32]), Ok(None));
+        assert!(RawMotion::parse(131, &RAW_MOTION[..50]).is_err());
+    }
+
+    #[test]
+    fn sparse_axes() {
+        let mut bytes = RAW_MOTION[..36].to_vec();
+        // only axis 1 moved
+        bytes[32] = 0b10;
+        bytes.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0]);
+        let motion = RawMotion::parse(131, &bytes).unwrap().unwrap();
+        assert_eq!(motion.delta(), [0.0, 5.0]);
+    }
+}
@@ -0,0 +1,97 @@
+//! Encoding and decoding the few parts of the XKEYBOARD extension that the core protocol's
+//! keyboard handling needs.
+//!
+//! Without XKB, a held key auto-repeats as a release and a press at the same time, which looks
+//! just like the user tapping it. Turning on detectable auto-repeat makes the server leave out
+//! the releases, so a repeat is a press of a key that's already down.
+//!
+//! Like [`randr`](crate::randr), every encoder takes the extension's major opcode as `major`.
+
+use crate::{
+    protocol::reply_reader,
+    wire::{ParseError, RequestBuilder},
+};
+
+/// The version that's asked for and needed.
+pub const VERSION: (u16, u16) = (1, 0);
+
+/// The minor opcodes of the XKB requests, which go in their second byte.
+pub mod minor_opcodes {
+    pub const USE_EXTENSION: u8 = 0;
+    pub const PER_CLIENT_FLAGS: u8 = 21;
+}
+
+/// The device spec for the core keyboard.
+pub const USE_CORE_KBD: u16 = 0x100;
+
+/// Flags for [`per_client_flags`].
+pub mod per_client {
+    /// Leave out the releases of auto-repeating keys.
+    pub const DETECTABLE_AUTO_REPEAT: u32 = 1 << 0;
+}
+
+/// Tells the server which version the client speaks. XKB requests fail until this is sent.
+pub fn use_extension(major: u8) -> Vec<u8> {
+    RequestBuilder::new(major, minor_opcodes::USE_EXTENSION)
+        .u16(VERSION.0)
+        .u16(VERSION.1)
+        .finish()
+}
+
+/// Parses the reply to [`use_extension`]: whether the server supports the version, and the
+/// version it has.
+pub fn parse_use_extension(reply: &[u8]) -> Result<(bool, (u16, u16)), ParseError> {
+    let (supported, mut r) = reply_reader(reply)?;
+    Ok((supported != 0, (r.u16()?, r.u16()?)))
+}
+
+/// Changes the `change` flags of this client to what they are in `value`.
+pub fn per_client_flags(major: u8, change: u32, value: u32) -> Vec<u8> {
+    RequestBuilder::new(major, minor_opcodes::PER_CLIENT_FLAGS)
+        .u16(USE_CORE_KBD)
+        .skip(2)
+        .u32(change)
+        .u32(value)
+        // the controls that are reset when the client exits, which aren't changed
+        .u32(0)
+        .u32(0)
+        .u32(0)
+        .finish()
+}
+
+/// Parses the reply to [`per_client_flags`]: which flags are supported, and which are now set.
+pub fn parse_per_client_flags(reply: &[u8]) -> Result<(u32, u32), ParseError> {
+    let (_device, mut r) = reply_reader(reply)?;
+    Ok((r.u32()?, r.u32()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests() {
+        assert_eq!(use_extension(135), [135, 0, 2, 0, 1, 0, 0, 0]);
+        let request = per_client_flags(
+            135,
+            per_client::DETECTABLE_AUTO_REPEAT,
+            per_client::DETECTABLE_AUTO_REPEAT,
+        );
+        assert_eq!(request.len(), 28);
+        assert_eq!(
+            &request[..16],
+            [135, 21, 7, 0, 0, 1, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn replies() {
+        let mut reply = vec![1, 1, 1, 0, 0, 0, 0, 0, 1, 0, 0, 0];
+        reply.resize(32, 0);
+        assert_eq!(parse_use_extension(&reply), Ok((true, (1, 0))));
+
+        let mut reply = vec![1, 3, 2, 0, 0, 0, 0, 0, 0x1F, 0, 0, 0, 1, 0, 0, 0];
+        reply.resize(32, 0);
+        assert_eq!(parse_per_client_flags(&reply), Ok((0x1F, 1)));
+    }
+}
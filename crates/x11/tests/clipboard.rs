@@ -0,0 +1,115 @@
+#![cfg(unix)]
+
+extern crate triangle_from_scratch_x11 as x11;
+
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use common::Xvfb;
+use x11::{clipboard::Clipboard, Connection};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A client that copies `text` and then answers requests for it on another thread, until it's
+/// stopped.
+struct Owner {
+    stop: Arc<AtomicBool>,
+    /// Whether the owner still owns the selection, sent when it's stopped.
+    owns: mpsc::Receiver<bool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Owner {
+    fn start(xvfb: &Xvfb, text: String) -> Self {
+        let mut conn = xvfb.connect();
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, owns) = mpsc::channel();
+        let mut clipboard = Clipboard::new(&mut conn).unwrap();
+        assert!(clipboard.set_text(&mut conn, &text).unwrap());
+
+        let stopped = stop.clone();
+        let thread = thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                if let Some(event) = conn.wait_event_timeout(Duration::from_millis(20)).unwrap() {
+                    clipboard.handle_event(&mut conn, &event).unwrap();
+                }
+            }
+            sender.send(clipboard.owns()).unwrap();
+        });
+        Self {
+            stop,
+            owns,
+            thread: Some(thread),
+        }
+    }
+
+    /// Stops answering requests, and returns whether it still owned the selection.
+    fn stop(mut self) -> bool {
+        self.stop.store(true, Ordering::Relaxed);
+        self.thread.take().unwrap().join().unwrap();
+        self.owns.recv().unwrap()
+    }
+}
+
+fn paste(conn: &mut Connection) -> Option<String> {
+    let mut clipboard = Clipboard::new(conn).unwrap();
+    let text = clipboard.text(conn, TIMEOUT).unwrap();
+    clipboard.destroy(conn).unwrap();
+    text
+}
+
+#[test]
+fn empty_clipboard() {
+    let Some(xvfb) = Xvfb::start() else {
+        return;
+    };
+    let mut conn = xvfb.connect();
+    assert_eq!(paste(&mut conn), None);
+}
+
+#[test]
+fn copy_and_paste() {
+    let Some(xvfb) = Xvfb::start() else {
+        return;
+    };
+    let owner = Owner::start(&xvfb, "héllo, wörld ✓".into());
+    let mut conn = xvfb.connect();
+    assert_eq!(paste(&mut conn).as_deref(), Some("héllo, wörld ✓"));
+    // and again, since pasting doesn't use it up
+    assert_eq!(paste(&mut conn).as_deref(), Some("héllo, wörld ✓"));
+
+    // copying something else takes the selection from the owner
+    let mut clipboard = Clipboard::new(&mut conn).unwrap();
+    assert!(clipboard.set_text(&mut conn, "mine").unwrap());
+    assert_eq!(
+        clipboard.text(&mut conn, TIMEOUT).unwrap().as_deref(),
+        Some("mine")
+    );
+    thread::sleep(Duration::from_millis(100));
+    assert!(!owner.stop());
+}
+
+#[test]
+fn large_text_goes_in_pieces() {
+    let Some(xvfb) = Xvfb::start() else {
+        return;
+    };
+    // several times bigger than the largest request
+    let text: String = (0..200_000).map(|i| format!("{i:x}\n")).collect();
+    assert!(text.len() > 3 * usize::from(xvfb.connect().setup().maximum_request_length) * 4);
+
+    let owner = Owner::start(&xvfb, text.clone());
+    let mut conn = xvfb.connect();
+    assert!(paste(&mut conn) == Some(text));
+    // the transfer's notifications aren't left for the application
+    assert_eq!(conn.poll_event().unwrap(), None);
+    assert!(owner.stop());
+}
@@ -0,0 +1,139 @@
+//! Shared setup for tests that need a real X server.
+//!
+//! Each test starts its own `Xvfb`, optionally with a lightweight window manager, so that tests
+//! don't interfere with each other or with a desktop. When `Xvfb` isn't installed, [`Xvfb::start`]
+//! returns `None` and tests should return early instead of failing.
+
+#![allow(dead_code)]
+
+use std::{
+    env,
+    io::{BufRead, BufReader},
+    process::{Child, Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use x11::{display::DisplayName, Connection};
+
+/// Window managers that are small enough to start quickly, in order of preference.
+const WINDOW_MANAGERS: &[&str] = &["openbox", "fluxbox", "icewm", "matchbox-window-manager"];
+
+/// A running `Xvfb`, which is killed when this is dropped.
+pub struct Xvfb {
+    pub display: DisplayName,
+    server: Child,
+    window_manager: Option<Child>,
+}
+
+fn find_program(name: &str) -> Option<std::path::PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+impl Xvfb {
+    /// Starts a 1280x1024 `Xvfb` on an unused display.
+    ///
+    /// `Xvfb` picks the display itself with `-displayfd`, which claims it with a lock file and
+    /// writes its number to stdout once the server is accepting connections, so tests running in
+    /// parallel can't race for the same display.
+    pub fn start() -> Option<Self> {
+        let Some(xvfb) = find_program("Xvfb") else {
+            eprintln!("skipping: Xvfb isn't installed");
+            return None;
+        };
+
+        let mut server = Command::new(xvfb)
+            .args(["-displayfd", "1"])
+            .args(["-screen", "0", "1280x1024x24", "-nolisten", "tcp"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+
+        // read the number on another thread so a server that hangs can't hang the test too
+        let stdout = server.stdout.take()?;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut line = String::new();
+            let _ = BufReader::new(stdout).read_line(&mut line);
+            let _ = sender.send(line);
+        });
+        let number = receiver
+            .recv_timeout(Duration::from_secs(10))
+            .ok()
+            .and_then(|line| line.trim().parse::<u32>().ok());
+        let Some(number) = number else {
+            eprintln!("skipping: Xvfb didn't start");
+            let _ = server.kill();
+            let _ = server.wait();
+            return None;
+        };
+
+        let xvfb = Self {
+            display: DisplayName::parse(&format!(":{number}")).unwrap(),
+            server,
+            window_manager: None,
+        };
+        if Connection::connect_to(&xvfb.display).is_err() {
+            eprintln!("skipping: couldn't connect to Xvfb");
+            return None;
+        }
+        Some(xvfb)
+    }
+
+    /// Starts `Xvfb` with the first window manager in [`WINDOW_MANAGERS`] that's installed, and
+    /// waits for it to announce itself.
+    pub fn start_with_window_manager() -> Option<Self> {
+        let Some(wm) = WINDOW_MANAGERS.iter().find_map(|name| find_program(name)) else {
+            eprintln!("skipping: no window manager installed (tried {WINDOW_MANAGERS:?})");
+            return None;
+        };
+        let mut xvfb = Self::start()?;
+        xvfb.window_manager = Command::new(wm)
+            .env("DISPLAY", format!(":{}", xvfb.display.display))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok();
+
+        let mut conn = xvfb.connect();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !x11::ewmh::has_window_manager(&mut conn).unwrap() {
+            if Instant::now() > deadline {
+                eprintln!("skipping: the window manager didn't start");
+                return None;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        Some(xvfb)
+    }
+
+    pub fn connect(&self) -> Connection {
+        Connection::connect_to(&self.display).unwrap()
+    }
+}
+
+impl Drop for Xvfb {
+    fn drop(&mut self) {
+        for child in self.window_manager.iter_mut().chain([&mut self.server]) {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Polls `f` until it returns `true`, for up to five seconds, since window managers do things
+/// asynchronously.
+pub fn eventually(mut f: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if f() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    false
+}
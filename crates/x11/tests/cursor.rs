@@ -0,0 +1,129 @@
+#![cfg(unix)]
+
+extern crate triangle_from_scratch_x11 as x11;
+
+mod common;
+
+use common::Xvfb;
+use x11::{
+    cursor::{CursorGrab, CursorMode, ImageCursors},
+    protocol::{self, window_class, CreateWindow, GrabStatus, WindowAttributes, CURRENT_TIME},
+    xcursor::ThemeLoader,
+    Connection,
+};
+
+fn create_window(conn: &mut Connection) -> protocol::Window {
+    let id = conn.generate_id();
+    let root = conn.root();
+    conn.request_checked(&protocol::create_window(&CreateWindow {
+        depth: 0,
+        id,
+        parent: root,
+        x: 0,
+        y: 0,
+        width: 320,
+        height: 240,
+        border_width: 0,
+        class: window_class::INPUT_OUTPUT,
+        visual: 0,
+        attributes: WindowAttributes::default(),
+    }))
+    .unwrap();
+    conn.request_checked(&protocol::map_window(id)).unwrap();
+    id
+}
+
+/// Tries to grab the pointer from another client, which only works if nobody else has it.
+fn grab_from(conn: &mut Connection) -> GrabStatus {
+    let root = conn.root();
+    let reply = conn
+        .request_with_reply(&protocol::grab_pointer(
+            false,
+            root,
+            0,
+            protocol::NONE,
+            protocol::NONE,
+            CURRENT_TIME,
+        ))
+        .unwrap();
+    let status = GrabStatus::parse(&reply).unwrap();
+    conn.request_checked(&protocol::ungrab_pointer(CURRENT_TIME))
+        .unwrap();
+    status
+}
+
+#[test]
+fn locking_grabs_the_pointer() {
+    let Some(xvfb) = Xvfb::start() else {
+        return;
+    };
+    let mut conn = xvfb.connect();
+    let mut other = xvfb.connect();
+    let window = create_window(&mut conn);
+
+    let mut grab = CursorGrab::new();
+    grab.set_mode(&mut conn, window, CursorMode::Locked)
+        .unwrap();
+    assert_eq!(grab_from(&mut other), GrabStatus::AlreadyGrabbed);
+
+    grab.focus_changed(&mut conn, window, false).unwrap();
+    assert_eq!(grab_from(&mut other), GrabStatus::Success);
+    grab.focus_changed(&mut conn, window, true).unwrap();
+    assert_eq!(grab_from(&mut other), GrabStatus::AlreadyGrabbed);
+
+    grab.set_mode(&mut conn, window, CursorMode::Hidden)
+        .unwrap();
+    assert_eq!(grab_from(&mut other), GrabStatus::Success);
+
+    grab.release(&mut conn, window).unwrap();
+    assert_eq!(grab.mode(), CursorMode::Normal);
+}
+
+#[test]
+fn image_cursors() {
+    let Some(xvfb) = Xvfb::start() else {
+        return;
+    };
+    let mut conn = xvfb.connect();
+    let window = create_window(&mut conn);
+    let cursors = ImageCursors::new(&mut conn).unwrap();
+
+    // a red square with a see-through border, and too big to send in one request
+    let size = 300;
+    let rgba: Vec<u8> = (0..size * size)
+        .flat_map(|i| {
+            let (x, y) = (i % size, i / size);
+            let edge = x == 0 || y == 0 || x == size - 1 || y == size - 1;
+            [0xFF, 0, 0, if edge { 0 } else { 0xFF }]
+        })
+        .collect();
+    let cursor = cursors
+        .create_from_rgba(&mut conn, &rgba, size as u16, size as u16, [5, 5])
+        .unwrap();
+    conn.request_checked(&protocol::change_window_attributes(
+        window,
+        &WindowAttributes {
+            cursor: Some(cursor),
+            ..Default::default()
+        },
+    ))
+    .unwrap();
+
+    let themes = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/cursors/themes");
+    let loader = ThemeLoader::new(vec![themes]);
+    let themed = cursors
+        .load_themed(&mut conn, &loader, "child", "left_ptr", 24)
+        .unwrap();
+    assert!(themed.is_some());
+    assert_eq!(
+        cursors
+            .load_themed(&mut conn, &loader, "child", "xterm", 24)
+            .unwrap(),
+        None
+    );
+
+    conn.request_checked(&protocol::free_cursor(cursor))
+        .unwrap();
+    conn.request_checked(&protocol::free_cursor(themed.unwrap()))
+        .unwrap();
+}
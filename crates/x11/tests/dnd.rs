@@ -0,0 +1,387 @@
+#![cfg(unix)]
+
+extern crate triangle_from_scratch_x11 as x11;
+
+mod common;
+
+use std::{thread, time::Duration};
+
+use common::Xvfb;
+use event::Event as WindowEvent;
+use x11::{
+    dnd::{DropTarget, VERSION},
+    protocol::{
+        self, event_masks, window_class, Atom, ClientMessage, CreateWindow, Event, PropertyMode,
+        SelectionRequest, WindowAttributes, CURRENT_TIME, NONE,
+    },
+    Connection,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn create_window(conn: &mut Connection) -> protocol::Window {
+    let id = conn.generate_id();
+    let root = conn.root();
+    conn.request_checked(&protocol::create_window(&CreateWindow {
+        depth: 0,
+        id,
+        parent: root,
+        x: 0,
+        y: 0,
+        width: 320,
+        height: 240,
+        border_width: 0,
+        class: window_class::INPUT_OUTPUT,
+        visual: 0,
+        attributes: WindowAttributes::default(),
+    }))
+    .unwrap();
+    id
+}
+
+/// A file manager dragging `uri_list` around, one message at a time.
+struct Source {
+    conn: Connection,
+    window: protocol::Window,
+    uri_list: String,
+}
+
+impl Source {
+    fn new(xvfb: &Xvfb, uri_list: &str) -> Self {
+        let mut conn = xvfb.connect();
+        let window = create_window(&mut conn);
+        Self {
+            conn,
+            window,
+            uri_list: uri_list.into(),
+        }
+    }
+
+    fn atom(&mut self, name: &str) -> Atom {
+        self.conn.intern_atom(name).unwrap()
+    }
+
+    fn send(&mut self, target: protocol::Window, message: &str, data: [u32; 4]) {
+        let type_ = self.atom(message);
+        let [a, b, c, d] = data;
+        let event = protocol::client_message32(target, type_, [self.window, a, b, c, d]);
+        self.conn
+            .request_checked(&protocol::send_event(false, target, 0, &event))
+            .unwrap();
+    }
+
+    fn receive(&mut self, message: &str) -> ClientMessage {
+        let type_ = self.atom(message);
+        let event = self
+            .conn
+            .wait_for_event(
+                TIMEOUT,
+                |event| matches!(event, Event::ClientMessage(m) if m.type_ == type_),
+            )
+            .unwrap();
+        let Some(Event::ClientMessage(message)) = event else {
+            panic!("no {message} message");
+        };
+        message
+    }
+
+    /// Enters `target` offering `types`, and moves over it.
+    fn hover(&mut self, target: protocol::Window, types: &[&str]) {
+        let types: Vec<Atom> = types.iter().map(|t| self.atom(t)).collect();
+        let mut offered = [NONE; 3];
+        let more_than_three = types.len() > 3;
+        if more_than_three {
+            let type_list = self.atom("XdndTypeList");
+            self.conn
+                .request_checked(&protocol::change_property32(
+                    PropertyMode::Replace,
+                    self.window,
+                    type_list,
+                    protocol::atoms::ATOM,
+                    &types,
+                ))
+                .unwrap();
+        } else {
+            offered[..types.len()].copy_from_slice(&types);
+        }
+        let [a, b, c] = offered;
+        self.send(
+            target,
+            "XdndEnter",
+            [VERSION << 24 | u32::from(more_than_three), a, b, c],
+        );
+        let copy = self.atom("XdndActionCopy");
+        self.send(
+            target,
+            "XdndPosition",
+            [0, 10 << 16 | 10, CURRENT_TIME, copy],
+        );
+    }
+
+    /// Drops on `target`, answering its request for the list.
+    fn drop_on(&mut self, target: &mut Target) -> (Vec<WindowEvent>, [u32; 5]) {
+        let request = self.start_drop(target);
+        self.conn
+            .request_checked(&protocol::change_property(
+                PropertyMode::Replace,
+                request.requestor,
+                request.property,
+                request.target,
+                8,
+                self.uri_list.as_bytes(),
+            ))
+            .unwrap();
+        self.notify(&request);
+
+        let events = target.next_drop();
+        (events, self.receive("XdndFinished").data32())
+    }
+
+    /// Drops on `target`, and waits for it to ask for the list.
+    fn start_drop(&mut self, target: &mut Target) -> SelectionRequest {
+        let selection = self.atom("XdndSelection");
+        self.conn
+            .request_checked(&protocol::set_selection_owner(
+                self.window,
+                selection,
+                CURRENT_TIME,
+            ))
+            .unwrap();
+        self.send(
+            target.drop_target.window(),
+            "XdndDrop",
+            [0, CURRENT_TIME, 0, 0],
+        );
+        assert_eq!(target.next_drop(), []);
+
+        let request = self
+            .conn
+            .wait_for_event(TIMEOUT, |event| matches!(event, Event::SelectionRequest(_)))
+            .unwrap();
+        let Some(Event::SelectionRequest(request)) = request else {
+            panic!("the list wasn't asked for");
+        };
+        assert_eq!(request.target, self.atom("text/uri-list"));
+        request
+    }
+
+    /// Answers `request` with the list in pieces of `piece_len` bytes, with `INCR`.
+    fn send_in_pieces(&mut self, request: &SelectionRequest, piece_len: usize) {
+        // the requestor deletes the property to ask for each piece
+        self.conn
+            .request_checked(&protocol::change_window_attributes(
+                request.requestor,
+                &WindowAttributes {
+                    event_mask: Some(event_masks::PROPERTY_CHANGE),
+                    ..Default::default()
+                },
+            ))
+            .unwrap();
+        let incr = self.atom("INCR");
+        self.conn
+            .request_checked(&protocol::change_property32(
+                PropertyMode::Replace,
+                request.requestor,
+                request.property,
+                incr,
+                &[self.uri_list.len() as u32],
+            ))
+            .unwrap();
+        self.notify(request);
+
+        // and an empty piece says that's all
+        let list = self.uri_list.clone();
+        for piece in list.as_bytes().chunks(piece_len).chain([&[][..]]) {
+            let asked = self
+                .conn
+                .wait_for_event(TIMEOUT, |event| {
+                    matches!(*event, Event::PropertyNotify { window, atom, deleted: true, .. }
+                        if window == request.requestor && atom == request.property)
+                })
+                .unwrap();
+            assert!(asked.is_some(), "the next piece wasn't asked for");
+            self.conn
+                .request_checked(&protocol::change_property(
+                    PropertyMode::Replace,
+                    request.requestor,
+                    request.property,
+                    request.target,
+                    8,
+                    piece,
+                ))
+                .unwrap();
+        }
+    }
+
+    /// Tells the requestor the list has been put in its property.
+    fn notify(&mut self, request: &SelectionRequest) {
+        let notify = protocol::selection_notify(
+            request.time,
+            request.requestor,
+            request.selection,
+            request.target,
+            request.property,
+        );
+        self.conn
+            .request_checked(&protocol::send_event(false, request.requestor, 0, &notify))
+            .unwrap();
+    }
+}
+
+struct Target {
+    conn: Connection,
+    drop_target: DropTarget,
+}
+
+impl Target {
+    fn new(xvfb: &Xvfb) -> Self {
+        let mut conn = xvfb.connect();
+        let window = create_window(&mut conn);
+        let drop_target = DropTarget::new(&mut conn, window).unwrap();
+        Self { conn, drop_target }
+    }
+
+    /// Handles the events that have come, returning what was dropped.
+    fn next_drop(&mut self) -> Vec<WindowEvent> {
+        self.conn.sync().unwrap();
+        let mut dropped = Vec::new();
+        while let Some(event) = self.conn.poll_event().unwrap() {
+            dropped.extend(
+                self.drop_target
+                    .handle_event(&mut self.conn, &event)
+                    .unwrap(),
+            );
+        }
+        dropped
+    }
+
+    /// Waits for the list to be sent, and handles it.
+    fn wait_for_drop(&mut self) -> Vec<WindowEvent> {
+        let notify = self
+            .conn
+            .wait_for_event(TIMEOUT, |event| {
+                matches!(event, Event::SelectionNotify { .. })
+            })
+            .unwrap();
+        let notify = notify.expect("the list wasn't sent");
+        self.drop_target
+            .handle_event(&mut self.conn, &notify)
+            .unwrap()
+    }
+}
+
+#[test]
+fn files_are_dropped() {
+    let Some(xvfb) = Xvfb::start() else {
+        return;
+    };
+    let mut target = Target::new(&xvfb);
+    let mut source = Source::new(
+        &xvfb,
+        "file:///home/me/cube%20mesh.obj\r\nfile:///home/me/brick.png\r\n",
+    );
+    let window = target.drop_target.window();
+    let copy = source.atom("XdndActionCopy");
+
+    source.hover(window, &["text/uri-list", "text/plain"]);
+    assert_eq!(target.next_drop(), []);
+    assert!(target.drop_target.is_dragging_files());
+    assert_eq!(
+        source.receive("XdndStatus").data32(),
+        [window, 1, 0, 0, copy]
+    );
+
+    let (events, finished) = source.drop_on(&mut target);
+    assert_eq!(
+        events,
+        [
+            WindowEvent::FileDropped("/home/me/cube mesh.obj".into()),
+            WindowEvent::FileDropped("/home/me/brick.png".into()),
+        ]
+    );
+    assert_eq!(finished, [window, 1, copy, 0, 0]);
+    assert!(!target.drop_target.is_dragging_files());
+}
+
+#[test]
+fn other_types_are_refused() {
+    let Some(xvfb) = Xvfb::start() else {
+        return;
+    };
+    let mut target = Target::new(&xvfb);
+    let mut source = Source::new(&xvfb, "");
+    let window = target.drop_target.window();
+
+    // more types than fit in the message, so they're read from the source's property
+    source.hover(window, &["text/plain", "UTF8_STRING", "STRING", "TEXT"]);
+    target.next_drop();
+    assert!(!target.drop_target.is_dragging_files());
+    assert_eq!(
+        source.receive("XdndStatus").data32(),
+        [window, 0, 0, 0, NONE]
+    );
+
+    // the drop finishes straight away, without asking for anything
+    source.send(window, "XdndDrop", [0, CURRENT_TIME, 0, 0]);
+    assert_eq!(target.next_drop(), []);
+    assert_eq!(
+        source.receive("XdndFinished").data32(),
+        [window, 0, NONE, 0, 0]
+    );
+}
+
+#[test]
+fn leaving_cancels_the_drag() {
+    let Some(xvfb) = Xvfb::start() else {
+        return;
+    };
+    let mut target = Target::new(&xvfb);
+    let mut source = Source::new(&xvfb, "file:///tmp/x\r\n");
+    let window = target.drop_target.window();
+
+    source.hover(window, &["text/uri-list"]);
+    target.next_drop();
+    assert!(target.drop_target.is_dragging_files());
+    source.send(window, "XdndLeave", [0; 4]);
+    target.next_drop();
+    assert!(!target.drop_target.is_dragging_files());
+
+    // a drop without an enter is ignored
+    source.send(window, "XdndDrop", [0, CURRENT_TIME, 0, 0]);
+    assert_eq!(target.next_drop(), []);
+}
+
+#[test]
+fn long_lists_come_in_pieces() {
+    let Some(xvfb) = Xvfb::start() else {
+        return;
+    };
+    let mut target = Target::new(&xvfb);
+    let paths: Vec<String> = (0..500).map(|i| format!("/tmp/mesh {i}.obj")).collect();
+    let uri_list: String = paths
+        .iter()
+        .map(|path| format!("file://{}\r\n", path.replace(' ', "%20")))
+        .collect();
+    let mut source = Source::new(&xvfb, &uri_list);
+    let window = target.drop_target.window();
+    let copy = source.atom("XdndActionCopy");
+
+    source.hover(window, &["text/uri-list"]);
+    target.next_drop();
+    let request = source.start_drop(&mut target);
+
+    // the target waits for every piece, so it needs a thread of its own
+    let target = thread::spawn(move || target.wait_for_drop());
+    source.send_in_pieces(&request, 1000);
+    let events = target.join().unwrap();
+
+    let expected: Vec<_> = paths
+        .iter()
+        .map(|path| WindowEvent::FileDropped(path.into()))
+        .collect();
+    assert_eq!(events, expected);
+    assert_eq!(
+        source.receive("XdndFinished").data32(),
+        [window, 1, copy, 0, 0]
+    );
+}
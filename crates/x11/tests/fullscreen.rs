@@ -0,0 +1,100 @@
+#![cfg(unix)]
+
+extern crate triangle_from_scratch_x11 as x11;
+
+mod common;
+
+use common::{eventually, Xvfb};
+use x11::{
+    ewmh::{self, Fullscreen, Placement},
+    protocol::{self, atoms, event_masks, window_class, CreateWindow, WindowAttributes},
+    Connection,
+};
+
+fn create_window(conn: &mut Connection) -> protocol::Window {
+    let id = conn.generate_id();
+    let root = conn.root();
+    conn.request_checked(&protocol::create_window(&CreateWindow {
+        depth: 0,
+        id,
+        parent: root,
+        x: 50,
+        y: 60,
+        width: 320,
+        height: 240,
+        border_width: 0,
+        class: window_class::INPUT_OUTPUT,
+        visual: 0,
+        attributes: WindowAttributes {
+            event_mask: Some(event_masks::STRUCTURE_NOTIFY),
+            ..Default::default()
+        },
+    }))
+    .unwrap();
+    conn.request_checked(&protocol::map_window(id)).unwrap();
+    id
+}
+
+fn has_fullscreen_state(conn: &mut Connection, window: protocol::Window) -> bool {
+    let fullscreen = conn.intern_atom("_NET_WM_STATE_FULLSCREEN").unwrap();
+    ewmh::get_property32(conn, window, "_NET_WM_STATE", atoms::ATOM)
+        .unwrap()
+        .contains(&fullscreen)
+}
+
+#[test]
+fn without_a_window_manager() {
+    let Some(xvfb) = Xvfb::start() else {
+        return;
+    };
+    let mut conn = xvfb.connect();
+    assert_eq!(conn.screen().width_in_pixels, 1280);
+    assert!(!ewmh::has_window_manager(&mut conn).unwrap());
+
+    let window = create_window(&mut conn);
+    let windowed = Placement::of(&mut conn, window).unwrap();
+    assert_eq!((windowed.x, windowed.y), (50, 60));
+
+    let mut fullscreen = Fullscreen::new();
+    assert!(fullscreen.toggle(&mut conn, window).unwrap());
+    let placement = Placement::of(&mut conn, window).unwrap();
+    assert_eq!(
+        (placement.x, placement.y, placement.width, placement.height),
+        (0, 0, 1280, 1024)
+    );
+    assert!(has_fullscreen_state(&mut conn, window));
+
+    assert!(!fullscreen.toggle(&mut conn, window).unwrap());
+    assert_eq!(Placement::of(&mut conn, window).unwrap(), windowed);
+    assert!(!has_fullscreen_state(&mut conn, window));
+}
+
+#[test]
+fn with_a_window_manager() {
+    let Some(xvfb) = Xvfb::start_with_window_manager() else {
+        return;
+    };
+    let mut conn = xvfb.connect();
+    let window = create_window(&mut conn);
+    // window managers set `WM_STATE` on the windows they manage
+    assert!(eventually(|| {
+        !ewmh::get_property32(&mut conn, window, "WM_STATE", protocol::NONE)
+            .unwrap()
+            .is_empty()
+    }));
+    let windowed = Placement::of(&mut conn, window).unwrap();
+
+    let mut fullscreen = Fullscreen::new();
+    fullscreen.enter(&mut conn, window).unwrap();
+    assert!(eventually(|| {
+        let placement = Placement::of(&mut conn, window).unwrap();
+        (placement.width, placement.height) == (1280, 1024)
+    }));
+    assert!(eventually(|| has_fullscreen_state(&mut conn, window)));
+
+    fullscreen.exit(&mut conn, window).unwrap();
+    assert!(eventually(|| {
+        Placement::of(&mut conn, window).unwrap() == windowed
+    }));
+    assert!(eventually(|| !has_fullscreen_state(&mut conn, window)));
+}
@@ -0,0 +1,109 @@
+#![cfg(unix)]
+
+extern crate triangle_from_scratch_x11 as x11;
+
+mod common;
+
+use common::Xvfb;
+use x11::{
+    ewmh,
+    protocol::{self, atoms, window_class, CreateWindow, Property, WindowAttributes},
+    Connection, Error,
+};
+
+fn create_window(conn: &mut Connection) -> protocol::Window {
+    let id = conn.generate_id();
+    let root = conn.root();
+    conn.request_checked(&protocol::create_window(&CreateWindow {
+        depth: 0,
+        id,
+        parent: root,
+        x: 0,
+        y: 0,
+        width: 320,
+        height: 240,
+        border_width: 0,
+        class: window_class::INPUT_OUTPUT,
+        visual: 0,
+        attributes: WindowAttributes::default(),
+    }))
+    .unwrap();
+    id
+}
+
+/// The whole of `_NET_WM_ICON`, which can be too long for [`ewmh::get_property32`].
+fn icon_property(conn: &mut Connection, window: protocol::Window) -> Option<Vec<u32>> {
+    let property = conn.intern_atom("_NET_WM_ICON").unwrap();
+    let reply = conn
+        .request_with_reply(&protocol::get_property(
+            false,
+            window,
+            property,
+            atoms::CARDINAL,
+            0,
+            u32::MAX / 4,
+        ))
+        .unwrap();
+    Property::parse(&reply).unwrap().as_u32s()
+}
+
+#[test]
+fn icons_are_scaled_down() {
+    let Some(xvfb) = Xvfb::start() else {
+        return;
+    };
+    let mut conn = xvfb.connect();
+    let window = create_window(&mut conn);
+
+    // opaque red, and big enough that the property takes more than one request
+    let rgba = [0xFF, 0, 0, 0xFF].repeat(256 * 128);
+    ewmh::set_icon(&mut conn, window, &rgba, 256, 128).unwrap();
+    let data = icon_property(&mut conn, window).unwrap();
+
+    let mut sizes = Vec::new();
+    let mut rest = &data[..];
+    while let [width, height, pixels @ ..] = rest {
+        let len = (width * height) as usize;
+        sizes.push((*width, *height));
+        assert!(pixels[..len].iter().all(|&p| p == 0xFFFF_0000));
+        rest = &pixels[len..];
+    }
+    assert_eq!(sizes, [(16, 8), (32, 16), (48, 24), (256, 128)]);
+
+    // a small icon isn't scaled up
+    ewmh::set_icon(&mut conn, window, &[0, 0, 0xFF, 0x80], 1, 1).unwrap();
+    assert_eq!(
+        icon_property(&mut conn, window).unwrap(),
+        [1, 1, 0x8000_00FF]
+    );
+
+    ewmh::set_icon_images(&mut conn, window, &[]).unwrap();
+    assert_eq!(icon_property(&mut conn, window), None);
+}
+
+#[test]
+fn bad_icons_are_rejected() {
+    let Some(xvfb) = Xvfb::start() else {
+        return;
+    };
+    let mut conn = xvfb.connect();
+    let window = create_window(&mut conn);
+    ewmh::set_icon(&mut conn, window, &[0, 0, 0xFF, 0x80], 1, 1).unwrap();
+
+    for (rgba, width, height) in [
+        (&[0; 12][..], 2, 2),
+        (&[0; 20][..], 2, 2),
+        (&[][..], 0, 0),
+        (&[][..], 0, 16),
+    ] {
+        assert!(matches!(
+            ewmh::set_icon(&mut conn, window, rgba, width, height),
+            Err(Error::BadIcon { len, .. }) if len == rgba.len()
+        ));
+    }
+    // and the icon that was there is left alone
+    assert_eq!(
+        icon_property(&mut conn, window).unwrap(),
+        [1, 1, 0x8000_00FF]
+    );
+}
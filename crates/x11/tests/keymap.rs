@@ -0,0 +1,26 @@
+#![cfg(unix)]
+
+extern crate triangle_from_scratch_x11 as x11;
+
+mod common;
+
+use common::Xvfb;
+use x11::{keymap, protocol::key_masks};
+
+#[test]
+fn default_mapping_is_us() {
+    let Some(xvfb) = Xvfb::start() else {
+        return;
+    };
+    let mut conn = xvfb.connect();
+    let keymap = keymap::Keymap::load(&mut conn).unwrap();
+
+    // Xvfb starts with a US layout, where keycode 38 is A and 9 is escape
+    assert_eq!(keymap.keysym(38, 0), u32::from(b'a'));
+    assert_eq!(keymap.keysym(38, key_masks::SHIFT), u32::from(b'A'));
+    assert_eq!(keymap.keysym(38, key_masks::LOCK), u32::from(b'A'));
+    assert_eq!(keymap.keysym(9, 0), 0xFF1B);
+
+    // Xvfb has XKB
+    assert!(keymap::enable_detectable_auto_repeat(&mut conn).unwrap());
+}
@@ -0,0 +1,33 @@
+#![cfg(unix)]
+
+extern crate triangle_from_scratch_x11 as x11;
+
+mod common;
+
+use common::Xvfb;
+use x11::monitor;
+
+#[test]
+fn monitors_are_on_the_screen() {
+    let Some(xvfb) = Xvfb::start() else {
+        return;
+    };
+    let mut conn = xvfb.connect();
+    let monitors = match monitor::monitors(&mut conn) {
+        Ok(monitors) => monitors,
+        Err(x11::Error::MissingExtension(name)) => {
+            eprintln!("skipping: Xvfb doesn't have {name}");
+            return;
+        }
+        Err(e) => panic!("{e}"),
+    };
+
+    // Xvfb has one virtual output covering its one screen
+    for monitor in &monitors {
+        assert!(monitor.position[0] >= 0 && monitor.position[1] >= 0);
+        assert!(monitor.position[0] as u32 + monitor.size[0] <= 1280);
+        assert!(monitor.position[1] as u32 + monitor.size[1] <= 1024);
+        assert_eq!(monitor.scale_factor, 1.0);
+        assert!(monitor.modes.iter().any(|mode| mode.size == monitor.size));
+    }
+}
@@ -0,0 +1,116 @@
+//! Reading Xcursor files and themes from the fixture themes in `cursors/themes`:
+//!
+//! - `base` has `left_ptr`, at sizes 8 and 16, and a truncated copy of it.
+//! - `child` inherits from `base`, and has an animated `watch`.
+//! - `base` inherits from `child` too, so the search has to notice the loop.
+
+extern crate triangle_from_scratch_x11 as x11;
+
+use std::path::{Path, PathBuf};
+
+use x11::xcursor::{self, Error, ThemeLoader};
+
+const LEFT_PTR: &[u8] = include_bytes!("cursors/themes/base/cursors/left_ptr");
+const WATCH: &[u8] = include_bytes!("cursors/themes/child/cursors/watch");
+
+fn themes() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/cursors/themes")
+}
+
+fn loader() -> ThemeLoader {
+    ThemeLoader::new(vec![PathBuf::from("/nonexistent"), themes()])
+}
+
+#[test]
+fn images() {
+    // the comment at the start is skipped
+    let images = xcursor::parse(LEFT_PTR).unwrap();
+    assert_eq!(images.len(), 2);
+
+    let small = &images[0];
+    assert_eq!(small.nominal_size, 8);
+    assert_eq!((small.width, small.height), (8, 8));
+    assert_eq!(small.hotspot, [1, 1]);
+    assert_eq!(small.pixels.len(), 64);
+    // red goes up to the right, and green down the image
+    assert_eq!(small.pixels[3 * 8 + 2], 0xFF40_6000);
+    assert_eq!(&small.to_rgba()[..8], [0, 0, 0, 0xFF, 0x20, 0, 0, 0xFF]);
+
+    let big = &images[1];
+    assert_eq!((big.nominal_size, big.width, big.height), (16, 16, 16));
+    assert_eq!(big.hotspot, [2, 3]);
+    // half-transparent on the diagonal, and premultiplied
+    assert_eq!(big.pixels[17], 0x8000_0040);
+    assert_eq!(big.pixels[1], 0);
+    assert_eq!(&big.to_rgba()[17 * 4..18 * 4], [0, 0, 0x80, 0x80]);
+}
+
+#[test]
+fn animations() {
+    let images = xcursor::parse(WATCH).unwrap();
+    let frames = xcursor::frames_for_size(&images, 10);
+    assert_eq!(frames.len(), 2);
+    assert_eq!(
+        frames.iter().map(|f| f.delay_ms).collect::<Vec<_>>(),
+        [50, 60]
+    );
+    assert_eq!(frames[1].pixels[0], 0xFF00_00FF);
+
+    let frames = xcursor::frames_for_size(&images, 32);
+    assert_eq!(frames.len(), 1);
+    // the hotspot was past the right edge
+    assert_eq!(frames[0].hotspot, [23, 2]);
+}
+
+#[test]
+fn broken_files() {
+    let truncated = std::fs::read(themes().join("base/cursors/truncated")).unwrap();
+    assert_eq!(xcursor::parse(&truncated), Err(Error::Truncated));
+    assert_eq!(xcursor::parse(b"[Icon Theme]"), Err(Error::NotXcursor));
+
+    // an image with no pixels
+    let mut empty = LEFT_PTR.to_vec();
+    let image = u32::from_le_bytes(empty[16 + 12 + 8..16 + 12 + 12].try_into().unwrap()) as usize;
+    empty[image + 16..image + 20].copy_from_slice(&0u32.to_le_bytes());
+    assert_eq!(
+        xcursor::parse(&empty),
+        Err(Error::BadImageSize {
+            width: 0,
+            height: 8
+        })
+    );
+}
+
+#[test]
+fn themes_inherit() {
+    let loader = loader();
+    let themes = themes();
+    assert_eq!(
+        loader.find("base", "left_ptr"),
+        Some(themes.join("base/cursors/left_ptr"))
+    );
+    // found in the theme that `child` inherits from
+    assert_eq!(
+        loader.find("child", "left_ptr"),
+        Some(themes.join("base/cursors/left_ptr"))
+    );
+    assert_eq!(
+        loader.find("base", "watch"),
+        Some(themes.join("child/cursors/watch"))
+    );
+    // the themes inherit from each other, which mustn't go round forever
+    assert_eq!(loader.find("child", "xterm"), None);
+    assert_eq!(loader.find("missing", "left_ptr"), None);
+}
+
+#[test]
+fn loading() {
+    let loader = loader();
+    let frames = loader.load("child", "left_ptr", 24).unwrap();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].nominal_size, 16);
+
+    assert_eq!(loader.load("child", "watch", 8).unwrap().len(), 2);
+    assert_eq!(loader.load("base", "truncated", 8), None);
+    assert_eq!(loader.load("base", "xterm", 8), None);
+}
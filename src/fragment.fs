@@ -1,4 +1,3 @@
-#version 330 core
 out vec4 FragColor;
 in vec3 vert_color;
 
@@ -3,33 +3,122 @@
 // the release profile).
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod triangle;
+
 use std::{
-    cell::{Ref, RefCell},
-    ffi::CStr,
-    mem, ptr,
-    rc::Rc,
+    env,
+    f64::consts::TAU,
+    mem, panic,
+    path::Path,
+    ptr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+    time::Duration,
 };
 
+use app::{
+    pacing::FramePacer,
+    pixel_format::{self, choose, PixelFormatRequirements},
+    present::{set_present_mode, wgl::WglSwapControl, PresentMode},
+    runner::{Application, Runner},
+};
 use c_types::CInt;
-use gl::{bindings::prelude::*, GlContext, GlProcLoader};
+use gl::{
+    bindings::prelude::*,
+    program_cache::{ProgramCache, ShaderSource},
+    program_manager::ProgramManager,
+    shader::ShaderError,
+    surface::Surface,
+    GlContext, GlProcLoader,
+};
+use glsl::preprocess::{GlslVersion, Preprocessor};
+use watch::FileWatcher;
 
 use win32::{
-    c_str, create_app_window, describe_pixel_format, do_wgl_choose_pixel_format_arb,
-    do_wgl_create_context_attribs_arb, get_any_message, get_dc, get_process_handle, get_wgl_basics,
-    get_window_userdata, load_library, load_predefined_cursor, post_quit_message, prelude::*,
-    register_class, release_dc, set_pixel_format, set_window_userdata, translate_message,
-    utf16_null, wgl_delete_context, wgl_make_current,
+    describe_pixel_format, do_wgl_create_context_attribs_arb,
+    fullscreen::{Fullscreen, FullscreenMode},
+    geometry::scale_factor,
+    get_process_handle, get_wgl_basics,
+    handles::{DeviceContext, GlRenderContext, Library, WindowClass},
+    load_predefined_cursor,
+    prelude::*,
+    set_pixel_format, utf16_null,
+    window_builder::WindowBuilder,
+    window_handler::{
+        run_app_loop, run_message_loop, window_procedure_trampoline, Size, WindowHandler,
+    },
 };
 
-const WINDOW_CLASS: &str = "Sample Window Class";
+use triangle::{SHADER_FILES, TRIANGLE_INDICES, TRIANGLE_LAYOUT, TRIANGLE_VERTICES};
+
 const WINDOW_CLASS_WN: [u16; 20] = utf16_null!("Sample Window Class");
 const WINDOW_NAME: &str = "Sample Window Name";
 
+/// How often the scene is updated, however often it's drawn.
+const TIMESTEP: Duration = Duration::from_millis(10);
+/// How long the background takes to pulse, in seconds of simulated time.
+const PULSE_PERIOD: f64 = 4.0;
+/// How many samples per pixel to ask for, if there are multisampled pixel formats.
+const MSAA_SAMPLES: u8 = 4;
+/// How long to wait between checks on whether the window is still minimized.
+const MINIMIZED_SLEEP: Duration = Duration::from_millis(50);
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let options = Options::from_args()?;
+    let exit_code = run(&options)?;
+    std::process::exit(exit_code);
+}
+
+/// How to run, from the command line.
+#[derive(Debug)]
+struct Options {
+    /// `--fps <rate>`: the frame rate to hold. Otherwise, only vsync holds it back.
+    frame_rate: Option<f64>,
+    /// `--render-thread`: render on a thread of its own, which keeps going while the window is
+    /// being moved or resized.
+    render_thread: bool,
+    /// `--present-mode <immediate|vsync|adaptive>`: whether frames wait for vertical blanks.
+    present_mode: PresentMode,
+}
+
+impl Options {
+    fn from_args() -> Result<Self, String> {
+        let mut options = Self {
+            frame_rate: None,
+            render_thread: false,
+            present_mode: PresentMode::AdaptiveVsync,
+        };
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--fps" => {
+                    let rate = args.next().and_then(|rate| rate.parse().ok());
+                    options.frame_rate = Some(rate.ok_or("--fps needs a frame rate")?);
+                }
+                "--render-thread" => options.render_thread = true,
+                "--present-mode" => {
+                    let mode = args.next().ok_or("--present-mode needs a mode")?;
+                    options.present_mode = mode.parse().map_err(|e| format!("{e}"))?;
+                }
+                _ => return Err(format!("Unknown argument {arg:?}")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Sets up the window and renders to it until it's closed, returning the exit code.
+///
+/// Everything the window needs is owned by a local here, so it's all cleaned up (in the right order)
+/// on the way out.
+fn run(options: &Options) -> Result<i32, Box<dyn std::error::Error>> {
     let hinstance = get_process_handle();
 
     let wc = WNDCLASSW {
-        lpfnWndProc: Some(window_procedure),
+        lpfnWndProc: Some(window_procedure_trampoline),
         hInstance: hinstance,
         lpszClassName: WINDOW_CLASS_WN.as_ptr(),
         hCursor: load_predefined_cursor(IDCursor::Arrow)?,
@@ -37,7 +126,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         ..Default::default()
     };
 
-    let _atom = unsafe { register_class(&wc) }?;
+    let class = unsafe { WindowClass::register(&wc) }?;
 
     // Set up our request for what we want the window's pixel format to be.
     // let pfd = PIXELFORMATDESCRIPTOR {
@@ -50,65 +139,43 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     //     ..Default::default()
     // };
 
-    // Get some basic WGL functions to use for context creation and vsync setting and multisampling
-    // and so on
-    let (wgl_extensions, wgl_choose_pixel_format, wgl_create_context_attribs, wgl_swap_interval) =
+    // Get some basic WGL functions to use for context creation and multisampling and so on
+    let (wgl_extensions, _, wgl_create_context_attribs, _, wgl_get_pixel_format_attribiv) =
         get_wgl_basics()?;
 
-    // This is data to pass to the window, which the window procedure can handle in its WM_CREATE
-    // or WM_NCCREATE message handlers.
-    // Note that we intentionally Box::leak the data - it should be cleaned up by the window procedure
-    // in is WM_DESTROY message handler.
-    let lparam: *mut WindowData = Box::leak(Box::new(WindowData::default()));
-
-    let hwnd =
-        unsafe { create_app_window(WINDOW_CLASS, WINDOW_NAME, None, [800, 600], lparam.cast())? };
-
-    // Bind a handle to the window's device context to the WindowData attached to the window.
-    let hdc = unsafe { get_dc(hwnd) }.unwrap();
-    unsafe { (*lparam).hdc = hdc };
-
-    // Set the pixel format for the window.
-    //
-    // First, define some base criteria:
-    let mut pf_int_attribs = vec![
-        [WGL_DRAW_TO_WINDOW_ARB, true as _],
-        [WGL_SUPPORT_OPENGL_ARB, true as _],
-        [WGL_DOUBLE_BUFFER_ARB, true as _],
-        [WGL_PIXEL_TYPE_ARB, WGL_TYPE_RGBA_ARB],
-        [WGL_COLOR_BITS_ARB, 32],
-        [WGL_DEPTH_BITS_ARB, 24],
-        [WGL_STENCIL_BITS_ARB, 8],
-    ];
-
-    // Additional extensions that may or may not exist:
-    for ext in wgl_extensions.iter() {
-        match ext.as_str() {
-            // if sRGB is supported, ask for that
-            "WGL_EXT_framebuffer_sRGB" => {
-                pf_int_attribs.push([WGL_FRAMEBUFFER_SRGB_CAPABLE_EXT, true as _]);
-            }
-
-            // enable multisampling if possible
-            "WGL_ARB_multisample" => {
-                pf_int_attribs.push([WGL_SAMPLE_BUFFERS_ARB, 1]);
-            }
-
-            _ => {}
-        }
-    }
-
-    // Finalize the list of requested pixel format attributes
-    pf_int_attribs.push([0, 0]);
-
-    // Choose the pixel format, get the PIXELFORMATDESCRIPTOR, and set it
-    let pix_format = unsafe {
-        do_wgl_choose_pixel_format_arb(wgl_choose_pixel_format, hdc, &pf_int_attribs, &[])
+    // The window owns its WindowData, and drops it when it's destroyed. Its controls go to
+    // whichever thread is running the scene.
+    let (controls, control_receiver) = mpsc::channel();
+    let window = WindowBuilder::new()
+        .title(WINDOW_NAME)
+        .inner_size([800, 600])
+        .build_with_handler(&class, Box::new(WindowData::new(controls)))?;
+
+    let dc = DeviceContext::get(&window)?;
+    let hdc = dc.hdc();
+
+    // Set the pixel format for the window: the one that best fits what's needed, with sRGB and
+    // multisampling if there are formats with them.
+    let pixel_formats = unsafe {
+        pixel_format::wgl::pixel_formats(hdc, &wgl_extensions, wgl_get_pixel_format_attribiv)
     }?;
-    let pfd = unsafe { describe_pixel_format(hdc, pix_format) }?;
-    unsafe { set_pixel_format(hdc, pix_format, &pfd) }?;
+    let mut requirements = PixelFormatRequirements {
+        samples: MSAA_SAMPLES,
+        srgb: true,
+        ..Default::default()
+    };
+    let pixel_format = match choose(&pixel_formats, &requirements) {
+        Some(format) => format,
+        None => {
+            requirements.srgb = false;
+            choose(&pixel_formats, &requirements).ok_or("No pixel format fits")?
+        }
+    };
+    println!("Pixel format {pixel_format:?}");
+    let pfd = unsafe { describe_pixel_format(hdc, pixel_format.id) }?;
+    unsafe { set_pixel_format(hdc, pixel_format.id, &pfd) }?;
 
-    // Now, create a OpenGL 4.6 Core context, and give it to our window procedure for later use.
+    // Now, create a OpenGL 4.6 Core context.
     const OPENGL_CONTEXT_FLAGS: CInt = WGL_CONTEXT_FORWARD_COMPATIBLE_BIT_ARB
         | if cfg!(debug_assertions) {
             WGL_CONTEXT_DEBUG_BIT_ARB
@@ -117,7 +184,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         };
 
     let hglrc = unsafe {
-        do_wgl_create_context_attribs_arb(
+        let hglrc = do_wgl_create_context_attribs_arb(
             wgl_create_context_attribs,
             hdc,
             ptr::null_mut(),
@@ -131,50 +198,117 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 [WGL_CONTEXT_FLAGS_ARB, OPENGL_CONTEXT_FLAGS],
                 [0, 0],
             ],
-        )
-    }?;
+        )?;
+        GlRenderContext::from_raw(&dc, hglrc)
+    };
 
-    unsafe { wgl_make_current(hdc, hglrc) }?;
-    unsafe { (*lparam).hglrc = hglrc };
-
-    // Load the OpenGL DLL, and give the window procedure a handle to it.
-    let lib_opengl32 = load_library("opengl32.dll")?;
-    unsafe { (*lparam).set_lib_opengl32_handle(lib_opengl32) };
-
-    // Enable "adaptive" vsync if possible, otherwise normal vsync
-    if wgl_extensions
-        .iter()
-        .any(|s| s == "WGL_EXT_swap_control_tear")
-    {
-        unsafe { (wgl_swap_interval.unwrap())(-1) };
-    } else {
-        unsafe { (wgl_swap_interval.unwrap())(1) };
-    }
+    // The swap interval belongs to the context, so the scene can set it from whichever thread it
+    // ends up on.
+    let swap_control = {
+        let _current = hglrc.make_current()?;
+        unsafe { WglSwapControl::load(&wgl_extensions) }
+    };
 
     // Show the window.
-    let _previously_visible = unsafe { ShowWindow(hwnd, SW_SHOW) };
+    let _previously_visible = window.show(SW_SHOW);
+    let setup = SceneSetup {
+        scale_factor: window.scale_factor(),
+        swap_control,
+        present_mode: options.present_mode,
+    };
 
-    loop {
-        match get_any_message() {
-            Ok(msg) => {
-                if msg.message == WM_QUIT {
-                    std::process::exit(msg.wParam as i32);
-                }
+    let mut pacer = FramePacer::new(options.frame_rate);
+    if !options.render_thread {
+        // Draw whenever the message queue is empty
+        let _current = hglrc.make_current()?;
+        let mut scene = Scene::new(setup)?;
+        let mut runner = Runner::new(TIMESTEP);
+        return Ok(run_app_loop(|| {
+            apply_controls(&mut runner, &mut scene, &control_receiver);
+            runner.frame(&mut scene);
+            if scene.surface.is_minimized() {
+                // nothing was drawn, so there's nothing to show
+                thread::sleep(MINIMIZED_SLEEP);
+                pacer.reset();
+                return;
+            }
+            if let Err(e) = hglrc.swap_buffers() {
+                eprintln!("Unable to swap buffers: {e}");
+            }
+            pacer.wait();
+        }));
+    }
 
-                translate_message(&msg);
+    // The messages get this thread to themselves, and the context is made current on another one
+    // that draws until the window is closed
+    let running = AtomicBool::new(true);
+    thread::scope(|scope| {
+        let render_thread = scope.spawn(|| {
+            render_loop(&hglrc, setup, &running, &mut pacer, control_receiver)
+                .map_err(|e| e.to_string())
+        });
+        let exit_code = run_message_loop();
+        running.store(false, Ordering::Relaxed);
+        match render_thread.join() {
+            Ok(rendered) => rendered?,
+            Err(payload) => panic::resume_unwind(payload),
+        }
+        Ok(exit_code?)
+    })
+}
 
-                unsafe {
-                    DispatchMessageW(&msg);
-                }
-            }
+/// Makes `hglrc` current on this thread, and runs the scene on it until `running` is cleared.
+fn render_loop(
+    hglrc: &GlRenderContext<'_>,
+    setup: SceneSetup,
+    running: &AtomicBool,
+    pacer: &mut FramePacer,
+    controls: Receiver<Control>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let _current = hglrc.make_current()?;
+    let mut scene = Scene::new(setup)?;
+    let mut runner = Runner::new(TIMESTEP);
+    while running.load(Ordering::Relaxed) {
+        apply_controls(&mut runner, &mut scene, &controls);
+        runner.frame(&mut scene);
+        if scene.surface.is_minimized() {
+            thread::sleep(MINIMIZED_SLEEP);
+            pacer.reset();
+            continue;
+        }
+        hglrc.swap_buffers()?;
+        pacer.wait();
+    }
+    Ok(())
+}
 
-            Err(e) => panic!("Error when fetching from message queue: {e}"),
+/// Applies what's happened to the window since the last frame.
+fn apply_controls(runner: &mut Runner, scene: &mut Scene, controls: &Receiver<Control>) {
+    for control in controls.try_iter() {
+        match control {
+            Control::Resize(size) => scene.surface.resize(size),
+            Control::ScaleFactor(scale_factor) => scene.surface.set_scale_factor(scale_factor),
+            Control::TogglePause if runner.is_paused() => runner.resume(),
+            Control::TogglePause => runner.pause(),
+            Control::Step => runner.step(),
+            Control::ScaleTime(factor) => {
+                runner.set_time_scale((runner.time_scale() * factor).clamp(1.0 / 16.0, 16.0));
+            }
+            Control::CyclePresentMode => scene.set_present_mode(scene.present_mode.next()),
         }
     }
 }
 
+/// Loads the GL functions for the context that's current on this thread.
+fn load_gl() -> Result<GlContext, Box<dyn std::error::Error>> {
+    let lib_opengl32 = Library::load("opengl32.dll")?;
+    Ok(GlContext::new_with_loader(Box::new(Win32GlProcLoader {
+        lib_opengl32,
+    })))
+}
+
 struct Win32GlProcLoader {
-    lib_opengl32: HMODULE,
+    lib_opengl32: Library,
 }
 
 impl GlProcLoader for Win32GlProcLoader {
@@ -188,205 +322,201 @@ impl GlProcLoader for Win32GlProcLoader {
         let p = wglGetProcAddress(name.as_ptr().cast());
 
         match p as usize {
-            0 | 1 | 2 | 3 | usize::MAX => GetProcAddress(self.lib_opengl32, name.as_ptr().cast()),
+            0 | 1 | 2 | 3 | usize::MAX => {
+                GetProcAddress(self.lib_opengl32.hmodule(), name.as_ptr().cast())
+            }
             _ => p,
         }
     }
 }
 
-/// Data to be stored in the window procedure's state.
+/// The window's state, which handles its messages.
+///
+/// Drawing happens outside of the window procedure, so all this has to do is react to input.
 struct WindowData {
-    hdc: HDC,
-    hglrc: HGLRC,
-
-    lib_opengl32: HMODULE,
+    /// Toggled with F11.
+    fullscreen: Fullscreen,
 
-    has_setup_ran: bool,
+    /// Where to tell the scene about the window's size, and the keys that control it: P pauses
+    /// and resumes the scene, `.` steps it while it's paused, `[` and `]` slow it down and
+    /// speed it up, and V cycles through the present modes.
+    controls: Sender<Control>,
+}
 
-    gl: Rc<RefCell<GlContext>>,
+/// Something that happened to the window, for whichever thread is running the scene.
+enum Control {
+    /// The client area is now this many physical pixels.
+    Resize([u32; 2]),
+    ScaleFactor(f64),
+    TogglePause,
+    Step,
+    /// Multiplies the time scale.
+    ScaleTime(f64),
+    CyclePresentMode,
+}
 
-    vao: GLuint,
-    vbo: GLuint,
-    ebo: GLuint,
-    shader_program: GLuint,
+/// What the scene starts with, from the thread that set up the window.
+struct SceneSetup {
+    scale_factor: f64,
+    /// `None` if the driver doesn't let the swap interval be changed.
+    swap_control: Option<WglSwapControl>,
+    present_mode: PresentMode,
 }
 
-impl WindowData {
-    pub fn set_lib_opengl32_handle(&mut self, lib_opengl32: HMODULE) {
-        self.lib_opengl32 = lib_opengl32;
+/// What the runner updates and draws: a triangle, on a background that pulses at the same speed
+/// however fast it's drawn.
+struct Scene {
+    gl: GlContext,
+    surface: Surface,
+    renderer: Renderer,
+    swap_control: Option<WglSwapControl>,
+    /// The present mode that was last asked for, which may not be the one that was achieved.
+    present_mode: PresentMode,
+    /// How many pulses the background had been through before the last update, and after it.
+    pulse: [f64; 2],
+}
 
-        self.gl
-            .borrow_mut()
-            .set_loader(Box::new(Win32GlProcLoader { lib_opengl32 }));
+impl Scene {
+    /// Sets up the scene for the context that's current on this thread.
+    ///
+    /// The surface has no size until the window's first [`Control::Resize`] arrives.
+    fn new(setup: SceneSetup) -> Result<Self, Box<dyn std::error::Error>> {
+        let gl = load_gl()?;
+        let mut renderer = Renderer::default();
+        gl_setup(&mut renderer, &gl)?;
+        let mut scene = Self {
+            gl,
+            surface: Surface::new([0, 0], setup.scale_factor),
+            renderer,
+            swap_control: setup.swap_control,
+            present_mode: setup.present_mode,
+            pulse: [0.0; 2],
+        };
+        scene.set_present_mode(setup.present_mode);
+        Ok(scene)
     }
 
-    pub fn get_gl_context(&self) -> Rc<RefCell<GlContext>> {
-        self.gl.clone()
+    /// Switches to `mode`, or the closest one to it that the driver can do, and says which one
+    /// that was.
+    fn set_present_mode(&mut self, mode: PresentMode) {
+        self.present_mode = mode;
+        let Some(swap_control) = &self.swap_control else {
+            println!("Present mode is up to the driver, which can't change the swap interval");
+            return;
+        };
+        // Safety: the context is current, since the scene was made with it
+        match unsafe { set_present_mode(swap_control, mode) } {
+            Ok(achieved) if achieved == mode => println!("Present mode: {achieved}"),
+            Ok(achieved) => println!("Present mode: {achieved} ({mode} is unavailable)"),
+            Err(e) => eprintln!("Couldn't set the present mode to {mode}: {e}"),
+        }
     }
 }
 
-impl Default for WindowData {
-    fn default() -> Self {
-        Self {
-            hdc: ptr::null_mut(),
-            hglrc: ptr::null_mut(),
-            lib_opengl32: ptr::null_mut(),
-            has_setup_ran: Default::default(),
-            gl: Default::default(),
-            vao: Default::default(),
-            vbo: Default::default(),
-            ebo: Default::default(),
-            shader_program: Default::default(),
+impl Application for Scene {
+    fn update(&mut self, dt: Duration) {
+        let [_, pulse] = self.pulse;
+        self.pulse = [pulse, pulse + dt.as_secs_f64() / PULSE_PERIOD];
+    }
+
+    fn render(&mut self, alpha: f64) {
+        // Safety: the context is current, since the scene was made with it
+        if !unsafe { self.surface.prepare(&self.gl) } {
+            return;
         }
+        let [before, after] = self.pulse;
+        gl_paint(
+            &mut self.renderer,
+            &self.gl,
+            before + (after - before) * alpha,
+        );
     }
 }
 
-/// The main window procedure.
+/// Everything that's drawn, and the GL objects it's drawn with.
 ///
-/// # Safety
-///
-/// - Runs in a different thread (potentially)
-/// - Uses weird calling conventions
-/// - Hates you
-pub unsafe extern "system" fn window_procedure(
-    hwnd: HWND,
-    msg: UINT,
-    wparam: WPARAM,
-    lparam: LPARAM,
-) -> LRESULT {
-    match msg {
-        // This message is sent *just* before the WM_CREATE message, when the window is first created.
-        // If window creation should procede, return TRUE (1). Otherwise, return FALSE (0).
-        WM_NCCREATE => {
-            println!("NC Create");
-
-            // We expect to receive window creation data in l_param when processing this message. If
-            // we don't receive it, disallow window creation and exit.
-            let createstruct: LPCREATESTRUCTW = lparam as _;
-            if createstruct.is_null() {
-                return 0;
-            }
-
-            let ptr = (*createstruct).lpCreateParams as *mut i32;
-            return set_window_userdata(hwnd, ptr).is_ok() as LRESULT;
-        }
-
-        // The window is being created. Application state should be setup here.
-        //
-        // Per MSDN:
-        //
-        // > If an application processes this message, it should return zero to continue creation of
-        // > the window. If the application returns –1, the window is destroyed and the
-        // > CreateWindowEx or CreateWindow function returns a NULL handle.
-        WM_CREATE => {
-            println!("Create");
-        }
-
-        // Paint the window's client area.
-        WM_PAINT => match get_window_userdata::<WindowData>(hwnd) {
-            Ok(ptr) if !ptr.is_null() => {
-                let window_data = ptr.as_mut().unwrap();
-
-                let gl_ctx = window_data.get_gl_context();
-                let gl_ctx_ref = gl_ctx.borrow();
-
-                if !window_data.has_setup_ran {
-                    gl_setup(window_data, &gl_ctx_ref).unwrap();
-                    window_data.has_setup_ran = true;
-                }
-
-                gl_paint(window_data, &gl_ctx_ref).unwrap();
-
-                // Do all OpenGL drawing before this line:
-                SwapBuffers(window_data.hdc);
-
-                // Immediately request a redraw:
-                InvalidateRect(hwnd, ptr::null(), 0);
-            }
+/// These belong to whichever thread the GL context is current on, which is the only one that
+/// touches them.
+#[derive(Default)]
+struct Renderer {
+    vao: GLuint,
+    vbo: GLuint,
+    ebo: GLuint,
+    shader_program: GLuint,
 
-            Ok(_) => {
-                println!("GWLP_USERDATA pointer is null.");
-            }
+    /// Set when the shaders are being loaded from disk and reloaded as they change.
+    hot_reload: Option<HotReload>,
+}
 
-            Err(e) => {
-                println!("Error while getting the GWLP_USERDATA pointer: {e}");
-            }
-        },
+/// Rebuilds the shader program whenever one of its source files changes.
+struct HotReload {
+    shaders: ProgramManager,
+    watcher: FileWatcher,
+}
 
-        // Destroy the window class when told to close.
-        WM_CLOSE => {
-            DestroyWindow(hwnd);
+impl WindowData {
+    fn new(controls: Sender<Control>) -> Self {
+        Self {
+            fullscreen: Fullscreen::new(),
+            controls,
         }
-        // Tell the system the application quit upon window class destruction.
-        WM_DESTROY => {
-            // Remember to clean up application state upon destruction!
-            match get_window_userdata::<WindowData>(hwnd) {
-                Ok(ptr) if !ptr.is_null() => {
-                    let window_data = Box::from_raw(ptr);
-
-                    FreeLibrary(window_data.lib_opengl32);
-
-                    wgl_delete_context(window_data.hglrc)
-                        .unwrap_or_else(|e| eprintln!("GL context deletion error: {e}"));
+    }
+}
 
-                    if !release_dc(hwnd, window_data.hdc) {
-                        eprintln!("Unable to release device context.");
-                    }
+impl WindowHandler for WindowData {
+    fn create(&mut self, _hwnd: HWND) -> bool {
+        println!("Create");
+        true
+    }
 
-                    println!("Deallocated application state!");
-                }
+    fn resize(&mut self, _hwnd: HWND, size: Size) {
+        let size = [size.width.into(), size.height.into()];
+        let _ = self.controls.send(Control::Resize(size));
+    }
 
-                Ok(_) => {
-                    println!(
-                        "GWLP_USERDATA pointer is null, so no application state cleanup required."
-                    );
-                }
+    fn dpi_changed(&mut self, _hwnd: HWND, dpi: u32) {
+        let _ = self.controls.send(Control::ScaleFactor(scale_factor(dpi)));
+    }
 
-                Err(e) => {
-                    println!("Error while getting the GWLP_USERDATA pointer to clean up application state: {e}");
-                }
+    fn key_down(&mut self, hwnd: HWND, virtual_key: u32, repeat: bool) {
+        if virtual_key == VK_F11 && !repeat {
+            // Safety: this is the window's own handler, so the window is valid
+            if let Err(e) = unsafe { self.fullscreen.toggle(hwnd, FullscreenMode::Borderless) } {
+                eprintln!("Couldn't toggle fullscreen: {e}");
             }
-
-            post_quit_message(0);
         }
+    }
 
-        _ => return DefWindowProcW(hwnd, msg, wparam, lparam),
+    fn text(&mut self, _hwnd: HWND, text: char) {
+        let control = match text {
+            'p' | 'P' => Control::TogglePause,
+            '.' => Control::Step,
+            '[' => Control::ScaleTime(0.5),
+            ']' => Control::ScaleTime(2.0),
+            'v' | 'V' => Control::CyclePresentMode,
+            _ => return,
+        };
+        // nothing's listening once the scene has stopped, which is fine
+        let _ = self.controls.send(control);
     }
 
-    0
+    fn destroy(&mut self, _hwnd: HWND) {
+        println!("Deallocating application state!");
+    }
 }
 
-#[rustfmt::skip]
-const TRIANGLE_VERTICES: [f32; 18] = [
-    // positions      // colors
-    -0.5, -0.5, 0.0,  1.0, 0.0, 0.0,
-     0.5, -0.5, 0.0,  0.0, 1.0, 0.0,
-     0.0,  0.5, 0.0,  0.0, 0.0, 1.0,
-];
-
-#[rustfmt::skip]
-const TRIANGLE_INDICES: [GLuint; 3] = [
-    0, 1, 2
-];
-
-const VERTEX_SHADER_SOURCE: &[u8] = c_str!(include_str!("./vertex.vs"));
-const FRAGMENT_SHADER_SOURCE: &[u8] = c_str!(include_str!("./fragment.fs"));
-
-fn gl_setup(
-    window_data: &mut WindowData,
-    ctx: &Ref<'_, GlContext>,
-) -> Result<(), Box<dyn std::error::Error>> {
+fn gl_setup(renderer: &mut Renderer, ctx: &GlContext) -> Result<(), Box<dyn std::error::Error>> {
     unsafe {
         // Gen VAO, VBO, and EBO
-        ctx.gl_gen_vertex_arrays(1, &mut window_data.vao);
-        ctx.gl_gen_buffers(1, &mut window_data.vbo);
-        ctx.gl_gen_buffers(1, &mut window_data.ebo);
+        ctx.gl_gen_vertex_arrays(1, &mut renderer.vao);
+        ctx.gl_gen_buffers(1, &mut renderer.vbo);
+        ctx.gl_gen_buffers(1, &mut renderer.ebo);
 
         // Bind VAO
-        ctx.gl_bind_vertex_array(window_data.vao);
+        ctx.gl_bind_vertex_array(renderer.vao);
 
         // Bind triangle VBO
-        ctx.gl_bind_buffer(GL_ARRAY_BUFFER, window_data.vbo);
+        ctx.gl_bind_buffer(GL_ARRAY_BUFFER, renderer.vbo);
         ctx.gl_buffer_data(
             GL_ARRAY_BUFFER,
             mem::size_of_val(&TRIANGLE_VERTICES) as _,
@@ -395,7 +525,7 @@ fn gl_setup(
         );
 
         // Bind triangle EBO
-        ctx.gl_bind_buffer(GL_ELEMENT_ARRAY_BUFFER, window_data.ebo);
+        ctx.gl_bind_buffer(GL_ELEMENT_ARRAY_BUFFER, renderer.ebo);
         ctx.gl_buffer_data(
             GL_ELEMENT_ARRAY_BUFFER,
             mem::size_of_val(&TRIANGLE_INDICES) as _,
@@ -404,134 +534,136 @@ fn gl_setup(
         );
 
         // Set vertex attrbute pointers tied to the VBO and the VAO
-        // position attribute
-        ctx.gl_vertex_attrib_pointer(
-            0,
-            3,
-            GL_FLOAT,
-            GL_FALSE,
-            (6 * mem::size_of::<f32>()) as _,
-            0 as _,
-        );
-        ctx.gl_enable_vertex_attrib_array(0);
-
-        // color attribute
-        ctx.gl_vertex_attrib_pointer(
-            1,
-            3,
-            GL_FLOAT,
-            GL_FALSE,
-            (6 * mem::size_of::<f32>()) as _,
-            (3 * mem::size_of::<f32>()) as _,
-        );
-        ctx.gl_enable_vertex_attrib_array(1);
+        TRIANGLE_LAYOUT.apply(ctx);
 
         ctx.gl_bind_vertex_array(0);
 
-        // Load and compile vertex shader
-        let vertex_shader = ctx.gl_create_shader(GL_VERTEX_SHADER);
-        ctx.gl_shader_source(
-            vertex_shader,
-            1,
-            [VERTEX_SHADER_SOURCE.as_ptr()].as_ptr() as _,
-            ptr::null(),
-        );
-        ctx.gl_compile_shader(vertex_shader);
-        gl_print_shader_compile_status(ctx, vertex_shader, "vertex.vs");
-
-        // Load and compile fragment shader
-        let fragment_shader = ctx.gl_create_shader(GL_FRAGMENT_SHADER);
-        ctx.gl_shader_source(
-            fragment_shader,
-            1,
-            [FRAGMENT_SHADER_SOURCE.as_ptr()].as_ptr() as _,
-            ptr::null(),
-        );
-        ctx.gl_compile_shader(fragment_shader);
-        gl_print_shader_compile_status(ctx, fragment_shader, "fragment.fs");
-
-        // Link shader objects into a program
-        window_data.shader_program = ctx.gl_create_program();
-        ctx.gl_attach_shader(window_data.shader_program, vertex_shader);
-        ctx.gl_attach_shader(window_data.shader_program, fragment_shader);
-        ctx.gl_link_program(window_data.shader_program);
-        gl_print_program_link_status(ctx, window_data.shader_program, "main");
-
-        // Delete now unneeded shader objects
-        ctx.gl_delete_shader(fragment_shader);
-        ctx.gl_delete_shader(vertex_shader);
+        // Debug builds run from a checkout load the shaders straight from the source directory,
+        // so they can be edited while the program is running
+        if cfg!(debug_assertions) {
+            match hot_reload_setup(ctx) {
+                Ok(Some(hot_reload)) => {
+                    renderer.shader_program = hot_reload.shaders.program().unwrap();
+                    renderer.hot_reload = Some(hot_reload);
+                    return Ok(());
+                }
+                Ok(None) => (),
+                Err(e) => eprintln!("Shader hot reloading is unavailable: {e}"),
+            }
+        }
+
+        // Expand #includes and add a #version header to each shader
+        let preprocessor = Preprocessor::new(GlslVersion::GL_460_CORE, SHADER_FILES);
+        let vertex_shader = preprocessor.run("vertex.vs")?;
+        let fragment_shader = preprocessor.run("fragment.fs")?;
+
+        // Load the shader program from the on-disk cache, or compile it if there's no usable
+        // cached binary for this driver
+        let cache = ProgramCache::in_user_cache_dir("triangle-from-scratch");
+        let (program, outcome) = cache
+            .load_or_build(
+                ctx,
+                &[
+                    ShaderSource::new(GL_VERTEX_SHADER, &vertex_shader.source),
+                    ShaderSource::new(GL_FRAGMENT_SHADER, &fragment_shader.source),
+                ],
+            )
+            .map_err(|e| match e {
+                // Point compile errors at the original files rather than the preprocessed output
+                ShaderError::Compile { shader_type, log } => {
+                    let map = if shader_type == GL_VERTEX_SHADER {
+                        &vertex_shader.map
+                    } else {
+                        &fragment_shader.map
+                    };
+                    ShaderError::Compile {
+                        shader_type,
+                        log: map.rewrite_info_log(&log),
+                    }
+                }
+                e => e,
+            })?;
+        renderer.shader_program = program;
+        println!("Shader program {program} ({outcome})");
     }
 
     Ok(())
 }
 
-fn gl_paint(
-    window_data: &mut WindowData,
-    ctx: &Ref<'_, GlContext>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    unsafe {
-        ctx.gl_clear_color(0.6, 0.7, 0.8, 1.0);
-        ctx.gl_clear(GL_COLOR_BUFFER_BIT);
+/// Builds the shader program from the files in `src/`, and starts watching them for changes.
+///
+/// Returns `None` if the source directory isn't around (e.g. the executable has been moved).
+unsafe fn hot_reload_setup(
+    ctx: &GlContext,
+) -> Result<Option<HotReload>, Box<dyn std::error::Error>> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    if !dir.is_dir() {
+        return Ok(None);
+    }
 
-        ctx.gl_use_program(window_data.shader_program);
-        ctx.gl_bind_vertex_array(window_data.vao);
-        ctx.gl_draw_elements(GL_TRIANGLES, 3, GL_UNSIGNED_INT, 0 as _);
-        ctx.gl_bind_vertex_array(0);
+    let mut shaders = ProgramManager::new(dir, GlslVersion::GL_460_CORE)
+        .stage(GL_VERTEX_SHADER, "vertex.vs")
+        .stage(GL_FRAGMENT_SHADER, "fragment.fs");
+    let mut watcher = FileWatcher::new()?;
+    let built = shaders.reload(ctx);
+    // watch whatever was read, even on failure, so that a fix doesn't need a restart
+    for file in shaders.files() {
+        watcher.watch(file)?;
     }
+    let program = built?;
+    println!(
+        "Shader program {program} (watching {} files)",
+        shaders.files().len()
+    );
 
-    Ok(())
+    Ok(Some(HotReload { shaders, watcher }))
 }
 
-fn gl_print_shader_compile_status(ctx: &Ref<'_, GlContext>, shader: GLuint, shader_name: &str) {
-    let mut success: GLint = 0;
-    unsafe { ctx.gl_get_shader_iv(shader, GL_COMPILE_STATUS, &mut success) };
-
-    if success != 1 {
-        let mut info_log: [u8; 512] = [0; 512];
-        let mut info_log_length: GLsizei = 0;
-        unsafe {
-            ctx.gl_get_shader_info_log(
-                shader,
-                512,
-                &mut info_log_length,
-                info_log.as_mut_ptr() as *mut GLchar,
-            )
-        };
+/// Rebuilds the shader program if any of its files have changed since the last frame.
+///
+/// Errors are printed rather than returned, and the last working program stays in use.
+unsafe fn hot_reload_poll(renderer: &mut Renderer, ctx: &GlContext) {
+    let Some(hot_reload) = &mut renderer.hot_reload else {
+        return;
+    };
 
-        let info_log_str = CStr::from_bytes_with_nul(&info_log[..=info_log_length as usize])
-            .expect("glGetShaderInfoLog returned in invalid C-style string")
-            .to_str()
-            .expect("glGetShaderInfoLog returned a valid C-style string with invalid UTF-8 data");
+    let changed = match hot_reload.watcher.poll() {
+        Ok(changed) => changed,
+        Err(e) => {
+            eprintln!("Unable to check shaders for changes: {e}");
+            return;
+        }
+    };
+    match hot_reload.shaders.reload_if_changed(ctx, &changed) {
+        None => return,
+        Some(Ok(program)) => {
+            println!("Reloaded shader program {program}");
+            renderer.shader_program = program;
+        }
+        Some(Err(e)) => eprintln!("Shader reload failed, keeping the previous program:\n{e}"),
+    }
 
-        eprintln!("Error compiling shader {shader} ({shader_name}):\n{info_log_str}");
-    } else {
-        println!("Successfully compiled shader {shader} ({shader_name})");
+    // includes may have been added
+    for file in hot_reload.shaders.files() {
+        if let Err(e) = hot_reload.watcher.watch(file) {
+            eprintln!("Unable to watch {}: {e}", file.display());
+        }
     }
 }
 
-fn gl_print_program_link_status(ctx: &Ref<'_, GlContext>, program: GLuint, program_name: &str) {
-    let mut success: GLint = 0;
-    unsafe { ctx.gl_get_program_iv(program, GL_LINK_STATUS, &mut success) };
-
-    if success != 1 {
-        let mut info_log: [u8; 512] = [0; 512];
-        let mut info_log_length: GLsizei = 0;
-        unsafe {
-            ctx.gl_get_program_info_log(
-                program,
-                512,
-                &mut info_log_length,
-                info_log.as_mut_ptr() as *mut GLchar,
-            )
-        };
+/// Draws the triangle, on a background that's `pulse` of the way through pulsing (and any number
+/// of pulses before that).
+fn gl_paint(renderer: &mut Renderer, ctx: &GlContext, pulse: f64) {
+    unsafe {
+        hot_reload_poll(renderer, ctx);
 
-        let info_log_str = CStr::from_bytes_with_nul(&info_log[..=info_log_length as usize])
-            .expect("glGetProgramInfoLog returned in invalid C-style string")
-            .to_str()
-            .expect("glGetProgramInfoLog returned a valid C-style string with invalid UTF-8 data");
+        let brightness = (0.9 + 0.1 * (pulse * TAU).sin()) as f32;
+        ctx.gl_clear_color(0.6 * brightness, 0.7 * brightness, 0.8 * brightness, 1.0);
+        ctx.gl_clear(GL_COLOR_BUFFER_BIT);
 
-        eprintln!("Error linking program {program} ({program_name}):\n{info_log_str}");
-    } else {
-        println!("Successfully linked program {program} ({program_name})");
+        ctx.gl_use_program(renderer.shader_program);
+        ctx.gl_bind_vertex_array(renderer.vao);
+        ctx.gl_draw_elements(GL_TRIANGLES, 3, GL_UNSIGNED_INT, 0 as _);
+        ctx.gl_bind_vertex_array(0);
     }
 }
@@ -0,0 +1,43 @@
+//! The triangle itself: its vertices, how they're laid out, and the shaders that draw them.
+//!
+//! This lives apart from `main.rs` so the shader interface test in the `gl` crate can include it
+//! and check the shaders against [`TRIANGLE_LAYOUT`] on any platform.
+
+use std::mem;
+
+use gl::{
+    bindings::prelude::*,
+    vertex::{VertexAttribute, VertexLayout},
+};
+use glsl::preprocess::EmbeddedFiles;
+
+#[rustfmt::skip]
+pub const TRIANGLE_VERTICES: [f32; 18] = [
+    // positions      // colors
+    -0.5, -0.5, 0.0,  1.0, 0.0, 0.0,
+     0.5, -0.5, 0.0,  0.0, 1.0, 0.0,
+     0.0,  0.5, 0.0,  0.0, 0.0, 1.0,
+];
+
+#[rustfmt::skip]
+pub const TRIANGLE_INDICES: [GLuint; 3] = [
+    0, 1, 2
+];
+
+/// How [`TRIANGLE_VERTICES`] is laid out: a position, then a color.
+pub const TRIANGLE_LAYOUT: VertexLayout = VertexLayout::new(
+    6 * mem::size_of::<f32>(),
+    &[
+        // position attribute
+        VertexAttribute::floats(0, 3, 0),
+        // color attribute
+        VertexAttribute::floats(1, 3, 3 * mem::size_of::<f32>()),
+    ],
+);
+
+/// Shader sources, embedded into the binary so that `#include`s can be resolved without touching
+/// the disk.
+pub const SHADER_FILES: EmbeddedFiles = EmbeddedFiles(&[
+    ("vertex.vs", include_str!("./vertex.vs")),
+    ("fragment.fs", include_str!("./fragment.fs")),
+]);
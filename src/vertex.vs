@@ -1,4 +1,3 @@
-#version 460 core
 layout (location = 0) in vec3 aPos;
 layout (location = 1) in vec3 aColor;
 